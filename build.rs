@@ -0,0 +1,61 @@
+//! 记录构建信息，供 `/api/version` 和启动日志使用
+//!
+//! - `GITNOTE_GIT_COMMIT`：构建时的提交，优先取同名环境变量，否则执行 `git rev-parse HEAD`
+//! - `GITNOTE_BUILD_TIMESTAMP`：构建时间，Unix 秒，设置了 `SOURCE_DATE_EPOCH` 时使用该值
+//!
+//! 从源码包构建、没有安装 git 或不在仓库中时不设置提交，运行时显示为 `unknown`。
+
+use std::{
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=GITNOTE_GIT_COMMIT");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let commit = std::env::var("GITNOTE_GIT_COMMIT")
+        .ok()
+        .filter(|c| !c.trim().is_empty())
+        .or_else(|| git(&["rev-parse", "HEAD"]));
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=GITNOTE_GIT_COMMIT={}", commit.trim());
+    }
+
+    // 切换分支或提交后重新执行
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        for file in ["HEAD", "packed-refs", "refs"] {
+            let path = git_dir.join(file);
+            if path.exists() {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs())
+        });
+    if let Some(timestamp) = timestamp {
+        println!("cargo:rustc-env=GITNOTE_BUILD_TIMESTAMP={timestamp}");
+    }
+}
+
+/// 执行 git 命令，失败或输出为空时返回 `None`
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (!stdout.is_empty()).then(|| stdout.to_string())
+}
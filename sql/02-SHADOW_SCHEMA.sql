@@ -0,0 +1,5 @@
+-- 重建时写入的影子 schema
+--
+-- 表结构在重建开始时按 gitnote 中的同名表创建 (LIKE ... INCLUDING ALL)，
+-- 写入完成后在一个短事务中移入 gitnote 替换旧表，失败时被删除。
+CREATE SCHEMA IF NOT EXISTS gitnote_shadow;
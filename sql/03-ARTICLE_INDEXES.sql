@@ -0,0 +1,4 @@
+-- 文章列表按更新时间倒序分页
+--
+-- 依赖 01-CREATE_TABLE.sql 创建的 gitnote.articles
+CREATE INDEX IF NOT EXISTS articles_updated_at_idx ON gitnote.articles (updated_at DESC);
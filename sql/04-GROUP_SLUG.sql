@@ -0,0 +1,8 @@
+-- 组对外使用的 id
+--
+-- 组配置中设置了 `slug` 时为该值，否则与 id（组目录路径）相同。
+-- API 返回的组 id、文章列表的组过滤和订阅源地址都使用 slug，id 只用于内部关联。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS slug VARCHAR(255);
+UPDATE gitnote.groups SET slug = id WHERE slug IS NULL;
+ALTER TABLE gitnote.groups ALTER COLUMN slug SET NOT NULL;
+CREATE INDEX IF NOT EXISTS groups_slug_idx ON gitnote.groups (slug);
@@ -0,0 +1,6 @@
+-- 文章的源文件位置
+--
+-- source_path 为文章文件相对仓库根目录的路径，source_commit 为最近一次写入该文章的同步的目标提交。
+-- 由同步写入，文件移动后更新；迁移前写入的文章为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS source_path TEXT;
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS source_commit TEXT;
@@ -0,0 +1,10 @@
+-- 组的默认标签
+--
+-- default_tags 来自组配置，合并到组内每篇文章的标签中。
+-- own_tags 为文章 front matter 中的标签，tags 为 own_tags 与所在组 default_tags 合并去重后的结果，
+-- 查询和过滤都使用 tags；组配置变更时按 own_tags 重新计算，不需要重新渲染。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS default_tags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS own_tags TEXT[];
+UPDATE gitnote.articles SET own_tags = tags WHERE own_tags IS NULL;
+ALTER TABLE gitnote.articles ALTER COLUMN own_tags SET DEFAULT '{}';
+ALTER TABLE gitnote.articles ALTER COLUMN own_tags SET NOT NULL;
@@ -0,0 +1,10 @@
+-- 最近一次同步后的内容指纹
+--
+-- 只有一行，每次同步成功后更新。content_fingerprint 为同步后公开文章和组配置的 SHA-256，
+-- 与离线按仓库快照计算的结果相同时说明数据库与仓库一致。不参与重建时的整体替换。
+CREATE TABLE IF NOT EXISTS gitnote.sync_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),     -- 固定为 TRUE，保证只有一行
+    commit_id TEXT NOT NULL,                        -- 指纹对应的提交
+    content_fingerprint TEXT NOT NULL,              -- 内容指纹，十六进制
+    computed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now() -- 计算时间
+);
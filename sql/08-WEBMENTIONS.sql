@@ -0,0 +1,14 @@
+-- 已发送的 webmention
+--
+-- 每篇公开文章的每个外部链接一行，记录最近一次发送的结果。文章的外部链接与已记录的目标相同时不再发送，
+-- 链接集合变化时向当前的全部目标和被移除的目标重新发送，被移除的目标随后删除。不参与重建时的整体替换。
+CREATE TABLE IF NOT EXISTS gitnote.webmentions_sent (
+    slug VARCHAR(255) NOT NULL,                     -- 来源文章
+    target TEXT NOT NULL,                           -- 文章中的外部链接
+    endpoint TEXT,                                  -- 发现的 webmention 端点，目标不支持时为 NULL
+    status INTEGER,                                 -- 端点的响应状态码，未发送或请求失败时为 NULL
+    error TEXT,                                     -- 未发送或请求失败的原因
+    sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(), -- 最近一次发送时间
+
+    PRIMARY KEY (slug, target)
+);
@@ -0,0 +1,6 @@
+-- 文章的渲染器
+--
+-- renderer 为写入该文章时渲染器的标识（名称和版本，如 github-gfm@2022-11-28），由同步写入。
+-- 与当前配置的渲染器不同的文章可通过 POST /api/admin/rerender-stale 重新渲染，
+-- 迁移前写入的文章为 NULL，同样视为需要重新渲染。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS renderer TEXT;
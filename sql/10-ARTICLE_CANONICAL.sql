@@ -0,0 +1,5 @@
+-- 文章的原文地址
+--
+-- canonical_url 来自 Front Matter 的 canonical 字段，为转载文章的原始发布地址，
+-- 同步时校验为 http 或 https 绝对地址。迁移前写入的文章为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS canonical_url TEXT;
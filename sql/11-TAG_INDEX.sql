@@ -0,0 +1,19 @@
+-- 标签索引
+--
+-- 每个出现在公开文章中的标签一行，查询标签列表时读取该表，不再扫描全部文章。
+-- 增量同步提交时只重新计算本次写入涉及的文章和组的标签，文章数为 0 的标签被删除，重建时整体重新计算。
+CREATE TABLE IF NOT EXISTS gitnote.tag_index (
+    tag TEXT PRIMARY KEY,                           -- 标签
+    article_count BIGINT NOT NULL,                  -- 带有该标签的公开文章数
+    last_used TIMESTAMP WITH TIME ZONE NOT NULL     -- 带有该标签的公开文章中最近的更新时间
+);
+
+-- 按迁移前的文章填充
+INSERT INTO gitnote.tag_index (tag, article_count, last_used)
+SELECT t.tag, COUNT(DISTINCT a.slug), MAX(a.updated_at)
+FROM gitnote.articles a
+JOIN gitnote.groups g ON a.group_id = g.id
+CROSS JOIN LATERAL unnest(a.tags) AS t(tag)
+WHERE g.public = true
+GROUP BY t.tag
+ON CONFLICT (tag) DO NOTHING;
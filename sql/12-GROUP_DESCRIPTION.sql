@@ -0,0 +1,5 @@
+-- 组说明
+--
+-- description_html 为组目录中说明文件（默认 README.md）渲染后的 HTML，忽略其 Front Matter。
+-- 说明文件不再作为文章收录，迁移前已收录为文章的说明文件在该文件再次变更或重建后删除。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS description_html TEXT;
@@ -0,0 +1,7 @@
+-- 组所属的分类
+--
+-- category_id 和 category_name 来自组配置的 category 字段，多个组可以属于同一分类，
+-- 用于 GET /api/categories/{id} 和文章列表的 category 筛选。迁移前写入的组为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS category_id TEXT;
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS category_name TEXT;
+CREATE INDEX IF NOT EXISTS groups_category_id_idx ON gitnote.groups (category_id);
@@ -0,0 +1,5 @@
+-- 文章级的公开设置
+--
+-- 来自 front matter 的 public 字段，为 NULL 时与所在组相同。
+-- 文章是否公开按 COALESCE(a.public, g.public) 判断，没有对应组的文章始终不公开。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS public BOOLEAN;
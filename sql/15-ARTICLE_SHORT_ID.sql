@@ -0,0 +1,14 @@
+-- 文章的短 id
+--
+-- 8 位小写 base32，创建文章时生成，文件移动或重建后沿用，用于 /api/a/{short_id} 短链接。
+-- 已有文章在迁移时生成，与同步时的格式相同；同步写入前的短暂时间内可能为 NULL。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS short_id TEXT;
+-- 子查询引用外层的行，使每行重新计算 random()
+UPDATE gitnote.articles a
+SET short_id = (
+    SELECT string_agg(substr('abcdefghijklmnopqrstuvwxyz234567', floor(random() * 32)::INT + 1, 1), '')
+    FROM generate_series(1, 8)
+    WHERE a.slug IS NOT NULL
+)
+WHERE a.short_id IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS articles_short_id_key ON gitnote.articles (short_id);
@@ -0,0 +1,6 @@
+-- 组在组列表中的排序和隐藏
+--
+-- 来自组配置的 nav_order 和 hidden 字段。GET /api/groups 按 nav_order NULLS LAST, name 排序，不返回 hidden 的组，
+-- 组内文章不受影响。迁移前写入的组 nav_order 为 NULL、不隐藏，重新同步或重建后补全。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS nav_order INTEGER;
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS hidden BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+-- 组内文章的保留规则
+--
+-- 来自组配置的 retain.max_articles 和 retain.max_age_days，为 NULL 时不限制。
+-- 同步后按创建时间从新到旧保留组内的公开文章，超出规则的文章标记 retention_pruned 并下线，不删除。
+-- 文章是否公开按 COALESCE(a.public, g.public) AND NOT a.retention_pruned 判断。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS retain_max_articles INTEGER;
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS retain_max_age_days INTEGER;
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS retention_pruned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+-- 文章最近一次渲染的耗时和大小
+--
+-- 同步实际调用渲染器时写入：render_ms 为渲染正文和摘要的耗时（毫秒），content_bytes 为渲染后正文的字节数，
+-- rendered_at 为写入时间。复用已有渲染结果时保持不变，迁移前写入的文章为 NULL。
+-- 通过 GET /api/admin/render-report 查看渲染最慢或最大的文章。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS render_ms BIGINT;
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS content_bytes BIGINT;
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS rendered_at TIMESTAMPTZ;
@@ -0,0 +1,5 @@
+-- 组内文章的许可协议
+--
+-- 来自组配置的 license 字段，存储为 {"id", "name", "url"} 对象，为 NULL 时使用部署配置的默认协议。
+-- 迁移前写入的组为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS license JSONB;
@@ -0,0 +1,11 @@
+-- 内容寻址的渲染结果
+--
+-- 以展开 include 后内容的 git blob oid 为键，内容相同的文章共用一份渲染结果，见 articles.content_oid。
+-- 编号在 20-CONTENT_SIMHASH.sql 之前，后者为该表增加 simhash 列。
+CREATE TABLE IF NOT EXISTS gitnote.content_blobs (
+    oid TEXT PRIMARY KEY,                           -- 展开 include 后内容的 git blob oid
+    renderer TEXT,                                  -- 渲染器版本，未知时为 NULL，不会被复用
+    raw TEXT NOT NULL,                              -- 展开 include 后的 Markdown 原文
+    content TEXT NOT NULL,                          -- 渲染后的正文
+    summary TEXT NOT NULL                           -- 渲染后的摘要
+);
@@ -0,0 +1,6 @@
+-- 渲染结果对应原文的 simhash
+--
+-- 去掉 Front Matter 后按词计算的 64 位指纹，用于发现不同组之间内容重复或近似重复的文章，
+-- 见 GET /api/admin/duplicates。只在内容变化、写入新的渲染结果时计算，复用渲染结果时沿用；
+-- 内容过短时为 NULL，迁移前写入的行在下次同步写入时补全。
+ALTER TABLE gitnote.content_blobs ADD COLUMN IF NOT EXISTS simhash BIGINT;
@@ -0,0 +1,17 @@
+-- 组的大小统计和配额
+--
+-- source_bytes 为文章原文（Markdown，含展开的引用文件）的字节数，每次写入文章时更新。
+-- group_stats 每个组一行，每次同步提交时按组内全部文章（含未公开的文章）重新汇总，数值变化时更新 updated_at；
+-- 重建时随其他表整体替换。asset_bytes 预留给资源文件，目前没有资源文件的处理流程，始终为 0。
+-- quota_bytes 来自组配置，content_bytes + asset_bytes 超过时同步报告和 GET /api/status 中给出警告，
+-- 严格模式下拒绝同步。迁移前写入的文章 source_bytes 为 0，重建或下次写入后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS source_bytes BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS quota_bytes BIGINT;
+
+CREATE TABLE IF NOT EXISTS gitnote.group_stats (
+    group_id TEXT PRIMARY KEY,                      -- 组目录路径
+    content_bytes BIGINT NOT NULL,                  -- 组内文章原文的总字节数
+    asset_bytes BIGINT NOT NULL DEFAULT 0,          -- 组内资源文件的总字节数
+    article_count BIGINT NOT NULL,                  -- 组内文章数
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL    -- 统计最近一次变化的时间
+);
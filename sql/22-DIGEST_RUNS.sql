@@ -0,0 +1,12 @@
+-- 每周摘要的发送记录
+--
+-- 每个周期一行，period_end 为该周期计划发送的时间。启动和每次到达发送时间时，最近一个周期已有记录则不再发送，
+-- 重启不会重复发送；发送失败同样记录，不自动重试。不参与重建时的整体替换。
+CREATE TABLE IF NOT EXISTS gitnote.digest_runs (
+    period_end TIMESTAMP WITH TIME ZONE PRIMARY KEY, -- 周期结束（计划发送）时间，不包含
+    period_start TIMESTAMP WITH TIME ZONE NOT NULL, -- 周期开始时间，包含
+    article_count BIGINT NOT NULL,                  -- 周期内新发布的公开文章数
+    status TEXT NOT NULL,                           -- sent、skipped（没有新文章）或 failed
+    error TEXT,                                     -- 发送失败的原因
+    ran_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now() -- 实际执行时间
+);
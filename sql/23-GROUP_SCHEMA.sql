@@ -0,0 +1,5 @@
+-- 组内文章 Front Matter 的附加约定
+--
+-- 来自组配置的 required_fields 和 field_types，存储为 {"required_fields", "field_types"} 对象，都未设置时为 NULL。
+-- 同步时按目标提交中的组配置检查文章，这里只记录当前生效的约定。迁移前写入的组为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS front_matter_schema JSONB;
@@ -0,0 +1,5 @@
+-- 文章的分享卡片信息
+--
+-- 同步时由摘要、正文和 Front Matter 的 cover 生成，存储为 {"description", "image"} 对象，图片可能是相对地址，
+-- 站点地址和默认图片在响应时展开。加密文章为 NULL。迁移前写入的文章为 NULL，重新同步或重建后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS social JSONB;
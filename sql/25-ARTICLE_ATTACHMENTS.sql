@@ -0,0 +1,16 @@
+-- 文章附件
+--
+-- 同步时从正文中指向附件扩展名（GITNOTE_ATTACHMENT_EXTENSIONS）文件的链接提取，图片不计入。
+-- 每次写入文章时在同一个事务中整体替换该文章的行，删除文章时一并删除；重建时随其他表整体替换。
+-- http/https 链接原样保存，仓库内的相对链接保存为相对仓库根目录的路径，目前没有资源文件的访问地址。
+-- 加密文章不记录附件。迁移前写入的文章没有附件，重新同步或重建后补全。
+CREATE TABLE IF NOT EXISTS gitnote.article_attachments (
+    slug VARCHAR(255) NOT NULL,                     -- 引用附件的文章
+    group_id VARCHAR(255) NOT NULL,                 -- 文章所在组
+    position INTEGER NOT NULL,                      -- 在正文中首次出现的顺序，从 0 开始
+    url_or_path TEXT NOT NULL,                      -- 外部链接或仓库路径
+    kind TEXT NOT NULL,                             -- 小写的扩展名，如 pdf
+    link_text TEXT NOT NULL,                        -- 链接文字
+
+    PRIMARY KEY (slug, url_or_path)
+);
@@ -0,0 +1,5 @@
+-- 文章标题的排序键
+--
+-- 同步时由标题生成，汉字转换为拼音，其他文字转为小写，末尾追加原标题，用于按标题排序和首字母索引，
+-- 按字节比较（COLLATE "C"），不依赖数据库的排序规则。迁移前写入的文章为 NULL，排在最后，重新同步或重建后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS title_sort_key TEXT;
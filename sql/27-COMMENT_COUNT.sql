@@ -0,0 +1,4 @@
+-- 文章的评论数
+--
+-- 由后台任务按 GITNOTE_COMMENTS_PROVIDER 配置的提供者周期刷新，未启用或查询失败时保留原值。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS comment_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,12 @@
+-- 文章重命名和别名产生的重定向
+--
+-- 同步时记录旧位置到新位置的映射，链式重定向在写入时折叠为直接指向最终位置，
+-- 访问旧 slug 时返回 308 跳转。
+CREATE TABLE IF NOT EXISTS gitnote.redirects (
+    from_group VARCHAR(255) NOT NULL,               -- 旧位置所在组
+    from_slug VARCHAR(255) NOT NULL,                -- 旧位置的 slug
+    to_group VARCHAR(255) NOT NULL,                 -- 新位置所在组
+    to_slug VARCHAR(255) NOT NULL,                  -- 新位置的 slug
+
+    PRIMARY KEY (from_group, from_slug)
+);
@@ -0,0 +1,5 @@
+-- 加密存储
+--
+-- 组配置 `encrypted: true` 时组内文章的正文和摘要加密后写入，nonce 按行保存，明文文章为 NULL。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS encrypted BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS nonce BYTEA;
@@ -0,0 +1,14 @@
+-- 文章中的外部链接
+--
+-- 同步时记录每篇文章引用的 http/https 链接，后台任务定期检查并记录状态，
+-- 见 GET /api/admin/link-report。
+CREATE TABLE IF NOT EXISTS gitnote.external_links (
+    slug VARCHAR(255) NOT NULL,                     -- 引用该链接的文章
+    url TEXT NOT NULL,                              -- http/https 外部链接
+    first_seen TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    status INTEGER,                                 -- 最近一次检查的状态码，请求失败时为 NULL
+    error TEXT,                                     -- 最近一次请求失败的原因，如 timeout
+    last_checked TIMESTAMP WITH TIME ZONE,          -- 最近一次检查时间，未检查为 NULL
+
+    PRIMARY KEY (slug, url)
+);
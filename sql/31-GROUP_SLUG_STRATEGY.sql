@@ -0,0 +1,4 @@
+-- 组内文章 slug 的生成方式
+--
+-- `stem` 使用文件名去掉扩展名，`path` 使用相对组目录的路径去掉扩展名，用于嵌套目录中存在同名文件的组。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS slug_strategy VARCHAR(16) NOT NULL DEFAULT 'stem';
@@ -0,0 +1,9 @@
+-- 文章引用的 include 片段
+--
+-- 记录每篇文章直接或间接引用的文件，片段变化时据此重新渲染引用它的文章。
+CREATE TABLE IF NOT EXISTS gitnote.article_includes (
+    article_path TEXT NOT NULL,                     -- 引用方文章的文件路径
+    include_path TEXT NOT NULL,                     -- 直接或间接引用的文件路径
+
+    PRIMARY KEY (article_path, include_path)
+);
@@ -0,0 +1,4 @@
+-- 去掉标签的纯文本摘要
+--
+-- 同步写入时由渲染后的摘要生成，`?summary=plain` 时返回。迁移前写入的行为空字符串，重新同步或重建后补全。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS summary_plain TEXT NOT NULL DEFAULT '';
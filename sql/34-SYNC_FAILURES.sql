@@ -0,0 +1,10 @@
+-- 同步时处理失败的文件
+--
+-- 单个文件渲染或解析失败时记录原因，不影响其他文件写入；成功写入或文件被删除后移除，
+-- 可通过 POST /api/repo/retry-failures 重试。
+CREATE TABLE IF NOT EXISTS gitnote.sync_failures (
+    path TEXT PRIMARY KEY,                          -- 处理失败的文件路径
+    reason TEXT NOT NULL,                           -- 失败原因
+    commit_id TEXT NOT NULL,                        -- 重试时读取文件的提交，每次增量同步后更新为目标提交
+    failed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now() -- 最近一次失败时间
+);
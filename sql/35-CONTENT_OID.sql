@@ -0,0 +1,6 @@
+-- 文章正文对应的 content_blobs.oid
+--
+-- 内容相同的文章共用一份渲染结果，读取时从 content_blobs 取出正文，此时 articles.content 为空。
+-- 加密文章和迁移前写入的行为 NULL，仍使用 articles.content。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS content_oid TEXT;
+CREATE INDEX IF NOT EXISTS articles_content_oid_idx ON gitnote.articles (content_oid);
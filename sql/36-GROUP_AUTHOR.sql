@@ -0,0 +1,5 @@
+-- 组的默认作者
+--
+-- 来自组配置的 `author`，用于按作者筛选和作者列表，未设置时为 NULL。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS author_id VARCHAR(255);
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS author_name TEXT;
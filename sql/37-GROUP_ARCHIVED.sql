@@ -0,0 +1,4 @@
+-- 组是否已归档
+--
+-- 归档组的文章不出现在默认的文章列表中，`include_archived=true` 时包含。
+ALTER TABLE gitnote.groups ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,5 @@
+-- 锚点带命名空间的正文
+--
+-- 启用 GITNOTE_NAMESPACE_ANCHORS 时同步写入，锚点 id 和页内链接带 slug 前缀；
+-- 未启用、加密文章或迁移前写入的行为 NULL，修改该配置后需重建。
+ALTER TABLE gitnote.articles ADD COLUMN IF NOT EXISTS content_namespaced TEXT;
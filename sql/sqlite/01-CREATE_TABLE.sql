@@ -0,0 +1,131 @@
+-- SQLite 后端的表结构
+--
+-- 与 sql/ 中 PostgreSQL 各迁移执行后的结构相同，列的含义见对应的迁移文件。区别：
+--
+-- - 没有 schema，表直接建在数据库文件中，重建时在一个事务中整体替换，不使用影子表
+-- - 数组列（tags、own_tags、default_tags）存储为 JSON 数组文本
+-- - 时间列存储为 UTC 的 RFC 3339 文本，小数秒固定 6 位，按文本比较即按时间比较
+-- - 布尔列存储为 0 或 1
+CREATE TABLE IF NOT EXISTS articles (
+    slug TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    summary_plain TEXT NOT NULL DEFAULT '',
+    tags TEXT NOT NULL DEFAULT '[]',                -- own_tags 与所在组 default_tags 合并去重后的 JSON 数组
+    content TEXT NOT NULL,
+    content_oid TEXT,
+    content_namespaced TEXT,
+    group_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    comment_count INTEGER NOT NULL DEFAULT 0,
+    nonce BLOB,
+    source_path TEXT,
+    source_commit TEXT,
+    own_tags TEXT NOT NULL DEFAULT '[]',            -- front matter 中标签的 JSON 数组
+    renderer TEXT,
+    canonical_url TEXT,
+    public BOOLEAN,
+    short_id TEXT,
+
+    UNIQUE (group_id, slug)
+);
+
+CREATE INDEX IF NOT EXISTS articles_content_oid_idx ON articles (content_oid);
+CREATE INDEX IF NOT EXISTS articles_updated_at_idx ON articles (updated_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS articles_short_id_key ON articles (short_id);
+
+
+CREATE TABLE IF NOT EXISTS groups (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    public BOOLEAN NOT NULL DEFAULT FALSE,
+    encrypted BOOLEAN NOT NULL DEFAULT FALSE,
+    slug_strategy TEXT NOT NULL DEFAULT 'stem',
+    author_id TEXT,
+    author_name TEXT,
+    archived BOOLEAN NOT NULL DEFAULT FALSE,
+    kind TEXT DEFAULT '{}',                         -- JSON 对象
+    slug TEXT NOT NULL,
+    default_tags TEXT NOT NULL DEFAULT '[]',        -- JSON 数组
+    description_html TEXT,
+    category_id TEXT,
+    category_name TEXT
+);
+
+CREATE INDEX IF NOT EXISTS groups_slug_idx ON groups (slug);
+CREATE INDEX IF NOT EXISTS groups_category_id_idx ON groups (category_id);
+
+
+CREATE TABLE IF NOT EXISTS redirects (
+    from_group TEXT NOT NULL,
+    from_slug TEXT NOT NULL,
+    to_group TEXT NOT NULL,
+    to_slug TEXT NOT NULL,
+
+    PRIMARY KEY (from_group, from_slug)
+);
+
+
+CREATE TABLE IF NOT EXISTS article_includes (
+    article_path TEXT NOT NULL,
+    include_path TEXT NOT NULL,
+
+    PRIMARY KEY (article_path, include_path)
+);
+
+
+CREATE TABLE IF NOT EXISTS external_links (
+    slug TEXT NOT NULL,
+    url TEXT NOT NULL,
+    first_seen TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f000Z', 'now')),
+    status INTEGER,
+    error TEXT,
+    last_checked TEXT,
+
+    PRIMARY KEY (slug, url)
+);
+
+
+CREATE TABLE IF NOT EXISTS sync_failures (
+    path TEXT PRIMARY KEY,
+    reason TEXT NOT NULL,
+    commit_id TEXT NOT NULL,
+    failed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f000Z', 'now'))
+);
+
+
+CREATE TABLE IF NOT EXISTS content_blobs (
+    oid TEXT PRIMARY KEY,
+    renderer TEXT,
+    raw TEXT NOT NULL,
+    content TEXT NOT NULL,
+    summary TEXT NOT NULL
+);
+
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    commit_id TEXT NOT NULL,
+    content_fingerprint TEXT NOT NULL,
+    computed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f000Z', 'now'))
+);
+
+
+CREATE TABLE IF NOT EXISTS webmentions_sent (
+    slug TEXT NOT NULL,
+    target TEXT NOT NULL,
+    endpoint TEXT,
+    status INTEGER,
+    error TEXT,
+    sent_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%f000Z', 'now')),
+
+    PRIMARY KEY (slug, target)
+);
+
+
+CREATE TABLE IF NOT EXISTS tag_index (
+    tag TEXT PRIMARY KEY,
+    article_count INTEGER NOT NULL,
+    last_used TEXT NOT NULL
+);
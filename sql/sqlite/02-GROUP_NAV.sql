@@ -0,0 +1,3 @@
+-- 组在组列表中的排序和隐藏，见 sql/16-GROUP_NAV.sql
+ALTER TABLE groups ADD COLUMN nav_order INTEGER;
+ALTER TABLE groups ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
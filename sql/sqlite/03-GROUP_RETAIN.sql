@@ -0,0 +1,4 @@
+-- 组内文章的保留规则，见 sql/17-GROUP_RETAIN.sql
+ALTER TABLE groups ADD COLUMN retain_max_articles INTEGER;
+ALTER TABLE groups ADD COLUMN retain_max_age_days INTEGER;
+ALTER TABLE articles ADD COLUMN retention_pruned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- 文章最近一次渲染的耗时和大小，见 sql/18-RENDER_STATS.sql
+ALTER TABLE articles ADD COLUMN render_ms INTEGER;
+ALTER TABLE articles ADD COLUMN content_bytes INTEGER;
+ALTER TABLE articles ADD COLUMN rendered_at TEXT;
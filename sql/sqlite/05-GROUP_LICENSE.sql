@@ -0,0 +1,2 @@
+-- 组内文章的许可协议，见 sql/19-GROUP_LICENSE.sql
+ALTER TABLE groups ADD COLUMN license TEXT;
@@ -0,0 +1,2 @@
+-- 渲染结果对应原文的 simhash，见 sql/20-CONTENT_SIMHASH.sql
+ALTER TABLE content_blobs ADD COLUMN simhash INTEGER;
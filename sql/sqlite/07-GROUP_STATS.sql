@@ -0,0 +1,11 @@
+-- 组的大小统计和配额，见 sql/21-GROUP_STATS.sql
+ALTER TABLE articles ADD COLUMN source_bytes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE groups ADD COLUMN quota_bytes INTEGER;
+
+CREATE TABLE IF NOT EXISTS group_stats (
+    group_id TEXT PRIMARY KEY,
+    content_bytes INTEGER NOT NULL,
+    asset_bytes INTEGER NOT NULL DEFAULT 0,
+    article_count INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
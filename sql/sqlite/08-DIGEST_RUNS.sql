@@ -0,0 +1,9 @@
+-- 每周摘要的发送记录，见 sql/22-DIGEST_RUNS.sql
+CREATE TABLE IF NOT EXISTS digest_runs (
+    period_end TEXT PRIMARY KEY,
+    period_start TEXT NOT NULL,
+    article_count INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    ran_at TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- 组内文章 Front Matter 的附加约定，见 sql/23-GROUP_SCHEMA.sql
+ALTER TABLE groups ADD COLUMN front_matter_schema TEXT;
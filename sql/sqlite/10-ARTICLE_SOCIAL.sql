@@ -0,0 +1,2 @@
+-- 文章的分享卡片信息，见 sql/24-ARTICLE_SOCIAL.sql
+ALTER TABLE articles ADD COLUMN social TEXT;
@@ -0,0 +1,11 @@
+-- 文章附件，见 sql/25-ARTICLE_ATTACHMENTS.sql
+CREATE TABLE IF NOT EXISTS article_attachments (
+    slug TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    url_or_path TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    link_text TEXT NOT NULL,
+
+    PRIMARY KEY (slug, url_or_path)
+);
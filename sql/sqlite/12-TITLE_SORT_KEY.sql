@@ -0,0 +1,2 @@
+-- 文章标题的排序键，见 sql/26-TITLE_SORT_KEY.sql
+ALTER TABLE articles ADD COLUMN title_sort_key TEXT;
@@ -1,10 +1,46 @@
+mod admin;
+mod allowlist;
+mod archives;
+mod cache_tags;
+mod conditional;
+mod edit_url;
+mod feed;
+mod fields;
 mod git_sync;
+mod index;
+mod openapi;
+mod permalink;
 mod query;
+mod read;
+mod repo_stats;
+mod request_id;
+mod timetravel;
+mod version;
 
-use axum::Router;
-use tower_http::trace::TraceLayer;
+use std::net::SocketAddr;
+
+use axum::{Router, extract::Request, middleware};
+use tower_http::{
+    compression::{
+        CompressionLayer,
+        predicate::{NotForContentType, Predicate, SizeAbove},
+    },
+    trace::TraceLayer,
+};
 use tracing::instrument;
 
+pub use self::{
+    allowlist::HookAllowlist,
+    edit_url::EditUrlTemplate,
+    index::IndexCache,
+    openapi::ApiDoc,
+    permalink::{Permalink, PermalinkPattern},
+    repo_stats::RepoStatsCache,
+    version::{BuildFeatures, GIT_COMMIT, VersionInfo},
+};
+
+pub(crate) use self::feed::{encode_path, escape};
+
 use crate::{
     error::{Error, Result},
     git_sync::{PersistMode, Persistable, PushKind},
@@ -14,43 +50,126 @@ use crate::{
 
 /// 设置应用的路由。
 ///
-/// 将 `/api` 下的 Git 仓库接口和查询接口组合在一起，并绑定应用状态。
+/// 将 `/api` 下的 API 索引、版本信息、Git 仓库接口、仓库统计、时间旅行接口、查询接口、管理接口、归档接口、订阅源和 OpenAPI 文档组合在一起，
+/// 与 `/read` 下的文章阅读页一起绑定应用状态。
+///
+/// 每个请求都分配请求 id，见 [`request_id::assign`]。设置了 [`AppState::hook_allowlist`] 时，
+/// Git 仓库接口拒绝来源地址不在列表中的请求，见 [`allowlist::check`]。
+/// 文章、列表和订阅源的响应带有缓存标签，响应头名称见 [`AppState::cache_tag_header`]。
+///
+/// 启用 `swagger-ui` feature 时，额外在 `/api/docs` 挂载 Swagger UI。
 pub fn setup_route(app: AppState) -> Router {
-    Router::new()
-        .nest("/api", git_sync::setup_route().merge(query::setup_route()))
-        .with_state(app)
+    let mut repo_route = git_sync::setup_route();
+    if let Some(allowlist) = app.hook_allowlist() {
+        repo_route = repo_route.route_layer(middleware::from_fn_with_state(
+            allowlist.clone(),
+            allowlist::check,
+        ));
+    }
+
+    let router = Router::new()
+        .nest(
+            "/api",
+            index::setup_route()
+                .merge(version::setup_route())
+                .merge(repo_route)
+                .merge(repo_stats::setup_route())
+                .merge(timetravel::setup_route())
+                .merge(query::setup_route())
+                .merge(admin::setup_route())
+                .merge(archives::setup_route())
+                .merge(feed::setup_route())
+                .merge(openapi::setup_route()),
+        )
+        .merge(read::setup_route())
+        .layer(middleware::from_fn_with_state(
+            app.cache_tag_header().clone(),
+            cache_tags::render,
+        ))
+        .layer(middleware::from_fn(request_id::assign))
+        .with_state(app);
+
+    mount_swagger_ui(router)
+}
+
+/// 在 `/api/docs` 挂载 Swagger UI，读取 `/api/openapi.json`。
+#[cfg(feature = "swagger-ui")]
+fn mount_swagger_ui(router: Router) -> Router {
+    router.merge(
+        utoipa_swagger_ui::SwaggerUi::new("/api/docs")
+            .config(utoipa_swagger_ui::Config::from("/api/openapi.json")),
+    )
+}
+
+/// 未启用 `swagger-ui` feature 时不挂载任何路由。
+#[cfg(not(feature = "swagger-ui"))]
+fn mount_swagger_ui(router: Router) -> Router {
+    router
 }
 
 /// 启动 HTTP 服务，并使用给定的路由处理请求。
 ///
-/// 在 `0.0.0.0:3000` 上监听 TCP 连接，并打印启动日志。
+/// 在 `addr` 上监听 TCP 连接，并打印启动日志。连接的对端地址以 [`ConnectInfo`](axum::extract::ConnectInfo) 记录在请求中。
 #[instrument(name = "http server", skip_all)]
-pub async fn run_server_with_router(router: Router) {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
+pub async fn run_server_with_router(router: Router, addr: SocketAddr) {
+    let listener = tokio::net::TcpListener::bind(addr)
         .await
-        .expect("Failed to bind TCP listener on 0.0.0.0:3000");
+        .unwrap_or_else(|e| panic!("Failed to bind TCP listener on {addr}: {e}"));
 
-    tracing::info!("listening on :3000");
+    tracing::info!("listening on {addr}");
 
-    axum::serve(listener, router)
-        .await
-        .expect("Failed to start Axum server");
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("Failed to start Axum server");
 }
 
 /// 启动 HTTP 服务，自动设置路由和中间件。
 ///
 /// 1. 生成路由
-/// 2. 添加日志和追踪中间件
-/// 3. 启动服务器
-pub async fn run_server(app: AppState) {
-    let router = setup_route(app);
+/// 2. `compression` 为 `Some` 时添加响应压缩，值为最小压缩字节数，见 [`add_compression`]
+/// 3. 添加日志和追踪中间件
+/// 4. 启动服务器
+pub async fn run_server(app: AppState, addr: SocketAddr, compression: Option<u16>) {
+    let mut router = setup_route(app);
+    if let Some(min_bytes) = compression {
+        router = add_compression(router, min_bytes);
+    }
     let router = add_middlewares(router);
-    run_server_with_router(router).await
+    run_server_with_router(router, addr).await
+}
+
+/// 默认的最小压缩字节数，更小的响应压缩后收益不大
+pub const DEFAULT_COMPRESSION_MIN_BYTES: u16 = 1024;
+
+/// 为路由添加响应压缩。
+///
+/// 按请求头 `Accept-Encoding` 协商 brotli 或 gzip，未声明时原样返回。长度小于 `min_bytes` 的响应不压缩，
+/// 长度未知的响应总是压缩。流式同步的 NDJSON 响应不压缩，压缩器会缓冲输出，使进度行延迟到同步结束才到达客户端。
+/// 满足压缩条件的响应都带 `Vary: Accept-Encoding`，包括客户端未声明编码时原样返回的响应。
+///
+/// `Last-Modified` 与编码无关，压缩前后相同。`GET /api/slugs` 的 ETag 按压缩前的响应体计算，
+/// 压缩后保持不变，因此为弱 ETag（`W/"..."`）；其余接口的条件请求只使用 `Last-Modified`。
+pub fn add_compression(router: Router, min_bytes: u16) -> Router {
+    let predicate = SizeAbove::new(min_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new(git_sync::NDJSON));
+
+    router.layer(
+        CompressionLayer::new()
+            .br(true)
+            .gzip(true)
+            .compress_when(predicate),
+    )
 }
 
 /// 为路由添加中间件，包括请求追踪和失败日志记录。
 ///
-/// 日志记录会在请求失败时输出错误信息。
+/// 每个请求一个 span，请求 id 由内层中间件记录到 span 中，请求失败时随错误信息一起输出。
 fn add_middlewares(router: Router) -> Router {
     fn log_failure(
         err: tower_http::classify::ServerErrorsFailureClass,
@@ -62,6 +181,14 @@ fn add_middlewares(router: Router) -> Router {
 
     router.layer(
         TraceLayer::new_for_http()
+            .make_span_with(|req: &Request| {
+                tracing::info_span!(
+                    "request",
+                    method = %req.method(),
+                    uri = %req.uri(),
+                    request_id = tracing::field::Empty,
+                )
+            })
             .on_failure(log_failure)
             .on_request(|_req: &_, _span: &tracing::Span| {
                 // 空实现，关闭请求日志
@@ -0,0 +1,963 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, header::AUTHORIZATION},
+    routing::{delete, get, post},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::{
+    Error, Querier, Result,
+    git_sync::{ensure_migrated, open_repo},
+    query::parse_bound,
+};
+
+use crate::{
+    content::{Renderer, distance, near_duplicate_clusters},
+    digest::{self, Digest, DigestPeriod},
+    git_client::{ArchiveRemoval, ChangeKind, FileKind, PruneDecision},
+    git_sync::{
+        self, ArticlePreview, PreviewSource, QuotaItem, ReportItem, SyncLogEntry, SyncReport,
+        SyncStatus,
+    },
+    render::CircuitStatus,
+    state::AppState,
+    storage::{RenderSort, Store},
+};
+
+/// 每批重新渲染的默认文章数
+const DEFAULT_RERENDER_BATCH: usize = 20;
+
+/// 配置管理相关路由。
+///
+/// 路由包括：
+/// - `GET /admin/link-report`：失效外部链接报告，需要 bearer token
+/// - `GET /admin/sync-status`：同步协调器状态，需要 bearer token
+/// - `GET /admin/sync-log`：最近的同步记录，需要 bearer token
+/// - `GET /admin/stale-render`：由其他渲染器生成的文章，需要 bearer token
+/// - `POST /admin/rerender-stale`：重新渲染由其他渲染器生成的文章，需要 bearer token
+/// - `GET /admin/orphan-articles`：所在目录没有组配置的文章，需要 bearer token
+/// - `GET /admin/pruned-articles`：因所在组的保留规则下线的文章，需要 bearer token
+/// - `GET /admin/render-report`：最近一次渲染最慢或最大的文章，需要 bearer token
+/// - `GET /admin/duplicates`：内容重复或近似重复的公开文章，需要 bearer token
+/// - `POST /admin/preview`：按同步规则解析和渲染单个文件，不写入数据，需要 bearer token
+/// - `POST /admin/diff-debug`：两个提交之间每个路径裁剪前的变更和合并结果，需要 bearer token
+/// - `POST /admin/digest/preview`：按时间范围生成摘要，不发送，需要 bearer token
+/// - `DELETE /admin/archives/{tag}`：删除归档分支和归档标签，需要 bearer token
+/// - `GET /status`：最近一次同步后的内容指纹
+pub fn setup_route() -> Router<AppState> {
+    Router::new()
+        .route("/admin/link-report", get(link_report))
+        .route("/admin/sync-status", get(sync_status))
+        .route("/admin/sync-log", get(sync_log))
+        .route("/admin/stale-render", get(stale_render))
+        .route("/admin/rerender-stale", post(rerender_stale))
+        .route("/admin/orphan-articles", get(orphan_articles))
+        .route("/admin/pruned-articles", get(pruned_articles))
+        .route("/admin/render-report", get(render_report))
+        .route("/admin/duplicates", get(duplicates))
+        .route("/admin/preview", post(preview))
+        .route("/admin/diff-debug", post(diff_debug))
+        .route("/admin/digest/preview", post(digest_preview))
+        .route("/admin/archives/{tag}", delete(delete_archive))
+        .route("/status", get(status))
+}
+
+/// 引用失效链接的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArticleLinks {
+    pub slug: String,
+    pub title: String,
+    pub links: Vec<BrokenLink>,
+}
+
+/// 失效的外部链接。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BrokenLink {
+    pub url: String,
+    /// 响应状态码，请求失败时为 `null`
+    pub status: Option<i32>,
+    /// 请求失败原因，如 `timeout`
+    pub error: Option<String>,
+    /// 最近一次检查时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    pub last_checked: Option<i64>,
+}
+
+/// 获取失效外部链接报告。
+///
+/// 返回最近一次检查为 4xx/5xx 或请求失败的链接，按文章分组。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/link-report",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "按文章分组的失效链接", body = Vec<ArticleLinks>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn link_report(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ArticleLinks>>> {
+    authorize(&app, &headers)?;
+    let rows = app.querier().broken_links().await?;
+
+    // 查询结果已按 slug 排序，相邻行合并为同一篇文章
+    let mut report: Vec<ArticleLinks> = Vec::new();
+    for row in rows {
+        let link = BrokenLink {
+            url: row.url,
+            status: row.status,
+            error: row.error,
+            last_checked: row.last_checked.map(|t| t.timestamp_millis()),
+        };
+
+        match report.last_mut() {
+            Some(article) if article.slug == row.slug => article.links.push(link),
+            _ => report.push(ArticleLinks {
+                slug: row.slug,
+                title: row.title,
+                links: vec![link],
+            }),
+        }
+    }
+
+    Ok(Json(report))
+}
+
+/// 内容指纹及其对应的提交。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContentStatus {
+    /// 指纹对应的提交，尚未同步过时为 `null`
+    pub commit: Option<String>,
+    /// 仓库 HEAD 指向的提交，仓库还没有提交时为 `null`
+    pub head: Option<String>,
+    /// 同步后公开文章和组配置的 SHA-256，十六进制
+    ///
+    /// 与 `gitnote-admin fingerprint <commit>` 按仓库快照计算的结果相同时，数据库与仓库一致。
+    pub content_fingerprint: Option<String>,
+    /// 计算时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    pub computed_at: Option<i64>,
+    /// 当前配置的渲染器标识
+    #[schema(example = "github-gfm@2022-11-28")]
+    pub renderer: String,
+    /// 由其他渲染器生成的文章数，见 `GET /api/admin/stale-render`
+    ///
+    /// 熔断期间降级发布的文章标识为 `render-pending`，同样计入。
+    pub stale_renders: usize,
+    /// 渲染器的熔断状态，熔断期间文章以转义后的原文发布
+    pub renderer_circuit: CircuitStatus,
+    /// 超出配额的组，见组配置中的 `quota_bytes`
+    pub over_quota: Vec<QuotaItem>,
+    /// 最近一次每周摘要的执行结果，未启用或尚未执行过时为 `null`
+    pub last_digest: Option<LastDigest>,
+}
+
+/// 每周摘要的一次执行结果。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LastDigest {
+    /// 周期开始时间（包含），Unix 毫秒时间戳
+    #[schema(example = 1716800400000_i64)]
+    pub period_start: i64,
+    /// 周期结束时间（不包含），即计划发送的时间，Unix 毫秒时间戳
+    #[schema(example = 1717405200000_i64)]
+    pub period_end: i64,
+    /// 周期内新发布的公开文章数
+    pub article_count: i64,
+    /// `sent`、`skipped`（没有新文章）或 `failed`，失败原因记录在服务日志中
+    #[schema(example = "sent")]
+    pub status: String,
+    /// 实际执行时间，Unix 毫秒时间戳
+    #[schema(example = 1717405201000_i64)]
+    pub ran_at: i64,
+}
+
+/// 获取内容指纹。
+///
+/// 每次同步成功后按数据库中的组和公开文章重新计算，不包含评论数等不来自仓库的数据。
+/// 同时返回仓库当前的 HEAD、当前渲染器标识、熔断状态、需要重新渲染的文章数、超出配额的组和最近一次每周摘要的执行结果。
+#[utoipa::path(
+    get,
+    path = "/api/status",
+    responses((status = 200, description = "内容指纹", body = ContentStatus))
+)]
+pub(super) async fn status(State(app): State<AppState>) -> Result<Json<ContentStatus>> {
+    let state = app.querier().sync_state().await?;
+    let head = open_repo(&app)?.head().await?;
+    let renderer = app.renderer().identity();
+    let stale_renders = app.querier().stale_renders(&renderer).await?.len();
+    let over_quota = app
+        .querier()
+        .group_stats()
+        .await?
+        .iter()
+        .filter_map(QuotaItem::from_stats)
+        .collect();
+    let last_digest = app
+        .querier()
+        .last_digest_run()
+        .await?
+        .map(|run| LastDigest {
+            period_start: run.period_start.timestamp_millis(),
+            period_end: run.period_end.timestamp_millis(),
+            article_count: run.article_count,
+            status: run.status,
+            ran_at: run.ran_at.timestamp_millis(),
+        });
+
+    Ok(Json(ContentStatus {
+        commit: state.as_ref().map(|s| s.commit_id.clone()),
+        head,
+        content_fingerprint: state.as_ref().map(|s| s.content_fingerprint.clone()),
+        computed_at: state.map(|s| s.computed_at.timestamp_millis()),
+        renderer,
+        stale_renders,
+        renderer_circuit: app.renderer().inner().status(),
+        over_quota,
+        last_digest,
+    }))
+}
+
+/// 由其他渲染器生成的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StaleArticle {
+    pub slug: String,
+    pub title: String,
+    /// 写入时的渲染器标识，迁移前写入的文章为 `null`
+    pub renderer: Option<String>,
+}
+
+/// 过期渲染查询参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StaleRenderParams {
+    /// 对比的渲染器标识，默认为当前配置的渲染器
+    current: Option<String>,
+}
+
+/// 获取由其他渲染器生成的文章。
+///
+/// 返回渲染器标识与 `current` 不同的文章，包括未公开的文章和迁移前写入、未记录渲染器的文章，按 slug 排序。
+/// 原样输出 HTML 正文的文章（标识为 `raw-html`）不经过渲染器，不返回。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/stale-render",
+    params(StaleRenderParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "需要重新渲染的文章", body = Vec<StaleArticle>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn stale_render(
+    Query(params): Query<StaleRenderParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<StaleArticle>>> {
+    authorize(&app, &headers)?;
+    let current = params.current.unwrap_or_else(|| app.renderer().identity());
+    let stale = app.querier().stale_renders(&current).await?;
+
+    Ok(Json(
+        stale
+            .into_iter()
+            .map(|a| StaleArticle {
+                slug: a.slug,
+                title: a.title,
+                renderer: a.renderer,
+            })
+            .collect(),
+    ))
+}
+
+/// 没有组的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrphanArticle {
+    pub slug: String,
+    pub title: String,
+    /// 文章所在的组目录路径，需要在该目录下添加组配置文件，仓库根目录为空字符串
+    #[schema(example = "notes/rust")]
+    pub group: String,
+    /// 源文件路径，迁移前写入的文章为 `null`
+    pub source_path: Option<String>,
+}
+
+/// 获取没有组的文章。
+///
+/// 返回组目录下没有组配置文件的文章，包括未公开的文章，按组目录路径和 slug 排序。
+/// 这些文章已写入但不会出现在任何公开查询中，同步时会在 [`SyncReport`] 的 `warnings` 中提示。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/orphan-articles",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "没有组的文章", body = Vec<OrphanArticle>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn orphan_articles(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<OrphanArticle>>> {
+    authorize(&app, &headers)?;
+    let orphans = app.querier().orphan_articles().await?;
+
+    Ok(Json(
+        orphans
+            .into_iter()
+            .map(|a| OrphanArticle {
+                slug: a.slug,
+                title: a.title,
+                group: a.group_id.into(),
+                source_path: a.source_path,
+            })
+            .collect(),
+    ))
+}
+
+/// 因所在组的保留规则下线的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PrunedArticle {
+    pub slug: String,
+    pub title: String,
+    /// 组目录路径
+    pub group: String,
+    /// 创建时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    pub created_at: i64,
+    /// 源文件路径，迁移前写入的文章为 `null`
+    pub source_path: Option<String>,
+}
+
+/// 获取因保留规则下线的文章。
+///
+/// 返回超出所在组 `retain` 设置、同步后被下线的文章，按组目录路径排列，组内从新到旧排序。
+/// 这些文章仍保存在数据库中，放宽 `retain` 后下次同步按创建时间从新到旧重新上线；
+/// 作者自己设置为不公开的文章不在其中。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/pruned-articles",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "因保留规则下线的文章", body = Vec<PrunedArticle>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn pruned_articles(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PrunedArticle>>> {
+    authorize(&app, &headers)?;
+    let pruned = app.querier().pruned_articles().await?;
+
+    Ok(Json(
+        pruned
+            .into_iter()
+            .map(|a| PrunedArticle {
+                slug: a.slug,
+                title: a.title,
+                group: a.group_id.into(),
+                created_at: a.created_at.timestamp_millis(),
+                source_path: a.source_path,
+            })
+            .collect(),
+    ))
+}
+
+/// 渲染报告的排序字段，都按从大到小排序。
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderReportSort {
+    /// 渲染耗时
+    #[default]
+    RenderMs,
+    /// 渲染后正文的字节数
+    ContentBytes,
+}
+
+impl From<RenderReportSort> for RenderSort {
+    fn from(sort: RenderReportSort) -> Self {
+        match sort {
+            RenderReportSort::RenderMs => Self::RenderMs,
+            RenderReportSort::ContentBytes => Self::ContentBytes,
+        }
+    }
+}
+
+/// 渲染报告查询参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderReportParams {
+    /// 排序字段，默认 `render_ms`
+    #[serde(default)]
+    sort: RenderReportSort,
+    /// 返回的文章数，默认 50，最大 500
+    #[serde(default = "RenderReportParams::default_limit")]
+    limit: u32,
+}
+
+impl RenderReportParams {
+    const MAX_LIMIT: u32 = 500;
+
+    fn default_limit() -> u32 {
+        50
+    }
+}
+
+/// 文章最近一次渲染的耗时和大小。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RenderReportItem {
+    pub slug: String,
+    pub title: String,
+    /// 组目录路径
+    pub group: String,
+    /// 源文件路径，迁移前写入的文章为 `null`
+    pub source_path: Option<String>,
+    /// 渲染正文和摘要的耗时，毫秒
+    pub render_ms: i64,
+    /// 渲染后正文的字节数
+    pub content_bytes: i64,
+    /// 最近一次渲染的时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    pub rendered_at: i64,
+}
+
+/// 获取渲染最慢或最大的文章。
+///
+/// 同步实际调用渲染器时记录每篇文章的渲染耗时和渲染后正文的字节数，复用已有渲染结果的文章保持上一次的记录，
+/// 没有记录的文章不在其中。按 `sort` 从大到小排列，包括未公开的文章。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/render-report",
+    params(RenderReportParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "按渲染耗时或大小排序的文章", body = Vec<RenderReportItem>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn render_report(
+    Query(params): Query<RenderReportParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<RenderReportItem>>> {
+    authorize(&app, &headers)?;
+    let limit = params.limit.min(RenderReportParams::MAX_LIMIT);
+    let stats = app
+        .querier()
+        .render_report(params.sort.into(), i64::from(limit))
+        .await?;
+
+    Ok(Json(
+        stats
+            .into_iter()
+            .map(|s| RenderReportItem {
+                slug: s.slug,
+                title: s.title,
+                group: s.group_id.into(),
+                source_path: s.source_path,
+                render_ms: s.render_ms,
+                content_bytes: s.content_bytes,
+                rendered_at: s.rendered_at.timestamp_millis(),
+            })
+            .collect(),
+    ))
+}
+
+/// 内容重复或近似重复的一组文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DuplicateCluster {
+    /// 组内任意两篇文章 simhash 相差的最大位数，0 表示内容相同
+    pub max_distance: u32,
+    /// 按 slug 排序
+    pub articles: Vec<DuplicateArticle>,
+}
+
+/// 重复内容中的一篇文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DuplicateArticle {
+    pub slug: String,
+    pub title: String,
+    /// 组目录路径
+    pub group: String,
+    /// 源文件路径，迁移前写入的文章为 `null`
+    pub source_path: Option<String>,
+}
+
+/// 获取内容重复或近似重复的公开文章。
+///
+/// 同步时为每份新的内容计算去掉 Front Matter 后原文的 64 位 simhash，相差不超过 8 位的文章视为近似重复，
+/// 经近似重复的文章连通的文章归为一组。加密文章和内容过短的文章不参与比较。
+/// 新增或修改的文章与其他组的公开文章重复时，同步时会在 [`SyncReport`] 的 `warnings` 中提示。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/duplicates",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "内容重复或近似重复的文章，按第一篇文章的 slug 排序", body = Vec<DuplicateCluster>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn duplicates(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<DuplicateCluster>>> {
+    authorize(&app, &headers)?;
+    let articles = app.storage().article_simhashes().await?;
+    let hashes = articles.iter().map(|a| a.simhash).collect::<Vec<_>>();
+    let hashes = hashes.as_slice();
+
+    Ok(Json(
+        near_duplicate_clusters(hashes)
+            .into_iter()
+            .map(|cluster| DuplicateCluster {
+                max_distance: cluster
+                    .iter()
+                    .flat_map(|&i| cluster.iter().map(move |&j| distance(hashes[i], hashes[j])))
+                    .max()
+                    .unwrap_or_default(),
+                articles: cluster
+                    .iter()
+                    .map(|&i| {
+                        let a = &articles[i];
+                        DuplicateArticle {
+                            slug: a.slug.to_owned(),
+                            title: a.title.to_owned(),
+                            group: a.group_id.to_owned().into(),
+                            source_path: a.source_path.to_owned(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect(),
+    ))
+}
+
+/// 预览请求，`path` 和 `content` 至少给出一个。
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PreviewRequest {
+    /// 文件在仓库中的路径，用于确定所属组和 slug；未给出 `content` 时从 `ref` 指向的提交中读取
+    #[schema(example = "notes/hello.md")]
+    path: Option<String>,
+    /// 读取文件、组配置和引用片段的分支、标签或提交，默认为 `HEAD`
+    #[serde(rename = "ref")]
+    #[schema(example = "main")]
+    rev: Option<String>,
+    /// 直接给出的 Markdown，未给出 `path` 时视为仓库根目录下的 `preview.md`
+    content: Option<String>,
+}
+
+/// 预览单个文件的解析和渲染结果。
+///
+/// 与同步使用相同的大小检查、字段检查、引用展开、组和 slug 推断以及渲染器，不写入任何数据。
+/// 内容无法处理时返回 422，响应体与同步报告中 `failed` 的记录相同。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    post,
+    path = "/api/admin/preview",
+    request_body = PreviewRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "解析和渲染结果", body = ArticlePreview),
+        (status = 400, description = "缺少 `path` 和 `content`", body = String, content_type = "text/plain"),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 404, description = "提交或文件不存在", body = String, content_type = "text/plain"),
+        (status = 422, description = "内容无法处理", body = ReportItem),
+    )
+)]
+pub(super) async fn preview(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<PreviewRequest>,
+) -> Result<Json<ArticlePreview>> {
+    authorize(&app, &headers)?;
+    let rev = req.rev.unwrap_or_else(|| "HEAD".to_string());
+    let source = match (req.path, req.content) {
+        (path, Some(content)) => PreviewSource::Inline {
+            rev,
+            path: path.unwrap_or_else(|| "preview.md".to_string()).into(),
+            content,
+        },
+        (Some(path), None) => PreviewSource::Repo {
+            rev,
+            path: path.into(),
+        },
+        (None, None) => return Err(Error::Custom("either `path` or `content` is required")),
+    };
+
+    let repo = open_repo(&app)?;
+    let preview = git_sync::preview_article(&repo, app.renderer(), app.limits(), source).await?;
+    Ok(Json(preview))
+}
+
+/// 裁剪排查请求。
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DiffDebugRequest {
+    /// 范围起点的分支、标签或提交，全零的提交 id 表示仓库初始状态
+    #[schema(example = "0000000000000000000000000000000000000000")]
+    before: String,
+    /// 范围终点的分支、标签或提交
+    #[schema(example = "main")]
+    after: String,
+}
+
+/// 单个路径在裁剪时的决定。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DiffDecision {
+    pub path: String,
+    /// 按提交顺序的原始变更
+    pub changes: Vec<DiffChange>,
+    /// 合并后的变更类型，变更相互抵消时为 `null`，此时同步不处理该路径
+    #[schema(example = "modified")]
+    pub merged: Option<&'static str>,
+}
+
+/// 单个提交中的原始变更，重命名拆分为旧路径的 `deleted` 和新路径的 `added`。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DiffChange {
+    pub commit: String,
+    /// `added`、`modified` 或 `deleted`
+    #[schema(example = "added")]
+    pub status: &'static str,
+    /// `group`、`markdown`、`readme` 或 `other`
+    #[schema(example = "markdown")]
+    pub file_kind: &'static str,
+    /// 变更后的 blob id，删除时为删除前的 blob
+    pub blob: String,
+    /// 重命名前的路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+}
+
+impl From<PruneDecision> for DiffDecision {
+    fn from(decision: PruneDecision) -> Self {
+        Self {
+            path: decision.path.to_string_lossy().into_owned(),
+            changes: decision
+                .changes
+                .into_iter()
+                .map(|c| DiffChange {
+                    commit: c.commit,
+                    status: change_name(c.change_kind),
+                    file_kind: match c.file_kind {
+                        FileKind::Group => "group",
+                        FileKind::Markdown => "markdown",
+                        FileKind::Readme => "readme",
+                        FileKind::Other => "other",
+                    },
+                    blob: c.id,
+                    renamed_from: c.renamed_from.map(|p| p.to_string_lossy().into_owned()),
+                })
+                .collect(),
+            merged: decision.merged.map(change_name),
+        }
+    }
+}
+
+fn change_name(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "added",
+        ChangeKind::Modified => "modified",
+        ChangeKind::Deleted => "deleted",
+    }
+}
+
+/// 排查两个提交之间的变更如何被合并。
+///
+/// 执行与同步相同的提交遍历和变更提取，按路径首次出现的顺序返回每个路径按提交顺序的原始变更，
+/// 以及裁剪后同步实际处理的变更类型，不写入任何数据。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    post,
+    path = "/api/admin/diff-debug",
+    request_body = DiffDebugRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "每个路径的裁剪决定", body = Vec<DiffDecision>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 404, description = "提交不存在", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn diff_debug(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<DiffDebugRequest>,
+) -> Result<Json<Vec<DiffDecision>>> {
+    authorize(&app, &headers)?;
+    let repo = open_repo(&app)?;
+    let before = if req.before.bytes().all(|b| b == b'0') {
+        req.before
+    } else {
+        repo.resolve(&req.before).await?
+    };
+    let after = repo.resolve(&req.after).await?;
+
+    let decisions = repo.diff_trace(&before, &after).await?;
+    Ok(Json(
+        decisions.into_iter().map(DiffDecision::from).collect(),
+    ))
+}
+
+/// 摘要预览请求。
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DigestPreviewRequest {
+    /// 开始时间（包含），格式与文章列表的 `created_after` 相同，不带偏移时按 `GITNOTE_TIMEZONE` 解释
+    #[schema(example = "2024-05-27")]
+    from: String,
+    /// 结束时间（不包含），格式相同，只有日期时取当天开始，默认为当前时间
+    #[schema(example = "2024-06-03")]
+    to: Option<String>,
+}
+
+/// 摘要预览。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DigestPreview {
+    /// webhook 以 `json` 格式发送的内容
+    pub digest: Digest,
+    /// webhook 以 `html` 格式发送的内容，也是邮件的 HTML 正文
+    pub html: String,
+}
+
+/// 预览时间范围内的摘要。
+///
+/// 与定期发送的摘要相同，汇总范围内创建的公开文章并按分类分组，不发送，也不记录执行结果。
+/// 范围为空或开始时间晚于结束时间时返回没有文章的摘要。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    post,
+    path = "/api/admin/digest/preview",
+    request_body = DigestPreviewRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "摘要内容", body = DigestPreview),
+        (status = 400, description = "时间格式错误", body = String, content_type = "text/plain"),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn digest_preview(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<DigestPreviewRequest>,
+) -> Result<Json<DigestPreview>> {
+    authorize(&app, &headers)?;
+    let tz = app.timezone();
+    let start =
+        parse_bound("from", Some(&req.from), tz, false)?.ok_or(Error::InvalidParam("from"))?;
+    let end = parse_bound("to", req.to.as_deref(), tz, false)?
+        .unwrap_or_else(|| chrono::Utc::now().fixed_offset());
+
+    let digest = digest::collect(
+        &app.querier(),
+        app.site_url(),
+        tz,
+        DigestPeriod { start, end },
+    )
+    .await?;
+    let html = digest.to_html();
+    Ok(Json(DigestPreview { digest, html }))
+}
+
+/// 重新渲染参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RerenderParams {
+    /// 每批重新渲染的文章数，每批单独提交，默认 20
+    batch: Option<usize>,
+}
+
+/// 重新渲染由其他渲染器生成的文章。
+///
+/// 与增量同步一同排队执行，使用当前配置的渲染器，从最近一次同步的目标提交（尚未同步过时为 `HEAD`）读取源文件，
+/// 分批持久化，返回 [`SyncReport`]，重新渲染的文件记录在 `modified` 中。
+/// 渲染器熔断期间降级发布的文章在恢复后通过此接口重新渲染。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    post,
+    path = "/api/admin/rerender-stale",
+    params(RerenderParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "重新渲染完成", body = SyncReport),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 409, description = "已有重建正在执行", body = String, content_type = "text/plain"),
+        (status = 503, description = "排队的增量同步已满，或数据库未迁移", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn rerender_stale(
+    Query(params): Query<RerenderParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SyncReport>> {
+    authorize(&app, &headers)?;
+    ensure_migrated(&app)?;
+    let _permit = app.coordinator().sync().await?;
+    let repo = open_repo(&app)?;
+
+    let commit = match app.querier().sync_state().await? {
+        Some(state) => state.commit_id,
+        None => repo.resolve("HEAD").await?,
+    };
+    let stale = app
+        .querier()
+        .stale_renders(&app.renderer().identity())
+        .await?;
+
+    let report = git_sync::rerender_stale(
+        &app.storage(),
+        app.renderer(),
+        &repo,
+        &commit,
+        app.limits(),
+        &stale,
+        params.batch.unwrap_or(DEFAULT_RERENDER_BATCH),
+    )
+    .await?;
+
+    Ok(Json(report))
+}
+
+/// 获取同步协调器状态。
+///
+/// 返回 [`SyncStatus`]，包括是否正在重建、排队的增量同步数量，以及开启防抖时等待合并执行的范围。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/sync-status",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "同步状态", body = SyncStatus),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn sync_status(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<SyncStatus>> {
+    authorize(&app, &headers)?;
+    let mut status = app.coordinator().status();
+    status.pending = app.debouncer().and_then(|d| d.pending());
+    Ok(Json(status))
+}
+
+/// 同步记录查询参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncLogParams {
+    /// 为 `true` 时只返回失败和部分失败的记录
+    #[serde(default)]
+    failed: bool,
+}
+
+/// 获取最近的同步记录。
+///
+/// 返回最近的推送触发的同步（包括防抖合并执行的同步）的结果、耗时和文本摘要或错误信息，按时间倒序。
+/// 记录只保存在内存中，数量见 `GITNOTE_SYNC_LOG_SIZE`，重启后清空。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/admin/sync-log",
+    params(SyncLogParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "同步记录，最新的在前", body = Vec<SyncLogEntry>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn sync_log(
+    Query(params): Query<SyncLogParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SyncLogEntry>>> {
+    authorize(&app, &headers)?;
+    Ok(Json(app.sync_log().entries(params.failed)))
+}
+
+/// 删除归档参数。
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct DeleteArchiveParams {
+    /// 是否允许删除最近的归档，默认不允许
+    force: bool,
+}
+
+/// 删除归档。
+///
+/// 删除 `archived/<tag>` 分支和 `archive/<tag>` 标签，返回因此无法到达的提交数。只删除引用，不执行 `git gc`，
+/// 提交在仓库下次 gc 之前仍然保留。`tag` 必须恰好为 `YYYY-Qn`，其他名称返回 400；
+/// `tag` 为最近的归档时需要 `force=true`，否则返回 409。批量删除见 `gitnote-admin archive prune`。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    delete,
+    path = "/api/admin/archives/{tag}",
+    params(("tag" = String, Path, description = "归档名，如 `2024-Q1`"), DeleteArchiveParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "删除的引用和无法到达的提交数", body = ArchiveRemoval),
+        (status = 400, description = "归档名不是 `YYYY-Qn`", body = String, content_type = "text/plain"),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 404, description = "归档不存在", body = String, content_type = "text/plain"),
+        (status = 409, description = "最近的归档，需要 `force=true`", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn delete_archive(
+    Path(tag): Path<String>,
+    Query(params): Query<DeleteArchiveParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ArchiveRemoval>> {
+    authorize(&app, &headers)?;
+    let repo = open_repo(&app)?;
+    let removal = git_sync::remove_archive(&repo, &tag, params.force).await?;
+    tracing::info!(
+        archive = removal.name,
+        unreachable_commits = removal.unreachable_commits,
+        "archive deleted"
+    );
+    Ok(Json(removal))
+}
+
+/// 校验请求头中的 bearer token 与 [`AppState::admin_token`] 一致
+pub(super) fn authorize(app: &AppState, headers: &HeaderMap) -> Result<()> {
+    let expected = app.admin_token().ok_or(Error::Unauthorized)?;
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?;
+
+    // 逐字节比较全部内容，耗时不随匹配的前缀长度变化
+    let matched = token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0;
+    if matched {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+
+use crate::error::{Error, Result};
+
+/// 代理转发的客户端地址请求头
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Git 仓库接口允许访问的来源地址
+///
+/// 由 `GITNOTE_HOOK_ALLOW` 配置，逗号分隔的 CIDR 列表，如 `127.0.0.1/32,10.0.0.0/8`，
+/// 不带前缀长度的地址视为单个主机。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookAllowlist {
+    networks: Arc<[IpNet]>,
+    trust_proxy: bool,
+}
+
+impl HookAllowlist {
+    /// 创建允许 `networks` 访问的列表
+    pub fn new(networks: impl IntoIterator<Item = IpNet>) -> Self {
+        Self {
+            networks: networks.into_iter().collect(),
+            trust_proxy: false,
+        }
+    }
+
+    /// 设置是否信任 `X-Forwarded-For` 请求头，见 [`HookAllowlist::client_ip`]
+    pub fn with_trust_proxy(mut self, trust_proxy: bool) -> Self {
+        self.trust_proxy = trust_proxy;
+        self
+    }
+
+    /// 允许访问的网段
+    pub fn networks(&self) -> &[IpNet] {
+        &self.networks
+    }
+
+    /// `ip` 是否位于任一允许的网段中，IPv4 映射的 IPv6 地址按 IPv4 地址判断
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|net| net.contains(&ip))
+    }
+
+    /// 请求的客户端地址
+    ///
+    /// 默认为连接的对端地址。信任代理时取 `X-Forwarded-For` 的最后一项，即直接相连的代理记录的地址，
+    /// 之前的项由客户端提供，可以伪造；请求头缺失或格式错误时退回对端地址。
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.trust_proxy {
+            return peer;
+        }
+        headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .next_back()
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit(',').next())
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(peer)
+    }
+}
+
+impl FromStr for HookAllowlist {
+    type Err = String;
+
+    /// 任一项不是合法的 CIDR 或 IP 地址时返回包含该项的错误
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let networks = s
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|e| {
+                e.parse::<IpNet>()
+                    .or_else(|err| e.parse::<IpAddr>().map(IpNet::from).map_err(|_| err))
+                    .map_err(|err| format!("`{e}` is not a CIDR network: {err}"))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self::new(networks))
+    }
+}
+
+/// 拒绝来源地址不在 [`HookAllowlist`] 中的请求，返回 403
+///
+/// 在解析请求体之前执行。服务未记录连接信息（见 [`ConnectInfo`]）时无法判断来源，一律拒绝。
+pub(super) async fn check(
+    State(allowlist): State<HookAllowlist>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let Some(peer) = peer else {
+        tracing::warn!("connect info unavailable, hook request rejected");
+        return Err(Error::Forbidden);
+    };
+
+    let ip = allowlist.client_ip(peer, req.headers());
+    if !allowlist.allows(ip) {
+        tracing::warn!(%ip, %peer, "hook request from disallowed address");
+        return Err(Error::Forbidden);
+    }
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_allowlist() {
+        let allowlist: HookAllowlist = "127.0.0.1/32, 10.0.0.0/8,,::1".parse().unwrap();
+        assert_eq!(allowlist.networks().len(), 3);
+        assert!(allowlist.allows("10.1.2.3".parse().unwrap()));
+        assert!(allowlist.allows("::ffff:127.0.0.1".parse().unwrap()));
+        assert!(allowlist.allows("::1".parse().unwrap()));
+        assert!(!allowlist.allows("192.168.0.1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<HookAllowlist>().is_err());
+        assert!("localhost".parse::<HookAllowlist>().is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("127.0.0.1, 203.0.113.9"),
+        );
+
+        let allowlist = HookAllowlist::new([]);
+        assert_eq!(allowlist.client_ip(peer, &headers), peer);
+
+        let allowlist = allowlist.with_trust_proxy(true);
+        assert_eq!(
+            allowlist.client_ip(peer, &headers),
+            "203.0.113.9".parse::<IpAddr>().unwrap()
+        );
+        headers.insert(X_FORWARDED_FOR, HeaderValue::from_static("unknown"));
+        assert_eq!(allowlist.client_ip(peer, &headers), peer);
+    }
+}
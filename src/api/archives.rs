@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, Method},
+    response::Response,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::{
+    Result,
+    query::{self, QueryParams},
+};
+
+use crate::{
+    content::ArticleBuilder,
+    git_client::{ChangeKind, FileKind, GitClient, previous_archive},
+    state::AppState,
+    storage::Store,
+};
+
+/// 配置归档相关路由。
+///
+/// 路由包括：
+/// - `GET /archives/{tag}/changes`：两个归档之间新增和删除的文章
+/// - `GET /archives/content`：已归档的文章
+pub fn setup_route() -> Router<AppState> {
+    Router::new()
+        .route("/archives/{tag}/changes", get(archive_changes))
+        .route("/archives/content", get(archived_content))
+}
+
+/// 两个归档之间的文章变更。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArchiveChanges {
+    pub tag: String,
+    /// 对比的旧归档，没有更早的归档时为 `null`，表示从仓库初始状态开始
+    pub since: Option<String>,
+    /// 新增的文章数
+    pub added: usize,
+    /// 删除的文章数
+    pub removed: usize,
+    /// 按组统计，按组 id 排序，只包含有变更的组
+    pub groups: Vec<GroupChanges>,
+}
+
+/// 单个组内的文章变更。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupChanges {
+    /// 组 id，组配置设置了 `slug` 时为该值
+    pub group: String,
+    /// 新增的文章文件路径
+    pub added: Vec<String>,
+    /// 删除的文章文件路径
+    pub removed: Vec<String>,
+}
+
+/// 归档变更查询参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChangesParams {
+    /// 对比的旧归档，省略时使用名称中 `YYYY-Qn` 早于 `tag` 的最近一个归档
+    since: Option<String>,
+}
+
+/// 获取两个归档之间新增和删除的文章。
+///
+/// 归档为 `archived/<tag>` 分支或 `archive/<tag>` 标签。只读取提交之间的差异，不渲染内容；
+/// 文章所属组按已存储的组设置确定。两个归档相同时返回空的变更，归档不存在时返回 404。
+#[utoipa::path(
+    get,
+    path = "/api/archives/{tag}/changes",
+    params(("tag" = String, Path, description = "归档名，如 `2024-Q2`"), ChangesParams),
+    responses(
+        (status = 200, description = "归档之间的文章变更", body = ArchiveChanges),
+        (status = 404, description = "归档不存在", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn archive_changes(
+    Path(tag): Path<String>,
+    Query(params): Query<ChangesParams>,
+    State(app): State<AppState>,
+) -> Result<Json<ArchiveChanges>> {
+    let repo = GitClient::open(app.repo_path())?.with_classifier(app.classifier().clone());
+
+    let since = match params.since {
+        Some(since) => Some(since),
+        None => {
+            let archives = repo.archives().await?;
+            previous_archive(&archives, &tag).map(str::to_string)
+        }
+    };
+    let entries = repo.diff_archives(since.as_deref(), &tag).await?;
+
+    let strategies = app
+        .storage()
+        .group_settings()
+        .await?
+        .into_iter()
+        .map(|(id, s)| (id, s.slug_strategy))
+        .collect::<HashMap<_, _>>();
+    let slugs = app.storage().group_slugs().await?;
+
+    let mut groups = BTreeMap::new();
+    for entry in entries
+        .iter()
+        .filter(|e| e.file_kind() == FileKind::Markdown)
+    {
+        let builder = ArticleBuilder::with_strategies(entry.path(), &strategies);
+        let group = builder.to_ref().group;
+        let changes = groups
+            .entry(slugs.get(group).map_or(group, String::as_str).to_string())
+            .or_insert_with_key(|group| GroupChanges {
+                group: group.clone(),
+                added: Vec::new(),
+                removed: Vec::new(),
+            });
+
+        let path = entry.path().to_string_lossy().into_owned();
+        match entry.change_kind() {
+            ChangeKind::Added => changes.added.push(path),
+            ChangeKind::Deleted => changes.removed.push(path),
+            ChangeKind::Modified => (),
+        }
+    }
+
+    let groups = groups
+        .into_values()
+        .filter(|g| !g.added.is_empty() || !g.removed.is_empty())
+        .collect::<Vec<_>>();
+
+    Ok(Json(ArchiveChanges {
+        tag,
+        since,
+        added: groups.iter().map(|g| g.added.len()).sum(),
+        removed: groups.iter().map(|g| g.removed.len()).sum(),
+        groups,
+    }))
+}
+
+/// 获取已归档的文章列表。
+///
+/// 只包含所在组配置了 `archived: true` 的公开文章，参数和响应与 `GET /api/articles` 相同，
+/// `include_archived` 参数不起作用。
+#[utoipa::path(
+    get,
+    path = "/api/archives/content",
+    params(QueryParams),
+    responses(
+        (status = 200, description = "已归档的文章列表", body = Vec<query::ArticleMeta>,
+            headers(("Last-Modified" = String, description = "本页文章中最新的更新时间"))),
+        (status = 304, description = "本页文章在 `If-Modified-Since` 之后没有更新"),
+    )
+)]
+pub(super) async fn archived_content(
+    method: Method,
+    headers: HeaderMap,
+    Query(params): Query<QueryParams>,
+    State(app): State<AppState>,
+) -> Result<Response> {
+    query::list_articles(method, headers, params, app, Some(true)).await
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponseParts, Response, ResponseParts},
+};
+
+use crate::cdn::{CacheTagHeader, article_tag, group_tag};
+
+/// 响应的缓存标签，由 [`render`] 写入 [`CacheTagHeader`] 指定的响应头
+///
+/// 作为响应的一部分返回，如 `(CacheTags::collection("articles"), Json(list))`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct CacheTags(Vec<String>);
+
+impl CacheTags {
+    /// 单篇文章：`article:<group>/<slug>` 和 `group:<group>`，组为组目录路径
+    pub(super) fn article(group: &str, slug: &str) -> Self {
+        Self(vec![article_tag(group, slug), group_tag(group)])
+    }
+
+    /// 组详情：`group:<group>` 和 `collection:groups`，组为组目录路径
+    pub(super) fn group(group: &str) -> Self {
+        Self(vec![group_tag(group), "collection:groups".to_string()])
+    }
+
+    /// 汇总内容：`collection:<name>`，名称见 [`COLLECTION_TAGS`](crate::cdn::COLLECTION_TAGS)
+    pub(super) fn collection(name: &str) -> Self {
+        Self(vec![format!("collection:{name}")])
+    }
+}
+
+impl IntoResponseParts for CacheTags {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions_mut().insert(self);
+        Ok(res)
+    }
+}
+
+/// 将处理函数返回的 [`CacheTags`] 以逗号分隔写入响应头
+pub(super) async fn render(
+    State(header): State<CacheTagHeader>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut resp = next.run(req).await;
+    if let Some(CacheTags(tags)) = resp.extensions_mut().remove::<CacheTags>()
+        && let Ok(value) = HeaderValue::from_str(&tags.join(","))
+    {
+        resp.headers_mut().insert(header.name().clone(), value);
+    }
+    resp
+}
@@ -0,0 +1,162 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    },
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, FixedOffset, Utc};
+use sha2::{Digest, Sha256};
+
+/// 格式化为 HTTP 日期，精确到秒
+pub(super) fn http_date(time: DateTime<FixedOffset>) -> String {
+    time.with_timezone(&Utc)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// 请求头 `If-Modified-Since` 是否表明客户端的副本仍然有效
+///
+/// HTTP 日期只精确到秒，`updated_at` 截断到整秒后与 `If-Modified-Since` 比较，不晚于时返回 304，
+/// 客户端原样回传 `Last-Modified` 时得到 304。同一秒内的后一次更新因此无法通过该请求头发现。
+pub(super) fn not_modified(headers: &HeaderMap, updated_at: DateTime<FixedOffset>) -> bool {
+    headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .is_some_and(|since| updated_at.timestamp() <= since.timestamp())
+}
+
+/// 附加 `Last-Modified` 响应头，`updated_at` 为 [`None`] 时原样返回
+pub(super) fn last_modified(
+    response: impl IntoResponse,
+    updated_at: Option<DateTime<FixedOffset>>,
+) -> Response {
+    let mut response = response.into_response();
+    if let Some(value) = updated_at.and_then(|t| HeaderValue::try_from(http_date(t)).ok()) {
+        response.headers_mut().insert(LAST_MODIFIED, value);
+    }
+    response
+}
+
+/// `304 Not Modified`，附带 `Last-Modified`
+pub(super) fn not_modified_response(updated_at: DateTime<FixedOffset>) -> Response {
+    last_modified(StatusCode::NOT_MODIFIED, Some(updated_at))
+}
+
+/// 按压缩前的响应体计算弱 ETag，内容相同的响应得到相同的值
+///
+/// 压缩中间件不修改 ETag，同一个值对应不同编码的响应体，因此不能作为强 ETag。
+pub(super) fn weak_etag(body: &[u8]) -> String {
+    format!("W/\"{:x}\"", Sha256::digest(body))
+}
+
+/// 请求头 `If-None-Match` 是否包含 `etag`
+///
+/// 可以是逗号分隔的多个值，`*` 匹配任意值；与 `If-None-Match` 的规定相同按弱比较，两边都忽略 `W/` 前缀。
+pub(super) fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// 附带 `ETag` 的 JSON 响应
+pub(super) fn etag_json(body: Vec<u8>, etag: &str) -> Response {
+    let mut response = ([(CONTENT_TYPE, "application/json")], body).into_response();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(ETAG, value);
+    }
+    response
+}
+
+/// `304 Not Modified`，附带 `ETag`
+pub(super) fn not_modified_etag(etag: &str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(ETAG, value);
+    }
+    response
+}
+
+/// `HEAD` 请求的 JSON 响应，响应头与 `GET` 相同，不生成响应体
+///
+/// 响应体长度未知，不返回 `Content-Length`。
+pub(super) fn head_json(updated_at: Option<DateTime<FixedOffset>>) -> Response {
+    let body = Body::from_stream(tokio_stream::empty::<Result<Bytes, Infallible>>());
+    last_modified(
+        (
+            [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            body,
+        ),
+        updated_at,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_not_modified() {
+        let at = |ms: i64| Utc.timestamp_millis_opt(ms).unwrap().fixed_offset();
+        let updated_at = at(1_717_171_200_400);
+        assert_eq!(http_date(updated_at), "Fri, 31 May 2024 16:00:00 GMT");
+
+        let mut headers = HeaderMap::new();
+        assert!(!not_modified(&headers, updated_at));
+
+        // 原样回传 Last-Modified，毫秒部分被截断
+        headers.insert(IF_MODIFIED_SINCE, http_date(updated_at).parse().unwrap());
+        assert!(not_modified(&headers, updated_at));
+
+        headers.insert(
+            IF_MODIFIED_SINCE,
+            http_date(at(1_717_171_201_000)).parse().unwrap(),
+        );
+        assert!(not_modified(&headers, updated_at));
+
+        headers.insert(
+            IF_MODIFIED_SINCE,
+            http_date(at(1_717_171_199_999)).parse().unwrap(),
+        );
+        assert!(!not_modified(&headers, updated_at));
+
+        headers.insert(IF_MODIFIED_SINCE, HeaderValue::from_static("yesterday"));
+        assert!(!not_modified(&headers, updated_at));
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = weak_etag(b"[]");
+        assert_eq!(etag, weak_etag(b"[]"));
+        assert_ne!(etag, weak_etag(b"[1]"));
+        assert!(etag.starts_with("W/\"") && etag.ends_with('"'));
+        let opaque = etag.trim_start_matches("W/");
+
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, &etag));
+
+        for value in [
+            etag.clone(),
+            opaque.to_string(),
+            format!("\"other\", {etag}"),
+            "*".to_string(),
+        ] {
+            headers.insert(IF_NONE_MATCH, value.parse().unwrap());
+            assert!(etag_matches(&headers, &etag), "{value}");
+        }
+
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+        assert!(!etag_matches(&headers, &etag));
+    }
+}
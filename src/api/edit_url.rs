@@ -0,0 +1,79 @@
+use std::{str::FromStr, sync::Arc};
+
+use super::feed::encode_path;
+
+/// 文章编辑链接模板
+///
+/// `{path}` 替换为百分号编码后的源文件路径（保留 `/`），`{commit}` 替换为写入文章的同步的目标提交，
+/// 如 `https://git.example.com/me/notes/_edit/main/{path}`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditUrlTemplate(Arc<str>);
+
+impl EditUrlTemplate {
+    const PATH: &str = "{path}";
+    const COMMIT: &str = "{commit}";
+
+    /// 展开模板，没有记录源文件路径时返回 `None`
+    pub fn expand(&self, path: Option<&str>, commit: Option<&str>) -> Option<String> {
+        let url = self.0.replace(Self::PATH, &encode_path(path?));
+        Some(url.replace(Self::COMMIT, commit.unwrap_or_default()))
+    }
+}
+
+impl FromStr for EditUrlTemplate {
+    type Err = &'static str;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        if !template.contains(Self::PATH) {
+            return Err("template must contain `{path}`");
+        }
+        Ok(Self(Arc::from(template)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "https://git.example.com/me/notes/_edit/main/{path}";
+
+    #[test]
+    fn test_expand_encodes_path() {
+        let template: EditUrlTemplate = TEMPLATE.parse().unwrap();
+
+        assert_eq!(
+            template.expand(Some("posts/hello.md"), None).as_deref(),
+            Some("https://git.example.com/me/notes/_edit/main/posts/hello.md")
+        );
+        assert_eq!(
+            template
+                .expand(Some("读书 笔记/c++ 入门.md"), None)
+                .as_deref(),
+            Some(
+                "https://git.example.com/me/notes/_edit/main/\
+                 %E8%AF%BB%E4%B9%A6%20%E7%AC%94%E8%AE%B0/c%2B%2B%20%E5%85%A5%E9%97%A8.md"
+            )
+        );
+        assert_eq!(template.expand(None, Some("abc")), None);
+    }
+
+    #[test]
+    fn test_expand_commit() {
+        let template: EditUrlTemplate = "https://git.example.com/src/commit/{commit}/{path}"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            template.expand(Some("a b.md"), Some("4db7754")).as_deref(),
+            Some("https://git.example.com/src/commit/4db7754/a%20b.md")
+        );
+    }
+
+    #[test]
+    fn test_template_requires_path() {
+        assert!(
+            "https://git.example.com/me/notes"
+                .parse::<EditUrlTemplate>()
+                .is_err()
+        );
+    }
+}
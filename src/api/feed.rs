@@ -0,0 +1,401 @@
+use std::fmt::Write;
+
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{HeaderValue, header::CONTENT_TYPE},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+use chrono::{DateTime, FixedOffset};
+
+use super::{
+    Error, Querier, Result,
+    cache_tags::CacheTags,
+    conditional::last_modified,
+    query::{group_license, moved_group},
+};
+
+use crate::{
+    content::GroupLicense,
+    state::AppState,
+    storage::{ArticleSummary, DateRange, ListColumns, ListSort},
+};
+
+const RSS_CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+
+/// 配置订阅源路由。
+///
+/// 路由包括：
+/// - `GET /tags/{tag}/feed.xml`：单个标签的 RSS 订阅源
+///
+/// 单个组的订阅源 `GET /groups/{id..}/feed.xml` 与组详情共用路由，见 [`query::setup_route`](super::query::setup_route)。
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/tags/{tag}/feed.xml", get(tag_feed))
+}
+
+/// 订阅源的频道信息
+struct Channel<'a> {
+    title: &'a str,
+    /// 频道自身的地址，用于 `<atom:link rel="self">`
+    self_url: String,
+    /// 频道的许可协议，用于 `<copyright>`
+    license: Option<&'a GroupLicense>,
+}
+
+/// 获取单个组的 RSS 订阅源。
+///
+/// 组 id 可以包含 `/`，如 `/api/groups/posts/blog/feed.xml`。频道标题为组名，
+/// 条目按更新时间倒序，最多 [`AppState::feed_entries`] 条，包含已归档组的文章。
+/// 组不存在或未公开时返回 404；组 id 为设置了 `slug` 的组的目录路径时，跳转到以 slug 表示的地址。
+#[utoipa::path(
+    get,
+    path = "/api/groups/{id}/feed.xml",
+    params(("id" = String, Path, description = "组 id，可以包含 `/`")),
+    responses(
+        (status = 200, description = "RSS 2.0 订阅源", body = String, content_type = "application/rss+xml"),
+        (status = 308, description = "组 id 为组目录路径，跳转到以组 slug 表示的地址"),
+        (status = 404, description = "组不存在或未公开", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn group_feed(
+    Path(id): Path<String>,
+    State(app): State<AppState>,
+) -> Result<Response> {
+    let Some(group) = app
+        .querier()
+        .groups()
+        .await?
+        .into_iter()
+        .find(|g| g.slug == id)
+    else {
+        let slug = moved_group(&app.querier(), &id)
+            .await?
+            .ok_or(Error::NotFound)?;
+        let uri = format!("/api/groups/{}/feed.xml", encode_path(&slug));
+        return Ok(Redirect::permanent(&uri).into_response());
+    };
+
+    let articles = app
+        .querier()
+        .article_list(
+            1,
+            feed_size(&app),
+            Some(&id),
+            None,
+            None,
+            vec![],
+            None,
+            DateRange::default(),
+            ListColumns::ALL,
+            ListSort::Updated,
+        )
+        .await?;
+
+    let channel = Channel {
+        title: &group.name,
+        self_url: format!(
+            "{}/api/groups/{}/feed.xml",
+            app.site_url(),
+            encode_path(&id)
+        ),
+        license: group_license(&group, app.default_license()),
+    };
+    Ok(rss_response(&app, &channel, &articles))
+}
+
+/// 获取单个标签的 RSS 订阅源。
+///
+/// 频道标题为标签名，条目按更新时间倒序，最多 [`AppState::feed_entries`] 条，
+/// 与默认文章列表一样不包含已归档组的文章。没有公开文章使用该标签时返回 404。
+#[utoipa::path(
+    get,
+    path = "/api/tags/{tag}/feed.xml",
+    params(("tag" = String, Path, description = "标签")),
+    responses(
+        (status = 200, description = "RSS 2.0 订阅源", body = String, content_type = "application/rss+xml"),
+        (status = 404, description = "没有公开文章使用该标签", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn tag_feed(
+    Path(tag): Path<String>,
+    State(app): State<AppState>,
+) -> Result<Response> {
+    if !app.querier().tags().await?.contains(&tag) {
+        return Err(Error::NotFound);
+    }
+
+    let articles = app
+        .querier()
+        .article_list(
+            1,
+            feed_size(&app),
+            None,
+            None,
+            None,
+            vec![tag.as_str()],
+            Some(false),
+            DateRange::default(),
+            ListColumns::ALL,
+            ListSort::Updated,
+        )
+        .await?;
+
+    let channel = Channel {
+        title: &tag,
+        self_url: format!("{}/api/tags/{}/feed.xml", app.site_url(), encode_path(&tag)),
+        license: app.default_license(),
+    };
+    Ok(rss_response(&app, &channel, &articles))
+}
+
+fn feed_size(app: &AppState) -> i32 {
+    i32::try_from(app.feed_entries()).unwrap_or(i32::MAX)
+}
+
+fn rss_response(app: &AppState, channel: &Channel, articles: &[ArticleSummary]) -> Response {
+    let updated_at = articles.iter().map(|a| a.updated_at).max();
+    let xml = render_rss(
+        app.site_url(),
+        channel,
+        articles,
+        app.default_license(),
+        updated_at,
+    );
+    last_modified(
+        (
+            CacheTags::collection("feeds"),
+            [(CONTENT_TYPE, HeaderValue::from_static(RSS_CONTENT_TYPE))],
+            xml,
+        ),
+        updated_at,
+    )
+}
+
+/// 生成 RSS 2.0 文档
+///
+/// 条目的 `<link>` 和 `<guid>` 都是文章的规范地址 `{site_url}/api/articles/{slug}`，
+/// 同一篇文章出现在多个订阅源中时 guid 相同。标签生成 `<category>`，
+/// 所在组的许可协议（未声明时为 `default_license`）生成 `<atom:rights>`。
+fn render_rss(
+    site_url: &str,
+    channel: &Channel,
+    articles: &[ArticleSummary],
+    default_license: Option<&GroupLicense>,
+    updated_at: Option<DateTime<FixedOffset>>,
+) -> String {
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>"#);
+    let _ = write!(
+        xml,
+        "<title>{title}</title><link>{site}</link><description>{title}</description>",
+        title = escape(channel.title),
+        site = escape(site_url),
+    );
+    let _ = write!(
+        xml,
+        r#"<atom:link href="{}" rel="self" type="application/rss+xml"/>"#,
+        escape(&channel.self_url)
+    );
+    if let Some(license) = channel.license {
+        let _ = write!(xml, "<copyright>{}</copyright>", rights(license));
+    }
+    if let Some(updated_at) = updated_at {
+        let _ = write!(
+            xml,
+            "<lastBuildDate>{}</lastBuildDate>",
+            updated_at.to_rfc2822()
+        );
+    }
+
+    for article in articles {
+        let url = escape(&format!(
+            "{site_url}/api/articles/{}",
+            encode_path(&article.slug)
+        ));
+        let _ = write!(
+            xml,
+            r#"<item><title>{}</title><link>{url}</link><guid isPermaLink="true">{url}</guid><pubDate>{}</pubDate><description>{}</description>"#,
+            escape(&article.title),
+            article.created_at.to_rfc2822(),
+            escape(&article.summary),
+        );
+        for tag in &article.tags {
+            let _ = write!(xml, "<category>{}</category>", escape(tag));
+        }
+        if let Some(license) = group_license(&article.group, default_license) {
+            let _ = write!(xml, "<atom:rights>{}</atom:rights>", rights(license));
+        }
+        xml.push_str("</item>");
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+/// 许可协议的文本表示，协议名后附地址，已转义
+fn rights(license: &GroupLicense) -> String {
+    match &license.url {
+        Some(url) => escape(&format!("{} ({url})", license.name)),
+        None => escape(&license.name),
+    }
+}
+
+/// 转义 XML 和 HTML 的文本和属性值
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 对 URL 路径进行百分号编码，保留 `/` 和非保留字符
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            b => {
+                let _ = write!(encoded, "%{b:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use sqlx::types::Json;
+
+    use super::*;
+    use crate::storage::Group;
+
+    const SITE: &str = "https://notes.example.com";
+
+    fn article(slug: &str, tags: &[&str]) -> ArticleSummary {
+        let at = chrono::Utc
+            .with_ymd_and_hms(2024, 5, 31, 16, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        ArticleSummary {
+            slug: slug.to_string(),
+            short_id: None,
+            title: format!("{slug} & more"),
+            summary: "<p>summary</p>".to_string(),
+            summary_plain: "summary".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            group: Json(Group {
+                id: "notes".to_string(),
+                slug: "notes".to_string(),
+                name: "Notes".to_string(),
+                public: true,
+                kind: Json(serde_json::Value::Null),
+                encrypted: false,
+                author_id: None,
+                author_name: None,
+                archived: false,
+                description_html: None,
+                category_id: None,
+                category_name: None,
+                nav_order: None,
+                hidden: false,
+                license: None,
+            }),
+            comment_count: 0,
+            nonce: None,
+            updated_at: at,
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn test_render_rss_escapes_channel() {
+        let channel = Channel {
+            title: "<Rust> & \"Go\"",
+            self_url: format!("{SITE}/api/groups/{}/feed.xml", encode_path("posts/a b")),
+            license: None,
+        };
+        let xml = render_rss(SITE, &channel, &[article("intro", &["a<b"])], None, None);
+
+        assert!(xml.contains("<title>&lt;Rust&gt; &amp; &quot;Go&quot;</title>"));
+        assert!(!xml.contains("<Rust>"));
+        assert!(xml.contains(
+            r#"<atom:link href="https://notes.example.com/api/groups/posts/a%20b/feed.xml" rel="self""#
+        ));
+        assert!(xml.contains("<title>intro &amp; more</title>"));
+        assert!(xml.contains("<description>&lt;p&gt;summary&lt;/p&gt;</description>"));
+        assert!(xml.contains("<category>a&lt;b</category>"));
+        assert!(xml.contains("<pubDate>Fri, 31 May 2024 16:00:00 +0000</pubDate>"));
+    }
+
+    #[test]
+    fn test_render_rss_guid_is_canonical_url() {
+        let shared = article("intro", &["rust", "web"]);
+        let guid =
+            r#"<guid isPermaLink="true">https://notes.example.com/api/articles/intro</guid>"#;
+
+        for tag in ["rust", "web"] {
+            let channel = Channel {
+                title: tag,
+                self_url: format!("{SITE}/api/tags/{tag}/feed.xml"),
+                license: None,
+            };
+            let xml = render_rss(SITE, &channel, std::slice::from_ref(&shared), None, None);
+            assert_eq!(xml.matches(guid).count(), 1, "{tag}");
+            assert!(xml.contains("<category>rust</category><category>web</category>"));
+        }
+    }
+
+    #[test]
+    fn test_render_rss_rights() {
+        let default = "CC0-1.0".parse::<GroupLicense>().unwrap();
+        let declared = GroupLicense {
+            id: "CC-BY-4.0".to_string(),
+            name: "CC BY & 4.0".to_string(),
+            url: Some("https://creativecommons.org/licenses/by/4.0/".to_string()),
+        };
+        let mut licensed = article("licensed", &[]);
+        licensed.group.license = Some(Json(declared.clone()));
+        let articles = [licensed, article("plain", &[])];
+        let channel = Channel {
+            title: "Notes",
+            self_url: format!("{SITE}/api/groups/notes/feed.xml"),
+            license: Some(&declared),
+        };
+
+        let xml = render_rss(SITE, &channel, &articles, Some(&default), None);
+        let rights = "CC BY &amp; 4.0 (https://creativecommons.org/licenses/by/4.0/)";
+        assert!(xml.contains(&format!("<copyright>{rights}</copyright>")));
+        assert!(xml.contains(&format!("<atom:rights>{rights}</atom:rights>")));
+        assert!(xml.contains("<atom:rights>CC0-1.0</atom:rights>"));
+
+        // 都未设置时不输出
+        let channel = Channel {
+            license: None,
+            ..channel
+        };
+        let xml = render_rss(SITE, &channel, &articles[1..], None, None);
+        assert!(
+            !xml.contains("rights>") && !xml.contains("<copyright>"),
+            "{xml}"
+        );
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path("posts/blog"), "posts/blog");
+        assert_eq!(encode_path("c++ 笔记"), "c%2B%2B%20%E7%AC%94%E8%AE%B0");
+    }
+}
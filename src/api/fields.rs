@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{Error, Result};
+
+/// 文章列表可以选择的字段，与 [`ArticleMeta`](super::query::ArticleMeta) 的字段相同
+pub(super) const META_FIELDS: &[&str] = &[
+    "id",
+    "slug",
+    "title",
+    "summary",
+    "tags",
+    "group",
+    "author",
+    "category",
+    "comment_count",
+    "archived",
+    "updated_at",
+    "created_at",
+];
+
+/// 文章详情可以选择的字段，与 [`ArticleDetail`](super::query::ArticleDetail) 的字段相同
+pub(super) const DETAIL_FIELDS: &[&str] = &[
+    "id",
+    "slug",
+    "title",
+    "summary",
+    "tags",
+    "group",
+    "author",
+    "category",
+    "comment_count",
+    "archived",
+    "updated_at",
+    "created_at",
+    "content",
+    "prev",
+    "next",
+    "edit_url",
+    "canonical_url",
+    "license",
+    "social",
+    "attachments",
+];
+
+/// 稀疏字段集，响应中只包含 `?fields=` 指定的顶层字段
+///
+/// 未指定时包含所有字段。`slug` 总是包含，客户端可以据此定位文章。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct FieldSet(Option<BTreeSet<&'static str>>);
+
+impl FieldSet {
+    /// 解析逗号分隔的字段名，`valid` 为可以选择的字段
+    ///
+    /// 为空时包含所有字段，有未知字段时返回 [`Error::UnknownField`]。
+    pub(super) fn parse(fields: Option<&str>, valid: &'static [&'static str]) -> Result<Self> {
+        let names = fields
+            .into_iter()
+            .flat_map(|f| f.split(','))
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            return Ok(Self(None));
+        }
+
+        let mut set = BTreeSet::from(["slug"]);
+        for name in names {
+            let field = valid
+                .iter()
+                .find(|f| **f == name)
+                .ok_or_else(|| Error::UnknownField(name.to_string(), valid))?;
+            set.insert(*field);
+        }
+        Ok(Self(Some(set)))
+    }
+
+    /// 是否包含字段 `name`
+    pub(super) fn contains(&self, name: &str) -> bool {
+        self.0.as_ref().is_none_or(|set| set.contains(name))
+    }
+
+    /// 是否指定了字段
+    pub(super) fn is_sparse(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// 序列化 `value`，只保留选择的顶层字段
+    pub(super) fn select<T: Serialize>(&self, value: &T) -> Map<String, Value> {
+        let Ok(Value::Object(mut map)) = serde_json::to_value(value) else {
+            return Map::new();
+        };
+        map.retain(|k, _| self.contains(k));
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_field_set() {
+        let all = FieldSet::parse(None, META_FIELDS).unwrap();
+        assert!(!all.is_sparse());
+        assert!(all.contains("summary"));
+        assert_eq!(FieldSet::parse(Some(" , "), META_FIELDS).unwrap(), all);
+
+        let fields = FieldSet::parse(Some("title, updated_at"), META_FIELDS).unwrap();
+        assert!(fields.is_sparse());
+        assert!(fields.contains("slug"));
+        assert!(!fields.contains("summary"));
+
+        let value = json!({"slug": "a", "title": "t", "summary": "s", "updated_at": 1});
+        assert_eq!(
+            Value::Object(fields.select(&value)),
+            json!({"slug": "a", "title": "t", "updated_at": 1})
+        );
+
+        // 正文只能在详情中选择
+        let err = FieldSet::parse(Some("title,content"), META_FIELDS).unwrap_err();
+        assert!(matches!(&err, Error::UnknownField(name, _) if name == "content"));
+        assert!(err.to_string().contains("updated_at"));
+        assert!(FieldSet::parse(Some("content"), DETAIL_FIELDS).is_ok());
+    }
+}
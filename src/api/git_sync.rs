@@ -1,26 +1,73 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    time::{Duration, Instant},
+};
+
 use axum::{
     Json, Router,
-    extract::State,
+    body::Body,
+    extract::{Query, State},
+    http::{
+        HeaderMap, HeaderValue,
+        header::{ACCEPT, CONTENT_TYPE},
+    },
     response::{IntoResponse, Response},
     routing::post,
 };
 use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_stream::{StreamExt, wrappers::UnboundedReceiverStream};
+use tracing::Instrument;
 
 use crate::{
-    git_client::{AsSummary, GitClient},
-    git_sync::GitPushPayload,
+    cdn::{self, PurgeQueue},
+    error::Error,
+    git_client::{AsSummary, GitClient, GitFileEntry},
+    git_sync::{
+        self, BackupOptions, CommitRange, DebouncedSync, EntryProgress, GitPushPayload,
+        PendingSync, ProgressSender, QuotaItem, SyncDebouncer, SyncLogEntry, SyncReport,
+        prune_groups,
+    },
     state::AppState,
+    storage::Store,
 };
 
-use super::{PersistMode, Persistable, PushKind, Result};
+use super::{PersistMode, Persistable, PushKind, Querier, Result, request_id::request_id};
+
+/// 逐行输出同步进度的媒体类型
+pub(super) const NDJSON: &str = "application/x-ndjson";
+
+/// 仓库还没有提交时同步的文本摘要
+const NO_COMMITS: &str = "repository has no commits yet";
+
+/// 持久化遇到临时数据库错误时最多尝试的次数
+const PERSIST_ATTEMPTS: u32 = 3;
+
+/// 第一次重试前的等待时间，之后每次加倍，足以覆盖托管数据库的短暂故障切换
+const PERSIST_BACKOFF: Duration = Duration::from_secs(2);
 
 // type AppState =
 
 /// 配置 Git 仓库更新相关的路由。
 ///
-/// 将 `/repo/update` 注册为 POST 请求，用于处理 Git push 事件。
+/// 路由包括：
+/// - `POST /repo/update`：处理 Git push 事件
+/// - `POST /repo/retry-failures`：重试处理失败的文件
 pub fn setup_route() -> Router<AppState> {
-    Router::new().route("/repo/update", post(update))
+    Router::new()
+        .route("/repo/update", post(update))
+        .route("/repo/retry-failures", post(retry))
+}
+
+/// 仓库更新请求的查询参数
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UpdateParams {
+    /// 为 `true` 时重建前写入备份失败也继续重建
+    #[serde(default)]
+    force: bool,
 }
 
 /// 处理 Git push 请求。
@@ -28,42 +75,740 @@ pub fn setup_route() -> Router<AppState> {
 /// 根据 push 类型执行不同操作：
 ///
 /// - [`PushKind::Sync`]：对比两个 commit 的差异，并进行增量持久化，同时返回变更摘要。
+///   被跳过的文件、处理失败的文件和警告附加在摘要之后。
+///   有文件处理失败时其余变更照常写入，返回 `207 Multi-Status`；
+///   [`AppState::strict_sync`] 开启时任一文件失败即整体失败。
 /// - [`PushKind::Rebuild`]：获取目标 commit 的完整快照，重建数据。
 /// - 其他类型：返回 `201 Created` 表示操作成功但没有内容返回。
 ///
+/// 请求头 `Accept` 包含 `application/json` 时返回 JSON 格式的 [`SyncReport`]，否则返回文本摘要。
+/// 包含 `application/x-ndjson` 时以流的形式返回，每个文件处理完成后输出一行 [`StreamEvent::Entry`]，
+/// 失败的文件带有修改建议时随后每条建议输出一行 [`StreamEvent::Hint`]，
+/// 最后一行为 [`StreamEvent::Done`]，说明变更是否已提交。流开始后状态码固定为 200，结果以最后一行为准。
+///
+/// 同步经由 [`SyncCoordinator`](crate::git_sync::SyncCoordinator) 依次执行：重建期间再次请求重建返回 409，
+/// 增量同步排队到重建完成后执行，排队已满时返回 503，两者都带有 `Retry-After` 响应头。
+/// 数据库未迁移时返回 503，见 [`AppState::schema_ready`]。
+///
+/// 开启防抖（[`AppState::debouncer`]）时增量同步只记录 commit 范围并返回 `202 Accepted` 和 [`DeferredSync`]，
+/// 距最近一次推送超过静默期后在后台合并执行一次，结果只记录在日志中，等待中的范围见同步状态接口。
+/// 重建请求会丢弃等待中和尚未开始执行的合并范围，由重建覆盖。
+///
 /// 执行流程：
-/// 1. 打开并 fetch 仓库
-/// 2. 根据 push 类型选择增量或全量处理
-/// 3. 调用 [`GitFileEntryPersist::persist`] 将数据写入应用
-/// 4. 返回 HTTP 响应
-async fn update(State(app): State<AppState>, Json(data): Json<GitPushPayload>) -> Result<Response> {
+/// 1. 等待协调器许可
+/// 2. 打开并 fetch 仓库
+/// 3. 根据 push 类型选择增量或全量处理
+/// 4. 调用 [`GitFileEntryPersist::persist`] 将数据写入应用
+/// 5. 清理没有文章且已删除组配置文件的孤立组
+/// 6. 返回 HTTP 响应
+///
+/// 设置了 [`AppState::backup`] 时，重建在写入事务开始之前将现有数据备份到备份目录，备份文件名写入同步报告的 `backup`，
+/// 并删除超出保留数的旧备份。备份失败时不执行重建，返回 500；查询参数 `force=true` 时只记录错误，继续重建。
+///
+/// 整个处理过程位于带有请求 id 的 `sync` span 中，每个文件的处理记录在其子 span `entry` 中。
+/// 请求 id 取自请求头 `X-Request-Id` 或自动生成，随响应头 `X-Request-Id` 返回，
+/// 并写入同步报告的 `request_id` 和文本摘要的最后一行。
+///
+/// 每次执行的同步无论成功与否都写入 [`AppState::sync_log`]，推迟的推送在合并执行后写入，忽略的 ref 不写入。
+///
+/// 设置了 [`AppState::hook_allowlist`] 时，来源地址不在列表中的请求在解析请求体之前返回 403。
+///
+/// 仓库还没有提交（HEAD 未指向任何提交）且推送的提交不存在时返回 200 和空的同步报告，文本摘要为
+/// `repository has no commits yet`，不写入也不清理任何数据。向空仓库推送第一个提交（`before` 为零值）按普通同步处理。
+#[utoipa::path(
+    post,
+    path = "/api/repo/update",
+    params(UpdateParams),
+    request_body = GitPushPayload,
+    responses(
+        (status = 200, description = "同步完成，返回变更摘要；流式请求时逐行返回处理进度",
+            headers(("X-Request-Id" = String, description = "请求 id")), content(
+            (String = "text/plain"),
+            (SyncReport = "application/json"),
+            (StreamEvent = "application/x-ndjson"),
+        )),
+        (status = 201, description = "无需处理的 ref"),
+        (status = 202, description = "开启防抖，增量同步已推迟并与等待中的推送合并", content(
+            (String = "text/plain"),
+            (DeferredSync = "application/json"),
+        )),
+        (status = 207, description = "部分文件处理失败，其余变更已写入", content(
+            (String = "text/plain"),
+            (SyncReport = "application/json"),
+        )),
+        (status = 403, description = "来源地址不在 `GITNOTE_HOOK_ALLOW` 中", body = String, content_type = "text/plain"),
+        (status = 409, description = "已有重建正在执行，响应体包含其开始时间", body = String, content_type = "text/plain",
+            headers(("Retry-After" = u64, description = "建议等待的秒数"))),
+        (status = 500, description = "重建前写入备份失败，未执行重建", body = String, content_type = "text/plain"),
+        (status = 503, description = "排队的增量同步已满，或数据库未迁移（不带 `Retry-After`）", body = String, content_type = "text/plain",
+            headers(("Retry-After" = u64, description = "建议等待的秒数"))),
+    )
+)]
+pub(super) async fn update(
+    State(app): State<AppState>,
+    Query(params): Query<UpdateParams>,
+    headers: HeaderMap,
+    Json(data): Json<GitPushPayload>,
+) -> Result<Response> {
+    ensure_migrated(&app)?;
+    let request_id = request_id(&headers);
+    let span = tracing::info_span!("sync", request_id = %request_id, refname = %data.refname);
+
+    // 成功的同步在 `sync` 中记录，这里只记录返回错误的同步
+    let started = Instant::now();
+    let push_kind = data.push_kind();
+    let range = CommitRange {
+        before: data.before.clone(),
+        after: data.after.clone(),
+    };
+    let result = sync(app.clone(), headers, data, params, request_id.clone())
+        .instrument(span)
+        .await;
+    if let Err(e) = &result {
+        app.sync_log().record(SyncLogEntry::failed(
+            push_kind,
+            range,
+            started.elapsed(),
+            e,
+            Some(request_id),
+        ));
+    }
+    result
+}
+
+/// 处理推送，在 [`update`] 创建的 `sync` span 中执行
+async fn sync(
+    app: AppState,
+    headers: HeaderMap,
+    data: GitPushPayload,
+    params: UpdateParams,
+    request_id: String,
+) -> Result<Response> {
     tracing::debug!(data = ?data, "git push paylaod");
 
-    let ref_kind = data.push_kind();
-    match ref_kind {
+    let started = Instant::now();
+    let push_kind = data.push_kind();
+    let (_permit, repo, entries, mode) = match push_kind {
         PushKind::Sync => {
-            let repo = GitClient::open(app.repo_path())?;
-            let entries = repo.diff_commits(&data.before, &data.after)?;
-            entries
-                .persist(
-                    app.storage(),
-                    app.renderer(),
-                    &repo,
-                    PersistMode::Incremental,
-                )
-                .await?;
-            Ok((StatusCode::OK, entries.as_summary()).into_response())
+            if let Some(debouncer) = app.debouncer() {
+                return Ok(defer(&app, debouncer, &headers, &data, request_id));
+            }
+            let permit = app.coordinator().sync().await?;
+            let repo = open_repo(&app)?;
+            let entries = repo.diff_commits(&data.before, &data.after).await?;
+            (permit, repo, entries, sync_mode(&app))
         }
 
         PushKind::Rebuild => {
-            let repo = GitClient::open(app.repo_path())?;
-            let entries = repo.snapshot(&data.after)?;
+            if let Some(cancelled) = app.debouncer().and_then(SyncDebouncer::cancel) {
+                tracing::info!(
+                    before = %cancelled.before,
+                    after = %cancelled.after,
+                    pushes = cancelled.pushes,
+                    "pending sync superseded by rebuild"
+                );
+            }
+            let permit = app.coordinator().rebuild().await?;
+            let repo = open_repo(&app)?;
+            let entries = repo.snapshot(&data.after).await?;
+            (permit, repo, entries, PersistMode::ResetAll)
+        }
+        PushKind::Ignore => return Ok(StatusCode::CREATED.into_response()),
+    };
+
+    if let Some(report) = no_commits(&repo, &entries, &data, started).await? {
+        let text = format!("{NO_COMMITS}\nrequest-id {request_id}");
+        let report = report.with_request_id(request_id);
+        app.sync_log()
+            .record(SyncLogEntry::finished(&report, &text));
+        if accepts(&headers, NDJSON) {
+            let line = StreamEvent::done(Ok(report)).to_line();
+            return Ok(([(CONTENT_TYPE, HeaderValue::from_static(NDJSON))], line).into_response());
+        }
+        return Ok(respond(&headers, &report, text));
+    }
+
+    let backup = match (mode, app.backup()) {
+        (PersistMode::ResetAll, Some(options)) => backup(&app, options, params.force).await?,
+        _ => None,
+    };
+
+    if accepts(&headers, NDJSON) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = async move {
+            // 许可随任务持有，客户端断开后同步照常完成
+            let _permit = _permit;
+            let (progress, mut events) = mpsc::unbounded_channel::<EntryProgress>();
+            let forward = {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    while let Some(mut entry) = events.recv().await {
+                        let path = entry.path.clone();
+                        let suggestions = std::mem::take(&mut entry.suggestions);
+                        let _ = tx.send(StreamEvent::Entry(entry).to_line());
+                        for suggestion in suggestions {
+                            let path = path.clone();
+                            let _ = tx.send(StreamEvent::Hint { path, suggestion }.to_line());
+                        }
+                    }
+                })
+            };
+
+            let result = run(&app, &repo, &entries, &data, mode, started, Some(&progress))
+                .await
+                .map(|report| SyncReport {
+                    backup,
+                    ..report.with_request_id(request_id.clone())
+                });
+            app.sync_log().record(match &result {
+                Ok(report) => SyncLogEntry::finished(report, &summary(&entries, report)),
+                Err(e) => SyncLogEntry::failed(
+                    push_kind,
+                    CommitRange {
+                        before: data.before.clone(),
+                        after: data.after.clone(),
+                    },
+                    started.elapsed(),
+                    e,
+                    Some(request_id),
+                ),
+            });
+            drop(progress);
+            let _ = forward.await;
+            let _ = tx.send(StreamEvent::done(result).to_line());
+        };
+        tokio::spawn(task.instrument(tracing::Span::current()));
+
+        let body = Body::from_stream(UnboundedReceiverStream::new(rx).map(Ok::<_, Infallible>));
+        return Ok(([(CONTENT_TYPE, HeaderValue::from_static(NDJSON))], body).into_response());
+    }
+
+    let report = SyncReport {
+        backup,
+        ..run(&app, &repo, &entries, &data, mode, started, None)
+            .await?
+            .with_request_id(request_id)
+    };
+    let text = summary(&entries, &report);
+    app.sync_log()
+        .record(SyncLogEntry::finished(&report, &text));
+    Ok(respond(&headers, &report, text))
+}
+
+/// 重建前备份现有数据，返回备份文件名
+///
+/// 备份失败时返回 [`Error::Backup`]；`force` 为 `true` 时只记录错误，返回 `None`。
+async fn backup(app: &AppState, options: &BackupOptions, force: bool) -> Result<Option<String>> {
+    match git_sync::write_backup(&app.storage(), options).await {
+        Ok(backup) => {
+            tracing::info!(
+                file = %backup.file,
+                groups = backup.counts.groups,
+                articles = backup.counts.articles,
+                "backup written before rebuild"
+            );
+            Ok(Some(backup.file))
+        }
+        Err(e) if force => {
+            tracing::warn!(error = %e, "backup failed, rebuilding anyway");
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 增量同步的持久化模式
+fn sync_mode(app: &AppState) -> PersistMode {
+    if app.strict_sync() {
+        PersistMode::Strict
+    } else {
+        PersistMode::Incremental
+    }
+}
+
+/// 开启防抖时增量同步的响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DeferredSync {
+    /// 合并后等待执行的范围
+    pub pending: PendingSync,
+    /// 静默期秒数
+    pub quiet_secs: u64,
+    pub request_id: String,
+}
+
+/// 记录推送的范围并返回 `202 Accepted`，开始新的范围时在后台启动 [`run_debounced`]
+fn defer(
+    app: &AppState,
+    debouncer: &SyncDebouncer,
+    headers: &HeaderMap,
+    data: &GitPushPayload,
+    request_id: String,
+) -> Response {
+    let (pending, started) = debouncer.push(&data.before, &data.after);
+    tracing::info!(
+        before = %pending.before,
+        after = %pending.after,
+        pushes = pending.pushes,
+        "sync deferred"
+    );
+    if started {
+        let task = run_debounced(app.clone(), debouncer.clone());
+        tokio::spawn(task.instrument(tracing::Span::current()));
+    }
+
+    let deferred = DeferredSync {
+        quiet_secs: debouncer.quiet().as_secs(),
+        pending,
+        request_id,
+    };
+    if accepts(headers, "application/json") {
+        (StatusCode::ACCEPTED, Json(deferred)).into_response()
+    } else {
+        let text = format!(
+            "deferred {}..{} ({} pushes), runs after {}s without new pushes\nrequest-id {}",
+            deferred.pending.before,
+            deferred.pending.after,
+            deferred.pending.pushes,
+            deferred.quiet_secs,
+            deferred.request_id,
+        );
+        (StatusCode::ACCEPTED, text).into_response()
+    }
+}
+
+/// 等待静默期结束后执行合并的增量同步，在开始该范围的推送的 `sync` span 中执行
+///
+/// 结果写入 [`AppState::sync_log`]，被重建取代时不写入。
+async fn run_debounced(app: AppState, debouncer: SyncDebouncer) {
+    let Some(pending) = debouncer.wait().await else {
+        return;
+    };
+    let started = Instant::now();
+    let range = &pending.range;
+    match sync_debounced(&app, &debouncer, &pending).await {
+        Ok(Some(report)) => tracing::info!(
+            before = %range.before,
+            after = %range.after,
+            pushes = range.pushes,
+            summary = %report.as_summary(),
+            "debounced sync finished"
+        ),
+        Ok(None) => tracing::info!(
+            before = %range.before,
+            after = %range.after,
+            "debounced sync superseded by rebuild"
+        ),
+        Err(e) => {
+            tracing::error!(
+                error = %e,
+                before = %range.before,
+                after = %range.after,
+                "debounced sync failed"
+            );
+            app.sync_log().record(SyncLogEntry::failed(
+                PushKind::Sync,
+                CommitRange {
+                    before: range.before.clone(),
+                    after: range.after.clone(),
+                },
+                started.elapsed(),
+                e,
+                None,
+            ));
+        }
+    }
+}
+
+/// 排队执行取出的范围，等待期间被重建取代时返回 `None`
+async fn sync_debounced(
+    app: &AppState,
+    debouncer: &SyncDebouncer,
+    pending: &DebouncedSync,
+) -> Result<Option<SyncReport>> {
+    let _permit = app.coordinator().sync().await?;
+    if !debouncer.is_current(pending) {
+        return Ok(None);
+    }
+
+    let started = Instant::now();
+    let data = GitPushPayload {
+        refname: "refs/heads/main".to_string(),
+        before: pending.range.before.clone(),
+        after: pending.range.after.clone(),
+    };
+    let repo = open_repo(app)?;
+    let entries = repo.diff_commits(&data.before, &data.after).await?;
+    if let Some(report) = no_commits(&repo, &entries, &data, started).await? {
+        app.sync_log()
+            .record(SyncLogEntry::finished(&report, NO_COMMITS));
+        return Ok(Some(report));
+    }
+    let report = run(app, &repo, &entries, &data, sync_mode(app), started, None).await?;
+    app.sync_log()
+        .record(SyncLogEntry::finished(&report, &summary(&entries, &report)));
+    Ok(Some(report))
+}
+
+/// 没有变更且仓库还没有提交时返回空的同步报告，此时推送的提交不存在，不能按提交清理组或计算指纹
+async fn no_commits(
+    repo: &GitClient,
+    entries: &[GitFileEntry],
+    data: &GitPushPayload,
+    started: Instant,
+) -> Result<Option<SyncReport>> {
+    if !entries.is_empty() || repo.head().await?.is_some() {
+        return Ok(None);
+    }
+    tracing::info!(after = %data.after, "{NO_COMMITS}");
+    Ok(Some(SyncReport::default().finish(
+        data.push_kind(),
+        data.before.clone(),
+        data.after.clone(),
+        started,
+    )))
+}
+
+/// 持久化变更、清理孤立组、记录内容指纹、检查组的配额、排队清除 CDN 缓存和发送 webmention 并生成同步报告
+///
+/// 持久化遇到临时数据库错误（见 [`Error::is_transient`]）时按指数退避重新执行，最多尝试 [`PERSIST_ATTEMPTS`] 次，
+/// 重试次数记录到 [`SyncReport::retries`]。持久化在一个事务中提交，失败的尝试不会留下部分写入，重放是安全的；
+/// 重试前内容指纹已记录为 `after` 且与开始时不同，说明并发的同步已写入同一提交，此时不再重放。
+/// 重放时已发出的进度事件会再次发出。其他错误立即返回。
+async fn run<E>(
+    app: &AppState,
+    repo: &GitClient,
+    entries: &E,
+    data: &GitPushPayload,
+    mode: PersistMode,
+    started: Instant,
+    progress: Option<&ProgressSender>,
+) -> Result<SyncReport>
+where
+    E: Persistable<Error = Error> + AsRef<[GitFileEntry]>,
+{
+    let synced = synced_commit(app).await;
+    let mut retries = 0;
+    let mut report = loop {
+        let result = entries
+            .persist_with_progress(
+                app.storage(),
+                app.renderer(),
+                repo,
+                &data.after,
+                mode,
+                app.limits(),
+                progress,
+            )
+            .await;
+        match result {
+            Err(e) if e.is_transient() && retries + 1 < PERSIST_ATTEMPTS => {
+                let delay = PERSIST_BACKOFF * 2u32.pow(retries);
+                retries += 1;
+                tracing::warn!(error = %e, retries, ?delay, "transient database error, retrying persist");
+                tokio::time::sleep(delay).await;
+
+                let head = synced_commit(app).await;
+                if head != synced && head.as_deref() == Some(data.after.as_str()) {
+                    tracing::info!(after = %data.after, "commit already applied, skipping replay");
+                    break SyncReport::default();
+                }
+            }
+            result => break result?,
+        }
+    };
+    report.retries = retries;
+    report.groups_removed = prune_groups(&app.storage(), repo, &data.after).await?;
+    record_fingerprint(app, &data.after).await;
+    report.over_quota = over_quota(app).await;
+    if let Some(queue) = app.cdn_purge() {
+        purge_cdn(app, queue, entries.as_ref()).await;
+    }
+    // 重建会把全部文章视为新增，只在增量同步后发送
+    if data.push_kind() == PushKind::Sync
+        && let Some(queue) = app.webmentions()
+    {
+        queue.enqueue(
+            report
+                .added
+                .iter()
+                .chain(&report.modified)
+                .cloned()
+                .collect(),
+        );
+    }
+
+    Ok(report.finish(
+        data.push_kind(),
+        data.before.clone(),
+        data.after.clone(),
+        started,
+    ))
+}
+
+/// 流式同步中的一行
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum StreamEvent {
+    /// 单个文件处理完成
+    Entry(EntryProgress),
+    /// 处理失败的文件的一条修改建议，紧跟在该文件的 [`StreamEvent::Entry`] 之后
+    Hint { path: String, suggestion: String },
+    /// 同步结束，总是最后一行
+    Done {
+        outcome: StreamOutcome,
+        /// 同步报告，变更未提交时为 `null`
+        report: Option<Box<SyncReport>>,
+        /// 回滚的原因
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+/// 流式同步的最终结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamOutcome {
+    /// 全部变更已提交
+    Committed,
+    /// 部分文件处理失败，其余变更已提交
+    Partial,
+    /// 变更全部回滚，此前输出的文件结果均未生效
+    RolledBack,
+}
+
+impl StreamEvent {
+    fn done(result: Result<SyncReport>) -> Self {
+        match result {
+            Ok(report) => Self::Done {
+                outcome: if report.failed.is_empty() {
+                    StreamOutcome::Committed
+                } else {
+                    StreamOutcome::Partial
+                },
+                report: Some(Box::new(report)),
+                error: None,
+            },
+            Err(e) => {
+                tracing::error!(error = %e, "streamed sync rolled back");
+                Self::Done {
+                    outcome: StreamOutcome::RolledBack,
+                    report: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    /// 序列化为以换行结尾的一行 JSON
+    fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+/// 重试处理失败的文件。
+///
+/// 与增量同步一同排队执行，从最近一次增量同步的目标提交读取失败记录中的文件并重新持久化，
+/// 返回格式与 [`update`] 相同，文本摘要中每个重试成功的文件一行。没有失败记录时返回空报告。
+/// 完成后按失败记录的提交重新记录内容指纹。
+#[utoipa::path(
+    post,
+    path = "/api/repo/retry-failures",
+    responses(
+        (status = 200, description = "重试完成，没有剩余的失败文件", content(
+            (String = "text/plain"),
+            (SyncReport = "application/json"),
+        )),
+        (status = 207, description = "仍有文件处理失败", content(
+            (String = "text/plain"),
+            (SyncReport = "application/json"),
+        )),
+        (status = 403, description = "来源地址不在 `GITNOTE_HOOK_ALLOW` 中", body = String, content_type = "text/plain"),
+        (status = 409, description = "已有重建正在执行", body = String, content_type = "text/plain",
+            headers(("Retry-After" = u64, description = "建议等待的秒数"))),
+        (status = 503, description = "排队的增量同步已满，或数据库未迁移（不带 `Retry-After`）", body = String, content_type = "text/plain",
+            headers(("Retry-After" = u64, description = "建议等待的秒数"))),
+    )
+)]
+pub(super) async fn retry(State(app): State<AppState>, headers: HeaderMap) -> Result<Response> {
+    ensure_migrated(&app)?;
+    let started = Instant::now();
+    let _permit = app.coordinator().sync().await?;
+    let repo = open_repo(&app)?;
+
+    let mut report =
+        git_sync::retry_failures(app.storage(), app.renderer(), &repo, app.limits()).await?;
+    report.duration_ms = started.elapsed().as_millis() as u64;
+
+    // 失败记录与内容指纹对应同一个提交
+    if let Some(state) = app.querier().sync_state().await? {
+        record_fingerprint(&app, &state.commit_id).await;
+    }
+
+    let text = report
+        .added
+        .iter()
+        .chain(&report.modified)
+        .chain(&report.removed)
+        .map(|path| format!("retried {path}"))
+        .chain((!report.is_empty()).then(|| report.as_summary()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(respond(&headers, &report, text))
+}
+
+/// 内容指纹对应的提交，查询失败时为 `None`
+async fn synced_commit(app: &AppState) -> Option<String> {
+    match app.querier().sync_state().await {
+        Ok(state) => state.map(|s| s.commit_id),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to read sync state");
+            None
+        }
+    }
+}
+
+/// 按同步后的数据重新计算并记录内容指纹
+///
+/// 变更已经提交，计算失败时只记录警告，不影响同步结果。
+async fn record_fingerprint(app: &AppState, commit: &str) {
+    match git_sync::record_fingerprint(app.storage(), commit).await {
+        Ok(fingerprint) => tracing::info!(commit, %fingerprint, "content fingerprint recorded"),
+        Err(e) => tracing::warn!(error = %e, "failed to record content fingerprint"),
+    }
+}
+
+/// 查询超出配额的组，查询失败时只记录警告，不影响已提交的同步
+async fn over_quota(app: &AppState) -> Vec<QuotaItem> {
+    match app.querier().group_stats().await {
+        Ok(stats) => stats.iter().filter_map(QuotaItem::from_stats).collect(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to query group stats");
+            Vec::new()
+        }
+    }
+}
+
+/// 按同步的条目排队清除 CDN 缓存，见 [`cdn::purge_tags`]
+///
+/// 读取组设置失败时只清除汇总内容的标签。
+async fn purge_cdn(app: &AppState, queue: &PurgeQueue, entries: &[GitFileEntry]) {
+    let strategies = match app.storage().group_settings().await {
+        Ok(settings) => settings
+            .into_iter()
+            .map(|(id, s)| (id, s.slug_strategy))
+            .collect(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to query group settings for cdn purge");
+            queue.enqueue(cdn::purge_tags(&[], &HashMap::new()));
+            return;
+        }
+    };
+    queue.enqueue(cdn::purge_tags(entries, &strategies));
+}
+
+/// 数据库未迁移时拒绝写入
+pub(super) fn ensure_migrated(app: &AppState) -> Result<()> {
+    if app.schema_ready() {
+        Ok(())
+    } else {
+        Err(Error::NotMigrated)
+    }
+}
+
+/// 打开应用的仓库
+pub(super) fn open_repo(app: &AppState) -> Result<GitClient> {
+    Ok(GitClient::open(app.repo_path())?
+        .with_classifier(app.classifier().clone())
+        .with_timezone(app.timezone()))
+}
+
+/// 按 `Accept` 返回 JSON 报告或文本摘要，有文件处理失败时状态码为 `207 Multi-Status`
+fn respond(headers: &HeaderMap, report: &SyncReport, text: String) -> Response {
+    let status = if report.failed.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+
+    if accepts(headers, "application/json") {
+        (status, Json(report)).into_response()
+    } else {
+        (status, text).into_response()
+    }
+}
+
+/// 请求头 `Accept` 是否包含 `mime`
+fn accepts(headers: &HeaderMap, mime: &str) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|t| t.split(';').next().map(str::trim) == Some(mime))
+}
+
+/// 拼接变更摘要和同步报告，有请求 id 时最后一行为 `request-id <id>`
+fn summary(entries: &impl AsSummary, report: &SyncReport) -> String {
+    let mut text = if report.is_empty() {
+        entries.as_summary()
+    } else {
+        format!("{}\n{}", entries.as_summary(), report.as_summary())
+    };
+    if let Some(id) = &report.request_id {
+        text.push_str(&format!("\nrequest-id {id}"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts(&headers, "application/json"));
+
+        headers.insert(ACCEPT, HeaderValue::from_static("text/plain"));
+        assert!(!accepts(&headers, "application/json"));
+
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/html, application/json;q=0.9"),
+        );
+        assert!(accepts(&headers, "application/json"));
+        assert!(!accepts(&headers, NDJSON));
+
+        headers.insert(ACCEPT, HeaderValue::from_static(NDJSON));
+        assert!(accepts(&headers, NDJSON));
+    }
+
+    #[test]
+    fn test_stream_done_line() {
+        let line = StreamEvent::done(Err(Error::Custom("boom"))).to_line();
+        assert!(line.ends_with('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], "done");
+        assert_eq!(value["outcome"], "rolled_back");
+        assert_eq!(value["error"], "boom");
+        assert!(value["report"].is_null());
+    }
 
-            entries
-                .persist(app.storage(), app.renderer(), &repo, PersistMode::ResetAll)
-                .await?;
-            Ok((StatusCode::OK, entries.as_summary()).into_response())
+    #[test]
+    fn test_stream_hint_line() {
+        let line = StreamEvent::Hint {
+            path: "notes/a.md".to_string(),
+            suggestion: "add `title:` to the front matter".to_string(),
         }
-        _ => Ok(StatusCode::CREATED.into_response()),
+        .to_line();
+        assert_eq!(
+            line,
+            "{\"event\":\"hint\",\"path\":\"notes/a.md\",\"suggestion\":\"add `title:` to the front matter\"}\n"
+        );
     }
 }
@@ -0,0 +1,100 @@
+use std::{
+    future::Future,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use axum::{Json, Router, extract::State, routing::get};
+use serde::Serialize;
+use tokio::{sync::Mutex, time::Instant};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    error::Result,
+    state::AppState,
+    storage::{Querier, SiteCounts, TimedQuerier},
+};
+
+use super::ApiDoc;
+
+/// API 索引中计数的缓存时间
+const INDEX_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// OpenAPI 文档中的接口路径，按路径排序
+static ENDPOINTS: LazyLock<Vec<String>> =
+    LazyLock::new(|| ApiDoc::openapi().paths.paths.into_keys().collect());
+
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/", get(index))
+}
+
+/// API 索引。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiIndex {
+    #[schema(example = "gitnote")]
+    pub name: &'static str,
+    #[schema(example = "0.3.3")]
+    pub version: &'static str,
+    /// 接口路径模板，与 `/api/openapi.json` 中的路径相同
+    pub endpoints: &'static [String],
+    /// 公开文章数
+    pub articles: i64,
+    /// 公开的组数
+    pub groups: i64,
+    /// 标签数
+    pub tags: i64,
+    /// 最近一次同步的时间，Unix 毫秒时间戳，尚未同步过时为 `null`
+    #[schema(example = 1717171200000_i64)]
+    pub last_synced_at: Option<i64>,
+}
+
+/// 获取 API 索引。
+///
+/// 返回服务名称、版本、接口列表和公开内容的数量，不需要认证，用于快速检查服务是否正常。
+/// 数量在内存中缓存 60 秒，频繁轮询不会每次查询数据库。
+#[utoipa::path(
+    get,
+    path = "/api",
+    responses((status = 200, description = "API 索引", body = ApiIndex))
+)]
+pub(super) async fn index(
+    State(querier): State<TimedQuerier>,
+    State(cache): State<IndexCache>,
+) -> Result<Json<ApiIndex>> {
+    let counts = cache.get_or_load(querier.site_counts()).await?;
+
+    Ok(Json(ApiIndex {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        endpoints: &ENDPOINTS,
+        articles: counts.articles,
+        groups: counts.groups,
+        tags: counts.tags,
+        last_synced_at: counts.synced_at.map(|t| t.timestamp_millis()),
+    }))
+}
+
+/// API 索引中数量的缓存
+///
+/// 克隆后共享同一份缓存。过期后第一个请求重新查询，其余请求等待它的结果，查询失败时不缓存。
+#[derive(Debug, Clone, Default)]
+pub struct IndexCache(Arc<Mutex<Option<(Instant, SiteCounts)>>>);
+
+impl IndexCache {
+    /// 返回未过期的缓存，否则执行 `load` 并缓存结果
+    async fn get_or_load(
+        &self,
+        load: impl Future<Output = Result<SiteCounts>>,
+    ) -> Result<SiteCounts> {
+        let mut cached = self.0.lock().await;
+        if let Some((at, counts)) = cached.as_ref()
+            && at.elapsed() < INDEX_CACHE_TTL
+        {
+            return Ok(counts.clone());
+        }
+
+        let counts = load.await?;
+        *cached = Some((Instant::now(), counts.clone()));
+        Ok(counts)
+    }
+}
@@ -0,0 +1,277 @@
+use axum::{Json, Router, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+
+use crate::{
+    content::TagNode,
+    digest::{Digest, DigestArticle, DigestCategory},
+    git_client::{ArchiveRemoval, DiffStat, RepoStats},
+    git_sync::{
+        ArticlePreview, CommitRange, DiffItem, EntryOutcome, EntryProgress, GitPushPayload,
+        PendingSync, PreviewFrontMatter, PushKind, QuotaItem, ReportItem, RetentionItem,
+        SlowRenderItem, SyncLogEntry, SyncOutcome, SyncReport, SyncState, SyncStatus,
+        VisibilityItem,
+    },
+    render::{CircuitState, CircuitStatus},
+    state::AppState,
+};
+
+use super::{admin, archives, feed, git_sync, index, query, repo_stats, timetravel, version};
+
+/// 公开 API 的 OpenAPI 描述。
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "GitNote API"),
+    paths(
+        index::index,
+        version::version,
+        query::articles_list,
+        query::article,
+        query::article_by_short_id,
+        query::resolve,
+        query::permalink,
+        query::tag_list,
+        query::group_list,
+        query::slug_list,
+        query::title_index,
+        query::group,
+        query::author_list,
+        query::category,
+        git_sync::update,
+        git_sync::retry,
+        repo_stats::repo_stats,
+        timetravel::timetravel_list,
+        timetravel::timetravel_article,
+        admin::link_report,
+        admin::sync_status,
+        admin::sync_log,
+        admin::status,
+        admin::stale_render,
+        admin::orphan_articles,
+        admin::pruned_articles,
+        admin::render_report,
+        admin::duplicates,
+        admin::preview,
+        admin::diff_debug,
+        admin::digest_preview,
+        admin::delete_archive,
+        admin::rerender_stale,
+        archives::archive_changes,
+        archives::archived_content,
+        feed::group_feed,
+        feed::tag_feed,
+    ),
+    components(schemas(
+        index::ApiIndex,
+        version::VersionInfo,
+        version::BuildFeatures,
+        query::ArticleMeta,
+        query::ArticleDetail,
+        query::Neighbor,
+        query::Group,
+        query::NavGroup,
+        query::SlugEntry,
+        query::TitleIndexEntry,
+        query::TitleBucket,
+        query::GroupDetail,
+        query::GroupSize,
+        query::Author,
+        query::AuthorStats,
+        query::Category,
+        query::License,
+        query::SocialCard,
+        query::Attachment,
+        query::CategoryGroup,
+        query::CategoryDetail,
+        query::RedirectHint,
+        query::SummaryFormat,
+        query::ArticleSort,
+        query::TagList,
+        TagNode,
+        GitPushPayload,
+        PushKind,
+        SyncReport,
+        ReportItem,
+        DiffItem,
+        VisibilityItem,
+        RetentionItem,
+        QuotaItem,
+        SlowRenderItem,
+        DiffStat,
+        RepoStats,
+        ArchiveRemoval,
+        timetravel::TimeTravelMeta,
+        timetravel::TimeTravelDetail,
+        CommitRange,
+        git_sync::StreamEvent,
+        git_sync::StreamOutcome,
+        git_sync::DeferredSync,
+        PendingSync,
+        EntryProgress,
+        EntryOutcome,
+        SyncStatus,
+        SyncState,
+        SyncLogEntry,
+        SyncOutcome,
+        admin::ArticleLinks,
+        admin::BrokenLink,
+        admin::ContentStatus,
+        admin::StaleArticle,
+        admin::OrphanArticle,
+        admin::PrunedArticle,
+        admin::RenderReportItem,
+        admin::RenderReportSort,
+        admin::DuplicateCluster,
+        admin::DuplicateArticle,
+        admin::PreviewRequest,
+        admin::DiffDebugRequest,
+        admin::DiffDecision,
+        admin::DiffChange,
+        admin::DigestPreviewRequest,
+        admin::DigestPreview,
+        admin::LastDigest,
+        Digest,
+        DigestCategory,
+        DigestArticle,
+        ArticlePreview,
+        PreviewFrontMatter,
+        CircuitStatus,
+        CircuitState,
+        archives::ArchiveChanges,
+        archives::GroupChanges,
+    )),
+    modifiers(&BearerAuth)
+)]
+pub struct ApiDoc;
+
+/// 注册管理接口使用的 bearer token 认证方式
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// 配置 OpenAPI 文档路由。
+///
+/// 将 `/openapi.json` 注册为 GET 请求，返回生成的 OpenAPI 文档。
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/openapi.json", get(openapi))
+}
+
+/// 返回 OpenAPI JSON 文档。
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_openapi_document() {
+        let json = ApiDoc::openapi().to_json().unwrap();
+        let doc: Value = serde_json::from_str(&json).unwrap();
+
+        for path in [
+            "/api",
+            "/api/articles",
+            "/api/articles/index",
+            "/api/articles/{path}",
+            "/api/a/{short_id}",
+            "/api/resolve/{path}",
+            "/api/permalink/{path}",
+            "/api/tags",
+            "/api/groups",
+            "/api/groups/{id}",
+            "/api/categories/{id}",
+            "/api/slugs",
+            "/api/version",
+            "/api/authors",
+            "/api/repo/update",
+            "/api/repo/retry-failures",
+            "/api/repo/stats",
+            "/api/timetravel/{commit}/articles",
+            "/api/timetravel/{commit}/articles/{path}",
+            "/api/admin/link-report",
+            "/api/admin/sync-status",
+            "/api/admin/sync-log",
+            "/api/status",
+            "/api/admin/stale-render",
+            "/api/admin/rerender-stale",
+            "/api/admin/orphan-articles",
+            "/api/admin/pruned-articles",
+            "/api/admin/render-report",
+            "/api/admin/duplicates",
+            "/api/admin/preview",
+            "/api/admin/diff-debug",
+            "/api/admin/digest/preview",
+            "/api/admin/archives/{tag}",
+            "/api/archives/{tag}/changes",
+            "/api/archives/content",
+            "/api/groups/{id}/feed.xml",
+            "/api/tags/{tag}/feed.xml",
+        ] {
+            assert!(doc["paths"].get(path).is_some(), "missing path {path}");
+        }
+
+        // 每个 200 响应都必须带有 schema
+        for (path, item) in doc["paths"].as_object().unwrap() {
+            for (method, op) in item.as_object().unwrap() {
+                let Some(ok) = op["responses"].get("200") else {
+                    continue;
+                };
+                let content = ok["content"].as_object().unwrap();
+                assert!(
+                    content.values().all(|c| c.get("schema").is_some()),
+                    "{method} {path} 200 response has no schema"
+                );
+            }
+        }
+
+        // 结构体响应通过引用指向 components
+        let schema = &doc["paths"]["/api/articles/{path}"]["get"]["responses"]["200"]["content"]["application/json"]
+            ["schema"];
+        assert_eq!(schema["$ref"], "#/components/schemas/ArticleDetail");
+
+        let schema = &doc["paths"]["/api/articles"]["get"]["responses"]["200"]["content"]["application/json"]
+            ["schema"];
+        assert_eq!(schema["items"]["$ref"], "#/components/schemas/ArticleMeta");
+
+        // 同步接口按 Accept 返回文本、JSON 或 NDJSON 流
+        let content = &doc["paths"]["/api/repo/update"]["post"]["responses"]["200"]["content"];
+        assert!(content.get("text/plain").is_some());
+        assert_eq!(
+            content["application/json"]["schema"]["$ref"],
+            "#/components/schemas/SyncReport"
+        );
+        assert_eq!(
+            content["application/x-ndjson"]["schema"]["$ref"],
+            "#/components/schemas/StreamEvent"
+        );
+
+        // 同步记录需要 bearer token
+        assert_eq!(
+            doc["paths"]["/api/admin/sync-log"]["get"]["security"][0]["bearer"],
+            serde_json::json!([])
+        );
+        assert_eq!(
+            doc["components"]["securitySchemes"]["bearer"]["scheme"],
+            "bearer"
+        );
+
+        // 时间戳为毫秒整数
+        let meta = &doc["components"]["schemas"]["ArticleMeta"]["properties"];
+        assert_eq!(meta["updated_at"]["type"], "integer");
+        assert_eq!(meta["updated_at"]["format"], "int64");
+    }
+}
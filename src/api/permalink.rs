@@ -0,0 +1,260 @@
+use std::{str::FromStr, sync::Arc};
+
+use chrono::{DateTime, Datelike, FixedOffset};
+
+/// 文章固定链接格式
+///
+/// 以 `/` 分隔的每一段为字面量或占位符 `:year`、`:month`、`:day`、`:group`、`:slug` 之一，
+/// 如 `/:year/:month/:slug`，用于兼容迁移前其他博客程序的文章地址。
+/// 必须包含 `:slug`，每个占位符最多出现一次，`:day` 需要 `:month`，`:month` 需要 `:year`。
+/// 组 slug 可以包含 `/`，`:group` 匹配多出的所有段，其余每段匹配路径中的一段。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermalinkPattern(Arc<[Segment]>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Group,
+    Slug,
+}
+
+/// 按 [`PermalinkPattern`] 解析出的路径，格式中没有的部分为 [`None`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permalink<'a> {
+    pub year: Option<i32>,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+    pub group: Option<&'a str>,
+    pub slug: &'a str,
+}
+
+impl PermalinkPattern {
+    /// 按格式解析 `path`，忽略首尾的 `/`
+    ///
+    /// 段数不足、字面量不一致、年月日不是合法的数字或有空段时返回 [`None`]。
+    pub fn parse<'a>(&self, path: &'a str) -> Option<Permalink<'a>> {
+        let parts = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        if parts.iter().any(|p| p.is_empty()) {
+            return None;
+        }
+        let has_group = self.0.contains(&Segment::Group);
+        let extra = parts.len().checked_sub(self.0.len())?;
+        if extra > 0 && !has_group {
+            return None;
+        }
+
+        let mut link = Permalink {
+            year: None,
+            month: None,
+            day: None,
+            group: None,
+            slug: "",
+        };
+        let mut at = 0;
+        for segment in self.0.iter() {
+            let part = parts[at];
+            match segment {
+                Segment::Literal(literal) if literal == part => {}
+                Segment::Literal(_) => return None,
+                Segment::Year => link.year = Some(number(part, 4, 4)?),
+                Segment::Month => {
+                    link.month = Some(number(part, 1, 2).filter(|m| (1..=12).contains(m))?)
+                }
+                Segment::Day => {
+                    link.day = Some(number(part, 1, 2).filter(|d| (1..=31).contains(d))?)
+                }
+                Segment::Slug => link.slug = part,
+                Segment::Group => {
+                    // 组 slug 占用多出的段，取原路径中对应的部分，保留其中的 `/`
+                    let start = parts[..at].iter().map(|p| p.len() + 1).sum::<usize>();
+                    let len = parts[at..=at + extra]
+                        .iter()
+                        .map(|p| p.len() + 1)
+                        .sum::<usize>()
+                        - 1;
+                    link.group = Some(&path.trim_matches('/')[start..start + len]);
+                    at += extra;
+                }
+            }
+            at += 1;
+        }
+
+        Some(link)
+    }
+}
+
+impl Permalink<'_> {
+    /// 路径中的年月日是否与 `created_at` 所在时区的日期一致，路径中没有的部分不比较
+    pub fn matches_date(&self, created_at: &DateTime<FixedOffset>) -> bool {
+        self.year.is_none_or(|y| y == created_at.year())
+            && self.month.is_none_or(|m| m == created_at.month())
+            && self.day.is_none_or(|d| d == created_at.day())
+    }
+}
+
+/// 解析 `min` 到 `max` 位的十进制数字
+fn number<T: FromStr>(part: &str, min: usize, max: usize) -> Option<T> {
+    ((min..=max).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| part.parse().ok())
+        .flatten()
+}
+
+impl FromStr for PermalinkPattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let pattern = pattern.trim_matches('/');
+        if pattern.is_empty() {
+            return Err("pattern must contain `:slug`".to_string());
+        }
+
+        let mut segments = Vec::new();
+        for part in pattern.split('/') {
+            let segment = match part {
+                "" => return Err("pattern must not contain empty segments".to_string()),
+                ":year" => Segment::Year,
+                ":month" => Segment::Month,
+                ":day" => Segment::Day,
+                ":group" => Segment::Group,
+                ":slug" => Segment::Slug,
+                _ if part.contains(':') => {
+                    return Err(format!(
+                        "unknown token in `{part}`, expected one of :year, :month, :day, :group, :slug as a whole segment"
+                    ));
+                }
+                _ => Segment::Literal(part.to_string()),
+            };
+            if !matches!(segment, Segment::Literal(_)) && segments.contains(&segment) {
+                return Err(format!("`{part}` appears more than once"));
+            }
+            segments.push(segment);
+        }
+
+        let has = |s: Segment| segments.contains(&s);
+        if !has(Segment::Slug) {
+            return Err("pattern must contain `:slug`".to_string());
+        }
+        if has(Segment::Day) && !has(Segment::Month) {
+            return Err("`:day` requires `:month`".to_string());
+        }
+        if has(Segment::Month) && !has(Segment::Year) {
+            return Err("`:month` requires `:year`".to_string());
+        }
+
+        Ok(Self(Arc::from(segments)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn pattern(s: &str) -> PermalinkPattern {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_slug() {
+        let p = pattern("/:year/:month/:slug");
+        let link = p.parse("/2023/06/hello/").unwrap();
+        assert_eq!(
+            link,
+            Permalink {
+                year: Some(2023),
+                month: Some(6),
+                day: None,
+                group: None,
+                slug: "hello",
+            }
+        );
+        assert_eq!(p.parse("2023/6/hello").unwrap().month, Some(6));
+
+        // 段数不同、日期不合法
+        for path in [
+            "2023/hello",
+            "2023/06/07/hello",
+            "23/06/hello",
+            "2023/13/hello",
+            "2023/00/hello",
+            "2023/+6/hello",
+            "2023//06/hello",
+            "",
+        ] {
+            assert_eq!(p.parse(path), None, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_parse_literals_and_group() {
+        let p = pattern("/blog/:year/:month/:day/:slug");
+        assert_eq!(p.parse("blog/2023/06/07/hello").unwrap().day, Some(7));
+        assert_eq!(p.parse("posts/2023/06/07/hello"), None);
+        assert_eq!(p.parse("blog/2023/06/32/hello"), None);
+
+        // 组 slug 占用多出的段
+        let p = pattern("/:group/:year/:slug");
+        let link = p.parse("notes/rust/2023/ownership").unwrap();
+        assert_eq!(link.group, Some("notes/rust"));
+        assert_eq!((link.year, link.slug), (Some(2023), "ownership"));
+        assert_eq!(
+            p.parse("notes/2023/ownership").unwrap().group,
+            Some("notes")
+        );
+        // 年份所在的段固定，不会被组吞掉
+        assert_eq!(p.parse("notes/rust/ownership"), None);
+        assert_eq!(p.parse("2023/ownership"), None);
+
+        let p = pattern(":slug");
+        assert_eq!(p.parse("hello").unwrap().slug, "hello");
+        assert_eq!(p.parse("a/hello"), None);
+    }
+
+    #[test]
+    fn test_malformed_patterns() {
+        for (pattern, reason) in [
+            ("", "must contain `:slug`"),
+            ("/", "must contain `:slug`"),
+            ("/:year/:month", "must contain `:slug`"),
+            ("/:year//:slug", "empty segments"),
+            ("/:yaer/:slug", "unknown token"),
+            ("/:slug.html", "unknown token"),
+            ("/post-:slug", "unknown token"),
+            ("/:slug/:slug", "more than once"),
+            ("/:year/:day/:slug", "`:day` requires `:month`"),
+            ("/:month/:slug", "`:month` requires `:year`"),
+        ] {
+            let err = pattern.parse::<PermalinkPattern>().unwrap_err();
+            assert!(err.contains(reason), "{pattern}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_matches_date() {
+        let created = FixedOffset::east_opt(8 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2023, 6, 1, 2, 0, 0)
+            .unwrap();
+        let p = pattern("/:year/:month/:day/:slug");
+        assert!(p.parse("2023/06/01/a").unwrap().matches_date(&created));
+        // 按文章自身的时区比较，UTC 下为 5 月 31 日
+        assert!(!p.parse("2023/05/31/a").unwrap().matches_date(&created));
+        assert!(
+            pattern(":year/:slug")
+                .parse("2023/a")
+                .unwrap()
+                .matches_date(&created)
+        );
+        assert!(
+            !pattern(":year/:slug")
+                .parse("2022/a")
+                .unwrap()
+                .matches_date(&created)
+        );
+        assert!(pattern(":slug").parse("a").unwrap().matches_date(&created));
+    }
+}
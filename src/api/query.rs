@@ -1,114 +1,1302 @@
-use axum::extract::{Path, State};
+use std::sync::Arc;
+
+use axum::extract::{FromRef, Path, State};
+use axum::http::{HeaderMap, Method, StatusCode, header::IF_MODIFIED_SINCE};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::routing::get;
 use axum::{Json, Router};
 use axum_extra::extract::Query;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use super::{Error, Querier, Result};
+use super::{
+    EditUrlTemplate, Error, PermalinkPattern, Querier, Result,
+    admin::authorize,
+    cache_tags::CacheTags,
+    conditional::{
+        etag_json, etag_matches, head_json, last_modified, not_modified, not_modified_etag,
+        not_modified_response, weak_etag,
+    },
+    feed::{self, encode_path},
+    fields::{DETAIL_FIELDS, FieldSet, META_FIELDS},
+    read::spa_url,
+};
 
-use crate::{state::AppState, storage::DBPool};
+use crate::{
+    content::{
+        GroupLicense, OTHER_INITIAL, TagNode, tag_tree, title_initial, title_sort_key,
+        truncate_text,
+    },
+    links::is_external,
+    state::AppState,
+    storage::{ArticleSummary, DateRange, ListColumns, ListSort, TimedQuerier, is_short_id},
+};
 
 /// 配置文章相关路由。
 ///
 /// 路由包括：
 /// - `GET /articles`：文章列表
-/// - `GET /articles/{slug}`：获取单篇文章
+/// - `GET /articles/index`：按标题首字母分组的文章索引
+/// - `GET /articles/{*path}`：获取单篇文章，路径为 slug 或 `{group..}/{slug}`
 /// - `GET /articles/tags`：获取所有标签
 /// - `GET /articles/categories`：获取所有分类
+/// - `GET /authors`：获取所有作者
+/// - `GET /categories/{id}`：获取单个分类及其组和文章
+/// - `GET /groups/{id..}`：获取单个组，以 `/feed.xml` 结尾时为组的 RSS 订阅源，见 [`group_path`]
+/// - `GET /a/{short_id}`：按短 id 获取单篇文章
+/// - `GET /resolve/{*path}`：查询旧位置的重定向目标
+/// - `GET /permalink/{*path}`：按配置的固定链接格式解析旧地址
+/// - `GET /slugs`：所有公开文章的组和 slug
 pub fn setup_route() -> Router<AppState> {
     Router::new()
         .route("/articles", get(articles_list))
-        .route("/articles/{slug}", get(article))
+        .route("/articles/index", get(title_index))
+        .route("/articles/{*path}", get(article))
+        .route("/a/{short_id}", get(article_by_short_id))
+        .route("/resolve/{*path}", get(resolve))
+        .route("/permalink/{*path}", get(permalink))
         .route("/tags", get(tag_list))
         .route("/groups", get(group_list))
+        .route("/groups/{*path}", get(group_path))
+        .route("/slugs", get(slug_list))
+        .route("/authors", get(author_list))
+        .route("/categories/{id}", get(category))
 }
 
 /// 文章元信息，用于列表展示。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ArticleMeta {
+    /// 不随 slug 变化的短 id，文章改名或移动后不变，可通过 `GET /api/a/{id}` 访问
+    #[schema(example = "mfrggzdf")]
+    pub id: Option<String>,
     pub slug: String,
     pub title: String,
-    pub summary: String,
+    /// 摘要，格式由 [`SummaryFormat`] 决定，为 [`SummaryFormat::None`] 时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     pub tags: Vec<String>,
     pub group: Group,
+    /// 所在组的默认作者，未设置时为 `null`
+    pub author: Option<Author>,
+    /// 所在组的分类，未设置时为 `null`
+    pub category: Option<Category>,
+    pub comment_count: i64,
+    /// 所在组是否已归档
+    pub archived: bool,
+    /// 更新时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
     pub updated_at: i64,
+    /// 创建时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
     pub created_at: i64,
 }
 
 /// 完整文章，包括元信息和正文。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ArticleDetail {
     #[serde(flatten)]
     meta: ArticleMeta,
 
+    /// 渲染后的 HTML 正文
     content: String,
+
+    /// 同组中的上一篇文章，第一篇为 `null`
+    prev: Option<Neighbor>,
+    /// 同组中的下一篇文章，最后一篇为 `null`
+    next: Option<Neighbor>,
+    /// 编辑源文件的链接，未配置 `GITNOTE_EDIT_URL_TEMPLATE` 时为 `null`
+    edit_url: Option<String>,
+    /// 转载文章的原文地址，来自 Front Matter 的 `canonical`，未设置时为 `null`
+    #[schema(example = "https://blog.example.com/original")]
+    canonical_url: Option<String>,
+    /// 所在组的许可协议，组未声明时为 `GITNOTE_DEFAULT_LICENSE`，都未设置时为 `null`
+    license: Option<License>,
+    /// 分享卡片信息，加密文章和迁移前写入、尚未重新同步的文章为 `null`
+    social: Option<SocialCard>,
+    /// 正文中链接的附件，按出现顺序排列，加密文章和迁移前写入、尚未重新同步的文章为空
+    attachments: Vec<Attachment>,
+}
+
+/// 分享卡片信息，用于生成 OpenGraph 和 Twitter 卡片。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SocialCard {
+    /// 文章标题
+    pub title: String,
+    /// 纯文本描述，取摘要，摘要为空时取正文，最多 200 个字符；都为空时为 `null`
+    pub description: Option<String>,
+    /// 封面图片的绝对地址，来自 Front Matter 的 `cover` 或正文中的第一张图片，
+    /// 都没有时为 `GITNOTE_DEFAULT_OG_IMAGE`，未配置时为 `null`
+    #[schema(example = "https://notes.example.com/posts/images/cover.png")]
+    pub image: Option<String>,
+    /// 站点上的文章地址 `{site_url}/{group}/{slug}`
+    #[schema(example = "https://notes.example.com/posts/intro")]
+    pub url: String,
+}
+
+impl SocialCard {
+    /// 按当前的站点地址和默认图片展开同步时保存的卡片信息，没有时返回 `None`
+    pub(super) fn new(
+        article: &crate::storage::ArticleDetail,
+        site_url: &str,
+        default_image: Option<&str>,
+    ) -> Option<Self> {
+        let social = article.social.as_ref()?;
+        let url = spa_url(site_url, article);
+        Some(Self {
+            title: article.title.to_owned(),
+            description: social.description.to_owned(),
+            image: social.image_url(&url, default_image),
+            url,
+        })
+    }
+}
+
+/// 生成文章详情使用的部署配置
+#[derive(Debug, Clone)]
+pub(super) struct DetailOptions {
+    edit_url: Option<EditUrlTemplate>,
+    default_license: Option<GroupLicense>,
+    site_url: Arc<str>,
+    default_og_image: Option<Arc<str>>,
+}
+
+impl FromRef<AppState> for DetailOptions {
+    fn from_ref(app: &AppState) -> Self {
+        Self {
+            edit_url: app.edit_url().cloned(),
+            default_license: app.default_license().cloned(),
+            site_url: Arc::from(app.site_url()),
+            default_og_image: app.default_og_image().map(Arc::from),
+        }
+    }
+}
+
+/// 相邻文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Neighbor {
+    slug: String,
+    title: String,
+}
+
+impl From<crate::storage::Neighbor> for Neighbor {
+    fn from(neighbor: crate::storage::Neighbor) -> Self {
+        Self {
+            slug: neighbor.slug,
+            title: neighbor.title,
+        }
+    }
+}
+
+/// 文章附件，来自正文中指向 `GITNOTE_ATTACHMENT_EXTENSIONS` 扩展名文件的链接，图片不计入。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Attachment {
+    /// 外部链接，或相对仓库根目录的文件路径
+    #[schema(example = "notes/rust/files/slides.pdf")]
+    url_or_path: String,
+    /// 是否为 http/https 外部链接，为 `false` 时 `url_or_path` 为仓库中的文件路径
+    external: bool,
+    /// 小写的扩展名
+    #[schema(example = "pdf")]
+    kind: String,
+    /// 链接文字，自动链接为链接本身
+    #[schema(example = "幻灯片")]
+    link_text: String,
+}
+
+impl From<crate::storage::Attachment> for Attachment {
+    fn from(attachment: crate::storage::Attachment) -> Self {
+        Self {
+            external: is_external(&attachment.url_or_path),
+            url_or_path: attachment.url_or_path,
+            kind: attachment.kind,
+            link_text: attachment.link_text,
+        }
+    }
 }
 
 /// 文章分类。
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct Group {
+    /// 组 id，组配置设置了 `slug` 时为该值，否则为组目录路径
+    id: String,
+    name: String,
+}
+
+impl Group {
+    /// 由组配置生成，用于不经过数据库的响应
+    pub(super) fn from_config(group: &crate::content::Group) -> Self {
+        Self {
+            id: group.public_id().to_owned(),
+            name: group.name.to_owned(),
+        }
+    }
+}
+
+/// 组列表中的组。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NavGroup {
+    #[serde(flatten)]
+    group: Group,
+    /// 组配置中的排序，未设置时为 `null`
+    nav_order: Option<i32>,
+    /// 是否从组列表中隐藏，只在 `include_hidden=true` 时可能为 `true`
+    hidden: bool,
+}
+
+/// 组详情。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupDetail {
+    #[serde(flatten)]
+    group: Group,
+    /// 所在组的默认作者，未设置时为 `null`
+    author: Option<Author>,
+    /// 组内文章是否已归档
+    archived: bool,
+    /// 组目录中说明文件（默认 `README.md`）渲染后的 HTML，没有说明文件时为 `null`
+    description_html: Option<String>,
+    /// 组的许可协议，未声明时为 `GITNOTE_DEFAULT_LICENSE`，都未设置时为 `null`
+    license: Option<License>,
+    /// 组的大小统计，迁移后尚未同步过时为 `null`
+    size: Option<GroupSize>,
+}
+
+/// 组的大小统计，包括组内未公开的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GroupSize {
+    /// 组内文章原文的总字节数
+    content_bytes: i64,
+    /// 组内资源文件的总字节数，目前始终为 0
+    asset_bytes: i64,
+    article_count: i64,
+    /// 组配置中的 `quota_bytes`，未设置时为 `null`
+    quota_bytes: Option<i64>,
+    /// 统计最近一次变化的时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    updated_at: i64,
+}
+
+impl From<crate::storage::GroupStats> for GroupSize {
+    fn from(stats: crate::storage::GroupStats) -> Self {
+        Self {
+            content_bytes: stats.content_bytes,
+            asset_bytes: stats.asset_bytes,
+            article_count: stats.article_count,
+            quota_bytes: stats.quota_bytes,
+            updated_at: stats.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+/// 作者。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Author {
+    /// 稳定的作者 id，用于筛选
+    id: String,
+    /// 当前的显示名
+    name: String,
+}
+
+impl Author {
+    fn from_group(group: &crate::storage::Group) -> Option<Self> {
+        Some(Self {
+            id: group.author_id.clone()?,
+            name: group.author_name.clone()?,
+        })
+    }
+
+    /// 由组配置生成，用于不经过数据库的响应
+    pub(super) fn from_config(group: &crate::content::Group) -> Option<Self> {
+        group.author.as_ref().map(|a| Self {
+            id: a.id.to_owned(),
+            name: a.name.to_owned(),
+        })
+    }
+}
+
+/// 组所属的分类。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Category {
+    /// 分类 id，用于筛选
     id: String,
     name: String,
 }
 
-/// 根据 slug 获取单篇文章。
+impl Category {
+    fn from_group(group: &crate::storage::Group) -> Option<Self> {
+        Some(Self {
+            id: group.category_id.clone()?,
+            name: group.category_name.clone()?,
+        })
+    }
+
+    /// 由组配置生成，用于不经过数据库的响应
+    pub(super) fn from_config(group: &crate::content::Group) -> Option<Self> {
+        group.category.as_ref().map(|c| Self {
+            id: c.id.to_owned(),
+            name: c.name.to_owned(),
+        })
+    }
+}
+
+/// 许可协议。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct License {
+    /// SPDX 风格的协议 id
+    #[schema(example = "CC-BY-4.0")]
+    id: String,
+    /// 显示名，组配置未设置时与 `id` 相同
+    name: String,
+    /// 协议全文的地址，未设置时为 `null`
+    #[schema(example = "https://creativecommons.org/licenses/by/4.0/")]
+    url: Option<String>,
+}
+
+impl From<&GroupLicense> for License {
+    fn from(license: &GroupLicense) -> Self {
+        Self {
+            id: license.id.to_owned(),
+            name: license.name.to_owned(),
+            url: license.url.to_owned(),
+        }
+    }
+}
+
+/// 组生效的许可协议：组声明的协议，未声明时为部署配置的默认协议
+pub(super) fn group_license<'a>(
+    group: &'a crate::storage::Group,
+    default: Option<&'a GroupLicense>,
+) -> Option<&'a GroupLicense> {
+    group.license.as_deref().or(default)
+}
+
+/// 分类中的组及其公开文章数。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryGroup {
+    #[serde(flatten)]
+    group: Group,
+    /// 公开文章数，包括已归档的文章
+    article_count: i64,
+}
+
+/// 分类详情，包括分类中的公开组和最新的公开文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryDetail {
+    #[serde(flatten)]
+    category: Category,
+    /// 分类中的公开组，按组 id 排序
+    groups: Vec<CategoryGroup>,
+    /// 分类中的公开文章，按 `page` 和 `limit` 分页
+    articles: Vec<ArticleMeta>,
+}
+
+/// 作者及其公开文章统计。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorStats {
+    #[serde(flatten)]
+    author: Author,
+    /// 公开文章数
+    article_count: i64,
+    /// 最新创建的公开文章
+    latest: Neighbor,
+}
+
+/// 重定向信息，指向文章的当前位置。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RedirectHint {
+    /// 文章当前位置，格式为 `group/slug`
+    redirect_to: String,
+    permanent: bool,
+}
+
+impl From<crate::storage::Redirect> for RedirectHint {
+    fn from(redirect: crate::storage::Redirect) -> Self {
+        let redirect_to = if redirect.group_id.is_empty() {
+            redirect.slug
+        } else {
+            format!("{}/{}", redirect.group_id, redirect.slug)
+        };
+
+        Self {
+            redirect_to,
+            permanent: true,
+        }
+    }
+}
+
+/// 文章详情查询参数。
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct ArticleParams {
+    /// 命中别名时返回 [`RedirectHint`]，而不是 308 跳转
+    redirect_hint: bool,
+    /// 逗号分隔的字段名，只返回这些顶层字段，`slug` 总是返回；有未知字段时返回 400 并列出可以选择的字段
+    #[param(example = "title,content")]
+    fields: Option<String>,
+}
+
+/// 根据路径获取单篇文章。
+///
+/// 路径为文章 slug，或以组 slug 为前缀的 `{group..}/{slug}`，如 `/api/articles/rust/ownership`，组 slug 可以包含 `/`。
+/// 先按整个路径查找 slug，找不到时以最后一段为 slug、其余部分为组 slug 查找，文章不在该组中时视为不存在。
+/// 路径同时是某个组的 slug 时返回同 slug 的文章，并记录警告。
+///
+/// 返回 [`ArticleFull`]。文章不存在但 slug 是某篇文章的旧位置时，
+/// 返回 308 跳转（或在 `redirect_hint=true` 时返回 [`RedirectHint`]），
+/// 否则返回 [`Error::NotFound`]。已归档的文章同样返回，`archived` 为 `true`。
+///
+/// 指定 `fields` 时只返回其中的顶层字段和 `slug`。
 ///
-/// 返回 [`ArticleFull`]，如果文章不存在返回 [`Error::NotFound`]。
-async fn article(
-    Path(slug): Path<String>,
-    State(pool): State<DBPool>,
-) -> Result<Json<ArticleDetail>> {
-    let article = pool.get_one(&slug).await?.ok_or(Error::NotFound)?;
+/// 响应头 `Last-Modified` 取文章的 `updated_at`，满足 `If-Modified-Since` 时返回 304。
+/// `HEAD` 请求和条件请求先查询更新时间，不读取正文。
+#[utoipa::path(
+    get,
+    path = "/api/articles/{path}",
+    params(("path" = String, Path, description = "文章 slug，或 `{group..}/{slug}`"), ArticleParams),
+    responses(
+        (status = 200, description = "文章详情", body = ArticleDetail,
+            headers(("Last-Modified" = String, description = "文章的更新时间"))),
+        (status = 304, description = "文章在 `If-Modified-Since` 之后没有更新"),
+        (status = 308, description = "slug 为旧位置，跳转到以 `group/slug` 表示的当前位置"),
+        (status = 400, description = "`fields` 中有未知字段", body = String, content_type = "text/plain"),
+        (status = 404, description = "文章不存在或未公开", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn article(
+    method: Method,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+    Query(params): Query<ArticleParams>,
+    State(pool): State<TimedQuerier>,
+    State(options): State<DetailOptions>,
+) -> Result<Response> {
+    let fields = FieldSet::parse(params.fields.as_deref(), DETAIL_FIELDS)?;
+    let path = path.trim_matches('/');
+    if (method == Method::HEAD || headers.contains_key(IF_MODIFIED_SINCE))
+        && let Some(updated_at) = pool.article_updated_at(path).await?
+    {
+        if not_modified(&headers, updated_at) {
+            return Ok(not_modified_response(updated_at));
+        }
+        if method == Method::HEAD {
+            return Ok(head_json(Some(updated_at)));
+        }
+    }
+
+    let Some(article) = find_article(&pool, path).await? else {
+        let (group, slug) = split_path(path);
+        let redirect = pool.redirect(group, slug).await?.ok_or(Error::NotFound)?;
+
+        let hint = RedirectHint::from(redirect);
+        return Ok(if params.redirect_hint {
+            Json(hint).into_response()
+        } else {
+            let uri = format!("/api/articles/{}", encode_path(&hint.redirect_to));
+            Redirect::permanent(&uri).into_response()
+        });
+    };
+
+    // 带组前缀的路径在查询到文章后才能判断条件请求
+    Ok(detail_response(
+        &method, &headers, article, &options, &fields,
+    ))
+}
+
+/// 将文章路径拆分为组 slug 和文章 slug，没有 `/` 时组为 [`None`]
+pub(super) fn split_path(path: &str) -> (Option<&str>, &str) {
+    match path.rsplit_once('/') {
+        Some((group, slug)) => (Some(group), slug),
+        None => (None, path),
+    }
+}
+
+/// 按 `GET /api/articles/{path}` 的规则查找公开文章，见 [`article`]
+pub(super) async fn find_article(
+    pool: &TimedQuerier,
+    path: &str,
+) -> Result<Option<crate::storage::ArticleDetail>> {
+    if let Some(article) = pool.get_one(path).await? {
+        if pool.groups().await?.iter().any(|g| g.slug == path) {
+            tracing::warn!(
+                path,
+                "article slug is also a group slug, serving the article"
+            );
+        }
+        return Ok(Some(article));
+    }
+
+    match split_path(path) {
+        (Some(group), slug) => Ok(pool.get_by_group_and_slug(group, slug).await?),
+        (None, _) => Ok(None),
+    }
+}
+
+/// 根据短 id 获取单篇文章。
+///
+/// 短 id 见 [`ArticleMeta::id`]，文章改名或移动后不变。返回与 `GET /api/articles/{path}` 相同的 [`ArticleDetail`]，
+/// 其中的 `slug` 为文章当前的 slug，客户端可以据此更新链接。短 id 不存在或文章未公开时返回 [`Error::NotFound`]。
+///
+/// 条件请求和 `HEAD` 请求的处理与 `GET /api/articles/{path}` 相同。
+#[utoipa::path(
+    get,
+    path = "/api/a/{short_id}",
+    params(("short_id" = String, Path, description = "文章的短 id")),
+    responses(
+        (status = 200, description = "文章详情", body = ArticleDetail,
+            headers(("Last-Modified" = String, description = "文章的更新时间"))),
+        (status = 304, description = "文章在 `If-Modified-Since` 之后没有更新"),
+        (status = 404, description = "短 id 不存在或文章未公开", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn article_by_short_id(
+    method: Method,
+    headers: HeaderMap,
+    Path(short_id): Path<String>,
+    State(pool): State<TimedQuerier>,
+    State(options): State<DetailOptions>,
+) -> Result<Response> {
+    if !is_short_id(&short_id) {
+        return Err(Error::NotFound);
+    }
+    let article = match pool.short_id_slug(&short_id).await? {
+        Some(slug) => pool.get_one(slug).await?,
+        None => None,
+    };
+    let article = article.ok_or(Error::NotFound)?;
+
+    Ok(detail_response(
+        &method,
+        &headers,
+        article,
+        &options,
+        &FieldSet::default(),
+    ))
+}
+
+/// 生成文章详情的响应，满足 `If-Modified-Since` 时返回 304，`HEAD` 请求不返回正文
+///
+/// 响应中只包含 `fields` 选择的字段。
+fn detail_response(
+    method: &Method,
+    headers: &HeaderMap,
+    article: crate::storage::ArticleDetail,
+    options: &DetailOptions,
+    fields: &FieldSet,
+) -> Response {
+    let updated_at = article.updated_at;
+    if not_modified(headers, updated_at) {
+        return not_modified_response(updated_at);
+    }
+    if method == Method::HEAD {
+        return head_json(Some(updated_at));
+    }
 
-    Ok(Json(ArticleDetail {
+    let tags = CacheTags::article(&article.group.id, &article.slug);
+    let license =
+        group_license(&article.group, options.default_license.as_ref()).map(License::from);
+    let social = SocialCard::new(
+        &article,
+        &options.site_url,
+        options.default_og_image.as_deref(),
+    );
+    let detail = ArticleDetail {
         meta: ArticleMeta {
+            id: article.short_id,
             slug: article.slug,
             title: article.title,
-            summary: article.summary,
+            summary: Some(article.summary),
             tags: article.tags,
+            comment_count: article.comment_count,
+            archived: article.group.archived,
             updated_at: article.updated_at.timestamp_millis(),
             created_at: article.created_at.timestamp_millis(),
+            author: Author::from_group(&article.group),
+            category: Category::from_group(&article.group),
             group: Group {
-                id: article.group.0.id,
+                id: article.group.0.slug,
                 name: article.group.0.name,
             },
         },
         content: article.content,
+        prev: article.prev.map(|n| n.0.into()),
+        next: article.next.map(|n| n.0.into()),
+        edit_url: options.edit_url.as_ref().and_then(|t| {
+            t.expand(
+                article.source_path.as_deref(),
+                article.source_commit.as_deref(),
+            )
+        }),
+        canonical_url: article.canonical_url,
+        license,
+        social,
+        attachments: article.attachments.0.into_iter().map(Into::into).collect(),
+    };
+    let detail = match fields.is_sparse() {
+        true => Json(fields.select(&detail)).into_response(),
+        false => Json(detail).into_response(),
+    };
+    (tags, last_modified(detail, Some(updated_at))).into_response()
+}
+
+/// 根据旧位置 `{group..}/{slug}` 查询重定向目标。
+///
+/// 返回 [`RedirectHint`]，没有对应重定向时返回 [`Error::NotFound`]。
+/// `group` 为设置了 `slug` 的组的目录路径、且文章仍在该组中时，返回以组 slug 表示的当前位置。
+#[utoipa::path(
+    get,
+    path = "/api/resolve/{path}",
+    params(("path" = String, Path, description = "旧位置，格式为 `group/slug`")),
+    responses(
+        (status = 200, description = "重定向目标", body = RedirectHint),
+        (status = 404, description = "没有对应的重定向", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn resolve(
+    Path(path): Path<String>,
+    State(pool): State<TimedQuerier>,
+) -> Result<Json<RedirectHint>> {
+    let path = path.trim_matches('/');
+    let (group, slug) = path.rsplit_once('/').unwrap_or(("", path));
+
+    if let Some(redirect) = pool.redirect(Some(group), slug).await? {
+        return Ok(Json(redirect.into()));
+    }
+
+    let group_slug = moved_group(&pool, group).await?.ok_or(Error::NotFound)?;
+    match pool.get_one(slug).await? {
+        Some(article) if article.group.id == group => Ok(Json(RedirectHint {
+            redirect_to: format!("{group_slug}/{slug}"),
+            permanent: true,
+        })),
+        _ => Err(Error::NotFound),
+    }
+}
+
+/// 按部署配置的固定链接格式（`GITNOTE_PERMALINK`）解析旧地址，返回文章的当前位置。
+///
+/// 格式包含 `:year`、`:month`、`:day` 时须与文章的创建日期一致，包含 `:group` 时须为文章所在组的
+/// slug 或目录路径。未配置格式、路径不符合格式或文章不匹配时返回 [`Error::NotFound`]。
+#[utoipa::path(
+    get,
+    path = "/api/permalink/{path}",
+    params(("path" = String, Path, description = "旧地址，格式由 `GITNOTE_PERMALINK` 决定，如 `2023/06/slug`")),
+    responses(
+        (status = 200, description = "文章当前位置", body = RedirectHint),
+        (status = 404, description = "未配置格式或没有匹配的文章", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn permalink(
+    Path(path): Path<String>,
+    State(pattern): State<Option<PermalinkPattern>>,
+    State(pool): State<TimedQuerier>,
+) -> Result<Json<RedirectHint>> {
+    let link = pattern
+        .as_ref()
+        .and_then(|p| p.parse(&path))
+        .ok_or(Error::NotFound)?;
+    let article = pool.get_one(link.slug).await?.ok_or(Error::NotFound)?;
+
+    let group = &article.group;
+    if !link.matches_date(&article.created_at)
+        || link.group.is_some_and(|g| g != group.slug && g != group.id)
+    {
+        return Err(Error::NotFound);
+    }
+
+    let redirect_to = if group.slug.is_empty() {
+        article.slug
+    } else {
+        format!("{}/{}", group.slug, article.slug)
+    };
+    Ok(Json(RedirectHint {
+        redirect_to,
+        permanent: true,
     }))
 }
 
+/// 查询以组目录路径 `path` 作为旧 id 的组，返回它当前的 slug
+///
+/// 组配置设置了 `slug` 后，由目录路径生成的旧 id 不再用于查询。
+/// 已有其他组以 `path` 为 slug 时返回 `None`。
+pub(super) async fn moved_group(pool: &TimedQuerier, path: &str) -> Result<Option<String>> {
+    let groups = pool.groups().await?;
+    if groups.iter().any(|g| g.slug == path) {
+        return Ok(None);
+    }
+
+    Ok(groups.into_iter().find(|g| g.id == path).map(|g| g.slug))
+}
+
+/// 标签查询参数。
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct TagParams {
+    /// 按 `/` 分隔的层级返回标签树
+    tree: bool,
+}
+
+/// 标签列表或标签树。
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum TagList {
+    /// 所有标签，按名称排序
+    Flat(Vec<String>),
+    /// 顶层标签节点
+    Tree(Vec<TagNode>),
+}
+
 /// 获取所有文章标签。
 ///
-/// 返回标签列表。
-async fn tag_list(State(pool): State<DBPool>) -> Result<Json<Vec<String>>> {
-    pool.tags().await.map(Json).map_err(Into::into)
+/// 返回按名称排序的标签列表。`tree=true` 时以 `/` 为层级分隔符返回 [`TagNode`] 树，
+/// 上层节点的文章数汇总所有后代标签，如 `lang` 包含 `lang/rust` 和 `lang/go` 的文章。
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    params(TagParams),
+    responses((status = 200, description = "标签列表或标签树", body = TagList))
+)]
+pub(super) async fn tag_list(
+    Query(params): Query<TagParams>,
+    State(pool): State<TimedQuerier>,
+) -> Result<(CacheTags, Json<TagList>)> {
+    let list = if params.tree {
+        TagList::Tree(tag_tree(&pool.article_tags().await?))
+    } else {
+        TagList::Flat(pool.tags().await?)
+    };
+
+    Ok((CacheTags::collection("tags"), Json(list)))
+}
+
+/// `GET /api/slugs` 单次返回的文章数上限
+pub const MAX_SLUGS: i64 = 50_000;
+
+/// 文章路径查询参数。
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct SlugParams {
+    /// 只返回该组中的文章，为组对外使用的 id
+    group: Option<String>,
 }
 
-/// 获取所有文章分类。
+/// 公开文章的位置。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SlugEntry {
+    /// 所在组的 id
+    group: String,
+    slug: String,
+    /// 更新时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    updated_at: i64,
+}
+
+/// 获取所有公开文章的组、slug 和更新时间。
 ///
-/// 返回 [`Category`] 列表。
-async fn group_list(State(pool): State<DBPool>) -> Result<Json<Vec<Group>>> {
-    match pool.groups().await {
-        Ok(data) => Ok(Json(
-            data.into_iter()
-                .map(|d| Group {
-                    id: d.id,
-                    name: d.name,
-                })
-                .collect::<Vec<_>>(),
-        )),
-        Err(e) => Err(e.into()),
+/// 用于静态站点生成全部文章路径，一次返回全部结果，按组 id 和 slug 排序，不分页。
+/// 响应头 `ETag` 为压缩前响应体的 SHA-256，是弱 ETag（`W/"..."`），与 `If-None-Match` 匹配时返回 304。
+/// 超过 [`MAX_SLUGS`] 篇时返回 400，可用 `group` 参数分组获取。
+#[utoipa::path(
+    get,
+    path = "/api/slugs",
+    params(SlugParams),
+    responses(
+        (status = 200, description = "文章路径列表", body = Vec<SlugEntry>),
+        (status = 304, description = "与 If-None-Match 中的 ETag 相同"),
+        (status = 400, description = "超过单次返回的上限，需要用 group 参数缩小范围", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn slug_list(
+    Query(params): Query<SlugParams>,
+    State(pool): State<TimedQuerier>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let rows = pool
+        .article_slugs(params.group.as_deref(), MAX_SLUGS + 1)
+        .await?;
+    if rows.len() as i64 > MAX_SLUGS {
+        return Err(Error::TooManyRows(MAX_SLUGS));
+    }
+
+    let entries = rows
+        .into_iter()
+        .map(|r| SlugEntry {
+            group: r.group,
+            slug: r.slug,
+            updated_at: r.updated_at.timestamp_millis(),
+        })
+        .collect::<Vec<_>>();
+    let body = serde_json::to_vec(&entries).map_err(std::io::Error::from)?;
+    let etag = weak_etag(&body);
+    if etag_matches(&headers, &etag) {
+        return Ok(not_modified_etag(&etag));
+    }
+
+    Ok((CacheTags::collection("articles"), etag_json(body, &etag)).into_response())
+}
+
+/// 标题索引中的一篇文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TitleIndexEntry {
+    slug: String,
+    title: String,
+    /// 所在组的 id
+    group: String,
+}
+
+/// 标题索引中首字母相同的文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TitleBucket {
+    /// 标题的首字母，`A`–`Z` 或 `#`，中文标题取拼音的首字母
+    #[schema(example = "Z")]
+    initial: String,
+    /// 按标题的字母顺序排列
+    articles: Vec<TitleIndexEntry>,
+}
+
+/// 获取按标题首字母分组的公开文章索引。
+///
+/// 中文标题按拼音归入 `A`–`Z`，与英文标题一起排列，多音字取最常用的读音；数字和其他文字开头的标题归入最后的 `#`，
+/// 开头的标点和符号忽略，如《三体》归入 `S`。只返回有文章的分组，一次返回全部结果，不分页，
+/// 超过 [`MAX_SLUGS`] 篇时返回 400，可用 `group` 参数分组获取。
+/// 迁移前写入且尚未重新同步的文章按标题即时计算排序键。
+///
+/// 根目录下 slug 为 `index` 的文章不能再通过 `GET /api/articles/index` 获取，可以使用 `{group}/index` 或短 id。
+#[utoipa::path(
+    get,
+    path = "/api/articles/index",
+    params(SlugParams),
+    responses(
+        (status = 200, description = "按首字母分组的文章", body = Vec<TitleBucket>),
+        (status = 400, description = "超过单次返回的上限，需要用 group 参数缩小范围", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn title_index(
+    Query(params): Query<SlugParams>,
+    State(pool): State<TimedQuerier>,
+) -> Result<(CacheTags, Json<Vec<TitleBucket>>)> {
+    let rows = pool
+        .title_index(params.group.as_deref(), MAX_SLUGS + 1)
+        .await?;
+    if rows.len() as i64 > MAX_SLUGS {
+        return Err(Error::TooManyRows(MAX_SLUGS));
+    }
+
+    let mut rows = rows
+        .into_iter()
+        .map(|mut r| {
+            let key = r
+                .title_sort_key
+                .take()
+                .unwrap_or_else(|| title_sort_key(&r.title));
+            (title_initial(&key), key, r)
+        })
+        .collect::<Vec<_>>();
+    // `#` 排在字母之后
+    rows.sort_by(|(a_initial, a_key, a), (b_initial, b_key, b)| {
+        (*a_initial == OTHER_INITIAL, a_key, &a.slug).cmp(&(
+            *b_initial == OTHER_INITIAL,
+            b_key,
+            &b.slug,
+        ))
+    });
+
+    let mut buckets: Vec<TitleBucket> = Vec::new();
+    for (initial, _, r) in rows {
+        let entry = TitleIndexEntry {
+            slug: r.slug,
+            title: r.title,
+            group: r.group,
+        };
+        match buckets.last_mut() {
+            Some(bucket) if bucket.initial.starts_with(initial) => bucket.articles.push(entry),
+            _ => buckets.push(TitleBucket {
+                initial: initial.to_string(),
+                articles: vec![entry],
+            }),
+        }
+    }
+
+    Ok((CacheTags::collection("articles"), Json(buckets)))
+}
+
+/// 组列表查询参数。
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct GroupListParams {
+    /// 包含隐藏的组，需要管理 token
+    include_hidden: bool,
+}
+
+/// 获取所有公开的组。
+///
+/// 返回 [`NavGroup`] 列表，按 `nav_order` 排序，相同或未设置时按组名排序，未设置 `nav_order` 的组排在最后。
+/// 不包含 `hidden` 的组，`include_hidden=true` 时需要携带管理 token。
+#[utoipa::path(
+    get,
+    path = "/api/groups",
+    params(GroupListParams),
+    responses(
+        (status = 200, description = "公开分组列表", body = Vec<NavGroup>),
+        (status = 401, description = "include_hidden=true 时缺少或错误的 token", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn group_list(
+    Query(params): Query<GroupListParams>,
+    State(app): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(CacheTags, Json<Vec<NavGroup>>)> {
+    if params.include_hidden {
+        authorize(&app, &headers)?;
+    }
+
+    let groups = app.querier().groups().await?;
+    let list = Json(
+        groups
+            .into_iter()
+            .filter(|g| params.include_hidden || !g.hidden)
+            .map(|g| NavGroup {
+                group: Group {
+                    id: g.slug,
+                    name: g.name,
+                },
+                nav_order: g.nav_order,
+                hidden: g.hidden,
+            })
+            .collect(),
+    );
+    Ok((CacheTags::collection("groups"), list))
+}
+
+/// 组详情和组订阅源共用的路由。
+///
+/// 组 id 可以包含 `/`，只能用一个通配路由匹配。地址以 `/feed.xml` 结尾时交给 [`feed::group_feed`]，
+/// 其余交给 [`group`]，组 id 本身以 `feed` 结尾时仍为组详情。
+async fn group_path(Path(path): Path<String>, State(app): State<AppState>) -> Result<Response> {
+    match path.strip_suffix("/feed.xml") {
+        Some(id) => feed::group_feed(Path(id.to_owned()), State(app)).await,
+        None => {
+            group(
+                Path(path),
+                State(app.querier().clone()),
+                State(app.default_license().cloned()),
+            )
+            .await
+        }
+    }
+}
+
+/// 获取单个组的详情。
+///
+/// 组 id 可以包含 `/`，如 `/api/groups/posts/blog`，与组订阅源共用路由，见 [`group_path`]。
+/// 组不存在或未公开时返回 404；组 id 为设置了 `slug` 的组的目录路径时，跳转到以 slug 表示的地址。
+/// 组的大小统计见 [`GroupSize`]，在每次同步提交时更新。
+#[utoipa::path(
+    get,
+    path = "/api/groups/{id}",
+    params(("id" = String, Path, description = "组 id，可以包含 `/`")),
+    responses(
+        (status = 200, description = "组详情", body = GroupDetail),
+        (status = 308, description = "组 id 为组目录路径，跳转到以组 slug 表示的地址"),
+        (status = 404, description = "组不存在或未公开", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn group(
+    Path(id): Path<String>,
+    State(pool): State<TimedQuerier>,
+    State(default_license): State<Option<GroupLicense>>,
+) -> Result<Response> {
+    let Some(group) = pool.groups().await?.into_iter().find(|g| g.slug == id) else {
+        let slug = moved_group(&pool, &id).await?.ok_or(Error::NotFound)?;
+        let uri = format!("/api/groups/{}", encode_path(&slug));
+        return Ok(Redirect::permanent(&uri).into_response());
+    };
+    let size = pool
+        .group_stats()
+        .await?
+        .into_iter()
+        .find(|s| s.group_id.as_str() == group.id)
+        .map(GroupSize::from);
+
+    let tags = CacheTags::group(&group.id);
+    Ok((
+        tags,
+        Json(GroupDetail {
+            size,
+            author: Author::from_group(&group),
+            license: group_license(&group, default_license.as_ref()).map(License::from),
+            archived: group.archived,
+            description_html: group.description_html,
+            group: Group {
+                id: group.slug,
+                name: group.name,
+            },
+        }),
+    )
+        .into_response())
+}
+
+/// 获取有公开文章的作者。
+///
+/// 返回 [`AuthorStats`] 列表，按作者 id 排序。
+#[utoipa::path(
+    get,
+    path = "/api/authors",
+    responses((status = 200, description = "作者列表", body = Vec<AuthorStats>))
+)]
+pub(super) async fn author_list(
+    State(pool): State<TimedQuerier>,
+) -> Result<(CacheTags, Json<Vec<AuthorStats>>)> {
+    let authors = pool.authors().await?;
+
+    let list = Json(
+        authors
+            .into_iter()
+            .map(|a| AuthorStats {
+                author: Author {
+                    id: a.id,
+                    name: a.name,
+                },
+                article_count: a.article_count,
+                latest: a.latest.0.into(),
+            })
+            .collect(),
+    );
+    Ok((CacheTags::collection("groups"), list))
+}
+
+/// 分类详情的分页参数。
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(default)]
+pub struct CategoryParams {
+    /// 每页文章数量，默认 13，最大 100
+    limit: i32,
+    /// 页码，从 1 开始，跳过的文章数 `(page - 1) * limit` 最多 10000
+    page: i32,
+    /// 摘要格式，默认 `html`
+    summary: SummaryFormat,
+}
+
+impl Default for CategoryParams {
+    fn default() -> Self {
+        Self {
+            limit: 13,
+            page: 1,
+            summary: SummaryFormat::default(),
+        }
+    }
+}
+
+/// 获取单个分类的详情。
+///
+/// 返回 [`CategoryDetail`]，包括分类中的公开组及其文章数和按更新时间倒序分页的公开文章，
+/// 文章包含已归档组中的文章，与组的文章数一致。分类不存在或只包含未公开的组时返回 404。
+#[utoipa::path(
+    get,
+    path = "/api/categories/{id}",
+    params(("id" = String, Path, description = "分类 id"), CategoryParams),
+    responses(
+        (status = 200, description = "分类详情", body = CategoryDetail),
+        (status = 400, description = "分页超过上限", body = String, content_type = "text/plain"),
+        (status = 404, description = "分类不存在或没有公开的组", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn category(
+    Path(id): Path<String>,
+    Query(params): Query<CategoryParams>,
+    State(app): State<AppState>,
+) -> Result<(CacheTags, Json<CategoryDetail>)> {
+    check_page(params.page, params.limit)?;
+    let groups = app.querier().category_groups(&id).await?;
+    let name = groups
+        .first()
+        .map(|g| g.category_name.to_owned())
+        .ok_or(Error::NotFound)?;
+
+    let articles = app
+        .querier()
+        .article_list(
+            params.page,
+            params.limit,
+            None,
+            None,
+            Some(&id),
+            vec![],
+            None,
+            DateRange::default(),
+            ListColumns::ALL,
+            ListSort::Updated,
+        )
+        .await?;
+
+    let detail = Json(CategoryDetail {
+        category: Category { id, name },
+        groups: groups
+            .into_iter()
+            .map(|g| CategoryGroup {
+                group: Group {
+                    id: g.slug,
+                    name: g.name,
+                },
+                article_count: g.article_count,
+            })
+            .collect(),
+        articles: articles
+            .into_iter()
+            .map(|a| article_meta(a, params.summary, &app))
+            .collect(),
+    });
+    Ok((CacheTags::collection("groups"), detail))
+}
+
+/// 文章列表中摘要的格式。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryFormat {
+    /// 渲染后的 HTML 摘要
+    #[default]
+    Html,
+    /// 去掉标签的纯文本，超过长度时按单词边界截断并追加 `…`
+    Plain,
+    /// 不返回摘要
+    None,
+}
+
+/// 文章列表的排序方式。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ArticleSort {
+    /// 按更新时间从新到旧
+    #[default]
+    Updated,
+    /// 按标题的字母顺序，中文标题按拼音，与英文标题一起排列；数字开头的标题排在最前，
+    /// 迁移后尚未重新同步的文章排在最后
+    Title,
+}
+
+impl From<ArticleSort> for ListSort {
+    fn from(sort: ArticleSort) -> Self {
+        match sort {
+            ArticleSort::Updated => ListSort::Updated,
+            ArticleSort::Title => ListSort::Title,
+        }
     }
 }
 
+/// 文章列表每页数量的上限
+const MAX_PAGE_SIZE: i32 = 100;
+
+/// 文章列表分页跳过的文章数 `(page - 1) * limit` 的上限，过大的 `OFFSET` 会让查询扫描大量行
+const MAX_PAGE_OFFSET: i64 = 10_000;
+
+/// 检查分页参数，`limit` 不在 1 到 [`MAX_PAGE_SIZE`] 之间或跳过的文章数超过 [`MAX_PAGE_OFFSET`] 时返回 400
+fn check_page(page: i32, limit: i32) -> Result<()> {
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(Error::Custom("`limit` must be between 1 and 100"));
+    }
+    if (i64::from(page.max(1)) - 1) * i64::from(limit) > MAX_PAGE_OFFSET {
+        return Err(Error::Custom(
+            "`page` is too deep, (page - 1) * limit must not exceed 10000",
+        ));
+    }
+    Ok(())
+}
+
 /// 查询参数，用于文章列表分页和筛选。
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(default)]
 pub struct QueryParams {
+    /// 每页数量，默认 13，最大 100
     limit: i32,
+    /// 页码，从 1 开始，跳过的文章数 `(page - 1) * limit` 最多 10000
     page: i32,
+    /// 分组 id，组配置设置了 `slug` 时为该值
     group: Option<String>,
+    /// 作者 id
+    author: Option<String>,
+    /// 分类 id
+    category: Option<String>,
+    /// 逗号分隔的标签，命中任意一个即可；上层标签同时匹配其后代，如 `lang` 匹配 `lang/rust`
     tags: String,
+    /// 摘要格式，默认 `html`
+    summary: SummaryFormat,
+    /// 排序方式，默认 `updated`
+    sort: ArticleSort,
+    /// 是否包含已归档的文章，默认不包含
+    include_archived: bool,
+    /// 创建时间下界（包含），格式见 [`parse_bound`]
+    created_after: Option<String>,
+    /// 创建时间上界（包含），只有日期时包含当天全部时间
+    created_before: Option<String>,
+    /// 更新时间下界（包含）
+    updated_after: Option<String>,
+    /// 更新时间上界（包含），只有日期时包含当天全部时间
+    updated_before: Option<String>,
+    /// 逗号分隔的字段名，只返回这些顶层字段，`slug` 总是返回；有未知字段时返回 400 并列出可以选择的字段
+    #[param(example = "title,updated_at")]
+    fields: Option<String>,
+}
+
+impl QueryParams {
+    /// 解析时间范围参数，格式错误时返回 [`Error::InvalidParam`]
+    fn date_range(&self, tz: Tz) -> Result<DateRange> {
+        let bound =
+            |name, value: &Option<String>, end| parse_bound(name, value.as_deref(), tz, end);
+        Ok(DateRange {
+            created_after: bound("created_after", &self.created_after, false)?,
+            created_before: bound("created_before", &self.created_before, true)?,
+            updated_after: bound("updated_after", &self.updated_after, false)?,
+            updated_before: bound("updated_before", &self.updated_before, true)?,
+        })
+    }
+}
+
+/// 解析时间边界参数
+///
+/// 支持 Unix 毫秒时间戳、RFC 3339 时间、不带偏移的 `YYYY-MM-DDTHH:MM:SS` 和 `YYYY-MM-DD`，
+/// 不带偏移时视为 `tz` 中的本地时间。只有日期时，下界取当天开始，`end` 为 `true` 的上界取当天最后一微秒，
+/// 使 `created_before=2024-03-31` 包含 3 月 31 日全天。
+pub(super) fn parse_bound(
+    name: &'static str,
+    value: Option<&str>,
+    tz: Tz,
+    end: bool,
+) -> Result<Option<DateTime<FixedOffset>>> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let invalid = || Error::InvalidParam(name);
+
+    if let Ok(millis) = value.parse::<i64>() {
+        let time = DateTime::from_timestamp_millis(millis).ok_or_else(invalid)?;
+        return Ok(Some(time.fixed_offset()));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(time));
+    }
+
+    let naive = match NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+        Ok(naive) => naive,
+        Err(_) => {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid())?;
+            if end {
+                date.and_hms_micro_opt(23, 59, 59, 999_999)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+            .ok_or_else(invalid)?
+        }
+    };
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|time| Some(time.fixed_offset()))
+        .ok_or_else(invalid)
 }
 
 impl Default for QueryParams {
@@ -117,50 +1305,151 @@ impl Default for QueryParams {
             limit: 13,
             page: 1,
             group: None,
+            author: None,
+            category: None,
             tags: Default::default(),
+            summary: SummaryFormat::default(),
+            sort: ArticleSort::default(),
+            include_archived: false,
+            created_after: None,
+            created_before: None,
+            updated_after: None,
+            updated_before: None,
+            fields: None,
         }
     }
 }
 
 /// 获取文章列表。
 ///
-/// 支持分页、作者、分类和标签筛选。
-/// 返回 [`ArticleMeta`] 列表。
-async fn articles_list(
+/// 支持分页、组、作者、分类和标签筛选，默认不包含已归档的文章，`include_archived=true` 时包含。
+/// 默认按更新时间从新到旧排列，`sort=title` 时按标题的字母顺序排列，中文标题按拼音，见 [`ArticleSort`]。
+/// `created_after`、`created_before`、`updated_after`、`updated_before` 按时间过滤，边界包含端点，
+/// 格式错误时返回 400 并指出参数名，下界晚于上界时返回空列表。
+/// `limit` 最大为 [`MAX_PAGE_SIZE`]，跳过的文章数最多为 [`MAX_PAGE_OFFSET`]，超过时返回 400。
+/// 指定 `fields` 时只返回其中的顶层字段和 `slug`，不需要的摘要和标签不从数据库读取。
+/// 查询超过 `GITNOTE_QUERY_TIMEOUT_MS` 时返回 503。
+/// 返回 [`ArticleMeta`] 列表，摘要格式由 `summary` 参数决定，纯文本摘要的长度由
+/// [`AppState::plain_summary_chars`] 配置。
+///
+/// 响应头 `Last-Modified` 取本页文章中最新的 `updated_at`，满足 `If-Modified-Since` 时返回 304，
+/// `HEAD` 请求不序列化响应体。
+#[utoipa::path(
+    get,
+    path = "/api/articles",
+    params(QueryParams),
+    responses(
+        (status = 200, description = "文章列表", body = Vec<ArticleMeta>,
+            headers(("Last-Modified" = String, description = "本页文章中最新的更新时间"))),
+        (status = 304, description = "本页文章在 `If-Modified-Since` 之后没有更新"),
+        (status = 400, description = "时间参数格式错误、分页超过上限或 `fields` 中有未知字段", body = String, content_type = "text/plain"),
+        (status = 404, description = "`group` 为设置了 `slug` 的组的目录路径，响应体提示新的组 id", body = String, content_type = "text/plain"),
+        (status = 503, description = "查询超时", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn articles_list(
+    method: Method,
+    headers: HeaderMap,
     Query(params): Query<QueryParams>,
-    State(pool): State<DBPool>,
-) -> Result<Json<Vec<ArticleMeta>>> {
-    match pool
+    State(app): State<AppState>,
+) -> Result<Response> {
+    let archived = (!params.include_archived).then_some(false);
+    list_articles(method, headers, params, app, archived).await
+}
+
+/// 按归档状态查询文章列表，`archived` 的含义见 [`Querier::article_list`]
+pub(super) async fn list_articles(
+    method: Method,
+    headers: HeaderMap,
+    params: QueryParams,
+    app: AppState,
+    archived: Option<bool>,
+) -> Result<Response> {
+    check_page(params.page, params.limit)?;
+    let dates = params.date_range(app.timezone())?;
+    let fields = FieldSet::parse(params.fields.as_deref(), META_FIELDS)?;
+    let columns = ListColumns {
+        summary: fields.contains("summary") && params.summary != SummaryFormat::None,
+        tags: fields.contains("tags"),
+    };
+
+    let data = app
+        .querier()
         .article_list(
             params.page,
             params.limit,
             params.group.as_deref(),
+            params.author.as_deref(),
+            params.category.as_deref(),
             params
                 .tags
                 .split(",")
                 .map(str::trim)
                 .filter(|t| !t.is_empty())
                 .collect::<Vec<_>>(),
+            archived,
+            dates,
+            columns,
+            params.sort.into(),
         )
-        .await
+        .await?;
+
+    // 按组目录路径查询设置了 slug 的组时提示新的组 id
+    if data.is_empty()
+        && let Some(group) = params.group.as_deref()
+        && let Some(slug) = moved_group(&app.querier(), group).await?
+    {
+        let hint = format!("group `{group}` is now `{slug}`");
+        return Ok((StatusCode::NOT_FOUND, hint).into_response());
+    }
+
+    let updated_at = data.iter().map(|a| a.updated_at).max();
+    if let Some(updated_at) = updated_at
+        && not_modified(&headers, updated_at)
     {
-        Ok(data) => Ok(Json(
-            data.into_iter()
-                .map(|a| ArticleMeta {
-                    slug: a.slug,
-                    title: a.title,
-
-                    summary: a.summary,
-                    tags: a.tags,
-                    updated_at: a.updated_at.timestamp_millis(),
-                    created_at: a.created_at.timestamp_millis(),
-                    group: Group {
-                        id: a.group.0.id,
-                        name: a.group.0.name,
-                    },
-                })
-                .collect(),
-        )),
-        Err(e) => Err(e.into()),
+        return Ok(not_modified_response(updated_at));
+    }
+    if method == Method::HEAD {
+        return Ok(head_json(updated_at));
+    }
+
+    let list = data
+        .into_iter()
+        .map(|a| article_meta(a, params.summary, &app));
+    let list = match fields.is_sparse() {
+        true => Json(list.map(|a| fields.select(&a)).collect::<Vec<_>>()).into_response(),
+        false => Json(list.collect::<Vec<_>>()).into_response(),
+    };
+    Ok((
+        CacheTags::collection("articles"),
+        last_modified(list, updated_at),
+    )
+        .into_response())
+}
+
+/// 由列表查询结果生成 [`ArticleMeta`]，摘要按 `summary` 格式返回
+fn article_meta(a: ArticleSummary, summary: SummaryFormat, app: &AppState) -> ArticleMeta {
+    ArticleMeta {
+        id: a.short_id,
+        slug: a.slug,
+        title: a.title,
+        summary: match summary {
+            SummaryFormat::Html => Some(a.summary),
+            SummaryFormat::Plain => {
+                Some(truncate_text(&a.summary_plain, app.plain_summary_chars()))
+            }
+            SummaryFormat::None => None,
+        },
+        tags: a.tags,
+        comment_count: a.comment_count,
+        archived: a.group.archived,
+        updated_at: a.updated_at.timestamp_millis(),
+        created_at: a.created_at.timestamp_millis(),
+        author: Author::from_group(&a.group),
+        category: Category::from_group(&a.group),
+        group: Group {
+            id: a.group.0.slug,
+            name: a.group.0.name,
+        },
     }
 }
@@ -0,0 +1,373 @@
+use std::fmt::Write;
+
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::{
+        HeaderMap, HeaderValue,
+        header::{CACHE_CONTROL, CONTENT_SECURITY_POLICY, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+
+use super::{
+    Error, Querier, Result,
+    cache_tags::CacheTags,
+    conditional::{last_modified, not_modified, not_modified_response},
+    feed::{encode_path, escape},
+    query::{SocialCard, find_article, group_license, split_path},
+};
+
+use crate::{content::GroupLicense, state::AppState, storage::ArticleDetail};
+
+const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// 阅读页的缓存时间，过期后按 `Last-Modified` 重新验证
+const CACHE: &str = "public, max-age=300, must-revalidate";
+
+/// 阅读页不执行任何脚本，只允许内联样式和正文中的图片、音视频
+const CSP: &str = "default-src 'none'; style-src 'unsafe-inline'; img-src * data:; media-src *; base-uri 'none'; form-action 'none'";
+
+/// 阅读页的内联样式，跟随系统的浅色和深色模式
+const STYLE: &str = "\
+:root{color-scheme:light dark;--fg:#1f2328;--bg:#fff;--muted:#59636e;--line:#d1d9e0;--link:#0969da;--code:#f6f8fa}\
+@media(prefers-color-scheme:dark){:root{--fg:#e6edf3;--bg:#0d1117;--muted:#9198a1;--line:#3d444d;--link:#4493f8;--code:#151b23}}\
+body{margin:0 auto;max-width:44rem;padding:1rem;font:1.0625rem/1.7 system-ui,sans-serif;color:var(--fg);background:var(--bg);overflow-wrap:break-word}\
+a{color:var(--link)}\
+h1{line-height:1.3}\
+header p,footer{margin:0;color:var(--muted);font-size:.9rem}\
+img,video{max-width:100%;height:auto}\
+pre{overflow:auto;padding:.75rem;background:var(--code);border-radius:6px}\
+code{font-family:ui-monospace,monospace;font-size:.9em}\
+table{display:block;overflow:auto;border-collapse:collapse}\
+th,td{padding:.25rem .5rem;border:1px solid var(--line)}\
+blockquote{margin:0;padding-left:1rem;border-left:3px solid var(--line);color:var(--muted)}\
+hr{border:0;border-top:1px solid var(--line)}\
+nav{display:flex;justify-content:space-between;gap:1rem;margin-top:2rem;padding-top:1rem;border-top:1px solid var(--line)}\
+nav a[rel=next]{margin-left:auto;text-align:right}";
+
+/// 配置阅读页路由，挂载在 `/api` 之外。
+///
+/// 路由包括：
+/// - `GET /read/{group..}/{slug}`：单篇公开文章的服务端渲染页面
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/read/{*path}", get(read_article))
+}
+
+/// 获取单篇文章的 HTML 页面，供不执行脚本或网络较差的读者使用。
+///
+/// 路径规则与 `GET /api/articles/{path}` 相同，文章为旧位置时跳转到当前位置的阅读页，
+/// 文章不存在或未公开时返回 404。页面只包含标题、作者、日期、正文、许可协议和同组的上一篇/下一篇，
+/// 不包含脚本，`<link rel="canonical">` 指向站点上的文章地址 `{site_url}/{group}/{slug}`。
+///
+/// 响应头 `Last-Modified` 取文章的 `updated_at`，满足 `If-Modified-Since` 时返回 304。
+async fn read_article(
+    headers: HeaderMap,
+    Path(path): Path<String>,
+    State(app): State<AppState>,
+) -> Result<Response> {
+    let path = path.trim_matches('/');
+    let Some(article) = find_article(&app.querier(), path).await? else {
+        let (group, slug) = split_path(path);
+        let redirect = app
+            .querier()
+            .redirect(group, slug)
+            .await?
+            .ok_or(Error::NotFound)?;
+        let uri = read_path(&redirect.group_id, &redirect.slug);
+        return Ok(Redirect::permanent(&uri).into_response());
+    };
+
+    let updated_at = article.updated_at;
+    if not_modified(&headers, updated_at) {
+        return Ok(not_modified_response(updated_at));
+    }
+
+    let social = SocialCard::new(&article, app.site_url(), app.default_og_image());
+    let html = render_page(
+        app.site_url(),
+        &article,
+        app.default_license(),
+        social.as_ref(),
+    );
+    let tags = CacheTags::article(&article.group.id, &article.slug);
+    let page = last_modified(
+        (
+            [
+                (CONTENT_TYPE, HeaderValue::from_static(HTML_CONTENT_TYPE)),
+                (CACHE_CONTROL, HeaderValue::from_static(CACHE)),
+                (CONTENT_SECURITY_POLICY, HeaderValue::from_static(CSP)),
+            ],
+            html,
+        ),
+        Some(updated_at),
+    );
+    Ok((tags, page).into_response())
+}
+
+/// 站点上的文章地址 `{site_url}/{group}/{slug}`，仓库根目录的组省略组 id
+pub(super) fn spa_url(site_url: &str, article: &ArticleDetail) -> String {
+    let group = &article.group.slug;
+    if group.is_empty() {
+        format!("{site_url}/{}", encode_path(&article.slug))
+    } else {
+        format!(
+            "{site_url}/{}/{}",
+            encode_path(group),
+            encode_path(&article.slug)
+        )
+    }
+}
+
+/// 阅读页地址 `/read/{group}/{slug}`，仓库根目录的组省略组 id
+fn read_path(group: &str, slug: &str) -> String {
+    if group.is_empty() {
+        format!("/read/{}", encode_path(slug))
+    } else {
+        format!("/read/{}/{}", encode_path(group), encode_path(slug))
+    }
+}
+
+/// 生成文章的 HTML 页面
+///
+/// 正文 `content` 是同步时渲染并清理过的 HTML，原样输出；其他字段都经过转义。
+/// 所在组的许可协议（未声明时为 `default_license`）显示在正文之后，`social` 输出为头部的分享卡片标签。
+fn render_page(
+    site_url: &str,
+    article: &ArticleDetail,
+    default_license: Option<&GroupLicense>,
+    social: Option<&SocialCard>,
+) -> String {
+    let title = escape(&article.title);
+    let mut html = String::new();
+    html.push_str(r#"<!DOCTYPE html><html><head><meta charset="utf-8">"#);
+    html.push_str(r#"<meta name="viewport" content="width=device-width,initial-scale=1">"#);
+    if let Some(social) = social {
+        push_social_meta(&mut html, social);
+    }
+    let _ = write!(
+        html,
+        r#"<title>{title}</title><link rel="canonical" href="{}"><style>{STYLE}</style></head><body>"#,
+        escape(&spa_url(site_url, article)),
+    );
+
+    let _ = write!(html, "<article><header><h1>{title}</h1><p>");
+    if let Some(author) = &article.group.author_name {
+        let _ = write!(html, "{} · ", escape(author));
+    }
+    let _ = write!(
+        html,
+        r#"<time datetime="{}">{}</time> · {}</p></header>"#,
+        article.created_at.to_rfc3339(),
+        article.created_at.format("%Y-%m-%d"),
+        escape(&article.group.name),
+    );
+    html.push_str(&article.content);
+    if let Some(license) = group_license(&article.group, default_license) {
+        let name = escape(&license.name);
+        match &license.url {
+            Some(url) => {
+                let _ = write!(
+                    html,
+                    r#"<footer><a rel="license" href="{}">{name}</a></footer>"#,
+                    escape(url)
+                );
+            }
+            None => {
+                let _ = write!(html, "<footer>{name}</footer>");
+            }
+        }
+    }
+    html.push_str("</article>");
+
+    if article.prev.is_some() || article.next.is_some() {
+        html.push_str("<nav>");
+        for (rel, label, neighbor) in [("prev", "←", &article.prev), ("next", "→", &article.next)]
+        {
+            if let Some(neighbor) = neighbor {
+                let _ = write!(
+                    html,
+                    r#"<a rel="{rel}" href="{}">{label} {}</a>"#,
+                    escape(&read_path(&article.group.slug, &neighbor.slug)),
+                    escape(&neighbor.title),
+                );
+            }
+        }
+        html.push_str("</nav>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// 输出 OpenGraph 和 Twitter 卡片的 `<meta>` 标签，有图片时使用大图卡片
+fn push_social_meta(html: &mut String, social: &SocialCard) {
+    let mut meta = |attribute: &str, name: &str, content: &str| {
+        let _ = write!(
+            html,
+            r#"<meta {attribute}="{name}" content="{}">"#,
+            escape(content)
+        );
+    };
+
+    meta("property", "og:type", "article");
+    meta("property", "og:title", &social.title);
+    meta("property", "og:url", &social.url);
+    if let Some(description) = &social.description {
+        meta("name", "description", description);
+        meta("property", "og:description", description);
+    }
+    if let Some(image) = &social.image {
+        meta("property", "og:image", image);
+    }
+    let card = match social.image {
+        Some(_) => "summary_large_image",
+        None => "summary",
+    };
+    meta("name", "twitter:card", card);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use sqlx::types::Json;
+
+    use super::*;
+    use crate::storage::{Group, Neighbor};
+
+    const SITE: &str = "https://notes.example.com";
+
+    fn article(group: &str) -> ArticleDetail {
+        let at = chrono::Utc
+            .with_ymd_and_hms(2024, 5, 31, 16, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        ArticleDetail {
+            slug: "intro".to_string(),
+            short_id: None,
+            title: "<script>alert(1)</script> & more".to_string(),
+            summary: String::new(),
+            tags: vec![],
+            content: "<p>trusted <em>body</em></p>".to_string(),
+            group: Json(Group {
+                id: group.to_string(),
+                slug: group.to_string(),
+                name: "Rust & Go".to_string(),
+                public: true,
+                kind: Json(serde_json::Value::Null),
+                encrypted: false,
+                author_id: Some("alice".to_string()),
+                author_name: Some("Alice <a@b>".to_string()),
+                archived: false,
+                description_html: None,
+                category_id: None,
+                category_name: None,
+                nav_order: None,
+                hidden: false,
+                license: None,
+            }),
+            comment_count: 0,
+            nonce: None,
+            updated_at: at,
+            created_at: at,
+            prev: None,
+            next: Some(Json(Neighbor {
+                slug: "next\"".to_string(),
+                title: "<b>Next</b>".to_string(),
+            })),
+            source_path: None,
+            source_commit: None,
+            canonical_url: None,
+            social: None,
+            attachments: Json(vec![]),
+        }
+    }
+
+    #[test]
+    fn test_render_page_escapes_fields() {
+        let html = render_page(SITE, &article("posts/rust"), None, None);
+
+        assert!(!html.contains("<script"), "{html}");
+        assert!(html.contains("<title>&lt;script&gt;alert(1)&lt;/script&gt; &amp; more</title>"));
+        assert!(html.contains("Alice &lt;a@b&gt; · "));
+        assert!(html.contains(r#"<time datetime="2024-05-31T16:00:00+00:00">2024-05-31</time>"#));
+        assert!(html.contains("Rust &amp; Go"));
+        assert!(html.contains("<p>trusted <em>body</em></p>"));
+        assert!(html.contains(
+            r#"<a rel="next" href="/read/posts/rust/next%22">→ &lt;b&gt;Next&lt;/b&gt;</a>"#
+        ));
+        assert!(!html.contains(r#"rel="prev""#));
+    }
+
+    #[test]
+    fn test_render_page_canonical() {
+        let html = render_page(SITE, &article("posts/rust"), None, None);
+        assert!(html.contains(
+            r#"<link rel="canonical" href="https://notes.example.com/posts/rust/intro">"#
+        ));
+
+        let html = render_page(SITE, &article(""), None, None);
+        assert!(html.contains(r#"<link rel="canonical" href="https://notes.example.com/intro">"#));
+    }
+
+    #[test]
+    fn test_render_page_license() {
+        let html = render_page(SITE, &article("posts/rust"), None, None);
+        assert!(!html.contains("<footer>"), "{html}");
+
+        let default = "CC0-1.0".parse::<GroupLicense>().unwrap();
+        let html = render_page(SITE, &article("posts/rust"), Some(&default), None);
+        assert!(
+            html.contains("<footer>CC0-1.0</footer></article>"),
+            "{html}"
+        );
+
+        let mut licensed = article("posts/rust");
+        licensed.group.license = Some(Json(GroupLicense {
+            id: "CC-BY-4.0".to_string(),
+            name: "CC <BY>".to_string(),
+            url: Some("https://example.com/?a=1&b=2".to_string()),
+        }));
+        let html = render_page(SITE, &licensed, Some(&default), None);
+        assert!(html.contains(
+            r#"<footer><a rel="license" href="https://example.com/?a=1&amp;b=2">CC &lt;BY&gt;</a></footer>"#
+        ));
+    }
+
+    #[test]
+    fn test_render_page_social() {
+        let mut card = SocialCard {
+            title: "Rust & Go".to_string(),
+            description: Some("Say \"hi\" <b>".to_string()),
+            image: Some("https://notes.example.com/posts/a.png?w=1&h=2".to_string()),
+            url: "https://notes.example.com/posts/rust/intro".to_string(),
+        };
+        let html = render_page(SITE, &article("posts/rust"), None, Some(&card));
+        for tag in [
+            r#"<meta property="og:type" content="article">"#,
+            r#"<meta property="og:title" content="Rust &amp; Go">"#,
+            r#"<meta property="og:url" content="https://notes.example.com/posts/rust/intro">"#,
+            r#"<meta name="description" content="Say &quot;hi&quot; &lt;b&gt;">"#,
+            r#"<meta property="og:description" content="Say &quot;hi&quot; &lt;b&gt;">"#,
+            r#"<meta property="og:image" content="https://notes.example.com/posts/a.png?w=1&amp;h=2">"#,
+            r#"<meta name="twitter:card" content="summary_large_image">"#,
+        ] {
+            assert!(html.contains(tag), "{tag}\n{html}");
+        }
+
+        card.description = None;
+        card.image = None;
+        let html = render_page(SITE, &article("posts/rust"), None, Some(&card));
+        assert!(html.contains(r#"<meta name="twitter:card" content="summary">"#));
+        assert!(!html.contains("og:image") && !html.contains("og:description"));
+
+        let html = render_page(SITE, &article("posts/rust"), None, None);
+        assert!(!html.contains("og:"));
+    }
+
+    #[test]
+    fn test_style_is_small() {
+        assert!(STYLE.len() < 2048, "{}", STYLE.len());
+    }
+}
@@ -0,0 +1,62 @@
+use std::{future::Future, sync::Arc};
+
+use axum::{Json, Router, extract::State, routing::get};
+use tokio::sync::Mutex;
+
+use super::{Result, git_sync::open_repo};
+
+use crate::{git_client::RepoStats, state::AppState};
+
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/repo/stats", get(repo_stats))
+}
+
+/// 获取仓库的提交统计。
+///
+/// 返回 HEAD 及其所有祖先的提交总数、作者数、最早和最近的提交时间，以及截至 HEAD 提交时间的 52 周每周提交数。
+/// 结果按 HEAD 缓存，推送使 HEAD 改变前的请求直接返回缓存。仓库还没有提交时各项为零。
+#[utoipa::path(
+    get,
+    path = "/api/repo/stats",
+    responses((status = 200, description = "仓库的提交统计", body = RepoStats))
+)]
+pub(super) async fn repo_stats(
+    State(app): State<AppState>,
+    State(cache): State<RepoStatsCache>,
+) -> Result<Json<RepoStats>> {
+    let repo = open_repo(&app)?;
+    let Some(head) = repo.head().await? else {
+        return Ok(Json(RepoStats::default()));
+    };
+
+    let stats = cache
+        .get_or_load(&head, async { Ok(repo.repo_stats(&head).await?) })
+        .await?;
+    Ok(Json(stats))
+}
+
+/// 仓库提交统计的缓存，键为统计时的 HEAD
+///
+/// 克隆后共享同一份缓存。HEAD 改变后第一个请求重新统计，其余请求等待它的结果，统计失败时不缓存。
+#[derive(Debug, Clone, Default)]
+pub struct RepoStatsCache(Arc<Mutex<Option<(String, RepoStats)>>>);
+
+impl RepoStatsCache {
+    /// 返回 `head` 对应的缓存，否则执行 `load` 并缓存结果
+    async fn get_or_load(
+        &self,
+        head: &str,
+        load: impl Future<Output = Result<RepoStats>>,
+    ) -> Result<RepoStats> {
+        let mut cached = self.0.lock().await;
+        if let Some((commit, stats)) = cached.as_ref()
+            && commit == head
+        {
+            return Ok(stats.clone());
+        }
+
+        let stats = load.await?;
+        *cached = Some((head.to_string(), stats.clone()));
+        Ok(stats)
+    }
+}
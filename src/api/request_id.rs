@@ -0,0 +1,74 @@
+use std::fmt::Write;
+
+use aes_gcm::aead::{OsRng, rand_core::RngCore};
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// 请求 id 请求头和响应头
+pub(super) const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 客户端提供的请求 id 的最大长度
+const MAX_LEN: usize = 128;
+
+/// 取请求头 `X-Request-Id`，缺失、过长或包含非可见字符时生成随机 id
+pub(super) fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(X_REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_LEN)
+        .filter(|id| id.bytes().all(|b| b.is_ascii_graphic()))
+        .map_or_else(generate, str::to_string)
+}
+
+/// 生成 16 字节随机数的十六进制表示
+fn generate() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().fold(String::with_capacity(32), |mut id, b| {
+        let _ = write!(id, "{b:02x}");
+        id
+    })
+}
+
+/// 为每个请求分配请求 id
+///
+/// 写回请求头供处理函数读取，记录到外层 `TraceLayer` 的请求 span，并附加到响应头。
+pub(super) async fn assign(mut req: Request, next: Next) -> Response {
+    let id = request_id(req.headers());
+    tracing::Span::current().record("request_id", id.as_str());
+
+    let value = HeaderValue::try_from(id).expect("request id is visible ASCII");
+    req.headers_mut().insert(X_REQUEST_ID, value.clone());
+
+    let mut resp = next.run(req).await;
+    resp.headers_mut().insert(X_REQUEST_ID, value);
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_id() {
+        let mut headers = HeaderMap::new();
+        let generated = request_id(&headers);
+        assert_eq!(generated.len(), 32);
+        assert!(generated.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_ne!(generated, request_id(&headers));
+
+        headers.insert(X_REQUEST_ID, HeaderValue::from_static(" abc-123 "));
+        assert_eq!(request_id(&headers), "abc-123");
+
+        // 过长或包含空格的 id 不被采用
+        headers.insert(X_REQUEST_ID, HeaderValue::from_static("a b"));
+        assert_eq!(request_id(&headers).len(), 32);
+        headers.insert(X_REQUEST_ID, "x".repeat(MAX_LEN + 1).parse().unwrap());
+        assert_eq!(request_id(&headers).len(), 32);
+    }
+}
@@ -0,0 +1,179 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::get,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::{
+    Error, Result,
+    admin::authorize,
+    git_sync::open_repo,
+    query::{ArticleMeta, Author, Category, Group, split_path},
+};
+
+use crate::{
+    content::Renderer,
+    git_sync::{
+        SnapshotArticle, TIMETRAVEL_MAX_ARTICLES, TIMETRAVEL_RENDER_CAP, snapshot_articles,
+    },
+    render::RenderCache,
+    state::AppState,
+};
+
+/// 配置时间旅行路由，只读取仓库，不读写数据库。
+///
+/// 路由包括：
+/// - `GET /timetravel/{commit}/articles`：某个提交中的公开文章列表，需要 bearer token
+/// - `GET /timetravel/{commit}/articles/{*path}`：某个提交中的单篇公开文章，需要 bearer token
+pub fn setup_route() -> Router<AppState> {
+    Router::new()
+        .route("/timetravel/{commit}/articles", get(timetravel_list))
+        .route(
+            "/timetravel/{commit}/articles/{*path}",
+            get(timetravel_article),
+        )
+}
+
+/// 某个提交中的文章元信息。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimeTravelMeta {
+    #[serde(flatten)]
+    meta: ArticleMeta,
+    /// 读取的提交
+    #[schema(example = "4db775450dee399c328935eb03fd4fcc6c60e333")]
+    commit: String,
+    /// 总为 `true`，表示内容来自历史提交而不是当前数据
+    timetravel: bool,
+}
+
+/// 某个提交中的完整文章。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimeTravelDetail {
+    #[serde(flatten)]
+    meta: TimeTravelMeta,
+    /// 渲染后的 HTML 正文
+    content: String,
+}
+
+/// 获取某个提交中的公开文章列表。
+///
+/// 按该提交中的组配置和 Front Matter 判断文章是否公开，与当前数据库无关。按更新时间从新到旧返回，
+/// 最多 [`TIMETRAVEL_MAX_ARTICLES`] 篇；`id` 总为 `null`，`comment_count` 总为 0。
+/// 摘要按需渲染并按内容缓存，单个请求最多渲染 [`TIMETRAVEL_RENDER_CAP`] 次，
+/// 超过后未缓存的摘要省略，再次请求时继续渲染。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/timetravel/{commit}/articles",
+    params(("commit" = String, Path, description = "提交 id、分支或标签")),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "提交中的公开文章", body = Vec<TimeTravelMeta>),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 404, description = "提交不存在", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn timetravel_list(
+    State(app): State<AppState>,
+    State(cache): State<RenderCache>,
+    headers: HeaderMap,
+    Path(commit): Path<String>,
+) -> Result<Json<Vec<TimeTravelMeta>>> {
+    authorize(&app, &headers)?;
+    let repo = open_repo(&app)?;
+    let commit = repo.resolve(&commit).await?;
+    let articles = snapshot_articles(&repo, &commit, app.limits()).await?;
+
+    let renderer = cache.budgeted(app.renderer(), TIMETRAVEL_RENDER_CAP);
+    let mut list = Vec::new();
+    for article in articles.into_iter().take(TIMETRAVEL_MAX_ARTICLES) {
+        let summary = &article.front_matter.summary;
+        let summary = match renderer.can_render(summary) {
+            true => Some(renderer.render(summary).await?),
+            false => None,
+        };
+        list.push(meta(&article, summary, &commit));
+    }
+    Ok(Json(list))
+}
+
+/// 获取某个提交中的单篇公开文章。
+///
+/// 路径规则与 `GET /api/articles/{path}` 相同，组 slug 按该提交中的组配置确定；文章在该提交中不存在或未公开时返回 404，
+/// 不查询旧位置的重定向。正文和摘要按需渲染并按内容缓存。
+///
+/// 需要请求头 `Authorization: Bearer <GITNOTE_ADMIN_TOKEN>`，未配置 token 时始终返回 401。
+#[utoipa::path(
+    get,
+    path = "/api/timetravel/{commit}/articles/{path}",
+    params(
+        ("commit" = String, Path, description = "提交 id、分支或标签"),
+        ("path" = String, Path, description = "文章 slug，或 `{group..}/{slug}`"),
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "提交中的文章", body = TimeTravelDetail),
+        (status = 401, description = "缺少或错误的 token", body = String, content_type = "text/plain"),
+        (status = 404, description = "提交不存在，或文章在该提交中不存在或未公开", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn timetravel_article(
+    State(app): State<AppState>,
+    State(cache): State<RenderCache>,
+    headers: HeaderMap,
+    Path((commit, path)): Path<(String, String)>,
+) -> Result<Json<TimeTravelDetail>> {
+    authorize(&app, &headers)?;
+    let repo = open_repo(&app)?;
+    let commit = repo.resolve(&commit).await?;
+    let mut articles = snapshot_articles(&repo, &commit, app.limits()).await?;
+
+    let path = path.trim_matches('/');
+    let index = articles
+        .iter()
+        .position(|a| a.slug == path)
+        .or_else(|| match split_path(path) {
+            (Some(group), slug) => articles
+                .iter()
+                .position(|a| a.slug == slug && a.group.public_id() == group),
+            (None, _) => None,
+        })
+        .ok_or(Error::NotFound)?;
+    let snapshot = articles.swap_remove(index);
+
+    let renderer = cache.budgeted(app.renderer(), TIMETRAVEL_RENDER_CAP);
+    let mut info = meta(&snapshot, None, &commit);
+    let article = snapshot.render(&renderer).await?;
+    info.meta.summary = Some(article.frontmatter.summary);
+    Ok(Json(TimeTravelDetail {
+        meta: info,
+        content: article.rendered_content,
+    }))
+}
+
+/// 由快照中的文章生成 [`TimeTravelMeta`]，`summary` 为渲染后的摘要
+fn meta(article: &SnapshotArticle, summary: Option<String>, commit: &str) -> TimeTravelMeta {
+    let group = &article.group;
+    TimeTravelMeta {
+        meta: ArticleMeta {
+            id: None,
+            slug: article.slug.to_owned(),
+            title: article.front_matter.title.to_owned(),
+            summary,
+            tags: article.tags.to_owned(),
+            group: Group::from_config(group),
+            author: Author::from_config(group),
+            category: Category::from_config(group),
+            comment_count: 0,
+            archived: group.archived,
+            updated_at: article.updated_at.timestamp_millis(),
+            created_at: article.front_matter.datetime.timestamp_millis(),
+        },
+        commit: commit.to_owned(),
+        timetravel: true,
+    }
+}
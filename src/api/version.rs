@@ -0,0 +1,101 @@
+use axum::{Json, Router, extract::State, routing::get};
+use chrono::DateTime;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{content::Renderer, state::AppState, storage::Backend};
+
+/// 无法获取构建信息时的取值
+const UNKNOWN: &str = "unknown";
+
+/// 构建时的 Git 提交，由 `build.rs` 写入
+pub const GIT_COMMIT: &str = match option_env!("GITNOTE_GIT_COMMIT") {
+    Some(commit) => commit,
+    None => UNKNOWN,
+};
+
+pub fn setup_route() -> Router<AppState> {
+    Router::new().route("/version", get(version))
+}
+
+/// 服务的版本和构建信息。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VersionInfo {
+    #[schema(example = "0.3.3")]
+    pub version: &'static str,
+    /// 构建时的 Git 提交，从源码包构建等无法获取时为 `unknown`
+    #[schema(example = "69b09b1c0e4d6f1a2b3c4d5e6f708192a3b4c5d6")]
+    pub commit: &'static str,
+    /// 构建时间，RFC 3339 格式，无法获取时为 `unknown`
+    #[schema(example = "2024-06-01T00:00:00+00:00")]
+    pub built_at: String,
+    pub features: BuildFeatures,
+    /// 非敏感配置的指纹，相同时表示各实例的配置一致，不包含数据库连接字符串和各类 token；
+    /// 未从环境变量读取配置时为 `null`
+    #[schema(example = "3f9a1c0b7e2d4a68")]
+    pub config_fingerprint: Option<String>,
+}
+
+/// 当前使用和编译时启用的功能。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BuildFeatures {
+    /// 当前渲染器的标识
+    #[schema(example = "github-gfm@2022-11-28")]
+    pub renderer: String,
+    /// 当前使用的存储后端：`postgres`、`sqlite` 或 `memory`
+    #[schema(example = "postgres")]
+    pub backend: &'static str,
+    /// 编译时启用的存储后端
+    pub backends: &'static [&'static str],
+    /// 是否在 `/api/docs` 提供 Swagger UI
+    pub swagger_ui: bool,
+}
+
+impl VersionInfo {
+    /// 当前进程的版本和构建信息
+    pub fn new(app: &AppState) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: GIT_COMMIT,
+            built_at: built_at().unwrap_or_else(|| UNKNOWN.to_string()),
+            features: BuildFeatures {
+                renderer: app.renderer().identity(),
+                backend: app.backend().kind(),
+                backends: Backend::COMPILED,
+                swagger_ui: cfg!(feature = "swagger-ui"),
+            },
+            config_fingerprint: app.config_fingerprint().map(str::to_string),
+        }
+    }
+}
+
+/// 由 `build.rs` 写入的构建时间
+fn built_at() -> Option<String> {
+    let secs = option_env!("GITNOTE_BUILD_TIMESTAMP")?.parse().ok()?;
+    DateTime::from_timestamp(secs, 0).map(|t| t.to_rfc3339())
+}
+
+/// 获取版本和构建信息。
+///
+/// 返回版本、构建时的提交和时间、启用的功能和配置指纹，不需要认证，用于确认各实例运行的构建和配置是否一致。
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    responses((status = 200, description = "版本和构建信息", body = VersionInfo))
+)]
+pub(super) async fn version(State(app): State<AppState>) -> Json<VersionInfo> {
+    Json(VersionInfo::new(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info() {
+        // 测试构建由 build.rs 写入构建时间
+        let built_at = built_at().unwrap();
+        assert!(DateTime::parse_from_rfc3339(&built_at).is_ok());
+        assert!(!GIT_COMMIT.is_empty());
+    }
+}
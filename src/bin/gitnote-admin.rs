@@ -0,0 +1,216 @@
+//! GitNote 管理命令行
+//!
+//! 读取与服务相同的环境变量，只使用各命令需要的部分：
+//!
+//! - `gitnote-admin fingerprint [<rev>]`：按仓库快照计算内容指纹，`rev` 默认为 `HEAD`，
+//!   输出 `<commit> <fingerprint>`。与 `GET /api/status` 返回的同一提交的 `content_fingerprint`
+//!   相同时，数据库与仓库一致。只使用仓库路径、时区、文章扩展名和内容限制，不连接数据库
+//! - `gitnote-admin export --out <file>`：将 `DATABASE_URL` 中的组、文章和渲染结果逐行导出，
+//!   包括未公开和加密的内容，文件名以 `.gz` 结尾时使用 gzip 压缩
+//! - `gitnote-admin import --in <file> [--truncate]`：校验格式版本后在一个事务中导入，
+//!   已存在的行被覆盖，`--truncate` 时先清空组、文章和渲染结果，任一行出错时不写入任何数据。
+//!   加密文章保持密文，需要使用与导出时相同的 `GITNOTE_CONTENT_KEY`
+//! - `gitnote-admin restore --from <file>`：用重建前写入 `GITNOTE_BACKUP_DIR` 的备份或导出文件替换现有数据，
+//!   与 `import --truncate` 相同，在一个事务中先清空组、文章和渲染结果再导入
+//! - `gitnote-admin archive prune --keep <n> [--force]`：保留最近的 `n` 个 `YYYY-Qn` 归档，删除更早归档的
+//!   `archived/<name>` 分支和 `archive/<name>` 标签，输出因此无法到达的提交数。`n` 为 0 时需要 `--force`。
+//!   只删除引用，不执行 `git gc`，不连接数据库
+
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use flate2::{Compression, read::MultiGzDecoder, write::GzEncoder};
+use gitnote::{
+    config::Config,
+    git_client::{FileClassifier, GitClient},
+    git_sync::{prune_archives, repo_fingerprint},
+    storage::{Backend, BackendStore, DumpCounts, export_dump, import_dump},
+};
+
+const USAGE: &str = "usage:
+    gitnote-admin fingerprint [<rev>]
+    gitnote-admin export --out <file>
+    gitnote-admin import --in <file> [--truncate]
+    gitnote-admin restore --from <file>
+    gitnote-admin archive prune --keep <n> [--force]";
+
+enum Command {
+    Fingerprint(String),
+    Export(PathBuf),
+    Import { file: PathBuf, truncate: bool },
+    Restore(PathBuf),
+    ArchivePrune { keep: usize, force: bool },
+}
+
+impl Command {
+    fn parse(args: &[String]) -> Option<Self> {
+        let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+        let command = match args.as_slice() {
+            ["fingerprint"] => Self::Fingerprint("HEAD".to_string()),
+            ["fingerprint", rev] => Self::Fingerprint(rev.to_string()),
+            ["export", "--out", file] => Self::Export(file.into()),
+            ["import", "--in", file] => Self::Import {
+                file: file.into(),
+                truncate: false,
+            },
+            ["import", "--in", file, "--truncate"] | ["import", "--truncate", "--in", file] => {
+                Self::Import {
+                    file: file.into(),
+                    truncate: true,
+                }
+            }
+            ["restore", "--from", file] => Self::Restore(file.into()),
+            ["archive", "prune", "--keep", keep] => Self::ArchivePrune {
+                keep: keep.parse().ok()?,
+                force: false,
+            },
+            ["archive", "prune", "--keep", keep, "--force"]
+            | ["archive", "prune", "--force", "--keep", keep] => Self::ArchivePrune {
+                keep: keep.parse().ok()?,
+                force: true,
+            },
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let Some(command) = Command::parse(&args) else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    let result = match command {
+        Command::Fingerprint(rev) => fingerprint(&rev)
+            .await
+            .map(|(commit, fingerprint)| println!("{commit} {fingerprint}")),
+        Command::Export(file) => export(&file)
+            .await
+            .map(|counts| println!("exported {}", describe(&counts))),
+        Command::Import { file, truncate } => import(&file, truncate)
+            .await
+            .map(|counts| println!("imported {}", describe(&counts))),
+        Command::Restore(file) => import(&file, true)
+            .await
+            .map(|counts| println!("restored {}", describe(&counts))),
+        Command::ArchivePrune { keep, force } => archive_prune(keep, force).await,
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 读取配置，`unused` 中的环境变量为服务必需、该命令用不到的，未设置时填入占位值
+fn config(unused: &[&str]) -> Result<Config, Box<dyn Error>> {
+    Ok(Config::from_lookup(|key| match env::var(key) {
+        Ok(value) => Some(value),
+        Err(_) if unused.contains(&key) => Some("unused".to_string()),
+        Err(_) => None,
+    })?)
+}
+
+/// 解析 `rev` 指向的提交并计算内容指纹
+async fn fingerprint(rev: &str) -> Result<(String, String), Box<dyn Error>> {
+    let config = config(&["DATABASE_URL", "GITHUB_MARKDOWN_RENDER_KEY"])?;
+
+    let repo = GitClient::open(&config.repo_path)?
+        .with_classifier(
+            FileClassifier::new(&config.article_extensions)
+                .with_readme_names(&config.readme_names)
+                .with_allowed_hidden_dirs(&config.allowed_hidden_dirs)
+                .with_attachment_extensions(&config.attachment_extensions),
+        )
+        .with_timezone(config.timezone);
+    let commit = repo.resolve(rev).await?;
+    let fingerprint = repo_fingerprint(&repo, &commit, &config.limits).await?;
+
+    Ok((commit, fingerprint))
+}
+
+/// 删除最近 `keep` 个之外的归档，逐个输出删除的引用
+async fn archive_prune(keep: usize, force: bool) -> Result<(), Box<dyn Error>> {
+    let config = config(&["DATABASE_URL", "GITHUB_MARKDOWN_RENDER_KEY"])?;
+    let repo = GitClient::open(&config.repo_path)?;
+
+    let removed = prune_archives(&repo, keep, force).await?;
+    for removal in &removed {
+        let refs = [(removal.branch, "branch"), (removal.tag, "tag")]
+            .into_iter()
+            .filter_map(|(deleted, name)| deleted.then_some(name))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        println!(
+            "removed {} ({refs}), {} commits unreachable",
+            removal.name, removal.unreachable_commits
+        );
+    }
+    println!(
+        "pruned {} archives, {} commits unreachable; run `git gc` to reclaim space",
+        removed.len(),
+        removed.iter().map(|r| r.unreachable_commits).sum::<usize>()
+    );
+    Ok(())
+}
+
+/// 连接 `DATABASE_URL` 指向的数据库，按 scheme 选择 PostgreSQL 或 SQLite
+async fn store() -> Result<BackendStore, Box<dyn Error>> {
+    let config = config(&["REPO_PATH", "GITHUB_MARKDOWN_RENDER_KEY"])?;
+    Ok(Backend::connect(&config.database_url).await?.store())
+}
+
+/// 导出到 `file`，失败时删除写了一半的文件
+async fn export(file: &Path) -> Result<DumpCounts, Box<dyn Error>> {
+    let store = store().await?;
+    let out = BufWriter::new(File::create(file)?);
+
+    let result = if is_gzip(file) {
+        let mut encoder = GzEncoder::new(out, Compression::default());
+        let counts = export_dump(&store, &mut encoder).await;
+        counts.and_then(|counts| Ok(encoder.finish()?.flush().map(|_| counts)?))
+    } else {
+        export_dump(&store, out).await
+    };
+
+    result.map_err(|e| {
+        let _ = std::fs::remove_file(file);
+        e.into()
+    })
+}
+
+/// 从 `file` 导入
+async fn import(file: &Path, truncate: bool) -> Result<DumpCounts, Box<dyn Error>> {
+    let store = store().await?;
+    let input = BufReader::new(File::open(file)?);
+    let input: Box<dyn BufRead + Send> = if is_gzip(file) {
+        Box::new(BufReader::new(MultiGzDecoder::new(input)))
+    } else {
+        Box::new(input)
+    };
+
+    Ok(import_dump(&store, input, truncate).await?)
+}
+
+fn is_gzip(file: &Path) -> bool {
+    file.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn describe(counts: &DumpCounts) -> String {
+    format!(
+        "{} groups, {} articles, {} content blobs",
+        counts.groups, counts.articles, counts.content_blobs
+    )
+}
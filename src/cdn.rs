@@ -0,0 +1,355 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
+
+use reqwest::{
+    Url,
+    header::{HeaderName, HeaderValue},
+};
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::{
+    api::encode_path,
+    content::{ArticleBuilder, GroupPath, SlugStrategy},
+    git_client::{FileKind, GitFileEntry},
+};
+
+/// 默认每个清除请求最多包含的标签数，与常见 CDN 的上限一致
+pub const DEFAULT_PURGE_BATCH: usize = 30;
+
+/// 列表、订阅源等汇总内容的缓存标签，每次同步后全部清除
+pub const COLLECTION_TAGS: [&str; 4] = [
+    "collection:articles",
+    "collection:feeds",
+    "collection:groups",
+    "collection:tags",
+];
+
+/// 文章的缓存标签 `article:<group>/<slug>`，组为组目录路径
+///
+/// 标签中的非 ASCII 字符、空格和逗号按百分号编码，保证可以放入以逗号分隔的响应头。
+pub fn article_tag(group: &str, slug: &str) -> String {
+    format!("article:{}", encode_path(&format!("{group}/{slug}")))
+}
+
+/// 组的缓存标签 `group:<group>`，组为组目录路径
+pub fn group_tag(group: &str) -> String {
+    format!("group:{}", encode_path(group))
+}
+
+/// 同步的条目影响的缓存标签，按字母顺序排列，总是包含 [`COLLECTION_TAGS`]
+///
+/// - Markdown 文件：文章和所在组的标签，由重命名产生时包括旧位置
+/// - 组配置和说明文件：所在组的标签，组内文章的响应都带有组标签，一并清除
+/// - 其他文件：不影响缓存
+///
+/// 文章所属组和 slug 按 `strategies` 确定，与同步时相同。
+pub fn purge_tags(
+    entries: &[GitFileEntry],
+    strategies: &HashMap<GroupPath, SlugStrategy>,
+) -> Vec<String> {
+    let mut tags = COLLECTION_TAGS
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<BTreeSet<_>>();
+    for entry in entries {
+        let paths = std::iter::once(entry.path()).chain(entry.renamed_from());
+        for path in paths {
+            match entry.file_kind() {
+                FileKind::Markdown => {
+                    let builder = ArticleBuilder::with_strategies(path, strategies);
+                    let article = builder.to_ref();
+                    tags.insert(article_tag(article.group, article.slug));
+                    tags.insert(group_tag(article.group));
+                }
+                FileKind::Group => {
+                    tags.insert(group_tag(GroupPath::from(path).as_str()));
+                }
+                FileKind::Readme => {
+                    let dir = path.parent().unwrap_or(path);
+                    tags.insert(group_tag(GroupPath::from(dir).as_str()));
+                }
+                FileKind::Other => (),
+            }
+        }
+    }
+    tags.into_iter().collect()
+}
+
+/// 响应中写入缓存标签的响应头名称，默认为 `Cache-Tag`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheTagHeader(HeaderName);
+
+impl CacheTagHeader {
+    pub fn name(&self) -> &HeaderName {
+        &self.0
+    }
+}
+
+impl Default for CacheTagHeader {
+    fn default() -> Self {
+        Self(HeaderName::from_static("cache-tag"))
+    }
+}
+
+impl FromStr for CacheTagHeader {
+    type Err = reqwest::header::InvalidHeaderName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Self)
+    }
+}
+
+/// 清除请求的认证头，格式为 `Name: value`，如 `Authorization: Bearer xxx`
+///
+/// 调试输出中不包含值。
+#[derive(Clone, PartialEq, Eq)]
+pub struct PurgeAuth {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl fmt::Debug for PurgeAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PurgeAuth")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for PurgeAuth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once(':')
+            .ok_or_else(|| "expected `Name: value`".to_string())?;
+        let name = name.trim().parse().map_err(|e| format!("{e}"))?;
+        let mut value = HeaderValue::from_str(value.trim()).map_err(|e| e.to_string())?;
+        value.set_sensitive(true);
+        Ok(Self { name, value })
+    }
+}
+
+/// CDN 清除配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeOptions {
+    /// 清除接口地址，以 JSON `{"tags": [...]}` POST 需要清除的标签
+    pub endpoint: Url,
+    /// 请求附带的认证头
+    pub auth: Option<PurgeAuth>,
+    /// 每个请求最多包含的标签数
+    pub batch_size: usize,
+    /// 单个请求的超时时间
+    pub timeout: Duration,
+}
+
+impl PurgeOptions {
+    /// 每个请求最多 [`DEFAULT_PURGE_BATCH`] 个标签，超时 10 秒
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            auth: None,
+            batch_size: DEFAULT_PURGE_BATCH,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PurgeRequest<'a> {
+    tags: &'a [String],
+}
+
+/// 待清除的缓存标签队列，同步提交后放入受影响的标签
+#[derive(Debug, Clone)]
+pub struct PurgeQueue {
+    tx: mpsc::UnboundedSender<Vec<String>>,
+}
+
+impl PurgeQueue {
+    /// 放入一批标签，清除在后台进行
+    pub fn enqueue(&self, tags: Vec<String>) {
+        if !tags.is_empty() && self.tx.send(tags).is_err() {
+            tracing::warn!("cdn purge worker stopped");
+        }
+    }
+}
+
+/// 启动后台任务，依次清除队列中的标签
+///
+/// 处理前合并队列中已有的各批标签并去重，再按 [`PurgeOptions::batch_size`] 分批请求。
+/// 请求失败或返回非 2xx 时只记录日志，不重试。
+pub fn spawn(options: PurgeOptions) -> PurgeQueue {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<String>>();
+    let client = reqwest::Client::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .timeout(options.timeout)
+        .build()
+        .expect("Failed to build reqwest client");
+
+    tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut tags = first.into_iter().collect::<BTreeSet<_>>();
+            while let Ok(more) = rx.try_recv() {
+                tags.extend(more);
+            }
+            let tags = tags.into_iter().collect::<Vec<_>>();
+            purge(&client, &options, &tags).await;
+        }
+    });
+
+    PurgeQueue { tx }
+}
+
+/// 按 `options` 分批请求清除 `tags`
+pub async fn purge(client: &reqwest::Client, options: &PurgeOptions, tags: &[String]) {
+    for batch in tags.chunks(options.batch_size.max(1)) {
+        let mut request = client
+            .post(options.endpoint.clone())
+            .json(&PurgeRequest { tags: batch });
+        if let Some(auth) = &options.auth {
+            request = request.header(auth.name.clone(), auth.value.clone());
+        }
+
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => tracing::debug!(tags = batch.len(), "cdn cache purged"),
+            Err(e) => tracing::warn!(error = %e, tags = ?batch, "cdn purge failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::git_client::ChangeKind;
+
+    fn entry(path: &str, kind: FileKind, change: ChangeKind) -> GitFileEntry {
+        GitFileEntry {
+            id: String::new(),
+            path: PathBuf::from(path),
+            change_kind: change,
+            file_kind: kind,
+            timestamp: DateTime::parse_from_rfc3339("2024-06-01T00:00:00+08:00").unwrap(),
+            renamed_from: None,
+            skip_reason: None,
+            old_id: None,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn test_purge_tags() {
+        let mut renamed = entry("notes/new.md", FileKind::Markdown, ChangeKind::Added);
+        renamed.renamed_from = Some("drafts/old.md".into());
+        let entries = [
+            entry("notes/a.md", FileKind::Markdown, ChangeKind::Modified),
+            entry("notes/b.md", FileKind::Markdown, ChangeKind::Deleted),
+            renamed,
+            entry(
+                "读书 笔记/.group.yaml",
+                FileKind::Group,
+                ChangeKind::Modified,
+            ),
+            entry("books/README.md", FileKind::Readme, ChangeKind::Added),
+            entry("books/cover.png", FileKind::Other, ChangeKind::Added),
+            entry("wiki/a/b/page.md", FileKind::Markdown, ChangeKind::Added),
+        ];
+        let strategies = HashMap::from([
+            (GroupPath::from("notes"), SlugStrategy::default()),
+            (GroupPath::from("wiki"), SlugStrategy::Path),
+        ]);
+
+        let tags = purge_tags(&entries, &strategies);
+        let expected = [
+            "article:drafts/old",
+            "article:notes/a",
+            "article:notes/b",
+            "article:notes/new",
+            "article:wiki/a-b-page",
+            "collection:articles",
+            "collection:feeds",
+            "collection:groups",
+            "collection:tags",
+            "group:%E8%AF%BB%E4%B9%A6%20%E7%AC%94%E8%AE%B0",
+            "group:books",
+            "group:drafts",
+            "group:notes",
+            "group:wiki",
+        ];
+        assert_eq!(tags, expected);
+
+        // 没有条目时只清除汇总内容
+        assert_eq!(purge_tags(&[], &strategies), COLLECTION_TAGS);
+    }
+
+    #[test]
+    fn test_purge_auth() {
+        let auth: PurgeAuth = "Authorization: Bearer secret".parse().unwrap();
+        assert_eq!(auth.name, "authorization");
+        assert_eq!(auth.value, "Bearer secret");
+        assert!(!format!("{auth:?}").contains("secret"));
+
+        assert!("Bearer secret".parse::<PurgeAuth>().is_err());
+        assert!("bad name: x".parse::<PurgeAuth>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_purge_batches() {
+        type Received = Arc<Mutex<Vec<(Option<String>, Vec<String>)>>>;
+        async fn receive(
+            State(received): State<Received>,
+            headers: HeaderMap,
+            Json(body): Json<serde_json::Value>,
+        ) {
+            let token = headers
+                .get("x-purge-token")
+                .map(|v| v.to_str().unwrap().to_string());
+            let tags = serde_json::from_value(body["tags"].clone()).unwrap();
+            received.lock().unwrap().push((token, tags));
+        }
+
+        let received = Received::default();
+        let router = Router::new()
+            .route("/purge", post(receive))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let mut options = PurgeOptions::new(format!("http://{addr}/purge").parse().unwrap());
+        options.auth = Some("X-Purge-Token: t0ken".parse().unwrap());
+        options.batch_size = 2;
+        let tags = ["a", "b", "c"].map(String::from);
+        purge(&reqwest::Client::new(), &options, &tags).await;
+
+        let token = Some("t0ken".to_string());
+        assert_eq!(
+            *received.lock().unwrap(),
+            [
+                (token.clone(), vec!["a".to_string(), "b".to_string()]),
+                (token, vec!["c".to_string()]),
+            ]
+        );
+
+        // 请求失败只记录日志
+        let options = PurgeOptions::new(format!("http://{addr}/missing").parse().unwrap());
+        purge(&reqwest::Client::new(), &options, &tags).await;
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use axum::http::{HeaderMap, HeaderValue};
+use reqwest::header;
+use serde_json::{Value, json};
+use tokio::task::JoinHandle;
+
+use crate::{
+    error::{Error, Result},
+    storage::{Backend, Querier, Store},
+};
+
+/// 评论数提供者
+///
+/// 根据文章 slug 批量查询评论数，查询失败的 slug 不会出现在结果中。
+pub trait CommentProvider: Send + Sync {
+    /// 单次请求最多查询的 slug 数量
+    const BATCH_SIZE: usize = 50;
+
+    /// 查询一批 slug 对应的评论数
+    fn comment_counts(
+        &self,
+        slugs: &[String],
+    ) -> impl Future<Output = Result<HashMap<String, i64>>> + Send;
+}
+
+/// 基于 GitHub Discussions 的评论数提供者
+///
+/// 按标题与 slug 完全匹配的 Discussion 统计评论数，与 giscus 的 `title` 映射方式一致。
+pub struct GithubDiscussions {
+    client: reqwest::Client,
+    repo: String,
+}
+
+impl GithubDiscussions {
+    const GITHUB_GRAPHQL_API: &str = "https://api.github.com/graphql";
+
+    /// 使用 GitHub Token 和 `owner/name` 格式的仓库名创建提供者
+    pub fn new<T: AsRef<str>>(token: T, repo: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .default_headers({
+                let mut header = HeaderMap::new();
+                header.insert(
+                    header::AUTHORIZATION,
+                    HeaderValue::from_str(&format!("Bearer {}", token.as_ref()))
+                        .expect("Failed to create Authorization header"),
+                );
+                header
+            })
+            .build()
+            .expect("Failed to build reqwest client");
+
+        Self {
+            client,
+            repo: repo.into(),
+        }
+    }
+}
+
+impl CommentProvider for GithubDiscussions {
+    async fn comment_counts(&self, slugs: &[String]) -> Result<HashMap<String, i64>> {
+        let body: Value = self
+            .client
+            .post(Self::GITHUB_GRAPHQL_API)
+            .json(&json!({ "query": build_query(&self.repo, slugs) }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let data = body
+            .get("data")
+            .ok_or(Error::Custom("GitHub GraphQL response has no data"))?;
+
+        Ok(parse_counts(slugs, data))
+    }
+}
+
+/// 构建 GraphQL 查询，每个 slug 对应一个别名为 `q{index}` 的搜索。
+fn build_query(repo: &str, slugs: &[String]) -> String {
+    let fields = slugs
+        .iter()
+        .enumerate()
+        .map(|(i, slug)| {
+            let search = Value::from(format!("repo:{} in:title {}", repo, slug));
+            format!(
+                "q{i}: search(query: {search}, type: DISCUSSION, first: 5) \
+                 {{ nodes {{ ... on Discussion {{ title comments {{ totalCount }} }} }} }}"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("query {{ {fields} }}")
+}
+
+/// 从 GraphQL 响应中提取评论数。
+///
+/// 只记录标题与 slug 完全匹配的 Discussion。没有匹配的 Discussion、别名缺失或为 null 时跳过该 slug，
+/// 搜索结果不完整（如只返回前几条）时不会把已有的评论数覆盖为 0。
+fn parse_counts(slugs: &[String], data: &Value) -> HashMap<String, i64> {
+    slugs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, slug)| {
+            let nodes = data.get(format!("q{i}"))?.get("nodes")?.as_array()?;
+            let count = nodes
+                .iter()
+                .find(|n| n.get("title").and_then(Value::as_str) == Some(slug.as_str()))?
+                .pointer("/comments/totalCount")?
+                .as_i64()?;
+            Some((slug.clone(), count))
+        })
+        .collect()
+}
+
+/// 启动后台任务，按 `interval` 周期刷新公开文章的评论数。
+pub fn spawn_refresher<P>(backend: Backend, provider: P, interval: Duration) -> JoinHandle<()>
+where
+    P: CommentProvider + 'static,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = refresh(&backend, &provider).await {
+                tracing::warn!(%e, "comment count refresh failed");
+            }
+        }
+    })
+}
+
+/// 从环境变量配置并启动评论数刷新任务，未启用时返回 [`None`]。
+///
+/// - `GITNOTE_COMMENTS_PROVIDER`：`github` 使用 GitHub Discussions，未设置或 `none` 时禁用
+/// - `GITNOTE_COMMENTS_REPO`：Discussions 所在仓库，格式为 `owner/name`
+/// - `GITNOTE_COMMENTS_INTERVAL`：刷新间隔秒数，默认 600
+///
+/// - Panics
+///
+/// 启用 `github` 但未设置 `GITNOTE_COMMENTS_REPO` 或 `GITHUB_MARKDOWN_RENDER_KEY` 时会 panic
+pub fn spawn_from_env(backend: Backend) -> Option<JoinHandle<()>> {
+    let interval = std::env::var("GITNOTE_COMMENTS_INTERVAL")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(600));
+
+    match std::env::var("GITNOTE_COMMENTS_PROVIDER").as_deref() {
+        Ok("github") => {
+            let provider = GithubDiscussions::new(
+                std::env::var("GITHUB_MARKDOWN_RENDER_KEY")
+                    .expect("GITHUB_MARKDOWN_RENDER_KEY not set"),
+                std::env::var("GITNOTE_COMMENTS_REPO").expect("GITNOTE_COMMENTS_REPO not set"),
+            );
+            Some(spawn_refresher(backend, provider, interval))
+        }
+        Ok("none") | Err(_) => None,
+        Ok(other) => {
+            tracing::warn!(provider = other, "unknown comment provider, disabled");
+            None
+        }
+    }
+}
+
+/// 分批查询评论数并写回数据库。
+///
+/// 失败的批次不会写入，对应文章保留上一次的评论数。
+async fn refresh<P: CommentProvider>(backend: &Backend, provider: &P) -> Result<()> {
+    let slugs = backend.public_slugs().await?;
+
+    let mut counts = HashMap::new();
+    for chunk in slugs.chunks(P::BATCH_SIZE) {
+        match provider.comment_counts(chunk).await {
+            Ok(c) => counts.extend(c),
+            Err(e) => tracing::warn!(%e, "comment count batch failed"),
+        }
+    }
+
+    let mut store = backend.store();
+    store.update_comment_counts(&counts);
+    store.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        content::{Article, FrontMatter, Group},
+        storage::MemoryStorage,
+    };
+
+    use super::*;
+
+    /// 每批只查询一个 slug，`down` 所在的批次失败
+    struct FlakyProvider;
+
+    impl CommentProvider for FlakyProvider {
+        const BATCH_SIZE: usize = 1;
+
+        async fn comment_counts(&self, slugs: &[String]) -> Result<HashMap<String, i64>> {
+            if slugs.iter().any(|s| s == "down") {
+                return Err(Error::Custom("remote unavailable"));
+            }
+            Ok(slugs.iter().map(|s| (s.clone(), 5)).collect())
+        }
+    }
+
+    #[test]
+    fn test_build_query_aliases_each_slug() {
+        let slugs = vec!["first".to_string(), "say \"hi\"".to_string()];
+        let query = build_query("owner/blog", &slugs);
+
+        assert!(query.starts_with("query {"));
+        assert!(query.contains(r#"q0: search(query: "repo:owner/blog in:title first""#));
+        assert!(query.contains(r#"q1: search(query: "repo:owner/blog in:title say \"hi\"""#));
+        assert!(query.contains("type: DISCUSSION"));
+    }
+
+    #[test]
+    fn test_parse_counts() {
+        let slugs = vec![
+            "matched".to_string(),
+            "no-discussion".to_string(),
+            "failed".to_string(),
+        ];
+        let data = json!({
+            "q0": { "nodes": [
+                { "title": "matched-but-longer", "comments": { "totalCount": 9 } },
+                { "title": "matched", "comments": { "totalCount": 3 } }
+            ] },
+            "q1": { "nodes": [] },
+            "q2": null
+        });
+
+        let counts = parse_counts(&slugs, &data);
+
+        assert_eq!(counts.get("matched"), Some(&3));
+        // 没有匹配的 Discussion 或查询失败的 slug 不返回，保留数据库中的旧值
+        assert_eq!(counts.get("no-discussion"), None);
+        assert_eq!(counts.get("failed"), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_keeps_counts_of_failed_batches() {
+        let memory = MemoryStorage::new();
+        let datetime = Utc
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        let article = |slug: &str| Article {
+            group: "notes".into(),
+            slug: slug.to_string(),
+            frontmatter: FrontMatter {
+                title: slug.to_string(),
+                summary: String::new(),
+                datetime,
+                tags: vec![],
+                aliases: vec![],
+                canonical: None,
+                public: None,
+                raw_html: false,
+                cover: None,
+            },
+            rendered_content: String::new(),
+            namespaced_content: None,
+            rendered_by: None,
+            updated_at: datetime,
+            source_bytes: 0,
+        };
+        let mut store = memory.store();
+        store
+            .upsert_group(
+                &Group::new(
+                    "notes/.group.yaml",
+                    "name: notes\npublic: true\n".to_string(),
+                )
+                .unwrap(),
+            )
+            .upsert_article(&article("up"))
+            .upsert_article(&article("down"))
+            .update_comment_counts(&HashMap::from([
+                ("up".to_string(), 2),
+                ("down".to_string(), 2),
+            ]));
+        store.commit().await.unwrap();
+
+        let backend = Backend::from(memory);
+        refresh(&backend, &FlakyProvider).await.unwrap();
+
+        let count = async |slug| backend.get_one(slug).await.unwrap().unwrap().comment_count;
+        assert_eq!(count("up").await, 5);
+        assert_eq!(count("down").await, 2);
+    }
+}
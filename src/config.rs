@@ -0,0 +1,891 @@
+use std::{
+    fmt::Debug, net::SocketAddr, num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration,
+};
+
+use chrono_tz::Tz;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    api::{DEFAULT_COMPRESSION_MIN_BYTES, EditUrlTemplate, HookAllowlist, PermalinkPattern},
+    cdn::{CacheTagHeader, DEFAULT_PURGE_BATCH, PurgeAuth, PurgeOptions},
+    content::{DEFAULT_PLAIN_SUMMARY_CHARS, GroupLicense},
+    git_client::FileClassifier,
+    git_sync::{BackupOptions, ContentLimits, DEFAULT_BACKUP_KEEP, DEFAULT_SYNC_LOG_ENTRIES},
+    render::{DEFAULT_COOLDOWN, DEFAULT_FAILURE_THRESHOLD},
+    state::{DEFAULT_FEED_ENTRIES, DEFAULT_SITE_URL},
+    storage::QueryLimits,
+};
+
+/// 配置读取错误
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    /// 必需的环境变量未设置或为空
+    #[error("environment variable `{0}` is not set")]
+    Missing(&'static str),
+
+    /// 环境变量的值无法解析
+    #[error("environment variable `{key}` has invalid value `{value}`: {reason}")]
+    Invalid {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+/// 应用配置
+///
+/// 启动时从环境变量读取：
+///
+/// - `REPO_PATH`：裸仓库路径（必需）
+/// - `DATABASE_URL`：数据库连接字符串（必需）
+/// - `GITHUB_MARKDOWN_RENDER_KEY`：GitHub Markdown 渲染 token（必需）
+/// - `GITNOTE_LISTEN_ADDR`：HTTP 监听地址，默认 `0.0.0.0:3000`
+/// - `GITNOTE_CONTENT_KEY`：加密组使用的 base64 编码 32 字节密钥，可选
+/// - `GITNOTE_ARTICLE_SOFT_LIMIT`：文章超过该字节数时记录警告，默认 524288
+/// - `GITNOTE_ARTICLE_HARD_LIMIT`：文章超过该字节数时跳过，默认 2097152
+/// - `GITNOTE_ARTICLE_BINARY_RATIO`：不可打印字符占比超过该值时跳过，默认 0.1
+/// - `GITNOTE_ARTICLE_EXTENSIONS`：逗号分隔的文章扩展名，默认 `md,markdown`
+/// - `GITNOTE_README_NAMES`：逗号分隔的组说明文件名，默认 `README.md`
+/// - `GITNOTE_ALLOWED_HIDDEN_DIRS`：逗号分隔的参与同步的隐藏目录名，如 `.well-known`，默认忽略所有隐藏目录
+/// - `GITNOTE_ATTACHMENT_EXTENSIONS`：逗号分隔的附件扩展名，文章中指向这些文件的链接列入附件，默认 `pdf,zip,csv,ipynb`
+/// - `GITNOTE_TIMEZONE`：IANA 时区名，如 `Asia/Shanghai`，默认 `UTC`（不跟随系统时区）
+/// - `GITNOTE_SUMMARY_PLAIN_CHARS`：文章列表中纯文本摘要的最大字符数，默认 200
+/// - `GITNOTE_SYNC_STRICT`：为 `true` 时增量同步中任一文件处理失败或有组超出配额即整体失败，默认 `false`
+/// - `GITNOTE_STRICT_METADATA`：为 `true` 时组配置和 Front Matter 中的未知字段视为错误，默认 `false`
+/// - `GITNOTE_SLOW_RENDER_MS`：单篇文章渲染超过该毫秒数时在同步摘要中提示，默认 5000
+/// - `GITNOTE_SITE_URL`：订阅源中链接使用的站点地址，默认 `http://localhost:3000`
+/// - `GITNOTE_DEFAULT_OG_IMAGE`：文章没有封面和图片时分享卡片使用的图片，绝对地址或站点上的路径，可选
+/// - `GITNOTE_DEFAULT_LICENSE`：组未声明 `license` 时文章使用的许可协议，协议 id 或 `{"id", "name", "url"}` JSON 对象，可选
+/// - `GITNOTE_FEED_ENTRIES`：订阅源的最大条目数，默认 20
+/// - `GITNOTE_NAMESPACE_ANCHORS`：为 `true` 时同步额外保存锚点 id 带文章 slug 前缀的正文，默认 `false`
+/// - `GITNOTE_HEADING_IDS`：是否将渲染结果中的标题 id 统一为 GitHub 风格的 slug，默认 `true`
+/// - `GITNOTE_AUTO_MIGRATE`：为 `true` 时启动时执行尚未执行的迁移，默认 `false`
+/// - `GITNOTE_EDIT_URL_TEMPLATE`：文章编辑链接模板，`{path}` 替换为编码后的源文件路径，`{commit}` 替换为提交，可选
+/// - `GITNOTE_PERMALINK`：旧文章地址的固定链接格式，如 `/:year/:month/:slug`，供 `/api/permalink` 解析，可选
+/// - `GITNOTE_SYNC_DEBOUNCE_SECS`：大于 0 时增量同步推送立即返回 202，静默该秒数后合并执行，默认 0
+/// - `GITNOTE_COMPRESSION`：是否按 `Accept-Encoding` 压缩响应，默认 `true`
+/// - `GITNOTE_COMPRESSION_MIN_BYTES`：小于该字节数的响应不压缩，默认 1024，最大 65535
+/// - `GITNOTE_RENDER_FAILURE_THRESHOLD`：GitHub API 连续失败该次数后熔断，不再渲染 Markdown，默认 3
+/// - `GITNOTE_RENDER_COOLDOWN_SECS`：熔断后再次尝试 GitHub API 前等待的秒数，默认 60
+/// - `GITNOTE_SYNC_LOG_SIZE`：内存中保留的最近同步记录数，为 0 时不记录，默认 50
+/// - `GITNOTE_ADMIN_TOKEN`：访问同步记录等管理接口的 bearer token，未设置时这些接口不可用
+/// - `GITNOTE_HOOK_ALLOW`：逗号分隔的 CIDR 列表，如 `127.0.0.1/32,10.0.0.0/8`，设置时只接受来源地址在列表中的仓库更新请求，可选
+/// - `GITNOTE_TRUST_PROXY`：为 `true` 时按 `X-Forwarded-For` 判断仓库更新请求的来源地址，默认 `false`
+/// - `GITNOTE_QUERY_TIMEOUT_MS`：处理请求时单次数据库查询的超时毫秒数，超时返回 503，为 0 时不限制，默认 10000
+/// - `GITNOTE_SLOW_QUERY_MS`：查询超过该毫秒数时以 WARN 记录查询和参数，为 0 时不记录，默认 1000
+/// - `GITNOTE_CACHE_TAG_HEADER`：文章、列表和订阅源响应中写入缓存标签的响应头，默认 `Cache-Tag`
+/// - `GITNOTE_CDN_PURGE_URL`：同步后 POST 受影响缓存标签的 CDN 清除接口，设置即启用，可选
+/// - `GITNOTE_CDN_PURGE_AUTH`：清除请求附带的认证头，格式为 `Name: value`，可选
+/// - `GITNOTE_CDN_PURGE_BATCH`：每个清除请求最多包含的标签数，默认 30
+/// - `GITNOTE_BACKUP_DIR`：重建前将现有数据备份到该目录，设置即启用，可选
+/// - `GITNOTE_BACKUP_KEEP`：保留最近的备份数，默认 5
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub repo_path: PathBuf,
+    pub database_url: String,
+    pub render_token: String,
+    pub listen_addr: SocketAddr,
+    pub content_key: Option<String>,
+    pub limits: ContentLimits,
+    pub article_extensions: Vec<String>,
+    pub readme_names: Vec<String>,
+    pub allowed_hidden_dirs: Vec<String>,
+    pub attachment_extensions: Vec<String>,
+    pub timezone: Tz,
+    pub plain_summary_chars: usize,
+    pub strict_sync: bool,
+    pub namespace_anchors: bool,
+    pub heading_ids: bool,
+    pub site_url: String,
+    pub default_og_image: Option<String>,
+    pub default_license: Option<GroupLicense>,
+    pub feed_entries: usize,
+    pub auto_migrate: bool,
+    pub sync_debounce: Duration,
+    pub edit_url_template: Option<EditUrlTemplate>,
+    pub permalink: Option<PermalinkPattern>,
+    pub compression: bool,
+    pub compression_min_bytes: u16,
+    pub render_failure_threshold: u32,
+    pub render_cooldown: Duration,
+    pub sync_log_size: usize,
+    pub admin_token: Option<String>,
+    pub hook_allowlist: Option<HookAllowlist>,
+    pub query_limits: QueryLimits,
+    pub cache_tag_header: CacheTagHeader,
+    pub cdn_purge: Option<PurgeOptions>,
+    pub backup: Option<BackupOptions>,
+}
+
+impl Config {
+    const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:3000";
+
+    /// 从进程环境变量读取配置
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// 从任意键值来源读取配置
+    ///
+    /// 值为空字符串时视为未设置。
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let get = |key: &'static str| lookup(key).filter(|v| !v.trim().is_empty());
+        let require = |key: &'static str| get(key).ok_or(ConfigError::Missing(key));
+
+        let listen_addr: SocketAddr = match get("GITNOTE_LISTEN_ADDR") {
+            Some(value) => {
+                value
+                    .parse()
+                    .map_err(|e: std::net::AddrParseError| ConfigError::Invalid {
+                        key: "GITNOTE_LISTEN_ADDR",
+                        reason: e.to_string(),
+                        value,
+                    })?
+            }
+            None => Self::DEFAULT_LISTEN_ADDR.parse().unwrap(),
+        };
+
+        let bytes = |key: &'static str, default: usize| -> Result<usize, ConfigError> {
+            get(key).map_or(Ok(default), |value| parse_value(key, value))
+        };
+        let defaults = ContentLimits::default();
+        let limits = ContentLimits {
+            soft_bytes: bytes("GITNOTE_ARTICLE_SOFT_LIMIT", defaults.soft_bytes)?,
+            hard_bytes: bytes("GITNOTE_ARTICLE_HARD_LIMIT", defaults.hard_bytes)?,
+            max_non_printable_ratio: get("GITNOTE_ARTICLE_BINARY_RATIO")
+                .map_or(Ok(defaults.max_non_printable_ratio), |value| {
+                    parse_value("GITNOTE_ARTICLE_BINARY_RATIO", value)
+                })?,
+            strict_metadata: get("GITNOTE_STRICT_METADATA")
+                .map_or(Ok(defaults.strict_metadata), |value| {
+                    parse_value("GITNOTE_STRICT_METADATA", value)
+                })?,
+            slow_render_ms: get("GITNOTE_SLOW_RENDER_MS")
+                .map_or(Ok(defaults.slow_render_ms), |value| {
+                    parse_value("GITNOTE_SLOW_RENDER_MS", value)
+                })?,
+        };
+        // 为 0 时不限制
+        let millis = |key: &'static str, default: Option<Duration>| {
+            get(key).map_or(Ok(default), |value| {
+                parse_value(key, value)
+                    .map(Duration::from_millis)
+                    .map(|d| (!d.is_zero()).then_some(d))
+            })
+        };
+        let query_defaults = QueryLimits::default();
+        let query_limits = QueryLimits {
+            timeout: millis("GITNOTE_QUERY_TIMEOUT_MS", query_defaults.timeout)?,
+            slow: millis("GITNOTE_SLOW_QUERY_MS", query_defaults.slow)?,
+        };
+
+        let trust_proxy = get("GITNOTE_TRUST_PROXY")
+            .map_or(Ok(false), |value| parse_value("GITNOTE_TRUST_PROXY", value))?;
+        let hook_allowlist = get("GITNOTE_HOOK_ALLOW")
+            .map(|value| parse_value::<HookAllowlist>("GITNOTE_HOOK_ALLOW", value))
+            .transpose()?
+            .map(|allowlist| allowlist.with_trust_proxy(trust_proxy));
+
+        let cdn_purge = match get("GITNOTE_CDN_PURGE_URL") {
+            Some(value) => {
+                let mut options = PurgeOptions::new(parse_value("GITNOTE_CDN_PURGE_URL", value)?);
+                // 认证头的值是凭据，错误信息中不包含
+                options.auth = get("GITNOTE_CDN_PURGE_AUTH")
+                    .map(|value| {
+                        value
+                            .parse::<PurgeAuth>()
+                            .map_err(|reason| ConfigError::Invalid {
+                                key: "GITNOTE_CDN_PURGE_AUTH",
+                                value: String::new(),
+                                reason,
+                            })
+                    })
+                    .transpose()?;
+                options.batch_size = get("GITNOTE_CDN_PURGE_BATCH")
+                    .map_or(Ok(DEFAULT_PURGE_BATCH), |value| {
+                        parse_value("GITNOTE_CDN_PURGE_BATCH", value).map(NonZeroUsize::get)
+                    })?;
+                Some(options)
+            }
+            None => None,
+        };
+
+        let backup = match get("GITNOTE_BACKUP_DIR") {
+            Some(dir) => Some(BackupOptions {
+                dir: PathBuf::from(dir.trim()),
+                keep: get("GITNOTE_BACKUP_KEEP").map_or(Ok(DEFAULT_BACKUP_KEEP), |value| {
+                    parse_value("GITNOTE_BACKUP_KEEP", value)
+                })?,
+            }),
+            None => None,
+        };
+
+        if limits.soft_bytes > limits.hard_bytes {
+            return Err(ConfigError::Invalid {
+                key: "GITNOTE_ARTICLE_SOFT_LIMIT",
+                value: limits.soft_bytes.to_string(),
+                reason: format!("greater than hard limit {}", limits.hard_bytes),
+            });
+        }
+
+        Ok(Self {
+            repo_path: require("REPO_PATH")?.into(),
+            database_url: require("DATABASE_URL")?,
+            render_token: require("GITHUB_MARKDOWN_RENDER_KEY")?,
+            listen_addr,
+            content_key: get("GITNOTE_CONTENT_KEY"),
+            limits,
+            article_extensions: get("GITNOTE_ARTICLE_EXTENSIONS")
+                .map(split_list)
+                .unwrap_or_else(|| vec!["md".to_string(), "markdown".to_string()]),
+            readme_names: get("GITNOTE_README_NAMES")
+                .map(split_list)
+                .unwrap_or_else(|| {
+                    FileClassifier::README_FILE_NAMES
+                        .map(str::to_string)
+                        .to_vec()
+                }),
+            allowed_hidden_dirs: get("GITNOTE_ALLOWED_HIDDEN_DIRS")
+                .map(split_list)
+                .unwrap_or_default(),
+            attachment_extensions: get("GITNOTE_ATTACHMENT_EXTENSIONS")
+                .map(split_list)
+                .unwrap_or_else(|| {
+                    FileClassifier::ATTACHMENT_EXTENSIONS
+                        .map(str::to_string)
+                        .to_vec()
+                }),
+            timezone: get("GITNOTE_TIMEZONE")
+                .map_or(Ok(Tz::UTC), |value| parse_value("GITNOTE_TIMEZONE", value))?,
+            plain_summary_chars: get("GITNOTE_SUMMARY_PLAIN_CHARS")
+                .map_or(Ok(DEFAULT_PLAIN_SUMMARY_CHARS), |value| {
+                    parse_value("GITNOTE_SUMMARY_PLAIN_CHARS", value)
+                })?,
+            strict_sync: get("GITNOTE_SYNC_STRICT")
+                .map_or(Ok(false), |value| parse_value("GITNOTE_SYNC_STRICT", value))?,
+            namespace_anchors: get("GITNOTE_NAMESPACE_ANCHORS").map_or(Ok(false), |value| {
+                parse_value("GITNOTE_NAMESPACE_ANCHORS", value)
+            })?,
+            heading_ids: get("GITNOTE_HEADING_IDS")
+                .map_or(Ok(true), |value| parse_value("GITNOTE_HEADING_IDS", value))?,
+            site_url: get("GITNOTE_SITE_URL").unwrap_or_else(|| DEFAULT_SITE_URL.to_string()),
+            default_og_image: get("GITNOTE_DEFAULT_OG_IMAGE").map(|v| v.trim().to_string()),
+            default_license: get("GITNOTE_DEFAULT_LICENSE")
+                .map(|value| parse_value("GITNOTE_DEFAULT_LICENSE", value))
+                .transpose()?,
+            feed_entries: get("GITNOTE_FEED_ENTRIES")
+                .map_or(Ok(DEFAULT_FEED_ENTRIES), |value| {
+                    parse_value("GITNOTE_FEED_ENTRIES", value)
+                })?,
+            auto_migrate: get("GITNOTE_AUTO_MIGRATE").map_or(Ok(false), |value| {
+                parse_value("GITNOTE_AUTO_MIGRATE", value)
+            })?,
+            sync_debounce: get("GITNOTE_SYNC_DEBOUNCE_SECS")
+                .map_or(Ok(0), |value| {
+                    parse_value("GITNOTE_SYNC_DEBOUNCE_SECS", value)
+                })
+                .map(Duration::from_secs)?,
+            edit_url_template: get("GITNOTE_EDIT_URL_TEMPLATE")
+                .map(|value| parse_value("GITNOTE_EDIT_URL_TEMPLATE", value))
+                .transpose()?,
+            permalink: get("GITNOTE_PERMALINK")
+                .map(|value| parse_value("GITNOTE_PERMALINK", value))
+                .transpose()?,
+            compression: get("GITNOTE_COMPRESSION")
+                .map_or(Ok(true), |value| parse_value("GITNOTE_COMPRESSION", value))?,
+            compression_min_bytes: get("GITNOTE_COMPRESSION_MIN_BYTES")
+                .map_or(Ok(DEFAULT_COMPRESSION_MIN_BYTES), |value| {
+                    parse_value("GITNOTE_COMPRESSION_MIN_BYTES", value)
+                })?,
+            render_failure_threshold: get("GITNOTE_RENDER_FAILURE_THRESHOLD")
+                .map_or(Ok(DEFAULT_FAILURE_THRESHOLD), |value| {
+                    parse_value("GITNOTE_RENDER_FAILURE_THRESHOLD", value)
+                })?,
+            render_cooldown: get("GITNOTE_RENDER_COOLDOWN_SECS")
+                .map_or(Ok(DEFAULT_COOLDOWN), |value| {
+                    parse_value("GITNOTE_RENDER_COOLDOWN_SECS", value).map(Duration::from_secs)
+                })?,
+            sync_log_size: get("GITNOTE_SYNC_LOG_SIZE")
+                .map_or(Ok(DEFAULT_SYNC_LOG_ENTRIES), |value| {
+                    parse_value("GITNOTE_SYNC_LOG_SIZE", value)
+                })?,
+            admin_token: get("GITNOTE_ADMIN_TOKEN").map(|v| v.trim().to_string()),
+            hook_allowlist,
+            query_limits,
+            cache_tag_header: get("GITNOTE_CACHE_TAG_HEADER")
+                .map_or(Ok(CacheTagHeader::default()), |value| {
+                    parse_value("GITNOTE_CACHE_TAG_HEADER", value)
+                })?,
+            cdn_purge,
+            backup,
+        })
+    }
+
+    /// 非敏感配置的指纹，SHA-256 的前 16 位十六进制
+    ///
+    /// 用于比较多个实例的配置是否一致。数据库连接字符串、渲染 token、内容密钥和管理 token 不参与计算，
+    /// 只记录后两者是否设置。
+    pub fn fingerprint(&self) -> String {
+        // 解构所有字段，新增字段时需要决定是否参与计算
+        let Self {
+            repo_path,
+            database_url: _,
+            render_token: _,
+            listen_addr,
+            content_key,
+            limits,
+            article_extensions,
+            readme_names,
+            allowed_hidden_dirs,
+            attachment_extensions,
+            timezone,
+            plain_summary_chars,
+            strict_sync,
+            namespace_anchors,
+            heading_ids,
+            site_url,
+            default_og_image,
+            default_license,
+            feed_entries,
+            auto_migrate,
+            sync_debounce,
+            edit_url_template,
+            permalink,
+            compression,
+            compression_min_bytes,
+            render_failure_threshold,
+            render_cooldown,
+            sync_log_size,
+            admin_token,
+            hook_allowlist,
+            query_limits,
+            cache_tag_header,
+            cdn_purge,
+            backup,
+        } = self;
+        let fields: [(&str, &dyn Debug); 32] = [
+            ("repo_path", repo_path),
+            ("listen_addr", listen_addr),
+            ("content_key", &content_key.is_some()),
+            ("limits", limits),
+            ("article_extensions", article_extensions),
+            ("readme_names", readme_names),
+            ("allowed_hidden_dirs", allowed_hidden_dirs),
+            ("attachment_extensions", attachment_extensions),
+            ("timezone", timezone),
+            ("plain_summary_chars", plain_summary_chars),
+            ("strict_sync", strict_sync),
+            ("namespace_anchors", namespace_anchors),
+            ("heading_ids", heading_ids),
+            ("site_url", site_url),
+            ("default_og_image", default_og_image),
+            ("default_license", default_license),
+            ("feed_entries", feed_entries),
+            ("auto_migrate", auto_migrate),
+            ("sync_debounce", sync_debounce),
+            ("edit_url_template", edit_url_template),
+            ("permalink", permalink),
+            ("compression", compression),
+            ("compression_min_bytes", compression_min_bytes),
+            ("render_failure_threshold", render_failure_threshold),
+            ("render_cooldown", render_cooldown),
+            ("sync_log_size", sync_log_size),
+            ("admin_token", &admin_token.is_some()),
+            ("hook_allowlist", hook_allowlist),
+            ("query_limits", query_limits),
+            ("cache_tag_header", cache_tag_header),
+            // 不包含认证头的值，见 PurgeAuth 的调试输出
+            ("cdn_purge", cdn_purge),
+            ("backup", backup),
+        ];
+
+        let mut hasher = Sha256::new();
+        for (name, value) in fields {
+            hasher.update(format!("{name}={value:?}\n"));
+        }
+        let mut digest = format!("{:x}", hasher.finalize());
+        digest.truncate(16);
+        digest
+    }
+}
+
+/// 拆分逗号分隔的列表，忽略空项
+fn split_list(value: String) -> Vec<String> {
+    value
+        .split(',')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// 解析环境变量的值，失败时返回 [`ConfigError::Invalid`]
+fn parse_value<T>(key: &'static str, value: String) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e: T::Err| ConfigError::Invalid {
+            key,
+            reason: e.to_string(),
+            value,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn lookup(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |key: &str| map.get(key).cloned()
+    }
+
+    const REQUIRED: [(&str, &str); 3] = [
+        ("REPO_PATH", "/srv/repo.git"),
+        ("DATABASE_URL", "postgres://localhost/gitnote"),
+        ("GITHUB_MARKDOWN_RENDER_KEY", "token"),
+    ];
+
+    #[test]
+    fn test_config_defaults() {
+        let config = Config::from_lookup(lookup(&REQUIRED)).unwrap();
+
+        assert_eq!(config.repo_path, PathBuf::from("/srv/repo.git"));
+        assert_eq!(config.database_url, "postgres://localhost/gitnote");
+        assert_eq!(config.render_token, "token");
+        assert_eq!(config.listen_addr, "0.0.0.0:3000".parse().unwrap());
+        assert_eq!(config.content_key, None);
+        assert_eq!(config.limits, ContentLimits::default());
+        assert_eq!(config.article_extensions, ["md", "markdown"]);
+        assert_eq!(config.readme_names, ["README.md"]);
+        assert!(config.allowed_hidden_dirs.is_empty());
+        assert_eq!(config.attachment_extensions, ["pdf", "zip", "csv", "ipynb"]);
+        assert_eq!(config.timezone, Tz::UTC);
+        assert_eq!(config.plain_summary_chars, DEFAULT_PLAIN_SUMMARY_CHARS);
+        assert!(!config.strict_sync);
+        assert!(!config.namespace_anchors);
+        assert!(config.heading_ids);
+        assert_eq!(config.site_url, DEFAULT_SITE_URL);
+        assert_eq!(config.default_og_image, None);
+        assert_eq!(config.default_license, None);
+        assert_eq!(config.feed_entries, DEFAULT_FEED_ENTRIES);
+        assert!(!config.auto_migrate);
+        assert!(config.sync_debounce.is_zero());
+        assert_eq!(config.edit_url_template, None);
+        assert_eq!(config.permalink, None);
+        assert!(config.compression);
+        assert_eq!(config.compression_min_bytes, DEFAULT_COMPRESSION_MIN_BYTES);
+        assert_eq!(config.render_failure_threshold, DEFAULT_FAILURE_THRESHOLD);
+        assert_eq!(config.render_cooldown, DEFAULT_COOLDOWN);
+        assert_eq!(config.sync_log_size, DEFAULT_SYNC_LOG_ENTRIES);
+        assert_eq!(config.admin_token, None);
+        assert_eq!(config.hook_allowlist, None);
+        assert_eq!(config.cache_tag_header, CacheTagHeader::default());
+        assert_eq!(config.cdn_purge, None);
+        assert_eq!(config.backup, None);
+    }
+
+    #[test]
+    fn test_config_fingerprint() {
+        let base = Config::from_lookup(lookup(&REQUIRED))
+            .unwrap()
+            .fingerprint();
+        assert_eq!(base.len(), 16);
+
+        // 敏感配置不影响指纹
+        let secrets = Config::from_lookup(lookup(&[
+            ("REPO_PATH", "/srv/repo.git"),
+            ("DATABASE_URL", "postgres://admin:hunter2@db/gitnote"),
+            ("GITHUB_MARKDOWN_RENDER_KEY", "ghp_other"),
+        ]))
+        .unwrap();
+        assert_eq!(secrets.fingerprint(), base);
+
+        // 只记录管理 token 是否设置
+        let with_token = |token| {
+            let mut pairs = REQUIRED.to_vec();
+            pairs.push(("GITNOTE_ADMIN_TOKEN", token));
+            Config::from_lookup(lookup(&pairs)).unwrap().fingerprint()
+        };
+        assert_ne!(with_token("a"), base);
+        assert_eq!(with_token("a"), with_token("b"));
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_FEED_ENTRIES", "50"));
+        let changed = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_ne!(changed.fingerprint(), base);
+    }
+
+    #[test]
+    fn test_config_hook_allowlist() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_HOOK_ALLOW", "127.0.0.1/32,10.0.0.0/8"));
+        pairs.push(("GITNOTE_TRUST_PROXY", "true"));
+        let allowlist = Config::from_lookup(lookup(&pairs))
+            .unwrap()
+            .hook_allowlist
+            .unwrap();
+        assert_eq!(allowlist.networks().len(), 2);
+        assert_eq!(
+            allowlist,
+            "127.0.0.1/32,10.0.0.0/8"
+                .parse::<HookAllowlist>()
+                .unwrap()
+                .with_trust_proxy(true)
+        );
+
+        pairs[3].1 = "127.0.0.1/32,10.0.0/8";
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid { key: "GITNOTE_HOOK_ALLOW", ref value, .. } if value == "127.0.0.1/32,10.0.0/8"
+        ));
+    }
+
+    #[test]
+    fn test_config_cdn() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_CACHE_TAG_HEADER", "Surrogate-Key"));
+        pairs.push(("GITNOTE_CDN_PURGE_URL", "https://cdn.example.com/purge"));
+        pairs.push(("GITNOTE_CDN_PURGE_AUTH", "Authorization: Bearer s3cret"));
+        pairs.push(("GITNOTE_CDN_PURGE_BATCH", "100"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.cache_tag_header.name(), "surrogate-key");
+        let purge = config.cdn_purge.as_ref().unwrap();
+        assert_eq!(purge.endpoint.as_str(), "https://cdn.example.com/purge");
+        assert!(purge.auth.is_some());
+        assert_eq!(purge.batch_size, 100);
+
+        // 认证头的值不参与指纹，也不出现在错误信息中
+        pairs[5].1 = "Authorization: Bearer other";
+        let other = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.fingerprint(), other.fingerprint());
+        pairs[5].1 = "Bearer s3cret";
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(!err.to_string().contains("s3cret"), "{err}");
+
+        pairs[5].1 = "Authorization: Bearer s3cret";
+        pairs[6].1 = "0";
+        assert!(matches!(
+            Config::from_lookup(lookup(&pairs)),
+            Err(ConfigError::Invalid {
+                key: "GITNOTE_CDN_PURGE_BATCH",
+                ..
+            })
+        ));
+
+        // 未设置清除接口时忽略其他清除配置
+        pairs.remove(4);
+        assert_eq!(Config::from_lookup(lookup(&pairs)).unwrap().cdn_purge, None);
+    }
+
+    #[test]
+    fn test_config_backup() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_BACKUP_DIR", "/var/backups/gitnote"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(
+            config.backup,
+            Some(BackupOptions::new("/var/backups/gitnote"))
+        );
+
+        pairs.push(("GITNOTE_BACKUP_KEEP", "2"));
+        let backup = Config::from_lookup(lookup(&pairs)).unwrap().backup.unwrap();
+        assert_eq!(backup.keep.get(), 2);
+
+        pairs[4].1 = "0";
+        assert!(matches!(
+            Config::from_lookup(lookup(&pairs)),
+            Err(ConfigError::Invalid {
+                key: "GITNOTE_BACKUP_KEEP",
+                ..
+            })
+        ));
+
+        // 未设置备份目录时不备份
+        pairs.remove(3);
+        assert_eq!(Config::from_lookup(lookup(&pairs)).unwrap().backup, None);
+    }
+
+    #[test]
+    fn test_config_sync_log() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_SYNC_LOG_SIZE", "10"));
+        pairs.push(("GITNOTE_ADMIN_TOKEN", " s3cret "));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.sync_log_size, 10);
+        assert_eq!(config.admin_token.as_deref(), Some("s3cret"));
+
+        pairs[3].1 = "-1";
+        assert!(matches!(
+            Config::from_lookup(lookup(&pairs)),
+            Err(ConfigError::Invalid {
+                key: "GITNOTE_SYNC_LOG_SIZE",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_config_compression() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_COMPRESSION", "false"));
+        pairs.push(("GITNOTE_COMPRESSION_MIN_BYTES", "256"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert!(!config.compression);
+        assert_eq!(config.compression_min_bytes, 256);
+
+        pairs.pop();
+        pairs.push(("GITNOTE_COMPRESSION_MIN_BYTES", "100000"));
+        assert!(matches!(
+            Config::from_lookup(lookup(&pairs)),
+            Err(ConfigError::Invalid {
+                key: "GITNOTE_COMPRESSION_MIN_BYTES",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_config_render_breaker() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_RENDER_FAILURE_THRESHOLD", "5"));
+        pairs.push(("GITNOTE_RENDER_COOLDOWN_SECS", "120"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.render_failure_threshold, 5);
+        assert_eq!(config.render_cooldown, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_config_default_license() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_DEFAULT_LICENSE", "CC-BY-4.0"));
+        let license = Config::from_lookup(lookup(&pairs))
+            .unwrap()
+            .default_license
+            .unwrap();
+        assert_eq!((license.id.as_str(), license.url), ("CC-BY-4.0", None));
+
+        pairs[3].1 =
+            r#"{"id": "CC-BY-4.0", "url": "https://creativecommons.org/licenses/by/4.0/"}"#;
+        let license = Config::from_lookup(lookup(&pairs))
+            .unwrap()
+            .default_license
+            .unwrap();
+        assert_eq!(license.name, "CC-BY-4.0");
+        assert!(license.url.is_some());
+
+        pairs[3].1 = "all rights reserved";
+        assert!(matches!(
+            Config::from_lookup(lookup(&pairs)),
+            Err(ConfigError::Invalid {
+                key: "GITNOTE_DEFAULT_LICENSE",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_config_timezone() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_TIMEZONE", "Asia/Shanghai"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.timezone, Tz::Asia__Shanghai);
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_TIMEZONE", "Mars/Olympus"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_TIMEZONE",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_article_extensions() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_ARTICLE_EXTENSIONS", "md, markdown,mdx,,txt"));
+
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.article_extensions, ["md", "markdown", "mdx", "txt"]);
+    }
+
+    #[test]
+    fn test_config_file_filters() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_README_NAMES", "README.md, index.md,"));
+        pairs.push(("GITNOTE_ALLOWED_HIDDEN_DIRS", ".well-known"));
+        pairs.push(("GITNOTE_ATTACHMENT_EXTENSIONS", "pdf, epub"));
+
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(config.readme_names, ["README.md", "index.md"]);
+        assert_eq!(config.allowed_hidden_dirs, [".well-known"]);
+        assert_eq!(config.attachment_extensions, ["pdf", "epub"]);
+    }
+
+    #[test]
+    fn test_config_missing_value() {
+        for (missing, _) in REQUIRED {
+            let pairs = REQUIRED
+                .iter()
+                .copied()
+                .filter(|(k, _)| *k != missing)
+                .collect::<Vec<_>>();
+
+            let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+            assert_eq!(err, ConfigError::Missing(missing));
+            assert!(err.to_string().contains(missing));
+        }
+    }
+
+    #[test]
+    fn test_config_empty_value_is_missing() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs[0].1 = "  ";
+
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert_eq!(err, ConfigError::Missing("REPO_PATH"));
+    }
+
+    #[test]
+    fn test_config_invalid_listen_addr() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_LISTEN_ADDR", "not-an-addr"));
+
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid { key: "GITNOTE_LISTEN_ADDR", ref value, .. } if value == "not-an-addr"
+        ));
+        assert!(err.to_string().contains("GITNOTE_LISTEN_ADDR"));
+    }
+
+    #[test]
+    fn test_config_query_limits() {
+        let config = Config::from_lookup(lookup(&REQUIRED)).unwrap();
+        assert_eq!(config.query_limits, QueryLimits::default());
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_QUERY_TIMEOUT_MS", "2500"));
+        pairs.push(("GITNOTE_SLOW_QUERY_MS", "0"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(
+            config.query_limits,
+            QueryLimits {
+                timeout: Some(Duration::from_millis(2500)),
+                slow: None,
+            }
+        );
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_QUERY_TIMEOUT_MS", "10s"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_QUERY_TIMEOUT_MS",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_permalink() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_PERMALINK", "/:year/:month/:slug"));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert!(config.permalink.is_some());
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_PERMALINK", "/:year/:month"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_PERMALINK",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_edit_url_template() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push((
+            "GITNOTE_EDIT_URL_TEMPLATE",
+            "https://git.example.com/{path}",
+        ));
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert!(config.edit_url_template.is_some());
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_EDIT_URL_TEMPLATE", "https://git.example.com/"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_EDIT_URL_TEMPLATE",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_config_limits() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_ARTICLE_SOFT_LIMIT", "1024"));
+        pairs.push(("GITNOTE_ARTICLE_HARD_LIMIT", "4096"));
+        pairs.push(("GITNOTE_ARTICLE_BINARY_RATIO", "0.2"));
+        pairs.push(("GITNOTE_STRICT_METADATA", "true"));
+        pairs.push(("GITNOTE_SLOW_RENDER_MS", "3000"));
+
+        let config = Config::from_lookup(lookup(&pairs)).unwrap();
+        assert_eq!(
+            config.limits,
+            ContentLimits {
+                soft_bytes: 1024,
+                hard_bytes: 4096,
+                max_non_printable_ratio: 0.2,
+                strict_metadata: true,
+                slow_render_ms: 3000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_invalid_limits() {
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_ARTICLE_HARD_LIMIT", "2MB"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_ARTICLE_HARD_LIMIT",
+                ..
+            }
+        ));
+
+        let mut pairs = REQUIRED.to_vec();
+        pairs.push(("GITNOTE_ARTICLE_SOFT_LIMIT", "4096"));
+        pairs.push(("GITNOTE_ARTICLE_HARD_LIMIT", "1024"));
+        let err = Config::from_lookup(lookup(&pairs)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "GITNOTE_ARTICLE_SOFT_LIMIT",
+                ..
+            }
+        ));
+    }
+}
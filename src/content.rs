@@ -1,7 +1,40 @@
 mod articles;
+mod datetime;
+mod fields;
 mod group;
+mod hints;
+mod include;
+mod schema;
+mod simhash;
+mod social;
+mod sort_key;
+mod summary;
+mod tags;
 
 pub use self::{
-    articles::{Article, ArticleBuilder, ArticleRef, NoContent, Renderer},
-    group::{Group, GroupKind},
+    articles::{
+        Article, ArticleBuilder, ArticleRef, Content, FrontMatter, NoContent, RAW_HTML_RENDERER,
+        Renderer,
+    },
+    datetime::{DATETIME_FORMATS, DateTimeError},
+    fields::{MetadataError, UnknownField, suggest, unknown_fields},
+    group::{
+        Group, GroupAuthor, GroupCategory, GroupKind, GroupLicense, GroupPath, GroupRetain,
+        GroupSettings, RawHtml, RawHtmlAllowlist, SlugStrategy, author_id,
+    },
+    hints::front_matter_suggestions,
+    include::{Expanded, IncludeError, MAX_INCLUDE_DEPTH, expand_includes},
+    schema::{FieldType, FrontMatterSchema, SchemaViolation},
+    simhash::{
+        MIN_SIMHASH_TOKENS, NEAR_DUPLICATE_BITS, distance, near_duplicate_clusters, simhash,
+    },
+    social::{SOCIAL_DESCRIPTION_CHARS, SocialMeta},
+    sort_key::{OTHER_INITIAL, title_initial, title_sort_key},
+    summary::{DEFAULT_PLAIN_SUMMARY_CHARS, TRUNCATION_MARKER, plain_text, truncate_text},
+    tags::{
+        MAX_TAG_LEN, TAG_SEPARATOR, TagError, TagNode, merge_tags, normalize_tag, normalize_tags,
+        tag_matches, tag_tree,
+    },
 };
+
+pub(crate) use self::summary::decode_entity;
@@ -1,25 +1,258 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
-use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
-use serde::{Deserialize, Deserializer};
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use reqwest::Url;
+use serde::Deserialize;
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    render::clean_with_allowlist,
+};
 
-#[derive(Debug, Deserialize)]
+use super::{
+    FrontMatterSchema, GroupPath, MetadataError, RawHtml, SchemaViolation, SlugStrategy, TagError,
+    UnknownField, datetime::RawDateTime, front_matter_suggestions, normalize_tags, unknown_fields,
+};
+
+/// UTF-8 BOM，部分 Windows 编辑器保存时添加在文件开头
+const BOM: char = '\u{feff}';
+
+/// 原样输出 HTML 正文的文章记录的渲染器标识，重新渲染时跳过这些文章
+pub const RAW_HTML_RENDERER: &str = "raw-html";
+
+/// 由 `render` 和 `format` 判断正文是否为 HTML，`format` 只能为 `markdown` 或 `html`
+fn is_raw_html(render: Option<bool>, format: Option<&str>) -> std::result::Result<bool, String> {
+    let html = match format.map(str::trim) {
+        None => false,
+        Some(f) if f.eq_ignore_ascii_case("markdown") => false,
+        Some(f) if f.eq_ignore_ascii_case("html") => true,
+        Some(f) => return Err(format!("`format` must be `markdown` or `html`, got `{f}`")),
+    };
+    Ok(html || render == Some(false))
+}
+
+#[derive(Debug, PartialEq)]
 pub struct FrontMatter {
     pub title: String,
     pub summary: String,
-    #[serde(deserialize_with = "parse_to_local")]
-    pub datetime: DateTime<Local>,
+    pub datetime: DateTime<FixedOffset>,
     pub tags: Vec<String>,
+    /// 文章曾经使用过的位置，格式为 `group/slug` 或 `slug`
+    pub aliases: Vec<String>,
+    /// 转载文章的原文地址，见 [`FrontMatter::canonical_url`]
+    pub canonical: Option<String>,
+    /// 覆盖所在组的 `public`，为 [`None`] 时与所在组相同
+    pub public: Option<bool>,
+    /// 正文是否为 HTML，由 `render: false` 或 `format: html` 设置，见 [`ArticleBuilder::raw_html`]
+    pub raw_html: bool,
+    /// 封面图片地址，分享链接时展示，见 [`SocialMeta`](super::SocialMeta)
+    pub cover: Option<String>,
+}
+
+impl FrontMatter {
+    /// Front Matter 中可用的字段
+    pub const FIELDS: &[&str] = &[
+        "title",
+        "summary",
+        "datetime",
+        "tags",
+        "aliases",
+        "canonical",
+        "public",
+        "render",
+        "format",
+        "cover",
+    ];
+
+    /// `canonical` 的最大长度
+    pub const MAX_CANONICAL_LEN: usize = 2048;
+
+    /// 校验 `canonical` 字段，返回去掉首尾空白的地址
+    ///
+    /// 只接受带主机名的 `http` 或 `https` 绝对地址，长度不超过 [`FrontMatter::MAX_CANONICAL_LEN`]。
+    pub fn canonical_url(url: &str) -> std::result::Result<String, String> {
+        let url = url.trim();
+        if url.len() > Self::MAX_CANONICAL_LEN {
+            return Err(format!(
+                "`canonical` is longer than {} characters",
+                Self::MAX_CANONICAL_LEN
+            ));
+        }
+
+        let parsed =
+            Url::parse(url).map_err(|e| format!("`canonical` is not an absolute URL: {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "`canonical` must use http or https, got `{}:`",
+                parsed.scheme()
+            ));
+        }
+        if parsed.host_str().is_none_or(str::is_empty) {
+            return Err("`canonical` has no host".to_string());
+        }
+
+        Ok(url.to_string())
+    }
+
+    /// 内容是否以 Front Matter 开头，忽略开头的 UTF-8 BOM
+    pub fn is_present(markdown: &str) -> bool {
+        markdown
+            .trim_start_matches(BOM)
+            .trim_start()
+            .starts_with("---")
+    }
+
+    /// 内容中的 UTF-8 BOM 和 `\r\n` 换行，没有时返回 `None`
+    ///
+    /// 解析 Front Matter 时兼容这两种情况，只用于在同步报告中提醒作者修改编辑器的保存设置。
+    pub fn style_warning(markdown: &str) -> Option<String> {
+        let issues = [
+            (markdown.starts_with(BOM), "a UTF-8 BOM"),
+            (markdown.contains("\r\n"), "CRLF line endings"),
+        ]
+        .into_iter()
+        .filter_map(|(found, issue)| found.then_some(issue))
+        .collect::<Vec<_>>();
+        if issues.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "file has {}, save it as UTF-8 without BOM and with LF line endings",
+            issues.join(" and ")
+        ))
+    }
+
+    /// 去掉开头的 Front Matter，返回正文；没有 Front Matter 或没有结束分隔符时返回原内容
+    pub fn strip(markdown: &str) -> &str {
+        ArticleBuilder::<Content>::extract_front_matter_and_body(markdown)
+            .map_or(markdown, |(_, body)| body)
+    }
+
+    /// Front Matter 中不在 [`FrontMatter::FIELDS`] 中的字段，无法解析时返回空
+    pub fn unknown_fields(markdown: &str) -> Vec<UnknownField> {
+        let Ok((yaml, _)) = ArticleBuilder::<Content>::extract_front_matter_and_body(markdown)
+        else {
+            return Vec::new();
+        };
+        let keys = serde_yaml::from_str::<serde_yaml::Mapping>(yaml)
+            .map(|m| {
+                m.keys()
+                    .filter_map(|k| k.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        unknown_fields(keys.iter().map(String::as_str), Self::FIELDS)
+    }
+
+    /// Front Matter 不符合 `schema` 的项，无法解析时返回空，解析错误在构建文章时报告
+    pub fn schema_violations(markdown: &str, schema: &FrontMatterSchema) -> Vec<SchemaViolation> {
+        ArticleBuilder::<Content>::extract_front_matter_and_body(markdown)
+            .ok()
+            .and_then(|(yaml, _)| serde_yaml::from_str::<serde_yaml::Mapping>(yaml).ok())
+            .map(|fields| schema.check(&fields))
+            .unwrap_or_default()
+    }
+
+    /// 是否设置了 `render: false` 或 `format: html`，无法解析时返回 `false`
+    ///
+    /// 用于在渲染前确定是否需要查找所在组的 `allow_raw_html`。
+    pub fn requests_raw_html(markdown: &str) -> bool {
+        #[derive(Deserialize)]
+        struct Format {
+            #[serde(default)]
+            render: Option<bool>,
+            #[serde(default)]
+            format: Option<String>,
+        }
+
+        ArticleBuilder::<Content>::extract_front_matter_and_body(markdown)
+            .ok()
+            .and_then(|(yaml, _)| serde_yaml::from_str::<Format>(yaml).ok())
+            .is_some_and(|f| is_raw_html(f.render, f.format.as_deref()).unwrap_or(false))
+    }
+
+    /// `tags` 中规范化后被丢弃的标签，无法解析时返回空
+    ///
+    /// 解析时这些标签已被丢弃，只用于在同步报告中逐个提醒作者。
+    pub fn rejected_tags(markdown: &str) -> Vec<TagError> {
+        #[derive(Deserialize)]
+        struct Tags {
+            #[serde(default)]
+            tags: Vec<String>,
+        }
+
+        ArticleBuilder::<Content>::extract_front_matter_and_body(markdown)
+            .ok()
+            .and_then(|(yaml, _)| serde_yaml::from_str::<Tags>(yaml).ok())
+            .map(|t| normalize_tags(t.tags).1)
+            .unwrap_or_default()
+    }
+}
+
+/// 反序列化得到的 Front Matter，`datetime` 按时区解析后转换为 [`FrontMatter`]
+#[derive(Debug, Deserialize)]
+struct RawFrontMatter {
+    title: String,
+    summary: String,
+    datetime: RawDateTime,
+    tags: Vec<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    canonical: Option<String>,
+    #[serde(default)]
+    public: Option<bool>,
+    #[serde(default)]
+    render: Option<bool>,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    cover: Option<String>,
+}
+
+impl RawFrontMatter {
+    fn in_zone(self, tz: Tz) -> std::result::Result<FrontMatter, serde_yaml::Error> {
+        use serde::de::Error as _;
+
+        Ok(FrontMatter {
+            raw_html: is_raw_html(self.render, self.format.as_deref())
+                .map_err(serde_yaml::Error::custom)?,
+            datetime: self
+                .datetime
+                .in_zone(tz)
+                .map_err(serde_yaml::Error::custom)?,
+            title: self.title,
+            summary: self.summary,
+            tags: normalize_tags(self.tags).0,
+            aliases: self.aliases,
+            canonical: self.canonical,
+            public: self.public,
+            cover: self
+                .cover
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
+        })
+    }
 }
 
 #[derive(Debug)]
 pub struct Article {
-    pub group: String,
+    pub group: GroupPath,
     pub slug: String,
     pub frontmatter: FrontMatter,
     pub rendered_content: String,
+    /// 锚点带命名空间的正文，只在渲染流水线启用 `namespace_anchors` 时生成，加密文章始终为 [`None`]
+    pub namespaced_content: Option<String>,
+    /// 降级渲染时实际使用的渲染器标识，原样输出 HTML 正文时为 [`RAW_HTML_RENDERER`]，
+    /// 与 [`Renderer::identity`] 相同或使用已渲染的结果时为 [`None`]
+    pub rendered_by: Option<String>,
+    /// 最后修改时间，同步时取最近一次修改该文件的提交时间，未设置时与 Front Matter 的 `datetime` 相同
+    pub updated_at: DateTime<FixedOffset>,
+    /// 原文的字节数，计入所在组的大小统计
+    pub source_bytes: usize,
 }
 
 #[derive(Debug)]
@@ -32,8 +265,13 @@ pub struct NoContent;
 pub struct Content(String);
 
 pub struct ArticleBuilder<T> {
-    group: String,
+    /// 文章文件路径，用于错误信息
+    path: String,
+    group: GroupPath,
     slug: String,
+    timezone: Tz,
+    updated_at: Option<DateTime<FixedOffset>>,
+    raw_html: RawHtml,
     content: T,
 }
 
@@ -42,16 +280,77 @@ pub trait Renderer: Send + Sync {
         &self,
         content: T,
     ) -> impl std::future::Future<Output = Result<String>>;
+
+    /// 渲染器标识，由名称和版本组成，如 `github-gfm@2022-11-28`
+    ///
+    /// 同步时记录在每篇文章上，标识不同的渲染器输出的 HTML 方言可能不同。
+    fn identity(&self) -> String;
+
+    /// 渲染器版本，同样的内容在同一版本下渲染结果相同
+    ///
+    /// 同步时复用已存储的同版本渲染结果，返回 [`None`] 时总是重新渲染。
+    fn version(&self) -> Option<String> {
+        None
+    }
+
+    /// 是否额外生成锚点带命名空间的正文，见 [`namespace_anchors`](crate::render::namespace_anchors)
+    fn namespace_anchors(&self) -> bool {
+        false
+    }
+
+    /// 渲染并返回实际生成结果的渲染器标识
+    ///
+    /// 组合多个渲染器时每次渲染的来源可能不同，如 [`FallbackRenderer`](crate::render::FallbackRenderer)
+    /// 降级时返回备用渲染器的标识。默认为 [`Renderer::identity`]。
+    fn render_tracked<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<(String, String)>> {
+        let identity = self.identity();
+        let rendered = self.render(content);
+        async move { Ok((rendered.await?, identity)) }
+    }
+}
+
+impl Article {
+    pub fn to_ref<'a>(&'a self) -> ArticleRef<'a> {
+        ArticleRef {
+            slug: &self.slug,
+            group: self.group.as_str(),
+        }
+    }
+
+    /// 解析 Front Matter 中声明的 `aliases`。
+    ///
+    /// 不含 `/` 的别名视为同组下的旧 slug，末尾的 `.md` 扩展名会被忽略。
+    pub fn aliases(&self) -> Vec<ArticleRef<'_>> {
+        self.frontmatter
+            .aliases
+            .iter()
+            .map(|alias| {
+                let alias = alias.trim().trim_matches('/');
+                let alias = alias.strip_suffix(".md").unwrap_or(alias);
+                match alias.rsplit_once('/') {
+                    Some((group, slug)) => ArticleRef {
+                        slug,
+                        group: group.trim_matches('/'),
+                    },
+                    None => ArticleRef {
+                        slug: alias,
+                        group: self.group.as_str(),
+                    },
+                }
+            })
+            .filter(|r| !(r.slug.is_empty() || r.slug == self.slug && self.group == r.group))
+            .collect()
+    }
 }
 
 impl ArticleBuilder<NoContent> {
     pub fn new(path: impl AsRef<Path>) -> Self {
         // 去除文件扩展名
         let path = path.as_ref();
-        let group = path
-            .parent()
-            .map(|p| p.to_string_lossy().trim_matches('/').to_string())
-            .unwrap_or_default();
+        let group = path.parent().map(GroupPath::from).unwrap_or_default();
 
         let slug = path
             .file_stem()
@@ -59,30 +358,106 @@ impl ArticleBuilder<NoContent> {
             .unwrap_or_default();
 
         Self {
+            path: path.display().to_string(),
             group,
             slug,
+            timezone: Tz::UTC,
+            updated_at: None,
+            raw_html: RawHtml::Denied,
             content: NoContent,
         }
     }
 
+    /// 按各组的 [`SlugStrategy`] 确定文章所属组和 slug，`strategies` 的键为组 id。
+    ///
+    /// 从文件所在目录向上查找最近的已知组，该组为上级目录且使用 [`SlugStrategy::Path`] 时，
+    /// 文章归属该组，slug 为相对组目录的路径；否则与 [`ArticleBuilder::new`] 相同。
+    pub fn with_strategies(
+        path: impl AsRef<Path>,
+        strategies: &HashMap<GroupPath, SlugStrategy>,
+    ) -> Self {
+        let path = path.as_ref();
+        let root = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::ancestors)
+            .find_map(|dir| {
+                let id = GroupPath::from(dir);
+                strategies.get(&id).map(|s| (dir, id, *s))
+            });
+
+        match root {
+            Some((dir, group, SlugStrategy::Path)) if Some(dir) != path.parent() => {
+                let slug = path
+                    .strip_prefix(dir)
+                    .unwrap_or(path)
+                    .with_extension("")
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("-");
+
+                Self {
+                    path: path.display().to_string(),
+                    group,
+                    slug,
+                    timezone: Tz::UTC,
+                    updated_at: None,
+                    raw_html: RawHtml::Denied,
+                    content: NoContent,
+                }
+            }
+            _ => Self::new(path),
+        }
+    }
+
     pub fn to_ref<'a>(&'a self) -> ArticleRef<'a> {
         ArticleRef {
             slug: &self.slug,
-            group: &self.group,
+            group: self.group.as_str(),
         }
     }
 
     pub fn content(self, md_content: impl Into<String>) -> ArticleBuilder<Content> {
         ArticleBuilder {
+            path: self.path,
             group: self.group,
             slug: self.slug,
+            timezone: self.timezone,
+            updated_at: self.updated_at,
+            raw_html: self.raw_html,
             content: Content(md_content.into()),
         }
     }
 }
 
 impl<T> ArticleBuilder<T> {
-    pub fn group(&self) -> &str {
+    /// 设置解析 Front Matter 中不带偏移的时间使用的时区，默认 UTC
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// 设置文章的最后修改时间，见 [`Article::updated_at`]
+    pub fn updated_at(mut self, updated_at: DateTime<FixedOffset>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// 设置所在组原样输出 HTML 正文的方式，默认 [`RawHtml::Denied`]
+    ///
+    /// 只对设置了 `render: false` 或 `format: html` 的文章生效，见 [`ArticleBuilder::build_with_renderer`]。
+    pub fn raw_html(mut self, raw_html: RawHtml) -> Self {
+        self.raw_html = raw_html;
+        self
+    }
+
+    /// 所在组原样输出 HTML 正文的方式
+    pub fn raw_html_policy(&self) -> &RawHtml {
+        &self.raw_html
+    }
+
+    pub fn group(&self) -> &GroupPath {
         &self.group
     }
 
@@ -94,87 +469,146 @@ impl<T> ArticleBuilder<T> {
 impl ArticleBuilder<Content> {
     fn parse_content(&self) -> Result<(FrontMatter, String)> {
         let (toml_str, body_str) = Self::extract_front_matter_and_body(&self.content.0)?;
-        let front_matter = Self::parse_front_matter(toml_str)?;
+        let mut front_matter = Self::parse_front_matter(toml_str, self.timezone)?;
+        front_matter.canonical = front_matter
+            .canonical
+            .as_deref()
+            .map(FrontMatter::canonical_url)
+            .transpose()
+            .map_err(|reason| MetadataError::Invalid {
+                path: self.path.clone(),
+                reason,
+            })?;
         Ok((front_matter, body_str.to_string()))
     }
 
     /// 从原始 Markdown 内容中提取 Front Matter 字符串和正文。
+    ///
+    /// 忽略开头的 UTF-8 BOM。分隔符独占一行，允许带有行尾空白，因此 `\r\n` 换行的文件同样可以识别；
+    /// 返回的正文是原内容的一部分，不改写换行。
     fn extract_front_matter_and_body(content: &str) -> Result<(&str, &str)> {
         const DELIM: &str = "---";
 
-        let content = content.trim_start();
+        let content = content.trim_start_matches(BOM).trim_start();
 
-        if !content.starts_with(DELIM) {
+        let (first, rest) = content.split_once('\n').unwrap_or((content, ""));
+        if first.trim_end() != DELIM {
             return Err(Error::Custom("Missing required YAML front matter"));
         }
 
-        let rest = &content[DELIM.len()..];
-        let end_pos = rest.find(DELIM).ok_or_else(|| {
-            Error::Custom("Front matter does not terminate with expected delimiter ---")
-        })?;
+        let mut end = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == DELIM {
+                let yaml_str = &rest[..end];
+                let body_str = rest[end + line.len()..].trim_start();
+                return Ok((yaml_str.trim(), body_str));
+            }
+            end += line.len();
+        }
 
-        let yaml_str = &rest[..end_pos];
-        let body_str = &rest[end_pos + DELIM.len()..].trim_start();
+        Err(Error::Custom(
+            "Front matter does not terminate with expected delimiter ---",
+        ))
+    }
 
-        Ok((yaml_str.trim(), body_str))
+    /// 解析 YAML 格式的 Front Matter 字符串，不带偏移的时间按 `tz` 解析。
+    ///
+    /// 解析失败时返回带修改建议的 [`MetadataError::Syntax`]，见 [`front_matter_suggestions`]。
+    fn parse_front_matter(yaml_str: &str, tz: Tz) -> Result<FrontMatter> {
+        serde_yaml::from_str::<RawFrontMatter>(yaml_str)
+            .and_then(|raw| raw.in_zone(tz))
+            .map_err(|error| {
+                let suggestions = front_matter_suggestions(&error, yaml_str);
+                MetadataError::Syntax { error, suggestions }.into()
+            })
     }
 
-    /// 解析 YAML 格式的 Front Matter 字符串。
-    fn parse_front_matter(yaml_str: &str) -> Result<FrontMatter> {
-        serde_yaml::from_str(yaml_str).map_err(Into::into)
+    /// 只解析 Front Matter，摘要保持 Markdown 原文
+    pub fn front_matter(&self) -> Result<FrontMatter> {
+        self.parse_content().map(|(front_matter, _)| front_matter)
     }
 
+    /// 解析 Front Matter 并渲染正文和摘要
+    ///
+    /// 文章设置了 `render: false` 或 `format: html` 且所在组允许时，正文不经过渲染器，按 [`RawHtml`] 原样保存或清理后保存，
+    /// 非空的摘要照常渲染，[`Article::rendered_by`] 为 [`RAW_HTML_RENDERER`]。所在组不允许时照常渲染正文。
     pub async fn build_with_renderer<R: Renderer>(self, renderer: &R) -> Result<Article> {
         let (mut frontmatter, body) = self.parse_content()?;
+        let source_bytes = self.content.0.len();
+
+        if frontmatter.raw_html && self.raw_html != RawHtml::Denied {
+            let rendered_content = match &self.raw_html {
+                RawHtml::Sanitized(allowlist) => {
+                    clean_with_allowlist(&body, &allowlist.tags, &allowlist.attributes)
+                }
+                _ => body,
+            };
+            if !frontmatter.summary.trim().is_empty() {
+                frontmatter.summary = renderer.render(&frontmatter.summary).await?;
+            }
+
+            return Ok(Article {
+                group: self.group,
+                slug: self.slug,
+                updated_at: self.updated_at.unwrap_or(frontmatter.datetime),
+                frontmatter,
+                rendered_content,
+                namespaced_content: None,
+                rendered_by: Some(RAW_HTML_RENDERER.to_string()),
+                source_bytes,
+            });
+        }
 
-        let (rendered_content, rendered_summary) =
-            tokio::try_join!(renderer.render(body), renderer.render(&frontmatter.summary))?;
+        let ((rendered_content, content_by), (rendered_summary, summary_by)) = tokio::try_join!(
+            renderer.render_tracked(body),
+            renderer.render_tracked(&frontmatter.summary)
+        )?;
+        let identity = renderer.identity();
+        let rendered_by = [content_by, summary_by]
+            .into_iter()
+            .find(|by| *by != identity);
 
         frontmatter.summary = rendered_summary;
 
         Ok(Article {
             group: self.group,
             slug: self.slug,
+            updated_at: self.updated_at.unwrap_or(frontmatter.datetime),
             frontmatter,
             rendered_content,
+            namespaced_content: None,
+            rendered_by,
+            source_bytes,
         })
     }
-}
 
-fn parse_to_local<'de, D>(deserializer: D) -> std::result::Result<DateTime<Local>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-
-    for fmt in &["%Y-%m-%d %H:%M:%S", "%Y/%m/%d %H:%M:%S"] {
-        if let Ok(naive_dt) = NaiveDateTime::parse_from_str(&s, fmt) {
-            return Local
-                .from_local_datetime(&naive_dt)
-                .single()
-                .ok_or_else(|| serde::de::Error::custom("本地时间不明确"));
-        }
-    }
+    /// 使用已渲染的正文和摘要构建文章，只解析 Front Matter
+    pub fn build_with_rendered(
+        self,
+        rendered_content: String,
+        rendered_summary: String,
+    ) -> Result<Article> {
+        let (mut frontmatter, _) = self.parse_content()?;
+        let source_bytes = self.content.0.len();
+        frontmatter.summary = rendered_summary;
 
-    for fmt in &["%Y-%m-%d", "%Y/%m/%d"] {
-        if let Ok(date) = NaiveDate::parse_from_str(&s, fmt) {
-            if let Some(naive_dt) = date.and_hms_opt(0, 0, 0) {
-                return Local
-                    .from_local_datetime(&naive_dt)
-                    .single()
-                    .ok_or_else(|| serde::de::Error::custom("本地时间不明确"));
-            } else {
-                return Err(serde::de::Error::custom("无法构建时间"));
-            }
-        }
+        Ok(Article {
+            group: self.group,
+            slug: self.slug,
+            updated_at: self.updated_at.unwrap_or(frontmatter.datetime),
+            frontmatter,
+            rendered_content,
+            namespaced_content: None,
+            rendered_by: None,
+            source_bytes,
+        })
     }
-
-    Err(serde::de::Error::custom(format!("无法解析日期: {}", s)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::content::RawHtmlAllowlist;
 
     // 模拟 Renderer：只包裹一层 <rendered> 标签
     struct FakeRenderer;
@@ -187,6 +621,10 @@ mod tests {
             let content = content.as_ref().to_string();
             async move { Ok(format!("<rendered>{}</rendered>", content)) }
         }
+
+        fn identity(&self) -> String {
+            "fake@1".to_string()
+        }
     }
 
     fn sample_markdown() -> String {
@@ -256,6 +694,353 @@ This is the body of the article.
         );
     }
 
+    #[tokio::test]
+    async fn test_raw_html_body() {
+        let markdown = |flag: &str, summary: &str| {
+            format!(
+                "---\ntitle: Demo\nsummary: '{summary}'\ndatetime: 2024-06-01\ntags: []\n{flag}\n---\n\n<div id=\"demo\"><canvas></canvas><script>run()</script></div>\n"
+            )
+        };
+        let build = |content: String, raw_html: RawHtml| async move {
+            ArticleBuilder::new("demos/canvas.md")
+                .raw_html(raw_html)
+                .content(content)
+                .build_with_renderer(&FakeRenderer)
+                .await
+        };
+        let body = "<div id=\"demo\"><canvas></canvas><script>run()</script></div>\n";
+
+        // 组允许时正文原样保存，摘要照常渲染
+        for flag in ["render: false", "format: html", "format: HTML"] {
+            assert!(FrontMatter::requests_raw_html(&markdown(flag, "s")));
+            let article = build(markdown(flag, "s"), RawHtml::Verbatim).await.unwrap();
+            assert_eq!(article.rendered_content, body, "{flag}");
+            assert_eq!(article.frontmatter.summary, "<rendered>s</rendered>");
+            assert_eq!(article.rendered_by.as_deref(), Some(RAW_HTML_RENDERER));
+        }
+
+        // 声明了白名单时按默认白名单加上声明的标签清理
+        let allowlist = RawHtmlAllowlist {
+            tags: vec!["canvas".to_string()],
+            attributes: Vec::new(),
+        };
+        let article = build(
+            markdown("render: false", "s"),
+            RawHtml::Sanitized(allowlist),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            article.rendered_content,
+            "<div id=\"demo\"><canvas></canvas></div>\n"
+        );
+
+        // 空摘要不经过渲染器
+        let article = build(markdown("render: false", ""), RawHtml::Verbatim)
+            .await
+            .unwrap();
+        assert_eq!(article.frontmatter.summary, "");
+        assert_eq!(article.rendered_content, body);
+
+        // 组不允许时照常渲染
+        let article = build(markdown("render: false", "s"), RawHtml::Denied)
+            .await
+            .unwrap();
+        assert_eq!(
+            article.rendered_content,
+            format!("<rendered>{body}</rendered>")
+        );
+        assert!(article.rendered_by.is_none());
+
+        // 未设置时不受组设置影响
+        for flag in ["render: true", "format: markdown", ""] {
+            assert!(!FrontMatter::requests_raw_html(&markdown(flag, "s")));
+            let article = build(markdown(flag, "s"), RawHtml::Verbatim).await.unwrap();
+            assert!(article.rendered_content.starts_with("<rendered>"), "{flag}");
+        }
+
+        let err = build(markdown("format: rst", "s"), RawHtml::Verbatim)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("`format` must be `markdown` or `html`")
+        );
+    }
+
+    #[test]
+    fn test_article_builder_with_rendered() {
+        let article = ArticleBuilder::new("group-a/test-article.md")
+            .content(sample_markdown())
+            .build_with_rendered("<p>body</p>".into(), "<p>summary</p>".into())
+            .expect("Failed to build article");
+
+        assert_eq!(article.frontmatter.title, "Test Article");
+        assert_eq!(article.frontmatter.summary, "<p>summary</p>");
+        assert_eq!(article.rendered_content, "<p>body</p>");
+    }
+
+    #[test]
+    fn test_front_matter_unknown_fields() {
+        assert!(FrontMatter::unknown_fields(&sample_markdown()).is_empty());
+
+        let markdown = "---\ntitle: t\ntag: [a]\ncathegory: x\n---\nbody";
+        let unknown = FrontMatter::unknown_fields(markdown);
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(unknown[0].suggestion, Some("tags"));
+        assert_eq!(unknown[1].name, "cathegory");
+        assert_eq!(unknown[1].suggestion, None);
+    }
+
+    #[test]
+    fn test_front_matter_tags_normalized() {
+        let markdown = "---\ntitle: t\nsummary: s\ndatetime: 2024-06-01\ntags: [' Rust  /  async ', '???', rust/async, '']\n---\nbody";
+        let article = ArticleBuilder::new("group-a/test-article.md")
+            .content(markdown)
+            .build_with_rendered(String::new(), String::new())
+            .expect("Failed to build article");
+        assert_eq!(article.frontmatter.tags, ["Rust/async", "rust/async"]);
+
+        assert_eq!(
+            FrontMatter::rejected_tags(markdown),
+            [
+                TagError::Punctuation("???".to_string()),
+                TagError::Empty(String::new())
+            ]
+        );
+        assert!(FrontMatter::rejected_tags(&sample_markdown()).is_empty());
+        assert!(FrontMatter::rejected_tags("# Body\n").is_empty());
+    }
+
+    #[test]
+    fn test_front_matter_strip() {
+        assert_eq!(
+            FrontMatter::strip("---\ntitle: a\n---\n\n# Body\n"),
+            "# Body\n"
+        );
+        assert_eq!(FrontMatter::strip("# Body\n"), "# Body\n");
+        assert_eq!(FrontMatter::strip("---\nunterminated"), "---\nunterminated");
+    }
+
+    #[test]
+    fn test_front_matter_bom_and_crlf() {
+        let parse = |markdown: String| {
+            ArticleBuilder::new("group-a/test-article.md")
+                .content(markdown)
+                .build_with_rendered(String::new(), String::new())
+                .expect("Failed to build article")
+                .frontmatter
+        };
+        let clean = sample_markdown();
+        let crlf = clean.replace('\n', "\r\n");
+        // 只有部分行使用 \r\n，分隔符带有行尾空白
+        let mixed = clean
+            .replacen("---\n", "--- \r\n", 2)
+            .replace("tags: [ rust, testing ]\n", "tags: [ rust, testing ]\r\n");
+        let expected = parse(clean.clone());
+
+        for markdown in [
+            format!("{BOM}{clean}"),
+            crlf.clone(),
+            format!("{BOM}{crlf}"),
+            mixed.clone(),
+        ] {
+            assert!(FrontMatter::is_present(&markdown), "{markdown:?}");
+            assert!(
+                FrontMatter::style_warning(&markdown).is_some(),
+                "{markdown:?}"
+            );
+            assert_eq!(parse(markdown), expected);
+        }
+        assert_eq!(FrontMatter::style_warning(&clean), None);
+        assert!(
+            FrontMatter::style_warning(&format!("{BOM}{crlf}"))
+                .unwrap()
+                .contains("a UTF-8 BOM and CRLF line endings")
+        );
+
+        // 正文保持原来的换行
+        assert_eq!(
+            FrontMatter::strip(&crlf),
+            "# Markdown Content\r\n\r\nThis is the body of the article.\r\n"
+        );
+        assert_eq!(FrontMatter::strip(&mixed), FrontMatter::strip(&clean));
+    }
+
+    #[test]
+    fn test_front_matter_delimiter_line() {
+        // 分隔符必须独占一行
+        let markdown = "---\ntitle: a --- b\n---\nbody";
+        assert_eq!(FrontMatter::strip(markdown), "body");
+        assert_eq!(
+            FrontMatter::strip("---\ntitle: a\n---body"),
+            "---\ntitle: a\n---body"
+        );
+        assert_eq!(FrontMatter::strip("---- \n---\nbody"), "---- \n---\nbody");
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(
+            FrontMatter::canonical_url(" https://example.com/post?id=1 ").unwrap(),
+            "https://example.com/post?id=1"
+        );
+        assert!(FrontMatter::canonical_url("http://example.com").is_ok());
+
+        let rejected = |url: &str| FrontMatter::canonical_url(url).unwrap_err();
+        assert!(rejected("/posts/original").contains("not an absolute URL"));
+        assert!(rejected("example.com/post").contains("not an absolute URL"));
+        assert!(rejected("javascript:alert(1)").contains("`javascript:`"));
+        assert!(rejected("JavaScript:alert(1)").contains("`javascript:`"));
+        assert!(rejected("ftp://example.com/post").contains("`ftp:`"));
+        assert!(rejected("mailto:me@example.com").contains("`mailto:`"));
+        assert!(rejected("file:///etc/passwd").contains("`file:`"));
+
+        let long = format!(
+            "https://example.com/{}",
+            "a".repeat(FrontMatter::MAX_CANONICAL_LEN)
+        );
+        assert!(rejected(&long).contains("longer than 2048"));
+    }
+
+    #[tokio::test]
+    async fn test_article_canonical() {
+        let markdown = |canonical: &str| {
+            format!(
+                "---\ntitle: t\nsummary: s\ndatetime: 2024-06-01\ntags: []\ncanonical: {canonical}\n---\nbody"
+            )
+        };
+
+        let article = ArticleBuilder::new("group-a/reposted.md")
+            .content(markdown("https://blog.example.com/original"))
+            .build_with_renderer(&FakeRenderer)
+            .await
+            .unwrap();
+        assert_eq!(
+            article.frontmatter.canonical.as_deref(),
+            Some("https://blog.example.com/original")
+        );
+
+        // 无效地址是该文件的元数据错误，而不是被忽略
+        let err = ArticleBuilder::new("group-a/reposted.md")
+            .content(markdown("\"javascript:alert(1)\""))
+            .build_with_renderer(&FakeRenderer)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                Error::Metadata(MetadataError::Invalid { path, .. }) if path == "group-a/reposted.md"
+            ),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_article_aliases_from_front_matter() {
+        let markdown = r#"
+---
+title: Moved Article
+summary: moved
+datetime: 2024-06-01
+tags: []
+aliases: [ old-group/old-slug, /legacy/nested/page.md, renamed, new-group/moved ]
+---
+
+body
+"#;
+
+        let article = ArticleBuilder::new("new-group/moved.md")
+            .content(markdown)
+            .build_with_renderer(&FakeRenderer)
+            .await
+            .expect("Failed to build article");
+
+        let aliases = article
+            .aliases()
+            .into_iter()
+            .map(|r| (r.group, r.slug))
+            .collect::<Vec<_>>();
+
+        // 指向自身的别名会被忽略
+        assert_eq!(
+            aliases,
+            vec![
+                ("old-group", "old-slug"),
+                ("legacy/nested", "page"),
+                ("new-group", "renamed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_article_builder_with_strategies() {
+        let strategies = HashMap::from([
+            ("wiki".into(), SlugStrategy::Path),
+            ("wiki/api".into(), SlugStrategy::Stem),
+            ("notes".into(), SlugStrategy::Stem),
+        ]);
+        let resolve = |path| {
+            let builder = ArticleBuilder::with_strategies(path, &strategies);
+            (builder.group().to_string(), builder.slug().to_string())
+        };
+
+        // 子目录中的文章归属上级组，slug 包含相对路径
+        assert_eq!(
+            resolve("wiki/guides/setup.md"),
+            ("wiki".into(), "guides-setup".into())
+        );
+        assert_eq!(
+            resolve("wiki/reference/setup.md"),
+            ("wiki".into(), "reference-setup".into())
+        );
+        assert_eq!(resolve("wiki/a/b/c.md"), ("wiki".into(), "a-b-c".into()));
+        assert_eq!(resolve("wiki/index.md"), ("wiki".into(), "index".into()));
+
+        // 子目录自身为组时使用该组
+        assert_eq!(
+            resolve("wiki/api/setup.md"),
+            ("wiki/api".into(), "setup".into())
+        );
+
+        // 使用 stem 的组保持原有行为
+        assert_eq!(
+            resolve("notes/daily/log.md"),
+            ("notes/daily".into(), "log".into())
+        );
+        assert_eq!(resolve("other/log.md"), ("other".into(), "log".into()));
+    }
+
+    #[tokio::test]
+    async fn test_article_builder_timezone() {
+        let article = ArticleBuilder::new("group-a/test-article.md")
+            .timezone(Tz::Asia__Shanghai)
+            .content(sample_markdown())
+            .build_with_renderer(&FakeRenderer)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            article.frontmatter.datetime.to_rfc3339(),
+            "2024-06-01T00:00:00+08:00"
+        );
+
+        // 整数按 Unix 秒时间戳解析，带偏移的时间保留原偏移
+        for (datetime, expected) in [
+            ("1717201800", "2024-06-01T08:30:00+08:00"),
+            ("2024-06-01T10:00:00+02:00", "2024-06-01T10:00:00+02:00"),
+        ] {
+            let markdown =
+                format!("---\ntitle: t\nsummary: s\ndatetime: {datetime}\ntags: []\n---\nbody");
+            let front_matter = ArticleBuilder::new("group-a/test-article.md")
+                .timezone(Tz::Asia__Shanghai)
+                .content(markdown)
+                .front_matter()
+                .unwrap();
+            assert_eq!(front_matter.datetime.to_rfc3339(), expected);
+        }
+    }
+
     #[tokio::test]
     async fn test_article_builder_missing_front_matter_should_fail() {
         let markdown = r#"
@@ -0,0 +1,291 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, de::Error as _};
+use serde_yaml::Value;
+
+/// 带偏移的时间格式，RFC 3339 之外另外支持的格式
+const OFFSET_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// 不带偏移的时间格式
+const LOCAL_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S", "%Y/%m/%d %H:%M:%S"];
+
+/// 只有日期的格式，视为当天零点
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%Y/%m/%d"];
+
+/// 字符串时间依次尝试的格式
+pub const DATETIME_FORMATS: [&str; 6] = [
+    "RFC 3339",
+    OFFSET_FORMAT,
+    LOCAL_FORMATS[0],
+    LOCAL_FORMATS[1],
+    DATE_FORMATS[0],
+    DATE_FORMATS[1],
+];
+
+/// 无法解析时间时错误信息的前缀
+pub(super) const UNPARSABLE_DATE: &str = "无法解析日期";
+
+/// Front Matter 中 `datetime` 无法解析
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DateTimeError {
+    /// 不符合任何格式，包含原文和尝试过的格式
+    #[error("{UNPARSABLE_DATE}: {input}，尝试过的格式: {}，或整数 Unix 秒时间戳", .tried.join(", "))]
+    Unparsable {
+        input: String,
+        tried: &'static [&'static str],
+    },
+    /// 本地时间在时区中不存在，如夏令时跳过的时间
+    #[error("时间 {input} 在时区 {tz} 中不存在")]
+    Nonexistent { input: String, tz: Tz },
+    /// Unix 秒时间戳超出可表示的范围
+    #[error("时间戳 {0} 超出范围")]
+    OutOfRange(i64),
+}
+
+/// Front Matter 中 `datetime` 的原始值，按时区解析见 [`RawDateTime::in_zone`]
+///
+/// 接受字符串、整数（Unix 秒时间戳）以及带标签的字符串（如 `!!timestamp 2024-06-01`）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum RawDateTime {
+    Text(String),
+    Epoch(i64),
+}
+
+impl<'de> Deserialize<'de> for RawDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = match Value::deserialize(deserializer)? {
+            Value::Tagged(tagged) => tagged.value,
+            value => value,
+        };
+        match value {
+            Value::String(s) => Ok(Self::Text(s)),
+            Value::Number(n) => n
+                .as_i64()
+                .map(Self::Epoch)
+                .ok_or_else(|| D::Error::custom(unparsable(&n.to_string()))),
+            other => Err(D::Error::custom(unparsable(
+                serde_yaml::to_string(&other).unwrap_or_default().trim(),
+            ))),
+        }
+    }
+}
+
+impl RawDateTime {
+    /// 转换为时间，字符串按 [`parse_in_zone`] 解析，时间戳使用 `tz` 中的偏移
+    pub(super) fn in_zone(&self, tz: Tz) -> Result<DateTime<FixedOffset>, DateTimeError> {
+        match self {
+            Self::Text(s) => parse_in_zone(s, tz),
+            Self::Epoch(secs) => tz
+                .timestamp_opt(*secs, 0)
+                .single()
+                .map(|dt| dt.fixed_offset())
+                .ok_or(DateTimeError::OutOfRange(*secs)),
+        }
+    }
+}
+
+fn unparsable(input: &str) -> DateTimeError {
+    DateTimeError::Unparsable {
+        input: input.to_string(),
+        tried: &DATETIME_FORMATS,
+    }
+}
+
+/// 解析 Front Matter 中的时间。
+///
+/// 带偏移的时间（RFC 3339 或 `%Y-%m-%d %H:%M:%S %z`）保留原偏移，
+/// 其他格式视为 `tz` 中的本地时间，夏令时重叠时取较早的时间。
+pub(super) fn parse_in_zone(s: &str, tz: Tz) -> Result<DateTime<FixedOffset>, DateTimeError> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt);
+    }
+    if let Ok(dt) = DateTime::parse_from_str(s, OFFSET_FORMAT) {
+        return Ok(dt);
+    }
+
+    let naive = LOCAL_FORMATS
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| unparsable(s))?;
+
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.fixed_offset())
+        .ok_or_else(|| DateTimeError::Nonexistent {
+            input: s.to_string(),
+            tz,
+        })
+}
+
+/// 支持的每种格式的示例，用于错误提示，最后一个为 Unix 秒时间戳
+pub(super) fn datetime_examples() -> Vec<String> {
+    let sample = FixedOffset::east_opt(8 * 3600)
+        .and_then(|tz| tz.with_ymd_and_hms(2024, 6, 1, 8, 30, 0).single())
+        .expect("valid sample datetime");
+    std::iter::once(sample.to_rfc3339())
+        .chain(
+            std::iter::once(OFFSET_FORMAT)
+                .chain(LOCAL_FORMATS)
+                .chain(DATE_FORMATS)
+                .map(|fmt| sample.format(fmt).to_string()),
+        )
+        .chain(std::iter::once(sample.timestamp().to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按 YAML 读取 `datetime` 的值后解析
+    fn parse(yaml: &str, tz: Tz) -> Result<String, String> {
+        serde_yaml::from_str::<RawDateTime>(yaml)
+            .map_err(|e| e.to_string())?
+            .in_zone(tz)
+            .map(|dt| dt.to_rfc3339())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        // 保留原偏移，不按本地时区重新解释
+        assert_eq!(
+            parse("2024-06-01T10:00:00+08:00", Tz::America__New_York).unwrap(),
+            "2024-06-01T10:00:00+08:00"
+        );
+        assert_eq!(
+            parse("2024-06-01T08:00:00Z", Tz::Asia__Shanghai).unwrap(),
+            "2024-06-01T08:00:00+00:00"
+        );
+        assert_eq!(
+            parse("'2024-06-01T08:00:00.250-03:30'", Tz::UTC).unwrap(),
+            "2024-06-01T08:00:00.250-03:30"
+        );
+    }
+
+    #[test]
+    fn test_offset_format() {
+        assert_eq!(
+            parse("2024-06-01 08:00:00 +0000", Tz::Asia__Shanghai).unwrap(),
+            "2024-06-01T08:00:00+00:00"
+        );
+        assert_eq!(
+            parse("2024-06-01 08:00:00 -0500", Tz::UTC).unwrap(),
+            "2024-06-01T08:00:00-05:00"
+        );
+    }
+
+    #[test]
+    fn test_local_formats() {
+        assert_eq!(
+            parse("2024/06/01 10:30:00", Tz::UTC).unwrap(),
+            "2024-06-01T10:30:00+00:00"
+        );
+        assert_eq!(
+            parse("2024-01-15 09:00:00", Tz::America__New_York).unwrap(),
+            "2024-01-15T09:00:00-05:00"
+        );
+        assert_eq!(
+            parse("2024-07-15 09:00:00", Tz::America__New_York).unwrap(),
+            "2024-07-15T09:00:00-04:00"
+        );
+    }
+
+    #[test]
+    fn test_date_formats() {
+        assert_eq!(
+            parse("2024-06-01", Tz::Asia__Shanghai).unwrap(),
+            "2024-06-01T00:00:00+08:00"
+        );
+        assert_eq!(
+            parse("2024/06/01", Tz::UTC).unwrap(),
+            "2024-06-01T00:00:00+00:00"
+        );
+        // 带标签的时间与字符串相同
+        assert_eq!(
+            parse("!!timestamp 2024-06-01", Tz::UTC).unwrap(),
+            "2024-06-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_epoch_seconds() {
+        assert_eq!(
+            parse("1717201800", Tz::Asia__Shanghai).unwrap(),
+            "2024-06-01T08:30:00+08:00"
+        );
+        assert_eq!(parse("0", Tz::UTC).unwrap(), "1970-01-01T00:00:00+00:00");
+        assert_eq!(
+            parse("-86400", Tz::UTC).unwrap(),
+            "1969-12-31T00:00:00+00:00"
+        );
+        assert!(parse("1717201800.5", Tz::UTC).is_err());
+        assert_eq!(
+            parse("9223372036854775807", Tz::UTC).unwrap_err(),
+            DateTimeError::OutOfRange(i64::MAX).to_string()
+        );
+    }
+
+    #[test]
+    fn test_dst() {
+        // 夏令时结束时重叠的时间取较早的偏移
+        assert_eq!(
+            parse("2024-11-03 01:30:00", Tz::America__New_York).unwrap(),
+            "2024-11-03T01:30:00-04:00"
+        );
+        assert_eq!(
+            parse("2024-10-27 02:30:00", Tz::Europe__Berlin).unwrap(),
+            "2024-10-27T02:30:00+02:00"
+        );
+
+        // 夏令时跳过的时间不存在
+        let err = parse_in_zone("2024-03-10 02:30:00", Tz::America__New_York).unwrap_err();
+        assert!(
+            matches!(err, DateTimeError::Nonexistent { tz, .. } if tz == Tz::America__New_York)
+        );
+    }
+
+    #[test]
+    fn test_unparsable() {
+        let err = parse_in_zone("June 1st", Tz::UTC).unwrap_err();
+        assert_eq!(
+            err,
+            DateTimeError::Unparsable {
+                input: "June 1st".into(),
+                tried: &DATETIME_FORMATS,
+            }
+        );
+        let message = err.to_string();
+        assert!(message.starts_with(UNPARSABLE_DATE), "{message}");
+        for format in DATETIME_FORMATS {
+            assert!(message.contains(format), "{format}");
+        }
+
+        // 列表和映射不是时间
+        let err = parse("[2024, 6, 1]", Tz::UTC).unwrap_err();
+        assert!(err.starts_with(UNPARSABLE_DATE), "{err}");
+    }
+
+    #[test]
+    fn test_examples() {
+        // 错误提示中的示例都能解析，且表示同一时刻
+        let examples = datetime_examples();
+        assert_eq!(examples.len(), 7);
+        let shanghai = examples
+            .iter()
+            .map(|e| parse(e, Tz::Asia__Shanghai).unwrap())
+            .collect::<Vec<_>>();
+        assert!(
+            shanghai
+                .iter()
+                .all(|t| t == "2024-06-01T08:30:00+08:00" || t.starts_with("2024-06-01T00:00:00"))
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use super::SchemaViolation;
+
+/// 组配置或 Front Matter 解析错误
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataError {
+    /// 字段类型或取值错误
+    #[error("invalid metadata in `{path}`: {reason}")]
+    Invalid { path: String, reason: String },
+
+    /// 严格模式下出现未知字段
+    #[error("`{path}`: {field}")]
+    Unknown { path: String, field: UnknownField },
+
+    /// 严格模式下 Front Matter 不符合所在组声明的字段约定
+    #[error("`{path}`: {}", .violations.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    Schema {
+        path: String,
+        violations: Vec<SchemaViolation>,
+    },
+
+    /// 两个组对外使用相同的 id
+    #[error("group slug `{slug}` is used by both `{first}` and `{second}`")]
+    SlugConflict {
+        slug: String,
+        first: String,
+        second: String,
+    },
+
+    /// Front Matter 不是合法的 YAML 或不符合格式，附带面向作者的修改建议
+    #[error("{error}")]
+    Syntax {
+        error: serde_yaml::Error,
+        suggestions: Vec<String>,
+    },
+}
+
+impl MetadataError {
+    /// 修改建议，只有 [`MetadataError::Syntax`] 带有建议
+    pub fn suggestions(&self) -> &[String] {
+        match self {
+            Self::Syntax { suggestions, .. } => suggestions,
+            _ => &[],
+        }
+    }
+}
+
+/// 未知的顶层字段，附带拼写最接近的已知字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    pub name: String,
+    pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown field `{}`", self.name)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, ", did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+/// 找出 `keys` 中不属于 `known` 的字段，按出现顺序返回
+pub fn unknown_fields<'a>(
+    keys: impl IntoIterator<Item = &'a str>,
+    known: &[&'static str],
+) -> Vec<UnknownField> {
+    keys.into_iter()
+        .filter(|key| !known.contains(key))
+        .map(|key| UnknownField {
+            name: key.to_string(),
+            suggestion: suggest(key, known),
+        })
+        .collect()
+}
+
+/// 编辑距离最小的已知字段，距离超过字段长度的三分之一（至少为 1）时不给出建议
+pub fn suggest(name: &str, known: &[&'static str]) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let max = (name.chars().count() / 3).max(1);
+
+    known
+        .iter()
+        .map(|k| (distance(&name, k), *k))
+        .filter(|(d, _)| *d <= max)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, k)| k)
+}
+
+/// Levenshtein 编辑距离
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            cur[j + 1] = (prev[j] + usize::from(ca != *cb))
+                .min(prev[j + 1] + 1)
+                .min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUP: &[&str] = &[
+        "type",
+        "name",
+        "public",
+        "encrypted",
+        "slug_strategy",
+        "author",
+    ];
+
+    #[test]
+    fn test_suggest() {
+        assert_eq!(suggest("pubic", GROUP), Some("public"));
+        assert_eq!(suggest("auther", GROUP), Some("author"));
+        assert_eq!(suggest("Public", GROUP), Some("public"));
+        assert_eq!(suggest("slug-strategy", GROUP), Some("slug_strategy"));
+        assert_eq!(
+            suggest("cathegory", &["title", "tags", "category"]),
+            Some("category")
+        );
+
+        // 没有足够接近的字段时不给出建议
+        assert_eq!(suggest("cathegory", GROUP), None);
+        assert_eq!(suggest("x", GROUP), None);
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let fields = unknown_fields(["name", "pubic", "layout"], GROUP);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(
+            fields[0].to_string(),
+            "unknown field `pubic`, did you mean `public`?"
+        );
+        assert_eq!(fields[1].to_string(), "unknown field `layout`");
+
+        assert!(unknown_fields(["name", "public"], GROUP).is_empty());
+    }
+}
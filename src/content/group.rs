@@ -1,8 +1,14 @@
-use std::path::Path;
+use std::{borrow::Borrow, collections::BTreeMap, fmt, path::Path};
 
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
-use crate::error::Result;
+use crate::{error::Result, git_client::FileClassifier};
+
+use super::{
+    FieldType, FrontMatterSchema, MetadataError, TagError, UnknownField, normalize_tags,
+    unknown_fields,
+};
 
 mod timeline;
 mod wiki;
@@ -21,52 +27,556 @@ pub enum GroupKind {
     // Wiki { column: WikiOptions },
 }
 
+/// 组内文章 slug 的生成方式
+///
+/// - [`SlugStrategy::Stem`]：使用文件名去掉扩展名，默认值
+/// - [`SlugStrategy::Path`]：使用相对组目录的路径去掉扩展名，各级以 `-` 连接，
+///   子目录中的文章归属该组，如 `guides/setup.md` 的 slug 为 `guides-setup`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SlugStrategy {
+    #[default]
+    Stem,
+    Path,
+}
+
+impl SlugStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stem => "stem",
+            Self::Path => "path",
+        }
+    }
+}
+
+impl From<&str> for SlugStrategy {
+    /// 未知取值视为 [`SlugStrategy::Stem`]
+    fn from(s: &str) -> Self {
+        match s {
+            "path" => Self::Path,
+            _ => Self::Stem,
+        }
+    }
+}
+
+/// 影响组内文章写入方式的设置，已存储的组修改后需要重建
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GroupSettings {
+    pub encrypted: bool,
+    pub slug_strategy: SlugStrategy,
+}
+
+/// 组内文章的默认作者
+///
+/// `id` 为稳定的作者标识，按作者筛选和作者列表都使用 `id`；省略时由 `name` 经 [`author_id`] 生成。
+/// 只修改显示名时保持 `id` 不变即可沿用已有的筛选条件。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct GroupAuthor {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+}
+
+/// 组所属的分类
+///
+/// 多个组可以属于同一分类。`id` 用于 `GET /api/categories/{id}` 和文章列表的 `category` 筛选，
+/// 省略时与作者 id 相同地由 `name` 经 [`author_id`] 生成；省略 `name` 时以 `id` 作为显示名。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct GroupCategory {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+/// 组内公开文章的保留规则
+///
+/// 超出规则的文章在同步后下线，仍保留在仓库和数据库中；放宽规则后按创建时间从新到旧重新上线。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GroupRetain {
+    /// 按创建时间从新到旧保留的文章数
+    #[serde(default)]
+    pub max_articles: Option<u32>,
+    /// 保留创建时间在最近多少天内的文章
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
+/// 原样输出的 HTML 正文额外允许的标签和通用属性
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct RawHtmlAllowlist {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+}
+
+/// 组内文章原样输出 HTML 正文的方式，见 [`Group::raw_html`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RawHtml {
+    /// 不允许，设置了 `render: false` 的文章仍按 Markdown 渲染
+    #[default]
+    Denied,
+    /// 不经过渲染器和清理，原样保存
+    Verbatim,
+    /// 使用默认白名单加上组声明的标签和属性清理后保存
+    Sanitized(RawHtmlAllowlist),
+}
+
+/// 组内文章的许可协议
+///
+/// 组配置中可以只写协议 id，如 `license: CC-BY-4.0`，也可以写完整的对象
+/// `{ id, name, url }`。`id` 为 SPDX 风格的标识，只能包含 ASCII 字母、数字和 `-`、`.`、`+`；
+/// 省略 `name` 时以 `id` 作为显示名，`url` 可选。
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct GroupLicense {
+    pub id: String,
+    pub name: String,
+    pub url: Option<String>,
+}
+
+impl GroupLicense {
+    /// 检查并规范化各字段，`id` 不是 SPDX 风格的标识时返回原因
+    fn new(id: &str, name: Option<&str>, url: Option<&str>) -> std::result::Result<Self, String> {
+        let id = id.trim();
+        if id.is_empty() {
+            return Err("license id is empty".to_string());
+        }
+        if let Some(c) = id
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '.' | '+'))
+        {
+            return Err(format!(
+                "license id `{id}` contains invalid character {c:?}"
+            ));
+        }
+        let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(id);
+        Ok(Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: url
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for GroupLicense {
+    /// 接受协议 id 字符串或 `{ id, name, url }` 对象
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Id(String),
+            Full {
+                id: String,
+                #[serde(default)]
+                name: Option<String>,
+                #[serde(default)]
+                url: Option<String>,
+            },
+        }
+
+        let license = match Raw::deserialize(deserializer).map_err(|_| {
+            serde::de::Error::custom("license must be an id string or a table with `id`")
+        })? {
+            Raw::Id(id) => Self::new(&id, None, None),
+            Raw::Full { id, name, url } => Self::new(&id, name.as_deref(), url.as_deref()),
+        };
+        license.map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for GroupLicense {
+    type Err = String;
+
+    /// 以 `{` 开头时按 JSON 对象解析，否则视为协议 id
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.trim_start().starts_with('{') {
+            serde_json::from_str(s).map_err(|e| e.to_string())
+        } else {
+            Self::new(s, None, None)
+        }
+    }
+}
+
+/// 由作者名生成作者 id
+///
+/// 转为小写，字母和数字以外的字符视为分隔符，以 `-` 连接各段。
+pub fn author_id(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// 组目录相对仓库根目录的路径，即组的内部 id，如 `programming/rust`
+///
+/// 只能通过 [`GroupPath::new`] 规范化后构造：`\` 视为 `/`，去除首尾和重复的 `/` 以及 `.`，
+/// 按 Unicode NFC 规范化。写法不同但指向同一目录的路径得到相同的值，仓库根目录为空字符串。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, sqlx::Type)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct GroupPath(String);
+
+impl GroupPath {
+    pub fn new(path: impl AsRef<str>) -> Self {
+        let path = path
+            .as_ref()
+            .split(['/', '\\'])
+            .filter(|s| !s.is_empty() && *s != ".")
+            .collect::<Vec<_>>()
+            .join("/");
+        Self(path.nfc().collect())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 是否为仓库根目录
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for GroupPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for GroupPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for GroupPath {
+    fn as_ref(&self) -> &Path {
+        Path::new(&self.0)
+    }
+}
+
+impl Borrow<str> for GroupPath {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for GroupPath {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for GroupPath {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl From<&str> for GroupPath {
+    fn from(path: &str) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<String> for GroupPath {
+    fn from(path: String) -> Self {
+        Self::new(path)
+    }
+}
+
+impl From<&Path> for GroupPath {
+    /// 目录路径，或组配置文件的路径，文件名为 [`FileClassifier::GROUP_FILE_NAMES`] 之一时取所在目录
+    ///
+    /// [`FileClassifier::GROUP_FILE_NAMES`]: crate::git_client::FileClassifier::GROUP_FILE_NAMES
+    fn from(path: &Path) -> Self {
+        let dir = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if FileClassifier::GROUP_FILE_NAMES.contains(&name) => {
+                path.parent().unwrap_or(path)
+            }
+            _ => path,
+        };
+        Self::new(dir.to_string_lossy())
+    }
+}
+
+impl From<GroupPath> for String {
+    fn from(path: GroupPath) -> Self {
+        path.0
+    }
+}
+
+impl<'de> Deserialize<'de> for GroupPath {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
 /// 表示一个分组（Group），包含名称和元信息。
 ///
 /// [`Group`] 通常用于表示仓库或文件系统中的逻辑分组。
 #[derive(Debug, Deserialize)]
 pub struct Group {
-    /// 分组名称，只会保留路径的父级部分。
+    /// 组目录的路径，只会保留路径的父级部分。
     #[serde(skip)]
-    pub id: String,
+    pub id: GroupPath,
 
     #[serde(default)]
     pub name: String,
 
+    /// 对外使用的组 id，如 `rust-notes`，代替由目录路径生成的 [`Group::id`]，也可以写作 `url`
+    #[serde(default, alias = "url")]
+    pub slug: Option<String>,
+
     #[serde(default)]
     pub public: bool,
 
+    /// 是否加密存储组内文章的正文和摘要
+    #[serde(default)]
+    pub encrypted: bool,
+
+    /// 组内文章 slug 的生成方式
+    #[serde(default)]
+    pub slug_strategy: SlugStrategy,
+
+    /// 组内文章的默认作者
+    #[serde(default)]
+    pub author: Option<GroupAuthor>,
+
+    /// 组所属的分类
+    #[serde(default)]
+    pub category: Option<GroupCategory>,
+
+    /// 组内文章是否已归档，归档的文章不出现在默认的文章列表中，仍可按 slug 访问
+    #[serde(default)]
+    pub archived: bool,
+
+    /// 合并到组内每篇文章标签中的默认标签
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+
+    /// 组列表中的排序，较小的在前，未设置的排在最后
+    #[serde(default)]
+    pub nav_order: Option<i32>,
+
+    /// 是否从组列表中隐藏，组内文章和组详情不受影响
+    #[serde(default)]
+    pub hidden: bool,
+
+    /// 组内公开文章的保留规则，未设置时不限制
+    #[serde(default)]
+    pub retain: Option<GroupRetain>,
+
+    /// 组内文章的许可协议，未设置时使用部署配置的默认协议
+    #[serde(default)]
+    pub license: Option<GroupLicense>,
+
+    /// 组内文章原文的总字节数上限，超出时同步报告中给出警告，严格模式下拒绝同步；为 0 或未设置时不限制
+    #[serde(default)]
+    pub quota_bytes: Option<u64>,
+
+    /// 是否允许组内文章在 Front Matter 中设置 `render: false` 或 `format: html`，原样输出 HTML 正文
+    #[serde(default)]
+    pub allow_raw_html: bool,
+
+    /// 原样输出的 HTML 正文的清理白名单，在默认白名单上追加；未设置时不清理
+    #[serde(default)]
+    pub raw_html_allowlist: Option<RawHtmlAllowlist>,
+
+    /// 组内文章 Front Matter 中必须设置的字段，见 [`Group::front_matter_schema`]
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+
+    /// 组内文章 Front Matter 中字段的取值类型，见 [`Group::front_matter_schema`]
+    #[serde(default)]
+    pub field_types: BTreeMap<String, FieldType>,
+
     /// 分组的类型
     #[serde(flatten)]
     pub kind: Option<GroupKind>,
+
+    /// `default_tags` 中规范化后被丢弃的标签，只用于同步报告
+    #[serde(skip)]
+    pub rejected_tags: Vec<TagError>,
 }
 
 impl Group {
+    /// 组配置文件中可用的顶层字段
+    pub const FIELDS: &[&str] = &[
+        "type",
+        "name",
+        "slug",
+        "url",
+        "public",
+        "encrypted",
+        "slug_strategy",
+        "author",
+        "category",
+        "archived",
+        "default_tags",
+        "nav_order",
+        "hidden",
+        "retain",
+        "license",
+        "quota_bytes",
+        "allow_raw_html",
+        "raw_html_allowlist",
+        "required_fields",
+        "field_types",
+    ];
+
+    /// 解析组配置文件，忽略未知字段
+    ///
+    /// `.toml` 文件按 TOML 解析，其他按 YAML 解析。
     pub fn new(id: impl AsRef<Path>, group_content: String) -> Result<Self> {
-        let mut group = serde_yaml::from_str::<Group>(&group_content)?;
+        Self::parse(id, group_content).map(|(group, _)| group)
+    }
 
+    /// 解析组配置文件，同时返回不在 [`Group::FIELDS`] 中的顶层字段
+    ///
+    /// 字段类型错误时返回 [`MetadataError::Invalid`]，错误信息包含文件路径。
+    pub fn parse(id: impl AsRef<Path>, group_content: String) -> Result<(Self, Vec<UnknownField>)> {
         let path = id.as_ref();
+        let invalid = |reason: String| MetadataError::Invalid {
+            path: path.display().to_string(),
+            reason,
+        };
+        let (mut group, keys) = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => (
+                toml::from_str::<Group>(&group_content).map_err(|e| invalid(e.to_string()))?,
+                group_content
+                    .parse::<toml::Table>()
+                    .map(|t| t.keys().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default(),
+            ),
+            _ => (
+                serde_yaml::from_str::<Group>(&group_content)
+                    .map_err(|e| invalid(e.to_string()))?,
+                serde_yaml::from_str::<serde_yaml::Mapping>(&group_content)
+                    .map(|m| {
+                        m.keys()
+                            .filter_map(|k| k.as_str().map(str::to_string))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default(),
+            ),
+        };
+        let unknown = unknown_fields(keys.iter().map(String::as_str), Self::FIELDS);
+
         let parent = path.parent().unwrap_or(path);
-        group.id = parent.to_string_lossy().trim_matches('/').to_string();
+        group.id = GroupPath::from(parent);
 
-        group
-            .name
-            .is_empty()
-            .then(|| group.name = parent.file_name().unwrap().to_string_lossy().to_string());
+        // 仓库根目录的组没有目录名，未设置 `name` 时保持为空
+        if group.name.is_empty()
+            && let Some(name) = parent.file_name()
+        {
+            group.name = name.to_string_lossy().to_string();
+        }
 
         group.kind = Some(group.kind.unwrap_or_default());
 
-        Ok(group)
+        group.slug = group
+            .slug
+            .map(|slug| slug.trim().trim_matches('/').to_string())
+            .filter(|slug| !slug.is_empty());
+
+        if let Some(author) = group.author.as_mut() {
+            author.id = match author.id.trim() {
+                "" => author_id(&author.name),
+                id => id.to_string(),
+            };
+        }
+        group.author = group.author.filter(|a| !a.id.is_empty());
+        if let Some(category) = group.category.as_mut() {
+            category.name = category.name.trim().to_string();
+            category.id = match category.id.trim() {
+                "" => author_id(&category.name),
+                id => id.to_string(),
+            };
+            if category.name.is_empty() {
+                category.name = category.id.to_owned();
+            }
+        }
+        group.category = group.category.filter(|c| !c.id.is_empty());
+        (group.default_tags, group.rejected_tags) =
+            normalize_tags(std::mem::take(&mut group.default_tags));
+        group.retain = group
+            .retain
+            .filter(|r| r.max_articles.is_some() || r.max_age_days.is_some());
+        group.quota_bytes = group.quota_bytes.filter(|q| *q > 0);
+        group.required_fields = std::mem::take(&mut group.required_fields)
+            .into_iter()
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+
+        Ok((group, unknown))
+    }
+
+    /// 对外使用的组 id，设置了 `slug` 时为 `slug`，否则为目录路径
+    pub fn public_id(&self) -> &str {
+        self.slug.as_deref().unwrap_or(self.id.as_str())
+    }
+
+    pub fn settings(&self) -> GroupSettings {
+        GroupSettings {
+            encrypted: self.encrypted,
+            slug_strategy: self.slug_strategy,
+        }
+    }
+
+    /// 组内文章 Front Matter 的附加约定，没有设置 `required_fields` 和 `field_types` 时为 `None`
+    pub fn front_matter_schema(&self) -> Option<FrontMatterSchema> {
+        let schema = FrontMatterSchema {
+            required_fields: self.required_fields.clone(),
+            field_types: self.field_types.clone(),
+        };
+        (!schema.is_empty()).then_some(schema)
+    }
+
+    /// 组内文章原样输出 HTML 正文的方式
+    pub fn raw_html(&self) -> RawHtml {
+        match (self.allow_raw_html, &self.raw_html_allowlist) {
+            (false, _) => RawHtml::Denied,
+            (true, None) => RawHtml::Verbatim,
+            (true, Some(allowlist)) => RawHtml::Sanitized(allowlist.clone()),
+        }
     }
 
     pub fn empty(id: impl AsRef<Path>) -> Self {
         let path = id.as_ref();
         let parent = path.parent().unwrap_or(path);
         Self {
-            id: parent.to_string_lossy().trim_matches('/').to_string(),
+            id: GroupPath::from(parent),
             public: Default::default(),
+            encrypted: Default::default(),
+            slug_strategy: Default::default(),
             name: Default::default(),
+            slug: None,
+            author: None,
+            category: None,
+            archived: false,
+            default_tags: Vec::new(),
+            nav_order: None,
+            hidden: false,
+            retain: None,
+            license: None,
+            quota_bytes: None,
+            allow_raw_html: false,
+            raw_html_allowlist: None,
+            required_fields: Vec::new(),
+            field_types: BTreeMap::new(),
             kind: None,
+            rejected_tags: Vec::new(),
         }
     }
 }
@@ -108,6 +618,293 @@ mod tests {
 
         // name 为空时使用 id
         assert_eq!(group.name, "to");
+        assert!(!group.encrypted);
+
+        // 仓库根目录的组没有目录名
+        let group = Group::new(".group.yaml", yaml_content.to_string()).unwrap();
+        assert_eq!(group.id, "");
+        assert_eq!(group.name, "");
+    }
+
+    #[test]
+    fn test_group_encrypted() {
+        let yaml_content = r#"
+              type: normal
+              encrypted: true
+          "#;
+
+        let group = Group::new("/secrets/.group.yaml", yaml_content.to_string()).unwrap();
+
+        assert!(group.encrypted);
+    }
+
+    #[test]
+    fn test_group_archived() {
+        let group = Group::new("2024/.group.toml", "archived = true".to_string()).unwrap();
+        assert!(group.archived);
+
+        let group = Group::new("notes/.group.yaml", "name: 笔记".to_string()).unwrap();
+        assert!(!group.archived);
+    }
+
+    #[test]
+    fn test_group_slug_strategy() {
+        let group = Group::new("wiki/.group.toml", "slug_strategy = \"path\"".to_string()).unwrap();
+        assert_eq!(group.slug_strategy, SlugStrategy::Path);
+
+        let group = Group::new("notes/.group.yaml", "name: 笔记".to_string()).unwrap();
+        assert_eq!(group.slug_strategy, SlugStrategy::Stem);
+
+        assert!(Group::new("notes/.group.yaml", "slug_strategy: dir".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_group_front_matter_schema() {
+        let yaml = "required_fields: [doi, ' authors ', '']\nfield_types: { year: int }\n";
+        let group = Group::new("papers/.group.yaml", yaml.to_string()).unwrap();
+        let schema = group.front_matter_schema().unwrap();
+        assert_eq!(schema.required_fields, ["doi", "authors"]);
+        assert_eq!(schema.field_types["year"], FieldType::Int);
+
+        let toml = "[field_types]\nyear = \"integer\"\n";
+        let group = Group::new("papers/.group.toml", toml.to_string()).unwrap();
+        assert!(group.required_fields.is_empty());
+        assert_eq!(group.field_types["year"], FieldType::Int);
+
+        let group = Group::new("journal/.group.yaml", "name: 日记".to_string()).unwrap();
+        assert_eq!(group.front_matter_schema(), None);
+
+        assert!(Group::new("papers/.group.yaml", "field_types: { a: date }".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_group_default_tags() {
+        let yaml = "default_tags: [kubernetes, ' ops/ ', kubernetes, '']\n";
+        let group = Group::new("kubernetes/.group.yaml", yaml.to_string()).unwrap();
+        assert_eq!(group.default_tags, ["kubernetes", "ops"]);
+        assert_eq!(group.rejected_tags, [TagError::Empty(String::new())]);
+
+        let group = Group::new("notes/.group.yaml", "name: 笔记".to_string()).unwrap();
+        assert!(group.default_tags.is_empty());
+        assert!(group.rejected_tags.is_empty());
+    }
+
+    #[test]
+    fn test_group_parsing_by_extension() {
+        let toml_content = r#"
+            type = "normal"
+            name = "笔记"
+            public = true
+        "#;
+        let group = Group::new("notes/.group.toml", toml_content.to_string()).unwrap();
+        assert_eq!(group.id, "notes");
+        assert_eq!(group.name, "笔记");
+        assert!(group.public);
+
+        let yaml_content = "name: 笔记\npublic: true\n";
+        let group = Group::new("notes/.group.yml", yaml_content.to_string()).unwrap();
+        assert_eq!(group.name, "笔记");
+        assert!(group.public);
+
+        // YAML 内容不是合法的 TOML
+        assert!(Group::new("notes/.group.toml", yaml_content.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_group_unknown_fields() {
+        let (group, unknown) =
+            Group::parse("notes/.group.toml", "pubic = true\nauther = \"x\"".into()).unwrap();
+        assert!(!group.public);
+        let names = unknown.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        // TOML 表按键名排序，不保留文件中的顺序
+        assert_eq!(names, ["auther", "pubic"]);
+        assert_eq!(unknown[0].suggestion, Some("author"));
+        assert_eq!(unknown[1].suggestion, Some("public"));
+
+        let (_, unknown) =
+            Group::parse("notes/.group.yaml", "type: normal\nname: 笔记".into()).unwrap();
+        assert!(unknown.is_empty());
+
+        // 类型错误仍然失败，错误信息包含文件路径
+        let err = Group::parse("notes/.group.yaml", "public: [1]".into()).unwrap_err();
+        assert!(err.to_string().contains("notes/.group.yaml"));
+    }
+
+    #[test]
+    fn test_group_author() {
+        let group =
+            Group::new("notes/.group.toml", "[author]\nname = \"Jane Doe\"".into()).unwrap();
+        let author = group.author.unwrap();
+        assert_eq!(author.id, "jane-doe");
+        assert_eq!(author.name, "Jane Doe");
+
+        let yaml = "author:\n  id: jane\n  name: Jane D.\n";
+        let group = Group::new("notes/.group.yaml", yaml.into()).unwrap();
+        assert_eq!(group.author.unwrap().id, "jane");
+
+        assert!(
+            Group::new("notes/.group.yaml", "name: 笔记".into())
+                .unwrap()
+                .author
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_group_category() {
+        let toml = "[category]\nid = \"notes\"\nname = \"笔记\"\n";
+        let group = Group::new("2023/rust/.group.toml", toml.into()).unwrap();
+        let category = group.category.unwrap();
+        assert_eq!(
+            (category.id.as_str(), category.name.as_str()),
+            ("notes", "笔记")
+        );
+
+        let yaml = "category:\n  name: ' Tech Notes '\n";
+        let category = Group::new("notes/.group.yaml", yaml.into())
+            .unwrap()
+            .category
+            .unwrap();
+        assert_eq!(category.id, "tech-notes");
+        assert_eq!(category.name, "Tech Notes");
+
+        let yaml = "category:\n  id: notes\n";
+        let category = Group::new("notes/.group.yaml", yaml.into())
+            .unwrap()
+            .category
+            .unwrap();
+        assert_eq!(category.name, "notes");
+
+        // 无法生成 id 时视为未设置
+        let group = Group::new("notes/.group.yaml", "category:\n  name: '--'\n".into()).unwrap();
+        assert!(group.category.is_none());
+    }
+
+    #[test]
+    fn test_group_nav() {
+        let group =
+            Group::new("notes/.group.yaml", "nav_order: -1\nhidden: true\n".into()).unwrap();
+        assert_eq!(group.nav_order, Some(-1));
+        assert!(group.hidden);
+
+        let group = Group::new("notes/.group.toml", "public = true\n".into()).unwrap();
+        assert_eq!(group.nav_order, None);
+        assert!(!group.hidden);
+    }
+
+    #[test]
+    fn test_group_retain() {
+        let group = Group::new(
+            "journal/.group.yaml",
+            "retain:\n  max_articles: 100\n".into(),
+        )
+        .unwrap();
+        assert_eq!(
+            group.retain,
+            Some(GroupRetain {
+                max_articles: Some(100),
+                max_age_days: None,
+            })
+        );
+
+        let group = Group::new(
+            "journal/.group.toml",
+            "[retain]\nmax_age_days = 30\n".into(),
+        )
+        .unwrap();
+        assert_eq!(group.retain.and_then(|r| r.max_age_days), Some(30));
+
+        // 两项都未设置时视为不限制
+        let group = Group::new("journal/.group.yaml", "retain: {}\n".into()).unwrap();
+        assert_eq!(group.retain, None);
+
+        assert!(
+            Group::new(
+                "journal/.group.yaml",
+                "retain:\n  max_articles: -1\n".into()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_group_license() {
+        let group = Group::new("notes/.group.yaml", "license: CC-BY-4.0\n".into()).unwrap();
+        assert_eq!(
+            group.license,
+            Some(GroupLicense {
+                id: "CC-BY-4.0".to_string(),
+                name: "CC-BY-4.0".to_string(),
+                url: None,
+            })
+        );
+
+        let toml = "[license]\nid = \"CC-BY-4.0\"\nname = \"CC BY 4.0\"\nurl = \"https://creativecommons.org/licenses/by/4.0/\"\n";
+        let license = Group::new("notes/.group.toml", toml.into())
+            .unwrap()
+            .license
+            .unwrap();
+        assert_eq!(license.name, "CC BY 4.0");
+        assert_eq!(
+            license.url.as_deref(),
+            Some("https://creativecommons.org/licenses/by/4.0/")
+        );
+
+        let group = Group::new("notes/.group.yaml", "name: 笔记\n".into()).unwrap();
+        assert_eq!(group.license, None);
+
+        for yaml in [
+            "license: ''\n",
+            "license: All Rights Reserved\n",
+            "license:\n  name: CC BY\n",
+            "license: [MIT]\n",
+        ] {
+            let err = Group::new("notes/.group.yaml", yaml.into()).unwrap_err();
+            assert!(err.to_string().contains("notes/.group.yaml"), "{yaml}");
+        }
+    }
+
+    #[test]
+    fn test_license_from_str() {
+        let license = "MIT".parse::<GroupLicense>().unwrap();
+        assert_eq!((license.id.as_str(), license.name.as_str()), ("MIT", "MIT"));
+
+        let license = r#"{"id": "LicenseRef-ARR", "name": "All rights reserved"}"#
+            .parse::<GroupLicense>()
+            .unwrap();
+        assert_eq!(license.id, "LicenseRef-ARR");
+        assert_eq!(license.name, "All rights reserved");
+        assert_eq!(license.url, None);
+
+        assert!("CC BY".parse::<GroupLicense>().is_err());
+        assert!("{\"name\": \"x\"}".parse::<GroupLicense>().is_err());
+    }
+
+    #[test]
+    fn test_group_slug() {
+        let group =
+            Group::new("2023/rust-notes/.group.yaml", "slug: /rust-notes/\n".into()).unwrap();
+        assert_eq!(group.id, "2023/rust-notes");
+        assert_eq!(group.slug.as_deref(), Some("rust-notes"));
+        assert_eq!(group.public_id(), "rust-notes");
+
+        let group = Group::new("2023/rust-notes/.group.toml", "url = \"rust\"".into()).unwrap();
+        assert_eq!(group.public_id(), "rust");
+
+        // 未设置或为空时使用目录路径
+        for content in ["name: 笔记", "slug: ' / '"] {
+            let group = Group::new("2023/rust-notes/.group.yaml", content.into()).unwrap();
+            assert_eq!(group.slug, None);
+            assert_eq!(group.public_id(), "2023/rust-notes");
+        }
+    }
+
+    #[test]
+    fn test_author_id() {
+        assert_eq!(author_id("Jane Doe"), "jane-doe");
+        assert_eq!(author_id(" jane_doe! "), "jane-doe");
+        assert_eq!(author_id("张三"), "张三");
+        assert_eq!(author_id("--"), "");
     }
 
     #[test]
@@ -119,4 +916,47 @@ mod tests {
         assert_eq!(group.id, "path/to");
         assert_eq!(group.name, "");
     }
+
+    #[test]
+    fn test_group_path_normalize() {
+        // 写法不同的同一目录得到相同的值
+        for path in [
+            "programming/rust",
+            "/programming/rust/",
+            "programming//rust",
+            "./programming/rust",
+            "programming\\rust",
+        ] {
+            assert_eq!(GroupPath::new(path), "programming/rust", "{path:?}");
+        }
+        // NFD 与 NFC 形式的目录名相同
+        assert_eq!(GroupPath::new("cafe\u{301}"), GroupPath::new("caf\u{e9}"));
+        assert_eq!(GroupPath::new("cafe\u{301}").as_str().len(), "café".len());
+
+        for path in ["", "/", ".", "./"] {
+            assert!(GroupPath::new(path).is_root());
+        }
+
+        let path = GroupPath::new("notes/rust");
+        assert_eq!(path.to_string(), "notes/rust");
+        assert_eq!(AsRef::<str>::as_ref(&path), "notes/rust");
+    }
+
+    #[test]
+    fn test_group_path_from_path() {
+        for file in FileClassifier::GROUP_FILE_NAMES {
+            let path = format!("/notes/rust/{file}");
+            assert_eq!(GroupPath::from(Path::new(&path)), "notes/rust");
+        }
+        assert_eq!(GroupPath::from(Path::new("notes/rust/")), "notes/rust");
+        assert_eq!(GroupPath::from(Path::new(".group.yaml")), "");
+        // 其他文件名不会被去除
+        assert_eq!(GroupPath::from(Path::new("notes/a.md")), "notes/a.md");
+
+        // 组配置和文章的路径写法不同时仍属于同一组
+        let group = Group::new("cafe\u{301}//.group.yaml", "name: 咖啡".into()).unwrap();
+        let builder = crate::content::ArticleBuilder::new("caf\u{e9}/menu.md");
+        assert_eq!(builder.group(), &group.id);
+        assert_eq!(group.public_id(), "caf\u{e9}");
+    }
 }
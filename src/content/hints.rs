@@ -0,0 +1,172 @@
+use super::datetime::{UNPARSABLE_DATE, datetime_examples};
+
+/// 值为字符串的 Front Matter 字段，以 `[` 或 `{` 开头时需要加引号
+const STRING_FIELDS: &[&str] = &["title", "summary", "datetime", "canonical", "cover"];
+
+/// 将 Front Matter 的 YAML 解析错误转换为面向作者的修改建议
+///
+/// `yaml` 为两个 `---` 之间的原文。识别以下情况，可能同时给出多条建议：
+/// - 缺少必需字段：提示添加该字段
+/// - 行首缩进使用了 Tab：YAML 不允许 Tab 缩进
+/// - 以 `[` 或 `{` 开头的值被解析为列表或映射：提示给值加引号
+/// - `datetime` 格式无法解析：列出支持的格式
+///
+/// 都无法识别时原样返回错误信息。
+pub fn front_matter_suggestions(error: &serde_yaml::Error, yaml: &str) -> Vec<String> {
+    let message = error.to_string();
+    let mut suggestions = Vec::new();
+
+    if let Some(field) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(field, _)| field)
+    {
+        suggestions.push(format!("add `{field}:` to the front matter"));
+    }
+
+    for (i, line) in yaml.lines().enumerate() {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        if indent.contains('\t') {
+            suggestions.push(format!(
+                "line {} of the front matter is indented with a tab, YAML forbids tabs, use spaces",
+                i + 1
+            ));
+        }
+    }
+
+    for line in yaml.lines() {
+        let Some((key, value)) = line.trim_start().split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if !value.starts_with(['[', '{']) {
+            continue;
+        }
+        if STRING_FIELDS.contains(&key) || !is_flow_collection(value) {
+            let quoted = value.replace('\\', "\\\\").replace('"', "\\\"");
+            suggestions.push(format!(
+                "a value starting with `{}` is read as a {}, quote it: {key}: \"{quoted}\"",
+                &value[..1],
+                if value.starts_with('[') {
+                    "list"
+                } else {
+                    "mapping"
+                },
+            ));
+        }
+    }
+
+    if message.starts_with(UNPARSABLE_DATE) {
+        suggestions.push(format!(
+            "write `datetime:` in one of the accepted formats: {}",
+            datetime_examples().join(", ")
+        ));
+    }
+
+    if suggestions.is_empty() {
+        suggestions.push(message);
+    }
+    suggestions
+}
+
+/// `value` 是否为在本行闭合、之后只有注释的 YAML 流式列表或映射
+fn is_flow_collection(value: &str) -> bool {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    let rest = value[i + 1..].trim_start();
+                    return rest.is_empty() || rest.starts_with('#');
+                }
+            }
+            _ => (),
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::ArticleBuilder;
+
+    /// 按同步时的方式解析 `yaml`，返回建议
+    fn suggestions(yaml: &str) -> Vec<String> {
+        let markdown = format!("---\n{yaml}\n---\nbody");
+        let err = ArticleBuilder::new("notes/a.md")
+            .content(markdown)
+            .build_with_rendered(String::new(), String::new())
+            .unwrap_err();
+        match err {
+            crate::error::Error::Metadata(crate::content::MetadataError::Syntax {
+                suggestions,
+                ..
+            }) => suggestions,
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_missing_field() {
+        assert_eq!(
+            suggestions("summary: s\ndatetime: 2024-06-01\ntags: []"),
+            ["add `title:` to the front matter"]
+        );
+    }
+
+    #[test]
+    fn test_tab_indent() {
+        let found = suggestions("title: t\nsummary: s\ndatetime: 2024-06-01\ntags:\n\t- rust");
+        assert!(
+            found.contains(
+                &"line 5 of the front matter is indented with a tab, YAML forbids tabs, use spaces"
+                    .to_string()
+            ),
+            "{found:?}"
+        );
+    }
+
+    #[test]
+    fn test_unquoted_flow_value() {
+        assert_eq!(
+            suggestions("title: [Draft] My \"post\"\nsummary: s\ndatetime: 2024-06-01\ntags: []"),
+            [
+                r#"a value starting with `[` is read as a list, quote it: title: "[Draft] My \"post\"""#
+            ]
+        );
+        // 列表字段中完整的流式列表不需要引号
+        assert_eq!(
+            suggestions("title: t\nsummary: {x} and y\ndatetime: 2024-06-01\ntags: [a, b]"),
+            [r#"a value starting with `{` is read as a mapping, quote it: summary: "{x} and y""#]
+        );
+        assert!(is_flow_collection("[a, ']', b] # comment"));
+        assert!(!is_flow_collection("[a] b"));
+        assert!(!is_flow_collection("{a: b"));
+    }
+
+    #[test]
+    fn test_datetime_format() {
+        let found = suggestions("title: t\nsummary: s\ndatetime: 06/01/2024\ntags: []");
+        assert_eq!(found.len(), 1, "{found:?}");
+        assert!(
+            found[0].starts_with("write `datetime:` in one of the accepted formats: "),
+            "{found:?}"
+        );
+        for example in datetime_examples() {
+            assert!(found[0].contains(&example), "{example}");
+        }
+    }
+
+    #[test]
+    fn test_fallback_message() {
+        let err = serde_yaml::from_str::<serde_yaml::Value>("a: b: c").unwrap_err();
+        assert_eq!(front_matter_suggestions(&err, "a: b: c"), [err.to_string()]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::path::{Component, Path, PathBuf};
+
+/// include 指令允许的最大嵌套层数
+pub const MAX_INCLUDE_DEPTH: usize = 3;
+
+/// include 展开错误
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum IncludeError {
+    /// 引用的文件在当前提交中不存在
+    #[error("included file `{path}` not found: {chain}")]
+    Missing { path: String, chain: String },
+
+    /// 引用形成环
+    #[error("include cycle: {0}")]
+    Cycle(String),
+
+    /// 嵌套超过 [`MAX_INCLUDE_DEPTH`] 层
+    #[error("includes nested deeper than {MAX_INCLUDE_DEPTH}: {0}")]
+    TooDeep(String),
+
+    /// 引用路径为空或超出仓库根目录
+    #[error("invalid include path `{0}`")]
+    InvalidPath(String),
+}
+
+/// 展开 include 后的内容
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Expanded {
+    pub content: String,
+    /// 直接或间接引用的文件，按首次出现顺序
+    pub includes: Vec<PathBuf>,
+}
+
+/// 展开 Markdown 中的 `{{include "path"}}` 指令。
+///
+/// - 路径相对仓库根目录，以 `./` 开头时相对 `path` 所在目录
+/// - `load` 读取被引用文件的内容，文件不存在时返回 [`None`]
+/// - 被引用文件中的指令递归展开，最多 [`MAX_INCLUDE_DEPTH`] 层，出现环时返回错误
+pub fn expand_includes(
+    path: &Path,
+    content: &str,
+    load: &mut dyn FnMut(&Path) -> Option<String>,
+) -> Result<Expanded, IncludeError> {
+    let mut includes = Vec::new();
+    let mut chain = vec![path.to_path_buf()];
+    let content = expand(content, &mut chain, &mut includes, load)?;
+
+    Ok(Expanded { content, includes })
+}
+
+/// 展开 `content` 中的指令，`chain` 为从文章到当前文件的引用链
+fn expand(
+    content: &str,
+    chain: &mut Vec<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+    load: &mut dyn FnMut(&Path) -> Option<String>,
+) -> Result<String, IncludeError> {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some((before, target, after)) = next_directive(rest) {
+        out.push_str(before);
+
+        let current = chain.last().expect("chain is never empty");
+        let path = resolve(current, target)
+            .ok_or_else(|| IncludeError::InvalidPath(target.to_string()))?;
+
+        if chain.contains(&path) {
+            chain.push(path);
+            return Err(IncludeError::Cycle(format_chain(chain)));
+        }
+        if chain.len() > MAX_INCLUDE_DEPTH {
+            chain.push(path);
+            return Err(IncludeError::TooDeep(format_chain(chain)));
+        }
+
+        let text = load(&path).ok_or_else(|| IncludeError::Missing {
+            path: path.display().to_string(),
+            chain: format_chain(chain),
+        })?;
+        if !includes.contains(&path) {
+            includes.push(path.clone());
+        }
+
+        chain.push(path);
+        out.push_str(&expand(&text, chain, includes, load)?);
+        chain.pop();
+
+        rest = after;
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// 查找下一个指令，返回指令前的内容、引用路径和指令后的内容
+fn next_directive(s: &str) -> Option<(&str, &str, &str)> {
+    let mut from = 0;
+    while let Some(start) = s[from..].find("{{").map(|i| i + from) {
+        let body = &s[start + 2..];
+        if let Some((target, len)) = parse_directive(body) {
+            return Some((&s[..start], target, &body[len..]));
+        }
+        from = start + 2;
+    }
+    None
+}
+
+/// 解析 `{{` 之后的 `include "path"}}`，返回引用路径和指令剩余部分的长度
+fn parse_directive(s: &str) -> Option<(&str, usize)> {
+    let body = s.trim_start().strip_prefix("include")?;
+    let body = body.strip_prefix(char::is_whitespace)?.trim_start();
+    let body = body.strip_prefix('"')?;
+    let end = body.find('"')?;
+    let rest = body[end + 1..].trim_start().strip_prefix("}}")?;
+
+    Some((&body[..end], s.len() - rest.len()))
+}
+
+/// 计算引用路径，`./` 开头时相对 `current` 所在目录，否则相对仓库根目录
+fn resolve(current: &Path, target: &str) -> Option<PathBuf> {
+    let joined = match target.strip_prefix("./") {
+        Some(relative) => current.parent().unwrap_or(Path::new("")).join(relative),
+        None => PathBuf::from(target.trim_start_matches('/')),
+    };
+
+    let mut path = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir => (),
+            Component::ParentDir if path.pop() => (),
+            _ => return None,
+        }
+    }
+
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+fn format_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn expand_with(files: &[(&str, &str)], path: &str) -> Result<Expanded, IncludeError> {
+        let files = files
+            .iter()
+            .map(|(p, c)| (PathBuf::from(p), c.to_string()))
+            .collect::<HashMap<_, _>>();
+        let content = files[Path::new(path)].clone();
+
+        expand_includes(Path::new(path), &content, &mut |p: &Path| {
+            files.get(p).cloned()
+        })
+    }
+
+    #[test]
+    fn test_expand_nested() {
+        let files = [
+            (
+                "notes/post.md",
+                "A\n{{include \"snippets/disclaimer.md\"}}\nB {{ include \"./local.md\" }} C",
+            ),
+            ("snippets/disclaimer.md", "[{{include \"./sign.md\"}}]"),
+            ("snippets/sign.md", "sign"),
+            ("notes/local.md", "local"),
+        ];
+
+        let expanded = expand_with(&files, "notes/post.md").unwrap();
+        assert_eq!(expanded.content, "A\n[sign]\nB local C");
+        assert_eq!(
+            expanded.includes,
+            [
+                "snippets/disclaimer.md",
+                "snippets/sign.md",
+                "notes/local.md"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_expand_ignores_other_braces() {
+        let files = [("post.md", "{{title}} {{include}} {{include \"x.md\"")];
+
+        let expanded = expand_with(&files, "post.md").unwrap();
+        assert_eq!(expanded.content, files[0].1);
+        assert!(expanded.includes.is_empty());
+    }
+
+    #[test]
+    fn test_expand_depth_limit() {
+        let files = [
+            ("post.md", "{{include \"a.md\"}}"),
+            ("a.md", "{{include \"b.md\"}}"),
+            ("b.md", "{{include \"c.md\"}}"),
+            ("c.md", "{{include \"d.md\"}}"),
+            ("d.md", "d"),
+        ];
+
+        assert_eq!(
+            expand_with(&files, "post.md"),
+            Err(IncludeError::TooDeep(
+                "post.md -> a.md -> b.md -> c.md -> d.md".to_string()
+            ))
+        );
+        assert_eq!(expand_with(&files, "a.md").unwrap().content, "d");
+    }
+
+    #[test]
+    fn test_expand_cycle() {
+        let files = [
+            ("post.md", "{{include \"a.md\"}}"),
+            ("a.md", "{{include \"./b.md\"}}"),
+            ("b.md", "{{include \"a.md\"}}"),
+        ];
+
+        assert_eq!(
+            expand_with(&files, "post.md"),
+            Err(IncludeError::Cycle(
+                "post.md -> a.md -> b.md -> a.md".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_expand_missing() {
+        let files = [
+            ("notes/post.md", "{{include \"./a.md\"}}"),
+            ("notes/a.md", "{{include \"gone.md\"}}"),
+        ];
+
+        let err = expand_with(&files, "notes/post.md").unwrap_err();
+        assert_eq!(
+            err,
+            IncludeError::Missing {
+                path: "gone.md".to_string(),
+                chain: "notes/post.md -> notes/a.md".to_string(),
+            }
+        );
+        assert!(err.to_string().contains("gone.md"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let current = Path::new("notes/rust/post.md");
+
+        assert_eq!(
+            resolve(current, "snippets/a.md"),
+            Some("snippets/a.md".into())
+        );
+        assert_eq!(
+            resolve(current, "/snippets/a.md"),
+            Some("snippets/a.md".into())
+        );
+        assert_eq!(resolve(current, "./a.md"), Some("notes/rust/a.md".into()));
+        assert_eq!(resolve(current, "./../a.md"), Some("notes/a.md".into()));
+        assert_eq!(resolve(current, "../a.md"), None);
+        assert_eq!(resolve(current, ""), None);
+    }
+}
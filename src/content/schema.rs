@@ -0,0 +1,167 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+/// Front Matter 字段的取值类型，见 [`FrontMatterSchema::field_types`]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    #[serde(alias = "integer")]
+    Int,
+    /// 整数和小数都可以
+    #[serde(alias = "number")]
+    Float,
+    #[serde(alias = "boolean")]
+    Bool,
+    #[serde(alias = "array")]
+    List,
+    Map,
+}
+
+impl FieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::List => "list",
+            Self::Map => "map",
+        }
+    }
+
+    /// `value` 是否为该类型
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Int => value.is_i64() || value.is_u64(),
+            Self::Float => value.is_number(),
+            Self::Bool => value.is_bool(),
+            Self::List => value.is_sequence(),
+            Self::Map => value.is_mapping(),
+        }
+    }
+}
+
+/// YAML 值的类型名，用于错误信息
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "list",
+        Value::Mapping(_) => "map",
+        Value::Tagged(_) => "tagged value",
+    }
+}
+
+/// 组内文章 Front Matter 的附加约定，由组配置的 `required_fields` 和 `field_types` 声明
+///
+/// 只检查字段是否存在和取值类型，不影响文章的解析和渲染。声明的字段不再视为未知字段。
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Default)]
+pub struct FrontMatterSchema {
+    /// 必须设置的字段，值为 `null` 时同样视为缺少
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+    /// 字段的取值类型，字段存在时检查
+    #[serde(default)]
+    pub field_types: BTreeMap<String, FieldType>,
+}
+
+impl FrontMatterSchema {
+    /// 是否没有任何约定
+    pub fn is_empty(&self) -> bool {
+        self.required_fields.is_empty() && self.field_types.is_empty()
+    }
+
+    /// 是否声明了字段 `name`
+    pub fn declares(&self, name: &str) -> bool {
+        self.required_fields.iter().any(|f| f == name) || self.field_types.contains_key(name)
+    }
+
+    /// 检查 Front Matter 的顶层字段，按缺少的必需字段、类型错误的字段的顺序返回不符合的项
+    pub fn check(&self, fields: &Mapping) -> Vec<SchemaViolation> {
+        let missing = self
+            .required_fields
+            .iter()
+            .filter(|name| fields.get(name.as_str()).is_none_or(Value::is_null))
+            .map(|name| SchemaViolation::Missing(name.to_owned()));
+        let mistyped = self.field_types.iter().filter_map(|(name, expected)| {
+            let value = fields.get(name.as_str()).filter(|v| !v.is_null())?;
+            (!expected.matches(value)).then(|| SchemaViolation::Mistyped {
+                field: name.to_owned(),
+                expected: *expected,
+                found: type_name(value),
+            })
+        });
+        missing.chain(mistyped).collect()
+    }
+}
+
+/// Front Matter 不符合所在组 [`FrontMatterSchema`] 的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaViolation {
+    /// 缺少必需字段
+    Missing(String),
+    /// 字段的取值类型错误
+    Mistyped {
+        field: String,
+        expected: FieldType,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "missing required field `{field}`"),
+            Self::Mistyped {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field `{field}` must be {}, found {found}",
+                expected.as_str()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_check() {
+        let schema: FrontMatterSchema = serde_yaml::from_str(
+            "required_fields: [doi, authors]\nfield_types: { doi: string, year: int, score: number }\n",
+        )
+        .unwrap();
+        let fields = |yaml: &str| serde_yaml::from_str::<Mapping>(yaml).unwrap();
+
+        let ok = fields("doi: '10.1000/1'\nauthors: [a]\nyear: 2024\nscore: 3\n");
+        assert!(schema.check(&ok).is_empty());
+
+        let violations = schema.check(&fields("doi: 10.5\nauthors:\nyear: '2024'\n"));
+        assert_eq!(
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "missing required field `authors`",
+                "field `doi` must be string, found float",
+                "field `year` must be int, found string",
+            ]
+        );
+
+        assert!(schema.declares("year"));
+        assert!(!schema.declares("title"));
+        assert!(serde_yaml::from_str::<FrontMatterSchema>("field_types: { a: date }").is_err());
+    }
+}
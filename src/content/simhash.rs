@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, iter::Peekable, str::Chars};
+
+use super::FrontMatter;
+
+/// 两篇文章的 [`simhash`] 相差不超过该位数时视为近似重复
+pub const NEAR_DUPLICATE_BITS: u32 = 8;
+
+/// 参与计算的最少词数，更短的文章不计算指纹，避免只有几个词的占位文章互相匹配
+pub const MIN_SIMHASH_TOKENS: usize = 16;
+
+/// 连续几个词作为一个特征
+const SHINGLE: usize = 2;
+
+/// 计算 Markdown 原文的 64 位 simhash。
+///
+/// 去掉 Front Matter 后统一为小写，按字母数字切分为词，中日韩等非 ASCII 文字每个字符为一个词，
+/// 以连续 2 个词为特征用 FNV-1a 散列后按位投票。只遍历一次原文，内容只有少量改动时结果只有少数位不同，
+/// 用 [`distance`] 比较。词数少于 [`MIN_SIMHASH_TOKENS`] 时返回 [`None`]。
+pub fn simhash(markdown: &str) -> Option<i64> {
+    let mut votes = [0i32; 64];
+    let mut window = [0u64; SHINGLE];
+    let mut tokens = 0;
+
+    for token in Tokens::new(FrontMatter::strip(markdown)) {
+        window.rotate_left(1);
+        window[SHINGLE - 1] = token;
+        tokens += 1;
+        if tokens < SHINGLE {
+            continue;
+        }
+        let feature = window
+            .iter()
+            .fold(FNV_OFFSET, |h, t| fnv1a(h, &t.to_le_bytes()));
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            *vote += if feature >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+
+    if tokens < MIN_SIMHASH_TOKENS {
+        return None;
+    }
+    let hash = votes
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v > 0)
+        .fold(0u64, |h, (bit, _)| h | 1 << bit);
+    Some(hash as i64)
+}
+
+/// 两个 [`simhash`] 不同的位数
+pub fn distance(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 按 [`NEAR_DUPLICATE_BITS`] 将 `hashes` 连通分组，返回包含至少两项的组中各项的下标
+///
+/// 组内每项至少与另一项相近，组按第一项的下标排序。两两比较，适合文章数不多的场景。
+pub fn near_duplicate_clusters(hashes: &[i64]) -> Vec<Vec<usize>> {
+    fn find(root: &mut [usize], mut i: usize) -> usize {
+        while root[i] != i {
+            root[i] = root[root[i]];
+            i = root[i];
+        }
+        i
+    }
+
+    let mut root = (0..hashes.len()).collect::<Vec<_>>();
+    for i in 0..hashes.len() {
+        for j in i + 1..hashes.len() {
+            if distance(hashes[i], hashes[j]) <= NEAR_DUPLICATE_BITS {
+                let (a, b) = (find(&mut root, i), find(&mut root, j));
+                root[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut clusters = BTreeMap::<usize, Vec<usize>>::new();
+    for i in 0..hashes.len() {
+        let r = find(&mut root, i);
+        clusters.entry(r).or_default().push(i);
+    }
+    clusters.into_values().filter(|c| c.len() > 1).collect()
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, b| (h ^ u64::from(*b)).wrapping_mul(FNV_PRIME))
+}
+
+/// 按字符切分出的词的散列，见 [`simhash`]
+struct Tokens<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while let Some(c) = self.chars.next() {
+            if c.is_ascii_alphanumeric() {
+                let mut hash = fnv1a(FNV_OFFSET, &[c.to_ascii_lowercase() as u8]);
+                while let Some(c) = self.chars.next_if(char::is_ascii_alphanumeric) {
+                    hash = fnv1a(hash, &[c.to_ascii_lowercase() as u8]);
+                }
+                return Some(hash);
+            }
+            if c.is_alphanumeric() {
+                let mut buf = [0; 4];
+                let c = c.to_lowercase().next().unwrap_or(c);
+                return Some(fnv1a(FNV_OFFSET, c.encode_utf8(&mut buf).as_bytes()));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = "---\ntitle: Ownership\n---\n\
+        # Ownership\n\n\
+        Rust manages memory through a system of ownership with a set of rules that the compiler checks. \
+        If any of the rules are violated, the program will not compile. None of the features of ownership \
+        will slow down your program while it is running. Each value in Rust has an owner, there can only \
+        be one owner at a time, and when the owner goes out of scope the value will be dropped.\n";
+
+    #[test]
+    fn test_simhash_identical() {
+        let a = simhash(ORIGINAL).unwrap();
+        assert_eq!(simhash(ORIGINAL), Some(a));
+        // Front Matter、大小写和标点不参与计算
+        let retitled = ORIGINAL
+            .replace("title: Ownership", "title: Copy\ntags: [rust]")
+            .replace("# Ownership", "## OWNERSHIP!");
+        assert_eq!(simhash(&retitled), Some(a));
+    }
+
+    #[test]
+    fn test_simhash_near_duplicate() {
+        let a = simhash(ORIGINAL).unwrap();
+        let edited = ORIGINAL.replace("will slow down", "slow down");
+        let b = simhash(&edited).unwrap();
+        assert_ne!(a, b);
+        assert!(distance(a, b) <= NEAR_DUPLICATE_BITS, "{}", distance(a, b));
+    }
+
+    #[test]
+    fn test_simhash_different() {
+        let a = simhash(ORIGINAL).unwrap();
+        let other = "Tokio is an asynchronous runtime for the Rust programming language. It provides \
+            the building blocks needed for writing network applications, gives the flexibility to target \
+            a wide range of systems, from large servers with dozens of cores to small embedded devices.";
+        let b = simhash(other).unwrap();
+        assert!(
+            distance(a, b) > 2 * NEAR_DUPLICATE_BITS,
+            "{}",
+            distance(a, b)
+        );
+    }
+
+    #[test]
+    fn test_near_duplicate_clusters() {
+        let hashes = [0, 0xff, 0xffff, -1, -2, 0x5555_5555_5555_5555];
+        // 0 与 2 相差 16 位，经 1 连通
+        assert_eq!(
+            near_duplicate_clusters(&hashes),
+            vec![vec![0, 1, 2], vec![3, 4]]
+        );
+        assert!(near_duplicate_clusters(&[0, -1]).is_empty());
+    }
+
+    #[test]
+    fn test_simhash_short_and_cjk() {
+        assert_eq!(simhash("---\ntitle: t\n---\nTODO"), None);
+        // 中文每个字符为一个词
+        let zh = "所有权是 Rust 最独特的功能，它让 Rust 无需垃圾回收器即可保证内存安全。";
+        assert!(simhash(zh).is_some());
+        assert_eq!(simhash(zh), simhash(&zh.replace('，', " ")));
+    }
+}
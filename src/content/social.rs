@@ -0,0 +1,206 @@
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::{Article, decode_entity, plain_text, truncate_text};
+
+/// 分享卡片描述的最大字符数
+pub const SOCIAL_DESCRIPTION_CHARS: usize = 200;
+
+/// 分享文章链接时展示的卡片信息，同步时按文章内容生成
+///
+/// 只保存来自文章的值，图片保持文章中的写法，可能是相对地址。站点地址和默认图片在响应时展开，
+/// 见 [`SocialMeta::image_url`]，修改配置后不需要重建。
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SocialMeta {
+    /// 纯文本描述，摘要为空时取正文，都为空时为 `None`
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 封面图片，Front Matter 的 `cover`，未设置时为正文中的第一张图片
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+impl SocialMeta {
+    /// 由渲染后的摘要和正文生成卡片信息
+    ///
+    /// 描述去掉标签后截断到 [`SOCIAL_DESCRIPTION_CHARS`] 个字符，截断规则与列表中的纯文本摘要相同，见 [`truncate_text`]。
+    pub fn from_article(article: &Article) -> Self {
+        let description = [&article.frontmatter.summary, &article.rendered_content]
+            .into_iter()
+            .map(|html| plain_text(html))
+            .find(|text| !text.is_empty())
+            .map(|text| truncate_text(&text, SOCIAL_DESCRIPTION_CHARS));
+        let image = article
+            .frontmatter
+            .cover
+            .clone()
+            .or_else(|| first_image(&article.rendered_content));
+
+        Self { description, image }
+    }
+
+    /// 封面图片的绝对地址，没有封面时使用 `default_image`，都没有时为 `None`
+    ///
+    /// 相对地址按文章在站点上的地址 `page_url` 解析，无法解析时原样返回。
+    pub fn image_url(&self, page_url: &str, default_image: Option<&str>) -> Option<String> {
+        let image = self.image.as_deref().or(default_image)?;
+        let resolved = Url::parse(page_url).and_then(|base| base.join(image));
+        Some(resolved.map_or_else(|_| image.to_string(), String::from))
+    }
+}
+
+/// 正文中第一张图片的地址，跳过 `data:` 图片
+///
+/// GitHub 渲染时外部图片经过代理，`src` 为代理地址，这时使用 `data-canonical-src` 中的原地址。
+fn first_image(html: &str) -> Option<String> {
+    let mut rest = html;
+    while let Some(start) = rest.find("<img") {
+        let tag = &rest[start..];
+        let tag = &tag[..tag.find('>').map_or(tag.len(), |end| end + 1)];
+        let src = attribute(tag, "data-canonical-src").or_else(|| attribute(tag, "src"));
+        if let Some(src) = src.filter(|s| !s.is_empty() && !s.starts_with("data:")) {
+            return Some(src);
+        }
+        rest = &rest[start + tag.len()..];
+    }
+    None
+}
+
+/// 标签中双引号属性 `name` 的值，解码字符实体
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!("{name}=\"");
+    let mut at = 0;
+    while let Some(offset) = tag[at..].find(&pattern) {
+        let start = at + offset;
+        let value_start = start + pattern.len();
+        let len = tag[value_start..].find('"')?;
+        // 属性名之前必须是空白，避免 `src` 匹配到 `data-canonical-src`
+        if tag[..start].ends_with(|c: char| c.is_ascii_whitespace()) {
+            return Some(unescape(tag[value_start..value_start + len].trim()));
+        }
+        at = value_start + len;
+    }
+    None
+}
+
+/// 解码属性值中的字符实体
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let (decoded, len) = decode_entity(&rest[start..]);
+        out.push_str(decoded);
+        rest = &rest[start + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::content::{FrontMatter, TRUNCATION_MARKER};
+
+    const PAGE: &str = "https://notes.example.com/posts/intro";
+
+    fn article(summary: &str, content: &str, cover: Option<&str>) -> Article {
+        let datetime = DateTime::parse_from_rfc3339("2024-06-01T00:00:00+08:00").unwrap();
+        Article {
+            group: "posts".into(),
+            slug: "intro".to_string(),
+            frontmatter: FrontMatter {
+                title: "Intro".to_string(),
+                summary: summary.to_string(),
+                datetime,
+                tags: vec![],
+                aliases: vec![],
+                canonical: None,
+                public: None,
+                raw_html: false,
+                cover: cover.map(str::to_string),
+            },
+            rendered_content: content.to_string(),
+            namespaced_content: None,
+            rendered_by: None,
+            updated_at: datetime,
+            source_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_description_truncation() {
+        let exact = "a".repeat(SOCIAL_DESCRIPTION_CHARS);
+        let meta = SocialMeta::from_article(&article(&format!("<p>{exact}</p>"), "", None));
+        assert_eq!(meta.description.as_deref(), Some(exact.as_str()));
+
+        // 超出一个字符时在单词边界截断
+        let words = format!("{}tailss", "word ".repeat(39));
+        assert_eq!(words.chars().count(), SOCIAL_DESCRIPTION_CHARS + 1);
+        let meta = SocialMeta::from_article(&article(&words, "", None));
+        let description = meta.description.unwrap();
+        assert_eq!(
+            description,
+            format!("{}{TRUNCATION_MARKER}", "word ".repeat(39).trim_end())
+        );
+
+        // 中日韩文字按字符截断
+        let cjk = "字".repeat(SOCIAL_DESCRIPTION_CHARS + 10);
+        let meta = SocialMeta::from_article(&article(&format!("<p>{cjk}</p>"), "", None));
+        assert_eq!(
+            meta.description.unwrap(),
+            format!(
+                "{}{TRUNCATION_MARKER}",
+                "字".repeat(SOCIAL_DESCRIPTION_CHARS)
+            )
+        );
+    }
+
+    #[test]
+    fn test_without_summary_or_images() {
+        let meta =
+            SocialMeta::from_article(&article("", "<h2>Notes</h2><p>Body &amp; more</p>", None));
+        assert_eq!(meta.description.as_deref(), Some("Notes Body & more"));
+        assert_eq!(meta.image, None);
+        assert_eq!(meta.image_url(PAGE, None), None);
+        assert_eq!(
+            meta.image_url(PAGE, Some("/og.png")).as_deref(),
+            Some("https://notes.example.com/og.png")
+        );
+
+        let meta = SocialMeta::from_article(&article("", "", None));
+        assert_eq!(meta, SocialMeta::default());
+    }
+
+    #[test]
+    fn test_image() {
+        let content = concat!(
+            r#"<p><img src="data:image/png;base64,AAAA"></p>"#,
+            r#"<a href="x"><img src="https://camo.githubusercontent.com/abc" data-canonical-src="https://img.example.com/a.png?w=1&amp;h=2" style="max-width: 100%;"></a>"#,
+        );
+        let meta = SocialMeta::from_article(&article("s", content, None));
+        assert_eq!(
+            meta.image.as_deref(),
+            Some("https://img.example.com/a.png?w=1&h=2")
+        );
+
+        let meta = SocialMeta::from_article(&article("s", r#"<img src="images/a.png">"#, None));
+        assert_eq!(
+            meta.image_url(PAGE, Some("/og.png")).as_deref(),
+            Some("https://notes.example.com/posts/images/a.png")
+        );
+
+        // `cover` 优先于正文中的图片
+        let meta = SocialMeta::from_article(&article(
+            "s",
+            r#"<img src="images/a.png">"#,
+            Some("/covers/intro.jpg"),
+        ));
+        assert_eq!(
+            meta.image_url(PAGE, None).as_deref(),
+            Some("https://notes.example.com/covers/intro.jpg")
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use pinyin::ToPinyin;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+/// 排序键中罗马化部分与原标题之间的分隔符，小于所有可打印字符，罗马化部分相同时按原标题排序
+const SEPARATOR: char = '\u{1}';
+
+/// 首字母不是 `A`–`Z` 的标题所在的分组
+pub const OTHER_INITIAL: char = '#';
+
+/// 生成标题的排序键，按字节比较即为标题的字母顺序
+///
+/// 汉字转换为不带声调的拼音，多音字取最常用的读音，音节之间以空格分隔；其他文字转为小写，
+/// 全角字符转为半角，并去掉重音等附加符号，开头的标点和符号忽略。最后追加原标题，罗马化相同的标题按原标题排序，
+/// 结果总是确定的。数字开头的标题排在字母之前，其他文字开头的排在字母之后。
+/// 数据库中按字节比较（PostgreSQL 中为 `COLLATE "C"`），不依赖数据库的排序规则。
+pub fn title_sort_key(title: &str) -> String {
+    let mut romanized = String::new();
+    let mut after_syllable = false;
+    for c in title.trim().nfkc() {
+        match c.to_pinyin() {
+            Some(pinyin) => {
+                if !romanized.is_empty() && !romanized.ends_with(' ') {
+                    romanized.push(' ');
+                }
+                romanized.push_str(pinyin.plain());
+                after_syllable = true;
+            }
+            None if c.is_whitespace() => {
+                if !romanized.is_empty() && !romanized.ends_with(' ') {
+                    romanized.push(' ');
+                }
+                after_syllable = false;
+            }
+            // 开头的标点和符号不参与排序，如书名号和引号
+            None if romanized.is_empty() && !c.is_alphanumeric() => {}
+            None => {
+                if after_syllable {
+                    romanized.push(' ');
+                    after_syllable = false;
+                }
+                romanized.extend(c.to_lowercase());
+            }
+        }
+    }
+
+    let mut key = romanized
+        .trim_end()
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect::<String>();
+    key.push(SEPARATOR);
+    key.push_str(title.trim());
+    key
+}
+
+/// 排序键所在的首字母分组，`A`–`Z` 之外的为 [`OTHER_INITIAL`]
+pub fn title_initial(sort_key: &str) -> char {
+    match sort_key.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+        _ => OTHER_INITIAL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn romanized(title: &str) -> String {
+        let key = title_sort_key(title);
+        key.split(SEPARATOR).next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_romanize() {
+        assert_eq!(romanized("中文标题"), "zhong wen biao ti");
+        assert_eq!(romanized("Rust 入门"), "rust ru men");
+        assert_eq!(romanized("学习Rust的第1天"), "xue xi rust de di 1 tian");
+        // 多音字取最常用的读音，ü 去掉附加符号
+        assert_eq!(romanized("银行"), "yin xing");
+        assert_eq!(romanized("绿色"), "lu se");
+        // 全角字符、大小写和重音
+        assert_eq!(romanized("  Ｈｅｌｌｏ  Éclair "), "hello eclair");
+        assert_eq!(romanized("《三体》读后感"), "san ti 》 du hou gan");
+        assert_eq!(romanized("\"Quoted\" title"), "quoted\" title");
+        assert_eq!(romanized("..."), "");
+        assert_eq!(romanized(""), "");
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let mut titles = vec![
+            "中文标题",
+            "zebra",
+            "Apple",
+            "阿里",
+            "2024 总结",
+            "《三体》读后感",
+            "banana",
+            "北京",
+            "apple",
+        ];
+        titles.sort_by_key(|t| title_sort_key(t));
+        assert_eq!(
+            titles,
+            [
+                "2024 总结",
+                "阿里",
+                "Apple",
+                "apple",
+                "banana",
+                "北京",
+                "《三体》读后感",
+                "zebra",
+                "中文标题",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_initial() {
+        let initial = |title: &str| title_initial(&title_sort_key(title));
+        assert_eq!(initial("中文"), 'Z');
+        assert_eq!(initial("apple"), 'A');
+        assert_eq!(initial("Éclair"), 'E');
+        assert_eq!(initial("2024 总结"), '#');
+        assert_eq!(initial("《三体》"), 'S');
+        assert_eq!(initial("¿Qué?"), 'Q');
+        assert_eq!(initial("Привет"), '#');
+        assert_eq!(initial("..."), '#');
+        assert_eq!(initial(""), '#');
+    }
+}
@@ -0,0 +1,125 @@
+/// 纯文本摘要被截断时追加的标记
+pub const TRUNCATION_MARKER: &str = "…";
+
+/// 纯文本摘要的默认长度，按字符计
+pub const DEFAULT_PLAIN_SUMMARY_CHARS: usize = 200;
+
+/// 将渲染后的 HTML 摘要转换为纯文本。
+///
+/// 去掉所有标签，解码常见的字符实体，连续空白合并为一个空格。
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find(['<', '&']) {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with('<') {
+            // 标签按空白处理，避免相邻块级元素的文字粘连
+            text.push(' ');
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else {
+            let (decoded, len) = decode_entity(rest);
+            text.push_str(decoded);
+            rest = &rest[len..];
+        }
+    }
+    text.push_str(rest);
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 解码 `s` 开头的字符实体，返回解码结果和消耗的长度；无法识别时原样保留 `&`
+pub(crate) fn decode_entity(s: &str) -> (&str, usize) {
+    const ENTITIES: [(&str, &str); 6] = [
+        ("&amp;", "&"),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&quot;", "\""),
+        ("&#39;", "'"),
+        ("&nbsp;", " "),
+    ];
+
+    ENTITIES
+        .iter()
+        .find(|(entity, _)| s.starts_with(entity))
+        .map_or(("&", 1), |(entity, decoded)| (*decoded, entity.len()))
+}
+
+/// 将纯文本截断到最多 `budget` 个字符，截断时追加 [`TRUNCATION_MARKER`]（不计入长度）。
+///
+/// - 截断点落在拉丁文等单词中间时回退到单词开头，整段只有一个单词时直接截断
+/// - 中日韩文字之间没有空格，任意两个字符之间都可以截断
+pub fn truncate_text(text: &str, budget: usize) -> String {
+    let Some((cut, next)) = text.char_indices().nth(budget) else {
+        return text.to_string();
+    };
+
+    let mut head = &text[..cut];
+    let inside_word = head.chars().next_back().is_some_and(is_word_char) && is_word_char(next);
+    if inside_word && let Some((i, c)) = head.char_indices().rev().find(|(_, c)| !is_word_char(*c))
+    {
+        head = &head[..i + c.len_utf8()];
+    }
+
+    format!("{}{}", head.trim_end(), TRUNCATION_MARKER)
+}
+
+/// 是否为需要按单词整体保留的字符
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_cjk(c)
+}
+
+/// 中日韩文字，包括假名和谚文
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF       // 平假名、片假名
+            | 0x3400..=0x4DBF // 扩展 A
+            | 0x4E00..=0x9FFF // 基本汉字
+            | 0xAC00..=0xD7AF // 谚文音节
+            | 0xF900..=0xFAFF // 兼容汉字
+            | 0x20000..=0x2FFFF // 扩展 B 及之后
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let html = "<p>Rust &amp; <strong>Go</strong></p>\n<p>a&lt;b &copy;</p>";
+        assert_eq!(plain_text(html), "Rust & Go a<b &copy;");
+        assert_eq!(plain_text("<p>未闭合 <a href=\"x\""), "未闭合");
+        assert_eq!(plain_text(""), "");
+    }
+
+    #[test]
+    fn test_truncate_word_boundary() {
+        let text = "The quick brown fox";
+
+        assert_eq!(truncate_text(text, 19), text);
+        assert_eq!(truncate_text(text, 100), text);
+        assert_eq!(truncate_text(text, 12), "The quick…");
+        assert_eq!(truncate_text(text, 10), "The quick…");
+        assert_eq!(truncate_text(text, 9), "The quick…");
+        assert_eq!(truncate_text("Supercalifragilistic", 5), "Super…");
+        assert_eq!(truncate_text(text, 0), "…");
+    }
+
+    #[test]
+    fn test_truncate_multibyte() {
+        assert_eq!(truncate_text("你好世界，欢迎阅读", 4), "你好世界…");
+        assert_eq!(truncate_text("こんにちは世界", 5), "こんにちは…");
+
+        // 中文后的英文单词整体保留或整体去掉
+        assert_eq!(truncate_text("学习Rust语言", 4), "学习…");
+        assert_eq!(truncate_text("学习Rust语言", 6), "学习Rust…");
+
+        // 按字符而不是字节计数
+        assert_eq!(truncate_text("café au lait", 4), "café…");
+        assert_eq!(truncate_text("🦀🦀🦀", 2), "🦀🦀…");
+    }
+}
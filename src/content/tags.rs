@@ -0,0 +1,415 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+use utoipa::ToSchema;
+
+/// 标签层级分隔符
+pub const TAG_SEPARATOR: char = '/';
+
+/// 标签 `tag` 是否等于 `filter` 或是它的后代，如 `lang/rust` 匹配 `lang`，`language` 不匹配
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    tag.strip_prefix(filter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+}
+
+/// 规范化后标签的最大长度，按字符计
+pub const MAX_TAG_LEN: usize = 64;
+
+/// 不可见、不参与连接的字符，规范化时去掉：零宽空格、词连接符、BOM 和软连字符
+const INVISIBLE: [char; 4] = ['\u{200b}', '\u{2060}', '\u{feff}', '\u{ad}'];
+
+/// 零宽连接符和零宽非连接符，只在词中间有意义，如 emoji 序列 `👩‍💻`，词首尾的去掉
+const JOINERS: [char; 2] = ['\u{200c}', '\u{200d}'];
+
+/// 被丢弃的标签，保存原始的标签
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TagError {
+    #[error("tag {} is empty after normalization, dropped", shorten(.0))]
+    Empty(String),
+    #[error("tag {} is longer than {MAX_TAG_LEN} characters, dropped", shorten(.0))]
+    TooLong(String),
+    #[error("tag {} has only punctuation, dropped", shorten(.0))]
+    Punctuation(String),
+}
+
+/// 警告中显示的标签，转义控制字符，过长时截断
+fn shorten(tag: &str) -> String {
+    const SHOWN: usize = 32;
+    match tag.char_indices().nth(SHOWN) {
+        Some((i, _)) => format!("{:?}…", &tag[..i]),
+        None => format!("{tag:?}"),
+    }
+}
+
+/// 是否为标点：ASCII 标点、通用标点、CJK 标点和全角标点
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c,
+            '\u{a1}'..='\u{bf}'
+            | '\u{2010}'..='\u{2027}'
+            | '\u{2030}'..='\u{205e}'
+            | '\u{3001}'..='\u{3003}'
+            | '\u{3008}'..='\u{3011}'
+            | '\u{3014}'..='\u{301f}'
+            | '\u{ff01}'..='\u{ff0f}'
+            | '\u{ff1a}'..='\u{ff20}'
+            | '\u{ff3b}'..='\u{ff40}'
+            | '\u{ff5b}'..='\u{ff65}')
+}
+
+/// 规范化单个标签，标签的校验规则都在这里
+///
+/// 按 Unicode NFC 规范化，去掉控制字符和不可见字符，按 `/` 分层后每层去掉首尾空白，
+/// 中间连续的空白（包括换行和全角空格）合并为一个空格，忽略空的层级。
+/// 规范化后为空、超过 [`MAX_TAG_LEN`] 个字符或只有标点时返回 [`TagError`]。
+pub fn normalize_tag(tag: &str) -> Result<String, TagError> {
+    let cleaned = tag
+        .nfc()
+        .filter(|c| c.is_whitespace() || !c.is_control())
+        .filter(|c| !INVISIBLE.contains(c))
+        .collect::<String>();
+    let normalized = cleaned
+        .split(TAG_SEPARATOR)
+        .map(|segment| {
+            segment
+                .split_whitespace()
+                .map(|word| word.trim_matches(JOINERS))
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(&TAG_SEPARATOR.to_string());
+
+    if normalized.is_empty() {
+        Err(TagError::Empty(tag.to_string()))
+    } else if normalized.chars().count() > MAX_TAG_LEN {
+        Err(TagError::TooLong(tag.to_string()))
+    } else if normalized
+        .chars()
+        .all(|c| c == TAG_SEPARATOR || c == ' ' || is_punctuation(c))
+    {
+        Err(TagError::Punctuation(tag.to_string()))
+    } else {
+        Ok(normalized)
+    }
+}
+
+/// 按 [`normalize_tag`] 规范化标签列表，重复的标签只保留第一个，同时返回被丢弃的标签
+pub fn normalize_tags(tags: impl IntoIterator<Item = String>) -> (Vec<String>, Vec<TagError>) {
+    let (mut normalized, mut rejected) = (Vec::new(), Vec::new());
+    for tag in tags {
+        match normalize_tag(&tag) {
+            Ok(tag) if !normalized.contains(&tag) => normalized.push(tag),
+            Ok(_) => (),
+            Err(e) => rejected.push(e),
+        }
+    }
+    (normalized, rejected)
+}
+
+/// 合并文章自身的标签和组的默认标签，按首次出现的顺序去重
+pub fn merge_tags(own: &[String], defaults: &[String]) -> Vec<String> {
+    let mut merged = Vec::with_capacity(own.len() + defaults.len());
+    for tag in own.iter().chain(defaults) {
+        if !merged.contains(tag) {
+            merged.push(tag.clone());
+        }
+    }
+    merged
+}
+
+/// 标签树中的节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TagNode {
+    /// 本层名称，如 `rust`
+    pub name: String,
+    /// 完整标签，如 `lang/rust`，可直接用于文章列表的 `tags` 过滤
+    pub path: String,
+    /// 带有该标签或其后代标签的文章数，每篇文章只计一次
+    pub count: i64,
+    /// 直接带有该标签的文章数，只作为上层出现的标签为 0
+    pub direct: i64,
+    /// 下一层标签，按名称排序
+    #[schema(no_recursion)]
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    count: i64,
+    direct: i64,
+    children: BTreeMap<&'a str, Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    fn child(&mut self, path: &[&'a str]) -> &mut Node<'a> {
+        path.iter()
+            .fold(self, |node, name| node.children.entry(*name).or_default())
+    }
+
+    fn into_tags(self, parent: &str) -> Vec<TagNode> {
+        self.children
+            .into_iter()
+            .map(|(name, node)| {
+                let path = if parent.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{parent}{TAG_SEPARATOR}{name}")
+                };
+                TagNode {
+                    name: name.to_string(),
+                    count: node.count,
+                    direct: node.direct,
+                    children: node.into_tags(&path),
+                    path,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 由每篇文章的标签构建标签树
+///
+/// 标签以 `/` 分隔层级，空的层级被忽略，如 `lang//rust/` 视为 `lang/rust`。
+/// 上层节点的 `count` 汇总所有后代，同一篇文章带有多个后代标签时只计一次。
+pub fn tag_tree(articles: &[Vec<String>]) -> Vec<TagNode> {
+    let mut root = Node::default();
+
+    for tags in articles {
+        let tags = tags
+            .iter()
+            .map(|tag| {
+                tag.split(TAG_SEPARATOR)
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|segments| !segments.is_empty())
+            .collect::<BTreeSet<_>>();
+        let nodes = tags
+            .iter()
+            .flat_map(|segments| (1..=segments.len()).map(move |n| &segments[..n]))
+            .collect::<BTreeSet<_>>();
+
+        for path in nodes {
+            root.child(path).count += 1;
+        }
+        for path in &tags {
+            root.child(path).direct += 1;
+        }
+    }
+
+    root.into_tags("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn articles(tags: &[&[&str]]) -> Vec<Vec<String>> {
+        tags.iter()
+            .map(|t| t.iter().map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_and_merge_tags() {
+        let (defaults, rejected) =
+            normalize_tags([" kubernetes ", "/k8s/", "", "kubernetes", "ops/ "].map(String::from));
+        assert_eq!(defaults, ["kubernetes", "k8s", "ops"]);
+        assert_eq!(rejected, [TagError::Empty(String::new())]);
+
+        let own = ["ops".to_string(), "helm".to_string()];
+        assert_eq!(
+            merge_tags(&own, &defaults),
+            ["ops", "helm", "kubernetes", "k8s"]
+        );
+        assert_eq!(merge_tags(&[], &defaults), defaults);
+    }
+
+    #[test]
+    fn test_normalize_tag_whitespace() {
+        for (tag, expected) in [
+            ("rust", "rust"),
+            ("  machine   learning ", "machine learning"),
+            ("machine\nlearning", "machine learning"),
+            ("tab\there", "tab here"),
+            ("全角\u{3000}空格", "全角 空格"),
+            ("no\u{a0}break", "no break"),
+            (" lang / rust ", "lang/rust"),
+            ("lang//rust/", "lang/rust"),
+            ("/lang/", "lang"),
+        ] {
+            assert_eq!(normalize_tag(tag).as_deref(), Ok(expected), "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_tag_invisible() {
+        for (tag, expected) in [
+            ("ru\u{7}st", "rust"),
+            ("\u{1b}[31mred", "[31mred"),
+            ("zero\u{200b}width", "zerowidth"),
+            ("\u{feff}bom", "bom"),
+            ("soft\u{ad}hyphen", "softhyphen"),
+            ("\u{200d}joined\u{200d}", "joined"),
+            ("a \u{200c} b", "a b"),
+        ] {
+            assert_eq!(normalize_tag(tag).as_deref(), Ok(expected), "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_tag_scripts() {
+        for (tag, expected) in [
+            ("编程语言", "编程语言"),
+            ("日本語/プログラミング", "日本語/プログラミング"),
+            ("한국어", "한국어"),
+            ("Rust与Go", "Rust与Go"),
+            ("Ελληνικά", "Ελληνικά"),
+            ("עברית", "עברית"),
+            ("🦀", "🦀"),
+            ("rust 🦀", "rust 🦀"),
+            // 零宽连接符组成的 emoji 序列保持不变
+            ("👩\u{200d}💻", "👩\u{200d}💻"),
+            ("👨\u{200d}👩\u{200d}👧", "👨\u{200d}👩\u{200d}👧"),
+            ("🏳\u{fe0f}\u{200d}🌈", "🏳\u{fe0f}\u{200d}🌈"),
+            // 组合字符按 NFC 合并
+            ("cafe\u{301}", "caf\u{e9}"),
+            ("c++", "c++"),
+            ("C#", "C#"),
+            (".net", ".net"),
+        ] {
+            assert_eq!(normalize_tag(tag).as_deref(), Ok(expected), "{tag:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_tag_rejected() {
+        for tag in [
+            "", "   ", "/", " / / ", "\n\t", "\u{200b}", "\u{200d}", "\u{7}",
+        ] {
+            assert_eq!(normalize_tag(tag), Err(TagError::Empty(tag.to_string())));
+        }
+        for tag in ["!!!", "...", "-", "#/?", "、。", "「」", "？！", "——", "…"] {
+            assert_eq!(
+                normalize_tag(tag),
+                Err(TagError::Punctuation(tag.to_string())),
+                "{tag:?}"
+            );
+        }
+
+        // 长度按规范化后的字符计算
+        let max = "字".repeat(MAX_TAG_LEN);
+        assert_eq!(normalize_tag(&max).as_deref(), Ok(max.as_str()));
+        assert_eq!(
+            normalize_tag(&format!("  {max}  ")).as_deref(),
+            Ok(max.as_str())
+        );
+        let long = "a".repeat(MAX_TAG_LEN + 1);
+        assert_eq!(normalize_tag(&long), Err(TagError::TooLong(long.clone())));
+        assert_eq!(
+            normalize_tag(&"🦀".repeat(MAX_TAG_LEN + 1)),
+            Err(TagError::TooLong("🦀".repeat(MAX_TAG_LEN + 1)))
+        );
+    }
+
+    #[test]
+    fn test_tag_error_message() {
+        let long = format!("{}\n{}", "a".repeat(200), "b".repeat(200));
+        let message = normalize_tag(&long).unwrap_err().to_string();
+        assert_eq!(
+            message,
+            format!(
+                "tag \"{}\"… is longer than 64 characters, dropped",
+                "a".repeat(32)
+            )
+        );
+        assert_eq!(
+            normalize_tag(" \n ").unwrap_err().to_string(),
+            "tag \" \\n \" is empty after normalization, dropped"
+        );
+    }
+
+    #[test]
+    fn test_tag_matches() {
+        assert!(tag_matches("lang", "lang"));
+        assert!(tag_matches("lang/rust", "lang"));
+        assert!(tag_matches("lang/rust/async", "lang/rust"));
+        assert!(!tag_matches("language", "lang"));
+        assert!(!tag_matches("lang", "lang/rust"));
+        assert!(tag_matches("语言/锈", "语言"));
+        assert!(!tag_matches("语言学", "语言"));
+    }
+
+    #[test]
+    fn test_tag_tree_deep_nesting() {
+        let tree = tag_tree(&articles(&[
+            &["lang/rust/async/tokio"],
+            &["lang/rust/async"],
+            &["lang/go"],
+        ]));
+
+        assert_eq!(tree.len(), 1);
+        let lang = &tree[0];
+        assert_eq!(
+            (lang.name.as_str(), lang.count, lang.direct),
+            ("lang", 3, 0)
+        );
+        assert_eq!(
+            lang.children
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["go", "rust"]
+        );
+
+        let async_ = &lang.children[1].children[0];
+        assert_eq!(async_.path, "lang/rust/async");
+        assert_eq!((async_.count, async_.direct), (2, 1));
+        let tokio = &async_.children[0];
+        assert_eq!(tokio.path, "lang/rust/async/tokio");
+        assert_eq!((tokio.count, tokio.direct), (1, 1));
+        assert!(tokio.children.is_empty());
+    }
+
+    #[test]
+    fn test_tag_tree_leaf_and_parent() {
+        // `lang` 既被直接使用，也是 `lang/rust` 的上层；同一篇文章只计一次
+        let tree = tag_tree(&articles(&[
+            &["lang", "lang/rust"],
+            &["lang"],
+            &["lang/rust", "lang/go"],
+            &["misc"],
+        ]));
+
+        assert_eq!(tree.len(), 2);
+        let lang = &tree[0];
+        assert_eq!((lang.count, lang.direct), (3, 2));
+        let rust = &lang.children[1];
+        assert_eq!(
+            (rust.path.as_str(), rust.count, rust.direct),
+            ("lang/rust", 2, 2)
+        );
+        assert_eq!((tree[1].path.as_str(), tree[1].count), ("misc", 1));
+    }
+
+    #[test]
+    fn test_tag_tree_multibyte() {
+        let tree = tag_tree(&articles(&[&["语言/锈"], &["语言//日本語/"], &["语言"]]));
+
+        let lang = &tree[0];
+        assert_eq!(
+            (lang.name.as_str(), lang.count, lang.direct),
+            ("语言", 3, 1)
+        );
+        let paths = lang
+            .children
+            .iter()
+            .map(|c| (c.name.as_str(), c.path.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [("日本語", "语言/日本語"), ("锈", "语言/锈")]);
+    }
+}
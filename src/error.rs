@@ -1,9 +1,12 @@
 use std::io;
 
 use axum::response::{IntoResponse, Response};
-use reqwest::StatusCode;
+use reqwest::{
+    StatusCode,
+    header::{RETRY_AFTER, WWW_AUTHENTICATE},
+};
 
-use crate::git_client;
+use crate::{content, git_client, git_sync, storage};
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -25,6 +28,10 @@ pub enum Error {
     #[error(transparent)]
     Serde(#[from] serde_yaml::Error),
 
+    /// TOML 解析错误
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+
     /// 自定义错误消息
     #[error("{0}")]
     Custom(&'static str),
@@ -44,6 +51,96 @@ pub enum Error {
     /// 文件 IO 错误
     #[error(transparent)]
     Io(#[from] io::Error),
+
+    /// 内容加解密错误
+    #[error(transparent)]
+    Cipher(#[from] storage::CipherError),
+
+    /// include 展开错误
+    #[error(transparent)]
+    Include(#[from] content::IncludeError),
+
+    /// 组配置或 Front Matter 字段错误
+    #[error(transparent)]
+    Metadata(#[from] content::MetadataError),
+
+    /// 重建前写入备份失败
+    #[error(transparent)]
+    Backup(#[from] git_sync::BackupError),
+
+    /// 拒绝删除归档
+    #[error(transparent)]
+    ArchiveRefused(#[from] git_sync::ArchiveRefused),
+
+    /// 同步请求因重建或排队已满被拒绝
+    #[error(transparent)]
+    Busy(#[from] git_sync::SyncBusy),
+
+    /// 数据库未迁移，拒绝同步
+    #[error("database not migrated")]
+    NotMigrated,
+
+    /// 查询参数格式错误，包含参数名
+    #[error("invalid query parameter `{0}`")]
+    InvalidParam(&'static str),
+
+    /// `fields` 参数中的未知字段，包含字段名和可以选择的字段
+    #[error("unknown field `{0}`, valid fields: {valid}", valid = .1.join(", "))]
+    UnknownField(String, &'static [&'static str]),
+
+    /// 导出文件格式错误
+    #[error(transparent)]
+    Dump(#[from] storage::DumpError),
+
+    /// 缺少或错误的 bearer token
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// 请求来源地址不在允许列表中
+    #[error("forbidden")]
+    Forbidden,
+
+    /// 文件内容无法处理，包含与同步报告中 `failed` 相同的文件路径和原因
+    #[error("{}: {}", .0.path, .0.reason)]
+    Unprocessable(git_sync::ReportItem),
+
+    /// 查询结果超过单次返回的行数上限，包含上限
+    #[error("result has more than {0} rows, narrow it with the `group` parameter")]
+    TooManyRows(i64),
+
+    /// 查询超过超时限制被取消
+    #[error("query too slow, narrow your filters")]
+    QueryTimeout,
+
+    /// 严格模式下同步后有组超出配额，包含第一个超出配额的组
+    #[error("{0}")]
+    QuotaExceeded(git_sync::QuotaItem),
+}
+
+impl Error {
+    /// 面向作者的修改建议，只有 Front Matter 解析错误带有建议，见 [`content::MetadataError::suggestions`]
+    pub fn suggestions(&self) -> &[String] {
+        match self {
+            Error::Metadata(e) => e.suggestions(),
+            _ => &[],
+        }
+    }
+
+    /// 是否为重试后可能成功的临时数据库错误
+    ///
+    /// 包括连接中断、等待连接池超时、序列化失败、死锁和数据库关闭或故障切换时的连接错误（SQLSTATE `08` 类和 `57P01`~`57P03`）。
+    /// 约束冲突、解析错误等其他错误重试后结果相同，返回 `false`。
+    pub fn is_transient(&self) -> bool {
+        const CODES: [&str; 5] = ["40001", "40P01", "57P01", "57P02", "57P03"];
+
+        match self {
+            Error::Sqlx(sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut) => true,
+            Error::Sqlx(sqlx::Error::Database(e)) => e
+                .code()
+                .is_some_and(|code| code.starts_with("08") || CODES.contains(&code.as_ref())),
+            _ => false,
+        }
+    }
 }
 
 impl IntoResponse for Error {
@@ -55,13 +152,32 @@ impl IntoResponse for Error {
     /// - [`Error::NotFound`] -> 404 Not Found
     /// - [`Error::Custom`] -> 400 Bad Request
     /// - [`Error::Serde`] -> 400 Bad Request
+    /// - [`Error::Toml`] -> 400 Bad Request
     /// - [`Error::Io`] -> 500 Internal Server Error
+    /// - [`Error::Cipher`] -> 500 Internal Server Error
+    /// - [`Error::Include`] -> 400 Bad Request
+    /// - [`Error::Metadata`] -> 400 Bad Request
+    /// - [`Error::Backup`] -> 500 Internal Server Error
+    /// - [`Error::ArchiveRefused`] -> 400 Bad Request（归档名错误）或 409 Conflict（最近的归档）
+    /// - [`Error::Busy`] -> 409 Conflict（重建中）或 503 Service Unavailable（排队已满），附带 `Retry-After`
+    /// - [`Error::NotMigrated`] -> 503 Service Unavailable
+    /// - [`Error::InvalidParam`] -> 400 Bad Request
+    /// - [`Error::UnknownField`] -> 400 Bad Request
+    /// - [`Error::Dump`] -> 400 Bad Request
+    /// - [`Error::Unauthorized`] -> 401 Unauthorized，附带 `WWW-Authenticate: Bearer`
+    /// - [`Error::Forbidden`] -> 403 Forbidden
+    /// - [`Error::Unprocessable`] -> 422 Unprocessable Entity，JSON 格式的 [`ReportItem`](git_sync::ReportItem)
+    /// - [`Error::TooManyRows`] -> 400 Bad Request
+    /// - [`Error::QueryTimeout`] -> 503 Service Unavailable
+    /// - [`Error::QuotaExceeded`] -> 422 Unprocessable Entity，JSON 格式的 [`QuotaItem`](git_sync::QuotaItem)
     fn into_response(self) -> Response {
         match self {
             Error::Git(e) => {
                 tracing::error!(%e, "git repo error");
                 match e {
-                    git_client::GitError::NotFound | git_client::GitError::NotExist => {
+                    git_client::GitError::NotFound
+                    | git_client::GitError::NotExist
+                    | git_client::GitError::RefNotFound(_) => {
                         (StatusCode::NOT_FOUND, e.to_string())
                     }
                     git_client::GitError::Git2(e) => {
@@ -70,6 +186,9 @@ impl IntoResponse for Error {
                     git_client::GitError::IO(e) => {
                         (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
                     }
+                    git_client::GitError::Auth(e) | git_client::GitError::Network(e) => {
+                        (StatusCode::BAD_GATEWAY, e.message().to_string())
+                    }
                 }
                 .into_response()
             }
@@ -88,11 +207,78 @@ impl IntoResponse for Error {
 
             Error::Serde(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
 
+            Error::Toml(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+
+            Error::Include(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+
+            Error::Metadata(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+
+            Error::Backup(e) => {
+                tracing::error!(%e, "backup before rebuild failed");
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            }
+
+            Error::ArchiveRefused(e) => {
+                let status = match e {
+                    git_sync::ArchiveRefused::InvalidName(_) => StatusCode::BAD_REQUEST,
+                    git_sync::ArchiveRefused::Latest(_) => StatusCode::CONFLICT,
+                };
+                (status, e.to_string()).into_response()
+            }
+
+            Error::Busy(e) => {
+                let status = match e {
+                    git_sync::SyncBusy::Rebuilding { .. } => StatusCode::CONFLICT,
+                    git_sync::SyncBusy::QueueFull => StatusCode::SERVICE_UNAVAILABLE,
+                };
+                let retry_after = git_sync::RETRY_AFTER_SECS.to_string();
+                (status, [(RETRY_AFTER, retry_after)], e.to_string()).into_response()
+            }
+
+            Error::NotMigrated => {
+                (StatusCode::SERVICE_UNAVAILABLE, self.to_string()).into_response()
+            }
+
+            Error::InvalidParam(_) | Error::UnknownField(..) => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+
+            Error::Dump(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+
+            Error::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                [(WWW_AUTHENTICATE, "Bearer")],
+                self.to_string(),
+            )
+                .into_response(),
+
+            Error::Forbidden => (StatusCode::FORBIDDEN, self.to_string()).into_response(),
+
+            Error::Unprocessable(item) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, axum::Json(item)).into_response()
+            }
+
+            Error::QuotaExceeded(item) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, axum::Json(item)).into_response()
+            }
+
+            Error::TooManyRows(_) => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+
+            Error::QueryTimeout => {
+                (StatusCode::SERVICE_UNAVAILABLE, self.to_string()).into_response()
+            }
+
             Error::Io(e) => {
                 tracing::error!(%e, "file io error");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
             }
             .into_response(),
+
+            Error::Cipher(e) => {
+                tracing::error!(%e, "content cipher error");
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+            .into_response(),
         }
     }
 }
@@ -1,16 +1,36 @@
+mod archive;
+mod diffstat;
 mod entry;
 mod error;
+mod mirror;
 mod operations;
+mod paths;
+mod prefetch;
 mod repository;
+mod stats;
 
 use self::{
-    entry::{ConsolidateFileChanges, IntoGitFileEntry},
+    archive::{ARCHIVE_BRANCH_PREFIX, ARCHIVE_TAG_PREFIX, sort_archives},
+    diffstat::diff_stat,
+    entry::{
+        ConsolidateFileChanges, IntoGitFileEntry, blob_size, commit_time, prune_traced,
+        resolve_link,
+    },
     operations::{AsyncGitClient, GitOperation},
+    stats::repo_stats,
 };
 
 pub use self::{
-    entry::{AsSummary, ChangeKind, FileKind, GitFileEntry},
+    archive::{ArchiveRemoval, archive_quarter, is_archive_name, previous_archive},
+    diffstat::{DiffStat, MAX_DIFF_BYTES},
+    entry::{
+        AsSummary, ChangeKind, FileClassifier, FileKind, GitFileEntry, PruneDecision, RawChange,
+        blob_oid,
+    },
     error::GitError,
+    mirror::{MirrorAction, clone_or_fetch},
+    paths::{PathCheck, check_path},
+    prefetch::{BLOB_PREFETCH_CHUNK, BlobPrefetch},
+    repository::GitClient,
+    stats::{ACTIVITY_WEEKS, RepoStats},
 };
-
-pub type GitClient = repository::GitClient<AsyncGitClient>;
@@ -0,0 +1,98 @@
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+/// 归档分支前缀，如 `refs/heads/archived/2024-Q1`
+pub const ARCHIVE_BRANCH_PREFIX: &str = "refs/heads/archived/";
+
+/// 归档标签前缀，如 `refs/tags/archive/2024-Q1`
+pub const ARCHIVE_TAG_PREFIX: &str = "refs/tags/archive/";
+
+/// 解析归档名中的 `YYYY-Qn`，返回年份和季度
+pub fn archive_quarter(name: &str) -> Option<(u16, u8)> {
+    name.as_bytes().windows(7).find_map(|w| {
+        let [year @ .., b'-', b'Q', quarter] = w else {
+            return None;
+        };
+        if !year.iter().all(u8::is_ascii_digit) || !(b'1'..=b'4').contains(quarter) {
+            return None;
+        }
+
+        let year = std::str::from_utf8(year).ok()?.parse().ok()?;
+        Some((year, quarter - b'0'))
+    })
+}
+
+/// 归档名是否恰好为 `YYYY-Qn`，只有这样的归档可以删除，避免误删其他分支或标签
+pub fn is_archive_name(name: &str) -> bool {
+    name.len() == 7 && archive_quarter(name).is_some()
+}
+
+/// 删除一个归档的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct ArchiveRemoval {
+    /// 归档名，如 `2024-Q1`
+    pub name: String,
+    /// 是否删除了 `archived/<name>` 分支
+    pub branch: bool,
+    /// 是否删除了 `archive/<name>` 标签
+    pub tag: bool,
+    /// 删除后从任何引用都无法到达的提交数，这些提交在 `git gc` 之前仍保留在仓库中
+    pub unreachable_commits: usize,
+}
+
+/// 按 `YYYY-Qn` 从早到晚排序并去重，不含季度的归档排在最前
+pub fn sort_archives(names: &mut Vec<String>) {
+    names.sort_by(compare);
+    names.dedup();
+}
+
+/// `names` 中季度早于 `name` 的最近一个归档，`name` 不含季度时返回 [`None`]
+pub fn previous_archive<'a>(names: &'a [String], name: &str) -> Option<&'a str> {
+    let quarter = archive_quarter(name)?;
+    names
+        .iter()
+        .filter(|n| archive_quarter(n).is_some_and(|q| q < quarter))
+        .max_by(|a, b| compare(a, b))
+        .map(String::as_str)
+}
+
+fn compare(a: &String, b: &String) -> Ordering {
+    archive_quarter(a)
+        .cmp(&archive_quarter(b))
+        .then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_quarter() {
+        assert_eq!(archive_quarter("2024-Q1"), Some((2024, 1)));
+        assert_eq!(archive_quarter("notes-2023-Q4-final"), Some((2023, 4)));
+        assert_eq!(archive_quarter("2024-Q5"), None);
+        assert_eq!(archive_quarter("24-Q1"), None);
+        assert_eq!(archive_quarter("latest"), None);
+
+        assert!(is_archive_name("2024-Q1"));
+        assert!(!is_archive_name("notes-2023-Q4-final"));
+        assert!(!is_archive_name("2024-Q1 "));
+        assert!(!is_archive_name("main"));
+    }
+
+    #[test]
+    fn test_sort_and_previous() {
+        let mut names = ["2024-Q1", "2023-Q4", "misc", "2024-Q3", "2023-Q4"]
+            .map(String::from)
+            .to_vec();
+        sort_archives(&mut names);
+        assert_eq!(names, ["misc", "2023-Q4", "2024-Q1", "2024-Q3"]);
+
+        assert_eq!(previous_archive(&names, "2024-Q3"), Some("2024-Q1"));
+        // 中间缺失的季度不影响查找
+        assert_eq!(previous_archive(&names, "2024-Q2"), Some("2024-Q1"));
+        assert_eq!(previous_archive(&names, "2023-Q4"), None);
+        assert_eq!(previous_archive(&names, "misc"), None);
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use git2::Patch;
+use serde::Serialize;
+
+/// 计算变更规模的内容大小上限，新旧内容任一超过时不计算
+pub const MAX_DIFF_BYTES: usize = 256 * 1024;
+
+/// 修改的文件的变更规模
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct DiffStat {
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub words_added: usize,
+    pub words_removed: usize,
+}
+
+/// 按行比较新旧内容，统计增删的行数和词数。
+///
+/// 行数与 `git diff --numstat` 相同；词数在每个差异块内比较删除的行和新增的行，
+/// 只修改一行中的一个词时记为增删各一个词，见 [`word_delta`]。
+pub fn diff_stat(old: &[u8], new: &[u8]) -> Result<DiffStat, git2::Error> {
+    let patch = Patch::from_buffers(old, None, new, None, None)?;
+    let mut stat = DiffStat::default();
+
+    for hunk in 0..patch.num_hunks() {
+        let (mut removed, mut added) = (String::new(), String::new());
+        for line in 0..patch.num_lines_in_hunk(hunk)? {
+            let line = patch.line_in_hunk(hunk, line)?;
+            let text = String::from_utf8_lossy(line.content());
+            match line.origin() {
+                '+' => {
+                    stat.lines_added += 1;
+                    added.push_str(&text);
+                }
+                '-' => {
+                    stat.lines_removed += 1;
+                    removed.push_str(&text);
+                }
+                _ => (),
+            }
+        }
+        let (words_added, words_removed) = word_delta(&removed, &added);
+        stat.words_added += words_added;
+        stat.words_removed += words_removed;
+    }
+
+    Ok(stat)
+}
+
+/// 比较两段文本中的词，返回新增和删除的词数。
+///
+/// 不考虑词的顺序，`new` 中比 `old` 多出的词记为新增，少了的记为删除。分词见 [`words`]。
+pub fn word_delta(old: &str, new: &str) -> (usize, usize) {
+    let mut counts = HashMap::<&str, isize>::new();
+    for word in words(old) {
+        *counts.entry(word).or_default() -= 1;
+    }
+    for word in words(new) {
+        *counts.entry(word).or_default() += 1;
+    }
+
+    counts.values().fold((0, 0), |(added, removed), &n| {
+        (added + n.max(0) as usize, removed + (-n).max(0) as usize)
+    })
+}
+
+/// 将文本拆分为词
+///
+/// 连续的字母和数字组成一个词；中日文没有空格分隔，无法按词切分，每个字单独计为一个词。
+/// 标点和空白只作为分隔符。
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let start = rest.find(|c: char| c.is_alphanumeric())?;
+        rest = &rest[start..];
+        let first = rest.chars().next()?;
+        let end = if is_cjk(first) {
+            first.len_utf8()
+        } else {
+            rest.find(|c: char| !c.is_alphanumeric() || is_cjk(c))
+                .unwrap_or(rest.len())
+        };
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// 是否为按字计数的中日文字符
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}' // CJK 扩展 A
+        | '\u{4E00}'..='\u{9FFF}' // CJK 统一表意文字
+        | '\u{F900}'..='\u{FAFF}' // CJK 兼容表意文字
+        | '\u{20000}'..='\u{2FA1F}' // CJK 扩展 B 及以后
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let split = |text| words(text).collect::<Vec<_>>();
+        assert_eq!(split("Hello, world! v2.0"), ["Hello", "world", "v2", "0"]);
+        assert_eq!(split("用 Rust 写博客。"), ["用", "Rust", "写", "博", "客"]);
+        assert_eq!(split("ひらがなABC"), ["ひ", "ら", "が", "な", "ABC"]);
+        assert!(split(" ，。!? ").is_empty());
+    }
+
+    #[test]
+    fn test_word_delta() {
+        assert_eq!(word_delta("", ""), (0, 0));
+        assert_eq!(word_delta("the quick fox", "the slow fox"), (1, 1));
+        assert_eq!(word_delta("a b c", "a b c d e"), (2, 0));
+        // 只调整顺序不计入
+        assert_eq!(word_delta("one two", "two one"), (0, 0));
+        // 重复的词按次数比较
+        assert_eq!(word_delta("go go", "go"), (0, 1));
+    }
+
+    #[test]
+    fn test_word_delta_cjk() {
+        // 中文按字计数
+        assert_eq!(word_delta("今天天气很好", "今天天气不错"), (2, 2));
+        assert_eq!(word_delta("学习", "学习 Rust 语言"), (3, 0));
+        // 中英混排，标点不计入
+        assert_eq!(word_delta("使用 axum，很方便。", "使用 tokio"), (1, 4));
+    }
+
+    #[test]
+    fn test_diff_stat() {
+        let old = "# Title\n\nthe quick fox\nkeep this line\n";
+        let new = "# Title\n\nthe slow fox\nkeep this line\n新增一行\n";
+        let stat = diff_stat(old.as_bytes(), new.as_bytes()).unwrap();
+        assert_eq!(
+            stat,
+            DiffStat {
+                lines_added: 2,
+                lines_removed: 1,
+                words_added: 5,
+                words_removed: 1,
+            }
+        );
+
+        assert_eq!(
+            diff_stat(new.as_bytes(), new.as_bytes()).unwrap(),
+            DiffStat::default()
+        );
+    }
+}
@@ -1,49 +1,178 @@
 use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
     fmt,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
-use chrono::{DateTime, Local, TimeZone};
-use git2::{Commit, Diff};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use git2::{Commit, Diff, DiffFile, FileMode, Oid, Repository, Tree};
+
+use super::{PathCheck, check_path};
 
 /// 枚举表示文件的类型。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileKind {
-    /// `.group.toml` 文件
+    /// `.group.yaml`、`.group.yml` 或 `.group.toml` 文件
     Group,
-    /// Markdown 文件 (`.md` 或 `.markdown`)
+    /// 文章文件，扩展名由 [`FileClassifier`] 配置
     Markdown,
+    /// 组目录的说明文件，文件名由 [`FileClassifier`] 配置，默认为 `README.md`
+    Readme,
     /// 其他文件类型
     Other,
 }
 
-impl FileKind {
+/// 文件类型分类器
+///
+/// 根据路径推断 [`FileKind`]，文章扩展名可配置，默认为 `md` 和 `markdown`；
+/// 说明文件名可配置，默认为 [`FileClassifier::README_FILE_NAMES`]。
+/// 扩展名和说明文件名比较不区分大小写。
+///
+/// 同时决定哪些隐藏目录下的文件参与同步，见 [`FileClassifier::check_path`]，
+/// 以及文章中哪些链接视为附件，见 [`FileClassifier::attachment_extensions`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileClassifier {
+    article_extensions: Vec<String>,
+    readme_names: Vec<String>,
+    allowed_hidden_dirs: Vec<String>,
+    attachment_extensions: Vec<String>,
+}
+
+impl Default for FileClassifier {
+    fn default() -> Self {
+        Self::new(["md", "markdown"])
+    }
+}
+
+impl FileClassifier {
+    /// 组配置文件名
+    pub const GROUP_FILE_NAMES: [&str; 3] = [".group.yaml", ".group.yml", ".group.toml"];
+
+    /// 默认的组说明文件名
+    pub const README_FILE_NAMES: [&str; 1] = ["README.md"];
+
+    /// 默认的附件扩展名
+    pub const ATTACHMENT_EXTENSIONS: [&str; 4] = ["pdf", "zip", "csv", "ipynb"];
+
+    /// 使用给定的文章扩展名创建分类器，扩展名可带前导 `.`
+    pub fn new<I, S>(article_extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            article_extensions: normalize_extensions(article_extensions),
+            readme_names: Self::README_FILE_NAMES.map(str::to_string).to_vec(),
+            allowed_hidden_dirs: Vec::new(),
+            attachment_extensions: normalize_extensions(Self::ATTACHMENT_EXTENSIONS),
+        }
+    }
+
+    /// 设置附件扩展名，替换默认的 [`FileClassifier::ATTACHMENT_EXTENSIONS`]，扩展名可带前导 `.`
+    pub fn with_attachment_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.attachment_extensions = normalize_extensions(extensions);
+        self
+    }
+
+    /// 附件扩展名，小写且不带前导 `.`，文章中指向这些扩展名文件的链接记录为附件
+    pub fn attachment_extensions(&self) -> &[String] {
+        &self.attachment_extensions
+    }
+
+    /// 设置允许同步的隐藏目录名，如 `.well-known`，默认忽略所有隐藏目录
+    pub fn with_allowed_hidden_dirs<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_hidden_dirs = names
+            .into_iter()
+            .map(|n| n.as_ref().trim().trim_matches('/').to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        self
+    }
+
+    /// 检查文件路径，见 [`check_path`]
+    pub fn check_path(&self, path: impl AsRef<Path>) -> PathCheck {
+        check_path(path.as_ref(), &self.allowed_hidden_dirs)
+    }
+
+    /// 设置组说明文件名，替换默认的 [`FileClassifier::README_FILE_NAMES`]
+    pub fn with_readme_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.readme_names = names
+            .into_iter()
+            .map(|n| n.as_ref().trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        self
+    }
+
+    /// 组说明文件名
+    pub fn readme_names(&self) -> &[String] {
+        &self.readme_names
+    }
+
     /// 根据文件路径推断文件类型
     ///
     /// 文件类型判断规则：
-    /// - 文件名为 `.group.toml` 返回 [`FileKind::Group`]
-    /// - 扩展名为 `.md` 或 `.markdown` 返回 [`FileKind::Markdown`]
+    /// - 文件名为 [`FileClassifier::GROUP_FILE_NAMES`] 之一返回 [`FileKind::Group`]
+    /// - 文件名为配置的说明文件名返回 [`FileKind::Readme`]
+    /// - 扩展名为配置的文章扩展名返回 [`FileKind::Markdown`]
     /// - 其他情况返回 [`FileKind::Other`]
     ///
-    pub(super) fn from_path(path: impl AsRef<Path>) -> Self {
+    pub fn classify(&self, path: impl AsRef<Path>) -> FileKind {
         let path = path.as_ref();
         if let Some(name) = path.file_name().and_then(|n| n.to_str())
-            && name == ".group.yaml"
+            && Self::GROUP_FILE_NAMES.contains(&name)
         {
             return FileKind::Group;
         }
-
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            return match ext {
-                "md" | "markdown" => FileKind::Markdown,
-                _ => FileKind::Other,
-            };
+        if let Some(name) = path.file_name().and_then(|n| n.to_str())
+            && self
+                .readme_names
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(name))
+        {
+            return FileKind::Readme;
         }
 
-        FileKind::Other
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext)
+                if self
+                    .article_extensions
+                    .iter()
+                    .any(|a| a.eq_ignore_ascii_case(ext)) =>
+            {
+                FileKind::Markdown
+            }
+            _ => FileKind::Other,
+        }
     }
 }
 
+/// 去掉扩展名两端的空白和前导 `.` 并转换为小写，忽略空项
+fn normalize_extensions<I, S>(extensions: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    extensions
+        .into_iter()
+        .map(|e| e.as_ref().trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
 /// 枚举表示文件变更类型。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChangeKind {
@@ -64,7 +193,11 @@ pub struct GitFileEntry {
     pub(crate) path: PathBuf,
     pub(crate) change_kind: ChangeKind,
     pub(crate) file_kind: FileKind,
-    pub(crate) timestamp: DateTime<Local>,
+    pub(crate) timestamp: DateTime<FixedOffset>,
+    pub(crate) renamed_from: Option<PathBuf>,
+    pub(crate) skip_reason: Option<&'static str>,
+    pub(crate) old_id: Option<String>,
+    pub(crate) size: u64,
 }
 
 impl GitFileEntry {
@@ -88,56 +221,240 @@ impl GitFileEntry {
         self.file_kind
     }
 
-    /// 提交时间。
-    pub fn timestamp(&self) -> DateTime<Local> {
+    /// 提交时间，由 [`GitClient`](super::GitClient) 转换到配置的时区。
+    pub fn timestamp(&self) -> DateTime<FixedOffset> {
         self.timestamp
     }
+
+    /// 重命名前的文件路径，仅在该条目由重命名产生时存在。
+    pub fn renamed_from(&self) -> Option<&Path> {
+        self.renamed_from.as_deref()
+    }
+
+    /// 无法读取内容的原因，如符号链接指向仓库外，此时 [`GitFileEntry::id`] 为链接本身的 blob。
+    pub fn skip_reason(&self) -> Option<&'static str> {
+        self.skip_reason
+    }
+
+    /// 修改前内容的 blob id，仅 [`ChangeKind::Modified`] 的普通文件存在，用于计算变更规模。
+    pub fn old_id(&self) -> Option<&str> {
+        self.old_id.as_deref()
+    }
+
+    /// 变更后内容的字节数，符号链接为链接目标的大小，删除的条目和无法读取的内容为 0。
+    pub fn size(&self) -> u64 {
+        self.size
+    }
 }
 
 /// Trait，用于将 Git `Diff` 和 `Commit` 转换为 [`GitFileEntry`]。
 pub(super) trait IntoGitFileEntry {
-    /// 将类型转换为 [`GitFileEntry`] 列表。
-    fn into_entry(self) -> Vec<GitFileEntry>;
+    /// 将类型转换为 [`GitFileEntry`] 列表，文件类型由 `classifier` 推断。
+    ///
+    /// 按文件模式处理：子模块（gitlink）不生成条目；符号链接在同一 commit 的 tree 中解析，
+    /// 条目使用链接目标的 blob，无法解析时记录 [`GitFileEntry::skip_reason`]。
+    /// 路径先经过 [`FileClassifier::check_path`]：隐藏目录下的文件不生成条目，不安全的路径记录原因。
+    fn into_entry(self, repo: &Repository, classifier: &FileClassifier) -> Vec<GitFileEntry>;
 }
 
 impl<'a> IntoGitFileEntry for (Diff<'a>, Commit<'a>) {
-    fn into_entry(self) -> Vec<GitFileEntry> {
+    fn into_entry(self, repo: &Repository, classifier: &FileClassifier) -> Vec<GitFileEntry> {
         let (diff, commit) = self;
-        let timestamp = Local.timestamp_opt(commit.time().seconds(), 0).unwrap();
+        let timestamp = commit_time(&commit);
+        let tree = commit.tree().ok();
+
+        let resolve = |file: &DiffFile<'_>, change_kind, renamed_from| {
+            let mut entry = to_entry(file, change_kind, timestamp, renamed_from, classifier)?;
+            match file.mode() {
+                FileMode::Commit => {
+                    tracing::debug!(path = %entry.path.display(), "submodule skipped");
+                    return None;
+                }
+                // 删除的链接只需要路径，路径不安全时不解析
+                FileMode::Link
+                    if change_kind != ChangeKind::Deleted && entry.skip_reason.is_none() =>
+                {
+                    let resolved = tree
+                        .as_ref()
+                        .ok_or("commit tree unreadable")
+                        .and_then(|tree| resolve_link(repo, tree, &entry.path, file.id()));
+                    match resolved {
+                        Ok(id) => entry.id = id.to_string(),
+                        Err(reason) => {
+                            tracing::warn!(path = %entry.path.display(), reason, "symlink unresolved");
+                            entry.skip_reason = Some(reason);
+                        }
+                    }
+                }
+                _ => (),
+            }
+            if change_kind != ChangeKind::Deleted
+                && let Ok(id) = Oid::from_str(&entry.id)
+            {
+                entry.size = blob_size(repo, id);
+            }
+            Some(entry)
+        };
 
         diff.deltas()
-            .filter_map(|d| {
-                let (file, change_kind) = match d.status() {
-                    git2::Delta::Added | git2::Delta::Copied => (d.new_file(), ChangeKind::Added),
-                    git2::Delta::Deleted => (d.old_file(), ChangeKind::Deleted),
-                    git2::Delta::Modified => (d.new_file(), ChangeKind::Modified),
-                    git2::Delta::Renamed => (d.new_file(), ChangeKind::Modified), // 重命名也可以算作修改
-                    _ => return None,
-                };
-
-                let path = file.path()?;
-                Some(GitFileEntry {
-                    id: file.id().to_string(),
-                    path: path.to_path_buf(),
-                    change_kind,
-                    file_kind: FileKind::from_path(path),
-                    timestamp,
-                })
+            .flat_map(|d| match d.status() {
+                git2::Delta::Added | git2::Delta::Copied => {
+                    vec![resolve(&d.new_file(), ChangeKind::Added, None)]
+                }
+                git2::Delta::Deleted => {
+                    vec![resolve(&d.old_file(), ChangeKind::Deleted, None)]
+                }
+                git2::Delta::Modified => {
+                    let entry = resolve(&d.new_file(), ChangeKind::Modified, None);
+                    // 符号链接两侧的 blob 不是链接目标的内容，不比较
+                    let regular = |mode| matches!(mode, FileMode::Blob | FileMode::BlobExecutable);
+                    vec![entry.map(|mut entry| {
+                        if regular(d.old_file().mode()) && regular(d.new_file().mode()) {
+                            entry.old_id = Some(d.old_file().id().to_string());
+                        }
+                        entry
+                    })]
+                }
+                // 重命名拆分为旧路径删除和新路径新增，新条目记录来源路径
+                git2::Delta::Renamed => vec![
+                    resolve(&d.old_file(), ChangeKind::Deleted, None),
+                    resolve(
+                        &d.new_file(),
+                        ChangeKind::Added,
+                        d.old_file().path().map(Path::to_path_buf),
+                    ),
+                ],
+                _ => vec![],
             })
+            .flatten()
             .collect()
     }
 }
 
+/// 符号链接最多跟随的层数
+const MAX_LINK_DEPTH: usize = 8;
+
+/// 在 `tree` 中解析 `path` 处的符号链接，返回最终指向的普通文件的 blob id。
+///
+/// `link` 为链接本身的 blob，内容为链接目标。目标为绝对路径、超出仓库根目录、不存在、
+/// 不是普通文件或链接层数过多时返回原因。
+pub(super) fn resolve_link(
+    repo: &Repository,
+    tree: &Tree<'_>,
+    path: &Path,
+    link: Oid,
+) -> Result<Oid, &'static str> {
+    let (mut path, mut link) = (path.to_path_buf(), link);
+    for _ in 0..MAX_LINK_DEPTH {
+        let blob = repo.find_blob(link).map_err(|_| "symlink unreadable")?;
+        let target =
+            std::str::from_utf8(blob.content()).map_err(|_| "symlink target is not UTF-8")?;
+        path =
+            link_target(&path, Path::new(target)).ok_or("symlink points outside the repository")?;
+
+        let entry = tree
+            .get_path(&path)
+            .map_err(|_| "symlink target not found")?;
+        match entry.filemode() {
+            mode if mode == i32::from(FileMode::Link) => link = entry.id(),
+            mode if mode == i32::from(FileMode::Blob)
+                || mode == i32::from(FileMode::BlobExecutable) =>
+            {
+                return Ok(entry.id());
+            }
+            _ => return Err("symlink target is not a regular file"),
+        }
+    }
+    Err("too many levels of symlinks")
+}
+
+/// 以链接所在目录为基准解析相对的链接目标，目标为绝对路径或超出仓库根目录时返回 [`None`]
+fn link_target(link: &Path, target: &Path) -> Option<PathBuf> {
+    let mut resolved = link.parent().map(Path::to_path_buf).unwrap_or_default();
+    for component in target.components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::CurDir => (),
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved)
+}
+
+/// blob 的字节数，只读取对象头，读取失败时为 0
+pub(super) fn blob_size(repo: &Repository, id: Oid) -> u64 {
+    repo.odb()
+        .and_then(|odb| odb.read_header(id))
+        .map_or(0, |(size, _)| size as u64)
+}
+
+/// 提交时间，保留提交记录的时区偏移
+pub(super) fn commit_time(commit: &Commit<'_>) -> DateTime<FixedOffset> {
+    let time = commit.time();
+    FixedOffset::east_opt(time.offset_minutes() * 60)
+        .unwrap_or(FixedOffset::east_opt(0).unwrap())
+        .timestamp_opt(time.seconds(), 0)
+        .unwrap()
+}
+
+/// 按 git blob 的方式计算内容的 oid，与仓库中相同内容文件的 [`GitFileEntry::id`] 一致
+pub fn blob_oid(content: &[u8]) -> String {
+    git2::Oid::hash_object(git2::ObjectType::Blob, content)
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+/// 由 diff 中的单个文件构建 [`GitFileEntry`]，文件无路径时返回 [`None`]。
+fn to_entry(
+    file: &DiffFile<'_>,
+    change_kind: ChangeKind,
+    timestamp: DateTime<FixedOffset>,
+    renamed_from: Option<PathBuf>,
+    classifier: &FileClassifier,
+) -> Option<GitFileEntry> {
+    let path = file.path()?;
+    let skip_reason = match classifier.check_path(path) {
+        PathCheck::Valid => None,
+        PathCheck::Hidden => {
+            tracing::debug!(path = %path.display(), "hidden directory skipped");
+            return None;
+        }
+        PathCheck::Invalid(reason) => {
+            tracing::warn!(path = %path.display(), reason, "unsafe path rejected");
+            Some(reason)
+        }
+    };
+    Some(GitFileEntry {
+        id: file.id().to_string(),
+        path: path.to_path_buf(),
+        change_kind,
+        file_kind: classifier.classify(path),
+        timestamp,
+        // 来源路径不安全时按新增处理
+        renamed_from: renamed_from.filter(|p| classifier.check_path(p) == PathCheck::Valid),
+        skip_reason,
+        old_id: None,
+        size: 0,
+    })
+}
+
 /// 合并旧的变更状态和新的变更状态，返回合并后的结果。
 ///
 /// 合并规则：
 /// — [`ChangeKind::Added`] -> [`ChangeKind::Deleted`] = 消失 (返回 `None`)
-/// — [`ChangeKind::Deleted`] -> [`ChangeKind::Added`] = [`ChangeKind::Added`]
+/// — [`ChangeKind::Deleted`] -> [`ChangeKind::Added`] = [`ChangeKind::Modified`]，文件在范围前后都存在，
+///   按修改原位更新，不产生删除（类型变化的 delta 同样拆分为这样的一对）
 /// — 新增后修改 (`Added` -> `Modified`) = [`ChangeKind::Modified`]
 /// — 其他情况保持最新状态或按逻辑覆盖
 fn merge_change(old: Option<&ChangeKind>, new: ChangeKind) -> Option<ChangeKind> {
     match (old, new) {
         (None, now) => Some(now),
+        (Some(ChangeKind::Deleted), ChangeKind::Added) => Some(ChangeKind::Modified),
         (Some(ChangeKind::Deleted), new) => Some(new),
         (Some(ChangeKind::Added), ChangeKind::Deleted) => None,
         (Some(ChangeKind::Added), _) => Some(ChangeKind::Modified),
@@ -146,6 +463,29 @@ fn merge_change(old: Option<&ChangeKind>, new: ChangeKind) -> Option<ChangeKind>
     }
 }
 
+/// 裁剪前某个路径在单个 commit 中的变更，重命名已拆分为旧路径删除和新路径新增。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawChange {
+    /// 产生变更的 commit id
+    pub commit: String,
+    pub change_kind: ChangeKind,
+    pub file_kind: FileKind,
+    /// 变更后的 blob id，删除时为删除前的 blob
+    pub id: String,
+    /// 重命名前的路径，见 [`GitFileEntry::renamed_from`]
+    pub renamed_from: Option<PathBuf>,
+}
+
+/// 裁剪时对单个路径做出的决定：按提交顺序的原始变更和合并后的结果。
+///
+/// `merged` 为 [`None`] 表示变更相互抵消，该路径不出现在 [`ConsolidateFileChanges::prune`] 的结果中。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneDecision {
+    pub path: PathBuf,
+    pub changes: Vec<RawChange>,
+    pub merged: Option<ChangeKind>,
+}
+
 /// 定义对一组 [`GitFileEntry`] 进行裁剪的行为。
 ///
 /// 用于在序列中合并或抵消重复的文件变更，得到精简后的最终结果。
@@ -158,36 +498,85 @@ impl ConsolidateFileChanges for Vec<GitFileEntry> {
     ///
     /// 返回的结果只包含必要的文件变更，便于后续处理。
     fn prune(self) -> Vec<GitFileEntry> {
-        use std::collections::HashMap;
-
-        let mut state: HashMap<std::path::PathBuf, usize> = HashMap::new();
-        let mut result: Vec<Option<GitFileEntry>> = (0..self.len()).map(|_| None).collect();
-
-        for (idx, mut entry) in self.into_iter().enumerate() {
-            let path = entry.path.clone();
-            match merge_change(
-                state
-                    .get(&path)
-                    .and_then(|&i| result[i].as_ref().map(|e: &GitFileEntry| &e.change_kind)),
-                entry.change_kind,
-            ) {
-                Some(real_change) => {
-                    entry.change_kind = real_change;
-                    if let Some(prev_idx) = state.insert(path, idx) {
-                        result[prev_idx] = None;
+        prune_with(self, |_, _, _| ())
+    }
+}
+
+/// 按 commit 分组的变更序列裁剪，同时记录每个路径的 [`PruneDecision`]。
+///
+/// 裁剪结果与 [`ConsolidateFileChanges::prune`] 相同，决定按路径首次出现的顺序排列。
+pub(super) fn prune_traced(
+    commits: Vec<(String, Vec<GitFileEntry>)>,
+) -> (Vec<GitFileEntry>, Vec<PruneDecision>) {
+    let mut decisions: Vec<PruneDecision> = Vec::new();
+    let mut index: HashMap<PathBuf, usize> = HashMap::new();
+    let mut entries = Vec::new();
+    let mut sources = Vec::new();
+    for (commit, changes) in commits {
+        sources.extend(std::iter::repeat_n(commit, changes.len()));
+        entries.extend(changes);
+    }
+
+    let pruned = prune_with(entries, |idx, entry, merged| {
+        let i = *index.entry(entry.path.clone()).or_insert_with(|| {
+            decisions.push(PruneDecision {
+                path: entry.path.clone(),
+                changes: Vec::new(),
+                merged: None,
+            });
+            decisions.len() - 1
+        });
+        decisions[i].changes.push(RawChange {
+            commit: sources[idx].clone(),
+            change_kind: entry.change_kind,
+            file_kind: entry.file_kind,
+            id: entry.id.clone(),
+            renamed_from: entry.renamed_from.clone(),
+        });
+        decisions[i].merged = merged;
+    });
+
+    (pruned, decisions)
+}
+
+/// 裁剪的实现，每处理一个原始条目调用一次 `observe`，参数为条目序号、裁剪前的条目和该路径合并后的状态。
+fn prune_with(
+    entries: Vec<GitFileEntry>,
+    mut observe: impl FnMut(usize, &GitFileEntry, Option<ChangeKind>),
+) -> Vec<GitFileEntry> {
+    let mut state: HashMap<PathBuf, usize> = HashMap::new();
+    let mut result: Vec<Option<GitFileEntry>> = (0..entries.len()).map(|_| None).collect();
+
+    for (idx, mut entry) in entries.into_iter().enumerate() {
+        let path = entry.path.clone();
+        let merged = merge_change(
+            state
+                .get(&path)
+                .and_then(|&i| result[i].as_ref().map(|e: &GitFileEntry| &e.change_kind)),
+            entry.change_kind,
+        );
+        observe(idx, &entry, merged);
+        match merged {
+            Some(real_change) => {
+                entry.change_kind = real_change;
+                if let Some(prev_idx) = state.insert(path, idx) {
+                    // 多次修改合并后与第一次修改前的内容比较，范围内新增的文件没有修改前的内容
+                    if real_change == ChangeKind::Modified {
+                        entry.old_id = result[prev_idx].as_mut().and_then(|e| e.old_id.take());
                     }
-                    result[idx] = Some(entry);
+                    result[prev_idx] = None;
                 }
-                None => {
-                    if let Some(prev_idx) = state.remove(&path) {
-                        result[prev_idx] = None;
-                    }
+                result[idx] = Some(entry);
+            }
+            None => {
+                if let Some(prev_idx) = state.remove(&path) {
+                    result[prev_idx] = None;
                 }
             }
         }
-
-        result.into_iter().flatten().collect()
     }
+
+    result.into_iter().flatten().collect()
 }
 
 impl fmt::Display for GitFileEntry {
@@ -202,66 +591,203 @@ impl fmt::Display for GitFileEntry {
     /// 枚举跳转：
     /// - [`FileKind::Group`]
     /// - [`FileKind::Markdown`]
+    /// - [`FileKind::Readme`]
     /// - [`FileKind::Other`]
     /// - [`ChangeKind::Added`]
     /// - [`ChangeKind::Deleted`]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.line(&self.path.display().to_string()))
+    }
+}
+
+impl GitFileEntry {
+    /// 以 `path` 作为显示路径的一行输出，格式见 [`fmt::Display`] 的实现
+    fn line(&self, path: &str) -> String {
         let kind_str = match self.file_kind {
             FileKind::Group => "[group]",
             FileKind::Markdown => "[md]",
+            FileKind::Readme => "[readme]",
             FileKind::Other => "[-]",
         };
 
-        let change_str = match self.change_kind {
-            ChangeKind::Added => "+",
-            ChangeKind::Deleted => "-",
-            ChangeKind::Modified => "~",
-        };
-
-        write!(
-            f,
+        format!(
             "{:<7} {} {} @ {}",
             kind_str,
-            change_str,
-            self.path.display(),
+            change_symbol(self.change_kind),
+            path,
             self.timestamp.format("%Y-%m-%d %H:%M")
         )
     }
 }
 
+fn change_symbol(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "+",
+        ChangeKind::Deleted => "-",
+        ChangeKind::Modified => "~",
+    }
+}
+
 pub trait AsSummary {
     fn as_summary(&self) -> String;
 }
 
+/// 摘要每行的最大字符数，超出时从中间截断路径
+const SUMMARY_WIDTH: usize = 80;
+
+/// 条目行中路径以外的部分的字符数：类型、变更符号和提交时间
+const ENTRY_LINE_OVERHEAD: usize = "[group] + ".len() + " @ 2024-08-22 12:30".len();
+
 impl AsSummary for Vec<GitFileEntry> {
+    /// 按所在目录和文件名排序，涉及多个目录时每个目录前输出一行目录路径和各类变更的数量，
+    /// 目录下的条目只显示文件名并缩进两格；只涉及一个目录时不输出目录行，条目显示完整路径。
+    /// 最后一行为变更的文件数、目录数和变更后内容的总字节数。路径过长时从中间截断为 `…`，
+    /// 每行不超过 [`SUMMARY_WIDTH`] 个字符：
+    ///
+    /// ```text
+    /// group-a (+1 ~1)
+    ///   [group] ~ .group.yaml @ 2024-08-22 12:35
+    ///   [md]    + test.md @ 2024-08-22 12:30
+    /// group-b (-1)
+    ///   [md]    - old.md @ 2024-08-22 12:30
+    /// 3 files changed, 2 groups, 1532 bytes
+    /// ```
     fn as_summary(&self) -> String {
         if self.is_empty() {
             return "No entries".to_string();
         }
 
-        // 按时间从老到新排序
-        // self.sort_by_key(|e| e.timestamp);
-        // 转换为多行字符串
-        self.iter()
-            .map(|e| e.to_string())
-            .collect::<Vec<_>>()
-            .join("\n")
+        let mut groups: BTreeMap<(&Path, &OsStr), &GitFileEntry> = BTreeMap::new();
+        for entry in self {
+            let dir = entry.path.parent().unwrap_or(Path::new(""));
+            let name = entry.path.file_name().unwrap_or_default();
+            groups.insert((dir, name), entry);
+        }
+        let mut dirs: Vec<(&Path, Vec<&GitFileEntry>)> = Vec::new();
+        for ((dir, _), entry) in groups {
+            match dirs.last_mut() {
+                Some((last, entries)) if *last == dir => entries.push(entry),
+                _ => dirs.push((dir, vec![entry])),
+            }
+        }
+
+        let mut lines = Vec::new();
+        if let [(_, entries)] = dirs.as_slice() {
+            let width = SUMMARY_WIDTH - ENTRY_LINE_OVERHEAD;
+            for entry in entries {
+                let path = entry.path.display().to_string();
+                lines.push(entry.line(&truncate_middle(&path, width)));
+            }
+        } else {
+            for (dir, entries) in &dirs {
+                let counts = [ChangeKind::Added, ChangeKind::Modified, ChangeKind::Deleted]
+                    .into_iter()
+                    .filter_map(|kind| {
+                        let n = entries.iter().filter(|e| e.change_kind == kind).count();
+                        (n > 0).then(|| format!("{}{n}", change_symbol(kind)))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let dir = match dir.to_str() {
+                    Some("") => "/".to_string(),
+                    _ => dir.display().to_string(),
+                };
+                let width = SUMMARY_WIDTH - counts.chars().count() - 3;
+                lines.push(format!("{} ({counts})", truncate_middle(&dir, width)));
+
+                let width = SUMMARY_WIDTH - ENTRY_LINE_OVERHEAD - 2;
+                for entry in entries {
+                    let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+                    lines.push(format!("  {}", entry.line(&truncate_middle(&name, width))));
+                }
+            }
+        }
+
+        let files = dirs.iter().map(|(_, entries)| entries.len()).sum::<usize>();
+        let bytes = self.iter().map(|e| e.size).sum::<u64>();
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        lines.push(format!(
+            "{files} file{} changed, {} group{}, {bytes} bytes",
+            plural(files),
+            dirs.len(),
+            plural(dirs.len())
+        ));
+        lines.join("\n")
+    }
+}
+
+/// 字符数超过 `max` 时保留首尾、中间替换为 `…`，结果恰好为 `max` 个字符
+fn truncate_middle(text: &str, max: usize) -> String {
+    let len = text.chars().count();
+    if len <= max {
+        return text.to_string();
     }
+    let keep = max.saturating_sub(1);
+    let (head, tail) = (keep - keep / 2, keep / 2);
+    let mut result = text.chars().take(head).collect::<String>();
+    result.push('…');
+    result.extend(text.chars().skip(len - tail));
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Local;
+    use chrono::FixedOffset;
     use std::path::PathBuf;
 
     #[test]
     fn test_file_kind_from_path() {
-        assert_eq!(FileKind::from_path(".group.yaml"), FileKind::Group);
-        assert_eq!(FileKind::from_path("doc.md"), FileKind::Markdown);
-        assert_eq!(FileKind::from_path("notes.markdown"), FileKind::Markdown);
-        assert_eq!(FileKind::from_path("image.png"), FileKind::Other);
-        assert_eq!(FileKind::from_path("folder/unknown"), FileKind::Other);
+        let classifier = FileClassifier::default();
+
+        assert_eq!(classifier.classify(".group.yaml"), FileKind::Group);
+        assert_eq!(classifier.classify("a/.group.yml"), FileKind::Group);
+        assert_eq!(classifier.classify("a/.group.toml"), FileKind::Group);
+        assert_eq!(classifier.classify("doc.md"), FileKind::Markdown);
+        assert_eq!(classifier.classify("a/README.md"), FileKind::Readme);
+        assert_eq!(classifier.classify("a/readme.md"), FileKind::Readme);
+        assert_eq!(classifier.classify("a/README.txt"), FileKind::Other);
+        assert_eq!(classifier.classify("DOC.MD"), FileKind::Markdown);
+        assert_eq!(classifier.classify("notes.markdown"), FileKind::Markdown);
+        assert_eq!(classifier.classify("notes.mdx"), FileKind::Other);
+        assert_eq!(classifier.classify("image.png"), FileKind::Other);
+        assert_eq!(classifier.classify("folder/unknown"), FileKind::Other);
+    }
+
+    #[test]
+    fn test_file_classifier_extra_extensions() {
+        let classifier = FileClassifier::new(["md", ".mdx", " TXT "]);
+
+        assert_eq!(classifier.classify("a/doc.md"), FileKind::Markdown);
+        assert_eq!(classifier.classify("a/doc.MD"), FileKind::Markdown);
+        assert_eq!(classifier.classify("a/doc.mdx"), FileKind::Markdown);
+        assert_eq!(classifier.classify("a/doc.txt"), FileKind::Markdown);
+        assert_eq!(classifier.classify("a/doc.markdown"), FileKind::Other);
+        assert_eq!(classifier.classify("a/.group.yaml"), FileKind::Group);
+    }
+
+    #[test]
+    fn test_file_classifier_readme_names() {
+        let classifier = FileClassifier::default().with_readme_names(["index.md", " ", "ABOUT.md"]);
+
+        assert_eq!(classifier.classify("a/index.md"), FileKind::Readme);
+        assert_eq!(classifier.classify("a/about.md"), FileKind::Readme);
+        assert_eq!(classifier.classify("a/README.md"), FileKind::Markdown);
+
+        let classifier = FileClassifier::default().with_readme_names(Vec::<String>::new());
+        assert_eq!(classifier.classify("a/README.md"), FileKind::Markdown);
+    }
+
+    #[test]
+    fn test_file_classifier_attachment_extensions() {
+        let classifier = FileClassifier::default();
+        assert_eq!(
+            classifier.attachment_extensions(),
+            ["pdf", "zip", "csv", "ipynb"]
+        );
+
+        let classifier = classifier.with_attachment_extensions([".PDF", " ", "7z"]);
+        assert_eq!(classifier.attachment_extensions(), ["pdf", "7z"]);
     }
 
     #[test]
@@ -279,7 +805,7 @@ mod tests {
         assert_eq!(merge_change(Some(&Added), Deleted), None);
 
         // Deleted -> Added / Deleted / Modified
-        assert_eq!(merge_change(Some(&Deleted), Added), Some(Added));
+        assert_eq!(merge_change(Some(&Deleted), Added), Some(Modified));
         assert_eq!(merge_change(Some(&Deleted), Deleted), Some(Deleted));
         assert_eq!(merge_change(Some(&Deleted), Modified), Some(Modified));
 
@@ -289,6 +815,82 @@ mod tests {
         assert_eq!(merge_change(Some(&Modified), Modified), Some(Modified));
     }
 
+    #[test]
+    fn test_prune_delete_then_add() {
+        let entry = |id: &str, path: &str, change_kind| GitFileEntry {
+            id: id.to_string(),
+            path: PathBuf::from(path),
+            change_kind,
+            file_kind: FileClassifier::default().classify(path),
+            timestamp: FixedOffset::east_opt(0)
+                .unwrap()
+                .with_ymd_and_hms(2024, 8, 22, 12, 30, 0)
+                .unwrap(),
+            renamed_from: None,
+            skip_reason: None,
+            old_id: None,
+            size: 0,
+        };
+
+        // 同一路径先删除后新增合并为一次修改，不保留删除
+        let pruned = vec![
+            entry("1", "a/.group.yaml", ChangeKind::Deleted),
+            entry("2", "a/.group.yaml", ChangeKind::Added),
+            entry("3", "a/post.md", ChangeKind::Deleted),
+            entry("4", "a/post.md", ChangeKind::Added),
+        ]
+        .prune();
+        let kinds = pruned
+            .iter()
+            .map(|e| (e.id.as_str(), e.change_kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [("2", ChangeKind::Modified), ("4", ChangeKind::Modified)]
+        );
+
+        // 范围内新增后删除再新增仍是新增
+        let pruned = vec![
+            entry("1", "a/.group.yaml", ChangeKind::Added),
+            entry("2", "a/.group.yaml", ChangeKind::Deleted),
+            entry("3", "a/.group.yaml", ChangeKind::Added),
+        ]
+        .prune();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].change_kind, ChangeKind::Added);
+    }
+
+    #[test]
+    fn test_link_target() {
+        let resolve = |link: &str, target: &str| link_target(Path::new(link), Path::new(target));
+
+        assert_eq!(
+            resolve("notes/a.md", "b.md"),
+            Some(PathBuf::from("notes/b.md"))
+        );
+        assert_eq!(
+            resolve("notes/a.md", "./sub/b.md"),
+            Some(PathBuf::from("notes/sub/b.md"))
+        );
+        assert_eq!(
+            resolve("notes/a.md", "../shared/b.md"),
+            Some(PathBuf::from("shared/b.md"))
+        );
+        assert_eq!(resolve("a.md", "b.md"), Some(PathBuf::from("b.md")));
+        assert_eq!(resolve("notes/a.md", "../../outside.md"), None);
+        assert_eq!(resolve("notes/a.md", "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_blob_oid() {
+        // 与 `git hash-object` 的结果一致
+        assert_eq!(blob_oid(b""), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(
+            blob_oid(b"hello\n"),
+            "ce013625030ba8dba906f756967f9e9ca394464a"
+        );
+    }
+
     #[test]
     fn test_repo_entry_display() {
         let entry_added = GitFileEntry {
@@ -296,7 +898,14 @@ mod tests {
             path: PathBuf::from("group-a/test.md"),
             change_kind: ChangeKind::Added,
             file_kind: FileKind::Markdown,
-            timestamp: Local.with_ymd_and_hms(2024, 8, 22, 12, 30, 0).unwrap(),
+            timestamp: FixedOffset::east_opt(8 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 8, 22, 12, 30, 0)
+                .unwrap(),
+            renamed_from: None,
+            skip_reason: None,
+            old_id: None,
+            size: 0,
         };
 
         let entry_modified = GitFileEntry {
@@ -304,7 +913,14 @@ mod tests {
             path: PathBuf::from("group-a/updated.md"),
             change_kind: ChangeKind::Modified,
             file_kind: FileKind::Markdown,
-            timestamp: Local.with_ymd_and_hms(2024, 8, 22, 12, 35, 0).unwrap(),
+            timestamp: FixedOffset::east_opt(8 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 8, 22, 12, 35, 0)
+                .unwrap(),
+            renamed_from: None,
+            skip_reason: None,
+            old_id: None,
+            size: 0,
         };
 
         let output_added = format!("{}", entry_added);
@@ -319,4 +935,85 @@ mod tests {
         assert!(output_modified.contains("group-a/updated.md"));
         assert!(output_modified.contains("2024-08-22 12:35"));
     }
+
+    fn summary_entry(path: &str, change_kind: ChangeKind, size: u64) -> GitFileEntry {
+        GitFileEntry {
+            id: "123".to_string(),
+            path: PathBuf::from(path),
+            change_kind,
+            file_kind: FileClassifier::default().classify(path),
+            timestamp: FixedOffset::east_opt(8 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 8, 22, 12, 30, 0)
+                .unwrap(),
+            renamed_from: None,
+            skip_reason: None,
+            old_id: None,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_summary_grouped_by_directory() {
+        let long_name = format!("{}.md", "a".repeat(100));
+        let entries = vec![
+            summary_entry("notes/b.md", ChangeKind::Modified, 100),
+            summary_entry("README.md", ChangeKind::Modified, 10),
+            summary_entry("notes/a.md", ChangeKind::Added, 200),
+            summary_entry("notes/old.md", ChangeKind::Deleted, 0),
+            summary_entry("notes/.group.yaml", ChangeKind::Added, 20),
+            summary_entry(&format!("drafts/{long_name}"), ChangeKind::Added, 5),
+        ];
+
+        let summary = entries.as_summary();
+        let lines = summary.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "/ (~1)");
+        assert_eq!(lines[1], "  [readme] ~ README.md @ 2024-08-22 12:30");
+        assert_eq!(lines[2], "drafts (+1)");
+        assert!(lines[3].contains('…'));
+        assert!(lines[3].ends_with(".md @ 2024-08-22 12:30"));
+        assert_eq!(lines[4], "notes (+2 ~1 -1)");
+        assert_eq!(lines[5], "  [group] + .group.yaml @ 2024-08-22 12:30");
+        assert_eq!(lines[6], "  [md]    + a.md @ 2024-08-22 12:30");
+        assert_eq!(lines[7], "  [md]    ~ b.md @ 2024-08-22 12:30");
+        assert_eq!(lines[8], "  [md]    - old.md @ 2024-08-22 12:30");
+        assert_eq!(lines[9], "6 files changed, 3 groups, 335 bytes");
+        assert_eq!(lines.len(), 10);
+        assert!(lines.iter().all(|l| l.chars().count() <= SUMMARY_WIDTH));
+    }
+
+    #[test]
+    fn test_summary_single_group() {
+        let entries = vec![
+            summary_entry("notes/b.md", ChangeKind::Modified, 1),
+            summary_entry("notes/a.md", ChangeKind::Added, 0),
+        ];
+
+        assert_eq!(
+            entries.as_summary(),
+            "[md]    + notes/a.md @ 2024-08-22 12:30\n\
+             [md]    ~ notes/b.md @ 2024-08-22 12:30\n\
+             2 files changed, 1 group, 1 bytes"
+        );
+        assert_eq!(
+            vec![summary_entry("a.md", ChangeKind::Added, 1)]
+                .as_summary()
+                .lines()
+                .last(),
+            Some("1 file changed, 1 group, 1 bytes")
+        );
+    }
+
+    #[test]
+    fn test_summary_empty() {
+        assert_eq!(Vec::<GitFileEntry>::new().as_summary(), "No entries");
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("short", 10), "short");
+        assert_eq!(truncate_middle("abcdefghij", 5), "ab…ij");
+        assert_eq!(truncate_middle("abcdefghij", 6), "abc…ij");
+    }
 }
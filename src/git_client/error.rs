@@ -4,9 +4,11 @@
 ///
 /// - [`GitError::NotFound`]：请求的 blob 或文件不存在  
 /// - [`GitError::NotExist`]：仓库不存在  
+/// - [`GitError::RefNotFound`]：请求的分支或标签不存在  
 /// - [`GitError::Git2`]：底层 [`git2::Error`] 错误  
 /// - [`GitError::IO`]：底层 IO 错误  
-/// - [`GitError::CommandFailed`]：外部命令执行失败，包含错误信息
+/// - [`GitError::Auth`]：访问远程仓库时认证失败  
+/// - [`GitError::Network`]：访问远程仓库时网络失败
 #[derive(Debug, thiserror::Error)]
 pub enum GitError {
     /// 请求的 blob 或文件不存在
@@ -17,6 +19,10 @@ pub enum GitError {
     #[error("repository not exist")]
     NotExist,
 
+    /// 请求的分支或标签不存在
+    #[error("ref `{0}` not found")]
+    RefNotFound(String),
+
     /// 底层 git2 错误
     #[error("{0}")]
     Git2(#[from] git2::Error),
@@ -24,4 +30,12 @@ pub enum GitError {
     /// 底层 IO 错误
     #[error(transparent)]
     IO(#[from] std::io::Error),
+
+    /// 访问远程仓库时认证失败
+    #[error("authentication failed: {0}")]
+    Auth(git2::Error),
+
+    /// 访问远程仓库时网络失败
+    #[error("network failure: {0}")]
+    Network(git2::Error),
 }
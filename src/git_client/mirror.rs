@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use git2::{
+    AutotagOption, Direction, ErrorClass, ErrorCode, FetchOptions, FetchPrune, Progress,
+    RemoteCallbacks, Repository, build::RepoBuilder,
+};
+
+use super::GitError;
+
+/// 镜像仓库的 fetch refspec，与 `git clone --mirror` 一致
+const MIRROR_REFSPEC: &str = "+refs/*:refs/*";
+
+/// [`clone_or_fetch`] 实际执行的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAction {
+    /// 本地仓库不存在，执行了镜像克隆
+    Cloned,
+    /// 本地仓库已存在，执行了 remote update
+    Updated,
+}
+
+/// 将远程仓库镜像到本地裸仓库。
+///
+/// - 本地路径不是有效的裸仓库时，执行等价于 `git clone --mirror` 的克隆
+/// - 否则对 `origin` 执行等价于 `git remote update --prune` 的更新
+///
+/// 返回执行的操作和更新后的 HEAD，HEAD 未指向任何提交时为 [`None`]。
+/// 认证失败返回 [`GitError::Auth`]，网络失败返回 [`GitError::Network`]。
+pub fn clone_or_fetch(
+    remote_url: &str,
+    path: impl AsRef<Path>,
+) -> Result<(MirrorAction, Option<String>), GitError> {
+    let path = path.as_ref();
+
+    let (action, repo) = match Repository::open_bare(path) {
+        Ok(repo) => {
+            fetch_mirror(&repo)?;
+            (MirrorAction::Updated, repo)
+        }
+        Err(_) => (MirrorAction::Cloned, clone_mirror(remote_url, path)?),
+    };
+
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .map(|oid| oid.to_string());
+
+    Ok((action, head))
+}
+
+/// 镜像克隆远程仓库到 `path`。
+fn clone_mirror(remote_url: &str, path: &Path) -> Result<Repository, GitError> {
+    tracing::info!(remote_url, path = %path.display(), "cloning mirror");
+
+    let repo = RepoBuilder::new()
+        .bare(true)
+        .fetch_options(fetch_options())
+        .remote_create(|repo, name, url| repo.remote_with_fetch(name, url, MIRROR_REFSPEC))
+        .clone(remote_url, path)
+        .map_err(classify)?;
+
+    repo.config()?.set_bool("remote.origin.mirror", true)?;
+
+    // 镜像 refspec 下克隆不会按远程 HEAD 设置本地 HEAD，本地 HEAD 仍指向 init.defaultBranch
+    let mut remote = repo.find_remote("origin")?;
+    remote.connect(Direction::Fetch).map_err(classify)?;
+    if let Ok(head) = remote.default_branch()
+        && let Some(head) = head.as_str()
+    {
+        repo.set_head(head)?;
+    }
+    drop(remote);
+
+    Ok(repo)
+}
+
+/// 从 `origin` 拉取全部 refs，并清理远程已删除的 refs。
+fn fetch_mirror(repo: &Repository) -> Result<(), GitError> {
+    let mut remote = repo.find_remote("origin")?;
+    tracing::info!(
+        remote_url = remote.url().unwrap_or_default(),
+        "updating mirror"
+    );
+
+    let mut opts = fetch_options();
+    opts.prune(FetchPrune::On);
+    remote
+        .fetch(&[MIRROR_REFSPEC], Some(&mut opts), None)
+        .map_err(classify)?;
+
+    Ok(())
+}
+
+/// 创建 fetch 选项，每传输 10% 的对象记录一次进度。
+fn fetch_options() -> FetchOptions<'static> {
+    let mut last_step = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.transfer_progress(move |progress: Progress<'_>| {
+        let total = progress.total_objects();
+        if let Some(step) = (progress.received_objects() * 10).checked_div(total)
+            && step > last_step
+        {
+            last_step = step;
+            tracing::info!(
+                received = progress.received_objects(),
+                total,
+                "mirror transfer {}%",
+                step * 10
+            );
+        }
+        true
+    });
+
+    let mut opts = FetchOptions::new();
+    opts.remote_callbacks(callbacks)
+        .download_tags(AutotagOption::All);
+    opts
+}
+
+/// 区分认证失败、网络失败和其他 git 错误。
+fn classify(e: git2::Error) -> GitError {
+    match (e.code(), e.class()) {
+        (ErrorCode::Auth | ErrorCode::Certificate, _) => GitError::Auth(e),
+        (_, ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssh | ErrorClass::Ssl) => {
+            GitError::Network(e)
+        }
+        _ => GitError::Git2(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAST_COMMIT_OID: &str = "4db775450dee399c328935eb03fd4fcc6c60e333";
+
+    fn fixture_url() -> String {
+        format!(
+            "file://{}/tests/data/localtest.git",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    #[test]
+    fn test_clone_then_update_mirror() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mirror.git");
+
+        let (action, head) = clone_or_fetch(&fixture_url(), &path).unwrap();
+        assert_eq!(action, MirrorAction::Cloned);
+        assert_eq!(head.as_deref(), Some(LAST_COMMIT_OID));
+
+        let repo = Repository::open_bare(&path).unwrap();
+        assert!(
+            repo.config()
+                .unwrap()
+                .get_bool("remote.origin.mirror")
+                .unwrap()
+        );
+
+        let (action, head) = clone_or_fetch(&fixture_url(), &path).unwrap();
+        assert_eq!(action, MirrorAction::Updated);
+        assert_eq!(head.as_deref(), Some(LAST_COMMIT_OID));
+    }
+
+    #[test]
+    fn test_clone_missing_remote_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote_url = format!("file://{}/missing.git", dir.path().display());
+
+        let result = clone_or_fetch(&remote_url, dir.path().join("mirror.git"));
+
+        assert!(result.is_err());
+    }
+}
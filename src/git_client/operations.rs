@@ -1,10 +1,25 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
-use git2::{Oid, Repository, Sort};
+use git2::{
+    DiffFindOptions, ErrorCode, FileMode, ObjectType, Oid, Repository, Sort, TreeWalkMode,
+    TreeWalkResult,
+};
 
 use crate::git_client::IntoGitFileEntry;
 
-use super::{GitError, GitFileEntry, ConsolidateFileChanges};
+use super::{
+    ARCHIVE_BRANCH_PREFIX, ARCHIVE_TAG_PREFIX, ArchiveRemoval, ChangeKind, ConsolidateFileChanges,
+    DiffStat, FileClassifier, GitError, GitFileEntry, MAX_DIFF_BYTES, PathCheck, PruneDecision,
+    RepoStats, blob_size, commit_time, diff_stat, prune_traced, repo_stats, resolve_link,
+};
 /// 提供对 Git 仓库的常用操作。
 ///
 /// 实现 [`GitOperation`] 可以方便地执行 commit 差异分析、远程更新和文件读取等操作。
@@ -13,61 +28,104 @@ pub trait GitOperation: Send {
     /// 按提交顺序遍历两个提交之间的差异，返回对应的 [`GitFileEntry`] 列表。
     ///
     /// 如果指定了 `old`，则计算从该 commit 到 `new` 的差异；否则返回从仓库初始提交到 `new` 的差异。
-    fn diff_commits_range(&self, old: &str, new: &str) -> Result<Vec<GitFileEntry>, GitError>;
+    /// 文件类型由 `classifier` 推断。`new` 为零值，或仓库还没有提交（HEAD 未指向任何提交）且 `new` 不存在时返回空列表。
+    fn diff_commits_range(
+        &self,
+        old: &str,
+        new: &str,
+        classifier: &FileClassifier,
+    ) -> Result<Vec<GitFileEntry>, GitError>;
+
+    /// 与 [`GitOperation::diff_commits_range`] 执行相同的遍历和裁剪，返回每个路径的裁剪决定而不是裁剪结果。
+    fn diff_commits_trace(
+        &self,
+        old: &str,
+        new: &str,
+        classifier: &FileClassifier,
+    ) -> Result<Vec<PruneDecision>, GitError>;
 
     /// 读取指定 blob 内容为 UTF-8 字符串，解析失败返回 [`None`]。
     fn read_blob(&self, blob_id: &str) -> Option<String>;
-}
 
-impl GitOperation for Repository {
-    /// 按提交顺序遍历两个 commit 之间的差异，并生成 [`GitFileEntry`] 列表。
+    /// 读取指定 blob 的原始字节，blob 不存在时返回 [`None`]。
+    fn read_blob_bytes(&self, blob_id: &str) -> Option<Vec<u8>>;
+
+    /// 一次读取多个 blob 的原始字节，键为 blob id，不存在的 blob 不出现在结果中。
+    fn read_blobs(&self, blob_ids: &[&str]) -> HashMap<String, Vec<u8>>;
+
+    /// 比较两个 blob 的内容，统计变更规模，任一 blob 超过 [`MAX_DIFF_BYTES`] 时返回 [`None`]。
+    fn blob_diff_stat(&self, old: &str, new: &str) -> Result<Option<DiffStat>, GitError>;
+
+    /// 列出指定 commit 中所有文件的路径，不读取文件内容。
+    fn list_files(&self, commit: &str) -> Result<Vec<PathBuf>, GitError>;
+
+    /// 按路径读取指定 commit 中的文件内容为 UTF-8 字符串，文件不存在或解析失败返回 [`None`]。
+    fn read_path(&self, commit: &str, path: &Path) -> Option<String>;
+
+    /// 按路径读取 `rev`（如 `HEAD`、分支名或提交 id）指向的 commit 中文件的原始字节。
     ///
-    /// 流程：
-    /// 1. 解析 commit ID 为 Oid
-    /// 2. 创建 revwalk，按拓扑顺序从新 commit 向旧 commit 遍历
-    /// 3. 对每个 commit 生成相对于前一个 tree 的差异
-    /// 4. 将差异转换为 [`GitFileEntry`] 列表返回
-    fn diff_commits_range(&self, old: &str, new: &str) -> Result<Vec<GitFileEntry>, GitError> {
-        let old_oid = Oid::from_str(old)?;
-        let new_oid = Oid::from_str(new)?;
-
-        let commit = if old_oid.is_zero() || old == Self::EMPTY_TREE_OID {
-            None // 初始化提交，prev_tree 为 None
-        } else {
-            Some(self.find_commit(old_oid)?)
-        };
+    /// `rev` 无法解析时返回 [`GitError::RefNotFound`]，文件不存在或不是普通文件时返回 [`GitError::NotFound`]。
+    fn read_file_at_ref(&self, rev: &str, path: &Path) -> Result<Vec<u8>, GitError>;
 
-        let mut revwalk = self.revwalk()?;
-        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
-        revwalk.push(new_oid)?;
+    /// 为指定 commit 中的文件生成 [`ChangeKind::Modified`] 条目，跳过不存在的路径和子模块。
+    ///
+    /// 符号链接与 [`GitOperation::diff_commits_range`] 相同，解析为链接目标的 blob。
+    fn entries_at(
+        &self,
+        commit: &str,
+        paths: &[PathBuf],
+        classifier: &FileClassifier,
+    ) -> Result<Vec<GitFileEntry>, GitError>;
 
-        let prev_tree_opt = if let Some(c) = commit {
-            revwalk.hide(c.id())?;
-            Some(c.tree()?)
-        } else {
-            None
-        };
-        let entries = revwalk
-            .filter_map(Result::ok)
-            .scan(prev_tree_opt, |prev_tree, oid| {
-                // 这里遇到严重错误就直接结束整个迭代（返回 None）
-                let commit = self.find_commit(oid).ok()?;
-                let tree = commit.tree().ok()?;
-
-                let diff = match self.diff_tree_to_tree(prev_tree.as_ref(), Some(&tree), None) {
-                    Ok(d) => d,
-                    Err(_) => return None, // TODO: 这里可以选择记录日志再返回 None
-                };
+    /// 列出归档分支和归档标签去掉前缀后的名称，未排序，可能重复。
+    fn archive_names(&self) -> Result<Vec<String>, GitError>;
 
-                *prev_tree = Some(tree);
-                Some((diff, commit))
-            })
-            .flat_map(IntoGitFileEntry::into_entry)
+    /// 解析归档对应的 commit，优先查找归档分支，其次查找归档标签，都不存在时返回 [`GitError::RefNotFound`]。
+    fn resolve_archive(&self, name: &str) -> Result<String, GitError>;
+
+    /// 删除归档分支和归档标签，返回删除的引用和因此无法到达的提交数，不执行 `git gc`。
+    ///
+    /// 都不存在时返回 [`GitError::RefNotFound`]。不检查归档名，调用方应先用 [`is_archive_name`](super::is_archive_name) 校验。
+    fn delete_archive(&self, name: &str) -> Result<ArchiveRemoval, GitError>;
+
+    /// 解析 `rev`（如 `HEAD`、分支名或提交 id 前缀）指向的 commit。
+    ///
+    /// `rev` 不存在或 HEAD 未指向任何提交时返回 [`GitError::RefNotFound`]。
+    fn resolve_commit(&self, rev: &str) -> Result<String, GitError>;
+
+    /// HEAD 指向的 commit，仓库还没有提交时返回 [`None`]。
+    fn head_commit(&self) -> Result<Option<String>, GitError>;
+
+    /// 一次遍历 `commit` 及其所有祖先，统计提交数、作者和每周的提交数，见 [`RepoStats`]。
+    fn repo_stats(&self, commit: &str) -> Result<RepoStats, GitError>;
+}
+
+impl GitOperation for Repository {
+    /// 按提交顺序遍历两个 commit 之间的差异，裁剪后生成 [`GitFileEntry`] 列表，遍历见 [`walk_commits`]。
+    fn diff_commits_range(
+        &self,
+        old: &str,
+        new: &str,
+        classifier: &FileClassifier,
+    ) -> Result<Vec<GitFileEntry>, GitError> {
+        let entries = walk_commits(self, old, new, classifier)?
+            .into_iter()
+            .flat_map(|(_, entries)| entries)
             .collect::<Vec<_>>();
 
         Ok(entries.prune())
     }
 
+    fn diff_commits_trace(
+        &self,
+        old: &str,
+        new: &str,
+        classifier: &FileClassifier,
+    ) -> Result<Vec<PruneDecision>, GitError> {
+        let (_, decisions) = prune_traced(walk_commits(self, old, new, classifier)?);
+        Ok(decisions)
+    }
+
     /// 读取 blob 内容为 UTF-8 字符串，解析失败返回 [`None`]。
     fn read_blob(&self, oid: &str) -> Option<String> {
         let blob = self.find_blob(Oid::from_str(oid).ok()?).ok()?;
@@ -75,30 +133,782 @@ impl GitOperation for Repository {
             .ok()
             .map(|s| s.to_string())
     }
+
+    fn read_blob_bytes(&self, oid: &str) -> Option<Vec<u8>> {
+        let blob = self.find_blob(Oid::from_str(oid).ok()?).ok()?;
+        Some(blob.content().to_vec())
+    }
+
+    fn read_blobs(&self, blob_ids: &[&str]) -> HashMap<String, Vec<u8>> {
+        blob_ids
+            .iter()
+            .filter_map(|id| Some((id.to_string(), self.read_blob_bytes(id)?)))
+            .collect()
+    }
+
+    fn blob_diff_stat(&self, old: &str, new: &str) -> Result<Option<DiffStat>, GitError> {
+        let old = self.find_blob(Oid::from_str(old)?)?;
+        let new = self.find_blob(Oid::from_str(new)?)?;
+        if old.size() > MAX_DIFF_BYTES || new.size() > MAX_DIFF_BYTES {
+            return Ok(None);
+        }
+        Ok(Some(diff_stat(old.content(), new.content())?))
+    }
+
+    fn list_files(&self, commit: &str) -> Result<Vec<PathBuf>, GitError> {
+        let tree = self.find_commit(Oid::from_str(commit)?)?.tree()?;
+
+        let mut files = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob)
+                && let Some(name) = entry.name()
+            {
+                files.push(PathBuf::from(dir).join(name));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        Ok(files)
+    }
+
+    fn read_path(&self, commit: &str, path: &Path) -> Option<String> {
+        let tree = self
+            .find_commit(Oid::from_str(commit).ok()?)
+            .ok()?
+            .tree()
+            .ok()?;
+        let blob = self.find_blob(tree.get_path(path).ok()?.id()).ok()?;
+        std::str::from_utf8(blob.content())
+            .ok()
+            .map(|s| s.to_string())
+    }
+
+    fn read_file_at_ref(&self, rev: &str, path: &Path) -> Result<Vec<u8>, GitError> {
+        let tree = self
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| GitError::RefNotFound(rev.to_string()))?
+            .tree()?;
+        let entry = tree.get_path(path).map_err(|_| GitError::NotFound)?;
+        if entry.kind() != Some(ObjectType::Blob) {
+            return Err(GitError::NotFound);
+        }
+        Ok(self.find_blob(entry.id())?.content().to_vec())
+    }
+
+    fn entries_at(
+        &self,
+        commit: &str,
+        paths: &[PathBuf],
+        classifier: &FileClassifier,
+    ) -> Result<Vec<GitFileEntry>, GitError> {
+        let commit = self.find_commit(Oid::from_str(commit)?)?;
+        let tree = commit.tree()?;
+        let timestamp = commit_time(&commit);
+
+        Ok(paths
+            .iter()
+            .filter_map(|path| {
+                let unsafe_path = match classifier.check_path(path) {
+                    PathCheck::Valid => None,
+                    PathCheck::Hidden => return None,
+                    PathCheck::Invalid(reason) => Some(reason),
+                };
+                let entry = tree.get_path(path).ok()?;
+                let (id, skip_reason) = match entry.filemode() {
+                    mode if mode == i32::from(FileMode::Commit) => return None,
+                    _ if unsafe_path.is_some() => (entry.id(), unsafe_path),
+                    mode if mode == i32::from(FileMode::Link) => {
+                        match resolve_link(self, &tree, path, entry.id()) {
+                            Ok(id) => (id, None),
+                            Err(reason) => (entry.id(), Some(reason)),
+                        }
+                    }
+                    _ => (entry.id(), None),
+                };
+                Some(GitFileEntry {
+                    id: id.to_string(),
+                    path: path.clone(),
+                    change_kind: ChangeKind::Modified,
+                    file_kind: classifier.classify(path),
+                    timestamp,
+                    renamed_from: None,
+                    skip_reason,
+                    old_id: None,
+                    size: blob_size(self, id),
+                })
+            })
+            .collect())
+    }
+
+    fn archive_names(&self) -> Result<Vec<String>, GitError> {
+        let mut names = Vec::new();
+        for prefix in [ARCHIVE_BRANCH_PREFIX, ARCHIVE_TAG_PREFIX] {
+            for name in self.references_glob(&format!("{prefix}*"))?.names() {
+                if let Some(name) = name?.strip_prefix(prefix) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        Ok(names)
+    }
+
+    fn resolve_archive(&self, name: &str) -> Result<String, GitError> {
+        let reference = [ARCHIVE_BRANCH_PREFIX, ARCHIVE_TAG_PREFIX]
+            .iter()
+            .find_map(|prefix| self.find_reference(&format!("{prefix}{name}")).ok())
+            .ok_or_else(|| GitError::RefNotFound(name.to_string()))?;
+
+        Ok(reference.peel_to_commit()?.id().to_string())
+    }
+
+    fn delete_archive(&self, name: &str) -> Result<ArchiveRemoval, GitError> {
+        let before = reachable_commits(self)?;
+        let delete = |prefix: &str| -> Result<bool, GitError> {
+            match self.find_reference(&format!("{prefix}{name}")) {
+                Ok(mut reference) => Ok(reference.delete().map(|_| true)?),
+                Err(e) if e.code() == ErrorCode::NotFound => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        };
+        let branch = delete(ARCHIVE_BRANCH_PREFIX)?;
+        let tag = delete(ARCHIVE_TAG_PREFIX)?;
+        if !branch && !tag {
+            return Err(GitError::RefNotFound(name.to_string()));
+        }
+
+        Ok(ArchiveRemoval {
+            name: name.to_string(),
+            branch,
+            tag,
+            unreachable_commits: before.saturating_sub(reachable_commits(self)?),
+        })
+    }
+
+    fn resolve_commit(&self, rev: &str) -> Result<String, GitError> {
+        let object = self.revparse_single(rev).map_err(|e| match e.code() {
+            ErrorCode::NotFound | ErrorCode::UnbornBranch => GitError::RefNotFound(rev.to_string()),
+            _ => e.into(),
+        })?;
+        Ok(object.peel_to_commit()?.id().to_string())
+    }
+
+    fn head_commit(&self) -> Result<Option<String>, GitError> {
+        match self.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?.id().to_string())),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn repo_stats(&self, commit: &str) -> Result<RepoStats, GitError> {
+        repo_stats(self, commit)
+    }
+}
+
+/// 从所有引用出发能到达的提交数，附注标签解析到指向的提交
+fn reachable_commits(repo: &Repository) -> Result<usize, GitError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_glob("*")?;
+    let mut count = 0;
+    for oid in revwalk {
+        oid?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 按提交顺序遍历两个 commit 之间的差异，返回每个 commit 的 id 和裁剪前的 [`GitFileEntry`] 列表。
+///
+/// 流程：
+/// 1. 解析 commit ID 为 Oid
+/// 2. 创建 revwalk，按拓扑顺序从新 commit 向旧 commit 遍历
+/// 3. 对每个 commit 生成相对于前一个 tree 的差异，并检测重命名
+/// 4. 将差异转换为 [`GitFileEntry`] 列表返回
+fn walk_commits(
+    repo: &Repository,
+    old: &str,
+    new: &str,
+    classifier: &FileClassifier,
+) -> Result<Vec<(String, Vec<GitFileEntry>)>, GitError> {
+    let old_oid = Oid::from_str(old)?;
+    let new_oid = Oid::from_str(new)?;
+
+    // 分支被删除，或向空仓库推送前收到的通知，没有可处理的文件
+    if new_oid.is_zero() || (repo.find_commit(new_oid).is_err() && repo.head_commit()?.is_none()) {
+        return Ok(Vec::new());
+    }
+
+    let commit = if old_oid.is_zero() || old == Repository::EMPTY_TREE_OID {
+        None // 初始化提交，prev_tree 为 None
+    } else {
+        Some(repo.find_commit(old_oid)?)
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    revwalk.push(new_oid)?;
+
+    let mut prev_tree = if let Some(c) = commit {
+        revwalk.hide(c.id())?;
+        Some(c.tree()?)
+    } else {
+        None
+    };
+
+    // 任一提交读取或比较失败时返回错误，避免只处理了部分提交却当作完整结果
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let mut diff = repo.diff_tree_to_tree(prev_tree.as_ref(), Some(&tree), None)?;
+
+        // 开启重命名检测，使移动的文件以 Renamed 形式出现
+        let mut find_opts = DiffFindOptions::new();
+        find_opts.renames(true);
+        diff.find_similar(Some(&mut find_opts))?;
+
+        prev_tree = Some(tree);
+        let id = commit.id().to_string();
+        commits.push((id, (diff, commit).into_entry(repo, classifier)));
+    }
+    Ok(commits)
 }
 
 /// 异步访问的仓库封装。
 ///
-/// 内部使用 `Arc<Mutex<Repository>>` 保证线程安全。
+/// 只保存仓库路径，每次操作在阻塞线程池中重新打开仓库执行，
+/// 避免 git2 的同步调用占用异步运行时的工作线程。克隆的实例共享操作计数。
+#[derive(Debug, Clone)]
 pub struct AsyncGitClient {
-    inner: Arc<Mutex<Repository>>,
+    path: Arc<Path>,
+    runs: Arc<AtomicUsize>,
+    hook: Option<OperationHook>,
+}
+
+/// 每个操作在阻塞线程中执行前调用的函数，见 [`AsyncGitClient::with_hook`]
+#[derive(Clone)]
+struct OperationHook(Arc<dyn Fn() + Send + Sync>);
+
+impl fmt::Debug for OperationHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OperationHook")
+    }
 }
 
 impl AsyncGitClient {
-    /// 构造新的异步仓库封装。
-    pub(super) fn new(repo: Repository) -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(repo)),
-        }
+    /// 打开裸仓库，确认路径有效后保存路径。
+    pub(super) fn open(path: impl AsRef<Path>) -> Result<Self, GitError> {
+        let path = path.as_ref();
+        Repository::open_bare(path)?;
+        Ok(Self {
+            path: Arc::from(path),
+            runs: Arc::default(),
+            hook: None,
+        })
+    }
+
+    /// 设置每个操作在阻塞线程中执行前调用的 `hook`
+    pub(super) fn with_hook(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.hook = Some(OperationHook(Arc::new(hook)));
+        self
+    }
+
+    /// 已执行的操作次数，即打开仓库并占用阻塞线程的次数。
+    pub(super) fn runs(&self) -> usize {
+        self.runs.load(Ordering::Relaxed)
+    }
+
+    /// 在阻塞线程池中打开仓库并执行 `f`。
+    pub(super) async fn run<T, F>(&self, f: F) -> Result<T, GitError>
+    where
+        F: FnOnce(&Repository) -> Result<T, GitError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (path, hook) = (self.path.clone(), self.hook.clone());
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        tokio::task::spawn_blocking(move || {
+            if let Some(OperationHook(hook)) = hook {
+                hook();
+            }
+            f(&Repository::open_bare(&path)?)
+        })
+        .await
+        .map_err(std::io::Error::from)?
     }
 }
 
-impl GitOperation for AsyncGitClient {
-    fn read_blob(&self, oid: &str) -> Option<String> {
-        self.inner.lock().unwrap().read_blob(oid)
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use git2::Signature;
+
+    use super::*;
+    use crate::git_client::ChangeKind;
+
+    /// 在工作区写入或删除文件后提交，返回新 commit 的 Oid。
+    fn commit(repo: &Repository, writes: &[(&str, &str)], removes: &[&str]) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+
+        for (path, content) in writes {
+            let full = workdir.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+
+        for path in removes {
+            std::fs::remove_file(workdir.join(path)).unwrap();
+            index.remove_path(Path::new(path)).unwrap();
+        }
+
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        repo.commit(Some("HEAD"), &sig, &sig, "test", &tree, &parents)
+            .unwrap()
+    }
+
+    /// 按指定文件模式直接写入索引后提交，用于构造符号链接和子模块。
+    fn commit_modes(repo: &Repository, entries: &[(&str, FileMode, Oid)]) -> Oid {
+        let mut index = repo.index().unwrap();
+        for (path, mode, id) in entries {
+            index
+                .add(&git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: u32::from(*mode),
+                    uid: 0,
+                    gid: 0,
+                    file_size: 0,
+                    id: *id,
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.as_bytes().to_vec(),
+                })
+                .unwrap();
+        }
+
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+
+        repo.commit(Some("HEAD"), &sig, &sig, "test", &tree, &[&parent])
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unborn_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+        let classifier = FileClassifier::default();
+        let missing = "1111111111111111111111111111111111111111";
+
+        assert_eq!(repo.head_commit().unwrap(), None);
+        assert!(matches!(
+            repo.resolve_commit("HEAD"),
+            Err(GitError::RefNotFound(rev)) if rev == "HEAD"
+        ));
+        for (old, new) in [
+            (Repository::EMPTY_TREE_OID, missing),
+            (
+                Repository::EMPTY_TREE_OID,
+                "0000000000000000000000000000000000000000",
+            ),
+            ("0000000000000000000000000000000000000000", missing),
+        ] {
+            assert!(
+                repo.diff_commits_range(old, new, &classifier)
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+
+        // 有提交后，不存在的提交仍然报错
+        let mut tree = repo.treebuilder(None).unwrap();
+        let blob = repo.blob(b"# a\n").unwrap();
+        tree.insert("a.md", blob, FileMode::Blob.into()).unwrap();
+        let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        let first = repo
+            .commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])
+            .unwrap()
+            .to_string();
+        assert_eq!(repo.head_commit().unwrap(), Some(first.clone()));
+        assert_eq!(
+            repo.diff_commits_range(Repository::EMPTY_TREE_OID, &first, &classifier)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            repo.diff_commits_range(Repository::EMPTY_TREE_OID, missing, &classifier)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_symlinks_and_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let content = "---\ntitle: note\n---\n\nShared body.\n";
+        let first = commit(&repo, &[("shared/note.md", content)], &[]);
+        let note = repo.blob(content.as_bytes()).unwrap().to_string();
+        let link = |target: &str| repo.blob(target.as_bytes()).unwrap();
+        let second = commit_modes(
+            &repo,
+            &[
+                ("notes/linked.md", FileMode::Link, link("../shared/note.md")),
+                ("notes/chained.md", FileMode::Link, link("linked.md")),
+                ("notes/outside.md", FileMode::Link, link("../../outside.md")),
+                ("notes/missing.md", FileMode::Link, link("gone.md")),
+                ("assets", FileMode::Commit, first),
+            ],
+        );
+
+        let classifier = FileClassifier::default();
+        let entries = repo
+            .diff_commits_range(&first.to_string(), &second.to_string(), &classifier)
+            .unwrap();
+        let find = |path: &str| {
+            entries
+                .iter()
+                .find(|e| e.path() == Path::new(path))
+                .map(|e| (e.id().to_string(), e.skip_reason()))
+        };
+
+        // 子模块不生成条目，链接使用目标文件的内容
+        assert_eq!(entries.len(), 4);
+        assert!(find("assets").is_none());
+        assert_eq!(find("notes/linked.md"), Some((note.clone(), None)));
+        assert_eq!(find("notes/chained.md"), Some((note.clone(), None)));
+        assert_eq!(
+            find("notes/outside.md").unwrap().1,
+            Some("symlink points outside the repository")
+        );
+        assert_eq!(
+            find("notes/missing.md").unwrap().1,
+            Some("symlink target not found")
+        );
+
+        let entries = repo
+            .entries_at(
+                &second.to_string(),
+                &[PathBuf::from("notes/linked.md"), PathBuf::from("assets")],
+                &classifier,
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id(), note);
+        assert!(entries[0].skip_reason().is_none());
     }
 
-    fn diff_commits_range(&self, old: &str, new: &str) -> Result<Vec<GitFileEntry>, GitError> {
-        self.inner.lock().unwrap().diff_commits_range(old, new)
+    #[test]
+    fn test_unsafe_and_hidden_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let content = "---\ntitle: note\n---\n\nBody.\n";
+        let first = commit(&repo, &[("notes/ok.md", content)], &[]);
+        let second = commit(
+            &repo,
+            &[
+                ("notes/ /evil.md", content),
+                (".github/ISSUE_TEMPLATE/bug.md", content),
+                (".well-known/about.md", content),
+                (".group.yaml", "public: true\n"),
+            ],
+            &[],
+        );
+
+        let classifier = FileClassifier::default().with_allowed_hidden_dirs([".well-known"]);
+        let entries = repo
+            .diff_commits_range(&first.to_string(), &second.to_string(), &classifier)
+            .unwrap();
+        let paths = entries
+            .iter()
+            .map(|e| (e.path().to_string_lossy().into_owned(), e.skip_reason()))
+            .collect::<Vec<_>>();
+
+        // 隐藏目录下的文件不生成条目，不安全的路径记录原因
+        assert_eq!(
+            paths,
+            [
+                (".group.yaml".to_string(), None),
+                (".well-known/about.md".to_string(), None),
+                (
+                    "notes/ /evil.md".to_string(),
+                    Some("path contains an empty component")
+                ),
+            ]
+        );
+
+        let entries = repo
+            .entries_at(
+                &second.to_string(),
+                &[
+                    PathBuf::from(".github/ISSUE_TEMPLATE/bug.md"),
+                    PathBuf::from("notes/ /evil.md"),
+                    PathBuf::from("notes/ok.md"),
+                ],
+                &classifier,
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].skip_reason().is_some());
+        assert!(entries[1].skip_reason().is_none());
+    }
+
+    #[test]
+    fn test_diff_commits_range_detects_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let content = "---\ntitle: note\n---\n\nThe body of a note that is moved around.\n";
+        let first = commit(&repo, &[("old/note.md", content)], &[]);
+        let second = commit(&repo, &[("new/note.md", content)], &["old/note.md"]);
+
+        let entries = repo
+            .diff_commits_range(
+                &first.to_string(),
+                &second.to_string(),
+                &FileClassifier::default(),
+            )
+            .unwrap();
+
+        assert_eq!(entries.len(), 2);
+
+        let deleted = entries
+            .iter()
+            .find(|e| e.path() == Path::new("old/note.md"))
+            .unwrap();
+        assert_eq!(deleted.change_kind(), ChangeKind::Deleted);
+        assert!(deleted.renamed_from().is_none());
+
+        let added = entries
+            .iter()
+            .find(|e| e.path() == Path::new("new/note.md"))
+            .unwrap();
+        assert_eq!(added.change_kind(), ChangeKind::Added);
+        assert_eq!(added.renamed_from(), Some(Path::new("old/note.md")));
+    }
+
+    #[test]
+    fn test_diff_commits_trace() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let classifier = FileClassifier::default();
+
+        let content = "---\ntitle: note\n---\n\nThe body of a note that is moved around.\n";
+        let base = commit(&repo, &[("a/old.md", "old\n"), ("a/x.md", content)], &[]);
+        let added = commit(&repo, &[("a/new.md", "draft\n")], &[]);
+        let modified = commit(&repo, &[("a/new.md", "final\n")], &[]);
+        let removed = commit(&repo, &[], &["a/new.md"]);
+        let deleted = commit(&repo, &[], &["a/old.md"]);
+        let readded = commit(&repo, &[("a/old.md", "rewritten\n")], &[]);
+        let to_y = commit(&repo, &[("a/y.md", content)], &["a/x.md"]);
+        let to_z = commit(&repo, &[("a/z.md", content)], &["a/y.md"]);
+
+        let trace = repo
+            .diff_commits_trace(&base.to_string(), &to_z.to_string(), &classifier)
+            .unwrap();
+        let find = |path: &str| {
+            let decision = trace.iter().find(|d| d.path == Path::new(path)).unwrap();
+            let changes = decision
+                .changes
+                .iter()
+                .map(|c| (c.commit.clone(), c.change_kind))
+                .collect::<Vec<_>>();
+            (changes, decision.merged)
+        };
+        let at = |oid: Oid, kind| (oid.to_string(), kind);
+
+        // 新增后修改先合并为修改，再删除时保留为删除，而不是像新增后直接删除那样抵消
+        assert_eq!(
+            find("a/new.md"),
+            (
+                vec![
+                    at(added, ChangeKind::Added),
+                    at(modified, ChangeKind::Modified),
+                    at(removed, ChangeKind::Deleted),
+                ],
+                Some(ChangeKind::Deleted)
+            )
+        );
+
+        // 删除后以不同内容重新新增合并为修改
+        assert_eq!(
+            find("a/old.md"),
+            (
+                vec![
+                    at(deleted, ChangeKind::Deleted),
+                    at(readded, ChangeKind::Added)
+                ],
+                Some(ChangeKind::Modified)
+            )
+        );
+
+        // 连续重命名：中间路径抵消，最终路径的来源是中间路径
+        assert_eq!(
+            find("a/x.md"),
+            (
+                vec![at(to_y, ChangeKind::Deleted)],
+                Some(ChangeKind::Deleted)
+            )
+        );
+        assert_eq!(
+            find("a/y.md"),
+            (
+                vec![at(to_y, ChangeKind::Added), at(to_z, ChangeKind::Deleted)],
+                None
+            )
+        );
+        assert_eq!(
+            find("a/z.md"),
+            (vec![at(to_z, ChangeKind::Added)], Some(ChangeKind::Added))
+        );
+        let z = trace
+            .iter()
+            .find(|d| d.path == Path::new("a/z.md"))
+            .unwrap();
+        assert_eq!(
+            z.changes[0].renamed_from.as_deref(),
+            Some(Path::new("a/y.md"))
+        );
+
+        // 按路径首次出现的顺序排列，合并结果与裁剪结果一致
+        let paths = trace.iter().map(|d| d.path.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            ["a/new.md", "a/old.md", "a/x.md", "a/y.md", "a/z.md"].map(PathBuf::from)
+        );
+        let pruned = repo
+            .diff_commits_range(&base.to_string(), &to_z.to_string(), &classifier)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.path().to_path_buf(), e.change_kind()))
+            .collect::<Vec<_>>();
+        let merged = trace
+            .iter()
+            .filter_map(|d| Some((d.path.clone(), d.merged?)))
+            .collect::<Vec<_>>();
+        assert_eq!(pruned, merged);
+    }
+
+    #[test]
+    fn test_modified_entry_old_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let first = commit(&repo, &[("notes/a.md", "今天天气很好\n")], &[]);
+        commit(&repo, &[("notes/a.md", "今天天气不错\n")], &[]);
+        let third = commit(&repo, &[("notes/a.md", "今天天气不错\nsee you\n")], &[]);
+
+        // 多次修改合并后与范围起点的内容比较
+        let entries = repo
+            .diff_commits_range(
+                &first.to_string(),
+                &third.to_string(),
+                &FileClassifier::default(),
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].change_kind(), ChangeKind::Modified);
+        let old_id = entries[0].old_id().unwrap();
+        assert_eq!(
+            old_id,
+            repo.blob("今天天气很好\n".as_bytes()).unwrap().to_string()
+        );
+
+        let stat = repo
+            .blob_diff_stat(old_id, entries[0].id())
+            .unwrap()
+            .unwrap();
+        assert_eq!((stat.lines_added, stat.lines_removed), (2, 1));
+        assert_eq!((stat.words_added, stat.words_removed), (4, 2));
+
+        // 范围内新增的文件没有修改前的内容
+        let entries = repo
+            .diff_commits_range(
+                Repository::EMPTY_TREE_OID,
+                &third.to_string(),
+                &FileClassifier::default(),
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].old_id().is_none());
+    }
+
+    #[test]
+    fn test_archive_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let first = commit(&repo, &[("notes/a.md", "a")], &[]);
+        let second = commit(&repo, &[("notes/b.md", "b")], &[]);
+
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        repo.reference("refs/heads/archived/2024-Q1", first, false, "archive")
+            .unwrap();
+        let target = repo.find_object(second, None).unwrap();
+        repo.tag("archive/2024-Q2", &target, &sig, "archive", false)
+            .unwrap();
+        repo.tag_lightweight("other", &target, false).unwrap();
+
+        let mut names = repo.archive_names().unwrap();
+        names.sort();
+        assert_eq!(names, ["2024-Q1", "2024-Q2"]);
+
+        // 附注标签解析到指向的 commit
+        assert_eq!(repo.resolve_archive("2024-Q1").unwrap(), first.to_string());
+        assert_eq!(repo.resolve_archive("2024-Q2").unwrap(), second.to_string());
+        assert!(matches!(
+            repo.resolve_archive("2023-Q4"),
+            Err(GitError::RefNotFound(name)) if name == "2023-Q4"
+        ));
+        assert_eq!(repo.resolve_commit("HEAD").unwrap(), second.to_string());
+        assert_eq!(
+            repo.resolve_commit("archive/2024-Q2").unwrap(),
+            second.to_string()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_run_does_not_block_runtime() {
+        let client = AsyncGitClient::open(crate::REPO_PATH).unwrap();
+
+        // 模拟耗时的 diff
+        let slow = client.run(|_| {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        });
+
+        // 单线程运行时上测量其他任务的调度延迟
+        let probe = async {
+            let mut worst = Duration::ZERO;
+            for _ in 0..10 {
+                let start = Instant::now();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                worst = worst.max(start.elapsed());
+            }
+            worst
+        };
+
+        let (slow, worst) = tokio::join!(slow, probe);
+        slow.unwrap();
+        assert!(
+            worst < Duration::from_millis(200),
+            "runtime stalled for {worst:?}"
+        );
     }
 }
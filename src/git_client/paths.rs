@@ -0,0 +1,130 @@
+use std::path::Path;
+
+/// 仓库文件路径的检查结果，见 [`check_path`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathCheck {
+    /// 可以用于推断组和 slug、写入数据库
+    Valid,
+    /// 位于未允许的隐藏目录（以 `.` 开头）下，直接忽略
+    Hidden,
+    /// 路径不安全，附带原因
+    Invalid(&'static str),
+}
+
+/// 检查仓库中的文件路径
+///
+/// 所有生成 [`GitFileEntry`](super::GitFileEntry) 的流程共用，推断组和 slug 或写入数据库之前调用。
+/// 按 `/` 逐段检查原始路径，不做规范化：
+/// - 含 NUL 字节、以 `/` 开头、某一段去掉首尾空白后为空、某一段为 `.` 或 `..` 时返回 [`PathCheck::Invalid`]
+/// - 某一级目录以 `.` 开头且不在 `allowed_hidden` 中时返回 [`PathCheck::Hidden`]，
+///   文件自身以 `.` 开头（如 `.group.yaml`）不受影响
+///
+/// 同时满足两者时返回 [`PathCheck::Invalid`]。
+pub fn check_path<S: AsRef<str>>(path: &Path, allowed_hidden: &[S]) -> PathCheck {
+    let path = path.to_string_lossy();
+    if path.contains('\0') {
+        return PathCheck::Invalid("path contains a NUL byte");
+    }
+    if path.starts_with('/') {
+        return PathCheck::Invalid("path is absolute");
+    }
+
+    let components = path.split('/').collect::<Vec<_>>();
+    let mut hidden = false;
+    for (i, component) in components.iter().enumerate() {
+        match component.trim() {
+            "" => return PathCheck::Invalid("path contains an empty component"),
+            "." | ".." => return PathCheck::Invalid("path contains a `.` or `..` component"),
+            name if i + 1 < components.len()
+                && name.starts_with('.')
+                && !allowed_hidden.iter().any(|a| a.as_ref() == name) =>
+            {
+                hidden = true;
+            }
+            _ => (),
+        }
+    }
+
+    if hidden {
+        PathCheck::Hidden
+    } else {
+        PathCheck::Valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: [&str; 0] = [];
+
+    fn check(path: &str) -> PathCheck {
+        check_path(Path::new(path), &NONE)
+    }
+
+    #[test]
+    fn test_valid_paths() {
+        for path in [
+            "intro.md",
+            "notes/intro.md",
+            "notes/.group.yaml",
+            ".group.toml",
+            "a/b/c/d.md",
+            "读书 笔记/intro.md",
+            "notes/..md",
+            "notes/a..b.md",
+        ] {
+            assert_eq!(check(path), PathCheck::Valid, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        for (path, reason) in [
+            ("../../etc/cron.d/evil.md", "`.` or `..`"),
+            ("notes/../evil.md", "`.` or `..`"),
+            ("notes/..", "`.` or `..`"),
+            ("./intro.md", "`.` or `..`"),
+            ("notes/ .. /evil.md", "`.` or `..`"),
+            ("/etc/passwd.md", "absolute"),
+            ("notes/in\0tro.md", "NUL"),
+            ("notes//intro.md", "empty component"),
+            ("notes/ /intro.md", "empty component"),
+            ("notes/", "empty component"),
+            ("", "empty component"),
+            ("notes/\t.md/", "empty component"),
+        ] {
+            match check(path) {
+                PathCheck::Invalid(r) => assert!(r.contains(reason), "{path:?}: {r}"),
+                other => panic!("{path:?}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_hidden_directories() {
+        assert_eq!(check(".github/workflows/ci.md"), PathCheck::Hidden);
+        assert_eq!(check("notes/.obsidian/templates/a.md"), PathCheck::Hidden);
+        assert_eq!(check(".obsidian/.group.yaml"), PathCheck::Hidden);
+
+        // 不安全的路径优先于隐藏目录
+        assert!(matches!(
+            check(".github/../intro.md"),
+            PathCheck::Invalid(_)
+        ));
+
+        let allowed = [".well-known"];
+        assert_eq!(
+            check_path(Path::new(".well-known/about.md"), &allowed),
+            PathCheck::Valid
+        );
+        assert_eq!(
+            check_path(Path::new("notes/.well-known/about.md"), &allowed),
+            PathCheck::Valid
+        );
+        assert_eq!(
+            check_path(Path::new(".well-known/.drafts/a.md"), &allowed),
+            PathCheck::Hidden
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use super::{GitClient, GitError};
+
+/// 每次预读的 blob 数量
+pub const BLOB_PREFETCH_CHUNK: usize = 100;
+
+/// 按顺序批量预读 blob
+///
+/// 创建时给出之后会依次读取的 blob id，读取时按 [`BLOB_PREFETCH_CHUNK`] 个一批通过
+/// [`GitClient::load_blobs`] 读入，每批只打开一次仓库，内存中最多保留一批内容。
+/// 读取不在列表中或已经越过的 id 时退回 [`GitClient::load_bytes`]。
+#[derive(Debug, Default)]
+pub struct BlobPrefetch {
+    ids: Vec<String>,
+    /// `ids` 中下一批的起始位置
+    next: usize,
+    blobs: HashMap<String, Vec<u8>>,
+}
+
+impl BlobPrefetch {
+    pub fn new(ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            ids: ids.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// 读取 `id` 的原始字节，当前批次中没有时读入从 `id` 开始的下一批
+    ///
+    /// 与 [`GitClient::load_bytes`] 相同，blob 不存在时返回 [`GitError::NotFound`]。
+    pub async fn load_bytes(&mut self, repo: &GitClient, id: &str) -> Result<Vec<u8>, GitError> {
+        if let Some(bytes) = self.blobs.remove(id) {
+            return Ok(bytes);
+        }
+
+        let Some(start) = self.ids[self.next..]
+            .iter()
+            .position(|i| i == id)
+            .map(|p| self.next + p)
+        else {
+            return repo.load_bytes(id).await;
+        };
+        let end = (start + BLOB_PREFETCH_CHUNK).min(self.ids.len());
+        self.blobs = repo.load_blobs(&self.ids[start..end]).await?;
+        self.next = end;
+
+        self.blobs.remove(id).ok_or(GitError::NotFound)
+    }
+
+    /// 读取 `id` 的内容为 UTF-8 字符串，与 [`GitClient::load_file`] 相同，不是 UTF-8 时返回 [`GitError::NotFound`]
+    pub async fn load_file(&mut self, repo: &GitClient, id: &str) -> Result<String, GitError> {
+        String::from_utf8(self.load_bytes(repo, id).await?).map_err(|_| GitError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAST_COMMIT_OID: &str = "4db775450dee399c328935eb03fd4fcc6c60e333";
+
+    #[tokio::test]
+    async fn test_prefetch_reads_once() {
+        let repo = GitClient::open(crate::REPO_PATH).unwrap();
+        let ids = repo
+            .snapshot(LAST_COMMIT_OID)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.id().to_string())
+            .collect::<Vec<_>>();
+        // 同一 blob 出现两次，第二次读取时已从批次中取走
+        let listed = [ids.clone(), ids.clone()].concat();
+        let mut prefetch = BlobPrefetch::new(&listed);
+
+        let before = repo.operations();
+        let mut loaded = vec![];
+        for id in &listed {
+            loaded.push(prefetch.load_bytes(&repo, id).await.unwrap());
+        }
+        assert_eq!(repo.operations() - before, 1 + ids.len());
+
+        for (id, bytes) in listed.iter().zip(loaded) {
+            assert_eq!(bytes, repo.load_bytes(id).await.unwrap());
+        }
+        assert!(matches!(
+            prefetch.load_file(&repo, &"0".repeat(40)).await,
+            Err(GitError::NotFound)
+        ));
+    }
+}
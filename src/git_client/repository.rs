@@ -1,81 +1,377 @@
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
+use chrono_tz::Tz;
 use git2::Repository;
 
-use super::{AsyncGitClient, GitError, GitFileEntry, GitOperation};
+use super::{
+    ArchiveRemoval, AsyncGitClient, DiffStat, FileClassifier, FileKind, GitError, GitFileEntry,
+    GitOperation, PathCheck, PruneDecision, RepoStats, sort_archives,
+};
 
-/// 内部持有实现了 [`GitOps`] 的实例，用于执行 Git 操作。
-#[derive(Debug)]
-pub struct GitClient<R: GitOperation>(R);
+/// 内部持有 [`AsyncGitClient`]，用于执行 Git 操作。
+///
+/// 所有操作都在阻塞线程池中执行，不会占用异步运行时。
+/// 生成 [`GitFileEntry`] 时使用持有的 [`FileClassifier`] 推断文件类型，提交时间转换到持有的时区。
+#[derive(Debug, Clone)]
+pub struct GitClient {
+    repo: AsyncGitClient,
+    classifier: FileClassifier,
+    timezone: Tz,
+}
 
-impl GitClient<AsyncGitClient> {
-    /// 打开一个裸仓库并返回 [`GitRepository`] 实例。
+impl GitClient {
+    /// 打开一个裸仓库并返回 [`GitClient`] 实例。
     ///
-    /// 仓库路径基于传入的名称
+    /// 仓库路径基于传入的名称，使用默认的 [`FileClassifier`] 和 UTC 时区
     pub fn open(repo_name: impl AsRef<Path>) -> Result<Self, GitError> {
-        let repo = Repository::open_bare(repo_name)?;
-        Ok(Self(AsyncGitClient::new(repo)))
+        Ok(Self {
+            repo: AsyncGitClient::open(repo_name)?,
+            classifier: FileClassifier::default(),
+            timezone: Tz::UTC,
+        })
+    }
+
+    /// 设置文件类型分类器
+    pub fn with_classifier(mut self, classifier: FileClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// 设置时区
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// 获取时区，解析文章中不带偏移的时间时使用
+    pub fn timezone(&self) -> Tz {
+        self.timezone
     }
-}
 
-impl<R: GitOperation> GitClient<R> {
-    /// 获取内部仓库实例。
-    fn repo(&self) -> &R {
-        &self.0
+    /// 获取附件扩展名，见 [`FileClassifier::attachment_extensions`]
+    pub fn attachment_extensions(&self) -> &[String] {
+        self.classifier.attachment_extensions()
     }
 
     /// 获取指定 commit 的快照。
     ///
     /// 返回对应的 [`GitFileEntry`] 列表，用于查看当前 commit 的文件状态。
-    pub fn snapshot(&self, commit_str: &str) -> Result<Vec<GitFileEntry>, GitError> {
-        let repo = self.repo();
-        let entries = repo.diff_commits_range(R::EMPTY_TREE_OID, commit_str)?;
-        Ok(entries)
+    pub async fn snapshot(&self, commit_str: &str) -> Result<Vec<GitFileEntry>, GitError> {
+        self.diff_commits(Repository::EMPTY_TREE_OID, commit_str)
+            .await
     }
 
     /// 比较两个 commit 之间的差异。
     ///
     /// 返回 [`GitFileEntry`] 列表，表示变更的文件和内容。
-    pub fn diff_commits(
+    pub async fn diff_commits(
+        &self,
+        old_commit_str: &str,
+        new_commit_str: &str,
+    ) -> Result<Vec<GitFileEntry>, GitError> {
+        let (old, new) = (old_commit_str.to_string(), new_commit_str.to_string());
+        let (classifier, timezone) = (self.classifier.clone(), self.timezone);
+        self.repo
+            .run(move |repo| {
+                let entries = repo.diff_commits_range(&old, &new, &classifier)?;
+                Ok(in_timezone(entries, timezone))
+            })
+            .await
+    }
+
+    /// 比较两个 commit 之间的差异，返回每个路径裁剪前的变更和合并结果，用于排查变更被合并或抵消的原因。
+    ///
+    /// 遍历和裁剪与 [`GitClient::diff_commits`] 相同，见 [`PruneDecision`]。
+    pub async fn diff_trace(
         &self,
         old_commit_str: &str,
         new_commit_str: &str,
+    ) -> Result<Vec<PruneDecision>, GitError> {
+        let (old, new) = (old_commit_str.to_string(), new_commit_str.to_string());
+        let classifier = self.classifier.clone();
+        self.repo
+            .run(move |repo| repo.diff_commits_trace(&old, &new, &classifier))
+            .await
+    }
+
+    /// 列出所有归档分支和归档标签的名称，按名称中的 `YYYY-Qn` 从早到晚排序。
+    pub async fn archives(&self) -> Result<Vec<String>, GitError> {
+        let mut names = self.repo.run(|repo| repo.archive_names()).await?;
+        sort_archives(&mut names);
+        Ok(names)
+    }
+
+    /// 比较两个归档之间的差异。
+    ///
+    /// 归档名解析为 `archived/<name>` 分支或 `archive/<name>` 标签指向的 commit，
+    /// `old_name` 为 [`None`] 时与仓库初始状态比较。归档不存在时返回 [`GitError::RefNotFound`]。
+    pub async fn diff_archives(
+        &self,
+        old_name: Option<&str>,
+        new_name: &str,
     ) -> Result<Vec<GitFileEntry>, GitError> {
-        let repo = self.repo();
-        let entries = repo.diff_commits_range(old_commit_str, new_commit_str)?;
-        Ok(entries)
+        let (old, new) = (old_name.map(str::to_string), new_name.to_string());
+        let (classifier, timezone) = (self.classifier.clone(), self.timezone);
+        self.repo
+            .run(move |repo| {
+                let new = repo.resolve_archive(&new)?;
+                let old = match old {
+                    Some(old) => repo.resolve_archive(&old)?,
+                    None => Repository::EMPTY_TREE_OID.to_string(),
+                };
+                let entries = repo.diff_commits_range(&old, &new, &classifier)?;
+                Ok(in_timezone(entries, timezone))
+            })
+            .await
+    }
+
+    /// 删除归档分支和归档标签，见 [`GitOperation::delete_archive`]。
+    ///
+    /// 不检查归档名，管理操作见 [`remove_archive`](crate::git_sync::remove_archive)。
+    pub async fn delete_archive(&self, name: &str) -> Result<ArchiveRemoval, GitError> {
+        let name = name.to_string();
+        self.repo.run(move |repo| repo.delete_archive(&name)).await
+    }
+
+    /// HEAD 指向的 commit id，仓库还没有提交时返回 [`None`]。
+    pub async fn head(&self) -> Result<Option<String>, GitError> {
+        self.repo.run(|repo| repo.head_commit()).await
+    }
+
+    /// 统计 `commit` 及其所有祖先的提交，见 [`RepoStats`]。
+    pub async fn repo_stats(&self, commit_str: &str) -> Result<RepoStats, GitError> {
+        let commit = commit_str.to_string();
+        self.repo.run(move |repo| repo.repo_stats(&commit)).await
+    }
+
+    /// 解析 `rev` 指向的 commit id，如 `HEAD` 或分支名。
+    pub async fn resolve(&self, rev: &str) -> Result<String, GitError> {
+        let rev = rev.to_string();
+        self.repo.run(move |repo| repo.resolve_commit(&rev)).await
     }
 
     /// 读取指定文件的内容。
     ///
     /// 返回 UTF-8 字符串，文件不存在时返回 [`GitError::NotFound`]。
-    pub fn load_file(&self, file_id: impl AsRef<str>) -> Result<String, GitError> {
-        self.repo()
-            .read_blob(file_id.as_ref())
-            .ok_or(GitError::NotFound)
+    pub async fn load_file(&self, file_id: impl AsRef<str>) -> Result<String, GitError> {
+        let file_id = file_id.as_ref().to_string();
+        self.repo
+            .run(move |repo| repo.read_blob(&file_id).ok_or(GitError::NotFound))
+            .await
+    }
+
+    /// 在一次操作中读取多个文件的原始字节。
+    ///
+    /// 键为文件 id，不存在的文件不出现在结果中。结果全部保存在内存中，调用方应控制每次读取的数量，
+    /// 见 [`BlobPrefetch`](super::BlobPrefetch)。
+    pub async fn load_blobs(
+        &self,
+        file_ids: &[String],
+    ) -> Result<HashMap<String, Vec<u8>>, GitError> {
+        let file_ids = file_ids.to_vec();
+        self.repo
+            .run(move |repo| {
+                let ids = file_ids.iter().map(String::as_str).collect::<Vec<_>>();
+                Ok(repo.read_blobs(&ids))
+            })
+            .await
+    }
+
+    /// 已在阻塞线程池中执行的 Git 操作次数，克隆的实例共享计数。
+    ///
+    /// 每次操作都会重新打开仓库并占用一个阻塞线程，用于观察同步过程中的仓库访问次数。
+    pub fn operations(&self) -> usize {
+        self.repo.runs()
+    }
+
+    /// 设置每个 Git 操作在阻塞线程中执行前调用的 `hook`，克隆的实例共享同一个函数。
+    ///
+    /// 用于观察或模拟耗时的仓库访问，例如检查慢速的比较和读取不会阻塞其他请求。
+    pub fn with_operation_hook(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.repo = self.repo.with_hook(hook);
+        self
     }
+
+    /// 比较修改前后两个 blob 的内容，统计增删的行数和词数。
+    ///
+    /// 任一内容超过 [`MAX_DIFF_BYTES`](super::MAX_DIFF_BYTES) 时不计算，返回 [`None`]。
+    pub async fn diff_stat(
+        &self,
+        old_id: impl AsRef<str>,
+        new_id: impl AsRef<str>,
+    ) -> Result<Option<DiffStat>, GitError> {
+        let (old, new) = (old_id.as_ref().to_string(), new_id.as_ref().to_string());
+        self.repo
+            .run(move |repo| repo.blob_diff_stat(&old, &new))
+            .await
+    }
+
+    /// 列出指定 commit 中所有组配置文件的路径。
+    pub async fn group_files(&self, commit_str: &str) -> Result<Vec<PathBuf>, GitError> {
+        let commit = commit_str.to_string();
+        let files = self.repo.run(move |repo| repo.list_files(&commit)).await?;
+        Ok(files
+            .into_iter()
+            .filter(|p| self.classifier.classify(p) == FileKind::Group)
+            .filter(|p| self.classifier.check_path(p) == PathCheck::Valid)
+            .collect())
+    }
+
+    /// 在阻塞线程中调用 `f`，`f` 可以按路径读取 `commit` 中的文件内容。
+    ///
+    /// 读取函数在文件不存在或不是 UTF-8 时返回 [`None`]。
+    pub async fn with_tree<T, F>(&self, commit_str: &str, f: F) -> Result<T, GitError>
+    where
+        F: FnOnce(&mut dyn FnMut(&Path) -> Option<String>) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let commit = commit_str.to_string();
+        self.repo
+            .run(move |repo| Ok(f(&mut |path: &Path| repo.read_path(&commit, path))))
+            .await
+    }
+
+    /// 按路径读取 `rev` 指向的 commit 中文件的原始字节，见 [`GitOperation::read_file_at_ref`]。
+    pub async fn load_file_at_ref(
+        &self,
+        rev: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<u8>, GitError> {
+        let (rev, path) = (rev.to_string(), path.as_ref().to_path_buf());
+        self.repo
+            .run(move |repo| repo.read_file_at_ref(&rev, &path))
+            .await
+    }
+
+    /// 读取 `commit` 中 `dir` 目录下的组说明文件，按配置的说明文件名依次查找，不存在时返回 [`None`]
+    pub async fn load_readme(
+        &self,
+        commit_str: &str,
+        dir: impl AsRef<Path>,
+    ) -> Result<Option<String>, GitError> {
+        let dir = dir.as_ref().to_path_buf();
+        let names = self.classifier.readme_names().to_vec();
+        self.with_tree(commit_str, move |load| {
+            names.iter().find_map(|name| load(&dir.join(name)))
+        })
+        .await
+    }
+
+    /// 为 `commit` 中已存在的文件生成 [`ChangeKind::Modified`](super::ChangeKind::Modified) 条目，
+    /// 用于重新处理未变更但依赖已变更内容的文件。
+    pub async fn entries_at(
+        &self,
+        commit_str: &str,
+        paths: Vec<PathBuf>,
+    ) -> Result<Vec<GitFileEntry>, GitError> {
+        let commit = commit_str.to_string();
+        let (classifier, timezone) = (self.classifier.clone(), self.timezone);
+        self.repo
+            .run(move |repo| {
+                let entries = repo.entries_at(&commit, &paths, &classifier)?;
+                Ok(in_timezone(entries, timezone))
+            })
+            .await
+    }
+
+    /// 读取指定文件的原始字节。
+    ///
+    /// 不要求内容为 UTF-8，文件不存在时返回 [`GitError::NotFound`]。
+    pub async fn load_bytes(&self, file_id: impl AsRef<str>) -> Result<Vec<u8>, GitError> {
+        let file_id = file_id.as_ref().to_string();
+        self.repo
+            .run(move |repo| repo.read_blob_bytes(&file_id).ok_or(GitError::NotFound))
+            .await
+    }
+}
+
+/// 将条目的提交时间转换到 `timezone`
+fn in_timezone(mut entries: Vec<GitFileEntry>, timezone: Tz) -> Vec<GitFileEntry> {
+    for entry in &mut entries {
+        entry.timestamp = entry.timestamp.with_timezone(&timezone).fixed_offset();
+    }
+    entries
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::git_client::AsSummary;
+    use crate::git_client::{AsSummary, ChangeKind};
 
     use super::*;
 
     const LAST_COMMIT_OID: &str = "4db775450dee399c328935eb03fd4fcc6c60e333";
 
-    #[test]
-    fn test_diff_commits() {
+    #[tokio::test]
+    async fn test_diff_commits() {
         let repo = GitClient::open(crate::REPO_PATH).unwrap();
-        let entries = repo.snapshot(LAST_COMMIT_OID).unwrap();
+        let entries = repo.snapshot(LAST_COMMIT_OID).await.unwrap();
 
         let summary = entries.as_summary();
 
         dbg!(&summary);
         let mut lines = summary.lines();
 
-        assert!(lines.next().unwrap().starts_with("[md]"));
         assert!(lines.next().unwrap().starts_with("[group]"));
+        assert!(lines.next().unwrap().starts_with("[md]"));
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("2 files changed, 1 group, ")
+        );
         assert!(lines.next().is_none());
     }
+
+    #[tokio::test]
+    async fn test_timestamp_in_timezone() {
+        let utc = GitClient::open(crate::REPO_PATH).unwrap();
+        let shanghai = utc.clone().with_timezone(Tz::Asia__Shanghai);
+
+        let a = utc.snapshot(LAST_COMMIT_OID).await.unwrap();
+        let b = shanghai.snapshot(LAST_COMMIT_OID).await.unwrap();
+
+        assert_eq!(a[0].timestamp(), b[0].timestamp());
+        assert_eq!(a[0].timestamp().offset().local_minus_utc(), 0);
+        assert_eq!(b[0].timestamp().offset().local_minus_utc(), 8 * 3600);
+    }
+
+    #[tokio::test]
+    async fn test_with_tree_and_entries_at() {
+        let repo = GitClient::open(crate::REPO_PATH).unwrap();
+
+        let (group, missing) = repo
+            .with_tree(LAST_COMMIT_OID, |load| {
+                (
+                    load(Path::new("notes/.group.yaml")),
+                    load(Path::new("notes/missing.md")),
+                )
+            })
+            .await
+            .unwrap();
+        assert!(group.is_some());
+        assert!(missing.is_none());
+
+        let entries = repo
+            .entries_at(
+                LAST_COMMIT_OID,
+                vec!["notes/markdown-test.md".into(), "notes/missing.md".into()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), Path::new("notes/markdown-test.md"));
+        assert_eq!(entries[0].file_kind(), FileKind::Markdown);
+        assert_eq!(entries[0].change_kind(), ChangeKind::Modified);
+    }
+
+    #[tokio::test]
+    async fn test_group_files() {
+        let repo = GitClient::open(crate::REPO_PATH).unwrap();
+        let files = repo.group_files(LAST_COMMIT_OID).await.unwrap();
+
+        assert_eq!(files, vec![PathBuf::from("notes/.group.yaml")]);
+    }
 }
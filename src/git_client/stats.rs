@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use git2::{Oid, Repository};
+use serde::Serialize;
+
+use super::{GitError, commit_time};
+
+/// 提交活动统计的周数
+pub const ACTIVITY_WEEKS: usize = 52;
+
+const WEEK_SECS: i64 = 7 * 24 * 3600;
+
+/// 仓库的提交统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct RepoStats {
+    /// 提交总数
+    pub commits: usize,
+    /// 提交作者数，按邮箱去重，不区分大小写
+    pub authors: usize,
+    /// 最早的提交时间，Unix 毫秒时间戳，仓库没有提交时为 `null`
+    pub first_commit_at: Option<i64>,
+    /// 最近的提交时间，Unix 毫秒时间戳，仓库没有提交时为 `null`
+    pub latest_commit_at: Option<i64>,
+    /// 每周的提交数，共 [`ACTIVITY_WEEKS`] 项，从早到晚排列，最后一项为截至统计的 commit 的一周
+    pub weekly: Vec<usize>,
+}
+
+impl Default for RepoStats {
+    fn default() -> Self {
+        Self {
+            commits: 0,
+            authors: 0,
+            first_commit_at: None,
+            latest_commit_at: None,
+            weekly: vec![0; ACTIVITY_WEEKS],
+        }
+    }
+}
+
+/// 遍历 `commit` 及其所有祖先，统计提交数、作者和每周的提交数。
+///
+/// 每周的提交数以 `commit` 的提交时间为终点向前划分，不受统计时的当前时间影响，结果只取决于 `commit`。
+/// 更早的提交只计入总数和时间范围；提交时间晚于 `commit` 的祖先（时钟偏差）计入最后一周。
+pub(super) fn repo_stats(repo: &Repository, commit: &str) -> Result<RepoStats, GitError> {
+    let head = repo.find_commit(Oid::from_str(commit)?)?;
+    let end = commit_time(&head).timestamp();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head.id())?;
+
+    let mut stats = RepoStats::default();
+    let mut authors = HashSet::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let time = commit_time(&commit);
+        let (secs, time) = (time.timestamp(), time.timestamp_millis());
+
+        stats.commits += 1;
+        stats.first_commit_at = Some(stats.first_commit_at.map_or(time, |t| t.min(time)));
+        stats.latest_commit_at = Some(stats.latest_commit_at.map_or(time, |t| t.max(time)));
+        if let Some(email) = commit.author().email() {
+            authors.insert(email.to_lowercase());
+        }
+
+        let week = (end - secs).max(0) / WEEK_SECS;
+        if let Some(bucket) = usize::try_from(week)
+            .ok()
+            .and_then(|w| ACTIVITY_WEEKS.checked_sub(w + 1))
+        {
+            stats.weekly[bucket] += 1;
+        }
+    }
+    stats.authors = authors.len();
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Signature, Time};
+
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+    const START: i64 = 1_700_000_000;
+
+    /// 以指定作者和时间提交一个空改动
+    fn commit(repo: &Repository, email: &str, time: i64) -> Oid {
+        let sig = Signature::new("tester", email, &Time::new(time, 480)).unwrap();
+        let tree = {
+            let mut index = repo.index().unwrap();
+            repo.find_tree(index.write_tree().unwrap()).unwrap()
+        };
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        repo.commit(Some("HEAD"), &sig, &sig, "test", &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_repo_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        // 超出统计周数的提交只计入总数
+        commit(&repo, "alice@example.com", START - 400 * DAY);
+        commit(&repo, "Alice@Example.com", START - 15 * DAY);
+        commit(&repo, "bob@example.com", START - 8 * DAY);
+        commit(&repo, "ALICE@example.com", START - 7 * DAY);
+        commit(&repo, "bob@example.com", START - DAY);
+        let head = commit(&repo, "alice@example.com", START);
+
+        let stats = repo_stats(&repo, &head.to_string()).unwrap();
+        assert_eq!(stats.commits, 6);
+        assert_eq!(stats.authors, 2);
+        assert_eq!(stats.first_commit_at, Some((START - 400 * DAY) * 1000));
+        assert_eq!(stats.latest_commit_at, Some(START * 1000));
+
+        assert_eq!(stats.weekly.len(), ACTIVITY_WEEKS);
+        assert_eq!(stats.weekly[ACTIVITY_WEEKS - 1], 2);
+        assert_eq!(stats.weekly[ACTIVITY_WEEKS - 2], 2);
+        assert_eq!(stats.weekly[ACTIVITY_WEEKS - 3], 1);
+        assert_eq!(stats.weekly.iter().sum::<usize>(), 5);
+
+        // 只统计 commit 及其祖先
+        let parent = repo.find_commit(head).unwrap().parent_id(0).unwrap();
+        let stats = repo_stats(&repo, &parent.to_string()).unwrap();
+        assert_eq!(stats.commits, 5);
+        assert_eq!(stats.latest_commit_at, Some((START - DAY) * 1000));
+        assert_eq!(stats.weekly[ACTIVITY_WEEKS - 1], 2);
+    }
+}
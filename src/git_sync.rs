@@ -1,6 +1,38 @@
+mod archive;
+mod backup;
+mod coordinator;
+mod debounce;
+mod fingerprint;
+mod history;
 mod hook;
+mod limits;
 mod persist;
+mod preview;
+mod progress;
+mod report;
+mod timetravel;
 pub use self::{
+    archive::{ArchiveRefused, prune_archives, remove_archive},
+    backup::{Backup, BackupError, BackupOptions, DEFAULT_BACKUP_KEEP, list_backups, write_backup},
+    coordinator::{
+        MAX_QUEUED_SYNCS, RETRY_AFTER_SECS, SyncBusy, SyncCoordinator, SyncPermit, SyncState,
+        SyncStatus,
+    },
+    debounce::{DebouncedSync, PendingSync, SyncDebouncer},
+    fingerprint::{content_fingerprint, record_fingerprint, repo_fingerprint},
+    history::{
+        DEFAULT_SYNC_LOG_ENTRIES, MAX_SYNC_LOG_TEXT_BYTES, SyncLog, SyncLogEntry, SyncOutcome,
+    },
     hook::{GitPushPayload, PushKind},
-    persist::{PersistMode, Persistable},
+    limits::{ContentLimits, Verdict},
+    persist::{PersistMode, Persistable, prune_groups, rerender_stale, retry_failures},
+    preview::{ArticlePreview, PreviewFrontMatter, PreviewSource, preview_article},
+    progress::{EntryOutcome, EntryProgress, ProgressSender},
+    report::{
+        CommitRange, DiffItem, QuotaItem, ReportItem, RetentionItem, SlowRenderItem, SyncReport,
+        VisibilityItem,
+    },
+    timetravel::{
+        SnapshotArticle, TIMETRAVEL_MAX_ARTICLES, TIMETRAVEL_RENDER_CAP, snapshot_articles,
+    },
 };
@@ -0,0 +1,200 @@
+use crate::{
+    error::Error,
+    git_client::{ArchiveRemoval, GitClient, is_archive_name},
+};
+
+/// 拒绝删除归档的原因，不删除任何引用
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ArchiveRefused {
+    /// 归档名不是 `YYYY-Qn`
+    #[error("`{0}` is not an archive name like `2024-Q1`")]
+    InvalidName(String),
+    /// 要删除最近的归档但没有指定 force
+    #[error("`{0}` is the most recent archive, retry with force to delete it")]
+    Latest(String),
+}
+
+/// 删除一个归档的分支和标签
+///
+/// 归档名必须恰好为 `YYYY-Qn`，最近的归档只有 `force` 时才删除，其他名称的归档不计入。
+/// 只删除引用，不执行 `git gc`。归档接口不在数据库中保存归档信息，不需要清理数据。
+pub async fn remove_archive(
+    repo: &GitClient,
+    name: &str,
+    force: bool,
+) -> Result<ArchiveRemoval, Error> {
+    if !is_archive_name(name) {
+        return Err(ArchiveRefused::InvalidName(name.to_string()).into());
+    }
+    let archives = quarterly_archives(repo).await?;
+    if !force && archives.last().is_some_and(|latest| latest == name) {
+        return Err(ArchiveRefused::Latest(name.to_string()).into());
+    }
+
+    Ok(repo.delete_archive(name).await?)
+}
+
+/// 保留最近的 `keep` 个归档，从早到晚删除其余的归档
+///
+/// 规则与 [`remove_archive`] 相同：只处理名称为 `YYYY-Qn` 的归档，`keep` 为 0 时只有 `force` 才删除最近的归档。
+/// 返回删除的归档，从早到晚排列。
+pub async fn prune_archives(
+    repo: &GitClient,
+    keep: usize,
+    force: bool,
+) -> Result<Vec<ArchiveRemoval>, Error> {
+    let archives = quarterly_archives(repo).await?;
+    let count = archives.len().saturating_sub(keep);
+    if !force
+        && count == archives.len()
+        && let Some(latest) = archives.last()
+    {
+        return Err(ArchiveRefused::Latest(latest.to_owned()).into());
+    }
+
+    let mut removed = Vec::with_capacity(count);
+    for name in &archives[..count] {
+        removed.push(repo.delete_archive(name).await?);
+    }
+    Ok(removed)
+}
+
+/// 名称为 `YYYY-Qn` 的归档，从早到晚排列
+async fn quarterly_archives(repo: &GitClient) -> Result<Vec<String>, Error> {
+    let mut archives = repo.archives().await?;
+    archives.retain(|name| is_archive_name(name));
+    Ok(archives)
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Oid, Repository, Signature};
+
+    use crate::git_client::GitError;
+
+    use super::*;
+
+    /// 在 `refname` 上提交一个空改动，返回新 commit 的 Oid
+    fn commit(repo: &Repository, refname: &str, parent: Option<Oid>, message: &str) -> Oid {
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parent = parent.map(|oid| repo.find_commit(oid).unwrap());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        repo.commit(Some(refname), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    /// 主分支上一个提交，三个归档分支各有一个独有的提交，最近的归档同时有同名的附注标签，
+    /// 另有一个名称不是 `YYYY-Qn` 的归档分支
+    fn fixture() -> (tempfile::TempDir, GitClient) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+
+        let main = commit(&repo, "refs/heads/main", None, "main");
+        for name in ["2024-Q1", "2024-Q2", "2024-Q3", "misc"] {
+            commit(
+                &repo,
+                &format!("refs/heads/archived/{name}"),
+                Some(main),
+                name,
+            );
+        }
+        let latest = repo
+            .find_reference("refs/heads/archived/2024-Q3")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        let sig = Signature::now("tester", "tester@example.com").unwrap();
+        repo.tag(
+            "archive/2024-Q3",
+            latest.as_object(),
+            &sig,
+            "archive",
+            false,
+        )
+        .unwrap();
+
+        let client = GitClient::open(dir.path()).unwrap();
+        (dir, client)
+    }
+
+    #[tokio::test]
+    async fn test_prune_keep() {
+        let (_dir, repo) = fixture();
+
+        // 归档数不超过保留数时不删除
+        assert!(prune_archives(&repo, 8, false).await.unwrap().is_empty());
+        assert!(prune_archives(&repo, 3, false).await.unwrap().is_empty());
+
+        let removed = prune_archives(&repo, 1, false).await.unwrap();
+        assert_eq!(
+            removed,
+            ["2024-Q1", "2024-Q2"].map(|name| ArchiveRemoval {
+                name: name.to_string(),
+                branch: true,
+                tag: false,
+                unreachable_commits: 1,
+            })
+        );
+        assert_eq!(repo.archives().await.unwrap(), ["misc", "2024-Q3"]);
+    }
+
+    #[tokio::test]
+    async fn test_prune_force() {
+        let (_dir, repo) = fixture();
+
+        // 不保留任何归档时需要 force
+        let err = prune_archives(&repo, 0, false).await.unwrap_err();
+        assert!(
+            matches!(&err, Error::ArchiveRefused(ArchiveRefused::Latest(name)) if name == "2024-Q3"),
+            "{err}"
+        );
+        assert_eq!(repo.archives().await.unwrap().len(), 4);
+
+        let removed = prune_archives(&repo, 0, true).await.unwrap();
+        assert_eq!(removed.len(), 3);
+        // 分支和标签都被删除，两者指向同一个提交
+        assert_eq!(
+            removed[2],
+            ArchiveRemoval {
+                name: "2024-Q3".to_string(),
+                branch: true,
+                tag: true,
+                unreachable_commits: 1,
+            }
+        );
+        assert_eq!(repo.archives().await.unwrap(), ["misc"]);
+    }
+
+    #[tokio::test]
+    async fn test_remove_archive() {
+        let (_dir, repo) = fixture();
+
+        for name in ["misc", "main", "../main", "2024-Q1/x"] {
+            let err = remove_archive(&repo, name, true).await.unwrap_err();
+            assert!(
+                matches!(err, Error::ArchiveRefused(ArchiveRefused::InvalidName(_))),
+                "{name}: {err}"
+            );
+        }
+
+        let err = remove_archive(&repo, "2024-Q3", false).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ArchiveRefused(ArchiveRefused::Latest(_))
+        ));
+
+        let removed = remove_archive(&repo, "2024-Q2", false).await.unwrap();
+        assert_eq!(removed.unreachable_commits, 1);
+        let err = remove_archive(&repo, "2024-Q2", false).await.unwrap_err();
+        assert!(matches!(err, Error::Git(GitError::RefNotFound(_))));
+
+        let removed = remove_archive(&repo, "2024-Q3", true).await.unwrap();
+        assert!(removed.branch && removed.tag);
+        // 主分支上的提交仍可到达
+        assert_eq!(repo.resolve("main").await.unwrap().len(), 40);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use flate2::{Compression, write::GzEncoder};
+
+use crate::{
+    error::Error,
+    storage::{DumpCounts, Store, export_dump},
+};
+
+/// 默认保留的备份数
+pub const DEFAULT_BACKUP_KEEP: NonZeroUsize = NonZeroUsize::new(5).unwrap();
+
+/// 备份文件名的前缀
+const BACKUP_PREFIX: &str = "gitnote-backup-";
+
+/// 备份文件名的后缀，与 `gitnote-admin export` 写入 `.gz` 文件时的格式相同
+const BACKUP_SUFFIX: &str = ".jsonl.gz";
+
+/// 重建前的备份配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupOptions {
+    /// 备份目录，不存在时创建
+    pub dir: PathBuf,
+    /// 保留最近的备份数，更早的备份在写入新备份后删除
+    pub keep: NonZeroUsize,
+}
+
+impl BackupOptions {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            keep: DEFAULT_BACKUP_KEEP,
+        }
+    }
+}
+
+/// 写入备份失败，重建不会执行
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "failed to write backup to {}: {reason}; rebuild aborted, retry with `?force=true` to rebuild without a backup",
+    .dir.display()
+)]
+pub struct BackupError {
+    pub dir: PathBuf,
+    pub reason: String,
+}
+
+/// 写入完成的备份
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// 备份文件名，位于 [`BackupOptions::dir`] 中
+    pub file: String,
+    pub counts: DumpCounts,
+}
+
+/// 将 `store` 中的组、文章和渲染结果写入带时间戳的 gzip 压缩文件，格式见 [`export_dump`]
+///
+/// 逐页读取并写入，先写入临时文件，完整写入后再改名，失败时删除临时文件。
+/// 写入后按文件名删除超出 [`BackupOptions::keep`] 的旧备份，删除失败只记录警告。
+/// 在重建的事务开始之前调用，不占用写事务。
+pub async fn write_backup<S: Store>(
+    store: &S,
+    options: &BackupOptions,
+) -> Result<Backup, BackupError> {
+    let fail = |reason: String| BackupError {
+        dir: options.dir.clone(),
+        reason,
+    };
+    fs::create_dir_all(&options.dir).map_err(|e| fail(e.to_string()))?;
+
+    let file = format!(
+        "{BACKUP_PREFIX}{}{BACKUP_SUFFIX}",
+        Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+    );
+    let path = options.dir.join(&file);
+    let partial = options.dir.join(format!(".{file}.partial"));
+    let counts = match dump_to(store, &partial).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(fail(e.to_string()));
+        }
+    };
+    fs::rename(&partial, &path).map_err(|e| fail(e.to_string()))?;
+
+    if let Err(e) = prune_backups(&options.dir, options.keep.get()) {
+        tracing::warn!(error = %e, dir = %options.dir.display(), "failed to prune old backups");
+    }
+    Ok(Backup { file, counts })
+}
+
+async fn dump_to<S: Store>(store: &S, path: &Path) -> Result<DumpCounts, Error> {
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    let counts = export_dump(store, &mut encoder).await?;
+    let mut out = encoder.finish()?;
+    out.flush()?;
+    out.get_ref().sync_all()?;
+    Ok(counts)
+}
+
+/// `dir` 中的备份文件名，从旧到新排列
+pub fn list_backups(dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut files = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_SUFFIX))
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// 删除 `dir` 中最近 `keep` 个之外的备份
+fn prune_backups(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let files = list_backups(dir)?;
+    for file in &files[..files.len().saturating_sub(keep)] {
+        fs::remove_file(dir.join(file))?;
+        tracing::info!(file, "old backup removed");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use crate::{
+        content::Group,
+        storage::{MemoryStorage, read_dump},
+    };
+
+    use super::*;
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let mut store = storage.store();
+        store.upsert_group(
+            &Group::new(
+                "notes/.group.yaml",
+                "name: Notes\npublic: true\n".to_string(),
+            )
+            .unwrap(),
+        );
+        store.commit().await.unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_backup_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage().await;
+        let options = BackupOptions {
+            dir: dir.path().join("backups"),
+            keep: NonZeroUsize::new(2).unwrap(),
+        };
+        // 其他文件不计入也不删除
+        fs::create_dir_all(&options.dir).unwrap();
+        fs::write(options.dir.join("notes.txt"), "keep me").unwrap();
+
+        let mut written = vec![];
+        for _ in 0..3 {
+            let backup = write_backup(&storage.store(), &options).await.unwrap();
+            assert_eq!(backup.counts.groups, 1);
+            written.push(backup.file);
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert!(written.is_sorted());
+        assert_eq!(list_backups(&options.dir).unwrap(), written[1..]);
+        assert!(options.dir.join("notes.txt").exists());
+
+        // 备份可以按导出格式读取
+        let mut data = String::new();
+        GzDecoder::new(File::open(options.dir.join(&written[2])).unwrap())
+            .read_to_string(&mut data)
+            .unwrap();
+        assert_eq!(read_dump(data.as_bytes()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backup_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("file");
+        fs::write(&blocker, "").unwrap();
+
+        // 备份目录的位置已有同名文件
+        let err = write_backup(&storage().await.store(), &BackupOptions::new(&blocker))
+            .await
+            .unwrap_err();
+        assert_eq!(err.dir, blocker);
+        assert!(err.to_string().contains("force=true"), "{err}");
+    }
+}
@@ -0,0 +1,292 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::OwnedMutexGuard;
+use utoipa::ToSchema;
+
+/// 等待执行的增量同步上限
+pub const MAX_QUEUED_SYNCS: usize = 10;
+
+/// 拒绝请求时通过 `Retry-After` 建议客户端等待的秒数
+pub const RETRY_AFTER_SECS: u64 = 30;
+
+/// 同步请求无法被接受
+#[derive(Debug, thiserror::Error)]
+pub enum SyncBusy {
+    /// 已有重建正在执行或等待执行
+    #[error("a rebuild started at {started_at} is still running")]
+    Rebuilding { started_at: DateTime<Utc> },
+
+    /// 等待执行的增量同步已达 [`MAX_QUEUED_SYNCS`]
+    #[error("sync queue is full ({MAX_QUEUED_SYNCS} pending)")]
+    QueueFull,
+}
+
+/// 协调器当前状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncState {
+    Idle,
+    Syncing,
+    Rebuilding,
+}
+
+/// 同步状态。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    /// 重建开始时间，Unix 毫秒时间戳，没有重建时为 `null`
+    #[schema(example = 1717171200000_i64)]
+    pub rebuild_started_at: Option<i64>,
+    /// 等待执行的增量同步数量
+    pub queued: usize,
+    /// 开启防抖时等待合并执行的增量同步，见 [`SyncDebouncer`](super::SyncDebouncer)
+    pub pending: Option<super::PendingSync>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// 正在执行或等待执行的重建的请求时间
+    rebuild: Option<DateTime<Utc>>,
+    /// 正在执行增量同步
+    syncing: bool,
+    /// 等待执行的增量同步数量
+    queued: usize,
+}
+
+/// 同步协调器
+///
+/// 所有同步按到达顺序依次执行：
+///
+/// - 同一时间最多一个重建，重建期间再次请求重建返回 [`SyncBusy::Rebuilding`]
+/// - 增量同步排队等待前面的任务完成，最多 [`MAX_QUEUED_SYNCS`] 个，超出时返回 [`SyncBusy::QueueFull`]
+///
+/// 许可或等待中的 future 被丢弃（同步失败或请求被取消）时，占用的位置随之释放。
+#[derive(Debug, Clone, Default)]
+pub struct SyncCoordinator {
+    state: Arc<Mutex<State>>,
+    run: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl SyncCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 申请执行重建，等待前面的任务完成后返回许可
+    pub async fn rebuild(&self) -> Result<SyncPermit, SyncBusy> {
+        let release = {
+            let mut state = self.lock();
+            if let Some(started_at) = state.rebuild {
+                return Err(SyncBusy::Rebuilding { started_at });
+            }
+            state.rebuild = Some(Utc::now());
+            Release::new(&self.state, Slot::Rebuild)
+        };
+
+        let run = self.run.clone().lock_owned().await;
+        Ok(SyncPermit {
+            _release: release,
+            _run: run,
+        })
+    }
+
+    /// 申请执行增量同步，排队等待前面的任务完成后返回许可
+    pub async fn sync(&self) -> Result<SyncPermit, SyncBusy> {
+        let queued = {
+            let mut state = self.lock();
+            if state.queued >= MAX_QUEUED_SYNCS {
+                return Err(SyncBusy::QueueFull);
+            }
+            state.queued += 1;
+            Release::new(&self.state, Slot::Queued)
+        };
+
+        let run = self.run.clone().lock_owned().await;
+        drop(queued);
+        self.lock().syncing = true;
+
+        Ok(SyncPermit {
+            _release: Release::new(&self.state, Slot::Syncing),
+            _run: run,
+        })
+    }
+
+    /// 获取当前状态
+    pub fn status(&self) -> SyncStatus {
+        let state = self.lock();
+        let current = match (state.rebuild, state.syncing) {
+            (Some(_), _) => SyncState::Rebuilding,
+            (None, true) => SyncState::Syncing,
+            (None, false) => SyncState::Idle,
+        };
+
+        SyncStatus {
+            state: current,
+            rebuild_started_at: state.rebuild.map(|t| t.timestamp_millis()),
+            queued: state.queued,
+            pending: None,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 执行同步的许可，丢弃后下一个任务开始执行
+#[must_use]
+pub struct SyncPermit {
+    _release: Release,
+    _run: OwnedMutexGuard<()>,
+}
+
+/// 释放时需要撤销的状态
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Rebuild,
+    Queued,
+    Syncing,
+}
+
+/// 丢弃时撤销 [`Slot`] 对应的状态
+struct Release {
+    state: Arc<Mutex<State>>,
+    slot: Slot,
+}
+
+impl Release {
+    fn new(state: &Arc<Mutex<State>>, slot: Slot) -> Self {
+        Self {
+            state: state.clone(),
+            slot,
+        }
+    }
+}
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match self.slot {
+            Slot::Rebuild => state.rebuild = None,
+            Slot::Queued => state.queued -= 1,
+            Slot::Syncing => state.syncing = false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_second_rebuild_conflicts() {
+        let coordinator = SyncCoordinator::new();
+
+        let permit = coordinator.rebuild().await.unwrap();
+        let status = coordinator.status();
+        assert_eq!(status.state, SyncState::Rebuilding);
+
+        let Err(SyncBusy::Rebuilding { started_at }) = coordinator.rebuild().await else {
+            panic!("second rebuild should be rejected");
+        };
+        assert_eq!(
+            status.rebuild_started_at,
+            Some(started_at.timestamp_millis())
+        );
+
+        drop(permit);
+        assert_eq!(coordinator.status().state, SyncState::Idle);
+        assert!(coordinator.rebuild().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_syncs_drain_in_order_after_rebuild() {
+        let coordinator = SyncCoordinator::new();
+        let applied = Arc::new(Mutex::new(Vec::new()));
+
+        let permit = coordinator.rebuild().await.unwrap();
+        let mut tasks = Vec::new();
+        for i in 0..3 {
+            let (coordinator, applied) = (coordinator.clone(), applied.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = coordinator.sync().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                applied.lock().unwrap().push(i);
+            }));
+            // 让任务进入等待队列，保证到达顺序
+            tokio::task::yield_now().await;
+        }
+
+        let status = coordinator.status();
+        assert_eq!(status.state, SyncState::Rebuilding);
+        assert_eq!(status.queued, 3);
+        assert!(applied.lock().unwrap().is_empty());
+
+        drop(permit);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*applied.lock().unwrap(), [0, 1, 2]);
+        let status = coordinator.status();
+        assert_eq!(status.state, SyncState::Idle);
+        assert_eq!(status.queued, 0);
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_bounded() {
+        let coordinator = SyncCoordinator::new();
+
+        let permit = coordinator.rebuild().await.unwrap();
+        let waiting = (0..MAX_QUEUED_SYNCS)
+            .map(|_| {
+                let coordinator = coordinator.clone();
+                tokio::spawn(async move { coordinator.sync().await.map(drop) })
+            })
+            .collect::<Vec<_>>();
+        while coordinator.status().queued < MAX_QUEUED_SYNCS {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(matches!(coordinator.sync().await, Err(SyncBusy::QueueFull)));
+
+        drop(permit);
+        for task in waiting {
+            task.await.unwrap().unwrap();
+        }
+        assert!(coordinator.sync().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_requests_release_slot() {
+        let coordinator = SyncCoordinator::new();
+
+        let sync = coordinator.sync().await.unwrap();
+        assert_eq!(coordinator.status().state, SyncState::Syncing);
+
+        // 等待中的重建和增量同步被取消
+        let rebuild = tokio::time::timeout(Duration::from_millis(10), coordinator.rebuild());
+        assert!(rebuild.await.is_err());
+        let queued = tokio::time::timeout(Duration::from_millis(10), coordinator.sync());
+        assert!(queued.await.is_err());
+
+        let status = coordinator.status();
+        assert_eq!(status.state, SyncState::Syncing);
+        assert_eq!(status.queued, 0);
+
+        // 执行中的重建失败
+        drop(sync);
+        let failed = async {
+            let _permit = coordinator.rebuild().await?;
+            Err::<(), _>(SyncBusy::QueueFull)
+        };
+        assert!(failed.await.is_err());
+
+        assert_eq!(coordinator.status().state, SyncState::Idle);
+        assert!(coordinator.rebuild().await.is_ok());
+    }
+}
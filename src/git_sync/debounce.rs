@@ -0,0 +1,250 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use chrono::{TimeDelta, Utc};
+use serde::Serialize;
+use tokio::time::Instant;
+use utoipa::ToSchema;
+
+/// 等待合并执行的增量同步
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PendingSync {
+    /// 最早一次推送的 `before`
+    pub before: String,
+    /// 最近一次推送的 `after`
+    pub after: String,
+    /// 已合并的推送次数
+    pub pushes: usize,
+    /// 预计开始执行的时间，Unix 毫秒时间戳，再次推送时顺延
+    #[schema(example = 1717171200000_i64)]
+    pub run_at: i64,
+}
+
+/// 静默期结束后取出的合并范围
+#[derive(Debug)]
+pub struct DebouncedSync {
+    pub range: PendingSync,
+    epoch: u64,
+}
+
+#[derive(Debug)]
+struct Batch {
+    range: PendingSync,
+    deadline: Instant,
+    epoch: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    batch: Option<Batch>,
+    /// 每次 [`SyncDebouncer::cancel`] 加一，用于识别已被重建取代的范围
+    epoch: u64,
+}
+
+/// 增量同步防抖
+///
+/// 推送只记录 commit 范围，距最近一次推送超过静默期后，
+/// 由 [`SyncDebouncer::wait`] 取出合并后的范围（最早的 `before`、最近的 `after`）执行一次增量同步。
+/// 取出后再到达的推送组成新的范围，按取出顺序经由 [`SyncCoordinator`](super::SyncCoordinator) 依次执行。
+///
+/// 重建覆盖了此前的全部变更，请求重建时调用 [`SyncDebouncer::cancel`] 丢弃等待中的范围。
+#[derive(Debug, Clone)]
+pub struct SyncDebouncer {
+    quiet: Duration,
+    state: Arc<Mutex<State>>,
+}
+
+impl SyncDebouncer {
+    pub fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            state: Default::default(),
+        }
+    }
+
+    /// 获取静默期
+    pub fn quiet(&self) -> Duration {
+        self.quiet
+    }
+
+    /// 记录一次推送，返回合并后的范围，以及是否开始了新的范围
+    ///
+    /// 开始新的范围时调用方需要启动一个等待 [`SyncDebouncer::wait`] 的任务。
+    pub fn push(&self, before: &str, after: &str) -> (PendingSync, bool) {
+        let deadline = Instant::now() + self.quiet;
+        let run_at =
+            (Utc::now() + TimeDelta::from_std(self.quiet).unwrap_or_default()).timestamp_millis();
+
+        let mut state = self.lock();
+        let epoch = state.epoch;
+        match &mut state.batch {
+            Some(batch) => {
+                batch.range.after = after.to_string();
+                batch.range.pushes += 1;
+                batch.range.run_at = run_at;
+                batch.deadline = deadline;
+                (batch.range.clone(), false)
+            }
+            batch @ None => {
+                let range = PendingSync {
+                    before: before.to_string(),
+                    after: after.to_string(),
+                    pushes: 1,
+                    run_at,
+                };
+                *batch = Some(Batch {
+                    range: range.clone(),
+                    deadline,
+                    epoch,
+                });
+                (range, true)
+            }
+        }
+    }
+
+    /// 等待距最近一次推送超过静默期后取出合并的范围
+    ///
+    /// 没有等待中的范围或等待期间被取消时返回 `None`。
+    pub async fn wait(&self) -> Option<DebouncedSync> {
+        loop {
+            let deadline = {
+                let mut state = self.lock();
+                let deadline = state.batch.as_ref()?.deadline;
+                if deadline <= Instant::now() {
+                    let batch = state.batch.take()?;
+                    return Some(DebouncedSync {
+                        range: batch.range,
+                        epoch: batch.epoch,
+                    });
+                }
+                deadline
+            };
+            tokio::time::sleep_until(deadline).await;
+        }
+    }
+
+    /// 丢弃等待中的范围，此前取出的范围也不再执行，返回被丢弃的范围
+    pub fn cancel(&self) -> Option<PendingSync> {
+        let mut state = self.lock();
+        state.epoch += 1;
+        state.batch.take().map(|batch| batch.range)
+    }
+
+    /// 取出的范围是否仍需执行，取出后调用过 [`SyncDebouncer::cancel`] 时返回 `false`
+    pub fn is_current(&self, sync: &DebouncedSync) -> bool {
+        self.lock().epoch == sync.epoch
+    }
+
+    /// 获取等待中的范围
+    pub fn pending(&self) -> Option<PendingSync> {
+        self.lock().batch.as_ref().map(|batch| batch.range.clone())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIET: Duration = Duration::from_secs(10);
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pushes_coalesce_after_quiet_period() {
+        let debouncer = SyncDebouncer::new(QUIET);
+        let started = Instant::now();
+
+        let (_, first) = debouncer.push("a", "b");
+        assert!(first);
+        let waiter = tokio::spawn({
+            let debouncer = debouncer.clone();
+            async move { (debouncer.wait().await, Instant::now()) }
+        });
+
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        let (pending, first) = debouncer.push("b", "c");
+        assert!(!first);
+        assert_eq!(
+            (pending.before.as_str(), pending.after.as_str()),
+            ("a", "c")
+        );
+
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        debouncer.push("c", "d");
+        assert_eq!(debouncer.pending().unwrap().pushes, 3);
+
+        // 静默期从最近一次推送开始计算
+        let (sync, finished) = waiter.await.unwrap();
+        let sync = sync.unwrap();
+        let waited = finished - started;
+        assert!(waited >= Duration::from_secs(22) && waited < Duration::from_secs(23));
+        assert_eq!(
+            sync.range,
+            PendingSync {
+                before: "a".to_string(),
+                after: "d".to_string(),
+                pushes: 3,
+                run_at: sync.range.run_at,
+            }
+        );
+        assert!(debouncer.is_current(&sync));
+        assert!(debouncer.pending().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_later_pushes_form_next_range() {
+        let debouncer = SyncDebouncer::new(QUIET);
+
+        debouncer.push("a", "b");
+        let first = debouncer.wait().await.unwrap();
+
+        // 取出后到达的推送开始新的范围
+        let (_, started) = debouncer.push("b", "c");
+        assert!(started);
+        debouncer.push("c", "d");
+        let second = debouncer.wait().await.unwrap();
+
+        let ranges = [first, second].map(|s| (s.range.before, s.range.after, s.range.pushes));
+        assert_eq!(
+            ranges,
+            [
+                ("a".to_string(), "b".to_string(), 1),
+                ("b".to_string(), "d".to_string(), 2),
+            ]
+        );
+        assert!(debouncer.wait().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancel_drops_pending_and_taken_ranges() {
+        let debouncer = SyncDebouncer::new(QUIET);
+
+        debouncer.push("a", "b");
+        let taken = debouncer.wait().await.unwrap();
+
+        debouncer.push("b", "c");
+        let waiter = tokio::spawn({
+            let debouncer = debouncer.clone();
+            async move { debouncer.wait().await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let cancelled = debouncer.cancel().unwrap();
+        assert_eq!(
+            (cancelled.before.as_str(), cancelled.after.as_str()),
+            ("b", "c")
+        );
+        assert!(waiter.await.unwrap().is_none());
+        assert!(!debouncer.is_current(&taken));
+
+        // 取消后的推送不受影响
+        debouncer.push("c", "d");
+        let next = debouncer.wait().await.unwrap();
+        assert!(debouncer.is_current(&next));
+        assert_eq!(next.range.before, "c");
+    }
+}
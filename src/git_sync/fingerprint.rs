@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    content::{ArticleBuilder, FrontMatter, Group, expand_includes},
+    error::Error,
+    git_client::{ChangeKind, FileKind, GitClient, blob_oid},
+    storage::{ArticleFingerprint, GroupFingerprint, Store},
+};
+
+use super::{ContentLimits, Verdict};
+
+/// 计算内容指纹
+///
+/// 组按目录路径、文章按所在组和 slug 排序后，每行一个 JSON 数组，对全部行计算 SHA-256，返回十六进制字符串。
+/// 结果与输入顺序无关，只要内容不变，重建前后相同。
+pub fn content_fingerprint(
+    mut groups: Vec<GroupFingerprint>,
+    mut articles: Vec<ArticleFingerprint>,
+) -> String {
+    groups.sort_by(|a, b| a.id.cmp(&b.id));
+    articles.sort_by(|a, b| (&a.group_id, &a.slug).cmp(&(&b.group_id, &b.slug)));
+
+    let mut hasher = Sha256::new();
+    for group in &groups {
+        write_line(&mut hasher, "group", group);
+    }
+    for article in &articles {
+        write_line(&mut hasher, "article", article);
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// 以 `["<kind>", <row>]` 形式的一行 JSON 写入
+fn write_line(hasher: &mut Sha256, kind: &str, row: &impl Serialize) {
+    hasher.update(serde_json::to_vec(&(kind, row)).unwrap_or_default());
+    hasher.update(b"\n");
+}
+
+/// 按存储中的组和公开文章计算内容指纹，与 `commit` 一同记录，返回指纹
+///
+/// 在同步提交后调用，见 [`content_fingerprint`]。
+pub async fn record_fingerprint<S: Store>(mut storage: S, commit: &str) -> Result<String, Error> {
+    let (groups, articles) = storage.fingerprint_rows().await?;
+    let fingerprint = content_fingerprint(groups, articles);
+
+    storage.record_sync_state(commit, &fingerprint);
+    storage.commit().await?;
+
+    Ok(fingerprint)
+}
+
+/// 只读取 `commit` 的快照计算内容指纹，不访问数据库
+///
+/// 按重建时的规则确定每篇文章的所属组、slug、内容 oid 和更新时间，但不渲染。
+/// 与同步后记录的指纹相同时说明数据库与仓库一致；处理失败或被 [`ContentLimits`] 跳过的文章不计入。
+pub async fn repo_fingerprint(
+    repo: &GitClient,
+    commit: &str,
+    limits: &ContentLimits,
+) -> Result<String, Error> {
+    let entries = repo
+        .snapshot(commit)
+        .await?
+        .into_iter()
+        .filter(|e| e.change_kind() != ChangeKind::Deleted && e.skip_reason().is_none())
+        .collect::<Vec<_>>();
+
+    let mut groups = BTreeMap::new();
+    for entry in entries.iter().filter(|e| e.file_kind() == FileKind::Group) {
+        let group = Group::new(entry.path(), repo.load_file(entry.id()).await?)?;
+        groups.insert(group.id.clone(), group);
+    }
+    let strategies = groups
+        .iter()
+        .map(|(id, g)| (id.to_owned(), g.slug_strategy))
+        .collect::<HashMap<_, _>>();
+
+    // 同一 slug 以最后处理的文件为准，同时记录文章级的公开设置
+    let mut articles = BTreeMap::<String, (ArticleFingerprint, Option<bool>)>::new();
+    for entry in entries
+        .iter()
+        .filter(|e| e.file_kind() == FileKind::Markdown)
+    {
+        let bytes = repo.load_bytes(entry.id()).await?;
+        if let Verdict::Skip(_) = limits.check(&bytes) {
+            continue;
+        }
+
+        let content = String::from_utf8_lossy(&bytes).into_owned();
+        let builder = ArticleBuilder::with_strategies(entry.path(), &strategies)
+            .timezone(repo.timezone())
+            .updated_at(entry.timestamp());
+        if !FrontMatter::is_present(&content) {
+            if articles
+                .get(builder.slug())
+                .is_some_and(|(a, _)| &a.group_id == builder.group())
+            {
+                articles.remove(builder.slug());
+            }
+            continue;
+        }
+
+        let file = entry.path().to_path_buf();
+        let Ok(expanded) = repo
+            .with_tree(commit, move |load| expand_includes(&file, &content, load))
+            .await?
+        else {
+            continue;
+        };
+        let encrypted = groups.get(builder.group()).is_some_and(|g| g.encrypted);
+        let content_oid = (!encrypted).then(|| blob_oid(expanded.content.as_bytes()));
+        let Ok(article) = builder
+            .content(expanded.content)
+            .build_with_rendered(String::new(), String::new())
+        else {
+            continue;
+        };
+
+        articles.insert(
+            article.slug.clone(),
+            (
+                ArticleFingerprint {
+                    group_id: article.group,
+                    slug: article.slug,
+                    content_oid,
+                    updated_at: article.updated_at.timestamp_millis(),
+                },
+                article.frontmatter.public,
+            ),
+        );
+    }
+
+    let articles = articles
+        .into_values()
+        .filter(|(a, public)| {
+            groups
+                .get(&a.group_id)
+                .is_some_and(|g| public.unwrap_or(g.public))
+        })
+        .map(|(a, _)| a)
+        .collect();
+    let groups = groups.into_values().map(group_row).collect();
+
+    Ok(content_fingerprint(groups, articles))
+}
+
+/// 组配置写入数据库后对应的行
+fn group_row(group: Group) -> GroupFingerprint {
+    GroupFingerprint {
+        slug: group.public_id().to_owned(),
+        name: group.name,
+        public: group.public,
+        encrypted: group.encrypted,
+        slug_strategy: group.slug_strategy.as_str().to_owned(),
+        author_id: group.author.as_ref().map(|a| a.id.to_owned()),
+        author_name: group.author.map(|a| a.name),
+        category_id: group.category.as_ref().map(|c| c.id.to_owned()),
+        category_name: group.category.map(|c| c.name),
+        nav_order: group.nav_order,
+        hidden: group.hidden,
+        retain_max_articles: group.retain.and_then(|r| r.max_articles).map(|n| n as i32),
+        retain_max_age_days: group.retain.and_then(|r| r.max_age_days).map(|n| n as i32),
+        license: group
+            .license
+            .as_ref()
+            .and_then(|l| serde_json::to_value(l).ok()),
+        archived: group.archived,
+        default_tags: group.default_tags,
+        kind: serde_json::to_value(&group.kind).unwrap_or_default(),
+        id: group.id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        content::Renderer,
+        git_sync::{PersistMode, Persistable},
+        storage::{MemoryStorage, Querier},
+    };
+
+    use super::*;
+
+    const LAST_COMMIT_OID: &str = "4db775450dee399c328935eb03fd4fcc6c60e333";
+
+    struct FakeRenderer;
+
+    impl Renderer for FakeRenderer {
+        fn render<T: AsRef<str>>(
+            &self,
+            content: T,
+        ) -> impl std::future::Future<Output = crate::error::Result<String>> {
+            let content = content.as_ref().to_string();
+            async move { Ok(format!("<rendered>{content}</rendered>")) }
+        }
+
+        fn identity(&self) -> String {
+            "fake@1".to_string()
+        }
+    }
+
+    fn group(id: &str) -> GroupFingerprint {
+        GroupFingerprint {
+            id: id.into(),
+            slug: id.to_string(),
+            name: id.to_string(),
+            public: true,
+            encrypted: false,
+            slug_strategy: "stem".to_string(),
+            author_id: None,
+            author_name: None,
+            archived: false,
+            default_tags: vec![],
+            kind: serde_json::json!({"type": "normal"}),
+            category_id: None,
+            category_name: None,
+            nav_order: None,
+            hidden: false,
+            retain_max_articles: None,
+            retain_max_age_days: None,
+            license: None,
+        }
+    }
+
+    fn article(group_id: &str, slug: &str, oid: &str) -> ArticleFingerprint {
+        ArticleFingerprint {
+            group_id: group_id.into(),
+            slug: slug.to_string(),
+            content_oid: Some(oid.to_string()),
+            updated_at: 1_704_067_200_000,
+        }
+    }
+
+    fn rows() -> (Vec<GroupFingerprint>, Vec<ArticleFingerprint>) {
+        (
+            vec![group("notes"), group("rust")],
+            vec![
+                article("notes", "a", "1"),
+                article("notes", "b", "2"),
+                article("rust", "c", "3"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_fingerprint_stable() {
+        let (groups, articles) = rows();
+        let expected = content_fingerprint(groups.clone(), articles.clone());
+        assert_eq!(expected.len(), 64);
+
+        // 与行的顺序无关
+        let (mut reversed_groups, mut reversed_articles) = (groups, articles);
+        reversed_groups.reverse();
+        reversed_articles.reverse();
+        assert_eq!(
+            content_fingerprint(reversed_groups, reversed_articles),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_sensitive_to_single_change() {
+        let (groups, articles) = rows();
+        let expected = content_fingerprint(groups.clone(), articles.clone());
+
+        type Change = fn(&mut Vec<GroupFingerprint>, &mut Vec<ArticleFingerprint>);
+        let changes: [Change; 5] = [
+            |_, a| a[1].content_oid = Some("4".to_string()),
+            |_, a| a[1].updated_at += 1,
+            |_, a| a[1].slug = "renamed".to_string(),
+            |_, a| {
+                a.pop();
+            },
+            |g, _| g[0].default_tags.push("new".to_string()),
+        ];
+        for change in changes {
+            let (mut groups, mut articles) = (groups.clone(), articles.clone());
+            change(&mut groups, &mut articles);
+            assert_ne!(content_fingerprint(groups, articles), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repo_fingerprint_matches_store() {
+        let repo = GitClient::open(crate::REPO_PATH).unwrap();
+        let limits = ContentLimits::default();
+        let memory = MemoryStorage::new();
+
+        let offline = repo_fingerprint(&repo, LAST_COMMIT_OID, &limits)
+            .await
+            .unwrap();
+
+        // 重建前后指纹相同，与只读取仓库计算的结果一致
+        for _ in 0..2 {
+            repo.snapshot(LAST_COMMIT_OID)
+                .await
+                .unwrap()
+                .persist(
+                    memory.store(),
+                    &FakeRenderer,
+                    &repo,
+                    LAST_COMMIT_OID,
+                    PersistMode::ResetAll,
+                    &limits,
+                )
+                .await
+                .unwrap();
+            let stored = record_fingerprint(memory.store(), LAST_COMMIT_OID)
+                .await
+                .unwrap();
+            assert_eq!(stored, offline);
+        }
+        assert_eq!(memory.public_slugs().await.unwrap(), ["markdown-test"]);
+    }
+}
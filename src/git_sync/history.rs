@@ -0,0 +1,228 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::{CommitRange, PushKind, SyncReport};
+
+/// 默认保留的同步记录数
+pub const DEFAULT_SYNC_LOG_ENTRIES: usize = 50;
+
+/// 每条记录保存的摘要或错误信息的最大字节数，超出部分截断
+pub const MAX_SYNC_LOG_TEXT_BYTES: usize = 4 * 1024;
+
+/// 同步尝试的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOutcome {
+    /// 全部变更已写入
+    Succeeded,
+    /// 部分文件处理失败，其余变更已写入
+    Partial,
+    /// 同步失败或被拒绝，没有变更写入
+    Failed,
+}
+
+/// 一次同步尝试的记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncLogEntry {
+    /// 结束时间，Unix 毫秒时间戳
+    #[schema(example = 1717171200000_i64)]
+    pub finished_at: i64,
+    pub push_kind: PushKind,
+    pub range: CommitRange,
+    pub outcome: SyncOutcome,
+    /// 从收到推送到结束的耗时，包括排队等待的时间
+    pub duration_ms: u64,
+    /// 成功时为文本摘要，失败时为错误信息
+    pub text: String,
+    /// `text` 是否超过 [`MAX_SYNC_LOG_TEXT_BYTES`] 被截断
+    pub truncated: bool,
+    /// 触发同步的请求 id，防抖合并执行的同步为 `null`
+    pub request_id: Option<String>,
+}
+
+impl SyncLogEntry {
+    /// 由同步报告和文本摘要生成记录，有文件处理失败时为 [`SyncOutcome::Partial`]
+    pub fn finished(report: &SyncReport, summary: &str) -> Self {
+        let outcome = if report.failed.is_empty() {
+            SyncOutcome::Succeeded
+        } else {
+            SyncOutcome::Partial
+        };
+        let (text, truncated) = truncate(summary);
+
+        Self {
+            finished_at: Utc::now().timestamp_millis(),
+            push_kind: report.push_kind.unwrap_or(PushKind::Sync),
+            range: report.range.clone().unwrap_or_default(),
+            outcome,
+            duration_ms: report.duration_ms,
+            text,
+            truncated,
+            request_id: report.request_id.clone(),
+        }
+    }
+
+    /// 生成失败的记录
+    pub fn failed(
+        push_kind: PushKind,
+        range: CommitRange,
+        elapsed: Duration,
+        error: impl Display,
+        request_id: Option<String>,
+    ) -> Self {
+        let (text, truncated) = truncate(&error.to_string());
+
+        Self {
+            finished_at: Utc::now().timestamp_millis(),
+            push_kind,
+            range,
+            outcome: SyncOutcome::Failed,
+            duration_ms: elapsed.as_millis() as u64,
+            text,
+            truncated,
+            request_id,
+        }
+    }
+}
+
+/// 最近的同步记录
+///
+/// 只保存在内存中，最多保留 `capacity` 条，超出时丢弃最早的记录，重启后清空。
+/// 克隆的实例共享同一份记录。
+#[derive(Debug, Clone)]
+pub struct SyncLog {
+    capacity: usize,
+    entries: Arc<Mutex<VecDeque<SyncLogEntry>>>,
+}
+
+impl Default for SyncLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYNC_LOG_ENTRIES)
+    }
+}
+
+impl SyncLog {
+    /// 创建最多保留 `capacity` 条记录的日志，为 0 时不记录
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// 添加一条记录，已满时丢弃最早的记录
+    pub fn record(&self, entry: SyncLogEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// 按时间倒序返回记录，`failed_only` 为 `true` 时只返回未完全成功的记录
+    pub fn entries(&self, failed_only: bool) -> Vec<SyncLogEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|e| !failed_only || e.outcome != SyncOutcome::Succeeded)
+            .cloned()
+            .collect()
+    }
+}
+
+/// 截断超过 [`MAX_SYNC_LOG_TEXT_BYTES`] 的文本，在字符边界处截断并附加说明
+fn truncate(text: &str) -> (String, bool) {
+    if text.len() <= MAX_SYNC_LOG_TEXT_BYTES {
+        return (text.to_string(), false);
+    }
+
+    let mut end = MAX_SYNC_LOG_TEXT_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let text = format!(
+        "{}\n… truncated, {} bytes in total",
+        &text[..end],
+        text.len()
+    );
+    (text, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(after: &str, outcome: SyncOutcome) -> SyncLogEntry {
+        SyncLogEntry {
+            finished_at: 0,
+            push_kind: PushKind::Sync,
+            range: CommitRange {
+                before: "a".to_string(),
+                after: after.to_string(),
+            },
+            outcome,
+            duration_ms: 0,
+            text: String::new(),
+            truncated: false,
+            request_id: None,
+        }
+    }
+
+    fn afters(entries: &[SyncLogEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.range.after.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sync_log_evicts_oldest() {
+        let log = SyncLog::new(3);
+        log.record(entry("1", SyncOutcome::Succeeded));
+        log.record(entry("2", SyncOutcome::Failed));
+        log.record(entry("3", SyncOutcome::Succeeded));
+        assert_eq!(afters(&log.entries(false)), ["3", "2", "1"]);
+
+        log.record(entry("4", SyncOutcome::Partial));
+        log.record(entry("5", SyncOutcome::Succeeded));
+        assert_eq!(afters(&log.entries(false)), ["5", "4", "3"]);
+        // 未完全成功的记录包括部分失败
+        assert_eq!(afters(&log.entries(true)), ["4"]);
+
+        // 克隆的实例共享记录
+        log.clone().record(entry("6", SyncOutcome::Failed));
+        assert_eq!(afters(&log.entries(true)), ["6", "4"]);
+    }
+
+    #[test]
+    fn test_sync_log_disabled() {
+        let log = SyncLog::new(0);
+        log.record(entry("1", SyncOutcome::Failed));
+        assert!(log.entries(false).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_text() {
+        let (text, truncated) = truncate("short");
+        assert_eq!((text.as_str(), truncated), ("short", false));
+
+        // 多字节字符跨过上限时在字符边界截断
+        let long = "文".repeat(MAX_SYNC_LOG_TEXT_BYTES);
+        let (text, truncated) = truncate(&long);
+        assert!(truncated);
+        let (kept, note) = text.split_once('\n').unwrap();
+        assert!(kept.len() <= MAX_SYNC_LOG_TEXT_BYTES);
+        assert!(kept.chars().all(|c| c == '文'));
+        assert_eq!(note, format!("… truncated, {} bytes in total", long.len()));
+    }
+}
@@ -3,7 +3,8 @@
 /// - [`PushKind::Sync`]：同步主分支，如 `refs/heads/main`
 /// - [`PushKind::Rebuild`]：触发数据库重建命令 refs，如 `refs/tags/cmd/rebuild`
 /// - [`PushKind::Ignore`]：其他不关心的 refs（分支或 tag）
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum PushKind {
     Sync,
     Rebuild,
@@ -13,7 +14,7 @@ pub enum PushKind {
 /// Git 更新 hook payload，通常由 `update` hook 触发。
 ///
 /// 包含触发更新的 ref、变更前后的 commit、操作用户以及仓库信息。
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
 pub struct GitPushPayload {
     /// 触发更新的 ref 名称
     pub refname: String,
@@ -0,0 +1,150 @@
+/// 文章内容的大小与二进制检测阈值，元数据的检查方式，以及慢渲染的提示阈值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentLimits {
+    /// 超过该字节数时记录警告
+    pub soft_bytes: usize,
+    /// 超过该字节数时跳过
+    pub hard_bytes: usize,
+    /// 不可打印字符占比超过该值时视为二进制内容并跳过
+    pub max_non_printable_ratio: f64,
+    /// 组配置和 Front Matter 中出现未知字段时是否视为错误，否则记录警告
+    pub strict_metadata: bool,
+    /// 单篇文章渲染超过该毫秒数时在同步摘要中提示
+    pub slow_render_ms: u64,
+}
+
+impl Default for ContentLimits {
+    /// 软限制 512 KB，硬限制 2 MB，不可打印字符占比 10%，未知字段只记录警告，渲染超过 5 秒时提示
+    fn default() -> Self {
+        Self {
+            soft_bytes: 512 * 1024,
+            hard_bytes: 2 * 1024 * 1024,
+            max_non_printable_ratio: 0.1,
+            strict_metadata: false,
+            slow_render_ms: 5000,
+        }
+    }
+}
+
+/// 内容检查结果
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    /// 正常处理
+    Accept,
+    /// 正常处理，但需要记录警告
+    Warn(String),
+    /// 跳过该文件
+    Skip(String),
+}
+
+impl ContentLimits {
+    /// 在渲染前检查原始内容
+    ///
+    /// 1. 超过硬限制 -> [`Verdict::Skip`]
+    /// 2. 不可打印字符占比超过阈值 -> [`Verdict::Skip`]
+    /// 3. 超过软限制 -> [`Verdict::Warn`]
+    pub fn check(&self, content: &[u8]) -> Verdict {
+        if content.len() > self.hard_bytes {
+            return Verdict::Skip(format!(
+                "size {} bytes exceeds hard limit {} bytes",
+                content.len(),
+                self.hard_bytes
+            ));
+        }
+
+        let ratio = non_printable_ratio(content);
+        if ratio > self.max_non_printable_ratio {
+            return Verdict::Skip(format!(
+                "{:.1}% non-printable characters, looks like binary content",
+                ratio * 100.0
+            ));
+        }
+
+        if content.len() > self.soft_bytes {
+            return Verdict::Warn(format!(
+                "size {} bytes exceeds soft limit {} bytes",
+                content.len(),
+                self.soft_bytes
+            ));
+        }
+
+        Verdict::Accept
+    }
+}
+
+/// 计算不可打印字符占比。
+///
+/// 无效的 UTF-8 序列和除换行、回车、制表符以外的控制字符视为不可打印。
+fn non_printable_ratio(content: &[u8]) -> f64 {
+    let (total, bad) =
+        String::from_utf8_lossy(content)
+            .chars()
+            .fold((0usize, 0usize), |(total, bad), c| {
+                let non_printable = c == char::REPLACEMENT_CHARACTER
+                    || (c.is_control() && !matches!(c, '\n' | '\r' | '\t'));
+                (total + 1, bad + usize::from(non_printable))
+            });
+
+    if total == 0 {
+        0.0
+    } else {
+        bad as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ContentLimits {
+        ContentLimits {
+            soft_bytes: 100,
+            hard_bytes: 200,
+            max_non_printable_ratio: 0.1,
+            strict_metadata: false,
+            slow_render_ms: 5000,
+        }
+    }
+
+    #[test]
+    fn test_size_boundaries() {
+        let limits = limits();
+
+        assert_eq!(limits.check(&[b'a'; 100]), Verdict::Accept);
+        assert!(matches!(limits.check(&[b'a'; 101]), Verdict::Warn(_)));
+        assert!(matches!(limits.check(&[b'a'; 200]), Verdict::Warn(_)));
+        assert!(matches!(limits.check(&[b'a'; 201]), Verdict::Skip(_)));
+    }
+
+    #[test]
+    fn test_non_printable_boundaries() {
+        let limits = limits();
+
+        // 10 个字符中 1 个控制字符，恰好 10%
+        let mut content = vec![b'a'; 9];
+        content.push(0x00);
+        assert_eq!(limits.check(&content), Verdict::Accept);
+
+        // 10 个字符中 2 个控制字符
+        let mut content = vec![b'a'; 8];
+        content.extend([0x00, 0x01]);
+        assert!(matches!(limits.check(&content), Verdict::Skip(_)));
+
+        // 无效 UTF-8
+        let mut content = vec![b'a'; 8];
+        content.extend([0xff, 0xfe]);
+        assert!(matches!(limits.check(&content), Verdict::Skip(_)));
+    }
+
+    #[test]
+    fn test_whitespace_and_unicode_are_printable() {
+        let content = "标题\n\n\t正文\r\n".repeat(5);
+
+        assert_eq!(limits().check(content.as_bytes()), Verdict::Accept);
+    }
+
+    #[test]
+    fn test_empty_content() {
+        assert_eq!(limits().check(b""), Verdict::Accept);
+    }
+}
@@ -1,15 +1,40 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use tracing::Instrument;
+
 use crate::{
-    content::{ArticleBuilder, Group, Renderer},
-    git_client::{ChangeKind, FileKind, GitClient, GitFileEntry},
-    storage::Store,
+    content::{
+        Article, ArticleBuilder, FrontMatter, FrontMatterSchema, Group, GroupAuthor, GroupPath,
+        GroupSettings, MetadataError, NEAR_DUPLICATE_BITS, NoContent, RawHtml, Renderer,
+        SlugStrategy, UnknownField, distance, expand_includes, simhash,
+    },
+    error::Error,
+    git_client::{
+        BlobPrefetch, ChangeKind, FileClassifier, FileKind, GitClient, GitFileEntry, blob_oid,
+    },
+    links::{attachment_path, extract_attachments, extract_links, is_external},
+    render::namespace_anchors,
+    storage::{
+        ArticleShortId, ArticleSimhash, Attachment, ContentBlob, StaleRender, Store, new_short_id,
+    },
 };
+
+use super::{ContentLimits, EntryOutcome, EntryProgress, ProgressSender, SyncReport, Verdict};
+
 /// 持久化模式
 ///
-/// - [`PersistMode::ResetAll`]：重置所有数据，然后再写入
-/// - [`PersistMode::Incremental`]：增量更新，只处理变化部分
+/// - [`PersistMode::ResetAll`]：重置所有数据，然后再写入；新数据完整写入后才替换线上数据
+/// - [`PersistMode::Incremental`]：增量更新，只处理变化部分；单个文件处理失败时记录失败，其余变更照常写入
+/// - [`PersistMode::Strict`]：增量更新，任一文件处理失败或提交后有组超出配额时整个同步失败
+#[derive(Debug, Clone, Copy)]
 pub enum PersistMode {
     ResetAll,
     Incremental,
+    Strict,
 }
 
 /// 定义可持久化的条目接口
@@ -18,15 +43,38 @@ pub enum PersistMode {
 pub trait Persistable {
     type Error;
 
-    /// 持久化条目
+    /// 持久化条目，返回同步报告
     ///
+    /// `commit` 为同步的目标提交，读取 include 引用的文件时使用。
     fn persist<R, S>(
         &self,
         storage: S,
         renderer: &R,
         repo: &GitClient,
+        commit: &str,
+        mode: PersistMode,
+        limits: &ContentLimits,
+    ) -> impl std::future::Future<Output = Result<SyncReport, Self::Error>>
+    where
+        R: Renderer,
+        S: Store,
+        S::Owned: Store,
+    {
+        self.persist_with_progress(storage, renderer, repo, commit, mode, limits, None)
+    }
+
+    /// 与 [`Persistable::persist`] 相同，每个条目处理完成后向 `progress` 发送 [`EntryProgress`]
+    #[allow(clippy::too_many_arguments)]
+    fn persist_with_progress<R, S>(
+        &self,
+        storage: S,
+        renderer: &R,
+        repo: &GitClient,
+        commit: &str,
         mode: PersistMode,
-    ) -> impl std::future::Future<Output = Result<(), Self::Error>>
+        limits: &ContentLimits,
+        progress: Option<&ProgressSender>,
+    ) -> impl std::future::Future<Output = Result<SyncReport, Self::Error>>
     where
         R: Renderer,
         S: Store,
@@ -42,61 +90,1226 @@ impl Persistable for Vec<GitFileEntry> {
     ///
     /// 处理逻辑：
     /// - GitNote 文件：
-    ///     - Added：加载内容，构建 [`Group`]，更新数据库
-    ///     - Deleted：构建空 [`Group`]，从数据库删除
+    ///     - Added / Modified：加载内容，构建 [`Group`]，更新数据库；由重命名产生时先将旧组迁移到新 id；
+    ///       作者 id 已被其他组中不同的作者名使用时记录警告。修改只原位更新组记录，不先删除，组内文章不受影响
+    ///     - Deleted：构建空 [`Group`]，从数据库删除；被重命名的组由新增条目迁移，不单独删除
     /// - Markdown 文件：
-    ///     - Added：加载内容，构建 [`ArticleBuilder`]，使用 [`App::renderer`] 渲染后写入数据库
+    ///     - Added：加载内容，展开 include 指令，构建 [`ArticleBuilder`]，使用 [`App::renderer`] 渲染后写入数据库，
+    ///       并为重命名来源和 `aliases` 写入重定向；没有 Front Matter 的文件（如被引用的片段）不发布
     ///     - Deleted：从数据库删除
+    ///     - 所属组和 slug 按各组的 [`SlugStrategy`](crate::content::SlugStrategy) 确定，与本批次或已存储的其他文件 slug 重复时记录警告
     /// - Other 文件类型：忽略
     ///
-    async fn persist<R, S>(
+    /// Markdown 内容在渲染前按 [`ContentLimits`] 检查：超过软限制记录警告，
+    /// 超过硬限制或疑似二进制内容时跳过该文件（不写入也不删除），并记录到 [`SyncReport`]。
+    /// 组配置和 Front Matter 中的未知字段记录为警告并给出拼写建议，
+    /// [`ContentLimits::strict_metadata`] 开启时视为该文件处理失败。
+    /// Front Matter 按目标提交中所在组的 [`FrontMatterSchema`] 检查，缺少或类型错误的字段同样记录为警告，
+    /// 严格模式下视为该文件处理失败；组声明的字段不视为未知字段。
+    ///
+    /// 已存储的组修改 `encrypted` 或 `slug_strategy` 时同步失败，需要重建。两个组对外使用相同的 id 时同步失败。
+    /// 所属组为 `encrypted` 时，文章正文和摘要加密后写入；未配置密钥时同步失败。
+    /// 非加密文章同时记录正文中的外部链接，供链接检查使用。
+    /// 写入的文章记录源文件路径和 `commit`，文件移动后路径随之更新。
+    /// 文章的短 id 依次按源文件路径、重命名前的路径和 slug 匹配已存储的文章沿用，重建时同样沿用；
+    /// 都没有匹配时生成新的短 id。
+    ///
+    /// 非加密文章的渲染结果按展开 include 后内容的 git blob oid 存储为 [`ContentBlob`]，内容相同的文章共享同一份；
+    /// 本批次已渲染或已存储当前 [`Renderer::version`] 的渲染结果时不再渲染。提交后删除不再被引用的渲染结果。
+    /// 渲染结果不随重建清空，重建时只有内容或渲染器版本变化的文章重新渲染，
+    /// 重新渲染和复用的文章数记录到 [`SyncReport`]。
+    ///
+    /// 增量同步时，引用了本批次变更文件的文章即使自身未变更，也会在同一批次中重新渲染。
+    ///
+    /// [`PersistMode::Incremental`] 下每个文件的写入以 [`Store::savepoint`] 隔开：处理失败时丢弃该文件的写入，
+    /// 记录到 [`SyncReport`] 和失败记录中，其余文件照常提交。处理成功的文件删除已有的失败记录，
+    /// 剩余的失败记录更新为本次的 `commit`，供 [`retry_failures`] 读取。
+    ///
+    /// 进度事件在提交前发出，之后的条目失败或提交失败时已发出的事件对应的写入同样不会生效。
+    ///
+    /// 提交后按 [`Store::apply_retention`] 对本批次涉及的组应用保留规则，重建时对全部组应用，
+    /// 下线和重新上线的文章数记录到 [`SyncReport`]。
+    async fn persist_with_progress<R, S>(
         &self,
         mut storage: S,
         renderer: &R,
         repo: &GitClient,
+        commit: &str,
         mode: PersistMode,
-    ) -> Result<(), Self::Error>
+        limits: &ContentLimits,
+        progress: Option<&ProgressSender>,
+    ) -> Result<SyncReport, Self::Error>
     where
         R: Renderer,
         S: Store,
         S::Owned: Store,
     {
-        if let PersistMode::ResetAll = mode {
-            storage.clean();
+        let (stored, mut authors, slugs, visibility) = match mode {
+            PersistMode::ResetAll => {
+                storage.clean();
+                (
+                    HashMap::new(),
+                    HashMap::new(),
+                    HashMap::new(),
+                    HashMap::new(),
+                )
+            }
+            PersistMode::Incremental | PersistMode::Strict => (
+                storage.group_settings().await?,
+                storage.group_authors().await?,
+                storage.group_slugs().await?,
+                storage.group_visibility().await?,
+            ),
+        };
+        // 重建时清空前读取，改名和重建后沿用文章原来的短 id
+        let stored_ids = storage.article_short_ids().await?;
+        // 已存储文章的 slug 及其源文件路径，用于发现与之前批次写入的文章的冲突。重建时全部重新写入，不需要比较
+        let stored_sources = match mode {
+            PersistMode::ResetAll => HashMap::new(),
+            PersistMode::Incremental | PersistMode::Strict => stored_ids
+                .iter()
+                .filter_map(|row| {
+                    Some((row.slug.clone(), PathBuf::from(row.source_path.as_ref()?)))
+                })
+                .collect::<HashMap<_, _>>(),
+        };
+        let mut short_ids = ShortIds::new(stored_ids);
+        let settings = final_settings(self, repo, stored).await?;
+        // 本批次处理完成后存在的组及其是否公开：已存储的组和新增的组，去掉删除和移走的组
+        let group_public = final_visibility(self, repo, visibility).await?;
+        check_group_slugs(self, repo, slugs).await?;
+        let strategies = settings
+            .iter()
+            .map(|(id, s)| (id.to_owned(), s.slug_strategy))
+            .collect::<HashMap<_, _>>();
+        let mut report = SyncReport::default();
+
+        // 本批次写入的 slug 及其来源文件，用于发现冲突
+        let mut written = HashMap::new();
+
+        // 本批次变更或移走的文件，这些文件已存储的文章由本批次覆盖，不与之比较
+        let touched = self
+            .iter()
+            .flat_map(|e| [Some(e.path()), e.renamed_from()])
+            .flatten()
+            .collect::<HashSet<_>>();
+
+        // 本批次渲染或复用的渲染结果，按内容 oid 索引
+        let mut blobs = HashMap::new();
+
+        // 文章所在组在目标提交中的组配置，按组目录索引。本批次修改的组配置对同一批次的文章生效
+        let mut configs = HashMap::new();
+
+        // 本批次成功写入、记录了 simhash 的文章，提交后与已存储的公开文章比较
+        let mut simhashed = Vec::new();
+
+        // 重命名的组配置文件，旧位置的删除由迁移代替
+        let moved_groups = self
+            .iter()
+            .filter(|e| e.file_kind() == FileKind::Group)
+            .filter_map(|e| e.renamed_from())
+            .collect::<HashSet<_>>();
+
+        // 本批次各组目录中最后一个说明文件条目的位置，排在组配置之后的说明文件条目会写入说明。
+        // 重建时条目按提交顺序排列，说明文件可能早于组配置
+        let readme_at = self
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.file_kind() == FileKind::Readme)
+            .map(|(i, e)| (Group::empty(e.path()).id, i))
+            .collect::<HashMap<_, _>>();
+
+        // 引用了变更文件、自身未变更的文章
+        let dependents = match mode {
+            PersistMode::ResetAll => Vec::new(),
+            PersistMode::Incremental | PersistMode::Strict => {
+                let changed = self
+                    .iter()
+                    .map(|e| e.path().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                let paths = storage
+                    .include_dependents(&changed)
+                    .await?
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .filter(|p| self.iter().all(|e| e.path() != p))
+                    .collect();
+                repo.entries_at(commit, paths).await?
+            }
         };
 
-        for entry in self {
-            match (entry.file_kind(), entry.change_kind()) {
-                (FileKind::Group, ChangeKind::Added | ChangeKind::Modified) => {
-                    let content = repo.load_file(entry.id())?;
-                    let group = Group::new(entry.path(), content)?;
-                    storage.upsert_group(&group);
+        // 组配置和文章的内容按处理顺序分批预读，减少逐个读取时打开仓库的次数
+        let mut prefetch = BlobPrefetch::new(
+            self.iter()
+                .chain(&dependents)
+                .filter(|e| e.skip_reason().is_none())
+                .filter(|e| matches!(e.file_kind(), FileKind::Group | FileKind::Markdown))
+                .filter(|e| e.change_kind() != ChangeKind::Deleted)
+                .map(|e| e.id()),
+        );
+
+        for (index, entry) in self.iter().chain(&dependents).enumerate() {
+            let path = entry.path().to_string_lossy().into_owned();
+            let savepoint = storage.savepoint();
+            let started = Instant::now();
+            let (warned, skipped) = (report.warnings.len(), report.skipped.len());
+            let span = tracing::info_span!(
+                "entry",
+                path = %path,
+                file_kind = ?entry.file_kind(),
+                change_kind = ?entry.change_kind(),
+                render_ms = tracing::field::Empty,
+                db_ms = tracing::field::Empty,
+                outcome = tracing::field::Empty,
+            );
+            let mut timings = EntryTimings::default();
+            let mut written_simhash = None;
+            let result = async {
+                if let Some(reason) = entry.skip_reason()
+                    && entry.file_kind() != FileKind::Other
+                {
+                    tracing::warn!(path = %entry.path().display(), reason, "entry skipped");
+                    report.skip(entry.path(), reason);
+                    return Ok(());
                 }
 
-                (FileKind::Group, ChangeKind::Deleted) => {
-                    let group = Group::empty(entry.path());
-                    storage.remove_group(&group);
+                match (entry.file_kind(), entry.change_kind()) {
+                    (FileKind::Group, ChangeKind::Added | ChangeKind::Modified) => {
+                        let content = prefetch.load_file(repo, entry.id()).await?;
+                        let (group, unknown) = Group::parse(entry.path(), content)?;
+                        if group.name.is_empty() {
+                            let reason = "group directory has no name, set `name`";
+                            tracing::warn!(path = %entry.path().display(), reason, "group skipped");
+                            report.skip(entry.path(), reason);
+                            return Ok(());
+                        }
+                        check_fields(&mut report, entry.path(), unknown, limits.strict_metadata)?;
+                        for rejected in &group.rejected_tags {
+                            tracing::warn!(path = %entry.path().display(), reason = %rejected, "tag dropped");
+                            report.warn(entry.path(), rejected.to_string());
+                        }
+
+                        if let Some(old_path) = entry.renamed_from() {
+                            let old = Group::empty(old_path);
+                            if old.id != group.id {
+                                storage.rename_group(&old.id, &group.id);
+                                report.group(old.id.as_str());
+                                authors.remove(&old.id);
+                            }
+                        }
+
+                        if let Some(reason) = author_conflict(&authors, &group) {
+                            tracing::warn!(path = %entry.path().display(), reason = %reason, "author conflict");
+                            report.warn(entry.path(), reason);
+                        }
+                        match &group.author {
+                            Some(author) => authors.insert(group.id.clone(), author.clone()),
+                            None => authors.remove(&group.id),
+                        };
+
+                        // 归档状态等组设置只来自组配置文件：重建时快照中的每个组配置都会重新写入，
+                        // 查询时再按组派生文章的归档状态，因此不依赖推送本身
+                        storage.upsert_group(&group);
+                        report.group(group.id.as_str());
+
+                        // 说明文件早于组配置提交时，组创建后从提交中补上说明
+                        if entry.change_kind() == ChangeKind::Added
+                            && readme_at.get(&group.id).is_none_or(|&i| i < index)
+                            && let Some(content) = repo.load_readme(commit, &group.id).await?
+                        {
+                            let html = timings.time_render(render_readme(renderer, &content)).await?;
+                            storage.set_group_description(&group.id, Some(&html));
+                        }
+                    }
+
+                    (FileKind::Group, ChangeKind::Deleted) if moved_groups.contains(entry.path()) => {}
+
+                    (FileKind::Group, ChangeKind::Deleted) => {
+                        let group = Group::empty(entry.path());
+                        storage.remove_group(&group);
+                        report.group(group.id.as_str());
+                        authors.remove(&group.id);
+                    }
+
+                    (FileKind::Markdown, ChangeKind::Added | ChangeKind::Modified) => {
+                        let bytes = prefetch.load_bytes(repo, entry.id()).await?;
+                        match limits.check(&bytes) {
+                            Verdict::Accept => (),
+                            Verdict::Warn(reason) => {
+                                tracing::warn!(path = %entry.path().display(), reason = %reason, "oversized article");
+                                report.warn(entry.path(), reason);
+                            }
+                            Verdict::Skip(reason) => {
+                                tracing::warn!(path = %entry.path().display(), reason = %reason, "article skipped");
+                                report.skip(entry.path(), reason);
+                                return Ok(());
+                            }
+                        }
+
+                        let content = String::from_utf8_lossy(&bytes).into_owned();
+                        if !FrontMatter::is_present(&content) {
+                            let builder = ArticleBuilder::with_strategies(entry.path(), &strategies);
+                            storage
+                                .remove_article(builder.to_ref())
+                                .replace_includes(&path, &[]);
+                            report.skip(entry.path(), "no front matter, not published");
+                            return Ok(());
+                        }
+                        let group = ArticleBuilder::with_strategies(entry.path(), &strategies)
+                            .group()
+                            .clone();
+                        if !configs.contains_key(&group) {
+                            let config = group_at(repo, commit, &group).await?;
+                            configs.insert(group.clone(), config);
+                        }
+                        let config = configs[&group].as_ref();
+                        check_front_matter(
+                            &mut report,
+                            entry.path(),
+                            &content,
+                            config.and_then(Group::front_matter_schema).as_ref(),
+                            limits.strict_metadata,
+                        )?;
+
+                        let expanded = {
+                            let file = entry.path().to_path_buf();
+                            repo.with_tree(commit, move |load| expand_includes(&file, &content, load))
+                                .await??
+                        };
+                        let includes = expanded
+                            .includes
+                            .iter()
+                            .map(|p| p.to_string_lossy().into_owned())
+                            .collect::<Vec<_>>();
+                        storage.replace_includes(&path, &includes);
+
+                        let links = extract_links(&expanded.content);
+                        let attachments =
+                            extract_attachments(&expanded.content, repo.attachment_extensions());
+                        let mut builder = ArticleBuilder::with_strategies(entry.path(), &strategies)
+                            .timezone(repo.timezone())
+                            .updated_at(entry.timestamp());
+                        if FrontMatter::requests_raw_html(&expanded.content) {
+                            let policy = config.map_or(RawHtml::Denied, Group::raw_html);
+                            if policy == RawHtml::Denied {
+                                let reason = raw_html_denied(&group);
+                                tracing::warn!(path = %entry.path().display(), reason = %reason, "raw html denied");
+                                report.warn(entry.path(), reason);
+                            }
+                            builder = builder.raw_html(policy);
+                        }
+                        // 加密文章不共享渲染结果
+                        let (article, blob, rendered) =
+                            if settings.get(builder.group()).is_some_and(|s| s.encrypted) {
+                                let (article, elapsed) = timings
+                                    .time_article(
+                                        builder
+                                            .content(expanded.content)
+                                            .build_with_renderer(renderer),
+                                    )
+                                    .await;
+                                (article?, None, Some(elapsed))
+                            } else {
+                                let (mut article, blob, rendered) = build_shared(
+                                    builder,
+                                    expanded.content,
+                                    &storage,
+                                    renderer,
+                                    &mut blobs,
+                                    &mut timings,
+                                )
+                                .await?;
+                                // 命名空间依赖 slug，不随渲染结果共享
+                                if renderer.namespace_anchors() {
+                                    article.namespaced_content = Some(namespace_anchors(
+                                        &article.rendered_content,
+                                        &article.slug,
+                                    ));
+                                }
+                                (article, Some(blob), rendered)
+                            };
+
+                        if let Some(other) = slug_collision(
+                            &mut written,
+                            &stored_sources,
+                            &touched,
+                            &article.slug,
+                            entry.path(),
+                        ) {
+                            let reason =
+                                format!("slug `{}` collides with {}", article.slug, other.display());
+                            tracing::warn!(path = %entry.path().display(), reason = %reason, "slug collision");
+                            report.warn(entry.path(), reason);
+                        }
+
+                        match group_public.get(&article.group) {
+                            // 没有组的文章照常写入，但不会出现在任何公开查询中
+                            None => {
+                                let reason = orphan_reason(&article.group);
+                                tracing::warn!(path = %entry.path().display(), reason = %reason, "article without group");
+                                report.warn(entry.path(), reason);
+                            }
+                            Some(&group) if article.frontmatter.public == Some(!group) => {
+                                report.visibility(entry.path(), !group);
+                            }
+                            Some(_) => {}
+                        }
+
+                        // 加密文章不记录附件
+                        let attachments = match blob {
+                            Some(_) => {
+                                resolve_attachments(
+                                    &mut report,
+                                    repo,
+                                    commit,
+                                    entry.path(),
+                                    attachments,
+                                )
+                                .await?
+                            }
+                            None => Vec::new(),
+                        };
+                        storage.replace_attachments(article.to_ref(), &attachments);
+
+                        match blob {
+                            // 加密文章不记录外部链接
+                            None => {
+                                storage
+                                    .upsert_encrypted_article(&article)?
+                                    .replace_links(&article.slug, &[]);
+                            }
+                            Some(blob) => {
+                                storage
+                                    .upsert_content_blob(&blob)
+                                    .upsert_blob_article(&article, &blob.oid)
+                                    .replace_links(&article.slug, &links);
+                                written_simhash = blob.simhash.map(|hash| {
+                                    (article.slug.clone(), article.group.clone(), hash)
+                                });
+                            }
+                        }
+                        let short_id =
+                            short_ids.assign(entry.path(), entry.renamed_from(), &article.slug);
+                        storage
+                            .set_article_source(&article.slug, &path, commit)
+                            .set_article_short_id(&article.slug, &short_id)
+                            .set_article_renderer(
+                                &article.slug,
+                                article.rendered_by.as_deref().unwrap_or(&renderer.identity()),
+                            );
+                        report.render(rendered.is_none());
+                        // 复用已有渲染结果时保留上一次渲染的记录
+                        if let Some(elapsed) = rendered {
+                            let render_ms = elapsed.as_millis() as u64;
+                            storage.set_article_render_stats(
+                                &article.slug,
+                                render_ms as i64,
+                                article.rendered_content.len() as i64,
+                            );
+                            if render_ms > limits.slow_render_ms {
+                                tracing::warn!(path = %entry.path().display(), render_ms, "slow render");
+                                report.slow_render(entry.path(), render_ms);
+                            }
+                        }
+
+                        // 文件移动后，旧位置重定向到新位置
+                        if let Some(old_path) = entry.renamed_from() {
+                            let old = ArticleBuilder::with_strategies(old_path, &strategies);
+                            storage.upsert_redirect(old.to_ref(), article.to_ref());
+                        }
+
+                        for alias in article.aliases() {
+                            storage.upsert_redirect(alias, article.to_ref());
+                        }
+
+                        report.article(entry.change_kind(), entry.path());
+                        if let Some(old_id) = entry.old_id() {
+                            report.diff(entry.path(), repo.diff_stat(old_id, entry.id()).await?);
+                        }
+                    }
+
+                    (FileKind::Markdown, ChangeKind::Deleted) => {
+                        let article_builder =
+                            ArticleBuilder::with_strategies(entry.path(), &strategies);
+                        storage
+                            .remove_article(article_builder.to_ref())
+                            .replace_includes(&path, &[]);
+                        report.article(entry.change_kind(), entry.path());
+                    }
+
+                    (FileKind::Readme, ChangeKind::Added | ChangeKind::Modified) => {
+                        let content = repo.load_file(entry.id()).await?;
+                        let html = timings.time_render(render_readme(renderer, &content)).await?;
+                        let group = Group::empty(entry.path());
+
+                        // 从其他组移动过来时清除原组的说明
+                        if let Some(old_path) = entry.renamed_from() {
+                            let old = Group::empty(old_path);
+                            if old.id != group.id {
+                                storage.set_group_description(&old.id, None);
+                                report.group(old.id.as_str());
+                            }
+                        }
+
+                        // 组不存在时不写入，组配置新增时再从提交中读取
+                        storage
+                            .set_group_description(&group.id, Some(&html))
+                            .remove_article(
+                                ArticleBuilder::with_strategies(entry.path(), &strategies).to_ref(),
+                            )
+                            .replace_includes(&path, &[]);
+                        report.group(group.id.as_str());
+                    }
+
+                    (FileKind::Readme, ChangeKind::Deleted) => {
+                        let group = Group::empty(entry.path());
+                        storage
+                            .set_group_description(&group.id, None)
+                            .remove_article(
+                                ArticleBuilder::with_strategies(entry.path(), &strategies).to_ref(),
+                            )
+                            .replace_includes(&path, &[]);
+                        report.group(group.id.as_str());
+                    }
+
+                    (FileKind::Other, _) => (),
                 }
+                Ok::<_, Error>(())
+            }
+            .instrument(span.clone())
+            .await;
+
+            let (outcome, reason) = match &result {
+                Err(e) => (EntryOutcome::Failed, Some(e.to_string())),
+                Ok(()) if report.skipped.len() > skipped => (
+                    EntryOutcome::Skipped,
+                    report.skipped.last().map(|i| i.reason.clone()),
+                ),
+                Ok(()) if report.warnings.len() > warned => (
+                    EntryOutcome::Warning,
+                    report.warnings.last().map(|i| i.reason.clone()),
+                ),
+                Ok(()) => (EntryOutcome::Ok, None),
+            };
+            span.record("render_ms", timings.render.as_millis() as u64);
+            span.record("db_ms", timings.db.as_millis() as u64);
+            span.record("outcome", outcome.as_str());
+            span.in_scope(|| tracing::debug!("entry processed"));
+
+            if let Some(progress) = progress {
+                // 接收方已断开时忽略，同步照常完成
+                let suggestions = result
+                    .as_ref()
+                    .err()
+                    .map(|e| e.suggestions().to_vec())
+                    .unwrap_or_default();
+                let _ = progress.send(
+                    EntryProgress::new(entry, outcome, reason, started)
+                        .with_suggestions(suggestions),
+                );
+            }
 
-                (FileKind::Markdown, ChangeKind::Added | ChangeKind::Modified) => {
-                    let content = repo.load_file(entry.id())?;
-                    let article = ArticleBuilder::new(entry.path())
-                        .content(content)
-                        .build_with_renderer(renderer)
-                        .await?;
-                    storage.upsert_article(&article);
+            if result.is_ok()
+                && let Some((slug, group, hash)) = written_simhash
+            {
+                simhashed.push((entry.path(), slug, group, hash));
+            }
+
+            match (result, &mode) {
+                (Ok(()), PersistMode::ResetAll) => (),
+                (Ok(()), _) => {
+                    storage.clear_sync_failure(&path);
                 }
+                (Err(e), PersistMode::Incremental) => {
+                    // 丢弃该文件已排队的写入，其余文件照常提交
+                    let reason = e.to_string();
+                    tracing::warn!(path = %entry.path().display(), reason = %reason, "entry failed");
+                    storage
+                        .rollback_to(savepoint)
+                        .record_sync_failure(&path, &reason, commit);
+                    report.fail_with_suggestions(entry.path(), reason, e.suggestions().to_vec());
+                }
+                (Err(e), _) => return Err(e),
+            }
+        }
+
+        let retained = match mode {
+            PersistMode::ResetAll => None,
+            PersistMode::Incremental | PersistMode::Strict => Some(retention_groups(
+                self.iter().chain(&dependents),
+                &strategies,
+            )),
+        };
+        commit_batch(storage, &mut report, commit, mode, retained, &simhashed).await?;
+
+        Ok(report)
+    }
+}
+
+/// 提交本批次的写入，之后检查重复内容、应用保留规则并清理不再引用的渲染结果
+///
+/// `retained` 为需要重新应用保留规则的组，[`None`] 时对全部组应用。
+async fn commit_batch<S>(
+    mut storage: S,
+    report: &mut SyncReport,
+    commit: &str,
+    mode: PersistMode,
+    retained: Option<Vec<GroupPath>>,
+    simhashed: &[(&Path, String, GroupPath, i64)],
+) -> Result<(), Error>
+where
+    S: Store,
+    S::Owned: Store,
+{
+    if !matches!(mode, PersistMode::ResetAll) {
+        // 重试时从最新的提交读取失败的文件
+        storage.retarget_sync_failures(commit);
+    }
+    if matches!(mode, PersistMode::Strict) {
+        storage.enforce_quotas();
+    }
+
+    // 提交后再清理，重建写入期间线上的旧文章仍引用原有的渲染结果
+    let mut gc = storage.to_owned();
+    storage.commit().await?;
+    if !simhashed.is_empty() {
+        duplicate_warnings(report, simhashed, &gc.article_simhashes().await?);
+    }
+    let changes = gc.apply_retention(retained.as_deref()).await?;
+    if !changes.is_empty() {
+        tracing::info!(changes = changes.len(), "retention applied");
+    }
+    report.retention(&changes);
+    gc.prune_content_blobs();
+    gc.commit().await?;
+    Ok(())
+}
+
+/// 与 `slug` 冲突的另一篇文章的源文件：本批次已写入同一 slug 的文件，或之前批次写入、本批次未变更或移走的文件
+///
+/// 同时把 `slug` 记为由 `path` 写入。
+fn slug_collision<'a>(
+    written: &mut HashMap<String, &'a Path>,
+    stored: &'a HashMap<String, PathBuf>,
+    touched: &HashSet<&Path>,
+    slug: &str,
+    path: &'a Path,
+) -> Option<&'a Path> {
+    match written.insert(slug.to_owned(), path) {
+        Some(other) => Some(other),
+        None => stored
+            .get(slug)
+            .map(PathBuf::as_path)
+            .filter(|p| *p != path && !touched.contains(p)),
+    }
+}
+
+/// 本批次写入的文章与其他组的公开文章内容重复或近似重复时，在两篇文章中较新写入的一篇记录警告
+///
+/// `written` 为文章的源文件路径、slug、所在组和 simhash。同一批次中互相重复的两篇文章只记录一次。
+fn duplicate_warnings(
+    report: &mut SyncReport,
+    written: &[(&Path, String, GroupPath, i64)],
+    stored: &[ArticleSimhash],
+) {
+    let batch = written
+        .iter()
+        .map(|(_, slug, ..)| slug.as_str())
+        .collect::<HashSet<_>>();
+    for (path, slug, group, hash) in written {
+        let near = stored.iter().filter(|other| {
+            &other.group_id != group
+                && !(batch.contains(other.slug.as_str()) && other.slug < *slug)
+                && distance(other.simhash, *hash) <= NEAR_DUPLICATE_BITS
+        });
+        for other in near {
+            let other_path = other.source_path.as_deref().unwrap_or(&other.slug);
+            let reason = match distance(other.simhash, *hash) {
+                0 => format!("duplicates the content of {other_path}"),
+                bits => format!("nearly duplicates {other_path} ({bits} of 64 bits differ)"),
+            };
+            tracing::warn!(path = %path.display(), reason = %reason, "duplicate content");
+            report.warn(path, reason);
+        }
+    }
+}
+
+/// 需要重新应用保留规则的组：文章增删、移入移出或组配置变化的组
+///
+/// 处理失败的条目同样计入，多处理的组结果不变。
+fn retention_groups<'a>(
+    entries: impl Iterator<Item = &'a GitFileEntry>,
+    strategies: &HashMap<GroupPath, SlugStrategy>,
+) -> Vec<GroupPath> {
+    let mut groups = HashSet::new();
+    for entry in entries {
+        for path in [Some(entry.path()), entry.renamed_from()]
+            .into_iter()
+            .flatten()
+        {
+            match entry.file_kind() {
+                FileKind::Markdown => {
+                    groups.insert(
+                        ArticleBuilder::with_strategies(path, strategies)
+                            .group()
+                            .to_owned(),
+                    );
+                }
+                FileKind::Group => {
+                    groups.insert(Group::empty(path).id);
+                }
+                FileKind::Readme | FileKind::Other => (),
+            }
+        }
+    }
+    groups.into_iter().collect()
+}
+
+/// 单个文件处理中渲染和数据库读取的耗时，记录到 `entry` span 的 `render_ms` 和 `db_ms`
+///
+/// 数据库写入排队到提交时统一执行，不计入 `db`。
+#[derive(Default)]
+struct EntryTimings {
+    render: Duration,
+    db: Duration,
+}
+
+impl EntryTimings {
+    async fn time_render<T>(&mut self, fut: impl Future<Output = T>) -> T {
+        self.time_article(fut).await.0
+    }
+
+    /// 与 [`EntryTimings::time_render`] 相同，同时返回本次渲染的耗时，用于记录单篇文章的渲染耗时
+    async fn time_article<T>(&mut self, fut: impl Future<Output = T>) -> (T, Duration) {
+        let started = Instant::now();
+        let output = fut.await;
+        let elapsed = started.elapsed();
+        self.render += elapsed;
+        (output, elapsed)
+    }
+
+    async fn time_db<T>(&mut self, fut: impl Future<Output = T>) -> T {
+        let started = Instant::now();
+        let output = fut.await;
+        self.db += started.elapsed();
+        output
+    }
+}
+
+/// 本批次文章短 id 的分配
+///
+/// 依次按源文件路径、重命名前的路径和 slug 匹配已存储的文章，沿用匹配到的短 id。
+/// 都没有匹配时生成新的短 id，如新文章，或迁移前写入、没有记录源文件路径的文章在重建时改了 slug。
+/// 一个短 id 在本批次中只分配给一篇文章。
+struct ShortIds {
+    by_path: HashMap<String, String>,
+    by_slug: HashMap<String, String>,
+    /// 已存储和本批次分配的短 id
+    taken: HashSet<String>,
+    assigned: HashSet<String>,
+}
 
-                (FileKind::Markdown, ChangeKind::Deleted) => {
-                    let article_builder = ArticleBuilder::new(entry.path());
-                    storage.remove_article(article_builder.to_ref());
+impl ShortIds {
+    fn new(stored: Vec<ArticleShortId>) -> Self {
+        let mut ids = Self {
+            by_path: HashMap::new(),
+            by_slug: HashMap::new(),
+            taken: HashSet::new(),
+            assigned: HashSet::new(),
+        };
+        for row in stored {
+            if let Some(path) = row.source_path {
+                ids.by_path.insert(path, row.short_id.clone());
+            }
+            ids.by_slug.insert(row.slug, row.short_id.clone());
+            ids.taken.insert(row.short_id);
+        }
+        ids
+    }
+
+    fn assign(&mut self, path: &Path, renamed_from: Option<&Path>, slug: &str) -> String {
+        let by_path = [Some(path), renamed_from]
+            .into_iter()
+            .flatten()
+            .filter_map(|p| self.by_path.get(p.to_string_lossy().as_ref()));
+        let id = by_path
+            .chain(self.by_slug.get(slug))
+            .find(|id| !self.assigned.contains(*id))
+            .cloned()
+            .unwrap_or_else(|| new_short_id(|id| self.taken.contains(id)));
+
+        self.taken.insert(id.clone());
+        self.assigned.insert(id.clone());
+        id
+    }
+}
+
+/// 构建非加密文章，返回文章、它的渲染结果和渲染耗时
+///
+/// 本批次已渲染过相同内容，或已存储相同内容且渲染器版本相同的 [`ContentBlob`] 时直接复用，只解析 Front Matter，
+/// 此时渲染耗时为 [`None`]。
+async fn build_shared<R: Renderer, S: Store>(
+    builder: ArticleBuilder<NoContent>,
+    raw: String,
+    storage: &S,
+    renderer: &R,
+    blobs: &mut HashMap<String, ContentBlob>,
+    timings: &mut EntryTimings,
+) -> Result<(Article, ContentBlob, Option<Duration>), Error> {
+    let oid = blob_oid(raw.as_bytes());
+    let cached = match blobs.get(&oid) {
+        Some(blob) => Some(blob.clone()),
+        // 原样输出 HTML 取决于所在组的设置，不复用同样内容按 Markdown 渲染的结果
+        None if *builder.raw_html_policy() != RawHtml::Denied => None,
+        None => match renderer.version() {
+            Some(version) => {
+                timings
+                    .time_db(storage.content_blob(&oid, &version))
+                    .await?
+            }
+            None => None,
+        },
+    };
+
+    let (article, blob, rendered) = match cached {
+        Some(mut blob) => {
+            // 迁移前写入的渲染结果没有 simhash，复用时补全
+            if blob.simhash.is_none() {
+                blob.simhash = simhash(&blob.raw);
+            }
+            let article = builder
+                .content(raw)
+                .build_with_rendered(blob.content.clone(), blob.summary.clone())?;
+            (article, blob, None)
+        }
+        None => {
+            let (article, elapsed) = timings
+                .time_article(builder.content(raw.as_str()).build_with_renderer(renderer))
+                .await;
+            let article = article?;
+            // 降级渲染和原样输出 HTML 的结果不复用
+            let blob = ContentBlob {
+                oid: oid.clone(),
+                renderer: renderer.version().filter(|_| article.rendered_by.is_none()),
+                simhash: simhash(&raw),
+                raw,
+                content: article.rendered_content.clone(),
+                summary: article.frontmatter.summary.clone(),
+            };
+            (article, blob, Some(elapsed))
+        }
+    };
+
+    if article.rendered_by.is_none() {
+        blobs.insert(oid, blob.clone());
+    }
+    Ok((article, blob, rendered))
+}
+
+/// 重新处理失败记录中的文件。
+///
+/// 从失败记录的提交读取文件后按 [`PersistMode::Incremental`] 持久化：处理成功的文件删除失败记录，
+/// 仍然失败的文件更新失败原因，提交中已不存在的文件直接删除失败记录。没有失败记录时返回空报告。
+pub async fn retry_failures<R, S>(
+    mut storage: S,
+    renderer: &R,
+    repo: &GitClient,
+    limits: &ContentLimits,
+) -> Result<SyncReport, Error>
+where
+    R: Renderer,
+    S: Store,
+    S::Owned: Store,
+{
+    let failures = storage.sync_failures().await?;
+    // 每次增量同步后所有失败记录都指向同一个提交
+    let Some(commit) = failures.first().map(|f| f.commit_id.clone()) else {
+        return Ok(SyncReport::default());
+    };
+
+    let paths = failures.iter().map(|f| PathBuf::from(&f.path)).collect();
+    let entries = repo.entries_at(&commit, paths).await?;
+    for failure in &failures {
+        if entries.iter().all(|e| e.path() != Path::new(&failure.path)) {
+            storage.clear_sync_failure(&failure.path);
+        }
+    }
+
+    entries
+        .persist(
+            storage,
+            renderer,
+            repo,
+            &commit,
+            PersistMode::Incremental,
+            limits,
+        )
+        .await
+}
+
+/// 重新渲染由其他渲染器生成的文章。
+///
+/// 从 `commit` 读取 `stale` 中文章的源文件，每批最多 `batch` 个文件按 [`PersistMode::Incremental`] 持久化并单独提交，
+/// 返回合并后的报告，重新渲染的文件记录为修改。没有记录源文件或源文件已不在 `commit` 中的文章跳过。
+pub async fn rerender_stale<R, S>(
+    storage: &S,
+    renderer: &R,
+    repo: &GitClient,
+    commit: &str,
+    limits: &ContentLimits,
+    stale: &[StaleRender],
+    batch: usize,
+) -> Result<SyncReport, Error>
+where
+    R: Renderer,
+    S: Store<Owned = S>,
+{
+    let mut report = SyncReport::default();
+    let mut paths = Vec::new();
+    for article in stale {
+        match &article.source_path {
+            Some(path) => paths.push(PathBuf::from(path)),
+            None => report.skip(&article.slug, "source path unknown, resync to re-render"),
+        }
+    }
+
+    for chunk in paths.chunks(batch.max(1)) {
+        let entries = repo.entries_at(commit, chunk.to_vec()).await?;
+        for path in chunk {
+            if entries.iter().all(|e| e.path() != path) {
+                report.skip(path, "source file not found in commit");
+            }
+        }
+
+        let batch_report = entries
+            .persist(
+                storage.to_owned(),
+                renderer,
+                repo,
+                commit,
+                PersistMode::Incremental,
+                limits,
+            )
+            .await?;
+        report.merge(batch_report);
+    }
+
+    Ok(report)
+}
+
+/// 清理孤立的组。
+///
+/// 在同步提交后调用，删除没有文章、且在 `commit` 中没有对应组配置文件的组，返回被删除的组 id。
+pub async fn prune_groups<S: Store>(
+    storage: &S,
+    repo: &GitClient,
+    commit: &str,
+) -> Result<Vec<String>, Error> {
+    let keep = repo
+        .group_files(commit)
+        .await?
+        .iter()
+        .map(|p| GroupPath::from(p.as_path()))
+        .collect::<Vec<_>>();
+
+    let removed = storage.prune_groups(&keep).await?;
+    if !removed.is_empty() {
+        tracing::info!(?removed, "orphan groups removed");
+    }
+
+    Ok(removed.into_iter().map(String::from).collect())
+}
+
+/// 将未知字段记录为警告，`strict` 时第一个未知字段即为错误
+fn check_fields(
+    report: &mut SyncReport,
+    path: &Path,
+    fields: Vec<UnknownField>,
+    strict: bool,
+) -> Result<(), Error> {
+    if strict && let Some(field) = fields.first() {
+        return Err(MetadataError::Unknown {
+            path: path.display().to_string(),
+            field: field.clone(),
+        }
+        .into());
+    }
+
+    for field in fields {
+        let reason = field.to_string();
+        tracing::warn!(path = %path.display(), reason = %reason, "unknown field");
+        report.warn(path, reason);
+    }
+    Ok(())
+}
+
+/// Front Matter 中的未知字段，去掉所在组 `schema` 声明的字段
+fn undeclared_fields(content: &str, schema: Option<&FrontMatterSchema>) -> Vec<UnknownField> {
+    let mut fields = FrontMatter::unknown_fields(content);
+    if let Some(schema) = schema {
+        fields.retain(|f| !schema.declares(&f.name));
+    }
+    fields
+}
+
+/// 按所在组的 `schema` 检查 Front Matter，不符合的项记录为警告
+///
+/// `strict` 时不符合即返回 [`MetadataError::Schema`]，列出全部不符合的项。
+fn check_schema(
+    report: &mut SyncReport,
+    path: &Path,
+    content: &str,
+    schema: Option<&FrontMatterSchema>,
+    strict: bool,
+) -> Result<(), Error> {
+    let Some(schema) = schema else {
+        return Ok(());
+    };
+    let violations = FrontMatter::schema_violations(content, schema);
+    if strict && !violations.is_empty() {
+        return Err(MetadataError::Schema {
+            path: path.display().to_string(),
+            violations,
+        }
+        .into());
+    }
+
+    for violation in violations {
+        let reason = violation.to_string();
+        tracing::warn!(path = %path.display(), reason = %reason, "front matter schema");
+        report.warn(path, reason);
+    }
+    Ok(())
+}
+
+/// 检查文章的 Front Matter：未声明的字段、所在组 `schema`、BOM 和 CRLF 以及被丢弃的标签，记录为警告
+///
+/// `strict` 时未知字段和不符合 `schema` 的项返回错误，见 [`check_fields`] 和 [`check_schema`]。
+pub(super) fn check_front_matter(
+    report: &mut SyncReport,
+    path: &Path,
+    content: &str,
+    schema: Option<&FrontMatterSchema>,
+    strict: bool,
+) -> Result<(), Error> {
+    check_fields(report, path, undeclared_fields(content, schema), strict)?;
+    check_schema(report, path, content, schema, strict)?;
+    if let Some(reason) = FrontMatter::style_warning(content) {
+        tracing::warn!(path = %path.display(), reason = %reason, "article style");
+        report.warn(path, reason);
+    }
+    for rejected in FrontMatter::rejected_tags(content) {
+        tracing::warn!(path = %path.display(), reason = %rejected, "tag dropped");
+        report.warn(path, rejected.to_string());
+    }
+    Ok(())
+}
+
+/// 没有组的文章的警告，指出需要放置组配置文件的目录
+pub(super) fn orphan_reason(group: &GroupPath) -> String {
+    let dir = if group.is_root() {
+        "the repository root".to_string()
+    } else {
+        format!("`{group}/`")
+    };
+    format!("no group owns this article, add `.group.yaml` to {dir} to publish it")
+}
+
+/// 检查组的作者 id 是否已被其他组中不同的作者名使用，返回冲突说明。
+///
+/// `authors` 为已处理的各组作者，键为组 id。不同的作者名生成相同的 id 时两者的文章会被合并到同一作者下，
+/// 需要在组配置中显式指定不同的 `id`。
+fn author_conflict(authors: &HashMap<GroupPath, GroupAuthor>, group: &Group) -> Option<String> {
+    let author = group.author.as_ref()?;
+    let (other_group, other) = authors
+        .iter()
+        .filter(|(id, _)| **id != group.id)
+        .find(|(_, other)| other.id == author.id && other.name != author.name)?;
+
+    Some(format!(
+        "author id `{}` of `{}` conflicts with `{}` in group `{}`",
+        author.id, author.name, other.name, other_group
+    ))
+}
+
+/// 检查本批次处理完成后各组对外使用的 id 是否重复。
+///
+/// `slugs` 为已存储的各组的 slug，键为组目录路径。两个组设置了相同的 `slug`，
+/// 或一个组的 `slug` 与另一个未设置 `slug` 的组的目录路径相同时，返回包含两个组目录路径的
+/// [`MetadataError::SlugConflict`]，整个同步失败。
+async fn check_group_slugs(
+    entries: &[GitFileEntry],
+    repo: &GitClient,
+    mut slugs: HashMap<GroupPath, String>,
+) -> Result<(), Error> {
+    for entry in readable_groups(entries) {
+        match entry.change_kind() {
+            ChangeKind::Added | ChangeKind::Modified => {
+                let group = Group::new(entry.path(), repo.load_file(entry.id()).await?)?;
+                if let Some(old_path) = entry.renamed_from() {
+                    slugs.remove(&Group::empty(old_path).id);
                 }
+                slugs.insert(group.id.clone(), group.public_id().to_string());
+            }
+            ChangeKind::Deleted => {
+                slugs.remove(&Group::empty(entry.path()).id);
+            }
+        }
+    }
 
-                (FileKind::Other, _) => (),
+    let mut owners = BTreeMap::new();
+    for (path, slug) in slugs.iter().collect::<BTreeMap<_, _>>() {
+        if let Some(first) = owners.insert(slug, path) {
+            return Err(MetadataError::SlugConflict {
+                slug: slug.to_owned(),
+                first: first.to_string(),
+                second: path.to_string(),
             }
+            .into());
         }
+    }
+
+    Ok(())
+}
+
+/// 本批次的组配置文件，跳过无法读取内容的条目，由 [`Persistable::persist`] 记录到 [`SyncReport`]
+fn readable_groups(entries: &[GitFileEntry]) -> impl Iterator<Item = &GitFileEntry> {
+    entries
+        .iter()
+        .filter(|e| e.file_kind() == FileKind::Group && e.skip_reason().is_none())
+}
 
-        storage.commit().await?;
-        Ok(())
+/// 渲染组说明文件，Front Matter 可有可无，存在时忽略
+async fn render_readme<R: Renderer>(renderer: &R, content: &str) -> Result<String, Error> {
+    renderer.render(FrontMatter::strip(content)).await
+}
+
+/// 计算本批次处理完成后存在的各组是否公开，`stored` 为已存储的组
+async fn final_visibility(
+    entries: &[GitFileEntry],
+    repo: &GitClient,
+    mut stored: HashMap<GroupPath, bool>,
+) -> Result<HashMap<GroupPath, bool>, Error> {
+    for entry in entries.iter().filter(|e| e.file_kind() == FileKind::Group) {
+        if entry.change_kind() == ChangeKind::Deleted {
+            stored.remove(&GroupPath::from(entry.path()));
+        }
+        if let Some(old_path) = entry.renamed_from() {
+            stored.remove(&GroupPath::from(old_path));
+        }
     }
+    for entry in readable_groups(entries).filter(|e| e.change_kind() != ChangeKind::Deleted) {
+        let group = Group::new(entry.path(), repo.load_file(entry.id()).await?)?;
+        stored.insert(group.id, group.public);
+    }
+
+    Ok(stored)
+}
+
+/// 将附件中仓库内的相对链接解析为相对仓库根目录的路径，外部链接原样保留
+///
+/// `commit` 中不存在的文件照常记录并警告，超出仓库根目录的链接不记录并警告。
+/// 目前没有资源文件的访问地址，仓库内的附件保存为仓库路径。解析后指向同一文件的链接只保留第一个。
+async fn resolve_attachments(
+    report: &mut SyncReport,
+    repo: &GitClient,
+    commit: &str,
+    path: &Path,
+    attachments: Vec<Attachment>,
+) -> Result<Vec<Attachment>, Error> {
+    let mut resolved: Vec<Attachment> = Vec::new();
+    let mut local = Vec::new();
+    for mut attachment in attachments {
+        if !is_external(&attachment.url_or_path) {
+            let Some(file) = attachment_path(path, &attachment.url_or_path) else {
+                let reason = format!(
+                    "attachment `{}` points outside the repository, ignored",
+                    attachment.url_or_path
+                );
+                tracing::warn!(path = %path.display(), reason = %reason, "attachment skipped");
+                report.warn(path, reason);
+                continue;
+            };
+            attachment.url_or_path = file.to_string_lossy().into_owned();
+            if !local.contains(&file) {
+                local.push(file);
+            }
+        }
+        if !resolved
+            .iter()
+            .any(|a| a.url_or_path == attachment.url_or_path)
+        {
+            resolved.push(attachment);
+        }
+    }
+    if local.is_empty() {
+        return Ok(resolved);
+    }
+
+    let found = repo.entries_at(commit, local.clone()).await?;
+    for file in local
+        .iter()
+        .filter(|f| !found.iter().any(|e| e.path() == *f))
+    {
+        let reason = format!("attachment `{}` not found in {commit}", file.display());
+        tracing::warn!(path = %path.display(), reason = %reason, "missing attachment");
+        report.warn(path, reason);
+    }
+    Ok(resolved)
+}
+
+/// `commit` 中 `group` 目录的组配置，没有组配置或无法解析时为 `None`
+async fn group_at(
+    repo: &GitClient,
+    commit: &str,
+    group: &GroupPath,
+) -> Result<Option<Group>, Error> {
+    let dir = PathBuf::from(group.as_str());
+    let file = repo
+        .with_tree(commit, move |load| {
+            FileClassifier::GROUP_FILE_NAMES.iter().find_map(|name| {
+                let path = dir.join(name);
+                load(&path).map(|content| (path, content))
+            })
+        })
+        .await?;
+
+    Ok(file.and_then(|(path, content)| Group::new(path, content).ok()))
+}
+
+/// 所在组不允许原样输出 HTML 时的警告
+pub(super) fn raw_html_denied(group: &GroupPath) -> String {
+    format!(
+        "`render: false` ignored, group `{group}` does not set `allow_raw_html: true`, rendered as Markdown"
+    )
+}
+
+/// 计算本批次处理完成后各组的 [`GroupSettings`]。
+///
+/// 已存储的组改变设置时返回错误，组内未变更的文章无法在增量同步中重新加密、解密或生成 slug，需要重建。
+/// 本批次删除的组保留已存储的设置，用于确定被删除文章的 slug。
+async fn final_settings(
+    entries: &[GitFileEntry],
+    repo: &GitClient,
+    stored: HashMap<GroupPath, GroupSettings>,
+) -> Result<HashMap<GroupPath, GroupSettings>, Error> {
+    let mut settings = stored.clone();
+
+    for entry in readable_groups(entries) {
+        if let ChangeKind::Added | ChangeKind::Modified = entry.change_kind() {
+            let group = Group::new(entry.path(), repo.load_file(entry.id()).await?)?;
+            settings.insert(group.id.clone(), group.settings());
+        }
+    }
+
+    let changed = settings
+        .iter()
+        .any(|(id, s)| stored.get(id).is_some_and(|stored| stored != s));
+    if changed {
+        return Err(Error::Custom(
+            "Changing `encrypted` or `slug_strategy` of an existing group requires a rebuild",
+        ));
+    }
+
+    Ok(settings)
 }
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    content::{ArticleBuilder, FrontMatter, Group, GroupPath, RawHtml, Renderer, expand_includes},
+    error::Error,
+    git_client::GitClient,
+};
+
+use super::{
+    ContentLimits, ReportItem, SyncReport, Verdict,
+    persist::{check_front_matter, orphan_reason, raw_html_denied},
+};
+
+/// 预览的文章内容来源
+#[derive(Debug, Clone)]
+pub enum PreviewSource {
+    /// 读取 `rev` 指向的提交中的文件
+    Repo { rev: String, path: PathBuf },
+    /// 直接给出的 Markdown，`path` 用于确定所属组和 slug，引用片段从 `rev` 指向的提交中读取
+    Inline {
+        rev: String,
+        path: PathBuf,
+        content: String,
+    },
+}
+
+impl PreviewSource {
+    fn rev(&self) -> &str {
+        match self {
+            Self::Repo { rev, .. } | Self::Inline { rev, .. } => rev,
+        }
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            Self::Repo { path, .. } | Self::Inline { path, .. } => path,
+        }
+    }
+}
+
+/// 解析后的 Front Matter
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PreviewFrontMatter {
+    pub title: String,
+    /// 渲染后的摘要
+    pub summary: String,
+    /// RFC 3339 格式，不带偏移的时间已按 `GITNOTE_TIMEZONE` 解析
+    #[schema(example = "2024-01-01T00:00:00+08:00")]
+    pub datetime: String,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    pub canonical: Option<String>,
+    pub public: Option<bool>,
+    pub cover: Option<String>,
+}
+
+/// 文章预览结果
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ArticlePreview {
+    pub path: String,
+    /// 所属组的目录路径，仓库根目录为空字符串
+    pub group: String,
+    pub slug: String,
+    pub front_matter: PreviewFrontMatter,
+    /// 与同步报告的 `warnings` 相同
+    pub warnings: Vec<ReportItem>,
+    /// 渲染后的正文
+    pub html: String,
+}
+
+/// 按同步时的规则解析并渲染单个文件，不写入任何数据
+///
+/// 所属组和 slug 按 `rev` 指向的提交中的组配置确定。内容被跳过或处理失败时返回 [`Error::Unprocessable`]，
+/// 原因与同步报告中的相同；提交或文件不存在、渲染失败等与内容无关的错误原样返回。
+pub async fn preview_article<R: Renderer>(
+    repo: &GitClient,
+    renderer: &R,
+    limits: &ContentLimits,
+    source: PreviewSource,
+) -> Result<ArticlePreview, Error> {
+    let path = source.path().to_path_buf();
+    let commit = repo.resolve(source.rev()).await?;
+    let bytes = match source {
+        PreviewSource::Repo { path, .. } => repo.load_file_at_ref(&commit, path).await?,
+        PreviewSource::Inline { content, .. } => content.into_bytes(),
+    };
+    let item = |reason: String| ReportItem {
+        path: path.to_string_lossy().into_owned(),
+        reason,
+        suggestions: Vec::new(),
+    };
+    let unprocessable = |reason: String| Error::Unprocessable(item(reason));
+
+    let mut report = SyncReport::default();
+    match limits.check(&bytes) {
+        Verdict::Accept => (),
+        Verdict::Warn(reason) => report.warn(&path, reason),
+        Verdict::Skip(reason) => return Err(unprocessable(reason)),
+    }
+
+    let content = String::from_utf8_lossy(&bytes).into_owned();
+    if !FrontMatter::is_present(&content) {
+        return Err(unprocessable("no front matter, not published".to_string()));
+    }
+    let groups = groups_at(repo, &commit).await?;
+    let strategies = groups
+        .iter()
+        .map(|(id, g)| (id.to_owned(), g.slug_strategy))
+        .collect::<HashMap<_, _>>();
+    let schema = groups
+        .get(ArticleBuilder::with_strategies(&path, &strategies).group())
+        .and_then(Group::front_matter_schema);
+
+    check_front_matter(
+        &mut report,
+        &path,
+        &content,
+        schema.as_ref(),
+        limits.strict_metadata,
+    )
+    .map_err(|e| unprocessable(e.to_string()))?;
+
+    let expanded = {
+        let file = path.clone();
+        repo.with_tree(&commit, move |load| expand_includes(&file, &content, load))
+            .await?
+            .map_err(|e| unprocessable(e.to_string()))?
+    };
+    let mut builder = ArticleBuilder::with_strategies(&path, &strategies).timezone(repo.timezone());
+    if FrontMatter::requests_raw_html(&expanded.content) {
+        let policy = groups
+            .get(builder.group())
+            .map_or(RawHtml::Denied, Group::raw_html);
+        if policy == RawHtml::Denied {
+            report.warn(&path, raw_html_denied(builder.group()));
+        }
+        builder = builder.raw_html(policy);
+    }
+    let article = builder
+        .content(expanded.content)
+        .build_with_renderer(renderer)
+        .await
+        .map_err(|e| match e {
+            Error::Serde(_) | Error::Metadata(_) | Error::Custom(_) => {
+                Error::Unprocessable(ReportItem {
+                    suggestions: e.suggestions().to_vec(),
+                    ..item(e.to_string())
+                })
+            }
+            e => e,
+        })?;
+
+    if !groups.contains_key(&article.group) {
+        report.warn(&path, orphan_reason(&article.group));
+    }
+
+    let frontmatter = article.frontmatter;
+    Ok(ArticlePreview {
+        path: path.to_string_lossy().into_owned(),
+        group: article.group.into(),
+        slug: article.slug,
+        front_matter: PreviewFrontMatter {
+            title: frontmatter.title,
+            summary: frontmatter.summary,
+            datetime: frontmatter.datetime.to_rfc3339(),
+            tags: frontmatter.tags,
+            aliases: frontmatter.aliases,
+            canonical: frontmatter.canonical,
+            public: frontmatter.public,
+            cover: frontmatter.cover,
+        },
+        warnings: report.warnings,
+        html: article.rendered_content,
+    })
+}
+
+/// `commit` 中可以解析的组配置，键为组目录路径
+pub(super) async fn groups_at(
+    repo: &GitClient,
+    commit: &str,
+) -> Result<HashMap<GroupPath, Group>, Error> {
+    let paths = repo.group_files(commit).await?;
+    let files = repo
+        .with_tree(commit, move |load| {
+            paths
+                .into_iter()
+                .filter_map(|p| load(&p).map(|content| (p, content)))
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+    Ok(files
+        .into_iter()
+        .filter_map(|(path, content)| Group::new(path, content).ok())
+        .map(|g| (g.id.clone(), g))
+        .collect())
+}
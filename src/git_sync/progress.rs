@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::git_client::{ChangeKind, GitFileEntry};
+
+/// 接收 [`EntryProgress`] 的通道
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<EntryProgress>;
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryOutcome {
+    Ok,
+    Warning,
+    Skipped,
+    Failed,
+}
+
+impl EntryOutcome {
+    /// 与序列化结果相同的小写名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warning => "warning",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// 单个文件处理完成时发出的进度事件
+///
+/// 事件在提交前发出，只表示该文件的处理结果，是否写入以同步的最终结果为准。
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EntryProgress {
+    pub path: String,
+    /// 变更类型：`added`、`modified` 或 `deleted`
+    pub action: &'static str,
+    pub outcome: EntryOutcome,
+    /// 警告、跳过或失败的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// 处理耗时，毫秒
+    pub duration_ms: u64,
+    /// 失败时面向作者的修改建议，流式同步中以单独的行输出
+    #[serde(skip)]
+    pub suggestions: Vec<String>,
+}
+
+impl EntryProgress {
+    pub fn new(
+        entry: &GitFileEntry,
+        outcome: EntryOutcome,
+        reason: Option<String>,
+        started: Instant,
+    ) -> Self {
+        let action = match entry.change_kind() {
+            ChangeKind::Added => "added",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Deleted => "deleted",
+        };
+
+        Self {
+            path: entry.path().to_string_lossy().into_owned(),
+            action,
+            outcome,
+            reason,
+            duration_ms: started.elapsed().as_millis() as u64,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// 附带修改建议
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+}
@@ -0,0 +1,556 @@
+use std::{path::Path, time::Instant};
+
+use serde::Serialize;
+
+use crate::{
+    git_client::{AsSummary, ChangeKind, DiffStat},
+    storage::{GroupStats, RetentionChange},
+};
+
+use super::PushKind;
+
+/// 同步过程中针对单个文件的记录
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ReportItem {
+    pub path: String,
+    pub reason: String,
+    /// 面向作者的修改建议，只有 Front Matter 解析失败时存在
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+/// 修改的文章的变更规模
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DiffItem {
+    pub path: String,
+    /// 内容过大未比较时不存在
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stat: Option<DiffStat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// 公开设置与所在组不同的文章
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct VisibilityItem {
+    pub path: String,
+    /// 文章 front matter 中的 `public`
+    pub public: bool,
+}
+
+/// 保留规则在一个组中下线和重新上线的文章数
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RetentionItem {
+    /// 组目录路径
+    pub group: String,
+    pub pruned: usize,
+    /// 放宽规则或组内文章减少后重新上线的文章数
+    pub restored: usize,
+}
+
+/// 超出配额的组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct QuotaItem {
+    /// 组目录路径
+    pub group: String,
+    /// 组内文章原文和资源文件的总字节数
+    pub used_bytes: u64,
+    /// 组配置中的 `quota_bytes`
+    pub quota_bytes: u64,
+}
+
+impl QuotaItem {
+    pub fn new(group: impl Into<String>, used_bytes: i64, quota_bytes: i64) -> Self {
+        Self {
+            group: group.into(),
+            used_bytes: used_bytes.max(0) as u64,
+            quota_bytes: quota_bytes.max(0) as u64,
+        }
+    }
+
+    /// 超出配额的组对应的记录，未超出时返回 [`None`]
+    pub fn from_stats(stats: &GroupStats) -> Option<Self> {
+        let quota = stats.quota_bytes.filter(|_| stats.over_quota())?;
+        Some(Self::new(
+            stats.group_id.as_str(),
+            stats.used_bytes(),
+            quota,
+        ))
+    }
+}
+
+impl std::fmt::Display for QuotaItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "group {} is over its size quota: {} of {} bytes",
+            self.group, self.used_bytes, self.quota_bytes
+        )
+    }
+}
+
+/// 渲染耗时超过阈值的文章
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SlowRenderItem {
+    pub path: String,
+    pub render_ms: u64,
+}
+
+/// 本次同步对应的 commit 范围
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct CommitRange {
+    pub before: String,
+    pub after: String,
+}
+
+/// 同步报告
+///
+/// 由 [`Persistable::persist`](super::Persistable::persist) 在处理过程中构建，
+/// 只记录实际写入的变更，被跳过的文件、处理失败的文件和警告单独列出。
+/// `push_kind`、`range` 和 `duration_ms` 由调用方通过 [`SyncReport::finish`] 补全。
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct SyncReport {
+    pub push_kind: Option<PushKind>,
+    pub range: Option<CommitRange>,
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+    /// 修改的文章的变更规模，与 `modified` 对应
+    pub diffs: Vec<DiffItem>,
+    pub groups_changed: Vec<String>,
+    /// 同步后被清理的孤立组
+    pub groups_removed: Vec<String>,
+    /// 同步后按组的保留规则下线和重新上线的文章数，没有变化的组不列出
+    pub retention: Vec<RetentionItem>,
+    /// 同步后超出配额的组，只作警告，严格模式下超出配额的同步整体失败
+    pub over_quota: Vec<QuotaItem>,
+    pub warnings: Vec<ReportItem>,
+    /// 写入的文章中公开设置与所在组不同的文章
+    pub visibility_overrides: Vec<VisibilityItem>,
+    /// 渲染耗时超过 `GITNOTE_SLOW_RENDER_MS` 的文章
+    pub slow_renders: Vec<SlowRenderItem>,
+    pub skipped: Vec<ReportItem>,
+    /// 处理失败、未写入的文件，可通过 `POST /api/repo/retry-failures` 重试
+    pub failed: Vec<ReportItem>,
+    /// 重新渲染的文章数
+    pub rendered: usize,
+    /// 内容和渲染器版本都未变化、复用已存储渲染结果的文章数
+    pub reused: usize,
+    /// 因临时数据库错误重新执行持久化的次数
+    pub retries: u32,
+    /// 重建前写入的备份文件名，未配置 `GITNOTE_BACKUP_DIR` 或跳过备份时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    pub duration_ms: u64,
+    /// 触发同步的请求 id，与响应头 `X-Request-Id` 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl SyncReport {
+    /// 记录已写入的文章变更
+    pub fn article(&mut self, change: ChangeKind, path: impl AsRef<Path>) {
+        let path = path.as_ref().to_string_lossy().into_owned();
+        match change {
+            ChangeKind::Added => self.added.push(path),
+            ChangeKind::Modified => self.modified.push(path),
+            ChangeKind::Deleted => self.removed.push(path),
+        }
+    }
+
+    /// 记录修改的文章的变更规模，`stat` 为 [`None`] 时表示内容过大未比较
+    pub fn diff(&mut self, path: impl AsRef<Path>, stat: Option<DiffStat>) {
+        self.diffs.push(DiffItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            note: stat.is_none().then(|| "too large to diff".to_string()),
+            stat,
+        });
+    }
+
+    /// 记录发生变更的组
+    pub fn group(&mut self, id: impl Into<String>) {
+        let id = id.into();
+        if !self.groups_changed.contains(&id) {
+            self.groups_changed.push(id);
+        }
+    }
+
+    /// 按组累计保留规则下线和重新上线的文章
+    pub fn retention(&mut self, changes: &[RetentionChange]) {
+        for change in changes {
+            let group = change.group_id.as_str();
+            let item = match self.retention.iter().position(|i| i.group == group) {
+                Some(i) => &mut self.retention[i],
+                None => {
+                    self.retention.push(RetentionItem {
+                        group: group.to_owned(),
+                        pruned: 0,
+                        restored: 0,
+                    });
+                    self.retention.last_mut().expect("just pushed")
+                }
+            };
+            if change.pruned {
+                item.pruned += 1;
+            } else {
+                item.restored += 1;
+            }
+        }
+    }
+
+    /// 记录警告
+    pub fn warn(&mut self, path: impl AsRef<Path>, reason: impl Into<String>) {
+        self.warnings.push(ReportItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            reason: reason.into(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    /// 记录公开设置与所在组不同的文章，`public` 为文章自身的设置
+    pub fn visibility(&mut self, path: impl AsRef<Path>, public: bool) {
+        self.visibility_overrides.push(VisibilityItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            public,
+        });
+    }
+
+    /// 记录一篇文章是重新渲染还是复用了已有的渲染结果
+    pub fn render(&mut self, reused: bool) {
+        if reused {
+            self.reused += 1;
+        } else {
+            self.rendered += 1;
+        }
+    }
+
+    /// 记录渲染耗时超过阈值的文章
+    pub fn slow_render(&mut self, path: impl AsRef<Path>, render_ms: u64) {
+        self.slow_renders.push(SlowRenderItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            render_ms,
+        });
+    }
+
+    /// 记录被跳过的文件
+    pub fn skip(&mut self, path: impl AsRef<Path>, reason: impl Into<String>) {
+        self.skipped.push(ReportItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            reason: reason.into(),
+            suggestions: Vec::new(),
+        });
+    }
+
+    /// 记录处理失败的文件
+    pub fn fail(&mut self, path: impl AsRef<Path>, reason: impl Into<String>) {
+        self.fail_with_suggestions(path, reason, Vec::new());
+    }
+
+    /// 记录处理失败的文件，附带修改建议
+    pub fn fail_with_suggestions(
+        &mut self,
+        path: impl AsRef<Path>,
+        reason: impl Into<String>,
+        suggestions: Vec<String>,
+    ) {
+        self.failed.push(ReportItem {
+            path: path.as_ref().to_string_lossy().into_owned(),
+            reason: reason.into(),
+            suggestions,
+        });
+    }
+
+    /// 是否没有任何变更规模、警告、公开设置覆盖、慢渲染、跳过、失败、清理、保留规则的变化、超出配额的组或重试
+    pub fn is_empty(&self) -> bool {
+        self.over_quota.is_empty()
+            && self.diffs.is_empty()
+            && self.warnings.is_empty()
+            && self.visibility_overrides.is_empty()
+            && self.slow_renders.is_empty()
+            && self.skipped.is_empty()
+            && self.failed.is_empty()
+            && self.groups_removed.is_empty()
+            && self.retention.is_empty()
+            && self.retries == 0
+            && self.backup.is_none()
+    }
+
+    /// 合并另一批次的报告，`push_kind`、`range`、`duration_ms` 和 `request_id` 保持不变
+    pub fn merge(&mut self, other: SyncReport) {
+        self.added.extend(other.added);
+        self.modified.extend(other.modified);
+        self.removed.extend(other.removed);
+        self.diffs.extend(other.diffs);
+        for id in other.groups_changed {
+            self.group(id);
+        }
+        self.groups_removed.extend(other.groups_removed);
+        for item in other.retention {
+            match self.retention.iter_mut().find(|i| i.group == item.group) {
+                Some(i) => {
+                    i.pruned += item.pruned;
+                    i.restored += item.restored;
+                }
+                None => self.retention.push(item),
+            }
+        }
+        for item in other.over_quota {
+            // 后一批次的统计较新
+            self.over_quota.retain(|i| i.group != item.group);
+            self.over_quota.push(item);
+        }
+        self.warnings.extend(other.warnings);
+        self.visibility_overrides.extend(other.visibility_overrides);
+        self.slow_renders.extend(other.slow_renders);
+        self.skipped.extend(other.skipped);
+        self.failed.extend(other.failed);
+        self.rendered += other.rendered;
+        self.reused += other.reused;
+        self.retries += other.retries;
+    }
+
+    /// 补全 push 类型、commit 范围和耗时
+    pub fn finish(
+        mut self,
+        push_kind: PushKind,
+        before: impl Into<String>,
+        after: impl Into<String>,
+        started: Instant,
+    ) -> Self {
+        self.push_kind = Some(push_kind);
+        self.range = Some(CommitRange {
+            before: before.into(),
+            after: after.into(),
+        });
+        self.duration_ms = started.elapsed().as_millis() as u64;
+        self
+    }
+
+    /// 记录触发同步的请求 id
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+impl AsSummary for SyncReport {
+    /// 超出配额的组以大写的 `WARNING` 开头列在最前面；
+    /// 其余只输出修改的文章的变更规模、警告、公开设置与所在组不同的文章、被跳过的文件、处理失败的文件、被清理的组
+    /// 和保留规则下线的文章数，每条记录一行；失败文件的修改建议列在该文件下方；
+    /// 有文章复用渲染结果时列出重新渲染和复用的文章数；渲染耗时超过阈值的文章合并为一行，只列出最慢的一篇；
+    /// 然后列出因临时数据库错误重试的次数，最后列出重建前写入的备份：
+    ///
+    /// ```text
+    /// WARNING group group-a is over its size quota: 5300000 of 5000000 bytes
+    /// ~ group-a/post.md (+120 −8 words)
+    /// ~ group-a/log.md (too large to diff)
+    /// warning group-a/big.md: size 600000 bytes exceeds soft limit 524288 bytes
+    /// private group-a/draft.md (group is public)
+    /// skipped group-a/huge.md: size 3000000 bytes exceeds hard limit 2097152 bytes
+    /// failed group-a/bad.md: included file `snippets/x.md` not found: group-a/bad.md
+    /// failed group-a/draft.md: missing field `title` at line 1 column 1
+    ///   hint: add `title:` to the front matter
+    /// removed group rust
+    /// retention journal: 3 pruned, 1 restored
+    /// render: 2 rendered, 118 reused
+    /// slow render: 2 articles over threshold, slowest group-a/long.md (6120 ms)
+    /// retried 1 time after transient database errors
+    /// backup gitnote-backup-20240601T080000.000Z.jsonl.gz
+    /// ```
+    fn as_summary(&self) -> String {
+        let diffs = self.diffs.iter().map(|i| match (&i.stat, &i.note) {
+            (Some(stat), _) => format!(
+                "~ {} (+{} \u{2212}{} words)",
+                i.path, stat.words_added, stat.words_removed
+            ),
+            (None, note) => format!("~ {} ({})", i.path, note.as_deref().unwrap_or_default()),
+        });
+        let warnings = self
+            .warnings
+            .iter()
+            .map(|i| format!("warning {}: {}", i.path, i.reason));
+        let overrides = self.visibility_overrides.iter().map(|i| {
+            let (article, group) = if i.public {
+                ("public", "private")
+            } else {
+                ("private", "public")
+            };
+            format!("{article} {} (group is {group})", i.path)
+        });
+        let skipped = self
+            .skipped
+            .iter()
+            .map(|i| format!("skipped {}: {}", i.path, i.reason));
+        let failed = self.failed.iter().flat_map(|i| {
+            std::iter::once(format!("failed {}: {}", i.path, i.reason))
+                .chain(i.suggestions.iter().map(|s| format!("  hint: {s}")))
+        });
+
+        let removed = self
+            .groups_removed
+            .iter()
+            .map(|id| format!("removed group {id}"));
+        let retention = self.retention.iter().map(|i| {
+            format!(
+                "retention {}: {} pruned, {} restored",
+                i.group, i.pruned, i.restored
+            )
+        });
+
+        // 只在有复用时列出，全部重新渲染是默认情况
+        let render = (self.reused > 0)
+            .then(|| format!("render: {} rendered, {} reused", self.rendered, self.reused));
+
+        let retries = (self.retries > 0).then(|| {
+            format!(
+                "retried {} time{} after transient database errors",
+                self.retries,
+                if self.retries == 1 { "" } else { "s" }
+            )
+        });
+
+        let slow = self
+            .slow_renders
+            .iter()
+            .max_by_key(|i| i.render_ms)
+            .map(|i| {
+                let count = self.slow_renders.len();
+                format!(
+                    "slow render: {count} article{} over threshold, slowest {} ({} ms)",
+                    if count == 1 { "" } else { "s" },
+                    i.path,
+                    i.render_ms
+                )
+            });
+
+        let over_quota = self.over_quota.iter().map(|i| format!("WARNING {i}"));
+
+        over_quota
+            .chain(diffs)
+            .chain(warnings)
+            .chain(overrides)
+            .chain(skipped)
+            .chain(failed)
+            .chain(removed)
+            .chain(retention)
+            .chain(render)
+            .chain(slow)
+            .chain(retries)
+            .chain(self.backup.as_ref().map(|file| format!("backup {file}")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_summary() {
+        let mut report = SyncReport::default();
+        assert!(report.is_empty());
+        assert_eq!(report.as_summary(), "");
+
+        report.diff(
+            "a/post.md",
+            Some(DiffStat {
+                lines_added: 10,
+                lines_removed: 2,
+                words_added: 120,
+                words_removed: 8,
+            }),
+        );
+        report.diff("a/log.md", None);
+        report.warn("a/big.md", "too big");
+        report.visibility("a/draft.md", false);
+        report.skip("a/bin.md", "binary");
+        report.fail_with_suggestions(
+            "a/bad.md",
+            "bad yaml",
+            vec!["add `title:` to the front matter".to_string()],
+        );
+        report.groups_removed.push("rust".to_string());
+        report.retention(&[
+            RetentionChange {
+                group_id: "journal".into(),
+                slug: "old".into(),
+                pruned: true,
+            },
+            RetentionChange {
+                group_id: "journal".into(),
+                slug: "older".into(),
+                pruned: true,
+            },
+            RetentionChange {
+                group_id: "journal".into(),
+                slug: "back".into(),
+                pruned: false,
+            },
+        ]);
+
+        report.render(false);
+        report.render(true);
+        report.render(true);
+        report.slow_render("a/long.md", 6120);
+        report.slow_render("a/longer.md", 5100);
+        report.retries = 2;
+        report.backup = Some("gitnote-backup-1.jsonl.gz".to_string());
+        report.over_quota.push(QuotaItem::new("a", 5300, 5000));
+
+        assert!(!report.is_empty());
+        assert_eq!(
+            report.as_summary(),
+            "WARNING group a is over its size quota: 5300 of 5000 bytes\n\
+             ~ a/post.md (+120 \u{2212}8 words)\n~ a/log.md (too large to diff)\n\
+             warning a/big.md: too big\nprivate a/draft.md (group is public)\nskipped a/bin.md: binary\nfailed a/bad.md: bad yaml\n  hint: add `title:` to the front matter\nremoved group rust\n\
+             retention journal: 2 pruned, 1 restored\n\
+             render: 1 rendered, 2 reused\n\
+             slow render: 2 articles over threshold, slowest a/long.md (6120 ms)\n\
+             retried 2 times after transient database errors\n\
+             backup gitnote-backup-1.jsonl.gz"
+        );
+    }
+
+    #[test]
+    fn test_report_json() {
+        let mut report = SyncReport::default();
+        report.article(ChangeKind::Added, "a/new.md");
+        report.article(ChangeKind::Modified, "a/old.md");
+        report.article(ChangeKind::Deleted, "a/gone.md");
+        report.diff(
+            "a/old.md",
+            Some(DiffStat {
+                lines_added: 3,
+                lines_removed: 1,
+                words_added: 5,
+                words_removed: 2,
+            }),
+        );
+        report.group("a");
+        report.group("a");
+
+        let report = report.finish(PushKind::Sync, "before", "after", Instant::now());
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["push_kind"], "sync");
+        assert_eq!(json["range"]["before"], "before");
+        assert_eq!(json["range"]["after"], "after");
+        assert_eq!(json["added"], serde_json::json!(["a/new.md"]));
+        assert_eq!(json["modified"], serde_json::json!(["a/old.md"]));
+        assert_eq!(json["removed"], serde_json::json!(["a/gone.md"]));
+        assert_eq!(
+            json["diffs"],
+            serde_json::json!([{
+                "path": "a/old.md",
+                "stat": {"lines_added": 3, "lines_removed": 1, "words_added": 5, "words_removed": 2},
+            }])
+        );
+        assert_eq!(json["groups_changed"], serde_json::json!(["a"]));
+        assert!(json["duration_ms"].is_u64());
+        assert!(json.get("request_id").is_none());
+
+        let json = serde_json::to_value(report.with_request_id("abc")).unwrap();
+        assert_eq!(json["request_id"], "abc");
+    }
+}
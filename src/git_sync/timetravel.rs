@@ -0,0 +1,119 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::{
+    content::{
+        Article, ArticleBuilder, Content, FrontMatter, Group, Renderer, expand_includes, merge_tags,
+    },
+    error::Error,
+    git_client::{ChangeKind, FileKind, GitClient},
+};
+
+use super::{ContentLimits, Verdict, preview::groups_at};
+
+/// 时间旅行列表最多返回的文章数
+pub const TIMETRAVEL_MAX_ARTICLES: usize = 200;
+
+/// 单个时间旅行请求最多渲染的次数，命中缓存的不计入
+pub const TIMETRAVEL_RENDER_CAP: usize = 20;
+
+/// 某个提交中的公开文章，正文尚未渲染
+pub struct SnapshotArticle {
+    /// 该提交中文章所在组的配置
+    pub group: Arc<Group>,
+    pub slug: String,
+    /// 摘要为 Markdown 原文
+    pub front_matter: FrontMatter,
+    /// 合并所在组的默认标签后的标签
+    pub tags: Vec<String>,
+    /// 文件最近一次修改的提交时间
+    pub updated_at: DateTime<FixedOffset>,
+    builder: ArticleBuilder<Content>,
+}
+
+impl SnapshotArticle {
+    /// 按同步时的规则渲染正文和摘要
+    pub async fn render<R: Renderer>(self, renderer: &R) -> Result<Article, Error> {
+        self.builder.build_with_renderer(renderer).await
+    }
+}
+
+/// 读取 `commit` 中的公开文章，不读取数据库
+///
+/// 文件筛选、大小检查、引用展开以及组和 slug 的推断与同步相同。文章是否公开按该提交中的组配置判断，
+/// 没有组配置的文章不返回；同一 slug 以最后处理的文件为准。结果按更新时间从新到旧排列。
+pub async fn snapshot_articles(
+    repo: &GitClient,
+    commit: &str,
+    limits: &ContentLimits,
+) -> Result<Vec<SnapshotArticle>, Error> {
+    let groups = groups_at(repo, commit)
+        .await?
+        .into_iter()
+        .map(|(id, g)| (id, Arc::new(g)))
+        .collect::<HashMap<_, _>>();
+    let strategies = groups
+        .iter()
+        .map(|(id, g)| (id.to_owned(), g.slug_strategy))
+        .collect::<HashMap<_, _>>();
+
+    let mut articles = BTreeMap::new();
+    for entry in repo.snapshot(commit).await?.into_iter().filter(|e| {
+        e.file_kind() == FileKind::Markdown
+            && e.change_kind() != ChangeKind::Deleted
+            && e.skip_reason().is_none()
+    }) {
+        let bytes = repo.load_bytes(entry.id()).await?;
+        if let Verdict::Skip(_) = limits.check(&bytes) {
+            continue;
+        }
+        let content = String::from_utf8_lossy(&bytes).into_owned();
+        if !FrontMatter::is_present(&content) {
+            continue;
+        }
+
+        let mut builder = ArticleBuilder::with_strategies(entry.path(), &strategies)
+            .timezone(repo.timezone())
+            .updated_at(entry.timestamp());
+        let Some(group) = groups.get(builder.group()).cloned() else {
+            continue;
+        };
+        let file = entry.path().to_path_buf();
+        let Ok(expanded) = repo
+            .with_tree(commit, move |load| expand_includes(&file, &content, load))
+            .await?
+        else {
+            continue;
+        };
+        if FrontMatter::requests_raw_html(&expanded.content) {
+            builder = builder.raw_html(group.raw_html());
+        }
+        let builder = builder.content(expanded.content);
+        let Ok(front_matter) = builder.front_matter() else {
+            continue;
+        };
+        if !front_matter.public.unwrap_or(group.public) {
+            continue;
+        }
+
+        articles.insert(
+            builder.slug().to_owned(),
+            SnapshotArticle {
+                slug: builder.slug().to_owned(),
+                tags: merge_tags(&front_matter.tags, &group.default_tags),
+                updated_at: entry.timestamp(),
+                group,
+                front_matter,
+                builder,
+            },
+        );
+    }
+
+    let mut articles = articles.into_values().collect::<Vec<_>>();
+    articles.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then(a.slug.cmp(&b.slug)));
+    Ok(articles)
+}
@@ -1,29 +1,224 @@
 pub mod api;
+pub mod cdn;
+pub mod comments;
+pub mod config;
 pub mod content;
+pub mod digest;
 pub mod error;
 pub mod git_client;
 pub mod git_sync;
+pub mod links;
+pub mod net;
 pub mod render;
 pub mod state;
 pub mod storage;
+pub mod webmention;
 
-use std::env;
+use std::{env, path::Path};
 
 use tracing_subscriber::{EnvFilter, fmt::time::ChronoLocal};
 
-pub const REPO_PATH: &str = env!("REPO_PATH");
+use crate::config::Config;
+
+/// 单元测试使用的仓库路径
+#[cfg(test)]
+pub const REPO_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/localtest.git");
 
 pub async fn run() {
-    tracing_subscriber::fmt()
+    init_tracing();
+
+    let config = Config::from_env().unwrap_or_else(|e| panic!("Invalid configuration: {e}"));
+    let fingerprint = config.fingerprint();
+    warn_default_timezone();
+
+    if let Some(key) = &config.content_key {
+        storage::ContentCipher::from_base64(key)
+            .unwrap_or_else(|e| panic!("Invalid GITNOTE_CONTENT_KEY: {e}"))
+            .install();
+    }
+
+    bootstrap_repo(&config.repo_path).await;
+
+    let app = {
+        let db = storage::Backend::connect_with_timeout(
+            &config.database_url,
+            config.query_limits.timeout,
+        )
+        .await
+        .expect("Failed to connect to database");
+        let schema_ready = prepare_schema(&db, config.auto_migrate).await;
+        let backend = if schema_ready {
+            comments::spawn_from_env(db.clone());
+            links::spawn_from_env(db.clone());
+            digest::spawn_from_env(db.clone(), &config.site_url, config.timezone);
+            db
+        } else {
+            // 查询接口使用空的内存存储，返回空结果而不是 500
+            storage::MemoryStorage::new().into()
+        };
+        let webmentions = schema_ready
+            .then(|| webmention::spawn_from_env(backend.clone(), &config.site_url))
+            .flatten();
+        state::AppState::new(
+            backend,
+            render::GithubAPiRenderer::new(&config.render_token),
+            &config.repo_path,
+        )
+        .with_limits(config.limits)
+        .with_classifier(
+            git_client::FileClassifier::new(&config.article_extensions)
+                .with_readme_names(&config.readme_names)
+                .with_allowed_hidden_dirs(&config.allowed_hidden_dirs)
+                .with_attachment_extensions(&config.attachment_extensions),
+        )
+        .with_timezone(config.timezone)
+        .with_plain_summary_chars(config.plain_summary_chars)
+        .with_strict_sync(config.strict_sync)
+        .with_namespace_anchors(config.namespace_anchors)
+        .with_heading_ids(config.heading_ids)
+        .with_site_url(&config.site_url)
+        .with_default_og_image(config.default_og_image.as_deref())
+        .with_feed_entries(config.feed_entries)
+        .with_schema_ready(schema_ready)
+        .with_sync_debounce(config.sync_debounce)
+        .with_edit_url(config.edit_url_template)
+        .with_permalink(config.permalink)
+        .with_default_license(config.default_license)
+        .with_webmentions(webmentions)
+        .with_cache_tag_header(config.cache_tag_header)
+        .with_cdn_purge(config.cdn_purge.map(cdn::spawn))
+        .with_backup(config.backup)
+        .with_render_breaker(config.render_failure_threshold, config.render_cooldown)
+        .with_sync_log_size(config.sync_log_size)
+        .with_admin_token(config.admin_token.as_deref())
+        .with_hook_allowlist(config.hook_allowlist)
+        .with_query_limits(config.query_limits)
+        .with_config_fingerprint(fingerprint)
+    };
+
+    let info = api::VersionInfo::new(&app);
+    tracing::info!(
+        version = info.version,
+        commit = info.commit,
+        built_at = info.built_at,
+        renderer = info.features.renderer,
+        backend = info.features.backend,
+        backends = ?info.features.backends,
+        swagger_ui = info.features.swagger_ui,
+        config_fingerprint = info.config_fingerprint,
+        "starting gitnote"
+    );
+
+    let compression = config.compression.then_some(config.compression_min_bytes);
+    api::run_server(app, config.listen_addr, compression).await
+}
+
+/// 启动时检查数据库是否已迁移，返回是否可以读写数据库
+///
+/// `auto_migrate` 为 `true` 时先按顺序执行尚未执行的迁移。未迁移时记录缺失的表和列，
+/// 同步接口返回 503，执行迁移后需要重启服务。
+///
+/// - Panics
+///
+/// 查询数据库或执行迁移失败时 panic
+async fn prepare_schema(db: &storage::Backend, auto_migrate: bool) -> bool {
+    if auto_migrate {
+        let applied = db
+            .run_migrations()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to migrate database: {e}"));
+        if !applied.is_empty() {
+            tracing::info!(?applied, "database migrated");
+        }
+    }
+
+    let missing = db
+        .missing_schema()
+        .await
+        .unwrap_or_else(|e| panic!("Failed to check database schema: {e}"));
+    if missing.is_empty() {
+        return true;
+    }
+
+    tracing::warn!(
+        ?missing,
+        "database not migrated, sync is disabled until sql/ is applied and the service restarts; \
+         set GITNOTE_AUTO_MIGRATE=true to migrate on startup"
+    );
+    false
+}
+
+/// 未设置 `GITNOTE_TIMEZONE` 且系统时区不是 UTC 时记录警告
+///
+/// 不带偏移的文章时间和提交时间按 `GITNOTE_TIMEZONE` 解释，未设置时为 UTC，不再跟随系统时区（`TZ`）。
+/// 升级前依赖系统时区的部署需要设置 `GITNOTE_TIMEZONE`，否则这些时间会整体偏移。
+fn warn_default_timezone() {
+    if env::var_os("GITNOTE_TIMEZONE").is_some() {
+        return;
+    }
+    let offset = *chrono::Local::now().offset();
+    if offset.local_minus_utc() != 0 {
+        tracing::warn!(
+            %offset,
+            "GITNOTE_TIMEZONE is not set, times without an offset are parsed as UTC \
+             instead of the system timezone; set GITNOTE_TIMEZONE to keep the previous behavior"
+        );
+    }
+}
+
+/// 初始化日志输出
+///
+/// 日志级别由 `GITNOTE_LOG` 控制。`GITNOTE_LOG_FORMAT=json` 时每行输出一个 JSON 对象，
+/// 包含当前 span 及其上层 span 的字段（如同步的 `request_id` 和文件的 `path`），便于日志聚合；
+/// 其他取值或未设置时输出文本格式。
+fn init_tracing() {
+    let builder = tracing_subscriber::fmt()
         .with_target(false)
         .with_timer(ChronoLocal::new("%Y-%m-%d %H:%M:%S%.3f".to_string()))
-        .with_env_filter(EnvFilter::from_env("GITNOTE_LOG"))
-        .init();
+        .with_env_filter(EnvFilter::from_env("GITNOTE_LOG"));
 
-    let app = {
-        let db = storage::init_db_from_env().await;
-        state::AppState::new(db, render::GithubAPiRenderer::default(), REPO_PATH)
+    if env::var("GITNOTE_LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json")) {
+        builder.json().with_span_list(true).init();
+    } else {
+        builder.init();
+    }
+}
+
+/// 根据 `GITNOTE_REMOTE_URL` 准备本地镜像仓库，未设置时跳过。
+///
+/// 本地仓库不存在时执行镜像克隆，已存在时执行 remote update，并记录更新后的 HEAD。
+///
+/// - Panics
+///
+/// 克隆或更新失败时 panic，错误信息区分认证失败与网络失败
+async fn bootstrap_repo(repo_path: &Path) {
+    let Ok(remote_url) = env::var("GITNOTE_REMOTE_URL") else {
+        return;
     };
 
-    api::run_server(app).await
+    let result = tokio::task::spawn_blocking({
+        let remote_url = remote_url.clone();
+        let repo_path = repo_path.to_path_buf();
+        move || git_client::clone_or_fetch(&remote_url, repo_path)
+    })
+    .await
+    .expect("Repository bootstrap task panicked");
+
+    match result {
+        Ok((action, head)) => tracing::info!(
+            ?action,
+            head = head.as_deref().unwrap_or("unborn"),
+            "repository ready"
+        ),
+        Err(git_client::GitError::Auth(e)) => {
+            panic!("Authentication to remote `{remote_url}` failed: {e}")
+        }
+        Err(git_client::GitError::Network(e)) => {
+            panic!("Network failure while reaching remote `{remote_url}`: {e}")
+        }
+        Err(e) => panic!(
+            "Failed to prepare repository at `{}`: {e}",
+            repo_path.display()
+        ),
+    }
 }
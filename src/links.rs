@@ -0,0 +1,486 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use reqwest::{StatusCode, Url};
+use tokio::{
+    sync::Semaphore,
+    task::{JoinHandle, JoinSet},
+};
+
+use crate::{
+    error::Result,
+    net::{NON_PUBLIC, host_is_public, outbound_client},
+    render::percent_decode,
+    storage::{Attachment, Backend, Querier, Store},
+};
+
+/// 提取 Markdown 中的外部链接（http/https）。
+///
+/// 包括行内链接、引用式链接和自动链接，按出现顺序去重。
+/// Front Matter、代码块和图片地址不计入。
+pub fn extract_links(markdown: &str) -> Vec<String> {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+
+    let mut links: Vec<String> = Vec::new();
+    for event in parser {
+        let Event::Start(Tag::Link { dest_url, .. }) = event else {
+            continue;
+        };
+
+        let url = dest_url.trim();
+        if is_external(url) && !links.iter().any(|l| l == url) {
+            links.push(url.to_string());
+        }
+    }
+
+    links
+}
+
+/// 是否为 http/https 外部链接
+pub fn is_external(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+}
+
+/// 提取 Markdown 中指向附件的链接。
+///
+/// 链接目标去掉查询和片段后的扩展名在 `extensions` 中时计入，不区分大小写；
+/// 包括行内链接、引用式链接和自动链接，按出现顺序去重。
+/// Front Matter、代码块和图片地址不计入，http/https 以外的协议不计入。
+/// `url_or_path` 为链接目标原文，仓库内的相对链接见 [`attachment_path`]。
+pub fn extract_attachments(markdown: &str, extensions: &[String]) -> Vec<Attachment> {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+
+    let mut attachments: Vec<Attachment> = Vec::new();
+    let mut current: Option<Attachment> = None;
+    for event in parser {
+        match event {
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let target = dest_url.trim();
+                current = attachment_kind(target, extensions).map(|kind| Attachment {
+                    url_or_path: target.to_string(),
+                    kind,
+                    link_text: String::new(),
+                });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(attachment) = current.as_mut() {
+                    attachment.link_text.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(attachment) = current.as_mut() {
+                    attachment.link_text.push(' ');
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                let Some(mut attachment) = current.take() else {
+                    continue;
+                };
+                attachment.link_text = attachment.link_text.trim().to_string();
+                if !attachments
+                    .iter()
+                    .any(|a| a.url_or_path == attachment.url_or_path)
+                {
+                    attachments.push(attachment);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    attachments
+}
+
+/// 链接目标是附件时返回小写的扩展名
+fn attachment_kind(target: &str, extensions: &[String]) -> Option<String> {
+    let path = match Url::parse(target) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => url.path().to_string(),
+        Ok(_) => return None,
+        Err(_) => percent_decode(strip_suffix(target)).into_owned(),
+    };
+    let kind = Path::new(&path).extension()?.to_str()?.to_lowercase();
+    extensions.contains(&kind).then_some(kind)
+}
+
+/// 去掉链接目标中的查询和片段
+fn strip_suffix(target: &str) -> &str {
+    target.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// 计算仓库内的相对链接指向的文件路径，相对仓库根目录
+///
+/// `/` 开头时相对仓库根目录，否则相对 `article` 所在目录；路径按百分号编码解码。
+/// 外部链接、空路径和超出仓库根目录的路径返回 [`None`]。
+pub fn attachment_path(article: &Path, target: &str) -> Option<PathBuf> {
+    if Url::parse(target).is_ok() {
+        return None;
+    }
+    let target = percent_decode(strip_suffix(target));
+    let joined = match target.strip_prefix('/') {
+        Some(absolute) => PathBuf::from(absolute),
+        None => article.parent().unwrap_or(Path::new("")).join(&*target),
+    };
+
+    let mut path = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir => (),
+            Component::ParentDir if path.pop() => (),
+            _ => return None,
+        }
+    }
+
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// 链接检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkStatus {
+    /// 响应状态码，请求失败时为 [`None`]
+    pub status: Option<i32>,
+    /// 请求失败原因，如 `timeout`
+    pub error: Option<String>,
+}
+
+/// 链接检查配置
+#[derive(Debug, Clone)]
+pub struct CheckerOptions {
+    /// 每轮最多检查的链接数
+    pub per_round: usize,
+    /// 两轮检查之间的间隔
+    pub interval: Duration,
+    /// 每个域名同时进行的请求数
+    pub per_domain: usize,
+    /// 同一域名两次请求之间的最小间隔
+    pub domain_delay: Duration,
+    /// 单个请求的超时时间
+    pub timeout: Duration,
+    /// 是否允许请求回环、私有网段、链路本地等非公网地址，只用于本地测试
+    pub allow_private: bool,
+}
+
+impl Default for CheckerOptions {
+    /// 每小时检查 60 个链接，每个域名同时 2 个请求、间隔 1 秒，超时 10 秒，只请求公网地址
+    fn default() -> Self {
+        Self {
+            per_round: 60,
+            interval: Duration::from_secs(3600),
+            per_domain: 2,
+            domain_delay: Duration::from_secs(1),
+            timeout: Duration::from_secs(10),
+            allow_private: false,
+        }
+    }
+}
+
+/// 外部链接检查器
+///
+/// 先发送 HEAD 请求，请求失败或返回 4xx/5xx 时改用 GET 重试，部分站点不支持 HEAD。
+///
+/// 链接来自文章内容，未开启 [`CheckerOptions::allow_private`] 时与 webmention 一样只请求公网地址：
+/// IP 地址形式的非公网链接不发出请求，直接记为失败；域名只连接解析出的公网地址，重定向同样检查。
+pub struct LinkChecker {
+    client: reqwest::Client,
+    options: CheckerOptions,
+}
+
+impl LinkChecker {
+    pub fn new(options: CheckerOptions) -> Self {
+        let client = outbound_client(options.timeout, options.allow_private);
+
+        Self { client, options }
+    }
+
+    /// 检查单个链接
+    pub async fn probe(&self, url: &str) -> LinkStatus {
+        let public = Url::parse(url).is_ok_and(|u| host_is_public(&u));
+        if !self.options.allow_private && !public {
+            return LinkStatus {
+                status: None,
+                error: Some(NON_PUBLIC.to_string()),
+            };
+        }
+
+        let head = self.client.head(url).send().await;
+        let response = match head {
+            Ok(resp) if !is_broken(resp.status()) => Ok(resp),
+            _ => self.client.get(url).send().await,
+        };
+
+        match response {
+            Ok(resp) => LinkStatus {
+                status: Some(resp.status().as_u16() as i32),
+                error: None,
+            },
+            Err(e) => LinkStatus {
+                status: None,
+                error: Some(if e.is_timeout() {
+                    "timeout".to_string()
+                } else {
+                    e.to_string()
+                }),
+            },
+        }
+    }
+
+    /// 并发检查一批链接
+    ///
+    /// 按域名限流：同一域名最多 [`CheckerOptions::per_domain`] 个请求同时进行，
+    /// 每个请求完成后等待 [`CheckerOptions::domain_delay`] 再释放名额。
+    pub async fn check_all(self: &Arc<Self>, urls: Vec<String>) -> HashMap<String, LinkStatus> {
+        let mut domains: HashMap<String, Arc<Semaphore>> = HashMap::new();
+        let mut tasks = JoinSet::new();
+
+        for url in urls {
+            let domain = Url::parse(&url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_default();
+            let permits = domains
+                .entry(domain)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.options.per_domain)))
+                .clone();
+            let checker = self.clone();
+
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await.expect("semaphore closed");
+                let status = checker.probe(&url).await;
+                tokio::time::sleep(checker.options.domain_delay).await;
+                (url, status)
+            });
+        }
+
+        let mut results = HashMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((url, status)) => {
+                    results.insert(url, status);
+                }
+                Err(e) => tracing::warn!(%e, "link check task failed"),
+            }
+        }
+        results
+    }
+}
+
+/// 4xx 和 5xx 视为失效
+fn is_broken(status: StatusCode) -> bool {
+    status.is_client_error() || status.is_server_error()
+}
+
+/// 启动后台任务，按 [`CheckerOptions::interval`] 周期检查最久未检查的链接。
+///
+/// 检查在独立任务中进行，只写入检查结果，不会阻塞同步流程。
+pub fn spawn_checker(backend: Backend, options: CheckerOptions) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(options.interval);
+        let checker = Arc::new(LinkChecker::new(options));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = check_round(&backend, &checker).await {
+                tracing::warn!(%e, "link check failed");
+            }
+        }
+    })
+}
+
+/// 从环境变量配置并启动链接检查任务，未启用时返回 [`None`]。
+///
+/// - `GITNOTE_LINK_CHECK`：`true` 时启用，默认禁用
+/// - `GITNOTE_LINK_CHECK_PER_HOUR`：每小时最多检查的链接数，默认 60
+pub fn spawn_from_env(backend: Backend) -> Option<JoinHandle<()>> {
+    if std::env::var("GITNOTE_LINK_CHECK").as_deref() != Ok("true") {
+        return None;
+    }
+
+    let mut options = CheckerOptions::default();
+    if let Some(per_hour) = std::env::var("GITNOTE_LINK_CHECK_PER_HOUR")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        options.per_round = per_hour;
+    }
+
+    Some(spawn_checker(backend, options))
+}
+
+/// 检查一轮链接并写回数据库
+async fn check_round(backend: &Backend, checker: &Arc<LinkChecker>) -> Result<()> {
+    let urls = backend.links_due(checker.options.per_round as i64).await?;
+    let results = checker.check_all(urls).await;
+
+    let mut store = backend.store();
+    store.prune_links();
+    for (url, s) in &results {
+        store.record_link_status(url, s.status, s.error.as_deref());
+    }
+    store.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_extract_inline_and_autolinks() {
+        let md = r#"---
+title: "links"
+homepage: <https://front.matter/ignored>
+---
+
+See [rust](https://www.rust-lang.org) and <https://docs.rs/> for docs.
+Relative [link](../other.md), [mail](mailto:a@b.c) and [anchor](#top) are internal.
+Again [rust](https://www.rust-lang.org).
+
+![image](https://img.example.com/a.png)
+
+```
+[code](https://code.example.com)
+```
+"#;
+
+        assert_eq!(
+            extract_links(md),
+            vec!["https://www.rust-lang.org", "https://docs.rs/"]
+        );
+    }
+
+    #[test]
+    fn test_extract_reference_links() {
+        let md = r#"Read [the book][book] and [crates][].
+
+[book]: https://doc.rust-lang.org/book/ "The Book"
+[crates]: http://crates.io
+[unused]: https://unused.example.com
+"#;
+
+        assert_eq!(
+            extract_links(md),
+            vec!["https://doc.rust-lang.org/book/", "http://crates.io"]
+        );
+    }
+
+    fn attachment(url_or_path: &str, kind: &str, link_text: &str) -> Attachment {
+        Attachment {
+            url_or_path: url_or_path.to_string(),
+            kind: kind.to_string(),
+            link_text: link_text.to_string(),
+        }
+    }
+
+    fn extensions() -> Vec<String> {
+        ["pdf", "zip", "csv", "ipynb"].map(str::to_string).to_vec()
+    }
+
+    #[test]
+    fn test_extract_attachments() {
+        let md = r#"---
+slides: "[deck](deck.pdf)"
+---
+
+Download [the *paper*](files/Paper.PDF#page=2) or <https://data.example.com/set.csv?v=1>.
+Notebook: [analysis][nb], again [paper](files/Paper.PDF#page=2).
+
+![chart](chart.pdf) [page](https://example.com/index.html) [mail](mailto:a@b.pdf)
+
+```
+[code](code.zip)
+```
+
+[nb]: ../notebooks/analysis.ipynb "Analysis"
+[unused]: unused.zip
+"#;
+
+        assert_eq!(
+            extract_attachments(md, &extensions()),
+            vec![
+                attachment("files/Paper.PDF#page=2", "pdf", "the paper"),
+                attachment(
+                    "https://data.example.com/set.csv?v=1",
+                    "csv",
+                    "https://data.example.com/set.csv?v=1"
+                ),
+                attachment("../notebooks/analysis.ipynb", "ipynb", "analysis"),
+            ]
+        );
+        assert!(extract_attachments(md, &["zip".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_attachment_path() {
+        let article = Path::new("notes/rust/intro.md");
+        let path = |target| attachment_path(article, target);
+
+        assert_eq!(path("slides.pdf"), Some("notes/rust/slides.pdf".into()));
+        assert_eq!(
+            path("./files/My%20Data.csv?raw=1"),
+            Some("notes/rust/files/My Data.csv".into())
+        );
+        assert_eq!(path("../../data/set.zip"), Some("data/set.zip".into()));
+        assert_eq!(path("/data/set.zip#x"), Some("data/set.zip".into()));
+        assert_eq!(path("../../../outside.pdf"), None);
+        assert_eq!(path("https://example.com/a.pdf"), None);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_non_public_links() {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = probes.clone();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::any(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { "ok" }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let options = CheckerOptions {
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let checker = LinkChecker::new(options.clone());
+        // IP 地址形式的链接不发出请求，域名解析到本机时不连接
+        for url in [
+            format!("http://127.0.0.1:{port}/"),
+            "http://10.0.0.1/".to_string(),
+            format!("http://localhost:{port}/"),
+        ] {
+            let status = checker.probe(&url).await;
+            assert_eq!(status.status, None, "{url}");
+            assert!(status.error.is_some(), "{url}");
+        }
+        assert_eq!(
+            checker.probe("http://10.0.0.1/").await.error.as_deref(),
+            Some(NON_PUBLIC)
+        );
+        assert_eq!(probes.load(Ordering::SeqCst), 0);
+
+        // 允许非公网地址时正常检查
+        let checker = LinkChecker::new(CheckerOptions {
+            allow_private: true,
+            ..options
+        });
+        let status = checker.probe(&format!("http://127.0.0.1:{port}/")).await;
+        assert_eq!(status.status, Some(200));
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_is_broken() {
+        assert!(!is_broken(StatusCode::OK));
+        assert!(!is_broken(StatusCode::MOVED_PERMANENTLY));
+        assert!(is_broken(StatusCode::NOT_FOUND));
+        assert!(is_broken(StatusCode::BAD_GATEWAY));
+    }
+}
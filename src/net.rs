@@ -0,0 +1,174 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use ipnet::IpNet;
+use reqwest::{
+    Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect,
+};
+
+/// 最多跟随的重定向次数
+pub const MAX_REDIRECTS: usize = 10;
+
+/// 目标不是公网地址时的错误原因
+pub const NON_PUBLIC: &str = "target resolves to a non-public address";
+
+/// 请求内容中出现的外部地址的 HTTP 客户端
+///
+/// `allow_private` 为 `false` 时只连接公网地址：域名经 [`PublicResolver`] 解析，
+/// 重定向按 [`redirect_policy`] 检查。IP 地址形式的请求地址不经过解析，需要调用方先用 [`host_is_public`] 检查。
+pub fn outbound_client(timeout: Duration, allow_private: bool) -> reqwest::Client {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .timeout(timeout)
+        .redirect(redirect_policy(allow_private));
+    if allow_private {
+        client
+    } else {
+        client.dns_resolver(Arc::new(PublicResolver))
+    }
+    .build()
+    .expect("Failed to build reqwest client")
+}
+
+/// 读取响应体，最多 `limit` 字节，超出的部分不再读取
+pub async fn read_capped(mut resp: reqwest::Response, limit: usize) -> reqwest::Result<String> {
+    let mut body = Vec::new();
+    while body.len() < limit
+        && let Some(chunk) = resp.chunk().await?
+    {
+        let take = chunk.len().min(limit - body.len());
+        body.extend_from_slice(&chunk[..take]);
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// 重定向策略：最多跟随 [`MAX_REDIRECTS`] 次，不允许非公网地址时拒绝跳转到 IP 地址形式的非公网主机
+pub fn redirect_policy(allow_private: bool) -> redirect::Policy {
+    redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if !allow_private && !host_is_public(attempt.url()) {
+            attempt.error(NON_PUBLIC)
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// 只返回公网地址的域名解析，没有公网地址时解析失败
+///
+/// 每次连接时解析，包括重定向后的主机，解析结果在检查和连接之间不会改变。
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<SocketAddr>>();
+            if addrs.is_empty() {
+                return Err(NON_PUBLIC.into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// 非公网地址段：本机、私有网段、运营商 NAT、链路本地、文档示例、组播和保留地址
+pub const NON_PUBLIC_NETWORKS: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.0.2.0/24",
+    "192.88.99.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "198.51.100.0/24",
+    "203.0.113.0/24",
+    "224.0.0.0/4",
+    "240.0.0.0/4",
+    "::/96",
+    "64:ff9b:1::/48",
+    "100::/64",
+    "2001::/23",
+    "2001:db8::/32",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+/// 是否为公网地址，IPv4 映射的 IPv6 地址按其 IPv4 地址判断
+pub fn is_public(ip: IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    };
+    !NON_PUBLIC_NETWORKS
+        .iter()
+        .filter_map(|net| net.parse::<IpNet>().ok())
+        .any(|net| net.contains(&ip))
+}
+
+/// 地址的主机是域名或公网 IP 地址
+pub fn host_is_public(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .map_or(true, is_public)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public() {
+        for ip in [
+            "93.184.216.34",
+            "2606:2800:220:1::1",
+            "::ffff:93.184.216.34",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.31.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+
+        let public = |url: &str| host_is_public(&Url::parse(url).unwrap());
+        assert!(public("https://example.com/a"));
+        assert!(public("http://93.184.216.34/"));
+        assert!(!public("http://127.0.0.1:8080/"));
+        assert!(!public("http://[::1]/"));
+        assert!(!public("http://[::ffff:10.0.0.1]/"));
+    }
+}
@@ -1,3 +1,20 @@
+mod anchors;
+mod cached;
+mod fallback;
 mod github;
+mod headings;
+mod sanitize;
 
-pub use self::github::GithubAPiRenderer;
+pub use self::{
+    anchors::namespace_anchors,
+    cached::{BudgetedRenderer, DEFAULT_RENDER_CACHE_ENTRIES, RenderCache},
+    fallback::{
+        CircuitState, CircuitStatus, DEFAULT_COOLDOWN, DEFAULT_FAILURE_THRESHOLD, FallbackRenderer,
+        PendingRenderer,
+    },
+    github::GithubAPiRenderer,
+    headings::normalize_heading_ids,
+    sanitize::{SanitizeOptions, Sanitized, Sanitizer, clean_with_allowlist},
+};
+
+pub(crate) use self::headings::percent_decode;
@@ -0,0 +1,146 @@
+/// GitHub 为用户内容中的 id 添加的前缀，页面上由脚本映射到不带前缀的锚点
+pub(super) const USER_CONTENT_PREFIX: &str = "user-content-";
+
+/// 引用其他元素 id 的属性，值为空格分隔的 id 列表
+const ID_REF_ATTRIBUTES: &[&str] = &["aria-describedby", "aria-labelledby"];
+
+/// 为 HTML 中的锚点 id 及引用它们的链接添加命名空间
+///
+/// 多篇文章拼接到同一页面时，标题锚点和脚注 id 会互相冲突。
+/// `id` 改写为 `{namespace}--{id}`，`href="#..."` 和 `aria-describedby` 等引用同步改写，
+/// GitHub 添加的 `user-content-` 前缀会被去掉，如 `user-content-fn-1` 改写为 `intro--fn-1`。
+///
+/// 外部链接、站内其他页面的链接和空 id 保持不变。
+pub fn namespace_anchors(html: &str, namespace: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = tag_end(rest);
+        rewrite_tag(&rest[..end], namespace, &mut out);
+        rest = &rest[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// 标签结束位置（含 `>`），引号内的 `>` 不视为结束
+pub(super) fn tag_end(html: &str) -> usize {
+    let mut quoted = false;
+    for (i, c) in html.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '>' if !quoted => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+/// 改写单个标签中的属性，只处理双引号属性值
+fn rewrite_tag(tag: &str, namespace: &str, out: &mut String) {
+    let mut rest = tag;
+
+    while let Some(eq) = rest.find("=\"") {
+        let name = rest[..eq]
+            .rsplit(|c: char| c.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+        let value_start = eq + 2;
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let value = &rest[value_start..value_start + len];
+
+        out.push_str(&rest[..value_start]);
+        match (name, value.strip_prefix('#')) {
+            ("id", _) => out.push_str(&namespaced(value, namespace)),
+            ("href", Some(target)) => {
+                out.push('#');
+                out.push_str(&namespaced(target, namespace));
+            }
+            (name, _) if ID_REF_ATTRIBUTES.contains(&name) => {
+                let ids = value
+                    .split(' ')
+                    .map(|id| namespaced(id, namespace))
+                    .collect::<Vec<_>>();
+                out.push_str(&ids.join(" "));
+            }
+            _ => out.push_str(value),
+        }
+        rest = &rest[value_start + len..];
+    }
+
+    out.push_str(rest);
+}
+
+fn namespaced(id: &str, namespace: &str) -> String {
+    if id.is_empty() {
+        return String::new();
+    }
+    let id = id.strip_prefix(USER_CONTENT_PREFIX).unwrap_or(id);
+    format!("{namespace}--{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// GitHub Markdown API 输出的标题锚点、脚注和任务列表
+    const GITHUB_HTML: &str = concat!(
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Setup</h2><a id="user-content-setup" class="anchor" aria-label="Permalink: Setup" href="#setup"></a></div>"##,
+        "\n",
+        r##"<p dir="auto">See <a href="https://example.com/#top" rel="nofollow">docs</a> and <a href="/posts/other#setup">other</a><sup><a href="#user-content-fn-1-4c2a" id="user-content-fnref-1-4c2a" data-footnote-ref="" aria-describedby="footnote-label">1</a></sup>.</p>"##,
+        "\n",
+        r##"<ul class="contains-task-list"><li class="task-list-item"><input type="checkbox" id="" disabled="" class="task-list-item-checkbox" checked=""> done</li></ul>"##,
+        "\n",
+        r##"<section data-footnotes="" class="footnotes"><h2 id="footnote-label" class="sr-only" dir="auto">Footnotes</h2><ol dir="auto"><li id="user-content-fn-1-4c2a"><p dir="auto">Note <a href="#user-content-fnref-1-4c2a" data-footnote-backref="" aria-label="Back to reference 1" class="data-footnote-backref">↩</a></p></li></ol></section>"##,
+    );
+
+    #[test]
+    fn test_namespace_anchors() {
+        let html = namespace_anchors(GITHUB_HTML, "intro");
+
+        // 标题锚点与链接一致
+        assert!(html.contains(r#"<a id="intro--setup" class="anchor""#));
+        assert!(html.contains(r##"href="#intro--setup""##));
+
+        // 脚注引用与回链
+        assert!(html.contains(r##"<a href="#intro--fn-1-4c2a" id="intro--fnref-1-4c2a""##));
+        assert!(html.contains(r#"aria-describedby="intro--footnote-label""#));
+        assert!(html.contains(r#"<h2 id="intro--footnote-label""#));
+        assert!(html.contains(r#"<li id="intro--fn-1-4c2a">"#));
+        assert!(html.contains(r##"<a href="#intro--fnref-1-4c2a" data-footnote-backref"##));
+
+        // 外部链接、其他页面的链接和任务列表的空 id 不变
+        assert!(html.contains(r##"href="https://example.com/#top""##));
+        assert!(html.contains(r##"href="/posts/other#setup""##));
+        assert!(html.contains(r#"id="" disabled="""#));
+
+        // 正文不变
+        assert!(html.contains("Note "));
+        assert!(html.contains("↩</a>"));
+    }
+
+    #[test]
+    fn test_namespace_anchors_text() {
+        // 标签外的文本不会被改写
+        let html = r##"<p>id="x" <code>&lt;a href="#x"&gt;</code></p>"##;
+        assert_eq!(namespace_anchors(html, "intro"), html);
+
+        assert_eq!(
+            namespace_anchors(r##"<a href="#">top</a>"##, "intro"),
+            r##"<a href="#">top</a>"##
+        );
+        assert_eq!(
+            namespace_anchors(
+                r##"<sup><a href="#fn-1" id="fnref-1">1</a></sup>"##,
+                "intro"
+            ),
+            r##"<sup><a href="#intro--fn-1" id="intro--fnref-1">1</a></sup>"##
+        );
+    }
+}
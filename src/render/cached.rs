@@ -0,0 +1,204 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    content::Renderer,
+    error::{Error, Result},
+    git_client::blob_oid,
+};
+
+/// 默认缓存的渲染结果数
+pub const DEFAULT_RENDER_CACHE_ENTRIES: usize = 1000;
+
+/// 渲染结果的内存缓存，键为 Markdown 原文的 blob oid
+///
+/// 克隆后共享同一份缓存，超过容量时淘汰最早写入的结果。只缓存主渲染器的结果，降级渲染的结果不缓存。
+#[derive(Debug, Clone)]
+pub struct RenderCache {
+    entries: Arc<Mutex<CacheEntries>>,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    html: HashMap<String, String>,
+    order: VecDeque<String>,
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RENDER_CACHE_ENTRIES)
+    }
+}
+
+impl RenderCache {
+    /// 最多缓存 `capacity` 个渲染结果
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 已缓存的渲染结果数
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().html.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, oid: &str) -> Option<String> {
+        self.entries.lock().unwrap().html.get(oid).cloned()
+    }
+
+    fn insert(&self, oid: String, html: String) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.html.insert(oid.clone(), html).is_some() {
+            return;
+        }
+        entries.order.push_back(oid);
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.html.remove(&oldest);
+            }
+        }
+    }
+
+    /// 使用缓存的 `renderer`，未命中缓存的渲染最多 `budget` 次
+    pub fn budgeted<'a, R: Renderer>(
+        &'a self,
+        renderer: &'a R,
+        budget: usize,
+    ) -> BudgetedRenderer<'a, R> {
+        BudgetedRenderer {
+            cache: self,
+            renderer,
+            remaining: AtomicUsize::new(budget),
+        }
+    }
+}
+
+/// 先查 [`RenderCache`] 再渲染的 [`Renderer`]，限制未命中缓存的渲染次数
+///
+/// 次数用完后未命中缓存的渲染返回错误，调用方可以先用 [`BudgetedRenderer::can_render`] 检查。
+pub struct BudgetedRenderer<'a, R> {
+    cache: &'a RenderCache,
+    renderer: &'a R,
+    remaining: AtomicUsize,
+}
+
+impl<R: Renderer> BudgetedRenderer<'_, R> {
+    /// `content` 已缓存或还有渲染次数
+    pub fn can_render(&self, content: &str) -> bool {
+        self.remaining.load(Ordering::SeqCst) > 0
+            || self.cache.get(&blob_oid(content.as_bytes())).is_some()
+    }
+
+    /// 剩余的渲染次数
+    pub fn remaining(&self) -> usize {
+        self.remaining.load(Ordering::SeqCst)
+    }
+}
+
+impl<R: Renderer> Renderer for BudgetedRenderer<'_, R> {
+    async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+        Ok(self.render_tracked(content).await?.0)
+    }
+
+    fn identity(&self) -> String {
+        self.renderer.identity()
+    }
+
+    fn version(&self) -> Option<String> {
+        self.renderer.version()
+    }
+
+    fn namespace_anchors(&self) -> bool {
+        self.renderer.namespace_anchors()
+    }
+
+    async fn render_tracked<T: AsRef<str>>(&self, content: T) -> Result<(String, String)> {
+        let content = content.as_ref();
+        let oid = blob_oid(content.as_bytes());
+        if let Some(html) = self.cache.get(&oid) {
+            return Ok((html, self.renderer.identity()));
+        }
+
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .map_err(|_| Error::Custom("render budget exhausted"))?;
+        let (html, by) = self.renderer.render_tracked(content).await?;
+        if by == self.renderer.identity() {
+            self.cache.insert(oid, html.clone());
+        }
+        Ok((html, by))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录调用次数，`fail` 开头的内容由备用渲染器渲染
+    #[derive(Default)]
+    struct Counting {
+        calls: AtomicUsize,
+    }
+
+    impl Renderer for Counting {
+        async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+            Ok(self.render_tracked(content).await?.0)
+        }
+
+        fn identity(&self) -> String {
+            "counting@1".to_string()
+        }
+
+        async fn render_tracked<T: AsRef<str>>(&self, content: T) -> Result<(String, String)> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let content = content.as_ref();
+            let by = match content.starts_with("fail") {
+                true => "pending",
+                false => "counting@1",
+            };
+            Ok((format!("<p>{content}</p>"), by.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budgeted_renderer() {
+        let cache = RenderCache::new(2);
+        let inner = Counting::default();
+
+        let renderer = cache.budgeted(&inner, 2);
+        assert_eq!(renderer.render("a").await.unwrap(), "<p>a</p>");
+        assert_eq!(renderer.render("a").await.unwrap(), "<p>a</p>");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(renderer.remaining(), 1);
+
+        // 降级渲染的结果不缓存
+        renderer.render("fail").await.unwrap();
+        assert_eq!(cache.len(), 1);
+
+        // 次数用完后只能返回缓存
+        assert!(renderer.can_render("a"));
+        assert!(!renderer.can_render("b"));
+        assert!(renderer.render("b").await.is_err());
+        assert_eq!(renderer.render("a").await.unwrap(), "<p>a</p>");
+
+        // 缓存在请求之间共享，超过容量时淘汰最早的结果
+        let renderer = cache.budgeted(&inner, 3);
+        renderer.render("b").await.unwrap();
+        renderer.render("c").await.unwrap();
+        assert_eq!(cache.len(), 2);
+        renderer.render("a").await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+    }
+}
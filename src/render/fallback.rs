@@ -0,0 +1,321 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::{content::Renderer, error::Result};
+
+/// 连续失败多少次后熔断的默认值
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// 熔断后再次尝试主渲染器前等待的默认时间
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// 主渲染器失败时使用备用渲染器的 [`Renderer`] 包装
+///
+/// 主渲染器连续失败 `threshold` 次后熔断，冷却期内直接使用备用渲染器；冷却期结束后的渲染再次尝试主渲染器，
+/// 成功则恢复，失败则重新进入冷却期。未熔断时单次失败同样使用备用渲染器的结果，内容照常发布。
+///
+/// [`Renderer::identity`] 和 [`Renderer::version`] 始终为主渲染器的标识和版本，
+/// 降级渲染的结果通过 [`Renderer::render_tracked`] 标记为备用渲染器的标识，之后作为过期渲染重新渲染。
+/// 克隆的实例共享熔断状态。
+#[derive(Clone)]
+pub struct FallbackRenderer<P, F> {
+    primary: P,
+    fallback: F,
+    threshold: u32,
+    cooldown: Duration,
+    breaker: Arc<Mutex<Breaker>>,
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    /// 主渲染器连续失败的次数
+    failures: u32,
+    /// 熔断时冷却期的结束时间
+    open_until: Option<Instant>,
+}
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 使用主渲染器
+    Closed,
+    /// 冷却期内，使用备用渲染器
+    Open,
+    /// 冷却期已结束，下一次渲染尝试主渲染器
+    HalfOpen,
+}
+
+/// 主渲染器的熔断状态
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    /// 主渲染器连续失败的次数
+    pub consecutive_failures: u32,
+    /// 距冷却期结束的毫秒数，未熔断或冷却期已结束时为 `null`
+    pub retry_in_ms: Option<u64>,
+}
+
+impl<P, F> FallbackRenderer<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self {
+            primary,
+            fallback,
+            threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+            breaker: Arc::default(),
+        }
+    }
+
+    /// 设置熔断的连续失败次数和冷却时间，`threshold` 为 0 时按 1 处理
+    pub fn with_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.threshold = threshold.max(1);
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// 当前熔断状态
+    pub fn status(&self) -> CircuitStatus {
+        let breaker = self.breaker.lock().unwrap();
+        let now = Instant::now();
+        let (state, retry_in) = match breaker.open_until {
+            None => (CircuitState::Closed, None),
+            Some(until) if until > now => (CircuitState::Open, Some(until - now)),
+            Some(_) => (CircuitState::HalfOpen, None),
+        };
+
+        CircuitStatus {
+            state,
+            consecutive_failures: breaker.failures,
+            retry_in_ms: retry_in.map(|d| d.as_millis() as u64),
+        }
+    }
+
+    /// 是否尝试主渲染器，熔断且仍在冷却期内时返回 `false`
+    fn allow_primary(&self) -> bool {
+        let breaker = self.breaker.lock().unwrap();
+        breaker
+            .open_until
+            .is_none_or(|until| until <= Instant::now())
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if breaker.open_until.is_some() {
+            tracing::info!("primary renderer recovered, circuit closed");
+        }
+        *breaker = Breaker::default();
+    }
+
+    fn record_failure(&self) {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.failures += 1;
+        if breaker.failures >= self.threshold {
+            if breaker.open_until.is_none() {
+                tracing::warn!(
+                    failures = breaker.failures,
+                    cooldown_secs = self.cooldown.as_secs(),
+                    "primary renderer keeps failing, circuit opened"
+                );
+            }
+            breaker.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+impl<P: Renderer, F: Renderer> Renderer for FallbackRenderer<P, F> {
+    async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+        Ok(self.render_tracked(content).await?.0)
+    }
+
+    /// 主渲染器的标识
+    fn identity(&self) -> String {
+        self.primary.identity()
+    }
+
+    /// 主渲染器的版本，降级渲染的结果不复用
+    fn version(&self) -> Option<String> {
+        self.primary.version()
+    }
+
+    fn namespace_anchors(&self) -> bool {
+        self.primary.namespace_anchors()
+    }
+
+    /// 优先使用主渲染器，失败或熔断时使用备用渲染器，返回实际使用的渲染器标识
+    async fn render_tracked<T: AsRef<str>>(&self, content: T) -> Result<(String, String)> {
+        let content = content.as_ref();
+        if self.allow_primary() {
+            match self.primary.render(content).await {
+                Ok(html) => {
+                    self.record_success();
+                    return Ok((html, self.primary.identity()));
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "primary renderer failed, using fallback");
+                    self.record_failure();
+                }
+            }
+        }
+
+        let html = self.fallback.render(content).await?;
+        Ok((html, self.fallback.identity()))
+    }
+}
+
+/// 不渲染 Markdown，将转义后的原文放在 `<pre>` 中
+///
+/// 作为 [`FallbackRenderer`] 的备用渲染器，结果以 [`PendingRenderer::IDENTITY`] 标记为待渲染。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PendingRenderer;
+
+impl PendingRenderer {
+    pub const IDENTITY: &str = "render-pending";
+}
+
+impl Renderer for PendingRenderer {
+    async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+        let content = content.as_ref();
+        let mut html = String::with_capacity(content.len() + 11);
+        html.push_str("<pre>");
+        for c in content.chars() {
+            match c {
+                '&' => html.push_str("&amp;"),
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '"' => html.push_str("&quot;"),
+                c => html.push(c),
+            }
+        }
+        html.push_str("</pre>");
+        Ok(html)
+    }
+
+    fn identity(&self) -> String {
+        Self::IDENTITY.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use crate::error::Error;
+
+    use super::*;
+
+    /// 按开关返回成功或失败，记录调用次数
+    #[derive(Clone, Default)]
+    struct Scripted {
+        down: Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Scripted {
+        fn set_down(&self, down: bool) {
+            self.down.store(down, Ordering::SeqCst);
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Renderer for Scripted {
+        async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                return Err(Error::Custom("service unavailable"));
+            }
+            Ok(format!("<p>{}</p>", content.as_ref()))
+        }
+
+        fn identity(&self) -> String {
+            "scripted@1".to_string()
+        }
+    }
+
+    fn renderer(primary: &Scripted) -> FallbackRenderer<Scripted, PendingRenderer> {
+        FallbackRenderer::new(primary.clone(), PendingRenderer)
+            .with_breaker(2, Duration::from_secs(30))
+    }
+
+    #[tokio::test]
+    async fn test_pending_renderer_escapes() {
+        assert_eq!(
+            PendingRenderer.render("a < b & \"c\"").await.unwrap(),
+            "<pre>a &lt; b &amp; &quot;c&quot;</pre>"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_breaker_opens_after_threshold() {
+        let primary = Scripted::default();
+        let renderer = renderer(&primary);
+
+        let rendered = renderer.render_tracked("hi").await.unwrap();
+        assert_eq!(
+            rendered,
+            ("<p>hi</p>".to_string(), "scripted@1".to_string())
+        );
+        assert_eq!(renderer.status().state, CircuitState::Closed);
+
+        primary.set_down(true);
+        // 未达到阈值时每次都尝试主渲染器，失败的渲染使用备用渲染器
+        let rendered = renderer.render_tracked("hi").await.unwrap();
+        assert_eq!(
+            rendered,
+            ("<pre>hi</pre>".to_string(), "render-pending".to_string())
+        );
+        assert_eq!(renderer.status().state, CircuitState::Closed);
+        assert_eq!(renderer.status().consecutive_failures, 1);
+
+        renderer.render_tracked("hi").await.unwrap();
+        let status = renderer.status();
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.retry_in_ms, Some(30_000));
+        assert_eq!(primary.calls(), 3);
+
+        // 冷却期内不再调用主渲染器
+        let (_, identity) = renderer.render_tracked("hi").await.unwrap();
+        assert_eq!(identity, PendingRenderer::IDENTITY);
+        assert_eq!(primary.calls(), 3);
+        // 标识不随降级改变，降级的文章作为过期渲染
+        assert_eq!(renderer.identity(), "scripted@1");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_breaker_recovers_after_cooldown() {
+        let primary = Scripted::default();
+        let renderer = renderer(&primary);
+        primary.set_down(true);
+        renderer.render("a").await.unwrap();
+        renderer.render("b").await.unwrap();
+        assert_eq!(renderer.status().state, CircuitState::Open);
+
+        // 冷却期结束后仍然失败，重新进入冷却期
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(renderer.status().state, CircuitState::HalfOpen);
+        renderer.render("c").await.unwrap();
+        assert_eq!(primary.calls(), 3);
+        assert_eq!(renderer.status().state, CircuitState::Open);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        primary.set_down(false);
+        let (html, identity) = renderer.render_tracked("d").await.unwrap();
+        assert_eq!(
+            (html.as_str(), identity.as_str()),
+            ("<p>d</p>", "scripted@1")
+        );
+
+        let status = renderer.status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+        assert_eq!(status.retry_in_ms, None);
+    }
+}
@@ -31,6 +31,7 @@ impl GithubAPiRenderer {
     /// 使用指定的 GitHub Token 创建渲染器
     ///
     const GITHUB_MARKDOWN_RENDER_API: &str = "https://api.github.com/markdown";
+    const GITHUB_API_VERSION: &str = "2022-11-28";
 
     pub fn new<T: AsRef<str>>(token: T) -> Self {
         let client = reqwest::Client::builder()
@@ -47,7 +48,7 @@ impl GithubAPiRenderer {
                 );
                 header.insert(
                     "X-GitHub-Api-Version",
-                    HeaderValue::from_static("2022-11-28"),
+                    HeaderValue::from_static(Self::GITHUB_API_VERSION),
                 );
                 header.insert(
                     header::AUTHORIZATION,
@@ -80,9 +81,20 @@ impl content::Renderer for GithubAPiRenderer {
                 mode: "gfm",
             })
             .send()
-            .await?;
+            .await?
+            .error_for_status()?;
         Ok(resp.text().await?)
     }
+
+    /// 渲染模式和 API 版本
+    fn identity(&self) -> String {
+        format!("github-gfm@{}", Self::GITHUB_API_VERSION)
+    }
+
+    /// GitHub 不公开渲染器版本，以 API 版本和渲染模式标识
+    fn version(&self) -> Option<String> {
+        Some(format!("github-gfm-{}", Self::GITHUB_API_VERSION))
+    }
 }
 
 #[cfg(test)]
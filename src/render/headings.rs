@@ -0,0 +1,423 @@
+use std::{borrow::Cow, collections::HashMap, ops::Range};
+
+use unicode_normalization::char::is_combining_mark;
+
+use super::anchors::{USER_CONTENT_PREFIX, tag_end};
+use crate::content::decode_entity;
+
+/// 将标题锚点 id 统一为 GitHub 风格的 slug，并同步改写文档内指向它们的链接
+///
+/// 不同渲染器生成的标题 id 格式不同，如 GitHub 为 `user-content-my-heading`，其他渲染器可能为 `my-heading`
+/// 或不生成 id，切换渲染器后读者保存的链接会失效。这里按标题文本重新生成 id：转为小写，去掉标点和符号，
+/// 空白替换为 `-`，保留中日韩等文字，重复的 slug 依次添加 `-1`、`-2` 后缀。
+///
+/// 标题标签和标题内或紧随其后的 `class="anchor"` 链接上的 `id` 改写为新的 id，都没有时在标题标签上添加；
+/// `href="#..."` 指向旧 id 的链接同步改写，旧 id 的 `user-content-` 前缀和百分号编码不影响匹配。
+/// 文本为空或只有符号的标题保持不变。
+pub fn normalize_heading_ids(html: &str) -> String {
+    let (headings, links) = scan(html);
+
+    let mut occurrences = HashMap::new();
+    let mut renamed = HashMap::new();
+    let mut edits = Vec::new();
+    for heading in headings {
+        let slug = slugify(&heading.text);
+        if slug.is_empty() {
+            continue;
+        }
+        let id = unique(slug, &mut occurrences);
+        for site in &heading.ids {
+            renamed
+                .entry(bare(&html[site.clone()]).to_string())
+                .or_insert_with(|| id.clone());
+            edits.push((site.clone(), id.clone()));
+        }
+        if heading.ids.is_empty() {
+            edits.push((heading.id_at..heading.id_at, format!(" id=\"{id}\"")));
+        }
+    }
+    for link in links {
+        if let Some(id) = renamed.get(bare(&percent_decode(&html[link.clone()]))) {
+            edits.push((link, id.clone()));
+        }
+    }
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut out = String::with_capacity(html.len());
+    let mut at = 0;
+    for (range, replacement) in edits {
+        out.push_str(&html[at..range.start]);
+        out.push_str(&replacement);
+        at = range.end;
+    }
+    out.push_str(&html[at..]);
+    out
+}
+
+/// 文档中的一个标题
+struct Heading {
+    /// 解码字符实体后的文本，不含锚点链接中的内容
+    text: String,
+    /// 标题标签和锚点链接上 `id` 属性值的位置
+    ids: Vec<Range<usize>>,
+    /// 没有 id 时插入 `id` 属性的位置，即标题标签名之后
+    id_at: usize,
+}
+
+/// 找出所有标题和 `href="#..."` 中 `#` 之后的部分的位置
+fn scan(html: &str) -> (Vec<Heading>, Vec<Range<usize>>) {
+    let mut headings = Vec::new();
+    let mut links = Vec::new();
+    // 未闭合的标题及其标签名
+    let mut open: Option<(&str, Heading)> = None;
+    // 刚闭合的标题，GitHub 的锚点链接紧随其后
+    let mut closed: Option<Heading> = None;
+    let mut in_anchor = false;
+    let mut pos = 0;
+
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let text = &html[pos..start];
+        match &mut open {
+            Some((_, heading)) if !in_anchor => decode_into(text, &mut heading.text),
+            Some(_) => {}
+            None if !text.trim().is_empty() => headings.extend(closed.take()),
+            None => {}
+        }
+
+        let end = start + tag_end(&html[start..]);
+        let tag = &html[start..end];
+        let (closing, name) = tag_name(tag);
+        let attributes = attributes(tag, start);
+        for (attribute, value) in &attributes {
+            if *attribute == "href" && html[value.clone()].starts_with('#') {
+                links.push(value.start + 1..value.end);
+            }
+        }
+        let value = |name: &str| {
+            attributes
+                .iter()
+                .find(|(attribute, _)| *attribute == name)
+                .map(|(_, value)| value.clone())
+        };
+        let id = value("id").filter(|range| !range.is_empty());
+        let is_anchor = !closing
+            && name.eq_ignore_ascii_case("a")
+            && value("class").is_some_and(|range| {
+                html[range]
+                    .split_ascii_whitespace()
+                    .any(|class| class == "anchor")
+            });
+
+        match &mut open {
+            None if !closing && is_heading(name) => {
+                headings.extend(closed.take());
+                let heading = Heading {
+                    text: String::new(),
+                    ids: id.into_iter().collect(),
+                    id_at: start + 1 + name.len(),
+                };
+                open = Some((name, heading));
+            }
+            None => {
+                if let Some(mut heading) = closed.take() {
+                    if is_anchor {
+                        heading.ids.extend(id);
+                    }
+                    headings.push(heading);
+                }
+            }
+            Some((level, _)) if closing && name.eq_ignore_ascii_case(level) => {
+                closed = open.take().map(|(_, heading)| heading);
+                in_anchor = false;
+            }
+            Some((_, heading)) if is_anchor => {
+                heading.ids.extend(id);
+                in_anchor = true;
+            }
+            Some(_) if closing && name.eq_ignore_ascii_case("a") => in_anchor = false,
+            Some(_) => {}
+        }
+        pos = end;
+    }
+
+    headings.extend(closed);
+    headings.extend(open.map(|(_, heading)| heading));
+    (headings, links)
+}
+
+/// 标签是否为结束标签及标签名
+fn tag_name(tag: &str) -> (bool, &str) {
+    let inner = tag.trim_start_matches('<');
+    let closing = inner.starts_with('/');
+    let inner = inner.trim_start_matches('/');
+    let len = inner
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(inner.len());
+    (closing, &inner[..len])
+}
+
+fn is_heading(name: &str) -> bool {
+    matches!(name.as_bytes(), [b'h' | b'H', b'1'..=b'6'])
+}
+
+/// 标签中的双引号属性及属性值在 HTML 中的位置，`offset` 为标签的起始位置
+fn attributes(tag: &str, offset: usize) -> Vec<(&str, Range<usize>)> {
+    let mut attributes = Vec::new();
+    let mut at = 0;
+
+    while let Some(eq) = tag[at..].find("=\"") {
+        let eq = at + eq;
+        let name = tag[at..eq]
+            .rsplit(|c: char| c.is_ascii_whitespace())
+            .next()
+            .unwrap_or_default();
+        let value_start = eq + 2;
+        let Some(len) = tag[value_start..].find('"') else {
+            break;
+        };
+        attributes.push((name, offset + value_start..offset + value_start + len));
+        at = value_start + len + 1;
+    }
+
+    attributes
+}
+
+/// 将 HTML 文本解码字符实体后追加到 `out`
+fn decode_into(text: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let (decoded, len) = decode_entity(&rest[start..]);
+        out.push_str(decoded);
+        rest = &rest[start + len..];
+    }
+    out.push_str(rest);
+}
+
+/// GitHub 风格的标题 slug：小写，只保留文字、数字、组合符号、`-` 和 `_`，空白替换为 `-`
+fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_whitespace() => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() || is_combining_mark(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 与已生成的 slug 重复时添加数字后缀，与 GitHub 的规则相同
+fn unique(slug: String, occurrences: &mut HashMap<String, usize>) -> String {
+    let mut id = slug.clone();
+    while occurrences.contains_key(&id) {
+        let count = occurrences.entry(slug.clone()).or_default();
+        *count += 1;
+        id = format!("{slug}-{count}");
+    }
+    occurrences.insert(id.clone(), 0);
+    id
+}
+
+/// 去掉 GitHub 添加的 `user-content-` 前缀
+fn bare(id: &str) -> &str {
+    id.strip_prefix(USER_CONTENT_PREFIX).unwrap_or(id)
+}
+
+/// 解码百分号编码，解码结果不是合法的 UTF-8 时原样返回
+pub(crate) fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(out).map_or(Cow::Borrowed(s), Cow::Owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// GitHub Markdown API 的输出：锚点链接紧随标题，id 带 `user-content-` 前缀，链接中的中文经过百分号编码
+    const GITHUB_HTML: &str = concat!(
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Install <code>gitnote</code></h2><a id="user-content-install-gitnote" class="anchor" aria-label="Permalink: Install gitnote" href="#install-gitnote"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Usage</h2><a id="user-content-usage" class="anchor" aria-label="Permalink: Usage" href="#usage"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h3 tabindex="-1" class="heading-element" dir="auto">Usage</h3><a id="user-content-usage-1" class="anchor" aria-label="Permalink: Usage" href="#usage-1"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">See <a href="https://example.com/docs" rel="nofollow">the docs</a>!</h2><a id="user-content-see-the-docs" class="anchor" aria-label="Permalink: See the docs!" href="#see-the-docs"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">快速开始：配置</h2><a id="user-content-快速开始配置" class="anchor" aria-label="Permalink: 快速开始：配置" href="#%E5%BF%AB%E9%80%9F%E5%BC%80%E5%A7%8B%E9%85%8D%E7%BD%AE"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">What&#39;s new?</h2><a id="user-content-whats-new" class="anchor" aria-label="Permalink: What&#39;s new?" href="#whats-new"></a></div>"##,
+        "\n",
+        r##"<p dir="auto">Read <a href="#usage-1">usage</a>, <a href="#whats-new">news</a> and <a href="#%E5%BF%AB%E9%80%9F%E5%BC%80%E5%A7%8B%E9%85%8D%E7%BD%AE">配置</a>.</p>"##,
+    );
+
+    const GITHUB_NORMALIZED: &str = concat!(
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Install <code>gitnote</code></h2><a id="install-gitnote" class="anchor" aria-label="Permalink: Install gitnote" href="#install-gitnote"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Usage</h2><a id="usage" class="anchor" aria-label="Permalink: Usage" href="#usage"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h3 tabindex="-1" class="heading-element" dir="auto">Usage</h3><a id="usage-1" class="anchor" aria-label="Permalink: Usage" href="#usage-1"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">See <a href="https://example.com/docs" rel="nofollow">the docs</a>!</h2><a id="see-the-docs" class="anchor" aria-label="Permalink: See the docs!" href="#see-the-docs"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">快速开始：配置</h2><a id="快速开始配置" class="anchor" aria-label="Permalink: 快速开始：配置" href="#快速开始配置"></a></div>"##,
+        "\n",
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">What&#39;s new?</h2><a id="whats-new" class="anchor" aria-label="Permalink: What&#39;s new?" href="#whats-new"></a></div>"##,
+        "\n",
+        r##"<p dir="auto">Read <a href="#usage-1">usage</a>, <a href="#whats-new">news</a> and <a href="#快速开始配置">配置</a>.</p>"##,
+    );
+
+    /// 本地渲染器的输出：id 在标题标签上，重复标题的后缀从 2 开始，标点替换为 `-`，中文标题没有 id，
+    /// 并生成指向这些 id 的目录
+    const LOCAL_HTML: &str = concat!(
+        r##"<nav><ul><li><a href="#usage-2">Usage</a></li><li><a href="#what-s-new-">What&#39;s new?</a></li></ul></nav>"##,
+        "\n",
+        r##"<h2 id="install-gitnote">Install <code>gitnote</code></h2>"##,
+        "\n",
+        r##"<h2 id="usage">Usage</h2>"##,
+        "\n",
+        r##"<h3 id="usage-2">Usage</h3>"##,
+        "\n",
+        r##"<h2 id="see-the-docs-">See <a href="https://example.com/docs">the docs</a>!</h2>"##,
+        "\n",
+        r##"<h2>快速开始：配置</h2>"##,
+        "\n",
+        r##"<h2 id="what-s-new-">What&#39;s new?</h2>"##,
+        "\n",
+        r##"<p>Read <a href="#usage-1">usage</a>, <a href="#whats-new">news</a> and <a href="#快速开始配置">配置</a>.</p>"##,
+    );
+
+    const LOCAL_NORMALIZED: &str = concat!(
+        r##"<nav><ul><li><a href="#usage-1">Usage</a></li><li><a href="#whats-new">What&#39;s new?</a></li></ul></nav>"##,
+        "\n",
+        r##"<h2 id="install-gitnote">Install <code>gitnote</code></h2>"##,
+        "\n",
+        r##"<h2 id="usage">Usage</h2>"##,
+        "\n",
+        r##"<h3 id="usage-1">Usage</h3>"##,
+        "\n",
+        r##"<h2 id="see-the-docs">See <a href="https://example.com/docs">the docs</a>!</h2>"##,
+        "\n",
+        r##"<h2 id="快速开始配置">快速开始：配置</h2>"##,
+        "\n",
+        r##"<h2 id="whats-new">What&#39;s new?</h2>"##,
+        "\n",
+        r##"<p>Read <a href="#usage-1">usage</a>, <a href="#whats-new">news</a> and <a href="#快速开始配置">配置</a>.</p>"##,
+    );
+
+    /// 所有 `id` 和文档内链接的目标，按出现顺序
+    fn anchors(html: &str) -> (Vec<String>, Vec<String>) {
+        let (mut ids, mut links) = (Vec::new(), Vec::new());
+        for (name, value) in attributes(html, 0) {
+            let value = &html[value];
+            match (name, value.strip_prefix('#')) {
+                ("id", _) => ids.push(value.to_string()),
+                ("href", Some(target)) => links.push(target.to_string()),
+                _ => {}
+            }
+        }
+        (ids, links)
+    }
+
+    #[test]
+    fn test_github_snapshot() {
+        assert_eq!(normalize_heading_ids(GITHUB_HTML), GITHUB_NORMALIZED);
+    }
+
+    #[test]
+    fn test_local_snapshot() {
+        assert_eq!(normalize_heading_ids(LOCAL_HTML), LOCAL_NORMALIZED);
+    }
+
+    #[test]
+    fn test_renderers_agree() {
+        let (github_ids, github_links) = anchors(&normalize_heading_ids(GITHUB_HTML));
+        let (local_ids, local_links) = anchors(&normalize_heading_ids(LOCAL_HTML));
+        assert_eq!(
+            github_ids,
+            [
+                "install-gitnote",
+                "usage",
+                "usage-1",
+                "see-the-docs",
+                "快速开始配置",
+                "whats-new"
+            ]
+        );
+        assert_eq!(github_ids, local_ids);
+
+        // 所有文档内链接都指向存在的标题
+        for link in github_links.iter().chain(&local_links) {
+            assert!(github_ids.contains(link), "{link}");
+        }
+    }
+
+    #[test]
+    fn test_idempotent() {
+        for html in [GITHUB_HTML, LOCAL_HTML] {
+            let once = normalize_heading_ids(html);
+            assert_eq!(normalize_heading_ids(&once), once);
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify(" Hello,  World! "), "hello--world");
+        assert_eq!(slugify("snake_case & kebab-case"), "snake_case--kebab-case");
+        assert_eq!(slugify("Ünïcode café"), "ünïcode-café");
+        assert_eq!(slugify("Cafe\u{301}"), "cafe\u{301}");
+        assert_eq!(slugify("版本 2.0 🎉"), "版本-20-");
+        assert_eq!(slugify("🎉"), "");
+    }
+
+    #[test]
+    fn test_unique_suffixes() {
+        let mut occurrences = HashMap::new();
+        let ids = ["a", "a", "a-1", "a"].map(|slug| unique(slug.to_string(), &mut occurrences));
+        assert_eq!(ids, ["a", "a-1", "a-1-1", "a-2"]);
+    }
+
+    #[test]
+    fn test_headings_without_text_or_anchor() {
+        // 只有符号的标题保持不变，不占用 slug
+        assert_eq!(
+            normalize_heading_ids(r#"<h2 id="x">🎉</h2><h2>🎉 Party</h2>"#),
+            r#"<h2 id="x">🎉</h2><h2 id="-party">🎉 Party</h2>"#
+        );
+        // 标题内的锚点链接文本不参与计算
+        assert_eq!(
+            normalize_heading_ids(
+                r##"<h1><a href="#Old" class="anchor" id="Old">#</a>New <em>Title</em></h1><a href="#Old">x</a>"##
+            ),
+            r##"<h1><a href="#new-title" class="anchor" id="new-title">#</a>New <em>Title</em></h1><a href="#new-title">x</a>"##
+        );
+        // 标签外的文本不会被改写
+        let html = r##"<p>id="x" <code>&lt;h2 id="y"&gt;</code></p>"##;
+        assert_eq!(normalize_heading_ids(html), html);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{collections::HashSet, sync::Arc};
+
+use super::normalize_heading_ids;
+use crate::{content::Renderer, error::Result};
+
+/// HTML 清理配置
+///
+/// 在默认白名单（覆盖 GitHub 渲染输出）的基础上追加标签和通用属性。
+#[derive(Debug, Clone)]
+pub struct SanitizeOptions {
+    /// 是否启用清理
+    pub enabled: bool,
+    /// 额外允许的标签
+    pub extra_tags: Vec<String>,
+    /// 额外允许的通用属性
+    pub extra_attributes: Vec<String>,
+}
+
+impl Default for SanitizeOptions {
+    /// 从环境变量读取配置
+    ///
+    /// - `GITNOTE_SANITIZE`：为 `false`、`0` 或 `off` 时禁用清理，默认启用
+    /// - `GITNOTE_SANITIZE_EXTRA_TAGS`：逗号分隔的额外标签
+    /// - `GITNOTE_SANITIZE_EXTRA_ATTRIBUTES`：逗号分隔的额外通用属性
+    fn default() -> Self {
+        fn list(key: &str) -> Vec<String> {
+            std::env::var(key)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+
+        Self {
+            enabled: !matches!(
+                std::env::var("GITNOTE_SANITIZE").as_deref(),
+                Ok("false" | "0" | "off")
+            ),
+            extra_tags: list("GITNOTE_SANITIZE_EXTRA_TAGS"),
+            extra_attributes: list("GITNOTE_SANITIZE_EXTRA_ATTRIBUTES"),
+        }
+    }
+}
+
+/// 默认白名单的版本，修改白名单时递增，使已存储的渲染结果失效
+const WHITELIST_VERSION: u32 = 1;
+
+/// 清理渲染结果中的危险 HTML
+///
+/// 禁用时原样返回输入。
+#[derive(Clone)]
+pub struct Sanitizer {
+    builder: Option<Arc<ammonia::Builder<'static>>>,
+    version: Arc<str>,
+}
+
+impl Default for Sanitizer {
+    /// 使用环境变量中的 [`SanitizeOptions`] 创建
+    fn default() -> Self {
+        Self::new(&SanitizeOptions::default())
+    }
+}
+
+impl Sanitizer {
+    /// 根据配置创建清理器
+    pub fn new(options: &SanitizeOptions) -> Self {
+        if !options.enabled {
+            return Self {
+                builder: None,
+                version: "off".into(),
+            };
+        }
+
+        // 额外白名单只在启动时构建一次，泄漏为 'static 以满足 ammonia 的生命周期要求
+        let leak = |s: &String| -> &'static str { Box::leak(s.clone().into_boxed_str()) };
+
+        let mut builder = default_builder();
+        builder
+            .add_tags(options.extra_tags.iter().map(leak))
+            .add_generic_attributes(options.extra_attributes.iter().map(leak));
+
+        let version = format!(
+            "v{WHITELIST_VERSION};tags={};attributes={}",
+            options.extra_tags.join(","),
+            options.extra_attributes.join(",")
+        );
+
+        Self {
+            builder: Some(Arc::new(builder)),
+            version: version.into(),
+        }
+    }
+
+    /// 清理规则的版本，由白名单版本和额外的标签、属性组成
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// 清理 HTML 片段
+    pub fn clean(&self, html: &str) -> String {
+        match &self.builder {
+            Some(builder) => builder.clean(html).to_string(),
+            None => html.to_string(),
+        }
+    }
+}
+
+/// 默认白名单，覆盖 GitHub 渲染输出
+fn default_builder<'a>() -> ammonia::Builder<'a> {
+    let mut builder = ammonia::Builder::default();
+    builder
+        // 保留 GitHub 输出中的 rel，不额外添加
+        .link_rel(None)
+        // 任务列表
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        // 标题锚点与图片链接
+        .add_tag_attributes("a", &["name", "target", "rel"])
+        .add_tag_attributes("img", &["style"])
+        // 表格对齐
+        .add_tag_attributes("th", &["align", "style"])
+        .add_tag_attributes("td", &["align", "style"])
+        .filter_style_properties(HashSet::from(["text-align", "max-width"]))
+        // 代码高亮 class 与无障碍属性
+        .add_generic_attributes(&[
+            "class",
+            "id",
+            "dir",
+            "tabindex",
+            "aria-label",
+            "aria-hidden",
+        ]);
+    builder
+}
+
+/// 使用默认白名单加上额外的标签和通用属性清理 HTML 片段
+///
+/// 不受 `GITNOTE_SANITIZE` 影响，每次调用构建一次规则，用于组内声明了白名单的原样输出的 HTML。
+pub fn clean_with_allowlist(
+    html: &str,
+    extra_tags: &[String],
+    extra_attributes: &[String],
+) -> String {
+    let mut builder = default_builder();
+    builder
+        .add_tags(extra_tags.iter().map(String::as_str))
+        .add_generic_attributes(extra_attributes.iter().map(String::as_str));
+    builder.clean(html).to_string()
+}
+
+/// 标题 id 统一规则的版本，修改 [`normalize_heading_ids`] 的输出时递增
+const HEADING_IDS_VERSION: u32 = 1;
+
+/// 对渲染结果进行 HTML 清理的 [`Renderer`] 包装
+///
+/// 清理后默认用 [`normalize_heading_ids`] 统一标题 id，切换渲染器不改变标题锚点。
+#[derive(Clone)]
+pub struct Sanitized<R> {
+    inner: R,
+    sanitizer: Sanitizer,
+    namespace_anchors: bool,
+    heading_ids: bool,
+}
+
+impl<R> Sanitized<R> {
+    pub fn new(inner: R, sanitizer: Sanitizer) -> Self {
+        Self {
+            inner,
+            sanitizer,
+            namespace_anchors: false,
+            heading_ids: true,
+        }
+    }
+
+    /// 设置是否额外生成锚点带命名空间的正文
+    pub fn with_namespace_anchors(mut self, enabled: bool) -> Self {
+        self.namespace_anchors = enabled;
+        self
+    }
+
+    /// 设置是否统一标题 id，默认启用
+    pub fn with_heading_ids(mut self, enabled: bool) -> Self {
+        self.heading_ids = enabled;
+        self
+    }
+
+    /// 内部渲染器
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// 替换内部渲染器，保留清理规则
+    pub fn map_inner<S>(self, f: impl FnOnce(R) -> S) -> Sanitized<S> {
+        Sanitized {
+            inner: f(self.inner),
+            sanitizer: self.sanitizer,
+            namespace_anchors: self.namespace_anchors,
+            heading_ids: self.heading_ids,
+        }
+    }
+
+    /// 清理 HTML，启用时统一标题 id
+    fn finish(&self, html: &str) -> String {
+        let html = self.sanitizer.clean(html);
+        if self.heading_ids {
+            normalize_heading_ids(&html)
+        } else {
+            html
+        }
+    }
+}
+
+impl<R: Renderer> Renderer for Sanitized<R> {
+    /// 使用内部渲染器渲染后清理 HTML 并统一标题 id
+    async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+        let html = self.inner.render(content).await?;
+        Ok(self.finish(&html))
+    }
+
+    /// 清理不改变渲染器，与内部渲染器相同
+    fn identity(&self) -> String {
+        self.inner.identity()
+    }
+
+    /// 内部渲染器版本加上清理规则版本，统一标题 id 时再加上其规则版本
+    fn version(&self) -> Option<String> {
+        let inner = self.inner.version()?;
+        let mut version = format!("{inner}+sanitize-{}", self.sanitizer.version());
+        if self.heading_ids {
+            version.push_str(&format!("+headings-v{HEADING_IDS_VERSION}"));
+        }
+        Some(version)
+    }
+
+    fn namespace_anchors(&self) -> bool {
+        self.namespace_anchors
+    }
+
+    /// 清理内部渲染器的结果，保留内部渲染器返回的标识
+    async fn render_tracked<T: AsRef<str>>(&self, content: T) -> Result<(String, String)> {
+        let (html, identity) = self.inner.render_tracked(content).await?;
+        Ok((self.finish(&html), identity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitizer() -> Sanitizer {
+        Sanitizer::new(&SanitizeOptions {
+            enabled: true,
+            extra_tags: vec![],
+            extra_attributes: vec![],
+        })
+    }
+
+    /// GitHub Markdown API 的典型输出
+    const GITHUB_HTML: &str = concat!(
+        r##"<div class="markdown-heading" dir="auto"><h2 tabindex="-1" class="heading-element" dir="auto">Title</h2><a id="user-content-title" class="anchor" aria-label="Permalink: Title" href="#title"></a></div>"##,
+        "\n",
+        r##"<p dir="auto"><a name="user-content-legacy"></a>legacy anchor</p>"##,
+        "\n",
+        r##"<ul class="contains-task-list">"##,
+        "\n",
+        r##"<li class="task-list-item"><input type="checkbox" id="" disabled="" class="task-list-item-checkbox" checked=""> done</li>"##,
+        "\n",
+        r##"</ul>"##,
+        "\n",
+        r##"<table>"##,
+        "\n",
+        r##"<thead><tr><th align="left">a</th><th align="center">b</th></tr></thead>"##,
+        "\n",
+        r##"<tbody><tr><td align="left">1</td><td align="center">2</td></tr></tbody>"##,
+        "\n",
+        r##"</table>"##,
+        "\n",
+        r##"<p dir="auto"><a target="_blank" rel="noopener noreferrer" href="https://example.com/a.png"><img src="https://example.com/a.png" alt="a"></a></p>"##,
+        "\n",
+        r##"<div class="highlight highlight-source-rust notranslate position-relative overflow-auto" dir="auto"><pre><span class="pl-k">fn</span> <span class="pl-en">main</span>() {}</pre></div>"##,
+    );
+
+    #[test]
+    fn test_github_constructs_survive() {
+        assert_eq!(sanitizer().clean(GITHUB_HTML), GITHUB_HTML);
+    }
+
+    #[test]
+    fn test_table_alignment_style_survives() {
+        let cleaned =
+            sanitizer().clean(r#"<table><tr><td style="text-align: center">1</td></tr></table>"#);
+
+        assert!(cleaned.contains("text-align"));
+    }
+
+    #[test]
+    fn test_dangerous_html_stripped() {
+        let cleaned = sanitizer().clean(concat!(
+            r#"<p onclick="steal()">hi</p>"#,
+            r#"<script>alert(1)</script>"#,
+            r#"<iframe src="https://evil.example"></iframe>"#,
+            r#"<a href="javascript:alert(1)" onmouseover="steal()">x</a>"#,
+            r#"<img src="x" onerror="steal()">"#,
+            r#"<td style="background: url(javascript:alert(1))">y</td>"#,
+        ));
+
+        assert!(!cleaned.contains("script"));
+        assert!(!cleaned.contains("iframe"));
+        assert!(!cleaned.contains("onclick"));
+        assert!(!cleaned.contains("onmouseover"));
+        assert!(!cleaned.contains("onerror"));
+        assert!(!cleaned.contains("steal"));
+        assert!(!cleaned.contains("background"));
+        assert!(cleaned.contains("<p>hi</p>"));
+    }
+
+    #[test]
+    fn test_disabled_sanitizer_passes_through() {
+        let sanitizer = Sanitizer::new(&SanitizeOptions {
+            enabled: false,
+            extra_tags: vec![],
+            extra_attributes: vec![],
+        });
+        let html = "<script>alert(1)</script>";
+
+        assert_eq!(sanitizer.clean(html), html);
+    }
+
+    /// 原样返回 Markdown 的渲染器
+    struct Echo;
+
+    impl Renderer for Echo {
+        async fn render<T: AsRef<str>>(&self, content: T) -> Result<String> {
+            Ok(content.as_ref().to_string())
+        }
+
+        fn identity(&self) -> String {
+            "echo".to_string()
+        }
+
+        fn version(&self) -> Option<String> {
+            Some("echo-v1".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heading_ids_normalized() {
+        let html = r##"<h2 id="Setup">Setup</h2><a href="#Setup">setup</a>"##;
+        let renderer = Sanitized::new(Echo, sanitizer());
+        assert_eq!(
+            renderer.render(html).await.unwrap(),
+            r##"<h2 id="setup">Setup</h2><a href="#setup">setup</a>"##
+        );
+
+        // 关闭后保留渲染器生成的 id，版本不同，已存储的渲染结果会重新渲染
+        let raw = Sanitized::new(Echo, sanitizer()).with_heading_ids(false);
+        assert_eq!(raw.render(html).await.unwrap(), html);
+        assert_ne!(raw.version(), renderer.version());
+    }
+
+    #[test]
+    fn test_extra_allowlist() {
+        let sanitizer = Sanitizer::new(&SanitizeOptions {
+            enabled: true,
+            extra_tags: vec!["note-box".to_string()],
+            extra_attributes: vec!["data-note".to_string()],
+        });
+        let html = r#"<note-box data-note="1"><p data-note="2">body</p></note-box>"#;
+
+        assert_eq!(sanitizer.clean(html), html);
+        assert_ne!(sanitizer.version(), self::sanitizer().version());
+    }
+}
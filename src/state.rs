@@ -1,49 +1,407 @@
-use std::{path::Path, sync::Arc};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use axum::extract::FromRef;
+use chrono_tz::Tz;
 
 use crate::{
-    render::GithubAPiRenderer,
-    storage::{DBPool, SqlxStore},
+    api::{EditUrlTemplate, HookAllowlist, IndexCache, PermalinkPattern, RepoStatsCache},
+    cdn::{CacheTagHeader, PurgeQueue},
+    content::{DEFAULT_PLAIN_SUMMARY_CHARS, GroupLicense},
+    git_client::FileClassifier,
+    git_sync::{BackupOptions, ContentLimits, SyncCoordinator, SyncDebouncer, SyncLog},
+    render::{
+        FallbackRenderer, GithubAPiRenderer, PendingRenderer, RenderCache, Sanitized, Sanitizer,
+    },
+    storage::{Backend, BackendStore, QueryLimits, TimedQuerier},
+    webmention::WebmentionQueue,
 };
 
+/// 应用使用的渲染器：GitHub API 不可用时降级为 [`PendingRenderer`]，输出经过 [`Sanitizer`] 清理
+pub type AppRenderer = Sanitized<FallbackRenderer<GithubAPiRenderer, PendingRenderer>>;
+
+/// 订阅源中链接使用的默认站点地址
+pub const DEFAULT_SITE_URL: &str = "http://localhost:3000";
+
+/// 订阅源默认的最大条目数
+pub const DEFAULT_FEED_ENTRIES: usize = 20;
+
 /// 应用程序上下文
 ///
-/// [`AppState`] 封装了数据库连接池、Git 渲染器和裸仓库引用，提供统一访问入口。
+/// [`AppState`] 封装了存储后端、Git 渲染器和裸仓库引用，提供统一访问入口。
+///
+/// 渲染器输出会经过 [`Sanitizer`] 清理，清理配置从环境变量读取；GitHub API 连续失败时熔断，
+/// 降级为 [`PendingRenderer`]，见 [`FallbackRenderer`]。
 #[derive(Clone, FromRef)]
 pub struct AppState {
-    pool: DBPool,
+    backend: Backend,
     repo_path: Arc<Path>,
-    renderer: GithubAPiRenderer,
+    renderer: AppRenderer,
+    limits: ContentLimits,
+    classifier: FileClassifier,
+    timezone: Tz,
+    coordinator: SyncCoordinator,
+    debouncer: Option<SyncDebouncer>,
+    plain_summary_chars: usize,
+    strict_sync: bool,
+    site_url: Arc<str>,
+    edit_url: Option<EditUrlTemplate>,
+    permalink: Option<PermalinkPattern>,
+    default_license: Option<GroupLicense>,
+    webmentions: Option<WebmentionQueue>,
+    cache_tag_header: CacheTagHeader,
+    cdn_purge: Option<PurgeQueue>,
+    backup: Option<BackupOptions>,
+    sync_log: SyncLog,
+    index_cache: IndexCache,
+    repo_stats_cache: RepoStatsCache,
+    render_cache: RenderCache,
+    #[from_ref(skip)]
+    admin_token: Option<Arc<str>>,
+    #[from_ref(skip)]
+    hook_allowlist: Option<HookAllowlist>,
+    #[from_ref(skip)]
+    feed_entries: usize,
+    #[from_ref(skip)]
+    schema_ready: bool,
+    #[from_ref(skip)]
+    query_limits: QueryLimits,
+    #[from_ref(skip)]
+    config_fingerprint: Option<Arc<str>>,
+    #[from_ref(skip)]
+    default_og_image: Option<Arc<str>>,
+}
+
+impl FromRef<AppState> for TimedQuerier {
+    fn from_ref(app: &AppState) -> Self {
+        app.querier()
+    }
 }
 
 impl AppState {
     /// 创建一个新的 [`App`] 实例
-    pub fn new(pool: DBPool, renderer: GithubAPiRenderer, repo_path: impl AsRef<Path>) -> Self {
+    ///
+    /// `backend` 可以是数据库连接池或 [`MemoryStorage`](crate::storage::MemoryStorage)。
+    pub fn new(
+        backend: impl Into<Backend>,
+        renderer: GithubAPiRenderer,
+        repo_path: impl AsRef<Path>,
+    ) -> Self {
         let repo_path = Arc::<Path>::from(repo_path.as_ref());
+        let renderer = Sanitized::new(
+            FallbackRenderer::new(renderer, PendingRenderer),
+            Sanitizer::default(),
+        );
 
         Self {
             repo_path,
             renderer,
-            pool,
+            backend: backend.into(),
+            limits: ContentLimits::default(),
+            classifier: FileClassifier::default(),
+            timezone: Tz::UTC,
+            coordinator: SyncCoordinator::new(),
+            debouncer: None,
+            plain_summary_chars: DEFAULT_PLAIN_SUMMARY_CHARS,
+            strict_sync: false,
+            site_url: Arc::from(DEFAULT_SITE_URL),
+            edit_url: None,
+            permalink: None,
+            default_license: None,
+            webmentions: None,
+            cache_tag_header: CacheTagHeader::default(),
+            cdn_purge: None,
+            backup: None,
+            sync_log: SyncLog::default(),
+            index_cache: IndexCache::default(),
+            repo_stats_cache: RepoStatsCache::default(),
+            render_cache: RenderCache::default(),
+            admin_token: None,
+            hook_allowlist: None,
+            feed_entries: DEFAULT_FEED_ENTRIES,
+            schema_ready: true,
+            query_limits: QueryLimits::default(),
+            config_fingerprint: None,
+            default_og_image: None,
         }
     }
 
+    /// 设置同步时的文章内容限制
+    pub fn with_limits(mut self, limits: ContentLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// 获取仓储对象
-    pub fn storage(&self) -> SqlxStore {
-        SqlxStore::new(self.pool.clone())
+    pub fn storage(&self) -> BackendStore {
+        self.backend.store()
+    }
+
+    /// 获取查询对象，每次查询受 [`AppState::with_query_limits`] 设置的超时限制
+    pub fn querier(&self) -> TimedQuerier {
+        TimedQuerier::new(self.backend.clone(), self.query_limits)
     }
 
-    /// 获取查询对象
-    pub fn querier(&self) -> &DBPool {
-        &self.pool
+    /// 设置处理请求时的查询超时和慢查询阈值
+    pub fn with_query_limits(mut self, limits: QueryLimits) -> Self {
+        self.query_limits = limits;
+        self
     }
 
     /// 获取 Markdown 渲染器
-    pub fn renderer(&self) -> &GithubAPiRenderer {
+    pub fn renderer(&self) -> &AppRenderer {
         &self.renderer
     }
 
+    /// 获取存储后端
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// 设置 GitHub API 连续失败多少次后熔断，以及熔断后再次尝试前的冷却时间
+    pub fn with_render_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.renderer = self
+            .renderer
+            .map_inner(|r| r.with_breaker(threshold, cooldown));
+        self
+    }
+
+    /// 设置同步时使用的文件类型分类器
+    pub fn with_classifier(mut self, classifier: FileClassifier) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// 获取文件类型分类器
+    pub fn classifier(&self) -> &FileClassifier {
+        &self.classifier
+    }
+
+    /// 设置解析和显示时间使用的时区
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// 获取时区
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// 获取文章内容限制
+    pub fn limits(&self) -> &ContentLimits {
+        &self.limits
+    }
+
+    /// 设置文章列表中纯文本摘要的最大字符数
+    pub fn with_plain_summary_chars(mut self, chars: usize) -> Self {
+        self.plain_summary_chars = chars;
+        self
+    }
+
+    /// 获取纯文本摘要的最大字符数
+    pub fn plain_summary_chars(&self) -> usize {
+        self.plain_summary_chars
+    }
+
+    /// 设置增量同步是否在任一文件处理失败时整体失败
+    pub fn with_strict_sync(mut self, strict: bool) -> Self {
+        self.strict_sync = strict;
+        self
+    }
+
+    /// 设置同步时是否额外生成锚点带命名空间的正文
+    pub fn with_namespace_anchors(mut self, enabled: bool) -> Self {
+        self.renderer = self.renderer.with_namespace_anchors(enabled);
+        self
+    }
+
+    /// 设置渲染后是否统一标题 id，见 [`normalize_heading_ids`](crate::render::normalize_heading_ids)
+    pub fn with_heading_ids(mut self, enabled: bool) -> Self {
+        self.renderer = self.renderer.with_heading_ids(enabled);
+        self
+    }
+
+    /// 增量同步是否为严格模式
+    pub fn strict_sync(&self) -> bool {
+        self.strict_sync
+    }
+
+    /// 设置订阅源中链接使用的站点地址，末尾的 `/` 会被去掉
+    pub fn with_site_url(mut self, site_url: &str) -> Self {
+        self.site_url = Arc::from(site_url.trim_end_matches('/'));
+        self
+    }
+
+    /// 获取站点地址，不以 `/` 结尾
+    pub fn site_url(&self) -> &str {
+        &self.site_url
+    }
+
+    /// 设置文章没有图片时分享卡片使用的图片，绝对地址或站点上的路径
+    pub fn with_default_og_image(mut self, image: Option<&str>) -> Self {
+        self.default_og_image = image.map(Arc::from);
+        self
+    }
+
+    /// 获取分享卡片的默认图片
+    pub fn default_og_image(&self) -> Option<&str> {
+        self.default_og_image.as_deref()
+    }
+
+    /// 设置文章编辑链接模板，未设置时文章详情的 `edit_url` 为 `null`
+    pub fn with_edit_url(mut self, template: Option<EditUrlTemplate>) -> Self {
+        self.edit_url = template;
+        self
+    }
+
+    /// 获取文章编辑链接模板
+    pub fn edit_url(&self) -> Option<&EditUrlTemplate> {
+        self.edit_url.as_ref()
+    }
+
+    /// 设置旧文章地址的固定链接格式，未设置时 `/api/permalink` 总是返回 404
+    pub fn with_permalink(mut self, pattern: Option<PermalinkPattern>) -> Self {
+        self.permalink = pattern;
+        self
+    }
+
+    /// 设置组未声明许可协议时使用的默认协议
+    pub fn with_default_license(mut self, license: Option<GroupLicense>) -> Self {
+        self.default_license = license;
+        self
+    }
+
+    /// 获取默认许可协议
+    pub fn default_license(&self) -> Option<&GroupLicense> {
+        self.default_license.as_ref()
+    }
+
+    /// 设置 webmention 发送队列，未设置时同步后不发送
+    pub fn with_webmentions(mut self, queue: Option<WebmentionQueue>) -> Self {
+        self.webmentions = queue;
+        self
+    }
+
+    /// 获取 webmention 发送队列
+    pub fn webmentions(&self) -> Option<&WebmentionQueue> {
+        self.webmentions.as_ref()
+    }
+
+    /// 设置写入缓存标签的响应头名称
+    pub fn with_cache_tag_header(mut self, header: CacheTagHeader) -> Self {
+        self.cache_tag_header = header;
+        self
+    }
+
+    /// 获取写入缓存标签的响应头名称
+    pub fn cache_tag_header(&self) -> &CacheTagHeader {
+        &self.cache_tag_header
+    }
+
+    /// 设置同步后清除 CDN 缓存的队列，为 [`None`] 时不清除
+    pub fn with_cdn_purge(mut self, queue: Option<PurgeQueue>) -> Self {
+        self.cdn_purge = queue;
+        self
+    }
+
+    /// 获取清除 CDN 缓存的队列
+    pub fn cdn_purge(&self) -> Option<&PurgeQueue> {
+        self.cdn_purge.as_ref()
+    }
+
+    /// 设置重建前的备份，为 [`None`] 时重建前不备份
+    pub fn with_backup(mut self, options: Option<BackupOptions>) -> Self {
+        self.backup = options;
+        self
+    }
+
+    /// 获取重建前的备份配置
+    pub fn backup(&self) -> Option<&BackupOptions> {
+        self.backup.as_ref()
+    }
+
+    /// 设置订阅源的最大条目数
+    pub fn with_feed_entries(mut self, entries: usize) -> Self {
+        self.feed_entries = entries;
+        self
+    }
+
+    /// 获取订阅源的最大条目数
+    pub fn feed_entries(&self) -> usize {
+        self.feed_entries
+    }
+
+    /// 设置数据库是否已迁移，未迁移时同步接口返回 503
+    pub fn with_schema_ready(mut self, ready: bool) -> Self {
+        self.schema_ready = ready;
+        self
+    }
+
+    /// 数据库是否已迁移，默认为 `true`
+    pub fn schema_ready(&self) -> bool {
+        self.schema_ready
+    }
+
+    /// 获取同步协调器
+    pub fn coordinator(&self) -> &SyncCoordinator {
+        &self.coordinator
+    }
+
+    /// 设置增量同步的防抖静默期，为 0 时关闭防抖，每次推送同步执行
+    pub fn with_sync_debounce(mut self, quiet: Duration) -> Self {
+        self.debouncer = (!quiet.is_zero()).then(|| SyncDebouncer::new(quiet));
+        self
+    }
+
+    /// 获取增量同步防抖器，未开启防抖时为 `None`
+    pub fn debouncer(&self) -> Option<&SyncDebouncer> {
+        self.debouncer.as_ref()
+    }
+
+    /// 设置保留的同步记录数，为 0 时不记录
+    pub fn with_sync_log_size(mut self, entries: usize) -> Self {
+        self.sync_log = SyncLog::new(entries);
+        self
+    }
+
+    /// 获取最近的同步记录
+    pub fn sync_log(&self) -> &SyncLog {
+        &self.sync_log
+    }
+
+    /// 设置管理接口的 bearer token，未设置时需要 token 的接口一律返回 401
+    pub fn with_admin_token(mut self, token: Option<&str>) -> Self {
+        self.admin_token = token.map(Arc::from);
+        self
+    }
+
+    /// 获取管理接口的 bearer token
+    pub fn admin_token(&self) -> Option<&str> {
+        self.admin_token.as_deref()
+    }
+
+    /// 设置 Git 仓库接口允许访问的来源地址，未设置时不限制
+    pub fn with_hook_allowlist(mut self, allowlist: Option<HookAllowlist>) -> Self {
+        self.hook_allowlist = allowlist;
+        self
+    }
+
+    /// 获取 Git 仓库接口允许访问的来源地址
+    pub fn hook_allowlist(&self) -> Option<&HookAllowlist> {
+        self.hook_allowlist.as_ref()
+    }
+
+    /// 设置配置指纹，见 [`Config::fingerprint`](crate::config::Config::fingerprint)
+    pub fn with_config_fingerprint(mut self, fingerprint: impl Into<Arc<str>>) -> Self {
+        self.config_fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// 获取配置指纹，未设置时为 `None`
+    pub fn config_fingerprint(&self) -> Option<&str> {
+        self.config_fingerprint.as_deref()
+    }
+
     /// 获取仓库路径
     pub fn repo_path(&self) -> &Path {
         &self.repo_path
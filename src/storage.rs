@@ -1,11 +1,43 @@
+mod backend;
+mod cipher;
+mod dump;
+mod memory;
 mod models;
 mod postgres;
 mod querier;
+mod short_id;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod store;
+mod timed;
 
 pub use self::{
-    models::{ArticleDetail, ArticleSummary, Group},
-    postgres::{DBPool, init_db_from_env, migrate, new_db_poll},
+    backend::{Backend, BackendStore, init_db_from_env},
+    cipher::{CipherError, ContentCipher},
+    dump::{
+        ArticleDump, DUMP_FORMAT, DUMP_PAGE_SIZE, DUMP_VERSION, DumpCounts, DumpError, DumpHeader,
+        DumpRecord, DumpTable, GroupDump, export_dump, import_dump, read_dump,
+    },
+    memory::{MemoryStorage, MemoryStore},
+    models::{
+        ArticleDetail, ArticleFingerprint, ArticleShortId, ArticleSimhash, ArticleSlug,
+        ArticleSummary, Attachment, AuthorSummary, BrokenLink, CategoryGroup, ContentBlob,
+        DateRange, DigestRun, Group, GroupFingerprint, GroupStats, ListColumns, ListSort, Neighbor,
+        OrphanArticle, PrunedArticle, Redirect, RenderSort, RenderStat, RetentionChange,
+        SiteCounts, StaleRender, SyncFailure, SyncState, TagCount, TitleEntry, WebmentionSource,
+    },
+    postgres::{
+        DBPool, MIGRATIONS, Migration, apply_migration, migrate, missing_schema, new_db_poll,
+        run_migrations,
+    },
     querier::Querier,
-    store::{SqlxStore, Store},
+    short_id::{SHORT_ID_LEN, is_short_id, new_short_id},
+    store::{Savepoint, SqlxStore, Store},
+    timed::{DEFAULT_QUERY_TIMEOUT, DEFAULT_SLOW_QUERY, QueryLimits, TimedQuerier},
+};
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::{
+    SQLITE_MIGRATIONS, SqliteStore, apply_sqlite_migration, new_sqlite_pool, run_sqlite_migrations,
+    sqlite_missing_schema,
 };
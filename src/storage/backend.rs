@@ -0,0 +1,588 @@
+use std::{collections::HashMap, env, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+
+use crate::{
+    content::{Article, ArticleRef, Group, GroupAuthor, GroupPath, GroupSettings},
+    error,
+};
+
+use super::{
+    ArticleDetail, ArticleFingerprint, ArticleShortId, ArticleSimhash, ArticleSlug, ArticleSummary,
+    Attachment, AuthorSummary, BrokenLink, CategoryGroup, ContentBlob, DBPool, DateRange,
+    DigestRun, DumpCounts, DumpError, DumpRecord, DumpTable, GroupFingerprint, GroupStats,
+    ListColumns, ListSort, MemoryStorage, MemoryStore, OrphanArticle, PrunedArticle, Querier,
+    Redirect, RenderSort, RenderStat, RetentionChange, Savepoint, SiteCounts, SqlxStore,
+    StaleRender, Store, SyncFailure, SyncState, TagCount, TitleEntry, WebmentionSource,
+    missing_schema, models, new_db_poll, run_migrations,
+};
+#[cfg(feature = "sqlite")]
+use super::{SqliteStore, new_sqlite_pool, run_sqlite_migrations, sqlite_missing_schema};
+
+/// 应用使用的存储后端
+///
+/// - [`Backend::Postgres`]：线上使用的数据库
+/// - [`Backend::Sqlite`]：SQLite 数据库，用于小型自托管部署，需要启用 `sqlite` feature
+/// - [`Backend::Memory`]：内存存储，用于不依赖数据库的测试
+#[derive(Debug, Clone)]
+pub enum Backend {
+    Postgres(DBPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqlitePool),
+    Memory(MemoryStorage),
+}
+
+impl Backend {
+    /// 按连接 URL 的 scheme 连接数据库
+    ///
+    /// `sqlite:` 开头时使用 SQLite，未启用 `sqlite` feature 时返回错误；其他 URL 按 PostgreSQL 连接。
+    pub async fn connect(conn_url: &str) -> Result<Self, sqlx::Error> {
+        Self::connect_with_timeout(conn_url, None).await
+    }
+
+    /// 与 [`Backend::connect`] 相同，PostgreSQL 连接设置 `statement_timeout`，见 [`new_db_poll`]
+    ///
+    /// SQLite 不支持服务端超时，忽略该参数。
+    pub async fn connect_with_timeout(
+        conn_url: &str,
+        statement_timeout: Option<Duration>,
+    ) -> Result<Self, sqlx::Error> {
+        if conn_url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            return Ok(new_sqlite_pool(conn_url).await?.into());
+            #[cfg(not(feature = "sqlite"))]
+            return Err(sqlx::Error::Configuration(
+                "SQLite backend requires the `sqlite` feature".into(),
+            ));
+        }
+        Ok(new_db_poll(conn_url, statement_timeout).await?.into())
+    }
+
+    /// 编译时启用的后端名称，与 [`Backend::kind`] 的取值相同
+    pub const COMPILED: &[&str] = &[
+        "postgres",
+        #[cfg(feature = "sqlite")]
+        "sqlite",
+        "memory",
+    ];
+
+    /// 后端名称：`postgres`、`sqlite` 或 `memory`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Postgres(_) => "postgres",
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(_) => "sqlite",
+            Self::Memory(_) => "memory",
+        }
+    }
+
+    /// 获取写入该后端的 [`BackendStore`]
+    pub fn store(&self) -> BackendStore {
+        match self {
+            Self::Postgres(pool) => BackendStore::Postgres(SqlxStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => BackendStore::Sqlite(SqliteStore::new(pool.clone())),
+            Self::Memory(memory) => BackendStore::Memory(memory.store()),
+        }
+    }
+
+    /// 按顺序执行该后端尚未执行的迁移，返回本次执行的文件名，内存存储不需要迁移
+    pub async fn run_migrations(&self) -> Result<Vec<&'static str>, sqlx::Error> {
+        match self {
+            Self::Postgres(pool) => run_migrations(pool).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => run_sqlite_migrations(pool).await,
+            Self::Memory(_) => Ok(vec![]),
+        }
+    }
+
+    /// 查询该后端缺失的表和列，见 [`missing_schema`]
+    pub async fn missing_schema(&self) -> Result<Vec<String>, sqlx::Error> {
+        match self {
+            Self::Postgres(pool) => missing_schema(pool).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(pool) => sqlite_missing_schema(pool).await,
+            Self::Memory(_) => Ok(vec![]),
+        }
+    }
+}
+
+/// 从环境变量 `DATABASE_URL` 连接数据库，见 [`Backend::connect`]
+pub async fn init_db_from_env() -> Backend {
+    let conn_url = env::var("DATABASE_URL").expect("`DATABASE_URL` env not set");
+    Backend::connect(&conn_url).await.unwrap()
+}
+
+impl From<DBPool> for Backend {
+    fn from(pool: DBPool) -> Self {
+        Self::Postgres(pool)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<SqlitePool> for Backend {
+    fn from(pool: SqlitePool) -> Self {
+        Self::Sqlite(pool)
+    }
+}
+
+impl From<MemoryStorage> for Backend {
+    fn from(memory: MemoryStorage) -> Self {
+        Self::Memory(memory)
+    }
+}
+
+/// 按后端类型分发调用
+macro_rules! dispatch {
+    ($value:expr, $inner:ident => $body:expr) => {
+        match $value {
+            Self::Postgres($inner) => $body,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite($inner) => $body,
+            Self::Memory($inner) => $body,
+        }
+    };
+}
+
+impl Querier for Backend {
+    type Error = sqlx::Error;
+
+    async fn get_one(&self, slug: impl AsRef<str>) -> Result<Option<ArticleDetail>, Self::Error> {
+        dispatch!(self, q => q.get_one(slug).await)
+    }
+
+    async fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> Result<Option<DateTime<FixedOffset>>, sqlx::Error> {
+        dispatch!(self, q => q.article_updated_at(slug).await)
+    }
+
+    async fn short_id_slug(&self, short_id: &str) -> Result<Option<String>, sqlx::Error> {
+        dispatch!(self, q => q.short_id_slug(short_id).await)
+    }
+
+    async fn article_list(
+        &self,
+        page: i32,
+        size: i32,
+        group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
+        tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
+    ) -> Result<Vec<ArticleSummary>, sqlx::Error> {
+        dispatch!(self, q => q.article_list(page, size, group, author, category, tags, archived, dates, columns, sort).await)
+    }
+
+    async fn category_groups(&self, category: &str) -> Result<Vec<CategoryGroup>, sqlx::Error> {
+        dispatch!(self, q => q.category_groups(category).await)
+    }
+
+    async fn authors(&self) -> Result<Vec<AuthorSummary>, sqlx::Error> {
+        dispatch!(self, q => q.authors().await)
+    }
+
+    async fn groups(&self) -> Result<Vec<models::Group>, sqlx::Error> {
+        dispatch!(self, q => q.groups().await)
+    }
+
+    async fn tags(&self) -> Result<Vec<String>, sqlx::Error> {
+        dispatch!(self, q => q.tags().await)
+    }
+
+    async fn tags_with_counts(&self) -> Result<Vec<TagCount>, sqlx::Error> {
+        dispatch!(self, q => q.tags_with_counts().await)
+    }
+
+    async fn article_tags(&self) -> Result<Vec<Vec<String>>, sqlx::Error> {
+        dispatch!(self, q => q.article_tags().await)
+    }
+
+    async fn public_slugs(&self) -> Result<Vec<String>, sqlx::Error> {
+        dispatch!(self, q => q.public_slugs().await)
+    }
+
+    async fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleSlug>, sqlx::Error> {
+        dispatch!(self, q => q.article_slugs(group, limit).await)
+    }
+
+    async fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TitleEntry>, sqlx::Error> {
+        dispatch!(self, q => q.title_index(group, limit).await)
+    }
+
+    async fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> Result<Option<Redirect>, sqlx::Error> {
+        dispatch!(self, q => q.redirect(group, slug).await)
+    }
+
+    async fn links_due(&self, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        dispatch!(self, q => q.links_due(limit).await)
+    }
+
+    async fn broken_links(&self) -> Result<Vec<BrokenLink>, sqlx::Error> {
+        dispatch!(self, q => q.broken_links().await)
+    }
+
+    async fn sync_state(&self) -> Result<Option<SyncState>, sqlx::Error> {
+        dispatch!(self, q => q.sync_state().await)
+    }
+
+    async fn site_counts(&self) -> Result<SiteCounts, sqlx::Error> {
+        dispatch!(self, q => q.site_counts().await)
+    }
+
+    async fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<WebmentionSource>, sqlx::Error> {
+        dispatch!(self, q => q.webmention_sources(paths).await)
+    }
+
+    async fn stale_renders(&self, current: &str) -> Result<Vec<StaleRender>, sqlx::Error> {
+        dispatch!(self, q => q.stale_renders(current).await)
+    }
+
+    async fn orphan_articles(&self) -> Result<Vec<OrphanArticle>, sqlx::Error> {
+        dispatch!(self, q => q.orphan_articles().await)
+    }
+
+    async fn pruned_articles(&self) -> Result<Vec<PrunedArticle>, sqlx::Error> {
+        dispatch!(self, q => q.pruned_articles().await)
+    }
+
+    async fn group_stats(&self) -> Result<Vec<GroupStats>, sqlx::Error> {
+        dispatch!(self, q => q.group_stats().await)
+    }
+
+    async fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> Result<Vec<RenderStat>, sqlx::Error> {
+        dispatch!(self, q => q.render_report(sort, limit).await)
+    }
+
+    async fn last_digest_run(&self) -> Result<Option<DigestRun>, sqlx::Error> {
+        dispatch!(self, q => q.last_digest_run().await)
+    }
+}
+
+/// [`Backend`] 对应的 [`Store`]
+pub enum BackendStore {
+    Postgres(SqlxStore),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStore),
+    Memory(MemoryStore),
+}
+
+impl ToOwned for BackendStore {
+    type Owned = BackendStore;
+
+    fn to_owned(&self) -> Self::Owned {
+        dispatch!(self, s => s.to_owned().into())
+    }
+}
+
+impl From<SqlxStore> for BackendStore {
+    fn from(store: SqlxStore) -> Self {
+        Self::Postgres(store)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<SqliteStore> for BackendStore {
+    fn from(store: SqliteStore) -> Self {
+        Self::Sqlite(store)
+    }
+}
+
+impl From<MemoryStore> for BackendStore {
+    fn from(store: MemoryStore) -> Self {
+        Self::Memory(store)
+    }
+}
+
+impl Store for BackendStore {
+    fn clean(&mut self) -> &mut Self {
+        dispatch!(self, s => { s.clean(); });
+        self
+    }
+
+    fn upsert_group(&mut self, group: &Group) -> &mut Self {
+        dispatch!(self, s => { s.upsert_group(group); });
+        self
+    }
+
+    fn remove_group(&mut self, group: &Group) -> &mut Self {
+        dispatch!(self, s => { s.remove_group(group); });
+        self
+    }
+
+    fn rename_group(&mut self, from: &GroupPath, to: &GroupPath) -> &mut Self {
+        dispatch!(self, s => { s.rename_group(from, to); });
+        self
+    }
+
+    async fn prune_groups(&self, keep: &[GroupPath]) -> Result<Vec<GroupPath>, error::Error> {
+        dispatch!(self, s => s.prune_groups(keep).await)
+    }
+
+    async fn apply_retention(
+        &self,
+        groups: Option<&[GroupPath]>,
+    ) -> Result<Vec<RetentionChange>, error::Error> {
+        dispatch!(self, s => s.apply_retention(groups).await)
+    }
+
+    fn upsert_article(&mut self, article: &Article) -> &mut Self {
+        dispatch!(self, s => { s.upsert_article(article); });
+        self
+    }
+
+    fn upsert_encrypted_article(&mut self, article: &Article) -> Result<&mut Self, error::Error> {
+        dispatch!(self, s => { s.upsert_encrypted_article(article)?; });
+        Ok(self)
+    }
+
+    fn upsert_blob_article(&mut self, article: &Article, oid: &str) -> &mut Self {
+        dispatch!(self, s => { s.upsert_blob_article(article, oid); });
+        self
+    }
+
+    async fn content_blob(
+        &self,
+        oid: &str,
+        renderer: &str,
+    ) -> Result<Option<ContentBlob>, error::Error> {
+        dispatch!(self, s => s.content_blob(oid, renderer).await)
+    }
+
+    fn upsert_content_blob(&mut self, blob: &ContentBlob) -> &mut Self {
+        dispatch!(self, s => { s.upsert_content_blob(blob); });
+        self
+    }
+
+    fn prune_content_blobs(&mut self) -> &mut Self {
+        dispatch!(self, s => { s.prune_content_blobs(); });
+        self
+    }
+
+    async fn group_settings(&self) -> Result<HashMap<GroupPath, GroupSettings>, error::Error> {
+        dispatch!(self, s => s.group_settings().await)
+    }
+
+    async fn group_authors(&self) -> Result<HashMap<GroupPath, GroupAuthor>, error::Error> {
+        dispatch!(self, s => s.group_authors().await)
+    }
+
+    async fn group_slugs(&self) -> Result<HashMap<GroupPath, String>, error::Error> {
+        dispatch!(self, s => s.group_slugs().await)
+    }
+
+    async fn article_simhashes(&self) -> Result<Vec<ArticleSimhash>, error::Error> {
+        dispatch!(self, s => s.article_simhashes().await)
+    }
+
+    async fn group_visibility(&self) -> Result<HashMap<GroupPath, bool>, error::Error> {
+        dispatch!(self, s => s.group_visibility().await)
+    }
+
+    fn remove_article(&mut self, article_ref: ArticleRef<'_>) -> &mut Self {
+        dispatch!(self, s => { s.remove_article(article_ref); });
+        self
+    }
+
+    fn upsert_redirect(&mut self, from: ArticleRef<'_>, to: ArticleRef<'_>) -> &mut Self {
+        dispatch!(self, s => { s.upsert_redirect(from, to); });
+        self
+    }
+
+    fn update_comment_counts(&mut self, counts: &HashMap<String, i64>) -> &mut Self {
+        dispatch!(self, s => { s.update_comment_counts(counts); });
+        self
+    }
+
+    fn set_article_source(&mut self, slug: &str, path: &str, commit: &str) -> &mut Self {
+        dispatch!(self, s => { s.set_article_source(slug, path, commit); });
+        self
+    }
+
+    async fn article_short_ids(&self) -> Result<Vec<ArticleShortId>, error::Error> {
+        dispatch!(self, s => s.article_short_ids().await)
+    }
+
+    fn set_article_short_id(&mut self, slug: &str, short_id: &str) -> &mut Self {
+        dispatch!(self, s => { s.set_article_short_id(slug, short_id); });
+        self
+    }
+
+    fn set_group_description(&mut self, group_id: &GroupPath, html: Option<&str>) -> &mut Self {
+        dispatch!(self, s => { s.set_group_description(group_id, html); });
+        self
+    }
+
+    fn set_article_renderer(&mut self, slug: &str, renderer: &str) -> &mut Self {
+        dispatch!(self, s => { s.set_article_renderer(slug, renderer); });
+        self
+    }
+
+    fn set_article_render_stats(
+        &mut self,
+        slug: &str,
+        render_ms: i64,
+        content_bytes: i64,
+    ) -> &mut Self {
+        dispatch!(self, s => { s.set_article_render_stats(slug, render_ms, content_bytes); });
+        self
+    }
+
+    fn replace_links(&mut self, slug: &str, urls: &[String]) -> &mut Self {
+        dispatch!(self, s => { s.replace_links(slug, urls); });
+        self
+    }
+
+    fn replace_attachments(
+        &mut self,
+        article_ref: ArticleRef<'_>,
+        attachments: &[Attachment],
+    ) -> &mut Self {
+        dispatch!(self, s => { s.replace_attachments(article_ref, attachments); });
+        self
+    }
+
+    fn record_link_status(
+        &mut self,
+        url: &str,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        dispatch!(self, s => { s.record_link_status(url, status, error); });
+        self
+    }
+
+    fn prune_links(&mut self) -> &mut Self {
+        dispatch!(self, s => { s.prune_links(); });
+        self
+    }
+
+    fn record_webmention(
+        &mut self,
+        slug: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        dispatch!(self, s => { s.record_webmention(slug, target, endpoint, status, error); });
+        self
+    }
+
+    fn remove_webmention(&mut self, slug: &str, target: &str) -> &mut Self {
+        dispatch!(self, s => { s.remove_webmention(slug, target); });
+        self
+    }
+
+    fn record_digest_run(
+        &mut self,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        article_count: i64,
+        status: &str,
+        error: Option<&str>,
+    ) -> &mut Self {
+        dispatch!(self, s => {
+            s.record_digest_run(period_start, period_end, article_count, status, error);
+        });
+        self
+    }
+
+    fn replace_includes(&mut self, path: &str, includes: &[String]) -> &mut Self {
+        dispatch!(self, s => { s.replace_includes(path, includes); });
+        self
+    }
+
+    async fn include_dependents(&self, paths: &[String]) -> Result<Vec<String>, error::Error> {
+        dispatch!(self, s => s.include_dependents(paths).await)
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        dispatch!(self, s => s.savepoint())
+    }
+
+    fn rollback_to(&mut self, savepoint: Savepoint) -> &mut Self {
+        dispatch!(self, s => { s.rollback_to(savepoint); });
+        self
+    }
+
+    fn record_sync_failure(&mut self, path: &str, reason: &str, commit: &str) -> &mut Self {
+        dispatch!(self, s => { s.record_sync_failure(path, reason, commit); });
+        self
+    }
+
+    fn clear_sync_failure(&mut self, path: &str) -> &mut Self {
+        dispatch!(self, s => { s.clear_sync_failure(path); });
+        self
+    }
+
+    fn retarget_sync_failures(&mut self, commit: &str) -> &mut Self {
+        dispatch!(self, s => { s.retarget_sync_failures(commit); });
+        self
+    }
+
+    async fn sync_failures(&self) -> Result<Vec<SyncFailure>, error::Error> {
+        dispatch!(self, s => s.sync_failures().await)
+    }
+
+    async fn fingerprint_rows(
+        &self,
+    ) -> Result<(Vec<GroupFingerprint>, Vec<ArticleFingerprint>), error::Error> {
+        dispatch!(self, s => s.fingerprint_rows().await)
+    }
+
+    fn record_sync_state(&mut self, commit: &str, fingerprint: &str) -> &mut Self {
+        dispatch!(self, s => { s.record_sync_state(commit, fingerprint); });
+        self
+    }
+
+    async fn dump_page(
+        &self,
+        table: DumpTable,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DumpRecord>, error::Error> {
+        dispatch!(self, s => s.dump_page(table, after, limit).await)
+    }
+
+    async fn load_dump<I>(&self, records: I, truncate: bool) -> Result<DumpCounts, error::Error>
+    where
+        I: Iterator<Item = Result<DumpRecord, DumpError>> + Send,
+    {
+        dispatch!(self, s => s.load_dump(records, truncate).await)
+    }
+
+    fn enforce_quotas(&mut self) -> &mut Self {
+        dispatch!(self, s => { s.enforce_quotas(); });
+        self
+    }
+
+    async fn commit(self) -> Result<(), error::Error> {
+        dispatch!(self, s => s.commit().await)
+    }
+}
@@ -0,0 +1,202 @@
+use std::sync::OnceLock;
+
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+/// 内容加解密错误
+#[derive(Debug, thiserror::Error)]
+pub enum CipherError {
+    /// 未配置密钥
+    #[error("GITNOTE_CONTENT_KEY is not set, refusing to handle encrypted content")]
+    MissingKey,
+
+    /// 密钥格式错误
+    #[error("content key must be 32 bytes encoded as base64")]
+    InvalidKey,
+
+    /// 加密失败
+    #[error("failed to encrypt content")]
+    Encrypt,
+
+    /// 解密失败，通常是密钥错误或数据损坏
+    #[error("failed to decrypt content: wrong key or corrupted data")]
+    Decrypt,
+}
+
+/// 加密字段在行内的序号，用于从行 nonce 派生各字段的 nonce
+#[derive(Debug, Clone, Copy)]
+pub enum Field {
+    Content = 0,
+    Summary = 1,
+    SummaryPlain = 2,
+}
+
+static INSTALLED: OnceLock<ContentCipher> = OnceLock::new();
+
+/// 文章内容的 AES-256-GCM 加解密器
+///
+/// 每行文章使用独立的随机 nonce，行内各字段通过 [`Field`] 派生不同的 nonce，
+/// 密文以 base64 形式存储。
+#[derive(Clone)]
+pub struct ContentCipher(Aes256Gcm);
+
+impl ContentCipher {
+    const NONCE_LEN: usize = 12;
+
+    /// 从 base64 编码的 32 字节密钥创建
+    pub fn from_base64(key: &str) -> Result<Self, CipherError> {
+        let key = STANDARD
+            .decode(key.trim())
+            .map_err(|_| CipherError::InvalidKey)?;
+        Aes256Gcm::new_from_slice(&key)
+            .map(Self)
+            .map_err(|_| CipherError::InvalidKey)
+    }
+
+    /// 设置全局使用的加解密器，仅首次调用生效
+    pub fn install(self) {
+        let _ = INSTALLED.set(self);
+    }
+
+    /// 获取全局加解密器，未配置密钥时返回 [`CipherError::MissingKey`]
+    pub fn installed() -> Result<&'static Self, CipherError> {
+        INSTALLED.get().ok_or(CipherError::MissingKey)
+    }
+
+    /// 生成新的行 nonce
+    pub fn new_nonce() -> Vec<u8> {
+        Aes256Gcm::generate_nonce(&mut OsRng).to_vec()
+    }
+
+    /// 加密字段，返回 base64 密文
+    pub fn seal(&self, nonce: &[u8], field: Field, plaintext: &str) -> Result<String, CipherError> {
+        let nonce = Self::field_nonce(nonce, field).ok_or(CipherError::Encrypt)?;
+        let ciphertext = self
+            .0
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|_| CipherError::Encrypt)?;
+        Ok(STANDARD.encode(ciphertext))
+    }
+
+    /// 解密 base64 密文
+    pub fn open(
+        &self,
+        nonce: &[u8],
+        field: Field,
+        ciphertext: &str,
+    ) -> Result<String, CipherError> {
+        let nonce = Self::field_nonce(nonce, field).ok_or(CipherError::Decrypt)?;
+        let ciphertext = STANDARD
+            .decode(ciphertext)
+            .map_err(|_| CipherError::Decrypt)?;
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| CipherError::Decrypt)?;
+        String::from_utf8(plaintext).map_err(|_| CipherError::Decrypt)
+    }
+
+    /// 由行 nonce 派生字段 nonce，长度不正确时返回 [`None`]
+    fn field_nonce(nonce: &[u8], field: Field) -> Option<[u8; Self::NONCE_LEN]> {
+        let mut bytes: [u8; Self::NONCE_LEN] = nonce.try_into().ok()?;
+        bytes[0] ^= field as u8;
+        Some(bytes)
+    }
+}
+
+/// 使用全局加解密器就地解密行内字段
+///
+/// `nonce` 为 [`None`] 表示该行以明文存储，字段保持不变。
+pub fn open_fields(
+    nonce: Option<&[u8]>,
+    fields: &mut [(Field, &mut String)],
+) -> Result<(), CipherError> {
+    let Some(nonce) = nonce else {
+        return Ok(());
+    };
+
+    let cipher = ContentCipher::installed()?;
+    for (field, value) in fields.iter_mut() {
+        let plaintext = cipher.open(nonce, *field, value)?;
+        **value = plaintext;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(byte: u8) -> ContentCipher {
+        ContentCipher::from_base64(&STANDARD.encode([byte; 32])).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_unicode() {
+        let cipher = cipher(7);
+        let nonce = ContentCipher::new_nonce();
+        let content = "<p>密码：hunter2 🔐 — naïve café</p>";
+
+        let sealed = cipher.seal(&nonce, Field::Content, content).unwrap();
+        assert_ne!(sealed, content);
+        assert_eq!(
+            cipher.open(&nonce, Field::Content, &sealed).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_ciphertext_differs_per_row_and_field() {
+        let cipher = cipher(7);
+        let content = "same plaintext";
+
+        let first = ContentCipher::new_nonce();
+        let second = ContentCipher::new_nonce();
+        assert_ne!(first, second);
+
+        let a = cipher.seal(&first, Field::Content, content).unwrap();
+        let b = cipher.seal(&second, Field::Content, content).unwrap();
+        let c = cipher.seal(&first, Field::Summary, content).unwrap();
+
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_key_fails() {
+        let nonce = ContentCipher::new_nonce();
+        let sealed = cipher(7).seal(&nonce, Field::Content, "secret").unwrap();
+
+        let err = cipher(8).open(&nonce, Field::Content, &sealed).unwrap_err();
+        assert!(matches!(err, CipherError::Decrypt));
+
+        let err = cipher(7)
+            .open(&nonce, Field::Content, "not base64!")
+            .unwrap_err();
+        assert!(matches!(err, CipherError::Decrypt));
+    }
+
+    #[test]
+    fn test_invalid_key() {
+        assert!(matches!(
+            ContentCipher::from_base64("c2hvcnQ="),
+            Err(CipherError::InvalidKey)
+        ));
+        assert!(matches!(
+            ContentCipher::from_base64("%%%"),
+            Err(CipherError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn test_open_plaintext_row_is_noop() {
+        let mut content = "plain".to_string();
+
+        open_fields(None, &mut [(Field::Content, &mut content)]).unwrap();
+        assert_eq!(content, "plain");
+    }
+}
@@ -0,0 +1,513 @@
+use std::io::{self, BufRead, Write};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{content::GroupPath, error};
+
+use super::{ContentBlob, Store};
+
+/// 导出文件第一行的格式标识
+pub const DUMP_FORMAT: &str = "gitnote-dump";
+
+/// 导出文件的格式版本，记录的字段变化时递增
+pub const DUMP_VERSION: u32 = 1;
+
+/// 导出时每次查询的行数
+pub const DUMP_PAGE_SIZE: usize = 500;
+
+/// 导出文件的第一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpHeader {
+    /// 固定为 [`DUMP_FORMAT`]
+    pub format: String,
+    /// 见 [`DUMP_VERSION`]
+    pub version: u32,
+    pub exported_at: DateTime<FixedOffset>,
+}
+
+/// 参与导出的表，按导入顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpTable {
+    Groups,
+    Articles,
+    ContentBlobs,
+}
+
+impl DumpTable {
+    pub const ALL: [Self; 3] = [Self::Groups, Self::Articles, Self::ContentBlobs];
+}
+
+/// 导出文件中除第一行外的每一行，对应表中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum DumpRecord {
+    Group(GroupDump),
+    Article(ArticleDump),
+    ContentBlob(ContentBlob),
+}
+
+impl DumpRecord {
+    /// 所在表的主键，导出时按主键分页
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Group(g) => g.id.as_str(),
+            Self::Article(a) => &a.slug,
+            Self::ContentBlob(b) => &b.oid,
+        }
+    }
+}
+
+/// `groups` 表中的一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GroupDump {
+    pub id: GroupPath,
+    pub slug: String,
+    pub name: String,
+    pub public: bool,
+    pub encrypted: bool,
+    pub slug_strategy: String,
+    pub author_id: Option<String>,
+    pub author_name: Option<String>,
+    pub archived: bool,
+    pub default_tags: Vec<String>,
+    pub kind: serde_json::Value,
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    /// 组列表中的排序，缺少时排在最后
+    #[serde(default)]
+    pub nav_order: Option<i32>,
+    /// 是否从组列表中隐藏，缺少时不隐藏
+    #[serde(default)]
+    pub hidden: bool,
+    /// 保留的文章数，缺少时不限制
+    #[serde(default)]
+    pub retain_max_articles: Option<i32>,
+    /// 保留的天数，缺少时不限制
+    #[serde(default)]
+    pub retain_max_age_days: Option<i32>,
+    /// 许可协议，缺少时使用部署配置的默认协议
+    #[serde(default)]
+    pub license: Option<serde_json::Value>,
+    /// 组内文章原文的总字节数上限，缺少时不限制
+    #[serde(default)]
+    pub quota_bytes: Option<i64>,
+    /// 组内文章 Front Matter 的附加约定，缺少时没有约定
+    #[serde(default)]
+    pub front_matter_schema: Option<serde_json::Value>,
+    pub description_html: Option<String>,
+}
+
+/// `articles` 表中的一行
+///
+/// 加密文章的正文和摘要保持密文，导入的实例需要使用相同的 `GITNOTE_CONTENT_KEY`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArticleDump {
+    pub slug: String,
+    pub group_id: GroupPath,
+    pub title: String,
+    pub summary: String,
+    pub summary_plain: String,
+    pub tags: Vec<String>,
+    pub own_tags: Vec<String>,
+    pub content: String,
+    pub content_oid: Option<String>,
+    pub content_namespaced: Option<String>,
+    /// base64 编码
+    #[serde(with = "base64_nonce")]
+    pub nonce: Option<Vec<u8>>,
+    pub comment_count: i64,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+    pub source_path: Option<String>,
+    pub source_commit: Option<String>,
+    pub renderer: Option<String>,
+    pub canonical_url: Option<String>,
+    /// 文章级的公开设置，缺少时与所在组相同
+    #[serde(default)]
+    pub public: Option<bool>,
+    /// 文章的短 id，缺少时在下次同步写入该文章时分配
+    #[serde(default)]
+    pub short_id: Option<String>,
+    /// 是否因所在组的保留规则下线，缺少时未下线
+    #[serde(default)]
+    pub retention_pruned: bool,
+    /// 原文的字节数，缺少时为 0，下次同步写入该文章时更新
+    #[serde(default)]
+    pub source_bytes: i64,
+    /// 分享卡片信息，缺少时在下次同步写入该文章时生成
+    #[serde(default)]
+    pub social: Option<serde_json::Value>,
+}
+
+/// 各表导出或导入的行数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DumpCounts {
+    pub groups: usize,
+    pub articles: usize,
+    pub content_blobs: usize,
+}
+
+impl DumpCounts {
+    pub(super) fn add(&mut self, record: &DumpRecord) {
+        match record {
+            DumpRecord::Group(_) => self.groups += 1,
+            DumpRecord::Article(_) => self.articles += 1,
+            DumpRecord::ContentBlob(_) => self.content_blobs += 1,
+        }
+    }
+}
+
+/// 读取导出文件的错误
+#[derive(Debug, thiserror::Error)]
+pub enum DumpError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// 第一行不是 [`DumpHeader`]
+    #[error("not a gitnote dump")]
+    NotADump,
+
+    /// 格式版本与 [`DUMP_VERSION`] 不同
+    #[error("unsupported dump version {0}, expected {DUMP_VERSION}")]
+    Version(u32),
+
+    /// 某一行无法解析，行号从 1 开始
+    #[error("invalid record at line {line}: {source}")]
+    Record {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+/// 按 [`DumpTable::ALL`] 的顺序分页读取所有行，逐行写入 `out`，第一行为 [`DumpHeader`]
+///
+/// 包括未公开组的文章和加密文章，每次只在内存中保留一页。
+pub async fn export_dump<S: Store>(
+    store: &S,
+    mut out: impl Write,
+) -> Result<DumpCounts, error::Error> {
+    let header = DumpHeader {
+        format: DUMP_FORMAT.to_string(),
+        version: DUMP_VERSION,
+        exported_at: Utc::now().fixed_offset(),
+    };
+    write_line(&mut out, &header)?;
+
+    let mut counts = DumpCounts::default();
+    for table in DumpTable::ALL {
+        let mut after = None;
+        loop {
+            let page = store
+                .dump_page(table, after.as_deref(), DUMP_PAGE_SIZE)
+                .await?;
+            for record in &page {
+                write_line(&mut out, record)?;
+                counts.add(record);
+            }
+            match page.last() {
+                Some(last) if page.len() == DUMP_PAGE_SIZE => after = Some(last.key().to_owned()),
+                _ => break,
+            }
+        }
+    }
+    out.flush()?;
+
+    Ok(counts)
+}
+
+/// 校验导出文件的第一行后逐行导入，见 [`Store::load_dump`]
+///
+/// `truncate` 为 `true` 时先清空组、文章和渲染结果。任一行无法解析时整体回滚，不写入任何数据。
+pub async fn import_dump<S: Store>(
+    store: &S,
+    input: impl BufRead + Send,
+    truncate: bool,
+) -> Result<DumpCounts, error::Error> {
+    let records = read_dump(input)?;
+    store.load_dump(records, truncate).await
+}
+
+/// 校验第一行的 [`DumpHeader`]，返回之后各行的迭代器，空行被忽略
+pub fn read_dump(
+    mut input: impl BufRead,
+) -> Result<impl Iterator<Item = Result<DumpRecord, DumpError>>, DumpError> {
+    let mut first = String::new();
+    input.read_line(&mut first)?;
+    let header: DumpHeader = serde_json::from_str(&first).map_err(|_| DumpError::NotADump)?;
+    if header.format != DUMP_FORMAT {
+        return Err(DumpError::NotADump);
+    }
+    if header.version != DUMP_VERSION {
+        return Err(DumpError::Version(header.version));
+    }
+
+    let records = input
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str(&line).map_err(|source| DumpError::Record {
+                    line: i + 2,
+                    source,
+                }),
+            ),
+            Err(e) => Some(Err(e.into())),
+        });
+    Ok(records)
+}
+
+fn write_line(out: &mut impl Write, value: &impl Serialize) -> Result<(), error::Error> {
+    serde_json::to_writer(&mut *out, value).map_err(io::Error::from)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// 以 base64 字符串读写可选的 nonce
+mod base64_nonce {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(nonce: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match nonce {
+            Some(bytes) => s.serialize_some(&STANDARD.encode(bytes)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|s| STANDARD.decode(s).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Read};
+
+    use chrono::TimeZone;
+    use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+    use crate::{
+        content::{Article, FrontMatter, Group},
+        storage::{MemoryStorage, Querier},
+    };
+
+    use super::*;
+
+    fn article(group: &str, slug: &str, tags: &[&str]) -> Article {
+        let datetime = Utc
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        Article {
+            group: group.into(),
+            slug: slug.to_string(),
+            frontmatter: FrontMatter {
+                title: slug.to_string(),
+                summary: format!("{slug} summary"),
+                datetime,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                aliases: vec![],
+                canonical: None,
+                public: None,
+                raw_html: false,
+                cover: None,
+            },
+            rendered_content: format!("<p>{slug}</p>"),
+            namespaced_content: None,
+            rendered_by: None,
+            updated_at: datetime,
+            source_bytes: 0,
+        }
+    }
+
+    fn group(id: &str, config: &str) -> Group {
+        Group::new(format!("{id}/.group.yaml"), config.to_string()).unwrap()
+    }
+
+    /// 公开组、未公开组、正文存储在渲染结果中的文章和同步后写入的各列
+    async fn seeded() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let mut store = storage.store();
+        store
+            .upsert_group(&group(
+                "notes",
+                "name: Notes\npublic: true\ndefault_tags: [note]\n",
+            ))
+            .upsert_group(&group("drafts", "name: Drafts\npublic: false\n"))
+            .set_group_description(&"notes".into(), Some("<p>about</p>"))
+            .upsert_article(&article("notes", "a", &["rust"]))
+            .upsert_article(&article("drafts", "secret", &["wip"]))
+            .upsert_blob_article(&article("notes", "b", &[]), "oid-b")
+            .upsert_content_blob(&ContentBlob {
+                oid: "oid-b".to_string(),
+                renderer: Some("r1".to_string()),
+                raw: "# b".to_string(),
+                content: "<h1>b</h1>".to_string(),
+                summary: "b".to_string(),
+                simhash: Some(7),
+            })
+            .set_article_source("a", "notes/a.md", "c1")
+            .set_article_renderer("a", "r1")
+            .update_comment_counts(&HashMap::from([("a".to_string(), 3)]));
+        store.commit().await.unwrap();
+        storage
+    }
+
+    async fn export(storage: &MemoryStorage) -> Vec<u8> {
+        let mut out = vec![];
+        export_dump(&storage.store(), &mut out).await.unwrap();
+        out
+    }
+
+    /// 导出的记录，不包含第一行
+    async fn records(storage: &MemoryStorage) -> Vec<DumpRecord> {
+        let data = export(storage).await;
+        read_dump(data.as_slice())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let source = seeded().await;
+        let data = export(&source).await;
+
+        let header: DumpHeader =
+            serde_json::from_str(std::str::from_utf8(&data).unwrap().lines().next().unwrap())
+                .unwrap();
+        assert_eq!(header.format, DUMP_FORMAT);
+        assert_eq!(header.version, DUMP_VERSION);
+
+        // 经过 gzip 压缩和解压
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&data).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut decompressed = vec![];
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+
+        let target = MemoryStorage::new();
+        let counts = import_dump(&target.store(), decompressed.as_slice(), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            counts,
+            DumpCounts {
+                groups: 2,
+                articles: 3,
+                content_blobs: 1,
+            }
+        );
+        assert_eq!(records(&target).await, records(&source).await);
+
+        // 导入后的数据可以直接查询，标签索引已重新计算
+        let detail = target.get_one("b").await.unwrap().unwrap();
+        assert_eq!(detail.content, "<h1>b</h1>");
+        assert_eq!(target.get_one("a").await.unwrap().unwrap().comment_count, 3);
+        assert!(target.get_one("secret").await.unwrap().is_none());
+        let tags = target.tags_with_counts().await.unwrap();
+        assert_eq!(
+            tags.iter().map(|t| t.tag.as_str()).collect::<Vec<_>>(),
+            ["note", "rust"]
+        );
+
+        // 重复导入结果不变
+        import_dump(&target.store(), data.as_slice(), false)
+            .await
+            .unwrap();
+        assert_eq!(records(&target).await, records(&source).await);
+    }
+
+    #[tokio::test]
+    async fn test_import_truncate() {
+        let source = MemoryStorage::new();
+        let mut store = source.store();
+        store
+            .upsert_group(&group("notes", "name: Notes\npublic: true\n"))
+            .upsert_article(&article("notes", "only", &[]));
+        store.commit().await.unwrap();
+        let data = export(&source).await;
+
+        // 不清空时保留导出文件中没有的行
+        let target = seeded().await;
+        import_dump(&target.store(), data.as_slice(), false)
+            .await
+            .unwrap();
+        assert_eq!(records(&target).await.len(), 7);
+
+        import_dump(&target.store(), data.as_slice(), true)
+            .await
+            .unwrap();
+        assert_eq!(records(&target).await, records(&source).await);
+    }
+
+    #[tokio::test]
+    async fn test_export_pages() {
+        let storage = MemoryStorage::new();
+        let mut store = storage.store();
+        store.upsert_group(&group("notes", "name: Notes\npublic: true\n"));
+        for i in 0..DUMP_PAGE_SIZE + 1 {
+            store.upsert_article(&article("notes", &format!("a{i:04}"), &[]));
+        }
+        store.commit().await.unwrap();
+
+        let records = records(&storage).await;
+        assert_eq!(records.len(), DUMP_PAGE_SIZE + 2);
+        let slugs = records
+            .iter()
+            .filter(|r| matches!(r, DumpRecord::Article(_)))
+            .map(DumpRecord::key)
+            .collect::<Vec<_>>();
+        assert!(slugs.is_sorted());
+        assert_eq!(slugs.len(), DUMP_PAGE_SIZE + 1);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_dump() {
+        let data = String::from_utf8(export(&seeded().await).await).unwrap();
+        let target = MemoryStorage::new();
+        let mut store = target.store();
+        store
+            .upsert_group(&group("kept", "name: Kept\npublic: true\n"))
+            .upsert_article(&article("kept", "k", &[]));
+        store.commit().await.unwrap();
+        let before = records(&target).await;
+
+        // 第三行被截断，此前已解析的行同样不写入
+        let mut lines = data.lines().map(str::to_string).collect::<Vec<_>>();
+        let half = lines[2].len() / 2;
+        lines[2].truncate(half);
+        let corrupted = lines.join("\n");
+        let err = import_dump(&target.store(), corrupted.as_bytes(), true)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, error::Error::Dump(DumpError::Record { line: 3, .. })),
+            "{err}"
+        );
+        assert_eq!(records(&target).await, before);
+
+        // 格式标识或版本不符时不读取任何记录
+        let err = import_dump(&target.store(), &b"{\"hello\": 1}\n"[..], true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, error::Error::Dump(DumpError::NotADump)));
+
+        let newer = data.replacen(
+            &format!("\"version\":{DUMP_VERSION}"),
+            &format!("\"version\":{}", DUMP_VERSION + 1),
+            1,
+        );
+        let err = import_dump(&target.store(), newer.as_bytes(), true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, error::Error::Dump(DumpError::Version(v)) if v == DUMP_VERSION + 1));
+        assert_eq!(records(&target).await, before);
+    }
+}
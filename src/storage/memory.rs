@@ -0,0 +1,2851 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{
+        Arc, PoisonError, RwLock, RwLockReadGuard,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::types::Json;
+
+use crate::{
+    content::{
+        self, Article, ArticleRef, FrontMatterSchema, GroupAuthor, GroupCategory, GroupLicense,
+        GroupPath, GroupSettings, RAW_HTML_RENDERER, SlugStrategy, SocialMeta, merge_tags,
+        plain_text, tag_matches, title_sort_key,
+    },
+    error,
+    git_sync::QuotaItem,
+    storage::cipher::{ContentCipher, Field, open_fields},
+};
+
+use super::{
+    ArticleDetail, ArticleDump, ArticleFingerprint, ArticleShortId, ArticleSimhash, ArticleSlug,
+    ArticleSummary, Attachment, AuthorSummary, BrokenLink, CategoryGroup, ContentBlob, DateRange,
+    DigestRun, DumpCounts, DumpError, DumpRecord, DumpTable, Group, GroupDump, GroupFingerprint,
+    GroupStats, ListColumns, ListSort, Neighbor, OrphanArticle, PrunedArticle, Querier, Redirect,
+    RenderSort, RenderStat, RetentionChange, Savepoint, SiteCounts, StaleRender, Store,
+    SyncFailure, SyncState, TagCount, TitleEntry, WebmentionSource, store::Touched,
+};
+
+/// 内存中的各张表，结构与 `sql/01-CREATE_TABLE.sql` 对应
+#[derive(Debug, Clone, Default)]
+struct Tables {
+    groups: BTreeMap<GroupPath, GroupRow>,
+    articles: BTreeMap<String, ArticleRow>,
+    /// `(from_group, from_slug)` 到 `(to_group, to_slug)`
+    redirects: BTreeMap<(String, String), (String, String)>,
+    /// `(article_path, include_path)`
+    includes: BTreeSet<(String, String)>,
+    /// `(slug, url)` 到检查结果
+    links: BTreeMap<(String, String), LinkRow>,
+    /// slug 到文章所在组和按出现顺序排列的附件
+    attachments: BTreeMap<String, (String, Vec<Attachment>)>,
+    /// 文件路径到失败记录
+    sync_failures: BTreeMap<String, FailureRow>,
+    /// 内容 oid 到渲染结果
+    blobs: BTreeMap<String, ContentBlob>,
+    /// 最近一次同步后的内容指纹
+    sync_state: Option<SyncState>,
+    /// `(slug, target)` 到 webmention 发送结果
+    webmentions: BTreeMap<(String, String), WebmentionRow>,
+    /// 标签到标签索引中的行
+    tag_index: BTreeMap<String, TagCount>,
+    /// 组目录路径到组的大小统计
+    group_stats: BTreeMap<GroupPath, StatsRow>,
+    /// 周期结束时间到每周摘要的执行记录
+    digest_runs: BTreeMap<DateTime<FixedOffset>, DigestRun>,
+}
+
+#[derive(Debug, Clone)]
+struct GroupRow {
+    slug: String,
+    name: String,
+    public: bool,
+    encrypted: bool,
+    slug_strategy: SlugStrategy,
+    author: Option<GroupAuthor>,
+    archived: bool,
+    kind: serde_json::Value,
+    default_tags: Vec<String>,
+    category: Option<GroupCategory>,
+    nav_order: Option<i32>,
+    hidden: bool,
+    retain_max_articles: Option<i32>,
+    retain_max_age_days: Option<i32>,
+    license: Option<GroupLicense>,
+    quota_bytes: Option<i64>,
+    front_matter_schema: Option<FrontMatterSchema>,
+    description_html: Option<String>,
+}
+
+impl GroupRow {
+    fn to_model(&self, id: &str) -> Group {
+        Group {
+            id: id.to_owned(),
+            slug: self.slug.to_owned(),
+            name: self.name.to_owned(),
+            public: self.public,
+            kind: Json(self.kind.clone()),
+            encrypted: self.encrypted,
+            author_id: self.author.as_ref().map(|a| a.id.to_owned()),
+            author_name: self.author.as_ref().map(|a| a.name.to_owned()),
+            archived: self.archived,
+            description_html: self.description_html.clone(),
+            category_id: self.category.as_ref().map(|c| c.id.to_owned()),
+            category_name: self.category.as_ref().map(|c| c.name.to_owned()),
+            nav_order: self.nav_order,
+            hidden: self.hidden,
+            license: self.license.clone().map(Json),
+        }
+    }
+
+    /// 随文章返回的组信息，不包含组说明
+    fn to_embedded(&self, id: &str) -> Group {
+        Group {
+            description_html: None,
+            ..self.to_model(id)
+        }
+    }
+
+    fn to_dump(&self, id: &GroupPath) -> GroupDump {
+        GroupDump {
+            id: id.to_owned(),
+            slug: self.slug.to_owned(),
+            name: self.name.to_owned(),
+            public: self.public,
+            encrypted: self.encrypted,
+            slug_strategy: self.slug_strategy.as_str().to_owned(),
+            author_id: self.author.as_ref().map(|a| a.id.to_owned()),
+            author_name: self.author.as_ref().map(|a| a.name.to_owned()),
+            archived: self.archived,
+            default_tags: self.default_tags.to_owned(),
+            kind: self.kind.clone(),
+            category_id: self.category.as_ref().map(|c| c.id.to_owned()),
+            category_name: self.category.as_ref().map(|c| c.name.to_owned()),
+            nav_order: self.nav_order,
+            hidden: self.hidden,
+            retain_max_articles: self.retain_max_articles,
+            retain_max_age_days: self.retain_max_age_days,
+            license: self
+                .license
+                .as_ref()
+                .and_then(|l| serde_json::to_value(l).ok()),
+            quota_bytes: self.quota_bytes,
+            front_matter_schema: self
+                .front_matter_schema
+                .as_ref()
+                .and_then(|s| serde_json::to_value(s).ok()),
+            description_html: self.description_html.clone(),
+        }
+    }
+
+    fn from_dump(g: GroupDump) -> (GroupPath, Self) {
+        let row = Self {
+            slug: g.slug,
+            name: g.name,
+            public: g.public,
+            encrypted: g.encrypted,
+            slug_strategy: SlugStrategy::from(g.slug_strategy.as_str()),
+            author: g.author_name.map(|name| GroupAuthor {
+                id: g.author_id.unwrap_or_default(),
+                name,
+            }),
+            archived: g.archived,
+            kind: g.kind,
+            default_tags: g.default_tags,
+            category: g.category_id.map(|id| GroupCategory {
+                id,
+                name: g.category_name.unwrap_or_default(),
+            }),
+            nav_order: g.nav_order,
+            hidden: g.hidden,
+            retain_max_articles: g.retain_max_articles,
+            retain_max_age_days: g.retain_max_age_days,
+            license: g.license.and_then(|l| serde_json::from_value(l).ok()),
+            quota_bytes: g.quota_bytes,
+            front_matter_schema: g
+                .front_matter_schema
+                .and_then(|s| serde_json::from_value(s).ok()),
+            description_html: g.description_html,
+        };
+        (g.id, row)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ArticleRow {
+    group_id: GroupPath,
+    title: String,
+    summary: String,
+    summary_plain: String,
+    /// 合并组默认标签后的标签
+    tags: Vec<String>,
+    /// front matter 中的标签
+    own_tags: Vec<String>,
+    content: String,
+    content_oid: Option<String>,
+    /// 只在导出时读取
+    content_namespaced: Option<String>,
+    nonce: Option<Vec<u8>>,
+    comment_count: i64,
+    created_at: DateTime<FixedOffset>,
+    updated_at: DateTime<FixedOffset>,
+    source_path: Option<String>,
+    source_commit: Option<String>,
+    renderer: Option<String>,
+    canonical_url: Option<String>,
+    /// 文章级的公开设置，为 `None` 时与所在组相同
+    public: Option<bool>,
+    short_id: Option<String>,
+    /// 是否因所在组的保留规则下线
+    retention_pruned: bool,
+    /// 原文的字节数
+    source_bytes: i64,
+    social: Option<SocialMeta>,
+    /// 标题的排序键，不导出
+    title_sort_key: String,
+    /// 最近一次渲染的耗时、正文字节数和时间，不导出
+    render_ms: Option<i64>,
+    content_bytes: Option<i64>,
+    rendered_at: Option<DateTime<FixedOffset>>,
+}
+
+impl ArticleRow {
+    fn to_dump(&self, slug: &str) -> ArticleDump {
+        ArticleDump {
+            slug: slug.to_owned(),
+            group_id: self.group_id.to_owned(),
+            title: self.title.to_owned(),
+            summary: self.summary.to_owned(),
+            summary_plain: self.summary_plain.to_owned(),
+            tags: self.tags.to_owned(),
+            own_tags: self.own_tags.to_owned(),
+            content: self.content.to_owned(),
+            content_oid: self.content_oid.to_owned(),
+            content_namespaced: self.content_namespaced.to_owned(),
+            nonce: self.nonce.to_owned(),
+            comment_count: self.comment_count,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            source_path: self.source_path.to_owned(),
+            source_commit: self.source_commit.to_owned(),
+            renderer: self.renderer.to_owned(),
+            canonical_url: self.canonical_url.to_owned(),
+            public: self.public,
+            short_id: self.short_id.to_owned(),
+            retention_pruned: self.retention_pruned,
+            source_bytes: self.source_bytes,
+            social: self
+                .social
+                .as_ref()
+                .and_then(|social| serde_json::to_value(social).ok()),
+        }
+    }
+
+    fn from_dump(a: ArticleDump) -> (String, Self) {
+        let sort_key = title_sort_key(&a.title);
+        let row = Self {
+            group_id: a.group_id,
+            title: a.title,
+            summary: a.summary,
+            summary_plain: a.summary_plain,
+            tags: a.tags,
+            own_tags: a.own_tags,
+            content: a.content,
+            content_oid: a.content_oid,
+            content_namespaced: a.content_namespaced,
+            nonce: a.nonce,
+            comment_count: a.comment_count,
+            created_at: a.created_at,
+            updated_at: a.updated_at,
+            source_path: a.source_path,
+            source_commit: a.source_commit,
+            renderer: a.renderer,
+            canonical_url: a.canonical_url,
+            public: a.public,
+            short_id: a.short_id,
+            retention_pruned: a.retention_pruned,
+            source_bytes: a.source_bytes,
+            social: a
+                .social
+                .and_then(|social| serde_json::from_value(social).ok()),
+            title_sort_key: sort_key,
+            render_ms: None,
+            content_bytes: None,
+            rendered_at: None,
+        };
+        (a.slug, row)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatsRow {
+    content_bytes: i64,
+    asset_bytes: i64,
+    article_count: i64,
+    updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct LinkRow {
+    status: Option<i32>,
+    error: Option<String>,
+    last_checked: Option<DateTime<FixedOffset>>,
+}
+
+// 仅镜像数据库中的列，内存后端没有读取方
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+struct WebmentionRow {
+    endpoint: Option<String>,
+    status: Option<i32>,
+    error: Option<String>,
+    sent_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Clone)]
+struct FailureRow {
+    reason: String,
+    commit_id: String,
+    failed_at: DateTime<FixedOffset>,
+}
+
+impl Tables {
+    /// 文章公开时返回所在组，因保留规则下线的文章不公开，见 [`Tables::published_group`]
+    fn public_group(&self, article: &ArticleRow) -> Option<&GroupRow> {
+        self.published_group(article)
+            .filter(|_| !article.retention_pruned)
+    }
+
+    /// 按公开设置判断，不考虑保留规则：文章的 `public` 为 `None` 时按所在组判断，没有对应组的文章不公开
+    fn published_group(&self, article: &ArticleRow) -> Option<&GroupRow> {
+        self.groups
+            .get(&article.group_id)
+            .filter(|g| article.public.unwrap_or(g.public))
+    }
+
+    /// 与 [`SqlxStore`](super::SqlxStore) 相同，按组的保留规则更新 `retention_pruned`，返回状态发生变化的文章
+    fn apply_retention(&mut self, groups: Option<&[GroupPath]>) -> Vec<RetentionChange> {
+        let now = Utc::now();
+        let mut ranked = self
+            .articles
+            .iter()
+            .filter(|(_, a)| groups.is_none_or(|groups| groups.contains(&a.group_id)))
+            .filter_map(|(slug, a)| self.published_group(a).map(|g| (slug, a, g)))
+            .collect::<Vec<_>>();
+        // 组内与 `ORDER BY a.created_at DESC, a.slug DESC` 相同
+        ranked.sort_by(|(sa, a, _), (sb, b, _)| {
+            (&b.group_id, b.created_at, sb).cmp(&(&a.group_id, a.created_at, sa))
+        });
+
+        let mut pruned = BTreeSet::new();
+        let mut seen = HashMap::<&GroupPath, i64>::new();
+        for (slug, a, g) in ranked {
+            let n = seen.entry(&a.group_id).or_default();
+            *n += 1;
+            let over_count = g.retain_max_articles.is_some_and(|max| *n > i64::from(max));
+            let too_old = g
+                .retain_max_age_days
+                .is_some_and(|days| a.created_at < now - chrono::Duration::days(days.into()));
+            if over_count || too_old {
+                pruned.insert(slug.to_owned());
+            }
+        }
+
+        let mut changes = vec![];
+        for (slug, a) in self.articles.iter_mut() {
+            if groups.is_some_and(|groups| !groups.contains(&a.group_id)) {
+                continue;
+            }
+            let next = pruned.contains(slug);
+            if a.retention_pruned != next {
+                a.retention_pruned = next;
+                changes.push(RetentionChange {
+                    group_id: a.group_id.to_owned(),
+                    slug: slug.to_owned(),
+                    pruned: next,
+                });
+            }
+        }
+        changes
+    }
+
+    /// 按文章自身的标签和所在组的默认标签重新计算 `tags`，`slug` 为 `None` 时更新组内全部文章
+    fn retag(&mut self, group_id: &GroupPath, slug: Option<&str>) {
+        let defaults = self
+            .groups
+            .get(group_id)
+            .map(|g| g.default_tags.as_slice())
+            .unwrap_or_default();
+        for (s, article) in self.articles.iter_mut() {
+            if article.group_id == *group_id && slug.is_none_or(|slug| slug == s) {
+                article.tags = merge_tags(&article.own_tags, defaults);
+            }
+        }
+    }
+
+    /// 公开的文章，见 [`Tables::public_group`]
+    fn public_articles(&self) -> impl Iterator<Item = (&String, &ArticleRow, &GroupRow)> {
+        self.articles
+            .iter()
+            .filter_map(|(slug, a)| self.public_group(a).map(|g| (slug, a, g)))
+    }
+
+    /// `touched` 中的文章和组内文章当前的标签
+    fn touched_tags(&self, touched: &Touched) -> BTreeSet<String> {
+        self.articles
+            .iter()
+            .filter(|(slug, a)| {
+                touched.slugs.contains(*slug) || touched.groups.contains(&a.group_id)
+            })
+            .flat_map(|(_, a)| a.tags.iter().cloned())
+            .collect()
+    }
+
+    /// 与 [`SqlxStore`](super::SqlxStore) 相同，按公开文章重新计算 `tags` 在标签索引中的行，
+    /// `tags` 为 [`None`] 时重新计算全部标签
+    fn refresh_tag_index(&mut self, tags: Option<&BTreeSet<String>>) {
+        let wanted = |tag: &String| tags.is_none_or(|tags| tags.contains(tag));
+
+        let mut index = BTreeMap::<String, TagCount>::new();
+        for (_, article, _) in self.public_articles() {
+            for tag in article.tags.iter().collect::<BTreeSet<_>>() {
+                if !wanted(tag) {
+                    continue;
+                }
+                let row = index.entry(tag.to_owned()).or_insert(TagCount {
+                    tag: tag.to_owned(),
+                    article_count: 0,
+                    last_used: article.updated_at,
+                });
+                row.article_count += 1;
+                row.last_used = row.last_used.max(article.updated_at);
+            }
+        }
+
+        self.tag_index.retain(|tag, _| !wanted(tag));
+        self.tag_index.extend(index);
+    }
+
+    /// 与 [`SqlxStore`](super::SqlxStore) 相同，按组内全部文章重新计算组的大小统计，
+    /// 数值没有变化的行保留原有的 `updated_at`
+    fn refresh_group_stats(&mut self) {
+        let now = Utc::now().fixed_offset();
+        let mut stats = self
+            .groups
+            .keys()
+            .map(|id| (id.to_owned(), (0, 0)))
+            .collect::<BTreeMap<_, _>>();
+        for article in self.articles.values() {
+            if let Some((bytes, count)) = stats.get_mut(&article.group_id) {
+                *bytes += article.source_bytes;
+                *count += 1;
+            }
+        }
+
+        let old = std::mem::take(&mut self.group_stats);
+        for (id, (content_bytes, article_count)) in stats {
+            let row = match old.get(&id) {
+                Some(row)
+                    if (row.content_bytes, row.asset_bytes, row.article_count)
+                        == (content_bytes, 0, article_count) =>
+                {
+                    row.clone()
+                }
+                _ => StatsRow {
+                    content_bytes,
+                    asset_bytes: 0,
+                    article_count,
+                    updated_at: now,
+                },
+            };
+            self.group_stats.insert(id, row);
+        }
+    }
+
+    /// 第一个超出配额的组
+    fn over_quota(&self) -> Option<QuotaItem> {
+        self.group_stats.iter().find_map(|(id, s)| {
+            let quota = self.groups.get(id)?.quota_bytes?;
+            let used = s.content_bytes + s.asset_bytes;
+            (used > quota).then(|| QuotaItem::new(id.as_str(), used, quota))
+        })
+    }
+}
+
+/// `(slug, target)` 形式的键中属于 `slug` 的目标，按目标排序
+fn targets_of<'a>(keys: impl Iterator<Item = &'a (String, String)>, slug: &str) -> Vec<String> {
+    keys.filter(|(s, _)| s == slug)
+        .map(|(_, target)| target.to_owned())
+        .collect()
+}
+
+/// 内存中的存储后端，不依赖数据库，用于测试
+///
+/// 克隆后共享同一份数据：通过 [`MemoryStorage::store`] 获取 [`Store`] 写入，
+/// 自身实现 [`Querier`] 用于查询，行为与 Postgres 实现保持一致。
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    tables: Arc<RwLock<Tables>>,
+    /// 查询读取数据的次数，克隆后共享
+    reads: Arc<AtomicUsize>,
+    /// 剩余的注入提交失败次数，见 [`MemoryStorage::fail_commits`]
+    faults: Arc<AtomicUsize>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取写入该存储的 [`MemoryStore`]
+    pub fn store(&self) -> MemoryStore {
+        MemoryStore {
+            tables: self.tables.clone(),
+            faults: self.faults.clone(),
+            ops: Vec::new(),
+            rebuild: false,
+            touched: Touched::default(),
+            enforce_quotas: false,
+        }
+    }
+
+    /// 让接下来的 `n` 次提交返回连接中断错误，不写入任何数据，用于测试数据库故障切换时的重试
+    pub fn fail_commits(&self, n: usize) {
+        self.faults.store(n, Ordering::Relaxed);
+    }
+
+    /// 查询读取数据的次数，用于检查缓存是否生效
+    pub fn reads(&self) -> usize {
+        self.reads.load(Ordering::Relaxed)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.tables.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 排队等待提交的写入操作
+type Op = Box<dyn FnOnce(&mut Tables) + Send + Sync>;
+
+/// [`MemoryStorage`] 的 [`Store`] 实现
+///
+/// 写入操作在提交时整体生效；调用 [`Store::clean`] 后提交时替换除外部链接和渲染结果以外的全部数据。
+pub struct MemoryStore {
+    tables: Arc<RwLock<Tables>>,
+    faults: Arc<AtomicUsize>,
+    ops: Vec<Op>,
+    rebuild: bool,
+    touched: Touched,
+    enforce_quotas: bool,
+}
+
+impl MemoryStore {
+    fn push(&mut self, op: impl FnOnce(&mut Tables) + Send + Sync + 'static) -> &mut Self {
+        self.ops.push(Box::new(op));
+        self
+    }
+
+    /// 写入文章，`content`、`summary` 和 `summary_plain` 为明文或密文，`nonce` 为 [`None`] 表示明文存储，
+    /// `content_oid` 不为 [`None`] 时正文存储在对应的 [`ContentBlob`] 中
+    fn push_article(
+        &mut self,
+        article: &Article,
+        content: String,
+        summary: String,
+        summary_plain: String,
+        nonce: Option<Vec<u8>>,
+        content_oid: Option<String>,
+    ) -> &mut Self {
+        let slug = article.slug.to_owned();
+        self.touched.slugs.insert(slug.clone());
+        // 加密文章的描述和图片来自明文，不保存
+        let social = nonce.is_none().then(|| SocialMeta::from_article(article));
+        let row = ArticleRow {
+            group_id: article.group.to_owned(),
+            title: article.frontmatter.title.to_owned(),
+            summary,
+            summary_plain,
+            tags: article.frontmatter.tags.to_owned(),
+            own_tags: article.frontmatter.tags.to_owned(),
+            content,
+            content_oid,
+            content_namespaced: article.namespaced_content.to_owned(),
+            nonce,
+            comment_count: 0,
+            created_at: article.frontmatter.datetime,
+            updated_at: article.updated_at,
+            source_path: None,
+            source_commit: None,
+            renderer: None,
+            canonical_url: article.frontmatter.canonical.to_owned(),
+            public: article.frontmatter.public,
+            short_id: None,
+            retention_pruned: false,
+            source_bytes: article.source_bytes as i64,
+            social,
+            title_sort_key: title_sort_key(&article.frontmatter.title),
+            render_ms: None,
+            content_bytes: None,
+            rendered_at: None,
+        };
+
+        self.push(move |t| {
+            // 文章所在位置已有实体，不再作为重定向来源
+            t.redirects
+                .remove(&(row.group_id.to_string(), slug.clone()));
+
+            let row = match t.articles.get(&slug) {
+                Some(old) => ArticleRow {
+                    comment_count: old.comment_count,
+                    created_at: old.created_at,
+                    short_id: old.short_id.clone(),
+                    retention_pruned: old.retention_pruned,
+                    source_path: old.source_path.clone(),
+                    source_commit: old.source_commit.clone(),
+                    renderer: old.renderer.clone(),
+                    render_ms: old.render_ms,
+                    content_bytes: old.content_bytes,
+                    rendered_at: old.rendered_at,
+                    // 公开设置变化时更新时间取当前时间，使缓存失效
+                    updated_at: if old.public != row.public {
+                        Utc::now().fixed_offset()
+                    } else {
+                        row.updated_at
+                    },
+                    ..row
+                },
+                None => row,
+            };
+            let group_id = row.group_id.clone();
+            t.articles.insert(slug.clone(), row);
+            t.retag(&group_id, Some(&slug));
+        })
+    }
+}
+
+impl ToOwned for MemoryStore {
+    type Owned = MemoryStore;
+
+    fn to_owned(&self) -> Self::Owned {
+        Self {
+            tables: self.tables.clone(),
+            faults: self.faults.clone(),
+            ops: Vec::new(),
+            rebuild: false,
+            touched: Touched::default(),
+            enforce_quotas: false,
+        }
+    }
+}
+
+impl Store for MemoryStore {
+    fn clean(&mut self) -> &mut Self {
+        self.ops.clear();
+        self.touched = Touched::default();
+        self.rebuild = true;
+        self
+    }
+
+    fn upsert_group(&mut self, group: &content::Group) -> &mut Self {
+        let id = group.id.to_owned();
+        let row = GroupRow {
+            slug: group.public_id().to_owned(),
+            name: group.name.to_owned(),
+            public: group.public,
+            encrypted: group.encrypted,
+            slug_strategy: group.slug_strategy,
+            author: group.author.clone(),
+            archived: group.archived,
+            kind: serde_json::to_value(&group.kind).unwrap_or_default(),
+            default_tags: group.default_tags.to_owned(),
+            category: group.category.clone(),
+            nav_order: group.nav_order,
+            hidden: group.hidden,
+            retain_max_articles: group.retain.and_then(|r| r.max_articles).map(|n| n as i32),
+            retain_max_age_days: group.retain.and_then(|r| r.max_age_days).map(|n| n as i32),
+            license: group.license.clone(),
+            quota_bytes: group.quota_bytes.map(|q| q as i64),
+            front_matter_schema: group.front_matter_schema(),
+            description_html: None,
+        };
+
+        self.touched.groups.insert(id.clone());
+        self.push(move |t| {
+            let mut row = row;
+            row.description_html = t.groups.get(&id).and_then(|g| g.description_html.clone());
+            t.groups.insert(id.clone(), row);
+            t.retag(&id, None);
+        })
+    }
+
+    fn remove_group(&mut self, group: &content::Group) -> &mut Self {
+        let id = group.id.to_owned();
+        self.touched.groups.insert(id.clone());
+        self.push(move |t| {
+            t.groups.remove(&id);
+            t.retag(&id, None);
+        })
+    }
+
+    fn rename_group(&mut self, from: &GroupPath, to: &GroupPath) -> &mut Self {
+        let (from, to) = (from.to_owned(), to.to_owned());
+        self.touched.groups.extend([from.clone(), to.clone()]);
+        self.push(move |t| {
+            if let Some(row) = t.groups.remove(&from) {
+                t.groups.entry(to).or_insert(row);
+            }
+        })
+    }
+
+    fn set_group_description(&mut self, group_id: &GroupPath, html: Option<&str>) -> &mut Self {
+        let (id, html) = (group_id.to_owned(), html.map(str::to_owned));
+        self.push(move |t| {
+            if let Some(group) = t.groups.get_mut(&id) {
+                group.description_html = html;
+            }
+        })
+    }
+
+    async fn prune_groups(&self, keep: &[GroupPath]) -> Result<Vec<GroupPath>, error::Error> {
+        let mut t = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+
+        let removed = t
+            .groups
+            .keys()
+            .filter(|id| !keep.contains(id))
+            .filter(|id| t.articles.values().all(|a| &a.group_id != *id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in &removed {
+            t.groups.remove(id);
+        }
+
+        Ok(removed)
+    }
+
+    async fn apply_retention(
+        &self,
+        groups: Option<&[GroupPath]>,
+    ) -> Result<Vec<RetentionChange>, error::Error> {
+        let mut t = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+
+        let changes = t.apply_retention(groups);
+        let touched = Touched {
+            slugs: changes.iter().map(|c| c.slug.to_owned()).collect(),
+            groups: BTreeSet::new(),
+        };
+        let tags = t.touched_tags(&touched);
+        if !tags.is_empty() {
+            t.refresh_tag_index(Some(&tags));
+        }
+
+        Ok(changes)
+    }
+
+    fn upsert_article(&mut self, article: &Article) -> &mut Self {
+        self.push_article(
+            article,
+            article.rendered_content.to_owned(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            None,
+        )
+    }
+
+    fn upsert_encrypted_article(&mut self, article: &Article) -> Result<&mut Self, error::Error> {
+        let cipher = ContentCipher::installed()?;
+        let nonce = ContentCipher::new_nonce();
+        let content = cipher.seal(&nonce, Field::Content, &article.rendered_content)?;
+        let summary = cipher.seal(&nonce, Field::Summary, &article.frontmatter.summary)?;
+        let summary_plain = cipher.seal(
+            &nonce,
+            Field::SummaryPlain,
+            &plain_text(&article.frontmatter.summary),
+        )?;
+
+        Ok(self.push_article(article, content, summary, summary_plain, Some(nonce), None))
+    }
+
+    fn upsert_blob_article(&mut self, article: &Article, oid: &str) -> &mut Self {
+        self.push_article(
+            article,
+            String::new(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            Some(oid.to_owned()),
+        )
+    }
+
+    async fn content_blob(
+        &self,
+        oid: &str,
+        renderer: &str,
+    ) -> Result<Option<ContentBlob>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.blobs
+            .get(oid)
+            .filter(|b| b.renderer.as_deref() == Some(renderer))
+            .cloned())
+    }
+
+    fn upsert_content_blob(&mut self, blob: &ContentBlob) -> &mut Self {
+        let blob = blob.clone();
+        self.push(move |t| {
+            t.blobs.insert(blob.oid.clone(), blob);
+        })
+    }
+
+    fn prune_content_blobs(&mut self) -> &mut Self {
+        self.push(|t| {
+            let articles = &t.articles;
+            t.blobs.retain(|oid, _| {
+                articles
+                    .values()
+                    .any(|a| a.content_oid.as_deref() == Some(oid.as_str()))
+            });
+        })
+    }
+
+    async fn article_simhashes(&self) -> Result<Vec<ArticleSimhash>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.articles
+            .iter()
+            .filter(|(_, a)| t.public_group(a).is_some())
+            .filter_map(|(slug, a)| {
+                let simhash = t.blobs.get(a.content_oid.as_deref()?)?.simhash?;
+                Some(ArticleSimhash {
+                    slug: slug.to_owned(),
+                    title: a.title.to_owned(),
+                    group_id: a.group_id.to_owned(),
+                    source_path: a.source_path.to_owned(),
+                    simhash,
+                })
+            })
+            .collect())
+    }
+
+    async fn group_settings(&self) -> Result<HashMap<GroupPath, GroupSettings>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.groups
+            .iter()
+            .map(|(id, g)| {
+                let settings = GroupSettings {
+                    encrypted: g.encrypted,
+                    slug_strategy: g.slug_strategy,
+                };
+                (id.to_owned(), settings)
+            })
+            .collect())
+    }
+
+    async fn group_authors(&self) -> Result<HashMap<GroupPath, GroupAuthor>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.groups
+            .iter()
+            .filter_map(|(id, g)| g.author.clone().map(|a| (id.to_owned(), a)))
+            .collect())
+    }
+
+    async fn group_visibility(&self) -> Result<HashMap<GroupPath, bool>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.groups
+            .iter()
+            .map(|(id, g)| (id.to_owned(), g.public))
+            .collect())
+    }
+
+    async fn group_slugs(&self) -> Result<HashMap<GroupPath, String>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.groups
+            .iter()
+            .map(|(id, g)| (id.to_owned(), g.slug.to_owned()))
+            .collect())
+    }
+
+    fn remove_article(&mut self, article_ref: ArticleRef<'_>) -> &mut Self {
+        let (slug, group) = (article_ref.slug.to_owned(), article_ref.group.to_owned());
+        self.touched.slugs.insert(slug.clone());
+        self.push(move |t| {
+            if t.articles
+                .get(&slug)
+                .is_some_and(|a| a.group_id == group.as_str())
+            {
+                t.articles.remove(&slug);
+            }
+            t.links.retain(|(s, _), _| *s != slug);
+            if t.attachments.get(&slug).is_some_and(|(g, _)| *g == group) {
+                t.attachments.remove(&slug);
+            }
+        })
+    }
+
+    /// 与 [`SqlxStore`](super::SqlxStore) 相同，写入时折叠重定向链路与环
+    fn upsert_redirect(&mut self, from: ArticleRef<'_>, to: ArticleRef<'_>) -> &mut Self {
+        let from = (from.group.to_owned(), from.slug.to_owned());
+        let to = (to.group.to_owned(), to.slug.to_owned());
+        self.push(move |t| {
+            t.redirects.remove(&to);
+            for target in t.redirects.values_mut() {
+                if *target == from {
+                    *target = to.clone();
+                }
+            }
+            t.redirects.insert(from, to);
+            t.redirects.retain(|from, to| from != to);
+        })
+    }
+
+    fn update_comment_counts(&mut self, counts: &HashMap<String, i64>) -> &mut Self {
+        let counts = counts.clone();
+        self.push(move |t| {
+            for (slug, count) in counts {
+                if let Some(article) = t.articles.get_mut(&slug) {
+                    article.comment_count = count;
+                }
+            }
+        })
+    }
+
+    fn set_article_source(&mut self, slug: &str, path: &str, commit: &str) -> &mut Self {
+        let (slug, path, commit) = (slug.to_owned(), path.to_owned(), commit.to_owned());
+        self.push(move |t| {
+            if let Some(article) = t.articles.get_mut(&slug) {
+                article.source_path = Some(path);
+                article.source_commit = Some(commit);
+            }
+        })
+    }
+
+    async fn article_short_ids(&self) -> Result<Vec<ArticleShortId>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.articles
+            .iter()
+            .filter_map(|(slug, a)| {
+                a.short_id.as_ref().map(|short_id| ArticleShortId {
+                    slug: slug.to_owned(),
+                    source_path: a.source_path.to_owned(),
+                    short_id: short_id.to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    fn set_article_short_id(&mut self, slug: &str, short_id: &str) -> &mut Self {
+        let (slug, short_id) = (slug.to_owned(), short_id.to_owned());
+        self.push(move |t| {
+            if !t.articles.contains_key(&slug) {
+                return;
+            }
+            for (other, article) in t.articles.iter_mut() {
+                if *other != slug && article.short_id.as_ref() == Some(&short_id) {
+                    article.short_id = None;
+                }
+            }
+            if let Some(article) = t.articles.get_mut(&slug) {
+                article.short_id = Some(short_id);
+            }
+        })
+    }
+
+    fn set_article_renderer(&mut self, slug: &str, renderer: &str) -> &mut Self {
+        let (slug, renderer) = (slug.to_owned(), renderer.to_owned());
+        self.push(move |t| {
+            if let Some(article) = t.articles.get_mut(&slug) {
+                article.renderer = Some(renderer);
+            }
+        })
+    }
+
+    fn set_article_render_stats(
+        &mut self,
+        slug: &str,
+        render_ms: i64,
+        content_bytes: i64,
+    ) -> &mut Self {
+        let slug = slug.to_owned();
+        self.push(move |t| {
+            if let Some(article) = t.articles.get_mut(&slug) {
+                article.render_ms = Some(render_ms);
+                article.content_bytes = Some(content_bytes);
+                article.rendered_at = Some(Utc::now().fixed_offset());
+            }
+        })
+    }
+
+    fn replace_links(&mut self, slug: &str, urls: &[String]) -> &mut Self {
+        let (slug, urls) = (slug.to_owned(), urls.to_vec());
+        self.push(move |t| {
+            t.links
+                .retain(|(s, url), _| *s != slug || urls.contains(url));
+            for url in urls {
+                t.links.entry((slug.clone(), url)).or_default();
+            }
+        })
+    }
+
+    fn replace_attachments(
+        &mut self,
+        article_ref: ArticleRef<'_>,
+        attachments: &[Attachment],
+    ) -> &mut Self {
+        let (slug, group) = (article_ref.slug.to_owned(), article_ref.group.to_owned());
+        let mut rows: Vec<Attachment> = Vec::new();
+        for attachment in attachments {
+            if !rows.iter().any(|a| a.url_or_path == attachment.url_or_path) {
+                rows.push(attachment.clone());
+            }
+        }
+        self.push(move |t| {
+            match rows.is_empty() {
+                true => t.attachments.remove(&slug),
+                false => t.attachments.insert(slug, (group, rows)),
+            };
+        })
+    }
+
+    fn record_link_status(
+        &mut self,
+        url: &str,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let (url, error) = (url.to_owned(), error.map(str::to_owned));
+        self.push(move |t| {
+            let now = Utc::now().fixed_offset();
+            for ((_, u), link) in t.links.iter_mut() {
+                if *u == url {
+                    link.status = status;
+                    link.error = error.clone();
+                    link.last_checked = Some(now);
+                }
+            }
+        })
+    }
+
+    fn prune_links(&mut self) -> &mut Self {
+        self.push(|t| {
+            let articles = &t.articles;
+            t.links.retain(|(slug, _), _| articles.contains_key(slug));
+        })
+    }
+
+    fn record_webmention(
+        &mut self,
+        slug: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let key = (slug.to_owned(), target.to_owned());
+        let row = WebmentionRow {
+            endpoint: endpoint.map(str::to_owned),
+            status,
+            error: error.map(str::to_owned),
+            sent_at: Utc::now().fixed_offset(),
+        };
+        self.push(move |t| {
+            t.webmentions.insert(key, row);
+        })
+    }
+
+    fn remove_webmention(&mut self, slug: &str, target: &str) -> &mut Self {
+        let key = (slug.to_owned(), target.to_owned());
+        self.push(move |t| {
+            t.webmentions.remove(&key);
+        })
+    }
+
+    fn record_digest_run(
+        &mut self,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        article_count: i64,
+        status: &str,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let run = DigestRun {
+            period_end,
+            period_start,
+            article_count,
+            status: status.to_owned(),
+            error: error.map(str::to_owned),
+            ran_at: Utc::now().fixed_offset(),
+        };
+        self.push(move |t| {
+            t.digest_runs.insert(run.period_end, run);
+        })
+    }
+
+    fn replace_includes(&mut self, path: &str, includes: &[String]) -> &mut Self {
+        let (path, includes) = (path.to_owned(), includes.to_vec());
+        self.push(move |t| {
+            t.includes.retain(|(article, _)| *article != path);
+            for include in includes {
+                t.includes.insert((path.clone(), include));
+            }
+        })
+    }
+
+    async fn include_dependents(&self, paths: &[String]) -> Result<Vec<String>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.includes
+            .iter()
+            .filter(|(_, include)| paths.contains(include))
+            .map(|(article, _)| article.to_owned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect())
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        Savepoint(self.ops.len())
+    }
+
+    fn rollback_to(&mut self, savepoint: Savepoint) -> &mut Self {
+        self.ops.truncate(savepoint.0);
+        self
+    }
+
+    fn record_sync_failure(&mut self, path: &str, reason: &str, commit: &str) -> &mut Self {
+        let path = path.to_owned();
+        let row = FailureRow {
+            reason: reason.to_owned(),
+            commit_id: commit.to_owned(),
+            failed_at: Utc::now().fixed_offset(),
+        };
+        self.push(move |t| {
+            t.sync_failures.insert(path, row);
+        })
+    }
+
+    fn clear_sync_failure(&mut self, path: &str) -> &mut Self {
+        let path = path.to_owned();
+        self.push(move |t| {
+            t.sync_failures.remove(&path);
+        })
+    }
+
+    fn retarget_sync_failures(&mut self, commit: &str) -> &mut Self {
+        let commit = commit.to_owned();
+        self.push(move |t| {
+            for failure in t.sync_failures.values_mut() {
+                failure.commit_id = commit.clone();
+            }
+        })
+    }
+
+    async fn sync_failures(&self) -> Result<Vec<SyncFailure>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        Ok(t.sync_failures
+            .iter()
+            .map(|(path, f)| SyncFailure {
+                path: path.to_owned(),
+                reason: f.reason.to_owned(),
+                commit_id: f.commit_id.to_owned(),
+                failed_at: f.failed_at,
+            })
+            .collect())
+    }
+
+    async fn fingerprint_rows(
+        &self,
+    ) -> Result<(Vec<GroupFingerprint>, Vec<ArticleFingerprint>), error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+
+        let groups = t
+            .groups
+            .iter()
+            .map(|(id, g)| GroupFingerprint {
+                id: id.to_owned(),
+                slug: g.slug.to_owned(),
+                name: g.name.to_owned(),
+                public: g.public,
+                encrypted: g.encrypted,
+                slug_strategy: g.slug_strategy.as_str().to_owned(),
+                author_id: g.author.as_ref().map(|a| a.id.to_owned()),
+                author_name: g.author.as_ref().map(|a| a.name.to_owned()),
+                archived: g.archived,
+                default_tags: g.default_tags.to_owned(),
+                kind: g.kind.clone(),
+                category_id: g.category.as_ref().map(|c| c.id.to_owned()),
+                category_name: g.category.as_ref().map(|c| c.name.to_owned()),
+                nav_order: g.nav_order,
+                hidden: g.hidden,
+                retain_max_articles: g.retain_max_articles,
+                retain_max_age_days: g.retain_max_age_days,
+                license: g
+                    .license
+                    .as_ref()
+                    .and_then(|l| serde_json::to_value(l).ok()),
+            })
+            .collect();
+        let articles = t
+            .articles
+            .iter()
+            .filter(|(_, a)| t.published_group(a).is_some())
+            .map(|(slug, a)| ArticleFingerprint {
+                group_id: a.group_id.to_owned(),
+                slug: slug.to_owned(),
+                content_oid: a.content_oid.to_owned(),
+                updated_at: a.updated_at.timestamp_millis(),
+            })
+            .collect();
+
+        Ok((groups, articles))
+    }
+
+    fn record_sync_state(&mut self, commit: &str, fingerprint: &str) -> &mut Self {
+        let state = SyncState {
+            commit_id: commit.to_owned(),
+            content_fingerprint: fingerprint.to_owned(),
+            computed_at: Utc::now().fixed_offset(),
+        };
+        self.push(move |t| t.sync_state = Some(state))
+    }
+
+    async fn dump_page(
+        &self,
+        table: DumpTable,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DumpRecord>, error::Error> {
+        let t = self.tables.read().unwrap_or_else(PoisonError::into_inner);
+        let after = |key: &str| after.is_none_or(|after| key > after);
+
+        let records = match table {
+            DumpTable::Groups => t
+                .groups
+                .iter()
+                .filter(|(id, _)| after(id.as_str()))
+                .take(limit)
+                .map(|(id, g)| DumpRecord::Group(g.to_dump(id)))
+                .collect(),
+            DumpTable::Articles => t
+                .articles
+                .iter()
+                .filter(|(slug, _)| after(slug))
+                .take(limit)
+                .map(|(slug, a)| DumpRecord::Article(a.to_dump(slug)))
+                .collect(),
+            DumpTable::ContentBlobs => t
+                .blobs
+                .iter()
+                .filter(|(oid, _)| after(oid))
+                .take(limit)
+                .map(|(_, b)| DumpRecord::ContentBlob(b.clone()))
+                .collect(),
+        };
+
+        Ok(records)
+    }
+
+    /// 与 [`MemoryStore::commit`] 相同，在数据副本上写入，全部成功后整体替换
+    async fn load_dump<I>(&self, records: I, truncate: bool) -> Result<DumpCounts, error::Error>
+    where
+        I: Iterator<Item = Result<DumpRecord, DumpError>> + Send,
+    {
+        let mut next = self
+            .tables
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if truncate {
+            next.groups.clear();
+            next.articles.clear();
+            next.blobs.clear();
+        }
+
+        let mut counts = DumpCounts::default();
+        for record in records {
+            let record = record?;
+            counts.add(&record);
+            match record {
+                DumpRecord::Group(g) => {
+                    let (id, row) = GroupRow::from_dump(g);
+                    next.groups.insert(id, row);
+                }
+                DumpRecord::Article(a) => {
+                    let (slug, row) = ArticleRow::from_dump(a);
+                    next.articles.insert(slug, row);
+                }
+                DumpRecord::ContentBlob(b) => {
+                    next.blobs.insert(b.oid.clone(), b);
+                }
+            }
+        }
+        next.refresh_tag_index(None);
+        next.refresh_group_stats();
+
+        *self.tables.write().unwrap_or_else(PoisonError::into_inner) = next;
+        Ok(counts)
+    }
+
+    fn enforce_quotas(&mut self) -> &mut Self {
+        self.enforce_quotas = true;
+        self
+    }
+
+    /// 在数据副本上依次执行写入，完成后整体替换，查询始终读取完整的旧数据或新数据
+    async fn commit(self) -> Result<(), error::Error> {
+        let injected = self
+            .faults
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+        if injected.is_ok() {
+            return Err(sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into()).into());
+        }
+
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+
+        let mut next = if self.rebuild {
+            // 外部链接记录检查历史，渲染结果供重建复用，内容指纹在重建后更新，
+            // webmention 和每周摘要的发送记录避免重复通知，均不参与重建
+            Tables {
+                links: tables.links.clone(),
+                blobs: tables.blobs.clone(),
+                sync_state: tables.sync_state.clone(),
+                webmentions: tables.webmentions.clone(),
+                digest_runs: tables.digest_runs.clone(),
+                ..Default::default()
+            }
+        } else {
+            tables.clone()
+        };
+        for op in self.ops {
+            op(&mut next);
+        }
+
+        // 增量写入只重新计算写入前后涉及的标签，重建时整体重新计算
+        if self.rebuild {
+            next.refresh_tag_index(None);
+        } else {
+            let mut tags = tables.touched_tags(&self.touched);
+            tags.extend(next.touched_tags(&self.touched));
+            next.refresh_tag_index(Some(&tags));
+        }
+        next.refresh_group_stats();
+        if self.enforce_quotas
+            && !self.rebuild
+            && let Some(item) = next.over_quota()
+        {
+            return Err(error::Error::QuotaExceeded(item));
+        }
+
+        *tables = next;
+        Ok(())
+    }
+}
+
+impl Querier for MemoryStorage {
+    type Error = sqlx::Error;
+
+    async fn get_one(&self, slug: impl AsRef<str>) -> Result<Option<ArticleDetail>, Self::Error> {
+        let t = self.read();
+        let slug = slug.as_ref();
+
+        let Some(article) = t.articles.get(slug) else {
+            return Ok(None);
+        };
+        let Some(group) = t.public_group(article) else {
+            return Ok(None);
+        };
+
+        let mut siblings = t
+            .articles
+            .iter()
+            .filter(|(_, a)| a.group_id == article.group_id && a.public.unwrap_or(group.public))
+            .collect::<Vec<_>>();
+        siblings.sort_by_key(|(s, a)| (a.created_at, *s));
+        let index = siblings.iter().position(|(s, _)| *s == slug);
+        let neighbor = |i: Option<usize>| {
+            i.and_then(|i| siblings.get(i)).map(|(s, a)| {
+                Json(Neighbor {
+                    slug: s.to_string(),
+                    title: a.title.to_owned(),
+                })
+            })
+        };
+
+        let mut detail = ArticleDetail {
+            slug: slug.to_owned(),
+            short_id: article.short_id.to_owned(),
+            title: article.title.to_owned(),
+            summary: article.summary.to_owned(),
+            tags: article.tags.to_owned(),
+            content: article
+                .content_oid
+                .as_ref()
+                .and_then(|oid| t.blobs.get(oid))
+                .map_or_else(|| article.content.to_owned(), |b| b.content.to_owned()),
+            group: Json(group.to_embedded(article.group_id.as_str())),
+            comment_count: article.comment_count,
+            nonce: article.nonce.to_owned(),
+            updated_at: article.updated_at,
+            created_at: article.created_at,
+            prev: neighbor(index.and_then(|i| i.checked_sub(1))),
+            next: neighbor(index.map(|i| i + 1)),
+            source_path: article.source_path.to_owned(),
+            source_commit: article.source_commit.to_owned(),
+            canonical_url: article.canonical_url.to_owned(),
+            social: article.social.clone().map(Json),
+            attachments: Json(
+                t.attachments
+                    .get(slug)
+                    .map(|(_, rows)| rows.clone())
+                    .unwrap_or_default(),
+            ),
+        };
+
+        open_fields(
+            detail.nonce.as_deref(),
+            &mut [
+                (Field::Content, &mut detail.content),
+                (Field::Summary, &mut detail.summary),
+            ],
+        )
+        .map_err(|e| sqlx::Error::Decode(e.into()))?;
+
+        Ok(Some(detail))
+    }
+
+    async fn article_list(
+        &self,
+        page: i32,
+        size: i32,
+        group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
+        tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
+    ) -> Result<Vec<ArticleSummary>, sqlx::Error> {
+        let t = self.read();
+        let offset = (page.max(1) - 1) * size;
+
+        let mut articles = t
+            .public_articles()
+            .filter(|(_, _, g)| group.is_none_or(|slug| g.slug == slug))
+            .filter(|(_, _, g)| {
+                author.is_none_or(|id| g.author.as_ref().is_some_and(|a| a.id == id))
+            })
+            .filter(|(_, _, g)| {
+                category.is_none_or(|id| g.category.as_ref().is_some_and(|c| c.id == id))
+            })
+            .filter(|(_, a, _)| {
+                tags.is_empty()
+                    || a.tags
+                        .iter()
+                        .any(|t| tags.iter().any(|filter| tag_matches(t, filter)))
+            })
+            .filter(|(_, _, g)| archived.is_none_or(|archived| g.archived == archived))
+            .filter(|(_, a, _)| dates.contains(a.created_at, a.updated_at))
+            .collect::<Vec<_>>();
+        match sort {
+            ListSort::Updated => articles.sort_by_key(|(_, a, _)| std::cmp::Reverse(a.updated_at)),
+            ListSort::Title => articles.sort_by(|(x_slug, x, _), (y_slug, y, _)| {
+                (&x.title_sort_key, &x.title, x_slug).cmp(&(&y.title_sort_key, &y.title, y_slug))
+            }),
+        }
+
+        let mut result = Vec::new();
+        for (slug, a, g) in articles
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(size.max(0) as usize)
+        {
+            let (mut summary, mut summary_plain) = (String::new(), String::new());
+            if columns.summary {
+                (summary, summary_plain) = (a.summary.to_owned(), a.summary_plain.to_owned());
+                open_fields(
+                    a.nonce.as_deref(),
+                    &mut [
+                        (Field::Summary, &mut summary),
+                        (Field::SummaryPlain, &mut summary_plain),
+                    ],
+                )
+                .map_err(|e| sqlx::Error::Decode(e.into()))?;
+            }
+
+            result.push(ArticleSummary {
+                slug: slug.to_owned(),
+                short_id: a.short_id.to_owned(),
+                title: a.title.to_owned(),
+                summary,
+                summary_plain,
+                tags: if columns.tags {
+                    a.tags.to_owned()
+                } else {
+                    Vec::new()
+                },
+                group: Json(g.to_embedded(a.group_id.as_str())),
+                comment_count: a.comment_count,
+                nonce: a.nonce.to_owned(),
+                updated_at: a.updated_at,
+                created_at: a.created_at,
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn authors(&self) -> Result<Vec<AuthorSummary>, sqlx::Error> {
+        let t = self.read();
+
+        let mut articles = t
+            .public_articles()
+            .filter_map(|(slug, a, g)| g.author.as_ref().map(|author| (slug, a, author)))
+            .collect::<Vec<_>>();
+        // 最新的文章在前，决定显示名
+        articles.sort_by(|(sa, a, _), (sb, b, _)| (b.created_at, sb).cmp(&(a.created_at, sa)));
+
+        let mut authors = BTreeMap::new();
+        for (slug, article, author) in articles {
+            authors
+                .entry(author.id.as_str())
+                .or_insert_with(|| AuthorSummary {
+                    id: author.id.to_owned(),
+                    name: author.name.to_owned(),
+                    article_count: 0,
+                    latest: Json(Neighbor {
+                        slug: slug.to_owned(),
+                        title: article.title.to_owned(),
+                    }),
+                })
+                .article_count += 1;
+        }
+
+        Ok(authors.into_values().collect())
+    }
+
+    async fn category_groups(&self, category: &str) -> Result<Vec<CategoryGroup>, sqlx::Error> {
+        let t = self.read();
+
+        let mut groups = t
+            .groups
+            .iter()
+            .filter(|(_, g)| g.public)
+            .filter_map(|(id, g)| {
+                let c = g.category.as_ref().filter(|c| c.id == category)?;
+                Some(CategoryGroup {
+                    slug: g.slug.to_owned(),
+                    name: g.name.to_owned(),
+                    category_name: c.name.to_owned(),
+                    article_count: t
+                        .articles
+                        .values()
+                        .filter(|a| &a.group_id == id && a.public.unwrap_or(g.public))
+                        .count() as i64,
+                })
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+        Ok(groups)
+    }
+
+    async fn groups(&self) -> Result<Vec<Group>, sqlx::Error> {
+        let t = self.read();
+
+        let mut groups = t
+            .groups
+            .iter()
+            .filter(|(_, g)| g.public)
+            .map(|(id, g)| g.to_model(id.as_str()))
+            .collect::<Vec<_>>();
+        // 与 `ORDER BY nav_order NULLS LAST, name, id` 相同
+        groups.sort_by(|a, b| {
+            (a.nav_order.is_none(), a.nav_order, &a.name, &a.id).cmp(&(
+                b.nav_order.is_none(),
+                b.nav_order,
+                &b.name,
+                &b.id,
+            ))
+        });
+        Ok(groups)
+    }
+
+    async fn tags(&self) -> Result<Vec<String>, sqlx::Error> {
+        Ok(self.read().tag_index.keys().cloned().collect())
+    }
+
+    async fn tags_with_counts(&self) -> Result<Vec<TagCount>, sqlx::Error> {
+        Ok(self.read().tag_index.values().cloned().collect())
+    }
+
+    async fn article_tags(&self) -> Result<Vec<Vec<String>>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.public_articles()
+            .map(|(_, a, _)| a.tags.clone())
+            .collect())
+    }
+
+    async fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> Result<Option<DateTime<FixedOffset>>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.articles
+            .get(slug)
+            .filter(|a| t.public_group(a).is_some())
+            .map(|a| a.updated_at))
+    }
+
+    async fn short_id_slug(&self, short_id: &str) -> Result<Option<String>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.public_articles()
+            .find(|(_, a, _)| a.short_id.as_deref() == Some(short_id))
+            .map(|(slug, _, _)| slug.to_owned()))
+    }
+
+    async fn public_slugs(&self) -> Result<Vec<String>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.public_articles()
+            .map(|(slug, _, _)| slug.to_owned())
+            .collect())
+    }
+
+    async fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleSlug>, sqlx::Error> {
+        let t = self.read();
+
+        let mut rows = t
+            .public_articles()
+            .filter(|(_, _, g)| group.is_none_or(|group| g.slug == group))
+            .map(|(slug, a, g)| ArticleSlug {
+                group: g.slug.to_owned(),
+                slug: slug.to_owned(),
+                updated_at: a.updated_at,
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| (&a.group, &a.slug).cmp(&(&b.group, &b.slug)));
+        rows.truncate(limit.max(0) as usize);
+        Ok(rows)
+    }
+
+    async fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TitleEntry>, sqlx::Error> {
+        let t = self.read();
+
+        let mut rows = t
+            .public_articles()
+            .filter(|(_, _, g)| group.is_none_or(|group| g.slug == group))
+            .map(|(slug, a, g)| TitleEntry {
+                slug: slug.to_owned(),
+                title: a.title.to_owned(),
+                group: g.slug.to_owned(),
+                title_sort_key: Some(a.title_sort_key.to_owned()),
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| {
+            (&a.title_sort_key, &a.title, &a.slug).cmp(&(&b.title_sort_key, &b.title, &b.slug))
+        });
+        rows.truncate(limit.max(0) as usize);
+        Ok(rows)
+    }
+
+    async fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> Result<Option<Redirect>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.redirects
+            .iter()
+            .filter(|((from_group, from_slug), _)| {
+                // 旧位置记录的是组目录路径，也可以用该组当前的 slug 查询
+                let matches = |g: &str| {
+                    from_group == g
+                        || t.groups
+                            .get(from_group.as_str())
+                            .is_some_and(|row| row.slug == g)
+                };
+                group.is_none_or(matches) && from_slug == slug
+            })
+            .find_map(|(_, (to_group, to_slug))| {
+                let article = t.articles.get(to_slug)?;
+                let group = t
+                    .public_group(article)
+                    .filter(|_| article.group_id == to_group.as_str())?;
+                Some(Redirect {
+                    group_id: group.slug.to_owned(),
+                    slug: to_slug.to_owned(),
+                })
+            }))
+    }
+
+    async fn links_due(&self, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        let t = self.read();
+
+        // 同一链接取最近一次检查时间，从未检查过的排在最前
+        let mut checked = BTreeMap::<&String, Option<DateTime<FixedOffset>>>::new();
+        for ((slug, url), link) in &t.links {
+            if t.articles.contains_key(slug) {
+                let last = checked.entry(url).or_default();
+                *last = (*last).max(link.last_checked);
+            }
+        }
+
+        let mut urls = checked.into_iter().collect::<Vec<_>>();
+        urls.sort_by_key(|(_, last)| *last);
+
+        Ok(urls
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(url, _)| url.to_owned())
+            .collect())
+    }
+
+    async fn broken_links(&self) -> Result<Vec<BrokenLink>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.links
+            .iter()
+            .filter(|(_, l)| l.status.is_some_and(|s| s >= 400) || l.error.is_some())
+            .filter_map(|((slug, url), l)| {
+                t.articles.get(slug).map(|a| BrokenLink {
+                    slug: slug.to_owned(),
+                    title: a.title.to_owned(),
+                    url: url.to_owned(),
+                    status: l.status,
+                    error: l.error.to_owned(),
+                    last_checked: l.last_checked,
+                })
+            })
+            .collect())
+    }
+
+    async fn sync_state(&self) -> Result<Option<SyncState>, sqlx::Error> {
+        Ok(self.read().sync_state.clone())
+    }
+
+    async fn site_counts(&self) -> Result<SiteCounts, sqlx::Error> {
+        let t = self.read();
+
+        Ok(SiteCounts {
+            articles: t.public_articles().count() as i64,
+            groups: t.groups.values().filter(|g| g.public).count() as i64,
+            tags: t.tag_index.len() as i64,
+            synced_at: t.sync_state.as_ref().map(|s| s.computed_at),
+        })
+    }
+
+    async fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<WebmentionSource>, sqlx::Error> {
+        let t = self.read();
+
+        Ok(t.public_articles()
+            .filter(|(_, a, _)| a.nonce.is_none())
+            .filter(|(_, a, _)| a.source_path.as_ref().is_some_and(|p| paths.contains(p)))
+            .map(|(slug, _, _)| WebmentionSource {
+                slug: slug.to_owned(),
+                links: targets_of(t.links.keys(), slug),
+                sent: targets_of(t.webmentions.keys(), slug),
+            })
+            .collect())
+    }
+
+    async fn stale_renders(&self, current: &str) -> Result<Vec<StaleRender>, sqlx::Error> {
+        Ok(self
+            .read()
+            .articles
+            .iter()
+            .filter(|(_, a)| {
+                a.renderer.as_deref() != Some(current)
+                    && a.renderer.as_deref() != Some(RAW_HTML_RENDERER)
+            })
+            .map(|(slug, a)| StaleRender {
+                slug: slug.to_owned(),
+                title: a.title.to_owned(),
+                renderer: a.renderer.to_owned(),
+                source_path: a.source_path.to_owned(),
+            })
+            .collect())
+    }
+
+    async fn orphan_articles(&self) -> Result<Vec<OrphanArticle>, sqlx::Error> {
+        let t = self.read();
+        let mut orphans = t
+            .articles
+            .iter()
+            .filter(|(_, a)| !t.groups.contains_key(&a.group_id))
+            .map(|(slug, a)| OrphanArticle {
+                slug: slug.to_owned(),
+                title: a.title.to_owned(),
+                group_id: a.group_id.to_owned(),
+                source_path: a.source_path.to_owned(),
+            })
+            .collect::<Vec<_>>();
+        orphans.sort_by(|a, b| (&a.group_id, &a.slug).cmp(&(&b.group_id, &b.slug)));
+
+        Ok(orphans)
+    }
+
+    async fn pruned_articles(&self) -> Result<Vec<PrunedArticle>, sqlx::Error> {
+        let t = self.read();
+        let mut pruned = t
+            .articles
+            .iter()
+            .filter(|(_, a)| a.retention_pruned)
+            .map(|(slug, a)| PrunedArticle {
+                slug: slug.to_owned(),
+                title: a.title.to_owned(),
+                group_id: a.group_id.to_owned(),
+                created_at: a.created_at,
+                source_path: a.source_path.to_owned(),
+            })
+            .collect::<Vec<_>>();
+        pruned.sort_by(|a, b| {
+            (&a.group_id, b.created_at, &b.slug).cmp(&(&b.group_id, a.created_at, &a.slug))
+        });
+
+        Ok(pruned)
+    }
+
+    async fn group_stats(&self) -> Result<Vec<GroupStats>, sqlx::Error> {
+        let t = self.read();
+        Ok(t.group_stats
+            .iter()
+            .filter_map(|(id, s)| {
+                let group = t.groups.get(id)?;
+                Some(GroupStats {
+                    group_id: id.to_owned(),
+                    slug: group.slug.to_owned(),
+                    content_bytes: s.content_bytes,
+                    asset_bytes: s.asset_bytes,
+                    article_count: s.article_count,
+                    quota_bytes: group.quota_bytes,
+                    updated_at: s.updated_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> Result<Vec<RenderStat>, sqlx::Error> {
+        let t = self.read();
+        let mut stats = t
+            .articles
+            .iter()
+            .filter_map(|(slug, a)| {
+                Some(RenderStat {
+                    slug: slug.to_owned(),
+                    title: a.title.to_owned(),
+                    group_id: a.group_id.to_owned(),
+                    source_path: a.source_path.to_owned(),
+                    render_ms: a.render_ms?,
+                    content_bytes: a.content_bytes?,
+                    rendered_at: a.rendered_at?,
+                })
+            })
+            .collect::<Vec<_>>();
+        let key = |s: &RenderStat| match sort {
+            RenderSort::RenderMs => s.render_ms,
+            RenderSort::ContentBytes => s.content_bytes,
+        };
+        stats.sort_by(|a, b| key(b).cmp(&key(a)).then_with(|| a.slug.cmp(&b.slug)));
+        stats.truncate(usize::try_from(limit).unwrap_or_default());
+
+        Ok(stats)
+    }
+
+    async fn last_digest_run(&self) -> Result<Option<DigestRun>, sqlx::Error> {
+        let t = self.read();
+        Ok(t.digest_runs.values().next_back().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use crate::content::FrontMatter;
+
+    use super::*;
+
+    fn day(d: u32) -> DateTime<FixedOffset> {
+        Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0)
+            .unwrap()
+            .fixed_offset()
+    }
+
+    fn article(group: &str, slug: &str, d: u32, tags: &[&str]) -> Article {
+        Article {
+            group: group.into(),
+            slug: slug.to_string(),
+            frontmatter: FrontMatter {
+                title: slug.to_string(),
+                summary: format!("{slug} summary"),
+                datetime: day(d),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                aliases: vec![],
+                canonical: None,
+                public: None,
+                raw_html: false,
+                cover: None,
+            },
+            rendered_content: format!("<p>{slug}</p>"),
+            namespaced_content: None,
+            rendered_by: None,
+            updated_at: day(d),
+            source_bytes: 100,
+        }
+    }
+
+    fn group(id: &str, public: bool) -> content::Group {
+        content::Group::new(
+            format!("{id}/.group.yaml"),
+            format!("name: {id}\npublic: {public}\n"),
+        )
+        .unwrap()
+    }
+
+    fn at<'a>(group: &'a str, slug: &'a str) -> ArticleRef<'a> {
+        ArticleRef { slug, group }
+    }
+
+    async fn seeded() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let mut store = storage.store();
+        store
+            .upsert_group(&group("notes", true))
+            .upsert_group(&group("drafts", false))
+            .upsert_article(&article("notes", "a", 1, &["rust"]))
+            .upsert_article(&article("notes", "b", 2, &["go"]))
+            .upsert_article(&article("notes", "c", 3, &["rust", "go"]))
+            .upsert_article(&article("drafts", "d", 4, &["secret"]));
+        store.commit().await.unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_queries() {
+        let storage = seeded().await;
+
+        let slugs =
+            |list: Vec<ArticleSummary>| list.into_iter().map(|a| a.slug).collect::<Vec<_>>();
+        assert_eq!(
+            slugs(
+                storage
+                    .article_list(
+                        1,
+                        10,
+                        None,
+                        None,
+                        None,
+                        vec![],
+                        None,
+                        DateRange::default(),
+                        ListColumns::ALL,
+                        ListSort::Updated,
+                    )
+                    .await
+                    .unwrap()
+            ),
+            ["c", "b", "a"]
+        );
+        assert_eq!(
+            slugs(
+                storage
+                    .article_list(
+                        2,
+                        2,
+                        None,
+                        None,
+                        None,
+                        vec![],
+                        None,
+                        DateRange::default(),
+                        ListColumns::ALL,
+                        ListSort::Updated,
+                    )
+                    .await
+                    .unwrap()
+            ),
+            ["a"]
+        );
+        assert_eq!(
+            slugs(
+                storage
+                    .article_list(
+                        1,
+                        10,
+                        None,
+                        None,
+                        None,
+                        vec!["rust"],
+                        None,
+                        DateRange::default(),
+                        ListColumns::ALL,
+                        ListSort::Updated,
+                    )
+                    .await
+                    .unwrap()
+            ),
+            ["c", "a"]
+        );
+        assert!(
+            storage
+                .article_list(
+                    1,
+                    10,
+                    Some("drafts"),
+                    None,
+                    None,
+                    vec![],
+                    None,
+                    DateRange::default(),
+                    ListColumns::ALL,
+                    ListSort::Updated,
+                )
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        assert_eq!(storage.tags().await.unwrap(), ["go", "rust"]);
+        assert_eq!(storage.public_slugs().await.unwrap(), ["a", "b", "c"]);
+        assert_eq!(storage.groups().await.unwrap().len(), 1);
+        assert!(storage.get_one("d").await.unwrap().is_none());
+
+        let b = storage.get_one("b").await.unwrap().unwrap();
+        assert_eq!(b.prev.unwrap().slug, "a");
+        assert_eq!(b.next.unwrap().slug, "c");
+        assert_eq!(b.content, "<p>b</p>");
+        assert_eq!(
+            storage.article_updated_at("b").await.unwrap(),
+            Some(b.updated_at)
+        );
+        assert!(storage.article_updated_at("d").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_archived_filter() {
+        let storage = seeded().await;
+        let old = content::Group::new("old/.group.yaml", "public: true\narchived: true\n".into())
+            .unwrap();
+        let mut store = storage.store();
+        store
+            .upsert_group(&old)
+            .upsert_article(&article("old", "e", 5, &[]));
+        store.commit().await.unwrap();
+
+        let list = |archived| {
+            storage.article_list(
+                1,
+                10,
+                None,
+                None,
+                None,
+                vec![],
+                archived,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+        };
+        let slugs =
+            |list: Vec<ArticleSummary>| list.into_iter().map(|a| a.slug).collect::<Vec<_>>();
+        assert_eq!(slugs(list(Some(false)).await.unwrap()), ["c", "b", "a"]);
+        assert_eq!(slugs(list(Some(true)).await.unwrap()), ["e"]);
+        assert_eq!(list(None).await.unwrap().len(), 4);
+
+        // 详情不按归档状态过滤
+        assert!(storage.get_one("e").await.unwrap().unwrap().group.archived);
+    }
+
+    #[tokio::test]
+    async fn test_tag_hierarchy_filter() {
+        let storage = seeded().await;
+        let mut store = storage.store();
+        store
+            .upsert_article(&article("notes", "rust", 5, &["lang/rust"]))
+            .upsert_article(&article("notes", "go", 6, &["lang/go/generics"]))
+            .upsert_article(&article("notes", "lang", 7, &["lang"]))
+            .upsert_article(&article("notes", "language", 8, &["language"]));
+        store.commit().await.unwrap();
+
+        // 上层标签匹配所有后代，不匹配仅前缀相同的标签
+        let list = |tags| {
+            storage.article_list(
+                1,
+                10,
+                None,
+                None,
+                None,
+                tags,
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+        };
+        let slugs =
+            |list: Vec<ArticleSummary>| list.into_iter().map(|a| a.slug).collect::<Vec<_>>();
+        assert_eq!(
+            slugs(list(vec!["lang"]).await.unwrap()),
+            ["lang", "go", "rust"]
+        );
+        assert_eq!(slugs(list(vec!["lang/go"]).await.unwrap()), ["go"]);
+        assert_eq!(
+            slugs(list(vec!["lang/rust", "language"]).await.unwrap()),
+            ["language", "rust"]
+        );
+
+        let tags = storage.article_tags().await.unwrap();
+        assert!(tags.contains(&vec!["lang/go/generics".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_date_range() {
+        let storage = &seeded().await;
+        let list = |page, size, tags, dates| async move {
+            storage
+                .article_list(
+                    page,
+                    size,
+                    None,
+                    None,
+                    None,
+                    tags,
+                    None,
+                    dates,
+                    ListColumns::ALL,
+                    ListSort::Updated,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|a| a.slug)
+                .collect::<Vec<_>>()
+        };
+        let range = |after, before| DateRange {
+            created_after: after,
+            created_before: before,
+            ..Default::default()
+        };
+
+        // 两端都包含
+        assert_eq!(
+            list(1, 10, vec![], range(Some(day(2)), Some(day(2)))).await,
+            ["b"]
+        );
+        assert_eq!(
+            list(1, 10, vec![], range(Some(day(2)), None)).await,
+            ["c", "b"]
+        );
+        let updated = DateRange {
+            updated_before: Some(day(1)),
+            ..Default::default()
+        };
+        assert_eq!(list(1, 10, vec![], updated).await, ["a"]);
+
+        // 与标签过滤和分页组合
+        assert_eq!(
+            list(1, 10, vec!["rust"], range(Some(day(2)), None)).await,
+            ["c"]
+        );
+        assert_eq!(
+            list(2, 1, vec![], range(Some(day(1)), Some(day(3)))).await,
+            ["b"]
+        );
+
+        // 空范围返回空列表
+        assert!(
+            list(1, 10, vec![], range(Some(day(3)), Some(day(1))))
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_keeps_created_at_and_comments() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        store.update_comment_counts(&HashMap::from([("a".to_string(), 7)]));
+        store.commit().await.unwrap();
+
+        let mut store = storage.store();
+        store.upsert_article(&article("notes", "a", 9, &[]));
+        store.commit().await.unwrap();
+
+        let a = storage.get_one("a").await.unwrap().unwrap();
+        assert_eq!(a.comment_count, 7);
+        assert_eq!(a.created_at, day(1));
+        assert_eq!(a.updated_at, day(9));
+    }
+
+    #[tokio::test]
+    async fn test_redirects_collapse() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        store
+            .upsert_redirect(at("notes", "old"), at("notes", "mid"))
+            .upsert_redirect(at("notes", "mid"), at("notes", "c"))
+            .upsert_redirect(at("notes", "c"), at("notes", "old"));
+        store.commit().await.unwrap();
+
+        // old → mid → c 折叠为 old → c，写入 c → old 后 old 不再作为来源，mid 改为指向 old
+        {
+            let t = storage.read();
+            assert_eq!(
+                t.redirects.get(&("notes".to_string(), "mid".to_string())),
+                Some(&("notes".to_string(), "old".to_string()))
+            );
+            assert!(
+                !t.redirects
+                    .contains_key(&("notes".to_string(), "old".to_string()))
+            );
+        }
+
+        let mut store = storage.store();
+        store
+            .upsert_article(&article("notes", "old", 5, &[]))
+            .upsert_redirect(at("notes", "gone"), at("notes", "a"));
+        store.commit().await.unwrap();
+
+        let redirect = storage.redirect(None, "gone").await.unwrap().unwrap();
+        assert_eq!(redirect.slug, "a");
+        assert!(
+            storage
+                .redirect(Some("other"), "gone")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(storage.redirect(None, "mid").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_group_slug() {
+        let storage = seeded().await;
+        let rust = content::Group::new(
+            "2023/rust-notes/.group.yaml",
+            "public: true\nslug: rust-notes\n".into(),
+        )
+        .unwrap();
+        let mut store = storage.store();
+        store
+            .upsert_group(&rust)
+            .upsert_article(&article("2023/rust-notes", "intro", 5, &[]))
+            .upsert_redirect(at("2023/rust-notes", "old"), at("2023/rust-notes", "intro"));
+        store.commit().await.unwrap();
+
+        // 按 slug 过滤，返回的组 id 为 slug
+        let list = |group| {
+            storage.article_list(
+                1,
+                10,
+                group,
+                None,
+                None,
+                vec![],
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+        };
+        let intro = list(Some("rust-notes")).await.unwrap();
+        assert_eq!(intro.len(), 1);
+        assert_eq!(intro[0].group.slug, "rust-notes");
+        assert_eq!(intro[0].group.id, "2023/rust-notes");
+        assert!(list(Some("2023/rust-notes")).await.unwrap().is_empty());
+        assert_eq!(list(Some("notes")).await.unwrap().len(), 3);
+
+        // 旧位置可以用目录路径或 slug 查询，目标为 slug
+        for group in ["2023/rust-notes", "rust-notes"] {
+            let redirect = storage.redirect(Some(group), "old").await.unwrap().unwrap();
+            assert_eq!(
+                (redirect.group_id.as_str(), redirect.slug.as_str()),
+                ("rust-notes", "intro")
+            );
+        }
+
+        let slugs = storage.store().group_slugs().await.unwrap();
+        assert_eq!(slugs["2023/rust-notes"], "rust-notes");
+        assert_eq!(slugs["notes"], "notes");
+    }
+
+    #[tokio::test]
+    async fn test_group_path_joins() {
+        let storage = MemoryStorage::new();
+        // 组配置路径为 NFD 形式，文章路径为 NFC 形式且带多余的 `/`
+        let cafe =
+            content::Group::new("/cafe\u{301}/.group.yaml", "public: true\n".into()).unwrap();
+        let mut store = storage.store();
+        store
+            .upsert_group(&cafe)
+            .upsert_article(&article("./caf\u{e9}//", "menu", 1, &[]));
+        store.commit().await.unwrap();
+
+        let list = storage
+            .article_list(
+                1,
+                10,
+                Some("caf\u{e9}"),
+                None,
+                None,
+                vec![],
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].group.id, "caf\u{e9}");
+        assert!(storage.get_one("menu").await.unwrap().is_some());
+
+        let slugs = storage.store().group_slugs().await.unwrap();
+        assert_eq!(slugs.len(), 1);
+        assert_eq!(slugs["caf\u{e9}"], "caf\u{e9}");
+    }
+
+    #[tokio::test]
+    async fn test_category_groups() {
+        let storage = MemoryStorage::new();
+        let grouped = |id: &str, public: bool| {
+            content::Group::new(
+                format!("{id}/.group.yaml"),
+                format!("name: {id}\npublic: {public}\ncategory:\n  name: Tech\n"),
+            )
+            .unwrap()
+        };
+        let mut store = storage.store();
+        store
+            .upsert_group(&grouped("rust", true))
+            .upsert_group(&grouped("go", true))
+            .upsert_group(&grouped("secret", false))
+            .upsert_group(&group("notes", true))
+            .upsert_article(&article("rust", "a", 1, &[]))
+            .upsert_article(&article("rust", "b", 2, &[]))
+            .upsert_article(&article("secret", "c", 3, &[]))
+            .upsert_article(&article("notes", "d", 4, &[]));
+        store.commit().await.unwrap();
+
+        // 未公开的组不出现，也不计入文章数
+        let groups = storage.category_groups("tech").await.unwrap();
+        let counts = groups
+            .iter()
+            .map(|g| (g.slug.as_str(), g.article_count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [("go", 0), ("rust", 2)]);
+        assert!(groups.iter().all(|g| g.category_name == "Tech"));
+        assert!(storage.category_groups("unknown").await.unwrap().is_empty());
+
+        let list = storage
+            .article_list(
+                1,
+                10,
+                None,
+                None,
+                Some("tech"),
+                vec![],
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+            .await
+            .unwrap();
+        let slugs = list.iter().map(|a| a.slug.as_str()).collect::<Vec<_>>();
+        assert_eq!(slugs, ["b", "a"]);
+        assert_eq!(list[0].group.category_id.as_deref(), Some("tech"));
+    }
+
+    #[tokio::test]
+    async fn test_group_description() {
+        let storage = seeded().await;
+        let description = |id: &'static str| {
+            let storage = &storage;
+            async move {
+                storage
+                    .groups()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .find(|g| g.id == id)
+                    .and_then(|g| g.description_html)
+            }
+        };
+
+        // 组不存在时忽略，更新组配置时保留说明
+        let mut store = storage.store();
+        store
+            .set_group_description(&"notes".into(), Some("<p>notes</p>"))
+            .set_group_description(&"missing".into(), Some("<p>missing</p>"))
+            .upsert_group(&group("notes", true));
+        store.commit().await.unwrap();
+        assert_eq!(description("notes").await.as_deref(), Some("<p>notes</p>"));
+        assert!(
+            storage
+                .groups()
+                .await
+                .unwrap()
+                .iter()
+                .all(|g| g.id != "missing")
+        );
+
+        // 随文章返回的组不包含说明
+        let list = storage
+            .article_list(
+                1,
+                10,
+                Some("notes"),
+                None,
+                None,
+                vec![],
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+            .await
+            .unwrap();
+        assert!(list.iter().all(|a| a.group.description_html.is_none()));
+
+        let mut store = storage.store();
+        store.set_group_description(&"notes".into(), None);
+        store.commit().await.unwrap();
+        assert_eq!(description("notes").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_default_tags() {
+        let storage = MemoryStorage::new();
+        let k8s = |tags: &str| {
+            content::Group::new(
+                "k8s/.group.yaml",
+                format!("public: true\ndefault_tags: [{tags}]\n"),
+            )
+            .unwrap()
+        };
+        let tags = |slug: &'static str| {
+            let storage = storage.clone();
+            async move { storage.get_one(slug).await.unwrap().unwrap().tags }
+        };
+
+        // 组配置在文章之后写入时同样合并
+        let mut store = storage.store();
+        store
+            .upsert_article(&article("k8s", "pods", 1, &["ops"]))
+            .upsert_group(&k8s("kubernetes, ops"));
+        store.commit().await.unwrap();
+        assert_eq!(tags("pods").await, ["ops", "kubernetes"]);
+
+        let mut store = storage.store();
+        store.upsert_article(&article("k8s", "helm", 2, &["helm"]));
+        store.commit().await.unwrap();
+        assert_eq!(tags("helm").await, ["helm", "kubernetes", "ops"]);
+        assert_eq!(storage.tags().await.unwrap(), ["helm", "kubernetes", "ops"]);
+
+        // 移除默认标签时保留 front matter 中声明的标签
+        let mut store = storage.store();
+        store.upsert_group(&k8s("cloud"));
+        store.commit().await.unwrap();
+        assert_eq!(tags("pods").await, ["ops", "cloud"]);
+        assert_eq!(tags("helm").await, ["helm", "cloud"]);
+        let list = storage
+            .article_list(
+                1,
+                10,
+                None,
+                None,
+                None,
+                vec!["ops"],
+                None,
+                DateRange::default(),
+                ListColumns::ALL,
+                ListSort::Updated,
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].slug, "pods");
+    }
+
+    #[tokio::test]
+    async fn test_tag_index() {
+        let storage = seeded().await;
+        let count = |tag: &str, article_count, d| TagCount {
+            tag: tag.to_string(),
+            article_count,
+            last_used: day(d),
+        };
+        let scratch = |storage: &MemoryStorage| {
+            let mut t = storage.read().clone();
+            t.refresh_tag_index(None);
+            t.tag_index.into_values().collect::<Vec<_>>()
+        };
+
+        // 私有组中的标签不计入
+        assert_eq!(
+            storage.tags_with_counts().await.unwrap(),
+            [count("go", 2, 3), count("rust", 2, 3)]
+        );
+
+        // 删除和修改文章使计数减少
+        let mut store = storage.store();
+        store
+            .remove_article(at("notes", "a"))
+            .upsert_article(&article("notes", "b", 5, &["zig"]));
+        store.commit().await.unwrap();
+        assert_eq!(
+            storage.tags_with_counts().await.unwrap(),
+            [count("go", 1, 3), count("rust", 1, 3), count("zig", 1, 5)]
+        );
+
+        // 组公开状态变化时重新计算组内文章的标签，计数为 0 的标签被删除
+        let mut store = storage.store();
+        store.upsert_group(&group("drafts", true));
+        store.commit().await.unwrap();
+        let mut store = storage.store();
+        store.upsert_group(&group("notes", false));
+        store.commit().await.unwrap();
+        assert_eq!(
+            storage.tags_with_counts().await.unwrap(),
+            [count("secret", 1, 4)]
+        );
+        assert_eq!(storage.tags().await.unwrap(), ["secret"]);
+
+        // 回滚的写入不计入
+        let mut store = storage.store();
+        let savepoint = store.savepoint();
+        store.upsert_article(&article("drafts", "e", 6, &["rolled-back"]));
+        store.rollback_to(savepoint);
+        store.commit().await.unwrap();
+        assert_eq!(storage.tags().await.unwrap(), ["secret"]);
+
+        assert_eq!(storage.tags_with_counts().await.unwrap(), scratch(&storage));
+    }
+
+    #[tokio::test]
+    async fn test_group_stats() {
+        let storage = seeded().await;
+        let sizes = |storage: &MemoryStorage| {
+            storage
+                .read()
+                .group_stats
+                .iter()
+                .map(|(id, s)| (id.to_string(), s.content_bytes, s.article_count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sizes(&storage),
+            [("drafts".into(), 100, 1), ("notes".into(), 300, 3)]
+        );
+
+        // 没有变化的组保留原有的更新时间
+        let before = storage.group_stats().await.unwrap();
+        let mut store = storage.store();
+        store.remove_article(at("notes", "a"));
+        store.commit().await.unwrap();
+        let after = storage.group_stats().await.unwrap();
+        assert_eq!(before[0], after[0]);
+        assert_eq!(sizes(&storage)[1], ("notes".into(), 200, 2));
+
+        // 恰好等于配额时不算超出
+        let quota = |bytes: u64| {
+            content::Group::new(
+                "notes/.group.yaml",
+                format!("name: notes\npublic: true\nquota_bytes: {bytes}\n"),
+            )
+            .unwrap()
+        };
+        let mut store = storage.store();
+        store.upsert_group(&quota(200)).enforce_quotas();
+        store.commit().await.unwrap();
+        assert!(!storage.group_stats().await.unwrap()[1].over_quota());
+
+        // 超出配额时整体回滚
+        let mut store = storage.store();
+        store
+            .upsert_article(&article("notes", "e", 5, &[]))
+            .enforce_quotas();
+        let err = store.commit().await.unwrap_err();
+        assert!(
+            matches!(&err, error::Error::QuotaExceeded(item) if item.used_bytes == 300 && item.quota_bytes == 200),
+            "{err}"
+        );
+        assert_eq!(sizes(&storage)[1], ("notes".into(), 200, 2));
+
+        // 不检查配额时照常写入，重建后从头计算
+        let mut store = storage.store();
+        store.upsert_article(&article("notes", "e", 5, &[]));
+        store.commit().await.unwrap();
+        assert!(storage.group_stats().await.unwrap()[1].over_quota());
+        let mut store = storage.store();
+        store
+            .clean()
+            .upsert_group(&quota(200))
+            .upsert_article(&article("notes", "a", 1, &[]));
+        store.commit().await.unwrap();
+        assert_eq!(sizes(&storage), [("notes".into(), 100, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_replaces_data_and_keeps_links() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        store
+            .replace_links("a", &["https://a.example".to_string()])
+            .record_link_status("https://a.example", Some(404), None);
+        store.commit().await.unwrap();
+
+        let mut store = storage.store();
+        store
+            .upsert_group(&group("ignored", true))
+            .clean()
+            .upsert_group(&group("notes", true))
+            .upsert_article(&article("notes", "a", 1, &[]));
+
+        // 提交前查询仍返回旧数据
+        assert_eq!(storage.public_slugs().await.unwrap().len(), 3);
+        store.commit().await.unwrap();
+
+        assert_eq!(storage.public_slugs().await.unwrap(), ["a"]);
+        assert_eq!(storage.groups().await.unwrap().len(), 1);
+
+        let broken = storage.broken_links().await.unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].status, Some(404));
+    }
+
+    #[tokio::test]
+    async fn test_links_and_includes() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        store
+            .replace_links("a", &["https://x".to_string(), "https://y".to_string()])
+            .replace_links("b", &["https://y".to_string()])
+            .record_link_status("https://y", Some(200), None)
+            .replace_includes("notes/a.md", &["snippets/s.md".to_string()])
+            .replace_includes("notes/b.md", &["snippets/s.md".to_string()]);
+        store.commit().await.unwrap();
+
+        assert_eq!(
+            storage.links_due(10).await.unwrap(),
+            ["https://x", "https://y"]
+        );
+        assert_eq!(
+            storage
+                .store()
+                .include_dependents(&["snippets/s.md".to_string()])
+                .await
+                .unwrap(),
+            ["notes/a.md", "notes/b.md"]
+        );
+
+        let mut store = storage.store();
+        store
+            .remove_article(at("notes", "a"))
+            .replace_includes("notes/a.md", &[]);
+        store.commit().await.unwrap();
+
+        assert_eq!(storage.links_due(10).await.unwrap(), ["https://y"]);
+        assert_eq!(
+            storage
+                .store()
+                .include_dependents(&["snippets/s.md".to_string()])
+                .await
+                .unwrap(),
+            ["notes/b.md"]
+        );
+
+        // 不属于该组的删除不生效
+        let mut store = storage.store();
+        store.remove_article(at("drafts", "b"));
+        store.commit().await.unwrap();
+        assert!(storage.get_one("b").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prune_groups() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        store.upsert_group(&group("empty", true));
+        store.commit().await.unwrap();
+
+        let removed = storage
+            .store()
+            .prune_groups(&["notes".into()])
+            .await
+            .unwrap();
+        assert_eq!(removed, ["empty"]);
+        assert_eq!(storage.store().group_settings().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_savepoint_and_sync_failures() {
+        let storage = seeded().await;
+
+        let mut store = storage.store();
+        let savepoint = store.savepoint();
+        store.upsert_article(&article("notes", "e", 5, &[]));
+        store
+            .rollback_to(savepoint)
+            .record_sync_failure("notes/c.md", "bad front matter", "c1")
+            .record_sync_failure("notes/d.md", "missing include", "c1")
+            .retarget_sync_failures("c2");
+        store.commit().await.unwrap();
+
+        assert!(storage.get_one("e").await.unwrap().is_none());
+        let failures = storage.store().sync_failures().await.unwrap();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].path, "notes/c.md");
+        assert_eq!(failures[0].reason, "bad front matter");
+        assert!(failures.iter().all(|f| f.commit_id == "c2"));
+
+        let mut store = storage.store();
+        store.clear_sync_failure("notes/c.md");
+        store.commit().await.unwrap();
+        let failures = storage.store().sync_failures().await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, "notes/d.md");
+
+        // 重建时清空失败记录
+        let mut store = storage.store();
+        store.clean();
+        store.commit().await.unwrap();
+        assert!(storage.store().sync_failures().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_content_blobs() {
+        let storage = seeded().await;
+        let blob = ContentBlob {
+            oid: "b1".into(),
+            renderer: Some("v1".into()),
+            raw: "# shared".into(),
+            content: "<h1>shared</h1>".into(),
+            summary: "<p>shared</p>".into(),
+            simhash: None,
+        };
+
+        let mut store = storage.store();
+        store
+            .upsert_content_blob(&blob)
+            .upsert_blob_article(&article("notes", "e", 5, &[]), "b1")
+            .upsert_blob_article(&article("notes", "f", 6, &[]), "b1");
+        store.commit().await.unwrap();
+
+        let detail = storage.get_one("f").await.unwrap().unwrap();
+        assert_eq!(detail.content, "<h1>shared</h1>");
+        let store = storage.store();
+        assert!(store.content_blob("b1", "v1").await.unwrap().is_some());
+        // 渲染器版本不同时不复用
+        assert!(store.content_blob("b1", "v2").await.unwrap().is_none());
+
+        // 仍有文章引用时保留
+        let mut store = storage.store();
+        store.remove_article(at("notes", "e")).prune_content_blobs();
+        store.commit().await.unwrap();
+        assert_eq!(
+            storage.get_one("f").await.unwrap().unwrap().content,
+            "<h1>shared</h1>"
+        );
+
+        let mut store = storage.store();
+        store.remove_article(at("notes", "f")).prune_content_blobs();
+        store.commit().await.unwrap();
+        let store = storage.store();
+        assert!(store.content_blob("b1", "v1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_article_public_override() {
+        let with_public = |group: &str, slug: &str, d: u32, public: Option<bool>| {
+            let mut article = article(group, slug, d, &[slug]);
+            article.frontmatter.public = public;
+            article
+        };
+
+        let storage = MemoryStorage::new();
+        let mut store = storage.store();
+        store
+            .upsert_group(&group("notes", true))
+            .upsert_group(&group("drafts", false))
+            // 组公开，文章未设置或公开
+            .upsert_article(&with_public("notes", "a", 1, None))
+            .upsert_article(&with_public("notes", "b", 2, Some(true)))
+            // 组公开，文章不公开
+            .upsert_article(&with_public("notes", "c", 3, Some(false)))
+            .upsert_article(&with_public("notes", "d", 4, None))
+            // 组不公开，文章公开
+            .upsert_article(&with_public("drafts", "e", 5, Some(true)))
+            // 组不公开，文章未设置或不公开
+            .upsert_article(&with_public("drafts", "f", 6, None))
+            .upsert_article(&with_public("drafts", "g", 7, Some(false)))
+            // 没有组的文章即使公开也不可见
+            .upsert_article(&with_public("loose", "h", 8, Some(true)));
+        store.commit().await.unwrap();
+
+        assert_eq!(storage.public_slugs().await.unwrap(), ["a", "b", "d", "e"]);
+        assert_eq!(storage.tags().await.unwrap(), ["a", "b", "d", "e"]);
+        for slug in ["c", "f", "g", "h"] {
+            assert!(storage.get_one(slug).await.unwrap().is_none(), "{slug}");
+            assert!(storage.article_updated_at(slug).await.unwrap().is_none());
+        }
+        // 组列表仍按组的设置
+        assert_eq!(storage.groups().await.unwrap().len(), 1);
+
+        // 上一篇和下一篇跳过不公开的文章
+        let d = storage.get_one("d").await.unwrap().unwrap();
+        assert_eq!(d.prev.unwrap().slug, "b");
+        assert!(d.next.is_none());
+        let e = storage.get_one("e").await.unwrap().unwrap();
+        assert!(e.prev.is_none() && e.next.is_none());
+
+        // 修改公开设置时更新时间取当前时间，内容不变的重新写入保持不变
+        let mut store = storage.store();
+        store
+            .upsert_article(&with_public("notes", "a", 1, None))
+            .upsert_article(&with_public("notes", "b", 2, Some(false)))
+            .upsert_article(&with_public("notes", "c", 3, None));
+        store.commit().await.unwrap();
+
+        assert_eq!(storage.article_updated_at("a").await.unwrap(), Some(day(1)));
+        assert!(storage.get_one("b").await.unwrap().is_none());
+        let c = storage.article_updated_at("c").await.unwrap().unwrap();
+        assert!(c > day(3));
+    }
+}
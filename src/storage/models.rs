@@ -1,7 +1,9 @@
-use chrono::{DateTime, Local};
-use serde::Deserialize;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
 use sqlx::types::Json;
 
+use crate::content::{GroupLicense, GroupPath, SocialMeta};
+
 /// 文章详情
 ///
 /// 包含文章完整内容、元信息以及可选分类和作者信息。
@@ -9,6 +11,8 @@ use sqlx::types::Json;
 pub struct ArticleDetail {
     /// 文章唯一标识
     pub slug: String,
+    /// 不随 slug 变化的短 id，同步写入前为 `None`
+    pub short_id: Option<String>,
     /// 标题
     pub title: String,
     /// 摘要
@@ -19,10 +23,50 @@ pub struct ArticleDetail {
     pub content: String,
     /// 文章的分组
     pub group: Json<Group>,
+    /// 评论数
+    pub comment_count: i64,
+    /// 加密存储时的行 nonce，明文存储时为 `None`
+    pub nonce: Option<Vec<u8>>,
     /// 更新时间
-    pub updated_at: DateTime<Local>,
+    pub updated_at: DateTime<FixedOffset>,
     /// 创建时间
-    pub created_at: DateTime<Local>,
+    pub created_at: DateTime<FixedOffset>,
+    /// 同组中的上一篇公开文章
+    pub prev: Option<Json<Neighbor>>,
+    /// 同组中的下一篇公开文章
+    pub next: Option<Json<Neighbor>>,
+    /// 文章文件相对仓库根目录的路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+    /// 最近一次写入文章的同步的目标提交
+    pub source_commit: Option<String>,
+    /// 转载文章的原文地址
+    pub canonical_url: Option<String>,
+    /// 分享卡片信息，加密文章和迁移前写入的文章为 `None`
+    pub social: Option<Json<SocialMeta>>,
+    /// 正文链接的附件，按出现顺序排列
+    pub attachments: Json<Vec<Attachment>>,
+}
+
+/// 相邻文章
+///
+/// 同组公开文章按 `created_at`、`slug` 升序排列时的前后位置。
+#[derive(Debug, Deserialize)]
+pub struct Neighbor {
+    pub slug: String,
+    pub title: String,
+}
+
+/// 文章附件
+///
+/// 同步时从正文中指向附件扩展名文件的链接提取，图片不计入。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// http/https 链接原样保存，仓库内的相对链接解析为相对仓库根目录的路径
+    pub url_or_path: String,
+    /// 小写的扩展名，如 `pdf`
+    pub kind: String,
+    /// 链接文字，自动链接为链接本身
+    pub link_text: String,
 }
 
 /// 文章列表项
@@ -31,19 +75,446 @@ pub struct ArticleDetail {
 #[derive(Debug, sqlx::FromRow)]
 pub struct ArticleSummary {
     pub slug: String,
+    pub short_id: Option<String>,
     pub title: String,
     pub summary: String,
+    /// 去掉标签的纯文本摘要，未截断
+    pub summary_plain: String,
     pub tags: Vec<String>,
     pub group: Json<Group>,
-    pub updated_at: DateTime<Local>,
-    pub created_at: DateTime<Local>,
+    pub comment_count: i64,
+    pub nonce: Option<Vec<u8>>,
+    pub updated_at: DateTime<FixedOffset>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+/// 公开文章的位置和更新时间，用于静态站点生成全部文章路径
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ArticleSlug {
+    /// 所在组对外使用的 id
+    pub group: String,
+    pub slug: String,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// 标签索引中的一行
+///
+/// 只统计公开文章，见 `sql/11-TAG_INDEX.sql`。
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct TagCount {
+    pub tag: String,
+    /// 带有该标签的公开文章数
+    pub article_count: i64,
+    /// 带有该标签的公开文章中最近的更新时间
+    pub last_used: DateTime<FixedOffset>,
+}
+
+/// 公开内容的数量和最近一次同步的时间，用于 API 索引
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SiteCounts {
+    /// 公开文章数，不包括被保留规则下线的文章
+    pub articles: i64,
+    /// 公开的组数，包括隐藏的组
+    pub groups: i64,
+    /// 标签索引中的标签数
+    pub tags: i64,
+    /// 最近一次同步后记录内容指纹的时间，尚未同步过时为 `None`
+    pub synced_at: Option<DateTime<FixedOffset>>,
+}
+
+/// 文章列表的时间范围
+///
+/// 四个边界都包含端点：`*_after` 为 `>=`，`*_before` 为 `<=`，为 `None` 时不限制。
+/// 下界晚于上界时范围为空，查询返回空列表。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub created_after: Option<DateTime<FixedOffset>>,
+    pub created_before: Option<DateTime<FixedOffset>>,
+    pub updated_after: Option<DateTime<FixedOffset>>,
+    pub updated_before: Option<DateTime<FixedOffset>>,
+}
+
+impl DateRange {
+    /// 创建时间和更新时间是否都在范围内
+    pub fn contains(
+        &self,
+        created_at: DateTime<FixedOffset>,
+        updated_at: DateTime<FixedOffset>,
+    ) -> bool {
+        self.created_after.is_none_or(|t| created_at >= t)
+            && self.created_before.is_none_or(|t| created_at <= t)
+            && self.updated_after.is_none_or(|t| updated_at >= t)
+            && self.updated_before.is_none_or(|t| updated_at <= t)
+    }
+}
+
+/// 文章列表读取的较大的列
+///
+/// 不读取的列在 [`ArticleSummary`] 中为空值，用于客户端只需要部分字段时减少读取的数据。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListColumns {
+    /// 是否读取 `summary` 和 `summary_plain`
+    pub summary: bool,
+    /// 是否读取 `tags`
+    pub tags: bool,
+}
+
+impl ListColumns {
+    /// 读取所有列
+    pub const ALL: Self = Self {
+        summary: true,
+        tags: true,
+    };
+}
+
+impl Default for ListColumns {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// 文章列表的排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListSort {
+    /// 按更新时间从新到旧
+    #[default]
+    Updated,
+    /// 按标题的字母顺序，见 [`title_sort_key`](crate::content::title_sort_key)，
+    /// 没有排序键的文章排在最后
+    Title,
+}
+
+/// 标题索引中的一篇文章
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct TitleEntry {
+    pub slug: String,
+    pub title: String,
+    /// 所在组对外使用的 id
+    pub group: String,
+    /// 标题的排序键，迁移前写入且未重新同步的文章为 [`None`]
+    pub title_sort_key: Option<String>,
 }
 
 /// 组信息
 #[derive(Debug, sqlx::FromRow, Deserialize)]
 pub struct Group {
+    /// 组目录路径，只用于内部关联
     pub id: String,
+    /// 对外使用的组 id，见 [`content::Group::public_id`](crate::content::Group::public_id)
+    pub slug: String,
     pub name: String,
     pub public: bool,
     pub kind: Json<serde_json::Value>,
+    #[serde(default)]
+    pub encrypted: bool,
+    /// 默认作者 id
+    #[serde(default)]
+    pub author_id: Option<String>,
+    /// 默认作者显示名
+    #[serde(default)]
+    pub author_name: Option<String>,
+    /// 组内文章是否已归档
+    #[serde(default)]
+    pub archived: bool,
+    /// 组说明渲染后的 HTML，随文章查询时不返回
+    #[serde(default)]
+    pub description_html: Option<String>,
+    /// 所属分类 id
+    #[serde(default)]
+    pub category_id: Option<String>,
+    /// 所属分类显示名
+    #[serde(default)]
+    pub category_name: Option<String>,
+    /// 组列表中的排序，见 [`content::Group::nav_order`](crate::content::Group::nav_order)
+    #[serde(default)]
+    pub nav_order: Option<i32>,
+    /// 是否从组列表中隐藏
+    #[serde(default)]
+    pub hidden: bool,
+    /// 组内文章的许可协议，未设置时为 `None`，不包含部署配置的默认协议
+    #[serde(default)]
+    pub license: Option<Json<GroupLicense>>,
+}
+
+/// 作者及其公开文章统计
+#[derive(Debug, sqlx::FromRow)]
+pub struct AuthorSummary {
+    pub id: String,
+    /// 最新文章所在组中的显示名
+    pub name: String,
+    /// 公开文章数
+    pub article_count: i64,
+    /// 最新创建的公开文章
+    pub latest: Json<Neighbor>,
+}
+
+/// 分类中的公开组及其公开文章数
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct CategoryGroup {
+    /// 对外使用的组 id
+    pub slug: String,
+    pub name: String,
+    /// 该组配置中的分类显示名
+    pub category_name: String,
+    /// 公开文章数，包括已归档的文章
+    pub article_count: i64,
+}
+
+/// 失效的外部链接
+///
+/// 最近一次检查返回 4xx/5xx 或请求失败的链接。
+#[derive(Debug, sqlx::FromRow)]
+pub struct BrokenLink {
+    /// 引用该链接的文章
+    pub slug: String,
+    /// 文章标题
+    pub title: String,
+    pub url: String,
+    /// 响应状态码，请求失败时为 `None`
+    pub status: Option<i32>,
+    /// 请求失败原因
+    pub error: Option<String>,
+    /// 最近一次检查时间
+    pub last_checked: Option<DateTime<FixedOffset>>,
+}
+
+/// 同步时处理失败的文件
+#[derive(Debug, sqlx::FromRow)]
+pub struct SyncFailure {
+    /// 文件路径
+    pub path: String,
+    /// 失败原因
+    pub reason: String,
+    /// 重试时读取文件的提交
+    pub commit_id: String,
+    /// 最近一次失败时间
+    pub failed_at: DateTime<FixedOffset>,
+}
+
+/// 按内容 oid 存储的渲染结果
+///
+/// 内容相同的文章共享同一行，`oid` 为展开 include 后内容的 git blob oid。
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, Serialize, Deserialize)]
+pub struct ContentBlob {
+    pub oid: String,
+    /// 渲染器版本，未知时为 `None`，不会被复用
+    pub renderer: Option<String>,
+    /// 展开 include 后的 Markdown 原文
+    pub raw: String,
+    /// 渲染后的正文
+    pub content: String,
+    /// 渲染后的摘要
+    pub summary: String,
+    /// 原文的 [`simhash`](crate::content::simhash)，内容过短或迁移前写入时为 `None`
+    #[serde(default)]
+    pub simhash: Option<i64>,
+}
+
+/// 参与内容指纹计算的组，见 [`content_fingerprint`](crate::git_sync::content_fingerprint)
+///
+/// 只包含来自组配置的列。
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, Serialize)]
+pub struct GroupFingerprint {
+    /// 组目录路径
+    pub id: GroupPath,
+    pub slug: String,
+    pub name: String,
+    pub public: bool,
+    pub encrypted: bool,
+    pub slug_strategy: String,
+    pub author_id: Option<String>,
+    pub author_name: Option<String>,
+    pub archived: bool,
+    pub default_tags: Vec<String>,
+    pub kind: serde_json::Value,
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub nav_order: Option<i32>,
+    pub hidden: bool,
+    pub retain_max_articles: Option<i32>,
+    pub retain_max_age_days: Option<i32>,
+    pub license: Option<serde_json::Value>,
+}
+
+/// 参与内容指纹计算的公开文章
+///
+/// 不包含评论数、创建时间等不来自仓库内容的列。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArticleFingerprint {
+    /// 所在组目录路径
+    pub group_id: GroupPath,
+    pub slug: String,
+    /// 展开 include 后内容的 git blob oid，加密文章为 `None`
+    pub content_oid: Option<String>,
+    /// 更新时间，Unix 毫秒时间戳
+    pub updated_at: i64,
+}
+
+/// 最近一次同步后记录的内容指纹
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SyncState {
+    /// 指纹对应的提交
+    pub commit_id: String,
+    pub content_fingerprint: String,
+    /// 计算时间
+    pub computed_at: DateTime<FixedOffset>,
+}
+
+/// 由其他渲染器生成的文章
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StaleRender {
+    pub slug: String,
+    pub title: String,
+    /// 写入时的渲染器标识，迁移前写入的文章为 `None`
+    pub renderer: Option<String>,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+}
+
+/// 所在组目录没有组配置的文章
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OrphanArticle {
+    pub slug: String,
+    pub title: String,
+    /// 文章记录的组目录路径，没有对应的组
+    pub group_id: GroupPath,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+}
+
+/// 组的大小统计，见 `sql/21-GROUP_STATS.sql`
+///
+/// 包括组内未公开的文章。
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct GroupStats {
+    /// 组目录路径
+    pub group_id: GroupPath,
+    /// 对外使用的组 id
+    pub slug: String,
+    /// 组内文章原文的总字节数
+    pub content_bytes: i64,
+    /// 组内资源文件的总字节数，目前没有资源文件的处理流程，始终为 0
+    pub asset_bytes: i64,
+    pub article_count: i64,
+    /// 组配置中的 `quota_bytes`，未设置时为 `None`
+    pub quota_bytes: Option<i64>,
+    /// 统计最近一次变化的时间
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl GroupStats {
+    /// 计入配额的总字节数
+    pub fn used_bytes(&self) -> i64 {
+        self.content_bytes + self.asset_bytes
+    }
+
+    /// 是否超出配额，恰好等于配额时不算超出
+    pub fn over_quota(&self) -> bool {
+        self.quota_bytes.is_some_and(|q| self.used_bytes() > q)
+    }
+}
+
+/// 每周摘要的一次执行记录，见 `sql/22-DIGEST_RUNS.sql`
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct DigestRun {
+    /// 周期结束时间，即计划发送的时间，不包含
+    pub period_end: DateTime<FixedOffset>,
+    /// 周期开始时间，包含
+    pub period_start: DateTime<FixedOffset>,
+    /// 周期内新发布的公开文章数
+    pub article_count: i64,
+    /// `sent`、`skipped` 或 `failed`，见 [`DigestStatus`](crate::digest::DigestStatus)
+    pub status: String,
+    /// 发送失败的原因
+    pub error: Option<String>,
+    /// 实际执行时间
+    pub ran_at: DateTime<FixedOffset>,
+}
+
+/// 因所在组的保留规则下线的文章
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PrunedArticle {
+    pub slug: String,
+    pub title: String,
+    pub group_id: GroupPath,
+    pub created_at: DateTime<FixedOffset>,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+}
+
+/// 渲染报告的排序字段，都按从大到小排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderSort {
+    /// 渲染耗时
+    #[default]
+    RenderMs,
+    /// 渲染后正文的字节数
+    ContentBytes,
+}
+
+/// 文章最近一次渲染的耗时和大小
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RenderStat {
+    pub slug: String,
+    pub title: String,
+    pub group_id: GroupPath,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+    /// 渲染正文和摘要的耗时，毫秒
+    pub render_ms: i64,
+    /// 渲染后正文的字节数
+    pub content_bytes: i64,
+    /// 最近一次渲染的时间
+    pub rendered_at: DateTime<FixedOffset>,
+}
+
+/// 应用保留规则后下线或重新上线的文章
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct RetentionChange {
+    pub group_id: GroupPath,
+    pub slug: String,
+    /// 为 `true` 时下线，否则重新上线
+    pub pruned: bool,
+}
+
+/// 已分配的文章短 id，同步时用于在文章改名或重建后沿用原来的短 id
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ArticleShortId {
+    pub slug: String,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+    pub short_id: String,
+}
+
+/// 公开文章原文的 simhash，用于发现不同组之间重复的内容
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ArticleSimhash {
+    pub slug: String,
+    pub title: String,
+    pub group_id: GroupPath,
+    /// 源文件路径，迁移前写入的文章为 `None`
+    pub source_path: Option<String>,
+    /// 见 [`ContentBlob::simhash`]
+    pub simhash: i64,
+}
+
+/// 待发送 webmention 的文章
+#[derive(Debug, sqlx::FromRow)]
+pub struct WebmentionSource {
+    pub slug: String,
+    /// 文章当前的外部链接
+    pub links: Vec<String>,
+    /// 已记录发送结果的目标
+    pub sent: Vec<String>,
+}
+
+/// 重定向目标
+///
+/// 表示旧位置当前应跳转到的文章位置。
+#[derive(Debug, sqlx::FromRow)]
+pub struct Redirect {
+    /// 目标文章所在组对外使用的 slug
+    pub group_id: String,
+    /// 目标文章 slug
+    pub slug: String,
 }
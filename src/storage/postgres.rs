@@ -1,16 +1,13 @@
-use std::{env, str, time::Duration};
+use std::{str, time::Duration};
 
-use sqlx::postgres::PgPoolOptions;
+use sqlx::{
+    Postgres, Transaction,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
 
 /// 数据库连接池类型
 pub type DBPool = sqlx::PgPool;
 
-/// 从环境变量 `DATABASE_URL` 初始化数据库连接池
-pub async fn init_db_from_env() -> DBPool {
-    let conn_url = env::var("DATABASE_URL").expect("`DATABASE_URL` env not set");
-    new_db_poll(&conn_url).await.unwrap()
-}
-
 /// 根据连接 URL 创建新的数据库连接池
 ///
 /// 连接池配置：
@@ -21,7 +18,18 @@ pub async fn init_db_from_env() -> DBPool {
 /// - 获取连接超时 2 秒
 /// - 获取前测试连接
 /// - 最小连接数 2
-pub async fn new_db_poll(conn_url: &str) -> Result<DBPool, sqlx::Error> {
+///
+/// `statement_timeout` 不为 `None` 时作为连接参数设置，超时的语句由服务端取消并释放连接；
+/// 同步写入和迁移的事务通过 [`begin_write`] 解除该限制。
+pub async fn new_db_poll(
+    conn_url: &str,
+    statement_timeout: Option<Duration>,
+) -> Result<DBPool, sqlx::Error> {
+    let mut options = conn_url.parse::<PgConnectOptions>()?;
+    if let Some(timeout) = statement_timeout {
+        options = options.options([("statement_timeout", timeout.as_millis().to_string())]);
+    }
+
     PgPoolOptions::new()
         .idle_timeout(Duration::from_secs(60))
         .max_lifetime(Duration::from_secs(1500))
@@ -29,22 +37,454 @@ pub async fn new_db_poll(conn_url: &str) -> Result<DBPool, sqlx::Error> {
         .acquire_timeout(Duration::from_secs(2))
         .test_before_acquire(true)
         .min_connections(2)
-        .connect(conn_url)
+        .connect_with(options)
         .await
 }
 
+/// 开始一个不受 `statement_timeout` 限制的事务，用于同步写入和迁移
+pub(super) async fn begin_write(
+    db: &DBPool,
+) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query("SET LOCAL statement_timeout = 0")
+        .execute(&mut *tx)
+        .await?;
+    Ok(tx)
+}
+
+/// 随程序打包的迁移文件，按文件名顺序执行
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "01-CREATE_TABLE.sql",
+        sql: include_str!("../../sql/01-CREATE_TABLE.sql"),
+    },
+    Migration {
+        name: "02-SHADOW_SCHEMA.sql",
+        sql: include_str!("../../sql/02-SHADOW_SCHEMA.sql"),
+    },
+    Migration {
+        name: "03-ARTICLE_INDEXES.sql",
+        sql: include_str!("../../sql/03-ARTICLE_INDEXES.sql"),
+    },
+    Migration {
+        name: "04-GROUP_SLUG.sql",
+        sql: include_str!("../../sql/04-GROUP_SLUG.sql"),
+    },
+    Migration {
+        name: "05-ARTICLE_SOURCE.sql",
+        sql: include_str!("../../sql/05-ARTICLE_SOURCE.sql"),
+    },
+    Migration {
+        name: "06-DEFAULT_TAGS.sql",
+        sql: include_str!("../../sql/06-DEFAULT_TAGS.sql"),
+    },
+    Migration {
+        name: "07-SYNC_STATE.sql",
+        sql: include_str!("../../sql/07-SYNC_STATE.sql"),
+    },
+    Migration {
+        name: "08-WEBMENTIONS.sql",
+        sql: include_str!("../../sql/08-WEBMENTIONS.sql"),
+    },
+    Migration {
+        name: "09-ARTICLE_RENDERER.sql",
+        sql: include_str!("../../sql/09-ARTICLE_RENDERER.sql"),
+    },
+    Migration {
+        name: "10-ARTICLE_CANONICAL.sql",
+        sql: include_str!("../../sql/10-ARTICLE_CANONICAL.sql"),
+    },
+    Migration {
+        name: "11-TAG_INDEX.sql",
+        sql: include_str!("../../sql/11-TAG_INDEX.sql"),
+    },
+    Migration {
+        name: "12-GROUP_DESCRIPTION.sql",
+        sql: include_str!("../../sql/12-GROUP_DESCRIPTION.sql"),
+    },
+    Migration {
+        name: "13-GROUP_CATEGORY.sql",
+        sql: include_str!("../../sql/13-GROUP_CATEGORY.sql"),
+    },
+    Migration {
+        name: "14-ARTICLE_PUBLIC.sql",
+        sql: include_str!("../../sql/14-ARTICLE_PUBLIC.sql"),
+    },
+    Migration {
+        name: "15-ARTICLE_SHORT_ID.sql",
+        sql: include_str!("../../sql/15-ARTICLE_SHORT_ID.sql"),
+    },
+    Migration {
+        name: "16-GROUP_NAV.sql",
+        sql: include_str!("../../sql/16-GROUP_NAV.sql"),
+    },
+    Migration {
+        name: "17-GROUP_RETAIN.sql",
+        sql: include_str!("../../sql/17-GROUP_RETAIN.sql"),
+    },
+    Migration {
+        name: "18-RENDER_STATS.sql",
+        sql: include_str!("../../sql/18-RENDER_STATS.sql"),
+    },
+    Migration {
+        name: "19-GROUP_LICENSE.sql",
+        sql: include_str!("../../sql/19-GROUP_LICENSE.sql"),
+    },
+    Migration {
+        name: "19b-CONTENT_BLOBS.sql",
+        sql: include_str!("../../sql/19b-CONTENT_BLOBS.sql"),
+    },
+    Migration {
+        name: "20-CONTENT_SIMHASH.sql",
+        sql: include_str!("../../sql/20-CONTENT_SIMHASH.sql"),
+    },
+    Migration {
+        name: "21-GROUP_STATS.sql",
+        sql: include_str!("../../sql/21-GROUP_STATS.sql"),
+    },
+    Migration {
+        name: "22-DIGEST_RUNS.sql",
+        sql: include_str!("../../sql/22-DIGEST_RUNS.sql"),
+    },
+    Migration {
+        name: "23-GROUP_SCHEMA.sql",
+        sql: include_str!("../../sql/23-GROUP_SCHEMA.sql"),
+    },
+    Migration {
+        name: "24-ARTICLE_SOCIAL.sql",
+        sql: include_str!("../../sql/24-ARTICLE_SOCIAL.sql"),
+    },
+    Migration {
+        name: "25-ARTICLE_ATTACHMENTS.sql",
+        sql: include_str!("../../sql/25-ARTICLE_ATTACHMENTS.sql"),
+    },
+    Migration {
+        name: "26-TITLE_SORT_KEY.sql",
+        sql: include_str!("../../sql/26-TITLE_SORT_KEY.sql"),
+    },
+    Migration {
+        name: "27-COMMENT_COUNT.sql",
+        sql: include_str!("../../sql/27-COMMENT_COUNT.sql"),
+    },
+    Migration {
+        name: "28-REDIRECTS.sql",
+        sql: include_str!("../../sql/28-REDIRECTS.sql"),
+    },
+    Migration {
+        name: "29-ARTICLE_ENCRYPTION.sql",
+        sql: include_str!("../../sql/29-ARTICLE_ENCRYPTION.sql"),
+    },
+    Migration {
+        name: "30-EXTERNAL_LINKS.sql",
+        sql: include_str!("../../sql/30-EXTERNAL_LINKS.sql"),
+    },
+    Migration {
+        name: "31-GROUP_SLUG_STRATEGY.sql",
+        sql: include_str!("../../sql/31-GROUP_SLUG_STRATEGY.sql"),
+    },
+    Migration {
+        name: "32-ARTICLE_INCLUDES.sql",
+        sql: include_str!("../../sql/32-ARTICLE_INCLUDES.sql"),
+    },
+    Migration {
+        name: "33-SUMMARY_PLAIN.sql",
+        sql: include_str!("../../sql/33-SUMMARY_PLAIN.sql"),
+    },
+    Migration {
+        name: "34-SYNC_FAILURES.sql",
+        sql: include_str!("../../sql/34-SYNC_FAILURES.sql"),
+    },
+    Migration {
+        name: "35-CONTENT_OID.sql",
+        sql: include_str!("../../sql/35-CONTENT_OID.sql"),
+    },
+    Migration {
+        name: "36-GROUP_AUTHOR.sql",
+        sql: include_str!("../../sql/36-GROUP_AUTHOR.sql"),
+    },
+    Migration {
+        name: "37-GROUP_ARCHIVED.sql",
+        sql: include_str!("../../sql/37-GROUP_ARCHIVED.sql"),
+    },
+    Migration {
+        name: "38-CONTENT_NAMESPACED.sql",
+        sql: include_str!("../../sql/38-CONTENT_NAMESPACED.sql"),
+    },
+];
+
+/// 服务运行所需的表和列，启动时用于检查数据库是否已迁移
+pub(super) const REQUIRED_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "articles",
+        &[
+            "slug",
+            "title",
+            "summary",
+            "summary_plain",
+            "tags",
+            "content",
+            "content_oid",
+            "content_namespaced",
+            "group_id",
+            "created_at",
+            "updated_at",
+            "comment_count",
+            "nonce",
+            "source_path",
+            "source_commit",
+            "own_tags",
+            "renderer",
+            "canonical_url",
+            "public",
+            "short_id",
+            "retention_pruned",
+            "render_ms",
+            "content_bytes",
+            "rendered_at",
+            "source_bytes",
+            "social",
+            "title_sort_key",
+        ],
+    ),
+    (
+        "groups",
+        &[
+            "id",
+            "name",
+            "public",
+            "encrypted",
+            "slug_strategy",
+            "author_id",
+            "author_name",
+            "archived",
+            "kind",
+            "slug",
+            "default_tags",
+            "description_html",
+            "category_id",
+            "category_name",
+            "nav_order",
+            "hidden",
+            "retain_max_articles",
+            "retain_max_age_days",
+            "license",
+            "quota_bytes",
+            "front_matter_schema",
+        ],
+    ),
+    (
+        "redirects",
+        &["from_group", "from_slug", "to_group", "to_slug"],
+    ),
+    ("article_includes", &["article_path", "include_path"]),
+    (
+        "article_attachments",
+        &[
+            "slug",
+            "group_id",
+            "position",
+            "url_or_path",
+            "kind",
+            "link_text",
+        ],
+    ),
+    (
+        "external_links",
+        &[
+            "slug",
+            "url",
+            "first_seen",
+            "status",
+            "error",
+            "last_checked",
+        ],
+    ),
+    (
+        "sync_failures",
+        &["path", "reason", "commit_id", "failed_at"],
+    ),
+    (
+        "content_blobs",
+        &["oid", "renderer", "raw", "content", "summary", "simhash"],
+    ),
+    (
+        "sync_state",
+        &["id", "commit_id", "content_fingerprint", "computed_at"],
+    ),
+    (
+        "webmentions_sent",
+        &["slug", "target", "endpoint", "status", "error", "sent_at"],
+    ),
+    ("tag_index", &["tag", "article_count", "last_used"]),
+    (
+        "group_stats",
+        &[
+            "group_id",
+            "content_bytes",
+            "asset_bytes",
+            "article_count",
+            "updated_at",
+        ],
+    ),
+    (
+        "digest_runs",
+        &[
+            "period_end",
+            "period_start",
+            "article_count",
+            "status",
+            "error",
+            "ran_at",
+        ],
+    ),
+];
+
+/// 重建时使用的影子 schema
+const SHADOW_SCHEMA: &str = "gitnote_shadow";
+
+/// 一个迁移文件
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// 文件名，记录在 `gitnote.schema_migrations` 中
+    pub name: &'static str,
+    /// 以 `;` 分隔的 SQL 语句
+    pub sql: &'static str,
+}
+
+/// 查询数据库中缺失的表和列
+///
+/// 返回 `表.列` 形式的列表，影子 schema 缺失时包含 `gitnote_shadow`，数据库已迁移时为空。
+pub async fn missing_schema(db: &DBPool) -> Result<Vec<String>, sqlx::Error> {
+    let existing: Vec<(String, String)> = sqlx::query_as(
+        r#"
+            SELECT table_name::text, column_name::text
+            FROM information_schema.columns
+            WHERE table_schema = 'gitnote'
+            "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut missing = REQUIRED_COLUMNS
+        .iter()
+        .flat_map(|(table, columns)| columns.iter().map(move |column| (*table, *column)))
+        .filter(|(table, column)| !existing.iter().any(|(t, c)| t == table && c == column))
+        .map(|(table, column)| format!("{table}.{column}"))
+        .collect::<Vec<_>>();
+
+    let shadow: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)")
+            .bind(SHADOW_SCHEMA)
+            .fetch_one(db)
+            .await?;
+    if !shadow {
+        missing.push(SHADOW_SCHEMA.to_string());
+    }
+
+    Ok(missing)
+}
+
+/// 按顺序执行 [`MIGRATIONS`] 中尚未执行的迁移，返回本次执行的文件名
+pub async fn run_migrations(db: &DBPool) -> Result<Vec<&'static str>, sqlx::Error> {
+    let mut applied = vec![];
+    for migration in MIGRATIONS {
+        if apply_migration(db, migration.name, migration.sql).await? {
+            applied.push(migration.name);
+        }
+    }
+    Ok(applied)
+}
+
 /// 执行 SQL 文件中的迁移语句
 ///
-/// 将文件内容按 `;` 分割，每条 SQL 单独执行
-#[allow(unused)]
+/// 以文件名记录到 `gitnote.schema_migrations`，已执行过的文件会被跳过，见 [`apply_migration`]。
 pub async fn migrate(db: &DBPool, file: &str) -> Result<(), sqlx::Error> {
     let content = std::fs::read_to_string(file)?;
-    let sqls: Vec<&str> = content.split(";").collect();
-    for sql in sqls {
-        if sql.is_empty() {
+    let name = std::path::Path::new(file)
+        .file_name()
+        .map_or(file.into(), |name| name.to_string_lossy());
+    apply_migration(db, &name, &content).await?;
+    Ok(())
+}
+
+/// 执行一个迁移，已执行过时跳过并返回 `false`
+///
+/// 将内容按 `;` 分割，每条 SQL 单独执行。整个文件和执行记录在同一个事务中提交，
+/// 失败时整体回滚；事务内持有 advisory lock，多个实例同时启动时依次执行。
+pub async fn apply_migration(db: &DBPool, name: &str, sql: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = begin_write(db).await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('gitnote.schema_migrations'))")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("CREATE SCHEMA IF NOT EXISTS gitnote")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS gitnote.schema_migrations (
+                name TEXT PRIMARY KEY,
+                applied_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+            )
+            "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let applied: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM gitnote.schema_migrations WHERE name = $1)",
+    )
+    .bind(name)
+    .fetch_one(&mut *tx)
+    .await?;
+    if applied {
+        return Ok(false);
+    }
+
+    for sql in sql.split(';') {
+        if sql.trim().is_empty() {
             continue;
         }
-        sqlx::query(sql).execute(db).await?;
+        sqlx::query(sql).execute(&mut *tx).await?;
+    }
+    sqlx::query("INSERT INTO gitnote.schema_migrations (name) VALUES ($1)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_cover_sql_dir() {
+        let mut files = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/sql"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".sql"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let names = MIGRATIONS.iter().map(|m| m.name).collect::<Vec<_>>();
+        assert_eq!(names, files);
+    }
+
+    #[test]
+    fn test_required_columns_in_migrations() {
+        let sql = MIGRATIONS.iter().map(|m| m.sql).collect::<String>();
+        for (table, columns) in REQUIRED_COLUMNS {
+            assert!(sql.contains(&format!("gitnote.{table} (")), "{table}");
+            for column in *columns {
+                assert!(
+                    sql.contains(&format!("\n    {column} "))
+                        || sql.contains(&format!("ADD COLUMN IF NOT EXISTS {column} ")),
+                    "{table}.{column}"
+                );
+            }
+        }
     }
-    Ok(())
 }
@@ -1,41 +1,260 @@
-use super::{ArticleDetail, ArticleSummary, DBPool, Group};
+use chrono::{DateTime, FixedOffset};
+
+use crate::content::RAW_HTML_RENDERER;
+
+use super::{
+    ArticleDetail, ArticleSlug, ArticleSummary, AuthorSummary, BrokenLink, CategoryGroup, DBPool,
+    DateRange, DigestRun, Group, GroupStats, ListColumns, ListSort, OrphanArticle, PrunedArticle,
+    Redirect, RenderSort, RenderStat, SiteCounts, StaleRender, SyncState, TagCount, TitleEntry,
+    WebmentionSource,
+    cipher::{Field, open_fields},
+};
 
 /// 用于查询文章相关数据
 ///
 /// 提供获取文章详情、列表、分类和标签的接口。
+///
+/// 文章是否公开按 `COALESCE(a.public, g.public)` 判断：front matter 设置了 `public` 时以文章为准，
+/// 否则与所在组相同；没有对应组的文章始终不公开。因所在组的保留规则下线（`retention_pruned`）的文章同样不公开。
 pub trait Querier: Send + Sync {
     type Error;
     /// 查询单个文章详情
     ///
     /// 返回 [`ArticleDetail`]，如果文章不存在则返回 `None`。
+    /// 同时返回同组中按 `created_at`、`slug` 升序排列的上一篇和下一篇公开文章，以及按出现顺序排列的附件。
     fn get_one(
         &self,
         slug: impl AsRef<str>,
     ) -> impl std::future::Future<Output = Result<Option<ArticleDetail>, Self::Error>>;
 
+    /// 查询组中的单个文章详情
+    ///
+    /// 与 [`Querier::get_one`] 相同，`group` 为组对外使用的 slug，文章不在该组中时返回 `None`。
+    fn get_by_group_and_slug(
+        &self,
+        group: &str,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<Option<ArticleDetail>, Self::Error>> {
+        async move {
+            Ok(self
+                .get_one(slug)
+                .await?
+                .filter(|article| article.group.slug == group))
+        }
+    }
+
+    /// 查询公开文章的更新时间
+    ///
+    /// 不读取正文，用于 `HEAD` 请求和条件请求。文章不存在或未公开时返回 `None`。
+    fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<Option<DateTime<FixedOffset>>, Self::Error>>;
+
+    /// 查询短 id 对应的公开文章当前的 slug
+    ///
+    /// 文章不存在或未公开时返回 `None`。
+    fn short_id_slug(
+        &self,
+        short_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<String>, Self::Error>>;
+
     /// 分页查询文章列表
     ///
-    /// 返回 [`ArticleListItem`] 的向量，可按分类、作者或标签过滤。
+    /// 返回 [`ArticleListItem`] 的向量，可按组、作者、分类或标签过滤，`group` 为组对外使用的 slug，`author` 为作者 id，
+    /// `category` 为组配置中的分类 id。
+    /// `archived` 为 `Some` 时只返回所在组归档状态与之相同的文章，为 `None` 时不区分。
+    /// `dates` 按创建时间和更新时间过滤，边界包含端点，见 [`DateRange`]。
+    /// `columns` 中不读取的列在结果中为空值，见 [`ListColumns`]。
+    /// `sort` 为排序方式，见 [`ListSort`]。
     ///
+    #[allow(clippy::too_many_arguments)]
     fn article_list(
         &self,
         page: i32,
         size: i32,
         group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
         tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
     ) -> impl std::future::Future<Output = Result<Vec<ArticleSummary>, Self::Error>>;
 
+    /// 查询有公开文章的作者
+    ///
+    /// 返回 [`AuthorSummary`]，按作者 id 排序。显示名取最新文章所在组中的作者名。
+    ///
+    fn authors(&self)
+    -> impl std::future::Future<Output = Result<Vec<AuthorSummary>, Self::Error>>;
+
+    /// 查询分类中的公开组
+    ///
+    /// 返回分类 id 为 `category` 的公开组及其公开文章数，按组 slug 排序；未公开的组不出现，也不计入文章数。
+    /// 分类不存在或只包含未公开的组时返回空。
+    ///
+    fn category_groups(
+        &self,
+        category: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<CategoryGroup>, Self::Error>>;
+
     /// 查询所有公开的 [`CategoryInfo`]
     ///
-    /// 返回系统中所有公开分组的分类信息。
+    /// 返回系统中所有公开分组的分类信息，包括隐藏的组，按 `nav_order NULLS LAST, name` 排序。
     ///
     fn groups(&self) -> impl std::future::Future<Output = Result<Vec<Group>, Self::Error>>;
 
     /// 查询所有文章标签
     ///
-    /// 返回系统中所有公开文章的标签集合。
+    /// 返回系统中所有公开文章的标签集合，读取标签索引，不扫描文章。
+    ///
+    fn tags(&self) -> impl std::future::Future<Output = Result<Vec<String>, Self::Error>>;
+
+    /// 查询所有标签及带有该标签的公开文章数
+    ///
+    /// 返回标签索引中的 [`TagCount`]，按标签排序。
+    ///
+    fn tags_with_counts(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<TagCount>, Self::Error>>;
+
+    /// 查询每篇公开文章的标签
+    ///
+    /// 用于构建带有文章数的标签树，见 [`tag_tree`](crate::content::tag_tree)。
+    ///
+    fn article_tags(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<String>>, Self::Error>>;
+
+    /// 查询所有公开文章的 slug
+    ///
+    fn public_slugs(&self) -> impl std::future::Future<Output = Result<Vec<String>, Self::Error>>;
+
+    /// 查询公开文章所在的组、slug 和更新时间
+    ///
+    /// 返回 [`ArticleSlug`]，按组 slug 和文章 slug 排序，最多 `limit` 个。`group` 为组对外使用的 slug。
+    /// 只读取这三列，用于一次返回全部文章路径。
+    ///
+    fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<ArticleSlug>, Self::Error>>;
+
+    /// 查询公开文章的标题索引
+    ///
+    /// 返回 [`TitleEntry`]，按 [`ListSort::Title`] 排序，最多 `limit` 个。`group` 为组对外使用的 slug。
+    ///
+    fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<TitleEntry>, Self::Error>>;
+
+    /// 查询旧位置对应的重定向目标
+    ///
+    /// 未指定 `group` 时仅按 slug 匹配，`group` 可以是旧位置的组目录路径或该组当前的 slug。
+    /// 返回的组为目标组的 slug。目标文章不存在或未公开时返回 `None`。
+    ///
+    fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<Option<Redirect>, Self::Error>>;
+
+    /// 查询最久未检查的外部链接
+    ///
+    /// 从未检查过的链接优先，最多返回 `limit` 个。
+    ///
+    fn links_due(
+        &self,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<String>, Self::Error>>;
+
+    /// 查询所有失效的外部链接
+    ///
+    /// 返回 [`BrokenLink`]，按文章和链接排序。
+    ///
+    fn broken_links(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<BrokenLink>, Self::Error>>;
+
+    /// 查询最近一次同步后记录的内容指纹
+    ///
+    /// 返回 [`SyncState`]，迁移后尚未同步过时返回 `None`。
+    ///
+    fn sync_state(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Option<SyncState>, Self::Error>>;
+
+    /// 查询公开文章、公开组和标签的数量，以及最近一次同步的时间
+    ///
+    /// 返回 [`SiteCounts`]，在一条语句中完成。
+    ///
+    fn site_counts(&self) -> impl std::future::Future<Output = Result<SiteCounts, Self::Error>>;
+
+    /// 查询源文件在 `paths` 中的公开文章的外部链接和已发送 webmention 的目标
+    ///
+    /// 返回 [`WebmentionSource`]，加密文章不记录外部链接，不会返回。
+    ///
+    fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> impl std::future::Future<Output = Result<Vec<WebmentionSource>, Self::Error>>;
+
+    /// 查询渲染器标识与 `current` 不同的文章，包括未公开的文章
+    ///
+    /// 返回 [`StaleRender`]，按 slug 排序。未记录渲染器的文章同样返回，
+    /// 原样输出 HTML 正文的文章（标识为 [`RAW_HTML_RENDERER`]）不经过渲染器，不返回。
+    ///
+    fn stale_renders(
+        &self,
+        current: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<StaleRender>, Self::Error>>;
+
+    /// 查询 `group_id` 没有对应组的文章，包括未公开的文章
     ///
-    fn tags(&self) -> impl std::future::Future<Output = Result<Vec<String>, sqlx::Error>>;
+    /// 返回 [`OrphanArticle`]，按组目录路径和 slug 排序。这些文章不会出现在任何公开查询中。
+    ///
+    fn orphan_articles(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<OrphanArticle>, Self::Error>>;
+
+    /// 查询因所在组的保留规则下线的文章
+    ///
+    /// 返回 [`PrunedArticle`]，按组目录路径排列，组内按 `created_at`、`slug` 从新到旧排序。
+    ///
+    fn pruned_articles(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<PrunedArticle>, Self::Error>>;
+
+    /// 查询各组的大小统计
+    ///
+    /// 返回 [`GroupStats`]，按组目录路径排序，包括未公开的组。统计在每次同步提交时更新。
+    ///
+    fn group_stats(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<GroupStats>, Self::Error>>;
+
+    /// 查询记录了渲染耗时的文章，包括未公开的文章
+    ///
+    /// 返回最多 `limit` 篇 [`RenderStat`]，按 `sort` 从大到小排列，相同时按 slug 排序。
+    ///
+    fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> impl std::future::Future<Output = Result<Vec<RenderStat>, Self::Error>>;
+
+    /// 查询周期结束时间最晚的每周摘要执行记录
+    ///
+    /// 返回 [`DigestRun`]，从未执行过时返回 `None`。
+    ///
+    fn last_digest_run(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Option<DigestRun>, Self::Error>>;
 }
 
 impl Querier for DBPool {
@@ -44,18 +263,84 @@ impl Querier for DBPool {
     async fn get_one(&self, slug: impl AsRef<str>) -> Result<Option<ArticleDetail>, Self::Error> {
         let result = sqlx::query_as::<_, ArticleDetail>(
                 r#"
-                SELECT a.slug, a.title, a.summary, a.tags, a.content, to_jsonb(g) as group, a.updated_at, a.created_at
+                WITH ordered AS (
+                    SELECT
+                        a.slug,
+                        LAG(jsonb_build_object('slug', a.slug, 'title', a.title)) OVER w AS prev,
+                        LEAD(jsonb_build_object('slug', a.slug, 'title', a.title)) OVER w AS next
+                    FROM articles a
+                    INNER JOIN groups g ON a.group_id = g.id
+                    WHERE a.group_id = (SELECT group_id FROM articles WHERE slug = $1)
+                    AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                    WINDOW w AS (ORDER BY a.created_at ASC, a.slug ASC)
+                )
+                SELECT a.slug, a.short_id, a.title, a.summary, a.tags, COALESCE(b.content, a.content) AS content, to_jsonb(g) - 'description_html' as group, a.comment_count, a.nonce, a.updated_at, a.created_at, o.prev, o.next, a.source_path, a.source_commit, a.canonical_url, a.social,
+                    COALESCE((
+                        SELECT jsonb_agg(jsonb_build_object('url_or_path', t.url_or_path, 'kind', t.kind, 'link_text', t.link_text) ORDER BY t.position)
+                        FROM article_attachments t
+                        WHERE t.slug = a.slug AND t.group_id = a.group_id
+                    ), '[]'::jsonb) AS attachments
                 FROM articles a
                 INNER JOIN groups g ON a.group_id = g.id
+                INNER JOIN ordered o ON o.slug = a.slug
+                LEFT JOIN content_blobs b ON b.oid = a.content_oid
                 WHERE a.slug = $1
-                AND g.public = TRUE
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
                 LIMIT 1
                 "#,
             )
             .bind(slug.as_ref())
             .fetch_optional(self)
             .await?;
-        Ok(result)
+
+        let Some(mut article) = result else {
+            return Ok(None);
+        };
+
+        open_fields(
+            article.nonce.as_deref(),
+            &mut [
+                (Field::Content, &mut article.content),
+                (Field::Summary, &mut article.summary),
+            ],
+        )
+        .map_err(|e| sqlx::Error::Decode(e.into()))?;
+
+        Ok(Some(article))
+    }
+
+    async fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> Result<Option<DateTime<FixedOffset>>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.updated_at
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.slug = $1
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                LIMIT 1
+                "#,
+        )
+        .bind(slug)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn short_id_slug(&self, short_id: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.slug
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.short_id = $1
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                "#,
+        )
+        .bind(short_id)
+        .fetch_optional(self)
+        .await
     }
 
     async fn article_list(
@@ -63,41 +348,140 @@ impl Querier for DBPool {
         page: i32,
         size: i32,
         group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
         tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
     ) -> Result<Vec<ArticleSummary>, sqlx::Error> {
         let offset = (page.max(1) - 1) * size;
-        let mut builder = sqlx::QueryBuilder::new(
-            r#"
-                SELECT a.slug, a.title, a.summary, a.tags, to_jsonb(g) as group, a.updated_at, a.created_at
+        let mut builder = sqlx::QueryBuilder::new("SELECT a.slug, a.short_id, a.title, ");
+        // 不读取的列以空值代替，保持结果的列不变
+        builder.push(if columns.summary {
+            "a.summary, a.summary_plain, "
+        } else {
+            "'' AS summary, '' AS summary_plain, "
+        });
+        builder.push(if columns.tags {
+            "a.tags, "
+        } else {
+            "'{}'::text[] AS tags, "
+        });
+        builder.push(
+            r#"to_jsonb(g) - 'description_html' as group, a.comment_count, a.nonce, a.updated_at, a.created_at
                 FROM articles a
                 INNER JOIN groups g ON a.group_id = g.id
                 "#,
         );
 
-        builder.push("WHERE g.public = true");
+        builder.push("WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned");
         if let Some(g) = group {
-            builder.push(" AND g.id = ").push_bind(g);
+            builder.push(" AND g.slug = ").push_bind(g);
+        }
+        if let Some(author) = author {
+            builder.push(" AND g.author_id = ").push_bind(author);
+        }
+        if let Some(category) = category {
+            builder.push(" AND g.category_id = ").push_bind(category);
         }
         if !tags.is_empty() {
-            builder.push(" AND a.tags && ").push_bind(tags);
+            // 标签以 `/` 分隔层级，过滤上层标签时同时匹配所有后代标签
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM unnest(a.tags) t, unnest(")
+                .push_bind(tags)
+                .push("::text[]) f WHERE t = f OR starts_with(t, f || '/'))");
+        }
+        if let Some(archived) = archived {
+            builder.push(" AND g.archived = ").push_bind(archived);
+        }
+        // 边界包含端点
+        if let Some(t) = dates.created_after {
+            builder.push(" AND a.created_at >= ").push_bind(t);
+        }
+        if let Some(t) = dates.created_before {
+            builder.push(" AND a.created_at <= ").push_bind(t);
+        }
+        if let Some(t) = dates.updated_after {
+            builder.push(" AND a.updated_at >= ").push_bind(t);
+        }
+        if let Some(t) = dates.updated_before {
+            builder.push(" AND a.updated_at <= ").push_bind(t);
         }
 
-        builder.push(" ORDER BY a.updated_at DESC ");
+        builder.push(match sort {
+            ListSort::Updated => " ORDER BY a.updated_at DESC ",
+            ListSort::Title => {
+                r#" ORDER BY a.title_sort_key COLLATE "C" NULLS LAST, a.title, a.slug "#
+            }
+        });
         builder.push(" LIMIT ").push_bind(size);
         builder.push(" OFFSET ").push_bind(offset);
 
         let query = builder.build_query_as::<ArticleSummary>();
-        let result = query.fetch_all(self).await?;
+        let mut result = query.fetch_all(self).await?;
+
+        // 没有读取摘要时不需要解密
+        if columns.summary {
+            for article in result.iter_mut() {
+                open_fields(
+                    article.nonce.as_deref(),
+                    &mut [
+                        (Field::Summary, &mut article.summary),
+                        (Field::SummaryPlain, &mut article.summary_plain),
+                    ],
+                )
+                .map_err(|e| sqlx::Error::Decode(e.into()))?;
+            }
+        }
+
         Ok(result)
     }
 
+    async fn authors(&self) -> Result<Vec<AuthorSummary>, sqlx::Error> {
+        sqlx::query_as::<_, AuthorSummary>(
+            r#"
+                SELECT DISTINCT ON (g.author_id)
+                    g.author_id AS id,
+                    g.author_name AS name,
+                    COUNT(*) OVER (PARTITION BY g.author_id) AS article_count,
+                    jsonb_build_object('slug', a.slug, 'title', a.title) AS latest
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND g.author_id IS NOT NULL
+                ORDER BY g.author_id, a.created_at DESC, a.slug DESC
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn category_groups(&self, category: &str) -> Result<Vec<CategoryGroup>, sqlx::Error> {
+        sqlx::query_as::<_, CategoryGroup>(
+            r#"
+                SELECT g.slug, g.name, COALESCE(g.category_name, '') AS category_name, COUNT(a.slug) FILTER (WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned) AS article_count
+                FROM groups g
+                LEFT JOIN articles a ON a.group_id = g.id
+                WHERE g.public = TRUE
+                AND g.category_id = $1
+                GROUP BY g.id
+                ORDER BY g.slug
+                "#,
+        )
+        .bind(category)
+        .fetch_all(self)
+        .await
+    }
+
     async fn groups(&self) -> Result<Vec<Group>, sqlx::Error> {
         let rows = sqlx::query_as::<_, Group>(
             r#"
                 SELECT *
                 FROM groups
                 WHERE public = true
-                ORDER BY id DESC
+                ORDER BY nav_order NULLS LAST, name, id
                 "#,
         )
         .fetch_all(self)
@@ -109,14 +493,289 @@ impl Querier for DBPool {
     async fn tags(&self) -> Result<Vec<String>, sqlx::Error> {
         sqlx::query_scalar(
             r#"
-                SELECT DISTINCT UNNEST(a.tags) AS "tag"
+                SELECT tag
+                FROM tag_index
+                ORDER BY tag
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn tags_with_counts(&self) -> Result<Vec<TagCount>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT tag, article_count, last_used
+                FROM tag_index
+                ORDER BY tag
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn article_tags(&self) -> Result<Vec<Vec<String>>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.tags
                 FROM articles a
                 JOIN groups g ON a.group_id = g.id
-                WHERE g.public = true
-                ORDER BY tag
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn public_slugs(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.slug
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                ORDER BY a.slug
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleSlug>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT g.slug AS "group", a.slug, a.updated_at
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND ($1::TEXT IS NULL OR g.slug = $1)
+                ORDER BY g.slug, a.slug
+                LIMIT $2
+                "#,
+        )
+        .bind(group)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TitleEntry>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT a.slug, a.title, g.slug AS "group", a.title_sort_key
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND ($1::TEXT IS NULL OR g.slug = $1)
+                ORDER BY a.title_sort_key COLLATE "C" NULLS LAST, a.title, a.slug
+                LIMIT $2
+                "#,
+        )
+        .bind(group)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> Result<Option<Redirect>, sqlx::Error> {
+        sqlx::query_as::<_, Redirect>(
+            r#"
+                SELECT g.slug AS group_id, r.to_slug AS slug
+                FROM redirects r
+                INNER JOIN articles a ON a.group_id = r.to_group AND a.slug = r.to_slug
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE (
+                    $1::VARCHAR IS NULL
+                    OR r.from_group = $1
+                    OR r.from_group IN (SELECT id FROM groups WHERE slug = $1)
+                )
+                AND r.from_slug = $2
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                ORDER BY r.from_group
+                LIMIT 1
+                "#,
+        )
+        .bind(group)
+        .bind(slug)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn links_due(&self, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT l.url
+                FROM external_links l
+                JOIN articles a ON a.slug = l.slug
+                GROUP BY l.url
+                ORDER BY MAX(l.last_checked) ASC NULLS FIRST
+                LIMIT $1
+                "#,
+        )
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn broken_links(&self) -> Result<Vec<BrokenLink>, sqlx::Error> {
+        sqlx::query_as::<_, BrokenLink>(
+            r#"
+                SELECT l.slug, a.title, l.url, l.status, l.error, l.last_checked
+                FROM external_links l
+                JOIN articles a ON a.slug = l.slug
+                WHERE l.status >= 400 OR l.error IS NOT NULL
+                ORDER BY l.slug, l.url
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn sync_state(&self) -> Result<Option<SyncState>, sqlx::Error> {
+        sqlx::query_as::<_, SyncState>(
+            "SELECT commit_id, content_fingerprint, computed_at FROM sync_state",
+        )
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn site_counts(&self) -> Result<SiteCounts, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT
+                    (SELECT count(*)
+                     FROM articles a
+                     JOIN groups g ON a.group_id = g.id
+                     WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned) AS articles,
+                    (SELECT count(*) FROM groups WHERE public = true) AS groups,
+                    (SELECT count(*) FROM tag_index) AS tags,
+                    (SELECT computed_at FROM sync_state) AS synced_at
+                "#,
+        )
+        .fetch_one(self)
+        .await
+    }
+
+    async fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<WebmentionSource>, sqlx::Error> {
+        sqlx::query_as::<_, WebmentionSource>(
+            r#"
+                SELECT
+                    a.slug,
+                    ARRAY(SELECT l.url FROM external_links l WHERE l.slug = a.slug ORDER BY l.url) AS links,
+                    ARRAY(SELECT w.target FROM webmentions_sent w WHERE w.slug = a.slug ORDER BY w.target) AS sent
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.source_path = ANY($1)
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND a.nonce IS NULL
+                ORDER BY a.slug
+                "#,
+        )
+        .bind(paths)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn stale_renders(&self, current: &str) -> Result<Vec<StaleRender>, sqlx::Error> {
+        sqlx::query_as::<_, StaleRender>(
+            r#"
+                SELECT slug, title, renderer, source_path
+                FROM articles
+                WHERE renderer IS DISTINCT FROM $1 AND renderer IS DISTINCT FROM $2
+                ORDER BY slug
+                "#,
+        )
+        .bind(current)
+        .bind(RAW_HTML_RENDERER)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn orphan_articles(&self) -> Result<Vec<OrphanArticle>, sqlx::Error> {
+        sqlx::query_as::<_, OrphanArticle>(
+            r#"
+                SELECT a.slug, a.title, a.group_id, a.source_path
+                FROM articles a
+                LEFT JOIN groups g ON a.group_id = g.id
+                WHERE g.id IS NULL
+                ORDER BY a.group_id, a.slug
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn pruned_articles(&self) -> Result<Vec<PrunedArticle>, sqlx::Error> {
+        sqlx::query_as::<_, PrunedArticle>(
+            r#"
+                SELECT slug, title, group_id, created_at, source_path
+                FROM articles
+                WHERE retention_pruned
+                ORDER BY group_id, created_at DESC, slug DESC
                 "#,
         )
         .fetch_all(self)
         .await
     }
+
+    async fn group_stats(&self) -> Result<Vec<GroupStats>, sqlx::Error> {
+        sqlx::query_as::<_, GroupStats>(
+            r#"
+                SELECT s.group_id, g.slug, s.content_bytes, s.asset_bytes, s.article_count, g.quota_bytes, s.updated_at
+                FROM group_stats s
+                JOIN groups g ON g.id = s.group_id
+                ORDER BY s.group_id
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> Result<Vec<RenderStat>, sqlx::Error> {
+        sqlx::query_as::<_, RenderStat>(
+            r#"
+                SELECT slug, title, group_id, source_path, render_ms, content_bytes, rendered_at
+                FROM articles
+                WHERE render_ms IS NOT NULL
+                ORDER BY CASE WHEN $1 THEN content_bytes ELSE render_ms END DESC, slug
+                LIMIT $2
+                "#,
+        )
+        .bind(sort == RenderSort::ContentBytes)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn last_digest_run(&self) -> Result<Option<DigestRun>, sqlx::Error> {
+        sqlx::query_as::<_, DigestRun>(
+            r#"
+                SELECT period_end, period_start, article_count, status, error, ran_at
+                FROM digest_runs
+                ORDER BY period_end DESC
+                LIMIT 1
+                "#,
+        )
+        .fetch_optional(self)
+        .await
+    }
 }
@@ -0,0 +1,81 @@
+use aes_gcm::aead::{OsRng, rand_core::RngCore};
+
+/// 短 id 使用的 base32 字母表（RFC 4648，小写）
+const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// 短 id 的长度，取 40 位随机数
+pub const SHORT_ID_LEN: usize = 8;
+
+/// 为文章生成新的短 id
+///
+/// 取 16 字节随机数（与 UUID v4 的随机部分相同）的前 40 位，编码为 [`SHORT_ID_LEN`] 位小写 base32。
+/// `taken` 返回 `true` 的 id 已被其他文章使用，重新生成。
+pub fn new_short_id(taken: impl Fn(&str) -> bool) -> String {
+    generate_with(
+        || {
+            let mut bytes = [0u8; 16];
+            OsRng.fill_bytes(&mut bytes);
+            bytes
+        },
+        taken,
+    )
+}
+
+/// 按 `random` 给出的随机数生成短 id，直到得到未被使用的 id
+fn generate_with(mut random: impl FnMut() -> [u8; 16], taken: impl Fn(&str) -> bool) -> String {
+    loop {
+        let id = encode(&random());
+        if !taken(&id) {
+            return id;
+        }
+    }
+}
+
+/// 将前 40 位按每 5 位一个字符编码
+fn encode(bytes: &[u8; 16]) -> String {
+    let bits = bytes[..5]
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    (0..SHORT_ID_LEN)
+        .rev()
+        .map(|i| ALPHABET[((bits >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// 是否为格式正确的短 id
+pub fn is_short_id(id: &str) -> bool {
+    id.len() == SHORT_ID_LEN && id.bytes().all(|b| ALPHABET.contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_encode_short_id() {
+        assert_eq!(encode(&[0; 16]), "aaaaaaaa");
+        assert_eq!(encode(&[0xff; 16]), "77777777");
+        // 只使用前 5 个字节
+        let mut bytes = [0xff; 16];
+        bytes[..5].copy_from_slice(&[0x00, 0x44, 0x32, 0x14, 0xc7]);
+        assert_eq!(encode(&bytes), "abcdefgh");
+
+        let id = new_short_id(|_| false);
+        assert!(is_short_id(&id), "{id}");
+        assert!(!is_short_id("abcdefg1"));
+        assert!(!is_short_id("abcdefghi"));
+    }
+
+    #[test]
+    fn test_short_id_collision() {
+        // 前两次生成的 id 已被使用，第三次才可用
+        let taken = HashSet::from(["aaaaaaaa".to_string(), "77777777".to_string()]);
+        let mut rounds = [[0u8; 16], [0xff; 16], [0x01; 16]].into_iter();
+        let id = generate_with(|| rounds.next().unwrap(), |id| taken.contains(id));
+
+        assert_eq!(id, encode(&[0x01; 16]));
+        assert_eq!(rounds.next(), None);
+    }
+}
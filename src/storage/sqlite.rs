@@ -0,0 +1,218 @@
+/// SQLite 中表示当前时间的表达式，格式与 [`timestamp`] 相同
+macro_rules! now {
+    () => {
+        "strftime('%Y-%m-%dT%H:%M:%f000Z', 'now')"
+    };
+}
+
+mod querier;
+mod store;
+
+use std::{str::FromStr, time::Duration};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::{
+    Row, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow},
+    types::Json,
+};
+
+use super::{Migration, postgres::REQUIRED_COLUMNS};
+
+pub use self::store::SqliteStore;
+
+/// 按 SQLite 连接 URL 创建连接池，数据库文件不存在时创建
+///
+/// 使用 WAL 模式，重建和同步写入期间查询读取提交前的数据。写入冲突时最多等待 5 秒。
+pub async fn new_sqlite_pool(conn_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(conn_url)?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(Duration::from_secs(5));
+
+    SqlitePoolOptions::new()
+        .max_connections(5)
+        .acquire_timeout(Duration::from_secs(2))
+        .connect_with(options)
+        .await
+}
+
+/// SQLite 后端随程序打包的迁移文件，位于 `sql/sqlite/`，按文件名顺序执行
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "01-CREATE_TABLE.sql",
+        sql: include_str!("../../sql/sqlite/01-CREATE_TABLE.sql"),
+    },
+    Migration {
+        name: "02-GROUP_NAV.sql",
+        sql: include_str!("../../sql/sqlite/02-GROUP_NAV.sql"),
+    },
+    Migration {
+        name: "03-GROUP_RETAIN.sql",
+        sql: include_str!("../../sql/sqlite/03-GROUP_RETAIN.sql"),
+    },
+    Migration {
+        name: "04-RENDER_STATS.sql",
+        sql: include_str!("../../sql/sqlite/04-RENDER_STATS.sql"),
+    },
+    Migration {
+        name: "05-GROUP_LICENSE.sql",
+        sql: include_str!("../../sql/sqlite/05-GROUP_LICENSE.sql"),
+    },
+    Migration {
+        name: "06-CONTENT_SIMHASH.sql",
+        sql: include_str!("../../sql/sqlite/06-CONTENT_SIMHASH.sql"),
+    },
+    Migration {
+        name: "07-GROUP_STATS.sql",
+        sql: include_str!("../../sql/sqlite/07-GROUP_STATS.sql"),
+    },
+    Migration {
+        name: "08-DIGEST_RUNS.sql",
+        sql: include_str!("../../sql/sqlite/08-DIGEST_RUNS.sql"),
+    },
+    Migration {
+        name: "09-GROUP_SCHEMA.sql",
+        sql: include_str!("../../sql/sqlite/09-GROUP_SCHEMA.sql"),
+    },
+    Migration {
+        name: "10-ARTICLE_SOCIAL.sql",
+        sql: include_str!("../../sql/sqlite/10-ARTICLE_SOCIAL.sql"),
+    },
+    Migration {
+        name: "11-ARTICLE_ATTACHMENTS.sql",
+        sql: include_str!("../../sql/sqlite/11-ARTICLE_ATTACHMENTS.sql"),
+    },
+    Migration {
+        name: "12-TITLE_SORT_KEY.sql",
+        sql: include_str!("../../sql/sqlite/12-TITLE_SORT_KEY.sql"),
+    },
+];
+
+/// 查询 SQLite 数据库中缺失的表和列
+///
+/// 返回 `表.列` 形式的列表，数据库已迁移时为空。
+pub async fn sqlite_missing_schema(db: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    let existing: Vec<(String, String)> = sqlx::query_as(
+        r#"
+            SELECT m.name, p.name
+            FROM sqlite_master m
+            JOIN pragma_table_info(m.name) p
+            WHERE m.type = 'table'
+            "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(REQUIRED_COLUMNS
+        .iter()
+        .flat_map(|(table, columns)| columns.iter().map(move |column| (*table, *column)))
+        .filter(|(table, column)| !existing.iter().any(|(t, c)| t == table && c == column))
+        .map(|(table, column)| format!("{table}.{column}"))
+        .collect())
+}
+
+/// 按顺序执行 [`SQLITE_MIGRATIONS`] 中尚未执行的迁移，返回本次执行的文件名
+pub async fn run_sqlite_migrations(db: &SqlitePool) -> Result<Vec<&'static str>, sqlx::Error> {
+    let mut applied = vec![];
+    for migration in SQLITE_MIGRATIONS {
+        if apply_sqlite_migration(db, migration.name, migration.sql).await? {
+            applied.push(migration.name);
+        }
+    }
+    Ok(applied)
+}
+
+/// 执行一个 SQLite 迁移，已执行过时跳过并返回 `false`
+///
+/// 与 [`apply_migration`](super::apply_migration) 相同，按 `;` 分割后在一个事务中执行，
+/// 以文件名记录到 `schema_migrations`。事务开始时即获取写锁，多个实例同时启动时依次执行。
+pub async fn apply_sqlite_migration(
+    db: &SqlitePool,
+    name: &str,
+    sql: &str,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin_with("BEGIN IMMEDIATE").await?;
+
+    sqlx::query(concat!(
+        r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                name TEXT PRIMARY KEY,
+                applied_at TEXT NOT NULL DEFAULT ("#,
+        now!(),
+        r#")
+            )
+            "#
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    let applied: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM schema_migrations WHERE name = $1)")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+    if applied {
+        return Ok(false);
+    }
+
+    for sql in sql.split(';') {
+        if sql.trim().is_empty() {
+            continue;
+        }
+        sqlx::query(sql).execute(&mut *tx).await?;
+    }
+    sqlx::query("INSERT INTO schema_migrations (name) VALUES ($1)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// 时间写入 SQLite 时的文本，转换为 UTC 并固定 6 位小数秒，按文本比较与按时间比较的结果相同
+fn timestamp(t: DateTime<FixedOffset>) -> String {
+    t.with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%S%.6fZ")
+        .to_string()
+}
+
+/// 读取 JSON 数组列
+///
+/// SQLite 没有数组类型，这些列不能像 PostgreSQL 中那样通过 `FromRow` 直接解码为 `Vec<String>`。
+fn json_array(row: &SqliteRow, column: &str) -> Result<Vec<String>, sqlx::Error> {
+    Ok(row.try_get::<Json<Vec<String>>, _>(column)?.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_migrations_cover_sql_dir() {
+        let mut files = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/sql/sqlite"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".sql"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let names = SQLITE_MIGRATIONS.iter().map(|m| m.name).collect::<Vec<_>>();
+        assert_eq!(names, files);
+    }
+
+    #[test]
+    fn test_timestamp_orders_as_text() {
+        let parse = |s| DateTime::parse_from_rfc3339(s).unwrap();
+        let earlier = timestamp(parse("2024-01-01T08:00:00.5+08:00"));
+        let later = timestamp(parse("2024-01-01T00:00:01Z"));
+
+        assert_eq!(earlier, "2024-01-01T00:00:00.500000Z");
+        assert!(earlier < later);
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&later).unwrap(),
+            parse("2024-01-01T08:00:01+08:00")
+        );
+    }
+}
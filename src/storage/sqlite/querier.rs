@@ -0,0 +1,630 @@
+use chrono::{DateTime, FixedOffset};
+use sqlx::{Row, SqlitePool, sqlite::SqliteRow, types::Json};
+
+use crate::{
+    content::RAW_HTML_RENDERER,
+    storage::{
+        ArticleDetail, ArticleSlug, ArticleSummary, AuthorSummary, BrokenLink, CategoryGroup,
+        DateRange, DigestRun, Group, GroupStats, ListColumns, ListSort, OrphanArticle,
+        PrunedArticle, Querier, Redirect, RenderSort, RenderStat, SiteCounts, StaleRender,
+        SyncState, TagCount, TitleEntry, WebmentionSource,
+        cipher::{Field, open_fields},
+    },
+};
+
+use super::{json_array, timestamp};
+
+/// 与 PostgreSQL 中 `to_jsonb(g) - 'description_html'` 相同的组 JSON，布尔列转换为 JSON 布尔值
+macro_rules! group_json {
+    () => {
+        r#"json_object(
+            'id', g.id,
+            'slug', g.slug,
+            'name', g.name,
+            'public', json(CASE WHEN g.public THEN 'true' ELSE 'false' END),
+            'kind', json(g.kind),
+            'encrypted', json(CASE WHEN g.encrypted THEN 'true' ELSE 'false' END),
+            'slug_strategy', g.slug_strategy,
+            'author_id', g.author_id,
+            'author_name', g.author_name,
+            'archived', json(CASE WHEN g.archived THEN 'true' ELSE 'false' END),
+            'default_tags', json(g.default_tags),
+            'category_id', g.category_id,
+            'category_name', g.category_name,
+            'nav_order', g.nav_order,
+            'hidden', json(CASE WHEN g.hidden THEN 'true' ELSE 'false' END),
+            'license', json(g.license)
+        )"#
+    };
+}
+
+fn article_detail(row: SqliteRow) -> Result<ArticleDetail, sqlx::Error> {
+    Ok(ArticleDetail {
+        slug: row.try_get("slug")?,
+        short_id: row.try_get("short_id")?,
+        title: row.try_get("title")?,
+        summary: row.try_get("summary")?,
+        tags: json_array(&row, "tags")?,
+        content: row.try_get("content")?,
+        group: row.try_get("group")?,
+        comment_count: row.try_get("comment_count")?,
+        nonce: row.try_get("nonce")?,
+        updated_at: row.try_get("updated_at")?,
+        created_at: row.try_get("created_at")?,
+        prev: row.try_get("prev")?,
+        next: row.try_get("next")?,
+        source_path: row.try_get("source_path")?,
+        source_commit: row.try_get("source_commit")?,
+        canonical_url: row.try_get("canonical_url")?,
+        social: row.try_get("social")?,
+        attachments: row.try_get("attachments")?,
+    })
+}
+
+fn article_summary(row: SqliteRow) -> Result<ArticleSummary, sqlx::Error> {
+    Ok(ArticleSummary {
+        slug: row.try_get("slug")?,
+        short_id: row.try_get("short_id")?,
+        title: row.try_get("title")?,
+        summary: row.try_get("summary")?,
+        summary_plain: row.try_get("summary_plain")?,
+        tags: json_array(&row, "tags")?,
+        group: row.try_get("group")?,
+        comment_count: row.try_get("comment_count")?,
+        nonce: row.try_get("nonce")?,
+        updated_at: row.try_get("updated_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// 与 `impl Querier for DBPool` 的查询相同，数组参数以 JSON 文本绑定，通过 `json_each` 展开
+impl Querier for SqlitePool {
+    type Error = sqlx::Error;
+
+    async fn get_one(&self, slug: impl AsRef<str>) -> Result<Option<ArticleDetail>, Self::Error> {
+        let result = sqlx::query(concat!(
+            r#"
+                WITH ordered AS (
+                    SELECT
+                        a.slug,
+                        LAG(json_object('slug', a.slug, 'title', a.title)) OVER w AS prev,
+                        LEAD(json_object('slug', a.slug, 'title', a.title)) OVER w AS next
+                    FROM articles a
+                    INNER JOIN groups g ON a.group_id = g.id
+                    WHERE a.group_id = (SELECT group_id FROM articles WHERE slug = $1)
+                    AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                    WINDOW w AS (ORDER BY a.created_at ASC, a.slug ASC)
+                )
+                SELECT a.slug, a.short_id, a.title, a.summary, a.tags, COALESCE(b.content, a.content) AS content, "#,
+            group_json!(),
+            r#" AS "group", a.comment_count, a.nonce, a.updated_at, a.created_at, o.prev, o.next, a.source_path, a.source_commit, a.canonical_url, a.social,
+                    COALESCE((
+                        SELECT json_group_array(json_object('url_or_path', t.url_or_path, 'kind', t.kind, 'link_text', t.link_text) ORDER BY t.position)
+                        FROM article_attachments t
+                        WHERE t.slug = a.slug AND t.group_id = a.group_id
+                    ), '[]') AS attachments
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                INNER JOIN ordered o ON o.slug = a.slug
+                LEFT JOIN content_blobs b ON b.oid = a.content_oid
+                WHERE a.slug = $1
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                LIMIT 1
+                "#,
+        ))
+        .bind(slug.as_ref())
+        .try_map(article_detail)
+        .fetch_optional(self)
+        .await?;
+
+        let Some(mut article) = result else {
+            return Ok(None);
+        };
+
+        open_fields(
+            article.nonce.as_deref(),
+            &mut [
+                (Field::Content, &mut article.content),
+                (Field::Summary, &mut article.summary),
+            ],
+        )
+        .map_err(|e| sqlx::Error::Decode(e.into()))?;
+
+        Ok(Some(article))
+    }
+
+    async fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> Result<Option<DateTime<FixedOffset>>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.updated_at
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.slug = $1
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                LIMIT 1
+                "#,
+        )
+        .bind(slug)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn short_id_slug(&self, short_id: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.slug
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.short_id = $1
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                "#,
+        )
+        .bind(short_id)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn article_list(
+        &self,
+        page: i32,
+        size: i32,
+        group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
+        tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
+    ) -> Result<Vec<ArticleSummary>, sqlx::Error> {
+        let offset = (page.max(1) - 1) * size;
+        let mut builder = sqlx::QueryBuilder::new("SELECT a.slug, a.short_id, a.title, ");
+        // 不读取的列以空值代替，保持结果的列不变
+        builder.push(if columns.summary {
+            "a.summary, a.summary_plain, "
+        } else {
+            "'' AS summary, '' AS summary_plain, "
+        });
+        builder.push(if columns.tags {
+            "a.tags, "
+        } else {
+            "'[]' AS tags, "
+        });
+        builder.push(concat!(
+            group_json!(),
+            r#" AS "group", a.comment_count, a.nonce, a.updated_at, a.created_at
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                "#,
+        ));
+
+        builder.push("WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned");
+        if let Some(g) = group {
+            builder.push(" AND g.slug = ").push_bind(g);
+        }
+        if let Some(author) = author {
+            builder.push(" AND g.author_id = ").push_bind(author);
+        }
+        if let Some(category) = category {
+            builder.push(" AND g.category_id = ").push_bind(category);
+        }
+        if !tags.is_empty() {
+            // 标签以 `/` 分隔层级，过滤上层标签时同时匹配所有后代标签
+            builder
+                .push(" AND EXISTS (SELECT 1 FROM json_each(a.tags) t, json_each(")
+                .push_bind(Json(tags))
+                .push(
+                    ") f WHERE t.value = f.value OR substr(t.value, 1, length(f.value) + 1) = f.value || '/')",
+                );
+        }
+        if let Some(archived) = archived {
+            builder.push(" AND g.archived = ").push_bind(archived);
+        }
+        // 边界包含端点
+        if let Some(t) = dates.created_after {
+            builder
+                .push(" AND a.created_at >= ")
+                .push_bind(timestamp(t));
+        }
+        if let Some(t) = dates.created_before {
+            builder
+                .push(" AND a.created_at <= ")
+                .push_bind(timestamp(t));
+        }
+        if let Some(t) = dates.updated_after {
+            builder
+                .push(" AND a.updated_at >= ")
+                .push_bind(timestamp(t));
+        }
+        if let Some(t) = dates.updated_before {
+            builder
+                .push(" AND a.updated_at <= ")
+                .push_bind(timestamp(t));
+        }
+
+        // 默认的 BINARY 排序规则按字节比较
+        builder.push(match sort {
+            ListSort::Updated => " ORDER BY a.updated_at DESC ",
+            ListSort::Title => " ORDER BY a.title_sort_key NULLS LAST, a.title, a.slug ",
+        });
+        builder.push(" LIMIT ").push_bind(size);
+        builder.push(" OFFSET ").push_bind(offset);
+
+        let mut result = builder
+            .build()
+            .try_map(article_summary)
+            .fetch_all(self)
+            .await?;
+
+        // 没有读取摘要时不需要解密
+        if columns.summary {
+            for article in result.iter_mut() {
+                open_fields(
+                    article.nonce.as_deref(),
+                    &mut [
+                        (Field::Summary, &mut article.summary),
+                        (Field::SummaryPlain, &mut article.summary_plain),
+                    ],
+                )
+                .map_err(|e| sqlx::Error::Decode(e.into()))?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn authors(&self) -> Result<Vec<AuthorSummary>, sqlx::Error> {
+        // 没有 DISTINCT ON，取每个作者按创建时间倒序的第一行
+        sqlx::query_as::<_, AuthorSummary>(
+            r#"
+                SELECT id, name, article_count, latest
+                FROM (
+                    SELECT
+                        g.author_id AS id,
+                        g.author_name AS name,
+                        COUNT(*) OVER (PARTITION BY g.author_id) AS article_count,
+                        json_object('slug', a.slug, 'title', a.title) AS latest,
+                        ROW_NUMBER() OVER (PARTITION BY g.author_id ORDER BY a.created_at DESC, a.slug DESC) AS n
+                    FROM articles a
+                    INNER JOIN groups g ON a.group_id = g.id
+                    WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                    AND g.author_id IS NOT NULL
+                )
+                WHERE n = 1
+                ORDER BY id
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn category_groups(&self, category: &str) -> Result<Vec<CategoryGroup>, sqlx::Error> {
+        sqlx::query_as::<_, CategoryGroup>(
+            r#"
+                SELECT g.slug, g.name, COALESCE(g.category_name, '') AS category_name, COUNT(a.slug) FILTER (WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned) AS article_count
+                FROM groups g
+                LEFT JOIN articles a ON a.group_id = g.id
+                WHERE g.public = TRUE
+                AND g.category_id = $1
+                GROUP BY g.id
+                ORDER BY g.slug
+                "#,
+        )
+        .bind(category)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn groups(&self) -> Result<Vec<Group>, sqlx::Error> {
+        sqlx::query_as::<_, Group>(
+            r#"
+                SELECT *
+                FROM groups
+                WHERE public = TRUE
+                ORDER BY nav_order NULLS LAST, name, id
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn tags(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT tag
+                FROM tag_index
+                ORDER BY tag
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn tags_with_counts(&self) -> Result<Vec<TagCount>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT tag, article_count, last_used
+                FROM tag_index
+                ORDER BY tag
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn article_tags(&self) -> Result<Vec<Vec<String>>, sqlx::Error> {
+        sqlx::query(
+            r#"
+                SELECT a.tags
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                "#,
+        )
+        .try_map(|row: SqliteRow| json_array(&row, "tags"))
+        .fetch_all(self)
+        .await
+    }
+
+    async fn public_slugs(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT a.slug
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                ORDER BY a.slug
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleSlug>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT g.slug AS "group", a.slug, a.updated_at
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND ($1 IS NULL OR g.slug = $1)
+                ORDER BY g.slug, a.slug
+                LIMIT $2
+                "#,
+        )
+        .bind(group)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TitleEntry>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT a.slug, a.title, g.slug AS "group", a.title_sort_key
+                FROM articles a
+                JOIN groups g ON a.group_id = g.id
+                WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND ($1 IS NULL OR g.slug = $1)
+                ORDER BY a.title_sort_key NULLS LAST, a.title, a.slug
+                LIMIT $2
+                "#,
+        )
+        .bind(group)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> Result<Option<Redirect>, sqlx::Error> {
+        sqlx::query_as::<_, Redirect>(
+            r#"
+                SELECT g.slug AS group_id, r.to_slug AS slug
+                FROM redirects r
+                INNER JOIN articles a ON a.group_id = r.to_group AND a.slug = r.to_slug
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE (
+                    $1 IS NULL
+                    OR r.from_group = $1
+                    OR r.from_group IN (SELECT id FROM groups WHERE slug = $1)
+                )
+                AND r.from_slug = $2
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                ORDER BY r.from_group
+                LIMIT 1
+                "#,
+        )
+        .bind(group)
+        .bind(slug)
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn links_due(&self, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+                SELECT l.url
+                FROM external_links l
+                JOIN articles a ON a.slug = l.slug
+                GROUP BY l.url
+                ORDER BY MAX(l.last_checked) ASC NULLS FIRST
+                LIMIT $1
+                "#,
+        )
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn broken_links(&self) -> Result<Vec<BrokenLink>, sqlx::Error> {
+        sqlx::query_as::<_, BrokenLink>(
+            r#"
+                SELECT l.slug, a.title, l.url, l.status, l.error, l.last_checked
+                FROM external_links l
+                JOIN articles a ON a.slug = l.slug
+                WHERE l.status >= 400 OR l.error IS NOT NULL
+                ORDER BY l.slug, l.url
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn sync_state(&self) -> Result<Option<SyncState>, sqlx::Error> {
+        sqlx::query_as::<_, SyncState>(
+            "SELECT commit_id, content_fingerprint, computed_at FROM sync_state",
+        )
+        .fetch_optional(self)
+        .await
+    }
+
+    async fn site_counts(&self) -> Result<SiteCounts, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+                SELECT
+                    (SELECT count(*)
+                     FROM articles a
+                     JOIN groups g ON a.group_id = g.id
+                     WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned) AS articles,
+                    (SELECT count(*) FROM groups WHERE public = TRUE) AS groups,
+                    (SELECT count(*) FROM tag_index) AS tags,
+                    (SELECT computed_at FROM sync_state) AS synced_at
+                "#,
+        )
+        .fetch_one(self)
+        .await
+    }
+
+    async fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<WebmentionSource>, sqlx::Error> {
+        sqlx::query(
+            r#"
+                SELECT
+                    a.slug,
+                    (SELECT json_group_array(l.url ORDER BY l.url) FROM external_links l WHERE l.slug = a.slug) AS links,
+                    (SELECT json_group_array(w.target ORDER BY w.target) FROM webmentions_sent w WHERE w.slug = a.slug) AS sent
+                FROM articles a
+                INNER JOIN groups g ON a.group_id = g.id
+                WHERE a.source_path IN (SELECT value FROM json_each($1))
+                AND COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND a.nonce IS NULL
+                ORDER BY a.slug
+                "#,
+        )
+        .bind(Json(paths))
+        .try_map(|row: SqliteRow| {
+            Ok(WebmentionSource {
+                slug: row.try_get("slug")?,
+                links: json_array(&row, "links")?,
+                sent: json_array(&row, "sent")?,
+            })
+        })
+        .fetch_all(self)
+        .await
+    }
+
+    async fn stale_renders(&self, current: &str) -> Result<Vec<StaleRender>, sqlx::Error> {
+        sqlx::query_as::<_, StaleRender>(
+            r#"
+                SELECT slug, title, renderer, source_path
+                FROM articles
+                WHERE renderer IS NOT $1 AND renderer IS NOT $2
+                ORDER BY slug
+                "#,
+        )
+        .bind(current)
+        .bind(RAW_HTML_RENDERER)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn orphan_articles(&self) -> Result<Vec<OrphanArticle>, sqlx::Error> {
+        sqlx::query_as::<_, OrphanArticle>(
+            r#"
+                SELECT a.slug, a.title, a.group_id, a.source_path
+                FROM articles a
+                LEFT JOIN groups g ON a.group_id = g.id
+                WHERE g.id IS NULL
+                ORDER BY a.group_id, a.slug
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn pruned_articles(&self) -> Result<Vec<PrunedArticle>, sqlx::Error> {
+        sqlx::query_as::<_, PrunedArticle>(
+            r#"
+                SELECT slug, title, group_id, created_at, source_path
+                FROM articles
+                WHERE retention_pruned
+                ORDER BY group_id, created_at DESC, slug DESC
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn group_stats(&self) -> Result<Vec<GroupStats>, sqlx::Error> {
+        sqlx::query_as::<_, GroupStats>(
+            r#"
+                SELECT s.group_id, g.slug, s.content_bytes, s.asset_bytes, s.article_count, g.quota_bytes, s.updated_at
+                FROM group_stats s
+                JOIN groups g ON g.id = s.group_id
+                ORDER BY s.group_id
+                "#,
+        )
+        .fetch_all(self)
+        .await
+    }
+
+    async fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> Result<Vec<RenderStat>, sqlx::Error> {
+        sqlx::query_as::<_, RenderStat>(
+            r#"
+                SELECT slug, title, group_id, source_path, render_ms, content_bytes, rendered_at
+                FROM articles
+                WHERE render_ms IS NOT NULL
+                ORDER BY CASE WHEN $1 THEN content_bytes ELSE render_ms END DESC, slug
+                LIMIT $2
+                "#,
+        )
+        .bind(sort == RenderSort::ContentBytes)
+        .bind(limit)
+        .fetch_all(self)
+        .await
+    }
+
+    async fn last_digest_run(&self) -> Result<Option<DigestRun>, sqlx::Error> {
+        sqlx::query_as::<_, DigestRun>(
+            r#"
+                SELECT period_end, period_start, article_count, status, error, ran_at
+                FROM digest_runs
+                ORDER BY period_end DESC
+                LIMIT 1
+                "#,
+        )
+        .fetch_optional(self)
+        .await
+    }
+}
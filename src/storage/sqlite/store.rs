@@ -0,0 +1,1422 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, FixedOffset};
+use sqlx::{Row, SqliteConnection, SqlitePool, sqlite::SqliteRow, types::Json};
+
+use crate::{
+    content::{
+        Article, ArticleRef, Group, GroupAuthor, GroupPath, GroupSettings, SocialMeta, plain_text,
+        title_sort_key,
+    },
+    error,
+    git_sync::QuotaItem,
+    storage::{
+        ArticleDump, ArticleFingerprint, ArticleShortId, ArticleSimhash, Attachment, ContentBlob,
+        DumpCounts, DumpError, DumpRecord, DumpTable, GroupDump, GroupFingerprint, RetentionChange,
+        Savepoint, Store, SyncFailure,
+        cipher::{ContentCipher, Field},
+        store::Touched,
+    },
+};
+
+use super::{json_array, timestamp};
+
+/// 按文章自身的标签和所在组的默认标签重新计算 `tags`，按首次出现的顺序去重
+///
+/// `$1` 为组 id，`$2` 不为 `NULL` 时只更新该 slug 的文章。
+const RETAG_ARTICLES: &str = r#"
+    UPDATE articles
+    SET tags = (
+        SELECT json_group_array(value ORDER BY i)
+        FROM (
+            SELECT value, MIN(i) AS i
+            FROM (
+                SELECT value, key AS i FROM json_each(articles.own_tags)
+                UNION ALL
+                SELECT value, json_array_length(articles.own_tags) + key
+                FROM json_each(COALESCE((SELECT g.default_tags FROM groups g WHERE g.id = articles.group_id), '[]'))
+            )
+            GROUP BY value
+        )
+    )
+    WHERE group_id = $1
+    AND ($2 IS NULL OR slug = $2)
+"#;
+
+/// 查询指定文章和指定组内文章的标签，`$1` 为 slug 的 JSON 数组，`$2` 为组 id 的 JSON 数组
+const TOUCHED_TAGS: &str = r#"
+    SELECT DISTINCT t.value
+    FROM articles a
+    JOIN json_each(a.tags) t
+    WHERE a.slug IN (SELECT value FROM json_each($1))
+    OR a.group_id IN (SELECT value FROM json_each($2))
+"#;
+
+/// 按组的保留规则更新文章的 `retention_pruned`，与 [`SqlxStore`](crate::storage::SqlxStore) 相同，
+/// `$1` 为组 id 的 JSON 数组，为 `NULL` 时处理全部组
+const APPLY_RETENTION: &str = r#"
+    WITH ranked AS (
+        SELECT
+            a.slug,
+            ROW_NUMBER() OVER (PARTITION BY a.group_id ORDER BY a.created_at DESC, a.slug DESC) > g.retain_max_articles
+            OR a.created_at < strftime('%Y-%m-%dT%H:%M:%f000Z', 'now', '-' || g.retain_max_age_days || ' days') AS pruned
+        FROM articles a
+        JOIN groups g ON a.group_id = g.id
+        WHERE COALESCE(a.public, g.public)
+        AND ($1 IS NULL OR a.group_id IN (SELECT value FROM json_each($1)))
+    )
+    UPDATE articles
+    SET retention_pruned = NOT retention_pruned
+    WHERE ($1 IS NULL OR group_id IN (SELECT value FROM json_each($1)))
+    AND retention_pruned <> COALESCE((SELECT r.pruned FROM ranked r WHERE r.slug = articles.slug), FALSE)
+    RETURNING group_id, slug, retention_pruned AS pruned
+"#;
+
+/// 按公开文章重新计算标签索引，`$1` 为 `NULL` 时计算全部标签，否则只计算其中的标签
+const REFRESH_TAG_INDEX: &str = r#"
+    INSERT INTO tag_index (tag, article_count, last_used)
+    SELECT t.value, COUNT(DISTINCT a.slug), MAX(a.updated_at)
+    FROM articles a
+    JOIN groups g ON a.group_id = g.id
+    JOIN json_each(a.tags) t
+    WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+    AND ($1 IS NULL OR t.value IN (SELECT value FROM json_each($1)))
+    GROUP BY t.value
+"#;
+
+/// 按组内全部文章重新计算组的大小统计，与 [`SqlxStore`](crate::storage::SqlxStore) 相同，
+/// 数值没有变化的行保留原有的 `updated_at`
+const REFRESH_GROUP_STATS: &str = concat!(
+    "
+    INSERT INTO group_stats (group_id, content_bytes, asset_bytes, article_count, updated_at)
+    SELECT g.id, COALESCE(SUM(a.source_bytes), 0), 0, COUNT(a.slug), ",
+    now!(),
+    "
+    FROM groups g
+    LEFT JOIN articles a ON a.group_id = g.id
+    -- 作为 upsert 的输入时需要 WHERE 子句避免解析歧义
+    WHERE TRUE
+    GROUP BY g.id
+    ON CONFLICT (group_id) DO UPDATE SET
+        content_bytes = excluded.content_bytes,
+        asset_bytes = excluded.asset_bytes,
+        article_count = excluded.article_count,
+        updated_at = excluded.updated_at
+    WHERE group_stats.content_bytes <> excluded.content_bytes
+    OR group_stats.asset_bytes <> excluded.asset_bytes
+    OR group_stats.article_count <> excluded.article_count
+    "
+);
+
+/// 查询第一个超出配额的组，与 [`SqlxStore`](crate::storage::SqlxStore) 相同
+const OVER_QUOTA: &str = r#"
+    SELECT s.group_id, s.content_bytes + s.asset_bytes, g.quota_bytes
+    FROM group_stats s
+    JOIN groups g ON g.id = s.group_id
+    WHERE s.content_bytes + s.asset_bytes > g.quota_bytes
+    ORDER BY s.group_id
+    LIMIT 1
+"#;
+
+/// 重建时整体替换的表，与 [`SqlxStore`](crate::storage::SqlxStore) 相同
+const TABLES: [&str; 8] = [
+    "groups",
+    "articles",
+    "redirects",
+    "article_includes",
+    "article_attachments",
+    "sync_failures",
+    "tag_index",
+    "group_stats",
+];
+
+/// SQLite 的 [`Store`] 实现
+///
+/// 写入与 [`SqlxStore`](crate::storage::SqlxStore) 相同，先排队，提交时在一个事务中执行。
+/// 调用 [`Store::clean`] 后进入重建模式：提交时在同一个事务中先清空要替换的表再写入，
+/// WAL 模式下查询在提交前始终读取旧数据，失败时整体回滚，不需要影子表。
+pub struct SqliteStore {
+    pool: SqlitePool,
+    queries: Vec<SqliteQuery>,
+    rebuild: bool,
+    touched: Touched,
+    enforce_quotas: bool,
+}
+
+type SqliteQuery =
+    sqlx::query::Query<'static, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'static>>;
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            queries: Default::default(),
+            rebuild: false,
+            touched: Default::default(),
+            enforce_quotas: false,
+        }
+    }
+
+    /// 在同一个事务中依次执行所有语句，最后重新计算涉及的标签和组的大小统计
+    ///
+    /// 调用过 [`Store::enforce_quotas`] 且有组超出配额时回滚。
+    async fn commit_live(mut self) -> Result<(), error::Error> {
+        // 开始时即获取写锁，避免读取后升级为写事务时与其他写入冲突
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let mut tags = touched_tags(&mut tx, &self.touched).await?;
+        for q in self.queries.drain(..) {
+            q.execute(&mut *tx).await?;
+        }
+        tags.extend(touched_tags(&mut tx, &self.touched).await?);
+
+        if !tags.is_empty() {
+            for q in refresh_tag_index(Some(tags.into_iter().collect())) {
+                q.execute(&mut *tx).await?;
+            }
+        }
+        for q in refresh_group_stats() {
+            q.execute(&mut *tx).await?;
+        }
+
+        if self.enforce_quotas {
+            let over: Option<(String, i64, i64)> =
+                sqlx::query_as(OVER_QUOTA).fetch_optional(&mut *tx).await?;
+            if let Some((group, used_bytes, quota_bytes)) = over {
+                // 事务随 `tx` 丢弃回滚
+                return Err(error::Error::QuotaExceeded(QuotaItem::new(
+                    group,
+                    used_bytes,
+                    quota_bytes,
+                )));
+            }
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    /// 在一个事务中清空 [`TABLES`] 后执行所有语句，出错时随事务丢弃回滚
+    async fn commit_rebuild(mut self) -> Result<(), error::Error> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        for table in TABLES {
+            sqlx::query(&format!("DELETE FROM {table}"))
+                .execute(&mut *tx)
+                .await?;
+        }
+        for q in self.queries.drain(..) {
+            q.execute(&mut *tx).await?;
+        }
+        // 重建后的标签索引和组的大小统计与从头计算的结果相同
+        for q in refresh_tag_index(None)
+            .into_iter()
+            .chain(refresh_group_stats())
+        {
+            q.execute(&mut *tx).await?;
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    /// 写入文章，参数与 [`SqlxStore`](crate::storage::SqlxStore) 中的同名方法相同
+    fn push_article(
+        &mut self,
+        article: &Article,
+        content: String,
+        summary: String,
+        summary_plain: String,
+        nonce: Option<Vec<u8>>,
+        content_oid: Option<String>,
+    ) -> &mut Self {
+        self.touched.slugs.insert(article.slug.to_owned());
+        // 加密文章的描述和图片来自明文，不保存
+        let social = nonce.is_none().then(|| SocialMeta::from_article(article));
+        let q = sqlx::query(concat!(
+            "
+            INSERT INTO articles
+                (slug, group_id, title, summary, tags, own_tags, content, created_at, updated_at, nonce, summary_plain, content_oid, content_namespaced, canonical_url, public, source_bytes, social, title_sort_key)
+            VALUES ($1, $2, $3, $4, $5, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            ON CONFLICT (slug)
+            DO UPDATE SET
+                group_id = excluded.group_id,
+                title = excluded.title,
+                summary = excluded.summary,
+                summary_plain = excluded.summary_plain,
+                tags = excluded.tags,
+                own_tags = excluded.own_tags,
+                content = excluded.content,
+                content_oid = excluded.content_oid,
+                content_namespaced = excluded.content_namespaced,
+                canonical_url = excluded.canonical_url,
+                nonce = excluded.nonce,
+                public = excluded.public,
+                source_bytes = excluded.source_bytes,
+                social = excluded.social,
+                title_sort_key = excluded.title_sort_key,
+                -- 公开设置变化时更新时间取当前时间，使缓存失效
+                updated_at = CASE
+                    WHEN articles.public IS NOT excluded.public THEN ",
+            now!(),
+            "
+                    ELSE excluded.updated_at
+                END
+            ",
+        ))
+        .bind(article.slug.to_owned())
+        .bind(article.group.to_owned())
+        .bind(article.frontmatter.title.to_owned())
+        .bind(summary)
+        .bind(Json(article.frontmatter.tags.to_owned()))
+        .bind(content)
+        .bind(timestamp(article.frontmatter.datetime))
+        .bind(timestamp(article.updated_at))
+        .bind(nonce)
+        .bind(summary_plain)
+        .bind(content_oid)
+        .bind(article.namespaced_content.to_owned())
+        .bind(article.frontmatter.canonical.to_owned())
+        .bind(article.frontmatter.public)
+        .bind(article.source_bytes as i64)
+        .bind(social.map(Json))
+        .bind(title_sort_key(&article.frontmatter.title));
+
+        self.queries.push(q);
+
+        // 合并所在组的默认标签
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(article.group.to_owned())
+            .bind(Some(article.slug.to_owned()));
+        self.queries.push(q);
+
+        // 文章所在位置已有实体，不再作为重定向来源
+        let q = sqlx::query("DELETE FROM redirects WHERE from_group = $1 AND from_slug = $2")
+            .bind(article.group.to_owned())
+            .bind(article.slug.to_owned());
+
+        self.queries.push(q);
+        self
+    }
+}
+
+impl ToOwned for SqliteStore {
+    type Owned = SqliteStore;
+
+    fn to_owned(&self) -> Self::Owned {
+        Self::new(self.pool.clone())
+    }
+}
+
+impl Store for SqliteStore {
+    /// 丢弃已排队的语句并进入重建模式，提交时先清空要替换的表
+    fn clean(&mut self) -> &mut Self {
+        self.queries.clear();
+        self.touched = Default::default();
+        self.rebuild = true;
+        self
+    }
+
+    fn remove_article(&mut self, article_ref: ArticleRef<'_>) -> &mut Self {
+        self.touched.slugs.insert(article_ref.slug.to_owned());
+        let query = sqlx::query("DELETE FROM articles WHERE slug = $1 AND group_id = $2")
+            .bind(article_ref.slug.to_owned())
+            .bind(article_ref.group.to_owned());
+        self.queries.push(query);
+
+        let query = sqlx::query("DELETE FROM external_links WHERE slug = $1")
+            .bind(article_ref.slug.to_owned());
+        self.queries.push(query);
+
+        let query =
+            sqlx::query("DELETE FROM article_attachments WHERE slug = $1 AND group_id = $2")
+                .bind(article_ref.slug.to_owned())
+                .bind(article_ref.group.to_owned());
+        self.queries.push(query);
+        self
+    }
+
+    fn upsert_group(&mut self, group: &Group) -> &mut Self {
+        self.touched.groups.insert(group.id.to_owned());
+        let q = sqlx::query(
+            r#"
+            INSERT INTO groups (id, name, public, kind, encrypted, slug_strategy, author_id, author_name, archived, slug, default_tags, category_id, category_name, nav_order, hidden, retain_max_articles, retain_max_age_days, license, quota_bytes, front_matter_schema)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            ON CONFLICT (id) DO UPDATE
+            SET
+                slug = excluded.slug,
+                license = excluded.license,
+                quota_bytes = excluded.quota_bytes,
+                front_matter_schema = excluded.front_matter_schema,
+                category_id = excluded.category_id,
+                category_name = excluded.category_name,
+                nav_order = excluded.nav_order,
+                hidden = excluded.hidden,
+                retain_max_articles = excluded.retain_max_articles,
+                retain_max_age_days = excluded.retain_max_age_days,
+                default_tags = excluded.default_tags,
+                public = excluded.public,
+                name = excluded.name,
+                kind = excluded.kind,
+                encrypted = excluded.encrypted,
+                slug_strategy = excluded.slug_strategy,
+                author_id = excluded.author_id,
+                author_name = excluded.author_name,
+                archived = excluded.archived
+            "#,
+        )
+        .bind(group.id.to_owned())
+        .bind(group.name.to_owned())
+        .bind(group.public)
+        .bind(Json(group.kind.clone()))
+        .bind(group.encrypted)
+        .bind(group.slug_strategy.as_str())
+        .bind(group.author.as_ref().map(|a| a.id.to_owned()))
+        .bind(group.author.as_ref().map(|a| a.name.to_owned()))
+        .bind(group.archived)
+        .bind(group.public_id().to_owned())
+        .bind(Json(group.default_tags.to_owned()))
+        .bind(group.category.as_ref().map(|c| c.id.to_owned()))
+        .bind(group.category.as_ref().map(|c| c.name.to_owned()))
+        .bind(group.nav_order)
+        .bind(group.hidden)
+        .bind(group.retain.and_then(|r| r.max_articles).map(|n| n as i32))
+        .bind(group.retain.and_then(|r| r.max_age_days).map(|n| n as i32))
+        .bind(group.license.clone().map(Json))
+        .bind(group.quota_bytes.map(|q| q as i64))
+        .bind(group.front_matter_schema().map(Json));
+        self.queries.push(q);
+
+        // 组内已有文章按新的默认标签重新计算，不重新渲染
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(group.id.to_owned())
+            .bind(None::<String>);
+        self.queries.push(q);
+        self
+    }
+
+    fn remove_group(&mut self, group: &Group) -> &mut Self {
+        self.touched.groups.insert(group.id.to_owned());
+        let q = sqlx::query("DELETE FROM groups WHERE id = $1").bind(group.id.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(group.id.to_owned())
+            .bind(None::<String>);
+        self.queries.push(q);
+        self
+    }
+
+    fn rename_group(&mut self, from: &GroupPath, to: &GroupPath) -> &mut Self {
+        self.touched.groups.extend([from.to_owned(), to.to_owned()]);
+        let q = sqlx::query(
+            r#"
+            UPDATE groups
+            SET id = $2
+            WHERE id = $1
+            AND NOT EXISTS (SELECT 1 FROM groups WHERE id = $2)
+            "#,
+        )
+        .bind(from.to_owned())
+        .bind(to.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query("DELETE FROM groups WHERE id = $1").bind(from.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn set_group_description(&mut self, group_id: &GroupPath, html: Option<&str>) -> &mut Self {
+        let q = sqlx::query("UPDATE groups SET description_html = $2 WHERE id = $1")
+            .bind(group_id.to_owned())
+            .bind(html.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    async fn prune_groups(&self, keep: &[GroupPath]) -> Result<Vec<GroupPath>, error::Error> {
+        let ids = sqlx::query_scalar(
+            r#"
+            DELETE FROM groups
+            WHERE id NOT IN (SELECT value FROM json_each($1))
+            AND NOT EXISTS (SELECT 1 FROM articles a WHERE a.group_id = groups.id)
+            RETURNING id
+            "#,
+        )
+        .bind(Json(keep))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn apply_retention(
+        &self,
+        groups: Option<&[GroupPath]>,
+    ) -> Result<Vec<RetentionChange>, error::Error> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let changes: Vec<RetentionChange> = sqlx::query_as(APPLY_RETENTION)
+            .bind(groups.map(Json))
+            .fetch_all(&mut *tx)
+            .await?;
+        let touched = Touched {
+            slugs: changes.iter().map(|c| c.slug.to_owned()).collect(),
+            groups: BTreeSet::new(),
+        };
+        let tags = touched_tags(&mut tx, &touched).await?;
+        if !tags.is_empty() {
+            for q in refresh_tag_index(Some(tags.into_iter().collect())) {
+                q.execute(&mut *tx).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(changes)
+    }
+
+    /// 同时写入由摘要生成的纯文本摘要
+    fn upsert_article(&mut self, article: &Article) -> &mut Self {
+        self.push_article(
+            article,
+            article.rendered_content.to_owned(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            None,
+        )
+    }
+
+    fn upsert_encrypted_article(&mut self, article: &Article) -> Result<&mut Self, error::Error> {
+        let cipher = ContentCipher::installed()?;
+        let nonce = ContentCipher::new_nonce();
+        let content = cipher.seal(&nonce, Field::Content, &article.rendered_content)?;
+        let summary = cipher.seal(&nonce, Field::Summary, &article.frontmatter.summary)?;
+        let summary_plain = cipher.seal(
+            &nonce,
+            Field::SummaryPlain,
+            &plain_text(&article.frontmatter.summary),
+        )?;
+
+        Ok(self.push_article(article, content, summary, summary_plain, Some(nonce), None))
+    }
+
+    fn upsert_blob_article(&mut self, article: &Article, oid: &str) -> &mut Self {
+        self.push_article(
+            article,
+            String::new(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            Some(oid.to_owned()),
+        )
+    }
+
+    async fn content_blob(
+        &self,
+        oid: &str,
+        renderer: &str,
+    ) -> Result<Option<ContentBlob>, error::Error> {
+        let row = sqlx::query_as(
+            r#"
+            SELECT oid, renderer, raw, content, summary, simhash
+            FROM content_blobs
+            WHERE oid = $1 AND renderer = $2
+            "#,
+        )
+        .bind(oid)
+        .bind(renderer)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    fn upsert_content_blob(&mut self, blob: &ContentBlob) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            INSERT INTO content_blobs (oid, renderer, raw, content, summary, simhash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (oid) DO UPDATE
+            SET
+                renderer = excluded.renderer,
+                content = excluded.content,
+                summary = excluded.summary,
+                simhash = excluded.simhash
+            "#,
+        )
+        .bind(blob.oid.to_owned())
+        .bind(blob.renderer.to_owned())
+        .bind(blob.raw.to_owned())
+        .bind(blob.content.to_owned())
+        .bind(blob.summary.to_owned())
+        .bind(blob.simhash);
+        self.queries.push(q);
+        self
+    }
+
+    fn prune_content_blobs(&mut self) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            DELETE FROM content_blobs
+            WHERE NOT EXISTS (SELECT 1 FROM articles a WHERE a.content_oid = content_blobs.oid)
+            "#,
+        );
+        self.queries.push(q);
+        self
+    }
+
+    async fn article_simhashes(&self) -> Result<Vec<ArticleSimhash>, error::Error> {
+        let rows = sqlx::query_as(
+            r#"
+            SELECT a.slug, a.title, a.group_id, a.source_path, b.simhash
+            FROM articles a
+            JOIN groups g ON g.id = a.group_id
+            JOIN content_blobs b ON b.oid = a.content_oid
+            WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND b.simhash IS NOT NULL
+            ORDER BY a.slug
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn group_settings(&self) -> Result<HashMap<GroupPath, GroupSettings>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, bool, String)>(
+            "SELECT id, encrypted, slug_strategy FROM groups",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, encrypted, strategy)| {
+                let settings = GroupSettings {
+                    encrypted,
+                    slug_strategy: strategy.as_str().into(),
+                };
+                (id, settings)
+            })
+            .collect())
+    }
+
+    async fn group_authors(&self) -> Result<HashMap<GroupPath, GroupAuthor>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, String, String)>(
+            "SELECT id, author_id, author_name FROM groups WHERE author_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(group, id, name)| (group, GroupAuthor { id, name }))
+            .collect())
+    }
+
+    async fn group_visibility(&self) -> Result<HashMap<GroupPath, bool>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, bool)>("SELECT id, public FROM groups")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn group_slugs(&self) -> Result<HashMap<GroupPath, String>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, String)>("SELECT id, slug FROM groups")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// 与 [`SqlxStore`](crate::storage::SqlxStore) 相同，写入时折叠重定向链路与环
+    fn upsert_redirect(&mut self, from: ArticleRef<'_>, to: ArticleRef<'_>) -> &mut Self {
+        let q = sqlx::query("DELETE FROM redirects WHERE from_group = $1 AND from_slug = $2")
+            .bind(to.group.to_owned())
+            .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            r#"
+            UPDATE redirects
+            SET to_group = $3, to_slug = $4
+            WHERE to_group = $1 AND to_slug = $2
+            "#,
+        )
+        .bind(from.group.to_owned())
+        .bind(from.slug.to_owned())
+        .bind(to.group.to_owned())
+        .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            r#"
+            INSERT INTO redirects (from_group, from_slug, to_group, to_slug)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (from_group, from_slug) DO UPDATE
+            SET
+                to_group = excluded.to_group,
+                to_slug = excluded.to_slug
+            "#,
+        )
+        .bind(from.group.to_owned())
+        .bind(from.slug.to_owned())
+        .bind(to.group.to_owned())
+        .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            "DELETE FROM redirects WHERE from_group = to_group AND from_slug = to_slug",
+        );
+        self.queries.push(q);
+        self
+    }
+
+    fn update_comment_counts(&mut self, counts: &HashMap<String, i64>) -> &mut Self {
+        if counts.is_empty() {
+            return self;
+        }
+
+        // 以 JSON 对象绑定，键为 slug
+        let q = sqlx::query(
+            r#"
+            UPDATE articles
+            SET comment_count = c.value
+            FROM json_each($1) c
+            WHERE articles.slug = c.key
+            "#,
+        )
+        .bind(Json(counts.clone()));
+
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_source(&mut self, slug: &str, path: &str, commit: &str) -> &mut Self {
+        let q =
+            sqlx::query("UPDATE articles SET source_path = $2, source_commit = $3 WHERE slug = $1")
+                .bind(slug.to_owned())
+                .bind(path.to_owned())
+                .bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn article_short_ids(&self) -> Result<Vec<ArticleShortId>, error::Error> {
+        Ok(sqlx::query_as::<_, ArticleShortId>(
+            "SELECT slug, source_path, short_id FROM articles WHERE short_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    fn set_article_short_id(&mut self, slug: &str, short_id: &str) -> &mut Self {
+        // 改名时新位置可能先于旧位置写入，先清除旧文章上的短 id 避免违反唯一约束
+        let q =
+            sqlx::query("UPDATE articles SET short_id = NULL WHERE short_id = $2 AND slug <> $1")
+                .bind(slug.to_owned())
+                .bind(short_id.to_owned());
+        self.queries.push(q);
+        let q = sqlx::query("UPDATE articles SET short_id = $2 WHERE slug = $1")
+            .bind(slug.to_owned())
+            .bind(short_id.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_renderer(&mut self, slug: &str, renderer: &str) -> &mut Self {
+        let q = sqlx::query("UPDATE articles SET renderer = $2 WHERE slug = $1")
+            .bind(slug.to_owned())
+            .bind(renderer.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_render_stats(
+        &mut self,
+        slug: &str,
+        render_ms: i64,
+        content_bytes: i64,
+    ) -> &mut Self {
+        let q = sqlx::query(concat!(
+            "UPDATE articles SET render_ms = $2, content_bytes = $3, rendered_at = ",
+            now!(),
+            " WHERE slug = $1",
+        ))
+        .bind(slug.to_owned())
+        .bind(render_ms)
+        .bind(content_bytes);
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_links(&mut self, slug: &str, urls: &[String]) -> &mut Self {
+        let q = sqlx::query(
+            "DELETE FROM external_links WHERE slug = $1 AND url NOT IN (SELECT value FROM json_each($2))",
+        )
+        .bind(slug.to_owned())
+        .bind(Json(urls.to_vec()));
+        self.queries.push(q);
+
+        // `WHERE true` 避免 `ON CONFLICT` 被解析为连接条件
+        let q = sqlx::query(
+            r#"
+            INSERT INTO external_links (slug, url)
+            SELECT $1, value FROM json_each($2) WHERE true
+            ON CONFLICT (slug, url) DO NOTHING
+            "#,
+        )
+        .bind(slug.to_owned())
+        .bind(Json(urls.to_vec()));
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_attachments(
+        &mut self,
+        article_ref: ArticleRef<'_>,
+        attachments: &[Attachment],
+    ) -> &mut Self {
+        let q = sqlx::query("DELETE FROM article_attachments WHERE slug = $1")
+            .bind(article_ref.slug.to_owned());
+        self.queries.push(q);
+
+        if !attachments.is_empty() {
+            let q = sqlx::query(
+                r#"
+                INSERT INTO article_attachments (slug, group_id, position, url_or_path, kind, link_text)
+                SELECT $1, $2, key, value ->> 'url_or_path', value ->> 'kind', value ->> 'link_text'
+                FROM json_each($3) WHERE true
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(article_ref.slug.to_owned())
+            .bind(article_ref.group.to_owned())
+            .bind(Json(attachments.to_vec()));
+            self.queries.push(q);
+        }
+        self
+    }
+
+    fn record_link_status(
+        &mut self,
+        url: &str,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(concat!(
+            r#"
+            UPDATE external_links
+            SET status = $2, error = $3, last_checked = "#,
+            now!(),
+            r#"
+            WHERE url = $1
+            "#,
+        ))
+        .bind(url.to_owned())
+        .bind(status)
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn prune_links(&mut self) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            DELETE FROM external_links
+            WHERE NOT EXISTS (SELECT 1 FROM articles a WHERE a.slug = external_links.slug)
+            "#,
+        );
+        self.queries.push(q);
+        self
+    }
+
+    fn record_webmention(
+        &mut self,
+        slug: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(concat!(
+            r#"
+            INSERT INTO webmentions_sent (slug, target, endpoint, status, error)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (slug, target) DO UPDATE
+            SET
+                endpoint = excluded.endpoint,
+                status = excluded.status,
+                error = excluded.error,
+                sent_at = "#,
+            now!(),
+            r#"
+            "#,
+        ))
+        .bind(slug.to_owned())
+        .bind(target.to_owned())
+        .bind(endpoint.map(str::to_owned))
+        .bind(status)
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn remove_webmention(&mut self, slug: &str, target: &str) -> &mut Self {
+        let q = sqlx::query("DELETE FROM webmentions_sent WHERE slug = $1 AND target = $2")
+            .bind(slug.to_owned())
+            .bind(target.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn record_digest_run(
+        &mut self,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        article_count: i64,
+        status: &str,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(concat!(
+            r#"
+            INSERT INTO digest_runs (period_end, period_start, article_count, status, error, ran_at)
+            VALUES ($1, $2, $3, $4, $5, "#,
+            now!(),
+            r#")
+            ON CONFLICT (period_end) DO UPDATE
+            SET
+                period_start = excluded.period_start,
+                article_count = excluded.article_count,
+                status = excluded.status,
+                error = excluded.error,
+                ran_at = excluded.ran_at
+            "#,
+        ))
+        .bind(timestamp(period_end))
+        .bind(timestamp(period_start))
+        .bind(article_count)
+        .bind(status.to_owned())
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_includes(&mut self, path: &str, includes: &[String]) -> &mut Self {
+        let q = sqlx::query("DELETE FROM article_includes WHERE article_path = $1")
+            .bind(path.to_owned());
+        self.queries.push(q);
+
+        if !includes.is_empty() {
+            let q = sqlx::query(
+                r#"
+                INSERT INTO article_includes (article_path, include_path)
+                SELECT $1, value FROM json_each($2) WHERE true
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(path.to_owned())
+            .bind(Json(includes.to_vec()));
+            self.queries.push(q);
+        }
+        self
+    }
+
+    async fn include_dependents(&self, paths: &[String]) -> Result<Vec<String>, error::Error> {
+        let rows = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT article_path
+            FROM article_includes
+            WHERE include_path IN (SELECT value FROM json_each($1))
+            ORDER BY article_path
+            "#,
+        )
+        .bind(Json(paths))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        Savepoint(self.queries.len())
+    }
+
+    fn rollback_to(&mut self, savepoint: Savepoint) -> &mut Self {
+        self.queries.truncate(savepoint.0);
+        self
+    }
+
+    fn record_sync_failure(&mut self, path: &str, reason: &str, commit: &str) -> &mut Self {
+        let q = sqlx::query(concat!(
+            r#"
+            INSERT INTO sync_failures (path, reason, commit_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (path) DO UPDATE
+            SET
+                reason = excluded.reason,
+                commit_id = excluded.commit_id,
+                failed_at = "#,
+            now!(),
+            r#"
+            "#,
+        ))
+        .bind(path.to_owned())
+        .bind(reason.to_owned())
+        .bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn clear_sync_failure(&mut self, path: &str) -> &mut Self {
+        let q = sqlx::query("DELETE FROM sync_failures WHERE path = $1").bind(path.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn retarget_sync_failures(&mut self, commit: &str) -> &mut Self {
+        let q = sqlx::query("UPDATE sync_failures SET commit_id = $1").bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn sync_failures(&self) -> Result<Vec<SyncFailure>, error::Error> {
+        let rows = sqlx::query_as(
+            "SELECT path, reason, commit_id, failed_at FROM sync_failures ORDER BY path",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn fingerprint_rows(
+        &self,
+    ) -> Result<(Vec<GroupFingerprint>, Vec<ArticleFingerprint>), error::Error> {
+        let groups = sqlx::query(
+            r#"
+            SELECT id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived, default_tags, kind, category_id, category_name, nav_order, hidden, retain_max_articles, retain_max_age_days, license, quota_bytes, front_matter_schema, description_html
+            FROM groups
+            "#,
+        )
+        .try_map(|row: SqliteRow| {
+            let dump = group_dump(row)?;
+            Ok(GroupFingerprint {
+                id: dump.id,
+                slug: dump.slug,
+                name: dump.name,
+                public: dump.public,
+                encrypted: dump.encrypted,
+                slug_strategy: dump.slug_strategy,
+                author_id: dump.author_id,
+                author_name: dump.author_name,
+                archived: dump.archived,
+                default_tags: dump.default_tags,
+                kind: dump.kind,
+                category_id: dump.category_id,
+                category_name: dump.category_name,
+                nav_order: dump.nav_order,
+                hidden: dump.hidden,
+                retain_max_articles: dump.retain_max_articles,
+                retain_max_age_days: dump.retain_max_age_days,
+                license: dump.license,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rows: Vec<(GroupPath, String, Option<String>, DateTime<FixedOffset>)> = sqlx::query_as(
+            r#"
+            SELECT a.group_id, a.slug, a.content_oid, a.updated_at
+            FROM articles a
+            INNER JOIN groups g ON a.group_id = g.id
+            WHERE COALESCE(a.public, g.public)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let articles = rows
+            .into_iter()
+            .map(
+                |(group_id, slug, content_oid, updated_at)| ArticleFingerprint {
+                    group_id,
+                    slug,
+                    content_oid,
+                    updated_at: updated_at.timestamp_millis(),
+                },
+            )
+            .collect();
+
+        Ok((groups, articles))
+    }
+
+    fn record_sync_state(&mut self, commit: &str, fingerprint: &str) -> &mut Self {
+        let q = sqlx::query(concat!(
+            r#"
+            INSERT INTO sync_state (commit_id, content_fingerprint)
+            VALUES ($1, $2)
+            ON CONFLICT (id) DO UPDATE
+            SET
+                commit_id = excluded.commit_id,
+                content_fingerprint = excluded.content_fingerprint,
+                computed_at = "#,
+            now!(),
+            r#"
+            "#,
+        ))
+        .bind(commit.to_owned())
+        .bind(fingerprint.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn dump_page(
+        &self,
+        table: DumpTable,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DumpRecord>, error::Error> {
+        let (after, limit) = (after.map(str::to_owned), limit as i64);
+        let records = match table {
+            DumpTable::Groups => sqlx::query(
+                r#"
+                SELECT id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived,
+                    default_tags, kind, category_id, category_name, nav_order, hidden,
+                    retain_max_articles, retain_max_age_days, license, quota_bytes, front_matter_schema,
+                    description_html
+                FROM groups
+                WHERE $1 IS NULL OR id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .try_map(group_dump)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::Group)
+            .collect(),
+            DumpTable::Articles => sqlx::query(
+                r#"
+                SELECT slug, group_id, title, summary, summary_plain, tags, own_tags, content, content_oid,
+                    content_namespaced, nonce, comment_count, created_at, updated_at, source_path,
+                    source_commit, renderer, canonical_url, public, short_id, retention_pruned,
+                    source_bytes, social
+                FROM articles
+                WHERE $1 IS NULL OR slug > $1
+                ORDER BY slug
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .try_map(article_dump)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::Article)
+            .collect(),
+            DumpTable::ContentBlobs => sqlx::query_as(
+                r#"
+                SELECT oid, renderer, raw, content, summary, simhash
+                FROM content_blobs
+                WHERE $1 IS NULL OR oid > $1
+                ORDER BY oid
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::ContentBlob)
+            .collect(),
+        };
+
+        Ok(records)
+    }
+
+    async fn load_dump<I>(&self, records: I, truncate: bool) -> Result<DumpCounts, error::Error>
+    where
+        I: Iterator<Item = Result<DumpRecord, DumpError>> + Send,
+    {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+        if truncate {
+            for table in ["articles", "groups", "content_blobs"] {
+                sqlx::query(&format!("DELETE FROM {table}"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        let mut counts = DumpCounts::default();
+        for record in records {
+            // 出错时事务随 `tx` 丢弃回滚
+            let record = record?;
+            upsert_record(&record).execute(&mut *tx).await?;
+            counts.add(&record);
+        }
+        for q in refresh_tag_index(None)
+            .into_iter()
+            .chain(refresh_group_stats())
+        {
+            q.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+
+        Ok(counts)
+    }
+
+    fn enforce_quotas(&mut self) -> &mut Self {
+        self.enforce_quotas = true;
+        self
+    }
+
+    async fn commit(self) -> Result<(), error::Error> {
+        if self.rebuild {
+            self.commit_rebuild().await
+        } else {
+            self.commit_live().await
+        }
+    }
+}
+
+fn group_dump(row: SqliteRow) -> Result<GroupDump, sqlx::Error> {
+    Ok(GroupDump {
+        id: row.try_get("id")?,
+        slug: row.try_get("slug")?,
+        name: row.try_get("name")?,
+        public: row.try_get("public")?,
+        encrypted: row.try_get("encrypted")?,
+        slug_strategy: row.try_get("slug_strategy")?,
+        author_id: row.try_get("author_id")?,
+        author_name: row.try_get("author_name")?,
+        archived: row.try_get("archived")?,
+        default_tags: json_array(&row, "default_tags")?,
+        kind: row
+            .try_get::<Option<Json<serde_json::Value>>, _>("kind")?
+            .map_or(serde_json::Value::Null, |kind| kind.0),
+        category_id: row.try_get("category_id")?,
+        category_name: row.try_get("category_name")?,
+        nav_order: row.try_get("nav_order")?,
+        hidden: row.try_get("hidden")?,
+        retain_max_articles: row.try_get("retain_max_articles")?,
+        retain_max_age_days: row.try_get("retain_max_age_days")?,
+        license: row
+            .try_get::<Option<Json<serde_json::Value>>, _>("license")?
+            .map(|license| license.0),
+        quota_bytes: row.try_get("quota_bytes")?,
+        front_matter_schema: row
+            .try_get::<Option<Json<serde_json::Value>>, _>("front_matter_schema")?
+            .map(|schema| schema.0),
+        description_html: row.try_get("description_html")?,
+    })
+}
+
+fn article_dump(row: SqliteRow) -> Result<ArticleDump, sqlx::Error> {
+    Ok(ArticleDump {
+        slug: row.try_get("slug")?,
+        group_id: row.try_get("group_id")?,
+        title: row.try_get("title")?,
+        summary: row.try_get("summary")?,
+        summary_plain: row.try_get("summary_plain")?,
+        tags: json_array(&row, "tags")?,
+        own_tags: json_array(&row, "own_tags")?,
+        content: row.try_get("content")?,
+        content_oid: row.try_get("content_oid")?,
+        content_namespaced: row.try_get("content_namespaced")?,
+        nonce: row.try_get("nonce")?,
+        comment_count: row.try_get("comment_count")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        source_path: row.try_get("source_path")?,
+        source_commit: row.try_get("source_commit")?,
+        renderer: row.try_get("renderer")?,
+        canonical_url: row.try_get("canonical_url")?,
+        public: row.try_get("public")?,
+        short_id: row.try_get("short_id")?,
+        retention_pruned: row.try_get("retention_pruned")?,
+        source_bytes: row.try_get("source_bytes")?,
+        social: row
+            .try_get::<Option<Json<serde_json::Value>>, _>("social")?
+            .map(|social| social.0),
+    })
+}
+
+/// 查询 `touched` 中的文章和组内文章当前的标签
+async fn touched_tags(
+    conn: &mut SqliteConnection,
+    touched: &Touched,
+) -> Result<BTreeSet<String>, sqlx::Error> {
+    if touched.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let tags: Vec<String> = sqlx::query_scalar(TOUCHED_TAGS)
+        .bind(Json(&touched.slugs))
+        .bind(Json(&touched.groups))
+        .fetch_all(conn)
+        .await?;
+    Ok(tags.into_iter().collect())
+}
+
+/// 重新计算 `tags` 在标签索引中的行，`tags` 为 [`None`] 时重新计算全部标签
+fn refresh_tag_index(tags: Option<Vec<String>>) -> [SqliteQuery; 2] {
+    let tags = tags.map(Json);
+    [
+        sqlx::query(
+            "DELETE FROM tag_index WHERE $1 IS NULL OR tag IN (SELECT value FROM json_each($1))",
+        )
+        .bind(tags.clone()),
+        sqlx::query(REFRESH_TAG_INDEX).bind(tags),
+    ]
+}
+
+/// 重新计算全部组的大小统计，删除已不存在的组的行
+fn refresh_group_stats() -> [SqliteQuery; 2] {
+    [
+        sqlx::query("DELETE FROM group_stats WHERE group_id NOT IN (SELECT id FROM groups)"),
+        sqlx::query(REFRESH_GROUP_STATS),
+    ]
+}
+
+/// 按导出的记录插入或更新一行，所有列取记录中的值
+fn upsert_record(record: &DumpRecord) -> SqliteQuery {
+    match record.clone() {
+        DumpRecord::Group(g) => sqlx::query(
+            r#"
+            INSERT INTO groups
+                (id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived,
+                default_tags, kind, category_id, category_name, nav_order, hidden,
+                retain_max_articles, retain_max_age_days, license, description_html, quota_bytes,
+                front_matter_schema)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+            ON CONFLICT (id) DO UPDATE SET
+                slug = excluded.slug,
+                name = excluded.name,
+                public = excluded.public,
+                encrypted = excluded.encrypted,
+                slug_strategy = excluded.slug_strategy,
+                author_id = excluded.author_id,
+                author_name = excluded.author_name,
+                archived = excluded.archived,
+                default_tags = excluded.default_tags,
+                kind = excluded.kind,
+                category_id = excluded.category_id,
+                category_name = excluded.category_name,
+                nav_order = excluded.nav_order,
+                hidden = excluded.hidden,
+                retain_max_articles = excluded.retain_max_articles,
+                retain_max_age_days = excluded.retain_max_age_days,
+                license = excluded.license,
+                description_html = excluded.description_html,
+                quota_bytes = excluded.quota_bytes,
+                front_matter_schema = excluded.front_matter_schema
+            "#,
+        )
+        .bind(g.id)
+        .bind(g.slug)
+        .bind(g.name)
+        .bind(g.public)
+        .bind(g.encrypted)
+        .bind(g.slug_strategy)
+        .bind(g.author_id)
+        .bind(g.author_name)
+        .bind(g.archived)
+        .bind(Json(g.default_tags))
+        .bind(Json(g.kind))
+        .bind(g.category_id)
+        .bind(g.category_name)
+        .bind(g.nav_order)
+        .bind(g.hidden)
+        .bind(g.retain_max_articles)
+        .bind(g.retain_max_age_days)
+        .bind(g.license.map(Json))
+        .bind(g.description_html)
+        .bind(g.quota_bytes)
+        .bind(g.front_matter_schema.map(Json)),
+
+        DumpRecord::Article(a) => sqlx::query(
+            r#"
+            INSERT INTO articles
+                (slug, group_id, title, summary, summary_plain, tags, own_tags, content, content_oid,
+                content_namespaced, nonce, comment_count, created_at, updated_at, source_path,
+                source_commit, renderer, canonical_url, public, short_id, retention_pruned, source_bytes,
+                social, title_sort_key)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+            ON CONFLICT (slug) DO UPDATE SET
+                group_id = excluded.group_id,
+                title = excluded.title,
+                summary = excluded.summary,
+                summary_plain = excluded.summary_plain,
+                tags = excluded.tags,
+                own_tags = excluded.own_tags,
+                content = excluded.content,
+                content_oid = excluded.content_oid,
+                content_namespaced = excluded.content_namespaced,
+                nonce = excluded.nonce,
+                comment_count = excluded.comment_count,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                source_path = excluded.source_path,
+                source_commit = excluded.source_commit,
+                renderer = excluded.renderer,
+                canonical_url = excluded.canonical_url,
+                public = excluded.public,
+                short_id = excluded.short_id,
+                retention_pruned = excluded.retention_pruned,
+                source_bytes = excluded.source_bytes,
+                social = excluded.social,
+                title_sort_key = excluded.title_sort_key
+            "#,
+        )
+        .bind(a.slug)
+        .bind(a.group_id)
+        .bind(a.title.to_owned())
+        .bind(a.summary)
+        .bind(a.summary_plain)
+        .bind(Json(a.tags))
+        .bind(Json(a.own_tags))
+        .bind(a.content)
+        .bind(a.content_oid)
+        .bind(a.content_namespaced)
+        .bind(a.nonce)
+        .bind(a.comment_count)
+        .bind(timestamp(a.created_at))
+        .bind(timestamp(a.updated_at))
+        .bind(a.source_path)
+        .bind(a.source_commit)
+        .bind(a.renderer)
+        .bind(a.canonical_url)
+        .bind(a.public)
+        .bind(a.short_id)
+        .bind(a.retention_pruned)
+        .bind(a.source_bytes)
+        .bind(a.social.map(Json))
+        // 排序键由标题生成，不写入导出文件
+        .bind(title_sort_key(&a.title)),
+
+        DumpRecord::ContentBlob(b) => sqlx::query(
+            r#"
+            INSERT INTO content_blobs (oid, renderer, raw, content, summary, simhash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (oid) DO UPDATE SET
+                renderer = excluded.renderer,
+                raw = excluded.raw,
+                content = excluded.content,
+                summary = excluded.summary,
+                simhash = excluded.simhash
+            "#,
+        )
+        .bind(b.oid)
+        .bind(b.renderer)
+        .bind(b.raw)
+        .bind(b.content)
+        .bind(b.summary)
+        .bind(b.simhash),
+    }
+}
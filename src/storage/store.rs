@@ -1,42 +1,539 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, FixedOffset};
 use sqlx::types::Json;
 
 use crate::{
-    content::{Article, ArticleRef, Group},
+    content::{
+        Article, ArticleRef, Group, GroupAuthor, GroupPath, GroupSettings, SocialMeta, plain_text,
+        title_sort_key,
+    },
     error,
-    storage::DBPool,
+    git_sync::QuotaItem,
+    storage::{
+        ArticleFingerprint, ArticleShortId, ArticleSimhash, Attachment, ContentBlob, DBPool,
+        DumpCounts, DumpError, DumpRecord, DumpTable, GroupFingerprint, RetentionChange,
+        SyncFailure,
+        cipher::{ContentCipher, Field},
+        postgres::begin_write,
+    },
 };
 
+/// [`Store::savepoint`] 记录的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Savepoint(pub(super) usize);
+
+/// 本次写入涉及的文章和组
+///
+/// 提交时只重新计算这些文章在写入前后的标签在标签索引中的行。
+/// 回滚到 [`Savepoint`] 时不撤销记录，多计算的标签结果不变。
+#[derive(Debug, Clone, Default)]
+pub(super) struct Touched {
+    pub(super) slugs: BTreeSet<String>,
+    pub(super) groups: BTreeSet<GroupPath>,
+}
+
+impl Touched {
+    pub(super) fn is_empty(&self) -> bool {
+        self.slugs.is_empty() && self.groups.is_empty()
+    }
+}
+
 /// 提供文章和分组的数据库操作接口
 ///
 /// 支持增删改查，包括文章的 [`ArticleRef`]、[`Article`] 和组的 [`Group`]。
 pub trait Store: ToOwned + Send + Sync {
-    /// 清空所有文章和组
+    /// 清空所有文章、组和重定向，之后的写入在提交时整体替换现有数据
     fn clean(&mut self) -> &mut Self;
     /// 插入或更新组
     fn upsert_group(&mut self, group: &Group) -> &mut Self;
     /// 删除组
     fn remove_group(&mut self, group: &Group) -> &mut Self;
+    /// 将组迁移到新的 id，保留原有属性；新 id 已存在时删除旧组
+    fn rename_group(&mut self, from: &GroupPath, to: &GroupPath) -> &mut Self;
+    /// 设置组说明渲染后的 HTML，为 `None` 时清除；组不存在时不做任何事
+    fn set_group_description(&mut self, group_id: &GroupPath, html: Option<&str>) -> &mut Self;
+    /// 立即删除不在 `keep` 中且没有文章的组，返回被删除的组 id
+    fn prune_groups(
+        &self,
+        keep: &[GroupPath],
+    ) -> impl std::future::Future<Output = Result<Vec<GroupPath>, error::Error>>;
+    /// 按组的保留规则立即更新组内文章的 `retention_pruned`，返回状态发生变化的文章，不经过 [`Store::commit`]
+    ///
+    /// `groups` 为 [`None`] 时处理全部组。组内公开的文章按 `created_at`、`slug` 从新到旧排列，
+    /// 超出 `retain_max_articles` 或创建时间早于 `retain_max_age_days` 天前的文章下线，其余文章重新上线；
+    /// 未公开的文章不占用保留数量。受影响的标签在同一事务中重新计算标签索引。
+    fn apply_retention(
+        &self,
+        groups: Option<&[GroupPath]>,
+    ) -> impl std::future::Future<Output = Result<Vec<RetentionChange>, error::Error>>;
     /// 插入或更新文章
     fn upsert_article(&mut self, article: &Article) -> &mut Self;
+    /// 加密正文和摘要后插入或更新文章，未配置密钥时返回错误
+    fn upsert_encrypted_article(&mut self, article: &Article) -> Result<&mut Self, error::Error>;
+    /// 插入或更新文章，正文不单独存储，读取时使用 `oid` 对应的 [`ContentBlob`]
+    fn upsert_blob_article(&mut self, article: &Article, oid: &str) -> &mut Self;
+    /// 查询 `oid` 对应、由 `renderer` 版本渲染的 [`ContentBlob`]
+    fn content_blob(
+        &self,
+        oid: &str,
+        renderer: &str,
+    ) -> impl std::future::Future<Output = Result<Option<ContentBlob>, error::Error>>;
+    /// 插入或更新 [`ContentBlob`]
+    fn upsert_content_blob(&mut self, blob: &ContentBlob) -> &mut Self;
+    /// 删除没有文章引用的 [`ContentBlob`]
+    fn prune_content_blobs(&mut self) -> &mut Self;
+    /// 查询已提交的公开文章中记录了 [`ContentBlob::simhash`] 的文章，按 slug 排序
+    fn article_simhashes(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<ArticleSimhash>, error::Error>>;
+    /// 查询已存储的各组的 [`GroupSettings`]
+    fn group_settings(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashMap<GroupPath, GroupSettings>, error::Error>>;
+    /// 查询已存储的各组的默认作者，键为组 id
+    fn group_authors(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashMap<GroupPath, GroupAuthor>, error::Error>>;
+    /// 查询已存储的各组对外使用的 id，键为组目录路径
+    fn group_slugs(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashMap<GroupPath, String>, error::Error>>;
+    /// 查询已存储的各组是否公开，键为组目录路径
+    fn group_visibility(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashMap<GroupPath, bool>, error::Error>>;
     /// 删除指定的文章
     fn remove_article(&mut self, article_ref: ArticleRef<'_>) -> &mut Self;
+    /// 记录文章从旧位置到新位置的重定向
+    fn upsert_redirect(&mut self, from: ArticleRef<'_>, to: ArticleRef<'_>) -> &mut Self;
+    /// 按 slug 更新文章评论数
+    fn update_comment_counts(&mut self, counts: &HashMap<String, i64>) -> &mut Self;
+    /// 记录文章的源文件路径和写入该文章的同步的目标提交
+    fn set_article_source(&mut self, slug: &str, path: &str, commit: &str) -> &mut Self;
+    /// 查询已分配短 id 的文章
+    fn article_short_ids(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<ArticleShortId>, error::Error>>;
+    /// 设置文章的短 id，该短 id 属于其他文章时先从其他文章上清除
+    fn set_article_short_id(&mut self, slug: &str, short_id: &str) -> &mut Self;
+    /// 替换文章引用的外部链接，保留仍存在链接的首次发现时间和检查结果
+    fn replace_links(&mut self, slug: &str, urls: &[String]) -> &mut Self;
+    /// 替换文章的附件，与文章的其他写入在同一个事务中提交
+    fn replace_attachments(
+        &mut self,
+        article_ref: ArticleRef<'_>,
+        attachments: &[Attachment],
+    ) -> &mut Self;
+    /// 记录外部链接的检查结果
+    fn record_link_status(
+        &mut self,
+        url: &str,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self;
+    /// 记录渲染文章使用的渲染器标识，见 [`Renderer::identity`](crate::content::Renderer::identity)
+    fn set_article_renderer(&mut self, slug: &str, renderer: &str) -> &mut Self;
+    /// 记录文章本次渲染的耗时和渲染后正文的字节数，渲染时间取当前时间
+    fn set_article_render_stats(
+        &mut self,
+        slug: &str,
+        render_ms: i64,
+        content_bytes: i64,
+    ) -> &mut Self;
+    /// 删除已不存在文章的外部链接
+    fn prune_links(&mut self) -> &mut Self;
+    /// 记录向 `target` 发送 webmention 的结果
+    fn record_webmention(
+        &mut self,
+        slug: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self;
+    /// 删除文章已不再链接的目标的发送记录
+    fn remove_webmention(&mut self, slug: &str, target: &str) -> &mut Self;
+    /// 记录每周摘要的执行结果，同一周期已有记录时覆盖，执行时间取当前时间
+    fn record_digest_run(
+        &mut self,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        article_count: i64,
+        status: &str,
+        error: Option<&str>,
+    ) -> &mut Self;
+    /// 替换文章文件直接或间接引用的文件路径
+    fn replace_includes(&mut self, path: &str, includes: &[String]) -> &mut Self;
+    /// 查询引用了 `paths` 中任一文件的文章文件路径
+    fn include_dependents(
+        &self,
+        paths: &[String],
+    ) -> impl std::future::Future<Output = Result<Vec<String>, error::Error>>;
+    /// 记录当前已排队写入的位置
+    fn savepoint(&self) -> Savepoint;
+    /// 丢弃 `savepoint` 之后排队的写入
+    fn rollback_to(&mut self, savepoint: Savepoint) -> &mut Self;
+    /// 记录处理失败的文件，`commit` 为重试时读取该文件的提交
+    fn record_sync_failure(&mut self, path: &str, reason: &str, commit: &str) -> &mut Self;
+    /// 删除文件的失败记录
+    fn clear_sync_failure(&mut self, path: &str) -> &mut Self;
+    /// 将所有失败记录的提交更新为 `commit`
+    fn retarget_sync_failures(&mut self, commit: &str) -> &mut Self;
+    /// 查询处理失败的文件，按路径排序
+    fn sync_failures(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<SyncFailure>, error::Error>>;
+    /// 查询参与内容指纹计算的组和公开文章，未排序
+    ///
+    /// 文章按公开设置判断，不考虑保留规则：保留规则的结果与同步时间有关，无法由仓库内容计算。
+    #[allow(clippy::type_complexity)]
+    fn fingerprint_rows(
+        &self,
+    ) -> impl std::future::Future<
+        Output = Result<(Vec<GroupFingerprint>, Vec<ArticleFingerprint>), error::Error>,
+    >;
+    /// 记录同步后的内容指纹及其对应的提交
+    fn record_sync_state(&mut self, commit: &str, fingerprint: &str) -> &mut Self;
+    /// 按主键升序查询 `table` 中主键大于 `after` 的最多 `limit` 行，用于导出
+    fn dump_page(
+        &self,
+        table: DumpTable,
+        after: Option<&str>,
+        limit: usize,
+    ) -> impl std::future::Future<Output = Result<Vec<DumpRecord>, error::Error>>;
+    /// 在一个事务中逐行插入或更新导入的记录并重新计算标签索引，立即执行，不经过 [`Store::commit`]
+    ///
+    /// `truncate` 为 `true` 时先清空组、文章和渲染结果。`records` 中出现错误时整体回滚。
+    fn load_dump<I>(
+        &self,
+        records: I,
+        truncate: bool,
+    ) -> impl std::future::Future<Output = Result<DumpCounts, error::Error>>
+    where
+        I: Iterator<Item = Result<DumpRecord, DumpError>> + Send;
+    /// 提交时检查组的配额，有组超出配额时回滚全部更改并返回 [`error::Error::QuotaExceeded`]
+    ///
+    /// 只影响增量提交，重建不检查配额。
+    fn enforce_quotas(&mut self) -> &mut Self;
     /// 提交更改
     fn commit(self) -> impl std::future::Future<Output = Result<(), error::Error>>;
 }
 
+/// 按文章自身的标签和所在组的默认标签重新计算 `tags`，按首次出现的顺序去重
+///
+/// `$1` 为组 id，`$2` 不为 `NULL` 时只更新该 slug 的文章。
+const RETAG_ARTICLES: &str = r#"
+    UPDATE articles a
+    SET tags = ARRAY(
+        SELECT t
+        FROM unnest(
+            a.own_tags || COALESCE((SELECT g.default_tags FROM groups g WHERE g.id = a.group_id), '{}')
+        ) WITH ORDINALITY AS u(t, i)
+        GROUP BY t
+        ORDER BY MIN(i)
+    )
+    WHERE a.group_id = $1
+    AND ($2::TEXT IS NULL OR a.slug = $2)
+"#;
+
+/// 查询指定文章和指定组内文章的标签，`$1` 为 slug 列表，`$2` 为组 id 列表
+const TOUCHED_TAGS: &str = r#"
+    SELECT DISTINCT t.tag
+    FROM articles a
+    CROSS JOIN LATERAL unnest(a.tags) AS t(tag)
+    WHERE a.slug = ANY($1) OR a.group_id = ANY($2)
+"#;
+
+/// 按组的保留规则更新文章的 `retention_pruned`，返回状态发生变化的文章，`$1` 为 `NULL` 时处理全部组
+///
+/// `ranked` 只包含按公开设置公开的文章，不在其中的文章不下线。
+const APPLY_RETENTION: &str = r#"
+    WITH ranked AS (
+        SELECT
+            a.slug,
+            ROW_NUMBER() OVER (PARTITION BY a.group_id ORDER BY a.created_at DESC, a.slug DESC) > g.retain_max_articles
+            OR a.created_at < now() - make_interval(days => g.retain_max_age_days) AS pruned
+        FROM articles a
+        JOIN groups g ON a.group_id = g.id
+        WHERE COALESCE(a.public, g.public)
+        AND ($1::TEXT[] IS NULL OR a.group_id = ANY($1))
+    )
+    UPDATE articles a
+    SET retention_pruned = NOT a.retention_pruned
+    WHERE ($1::TEXT[] IS NULL OR a.group_id = ANY($1))
+    AND a.retention_pruned <> COALESCE((SELECT r.pruned FROM ranked r WHERE r.slug = a.slug), FALSE)
+    RETURNING a.group_id, a.slug, a.retention_pruned AS pruned
+"#;
+
+/// 按公开文章重新计算标签索引，`$1` 为 `NULL` 时计算全部标签，否则只计算其中的标签
+const REFRESH_TAG_INDEX: &str = r#"
+    INSERT INTO tag_index (tag, article_count, last_used)
+    SELECT t.tag, COUNT(DISTINCT a.slug), MAX(a.updated_at)
+    FROM articles a
+    JOIN groups g ON a.group_id = g.id
+    CROSS JOIN LATERAL unnest(a.tags) AS t(tag)
+    WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+    AND ($1::TEXT[] IS NULL OR t.tag = ANY($1))
+    GROUP BY t.tag
+"#;
+
+/// 按组内全部文章重新计算组的大小统计，数值没有变化的行保留原有的 `updated_at`
+const REFRESH_GROUP_STATS: &str = r#"
+    INSERT INTO group_stats (group_id, content_bytes, asset_bytes, article_count, updated_at)
+    SELECT g.id, COALESCE(SUM(a.source_bytes), 0)::BIGINT, 0, COUNT(a.slug), now()
+    FROM groups g
+    LEFT JOIN articles a ON a.group_id = g.id
+    GROUP BY g.id
+    ON CONFLICT (group_id) DO UPDATE SET
+        content_bytes = EXCLUDED.content_bytes,
+        asset_bytes = EXCLUDED.asset_bytes,
+        article_count = EXCLUDED.article_count,
+        updated_at = EXCLUDED.updated_at
+    WHERE (group_stats.content_bytes, group_stats.asset_bytes, group_stats.article_count)
+        IS DISTINCT FROM (EXCLUDED.content_bytes, EXCLUDED.asset_bytes, EXCLUDED.article_count)
+"#;
+
+/// 查询第一个超出配额的组，返回组目录路径、计入配额的字节数和配额
+const OVER_QUOTA: &str = r#"
+    SELECT s.group_id, s.content_bytes + s.asset_bytes, g.quota_bytes
+    FROM group_stats s
+    JOIN groups g ON g.id = s.group_id
+    WHERE s.content_bytes + s.asset_bytes > g.quota_bytes
+    ORDER BY s.group_id
+    LIMIT 1
+"#;
+
+/// 线上数据所在的 schema
+const LIVE_SCHEMA: &str = "gitnote";
+/// 重建时写入的影子 schema，由 `sql/02-SHADOW_SCHEMA.sql` 创建
+const SHADOW_SCHEMA: &str = "gitnote_shadow";
+/// 重建时整体替换的表
+///
+/// `external_links` 记录链接的检查历史，`content_blobs` 供重建时复用渲染结果，
+/// 两者不参与替换，重建时写入线上表。`sync_failures` 随重建清空。
+/// `sync_state` 在同步提交后单独更新，同样不参与替换。`tag_index` 和 `group_stats` 在写入影子表的最后整体重新计算。
+const TABLES: [&str; 8] = [
+    "groups",
+    "articles",
+    "redirects",
+    "article_includes",
+    "article_attachments",
+    "sync_failures",
+    "tag_index",
+    "group_stats",
+];
+/// 写入影子表时每个事务执行的语句数
+const SHADOW_BATCH_SIZE: usize = 500;
+
 /// sqlx 的 [`Store`] 实现
+///
+/// 调用 [`Store::clean`] 后进入重建模式：提交时先在影子 schema 中分批写入，
+/// 再通过一个短事务将影子表移入线上 schema，替换期间查询始终读取完整的旧数据或新数据。
+/// 重建失败时线上表保持不变，影子表被清理。
 pub struct SqlxStore {
     pool: DBPool,
-    queries: Vec<sqlx::query::Query<'static, sqlx::Postgres, sqlx::postgres::PgArguments>>,
+    queries: Vec<PgQuery>,
+    rebuild: bool,
+    touched: Touched,
+    enforce_quotas: bool,
 }
 
+type PgQuery = sqlx::query::Query<'static, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
 impl SqlxStore {
     pub fn new(pool: DBPool) -> Self {
         Self {
             pool,
             queries: Default::default(),
+            rebuild: false,
+            touched: Default::default(),
+            enforce_quotas: false,
         }
     }
+
+    /// 在同一个事务中依次执行所有语句，最后重新计算涉及的标签和组的大小统计
+    ///
+    /// 调用过 [`Store::enforce_quotas`] 且有组超出配额时回滚。
+    async fn commit_live(mut self) -> Result<(), error::Error> {
+        let mut tx = begin_write(&self.pool).await?;
+
+        let mut tags = touched_tags(tx.as_mut(), &self.touched).await?;
+        for q in self.queries.drain(..) {
+            q.execute(tx.as_mut()).await?;
+        }
+        tags.extend(touched_tags(tx.as_mut(), &self.touched).await?);
+
+        if !tags.is_empty() {
+            for q in refresh_tag_index(Some(tags.into_iter().collect())) {
+                q.execute(tx.as_mut()).await?;
+            }
+        }
+        for q in refresh_group_stats() {
+            q.execute(tx.as_mut()).await?;
+        }
+
+        if self.enforce_quotas {
+            let over: Option<(String, i64, i64)> = sqlx::query_as(OVER_QUOTA)
+                .fetch_optional(tx.as_mut())
+                .await?;
+            if let Some((group, used_bytes, quota_bytes)) = over {
+                // 事务随 `tx` 丢弃回滚
+                return Err(error::Error::QuotaExceeded(QuotaItem::new(
+                    group,
+                    used_bytes,
+                    quota_bytes,
+                )));
+            }
+        }
+
+        Ok(tx.commit().await?)
+    }
+
+    /// 写入影子表并替换线上表，失败时清理影子表
+    async fn commit_shadow(mut self) -> Result<(), error::Error> {
+        let result = self.write_shadow().await;
+
+        if result.is_err() {
+            let cleanup = drop_shadow_tables(&self.pool).await;
+            if let Err(e) = cleanup {
+                tracing::warn!(error = %e, "failed to clean up shadow tables");
+            }
+        }
+
+        result
+    }
+
+    /// 重建流程：
+    ///
+    /// 1. 按线上表结构创建空的影子表
+    /// 2. 以 `search_path` 优先指向影子 schema，分批在短事务中执行写入
+    /// 3. 在一个事务中删除线上表，并将影子表移入线上 schema
+    async fn write_shadow(&mut self) -> Result<(), error::Error> {
+        let mut tx = begin_write(&self.pool).await?;
+        for table in TABLES {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {SHADOW_SCHEMA}.{table}"))
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query(&format!(
+                "CREATE TABLE {SHADOW_SCHEMA}.{table} (LIKE {LIVE_SCHEMA}.{table} INCLUDING ALL)"
+            ))
+            .execute(tx.as_mut())
+            .await?;
+        }
+        tx.commit().await?;
+
+        let mut queries = self.queries.drain(..).peekable();
+        while queries.peek().is_some() {
+            let mut tx = begin_write(&self.pool).await?;
+            sqlx::query(&format!(
+                "SET LOCAL search_path TO {SHADOW_SCHEMA}, {LIVE_SCHEMA}"
+            ))
+            .execute(tx.as_mut())
+            .await?;
+            for q in queries.by_ref().take(SHADOW_BATCH_SIZE) {
+                q.execute(tx.as_mut()).await?;
+            }
+            tx.commit().await?;
+        }
+
+        let mut tx = begin_write(&self.pool).await?;
+        sqlx::query(&format!(
+            "LOCK TABLE {} IN ACCESS EXCLUSIVE MODE",
+            TABLES.map(|t| format!("{LIVE_SCHEMA}.{t}")).join(", ")
+        ))
+        .execute(tx.as_mut())
+        .await?;
+        for table in TABLES {
+            sqlx::query(&format!("DROP TABLE {LIVE_SCHEMA}.{table}"))
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query(&format!(
+                "ALTER TABLE {SHADOW_SCHEMA}.{table} SET SCHEMA {LIVE_SCHEMA}"
+            ))
+            .execute(tx.as_mut())
+            .await?;
+        }
+        Ok(tx.commit().await?)
+    }
+
+    /// 写入文章，`content`、`summary` 和 `summary_plain` 为明文或密文，`nonce` 为 [`None`] 表示明文存储，
+    /// `content_oid` 不为 [`None`] 时正文存储在对应的 [`ContentBlob`] 中
+    fn push_article(
+        &mut self,
+        article: &Article,
+        content: String,
+        summary: String,
+        summary_plain: String,
+        nonce: Option<Vec<u8>>,
+        content_oid: Option<String>,
+    ) -> &mut Self {
+        self.touched.slugs.insert(article.slug.to_owned());
+        // 加密文章的描述和图片来自明文，不保存
+        let social = nonce.is_none().then(|| SocialMeta::from_article(article));
+        let q = sqlx::query(
+            "
+            INSERT INTO articles
+                (slug, group_id, title, summary, tags, own_tags, content, created_at, updated_at, nonce, summary_plain, content_oid, content_namespaced, canonical_url, public, source_bytes, social, title_sort_key)
+            VALUES ($1, $2, $3, $4, $5, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            ON CONFLICT (slug)
+            DO UPDATE SET
+                group_id = EXCLUDED.group_id,
+                title = EXCLUDED.title,
+                summary = EXCLUDED.summary,
+                summary_plain = EXCLUDED.summary_plain,
+                tags = EXCLUDED.tags,
+                own_tags = EXCLUDED.own_tags,
+                content = EXCLUDED.content,
+                content_oid = EXCLUDED.content_oid,
+                content_namespaced = EXCLUDED.content_namespaced,
+                canonical_url = EXCLUDED.canonical_url,
+                nonce = EXCLUDED.nonce,
+                public = EXCLUDED.public,
+                source_bytes = EXCLUDED.source_bytes,
+                social = EXCLUDED.social,
+                title_sort_key = EXCLUDED.title_sort_key,
+                -- 公开设置变化时更新时间取当前时间，使缓存失效
+                updated_at = CASE
+                    WHEN articles.public IS DISTINCT FROM EXCLUDED.public THEN now()
+                    ELSE EXCLUDED.updated_at
+                END
+            ",
+        )
+        .bind(article.slug.to_owned())
+        .bind(article.group.to_owned())
+        .bind(article.frontmatter.title.to_owned())
+        .bind(summary)
+        .bind(article.frontmatter.tags.to_owned())
+        .bind(content)
+        .bind(article.frontmatter.datetime)
+        .bind(article.updated_at)
+        .bind(nonce)
+        .bind(summary_plain)
+        .bind(content_oid)
+        .bind(article.namespaced_content.to_owned())
+        .bind(article.frontmatter.canonical.to_owned())
+        .bind(article.frontmatter.public)
+        .bind(article.source_bytes as i64)
+        .bind(social.map(Json))
+        .bind(title_sort_key(&article.frontmatter.title));
+
+        self.queries.push(q);
+
+        // 合并所在组的默认标签
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(article.group.to_owned())
+            .bind(Some(article.slug.to_owned()));
+        self.queries.push(q);
+
+        // 文章所在位置已有实体，不再作为重定向来源
+        let q = sqlx::query("DELETE FROM redirects WHERE from_group = $1 AND from_slug = $2")
+            .bind(article.group.to_owned())
+            .bind(article.slug.to_owned());
+
+        self.queries.push(q);
+        self
+    }
 }
 
 impl ToOwned for SqlxStore {
@@ -46,47 +543,102 @@ impl ToOwned for SqlxStore {
         Self {
             pool: self.pool.clone(),
             queries: Default::default(),
+            rebuild: false,
+            touched: Default::default(),
+            enforce_quotas: false,
         }
     }
 }
 
 impl Store for SqlxStore {
+    /// 丢弃已排队的语句并进入重建模式，写入目标切换为影子表
     fn clean(&mut self) -> &mut Self {
-        let query = sqlx::query("TRUNCATE TABLE groups, articles");
-        self.queries.push(query);
+        self.queries.clear();
+        self.touched = Default::default();
+        self.rebuild = true;
         self
     }
 
     fn remove_article(&mut self, article_ref: ArticleRef<'_>) -> &mut Self {
+        self.touched.slugs.insert(article_ref.slug.to_owned());
         let query = sqlx::query("DELETE FROM articles WHERE slug = $1 AND group_id = $2")
             .bind(article_ref.slug.to_owned())
             .bind(article_ref.group.to_owned());
         self.queries.push(query);
+
+        let query = sqlx::query("DELETE FROM external_links WHERE slug = $1")
+            .bind(article_ref.slug.to_owned());
+        self.queries.push(query);
+
+        let query =
+            sqlx::query("DELETE FROM article_attachments WHERE slug = $1 AND group_id = $2")
+                .bind(article_ref.slug.to_owned())
+                .bind(article_ref.group.to_owned());
+        self.queries.push(query);
         self
     }
 
     fn upsert_group(&mut self, group: &Group) -> &mut Self {
+        self.touched.groups.insert(group.id.to_owned());
         let q = sqlx::query(
             r#"
-            INSERT INTO groups (id, name, public, kind)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO groups (id, name, public, kind, encrypted, slug_strategy, author_id, author_name, archived, slug, default_tags, category_id, category_name, nav_order, hidden, retain_max_articles, retain_max_age_days, license, quota_bytes, front_matter_schema)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
             ON CONFLICT (id) DO UPDATE
             SET
+                slug = EXCLUDED.slug,
+                license = EXCLUDED.license,
+                quota_bytes = EXCLUDED.quota_bytes,
+                front_matter_schema = EXCLUDED.front_matter_schema,
+                category_id = EXCLUDED.category_id,
+                category_name = EXCLUDED.category_name,
+                nav_order = EXCLUDED.nav_order,
+                hidden = EXCLUDED.hidden,
+                retain_max_articles = EXCLUDED.retain_max_articles,
+                retain_max_age_days = EXCLUDED.retain_max_age_days,
+                default_tags = EXCLUDED.default_tags,
                 public = EXCLUDED.public,
                 name = EXCLUDED.name,
-                kind = EXCLUDED.kind
+                kind = EXCLUDED.kind,
+                encrypted = EXCLUDED.encrypted,
+                slug_strategy = EXCLUDED.slug_strategy,
+                author_id = EXCLUDED.author_id,
+                author_name = EXCLUDED.author_name,
+                archived = EXCLUDED.archived
             "#,
         )
         .bind(group.id.to_owned())
         .bind(group.name.to_owned())
         .bind(group.public)
-        .bind(Json(group.kind.clone()));
+        .bind(Json(group.kind.clone()))
+        .bind(group.encrypted)
+        .bind(group.slug_strategy.as_str())
+        .bind(group.author.as_ref().map(|a| a.id.to_owned()))
+        .bind(group.author.as_ref().map(|a| a.name.to_owned()))
+        .bind(group.archived)
+        .bind(group.public_id().to_owned())
+        .bind(group.default_tags.to_owned())
+        .bind(group.category.as_ref().map(|c| c.id.to_owned()))
+        .bind(group.category.as_ref().map(|c| c.name.to_owned()))
+        .bind(group.nav_order)
+        .bind(group.hidden)
+        .bind(group.retain.and_then(|r| r.max_articles).map(|n| n as i32))
+        .bind(group.retain.and_then(|r| r.max_age_days).map(|n| n as i32))
+        .bind(group.license.clone().map(Json))
+        .bind(group.quota_bytes.map(|q| q as i64))
+        .bind(group.front_matter_schema().map(Json));
+        self.queries.push(q);
 
+        // 组内已有文章按新的默认标签重新计算，不重新渲染
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(group.id.to_owned())
+            .bind(None::<String>);
         self.queries.push(q);
         self
     }
 
     fn remove_group(&mut self, group: &Group) -> &mut Self {
+        self.touched.groups.insert(group.id.to_owned());
         let q = sqlx::query(
             r#"
             DELETE FROM groups
@@ -94,47 +646,951 @@ impl Store for SqlxStore {
             "#,
         )
         .bind(group.id.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(RETAG_ARTICLES)
+            .bind(group.id.to_owned())
+            .bind(None::<String>);
+        self.queries.push(q);
+        self
+    }
+
+    fn rename_group(&mut self, from: &GroupPath, to: &GroupPath) -> &mut Self {
+        self.touched.groups.extend([from.to_owned(), to.to_owned()]);
+        let q = sqlx::query(
+            r#"
+            UPDATE groups
+            SET id = $2
+            WHERE id = $1
+            AND NOT EXISTS (SELECT 1 FROM groups WHERE id = $2)
+            "#,
+        )
+        .bind(from.to_owned())
+        .bind(to.to_owned());
+        self.queries.push(q);
 
+        let q = sqlx::query("DELETE FROM groups WHERE id = $1").bind(from.to_owned());
         self.queries.push(q);
         self
     }
 
+    fn set_group_description(&mut self, group_id: &GroupPath, html: Option<&str>) -> &mut Self {
+        let q = sqlx::query("UPDATE groups SET description_html = $2 WHERE id = $1")
+            .bind(group_id.to_owned())
+            .bind(html.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    async fn prune_groups(&self, keep: &[GroupPath]) -> Result<Vec<GroupPath>, error::Error> {
+        let ids = sqlx::query_scalar(
+            r#"
+            DELETE FROM groups g
+            WHERE NOT (g.id = ANY($1))
+            AND NOT EXISTS (SELECT 1 FROM articles a WHERE a.group_id = g.id)
+            RETURNING g.id
+            "#,
+        )
+        .bind(keep.to_vec())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn apply_retention(
+        &self,
+        groups: Option<&[GroupPath]>,
+    ) -> Result<Vec<RetentionChange>, error::Error> {
+        let mut tx = begin_write(&self.pool).await?;
+
+        let changes: Vec<RetentionChange> = sqlx::query_as(APPLY_RETENTION)
+            .bind(groups.map(<[GroupPath]>::to_vec))
+            .fetch_all(tx.as_mut())
+            .await?;
+        let touched = Touched {
+            slugs: changes.iter().map(|c| c.slug.to_owned()).collect(),
+            groups: BTreeSet::new(),
+        };
+        let tags = touched_tags(tx.as_mut(), &touched).await?;
+        if !tags.is_empty() {
+            for q in refresh_tag_index(Some(tags.into_iter().collect())) {
+                q.execute(tx.as_mut()).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(changes)
+    }
+
+    /// 同时写入由摘要生成的纯文本摘要
     fn upsert_article(&mut self, article: &Article) -> &mut Self {
+        self.push_article(
+            article,
+            article.rendered_content.to_owned(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            None,
+        )
+    }
+
+    fn upsert_encrypted_article(&mut self, article: &Article) -> Result<&mut Self, error::Error> {
+        let cipher = ContentCipher::installed()?;
+        let nonce = ContentCipher::new_nonce();
+        let content = cipher.seal(&nonce, Field::Content, &article.rendered_content)?;
+        let summary = cipher.seal(&nonce, Field::Summary, &article.frontmatter.summary)?;
+        let summary_plain = cipher.seal(
+            &nonce,
+            Field::SummaryPlain,
+            &plain_text(&article.frontmatter.summary),
+        )?;
+
+        Ok(self.push_article(article, content, summary, summary_plain, Some(nonce), None))
+    }
+
+    fn upsert_blob_article(&mut self, article: &Article, oid: &str) -> &mut Self {
+        self.push_article(
+            article,
+            String::new(),
+            article.frontmatter.summary.to_owned(),
+            plain_text(&article.frontmatter.summary),
+            None,
+            Some(oid.to_owned()),
+        )
+    }
+
+    async fn content_blob(
+        &self,
+        oid: &str,
+        renderer: &str,
+    ) -> Result<Option<ContentBlob>, error::Error> {
+        let row = sqlx::query_as(
+            r#"
+            SELECT oid, renderer, raw, content, summary, simhash
+            FROM content_blobs
+            WHERE oid = $1 AND renderer = $2
+            "#,
+        )
+        .bind(oid)
+        .bind(renderer)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    fn upsert_content_blob(&mut self, blob: &ContentBlob) -> &mut Self {
         let q = sqlx::query(
-            "
-            INSERT INTO articles
-                (slug, group_id, title, summary, tags, content, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            ON CONFLICT (slug)
-            DO UPDATE SET
-                group_id = EXCLUDED.group_id,
-                title = EXCLUDED.title,
-                summary = EXCLUDED.summary,
-                tags = EXCLUDED.tags,
+            r#"
+            INSERT INTO content_blobs (oid, renderer, raw, content, summary, simhash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (oid) DO UPDATE
+            SET
+                renderer = EXCLUDED.renderer,
                 content = EXCLUDED.content,
-                updated_at = EXCLUDED.updated_at
-            ",
+                summary = EXCLUDED.summary,
+                simhash = EXCLUDED.simhash
+            "#,
         )
-        .bind(article.slug.to_owned())
-        .bind(article.group.to_owned())
-        .bind(article.frontmatter.title.to_owned())
-        .bind(article.frontmatter.summary.to_owned())
-        .bind(article.frontmatter.tags.to_owned())
-        .bind(article.rendered_content.to_owned())
-        .bind(article.frontmatter.datetime)
-        .bind(article.frontmatter.datetime);
+        .bind(blob.oid.to_owned())
+        .bind(blob.renderer.to_owned())
+        .bind(blob.raw.to_owned())
+        .bind(blob.content.to_owned())
+        .bind(blob.summary.to_owned())
+        .bind(blob.simhash);
+        self.queries.push(q);
+        self
+    }
 
+    fn prune_content_blobs(&mut self) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            DELETE FROM content_blobs b
+            WHERE NOT EXISTS (SELECT 1 FROM articles a WHERE a.content_oid = b.oid)
+            "#,
+        );
         self.queries.push(q);
         self
     }
 
-    async fn commit(mut self) -> Result<(), error::Error> {
-        let mut tx = self.pool.begin().await?;
+    async fn article_simhashes(&self) -> Result<Vec<ArticleSimhash>, error::Error> {
+        let rows = sqlx::query_as(
+            r#"
+            SELECT a.slug, a.title, a.group_id, a.source_path, b.simhash
+            FROM articles a
+            JOIN groups g ON g.id = a.group_id
+            JOIN content_blobs b ON b.oid = a.content_oid
+            WHERE COALESCE(a.public, g.public) AND NOT a.retention_pruned
+                AND b.simhash IS NOT NULL
+            ORDER BY a.slug
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
 
-        for q in self.queries.drain(..) {
+        Ok(rows)
+    }
+
+    async fn group_settings(&self) -> Result<HashMap<GroupPath, GroupSettings>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, bool, String)>(
+            "SELECT id, encrypted, slug_strategy FROM groups",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, encrypted, strategy)| {
+                let settings = GroupSettings {
+                    encrypted,
+                    slug_strategy: strategy.as_str().into(),
+                };
+                (id, settings)
+            })
+            .collect())
+    }
+
+    async fn group_authors(&self) -> Result<HashMap<GroupPath, GroupAuthor>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, String, String)>(
+            "SELECT id, author_id, author_name FROM groups WHERE author_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(group, id, name)| (group, GroupAuthor { id, name }))
+            .collect())
+    }
+
+    async fn group_visibility(&self) -> Result<HashMap<GroupPath, bool>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, bool)>("SELECT id, public FROM groups")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn group_slugs(&self) -> Result<HashMap<GroupPath, String>, error::Error> {
+        let rows = sqlx::query_as::<_, (GroupPath, String)>("SELECT id, slug FROM groups")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// 写入重定向时折叠链路与环：
+    ///
+    /// 1. 新位置已有实体，删除以它为来源的重定向
+    /// 2. 指向旧位置的重定向改为直接指向新位置（A→B, B→C 变为 A→C）
+    /// 3. 插入或更新旧位置到新位置的重定向
+    /// 4. 删除来源与目标相同的重定向，消除环
+    fn upsert_redirect(&mut self, from: ArticleRef<'_>, to: ArticleRef<'_>) -> &mut Self {
+        let q = sqlx::query("DELETE FROM redirects WHERE from_group = $1 AND from_slug = $2")
+            .bind(to.group.to_owned())
+            .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            r#"
+            UPDATE redirects
+            SET to_group = $3, to_slug = $4
+            WHERE to_group = $1 AND to_slug = $2
+            "#,
+        )
+        .bind(from.group.to_owned())
+        .bind(from.slug.to_owned())
+        .bind(to.group.to_owned())
+        .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            r#"
+            INSERT INTO redirects (from_group, from_slug, to_group, to_slug)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (from_group, from_slug) DO UPDATE
+            SET
+                to_group = EXCLUDED.to_group,
+                to_slug = EXCLUDED.to_slug
+            "#,
+        )
+        .bind(from.group.to_owned())
+        .bind(from.slug.to_owned())
+        .bind(to.group.to_owned())
+        .bind(to.slug.to_owned());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            "DELETE FROM redirects WHERE from_group = to_group AND from_slug = to_slug",
+        );
+        self.queries.push(q);
+        self
+    }
+
+    fn update_comment_counts(&mut self, counts: &HashMap<String, i64>) -> &mut Self {
+        if counts.is_empty() {
+            return self;
+        }
+
+        let (slugs, counts): (Vec<String>, Vec<i64>) =
+            counts.iter().map(|(s, c)| (s.to_owned(), *c)).unzip();
+
+        let q = sqlx::query(
+            r#"
+            UPDATE articles a
+            SET comment_count = c.count
+            FROM UNNEST($1::VARCHAR[], $2::BIGINT[]) AS c(slug, count)
+            WHERE a.slug = c.slug
+            "#,
+        )
+        .bind(slugs)
+        .bind(counts);
+
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_source(&mut self, slug: &str, path: &str, commit: &str) -> &mut Self {
+        let q =
+            sqlx::query("UPDATE articles SET source_path = $2, source_commit = $3 WHERE slug = $1")
+                .bind(slug.to_owned())
+                .bind(path.to_owned())
+                .bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn article_short_ids(&self) -> Result<Vec<ArticleShortId>, error::Error> {
+        Ok(sqlx::query_as::<_, ArticleShortId>(
+            "SELECT slug, source_path, short_id FROM articles WHERE short_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    fn set_article_short_id(&mut self, slug: &str, short_id: &str) -> &mut Self {
+        // 改名时新位置可能先于旧位置写入，先清除旧文章上的短 id 避免违反唯一约束
+        let q =
+            sqlx::query("UPDATE articles SET short_id = NULL WHERE short_id = $2 AND slug <> $1")
+                .bind(slug.to_owned())
+                .bind(short_id.to_owned());
+        self.queries.push(q);
+        let q = sqlx::query("UPDATE articles SET short_id = $2 WHERE slug = $1")
+            .bind(slug.to_owned())
+            .bind(short_id.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_renderer(&mut self, slug: &str, renderer: &str) -> &mut Self {
+        let q = sqlx::query("UPDATE articles SET renderer = $2 WHERE slug = $1")
+            .bind(slug.to_owned())
+            .bind(renderer.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn set_article_render_stats(
+        &mut self,
+        slug: &str,
+        render_ms: i64,
+        content_bytes: i64,
+    ) -> &mut Self {
+        let q = sqlx::query(
+            "UPDATE articles SET render_ms = $2, content_bytes = $3, rendered_at = now() WHERE slug = $1",
+        )
+        .bind(slug.to_owned())
+        .bind(render_ms)
+        .bind(content_bytes);
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_links(&mut self, slug: &str, urls: &[String]) -> &mut Self {
+        let q = sqlx::query("DELETE FROM external_links WHERE slug = $1 AND NOT (url = ANY($2))")
+            .bind(slug.to_owned())
+            .bind(urls.to_vec());
+        self.queries.push(q);
+
+        let q = sqlx::query(
+            r#"
+            INSERT INTO external_links (slug, url)
+            SELECT $1, UNNEST($2::TEXT[])
+            ON CONFLICT (slug, url) DO NOTHING
+            "#,
+        )
+        .bind(slug.to_owned())
+        .bind(urls.to_vec());
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_attachments(
+        &mut self,
+        article_ref: ArticleRef<'_>,
+        attachments: &[Attachment],
+    ) -> &mut Self {
+        let q = sqlx::query("DELETE FROM article_attachments WHERE slug = $1")
+            .bind(article_ref.slug.to_owned());
+        self.queries.push(q);
+
+        if !attachments.is_empty() {
+            let q = sqlx::query(
+                r#"
+                INSERT INTO article_attachments (slug, group_id, position, url_or_path, kind, link_text)
+                SELECT $1, $2, t.position - 1, t.url_or_path, t.kind, t.link_text
+                FROM UNNEST($3::TEXT[], $4::TEXT[], $5::TEXT[]) WITH ORDINALITY
+                    AS t(url_or_path, kind, link_text, position)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(article_ref.slug.to_owned())
+            .bind(article_ref.group.to_owned())
+            .bind(attachments.iter().map(|a| a.url_or_path.to_owned()).collect::<Vec<_>>())
+            .bind(attachments.iter().map(|a| a.kind.to_owned()).collect::<Vec<_>>())
+            .bind(attachments.iter().map(|a| a.link_text.to_owned()).collect::<Vec<_>>());
+            self.queries.push(q);
+        }
+        self
+    }
+
+    fn record_link_status(
+        &mut self,
+        url: &str,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            UPDATE external_links
+            SET status = $2, error = $3, last_checked = now()
+            WHERE url = $1
+            "#,
+        )
+        .bind(url.to_owned())
+        .bind(status)
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn prune_links(&mut self) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            DELETE FROM external_links l
+            WHERE NOT EXISTS (SELECT 1 FROM articles a WHERE a.slug = l.slug)
+            "#,
+        );
+        self.queries.push(q);
+        self
+    }
+
+    fn record_webmention(
+        &mut self,
+        slug: &str,
+        target: &str,
+        endpoint: Option<&str>,
+        status: Option<i32>,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            INSERT INTO webmentions_sent (slug, target, endpoint, status, error)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (slug, target) DO UPDATE
+            SET
+                endpoint = EXCLUDED.endpoint,
+                status = EXCLUDED.status,
+                error = EXCLUDED.error,
+                sent_at = now()
+            "#,
+        )
+        .bind(slug.to_owned())
+        .bind(target.to_owned())
+        .bind(endpoint.map(str::to_owned))
+        .bind(status)
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn remove_webmention(&mut self, slug: &str, target: &str) -> &mut Self {
+        let q = sqlx::query("DELETE FROM webmentions_sent WHERE slug = $1 AND target = $2")
+            .bind(slug.to_owned())
+            .bind(target.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn record_digest_run(
+        &mut self,
+        period_start: DateTime<FixedOffset>,
+        period_end: DateTime<FixedOffset>,
+        article_count: i64,
+        status: &str,
+        error: Option<&str>,
+    ) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            INSERT INTO digest_runs (period_end, period_start, article_count, status, error)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (period_end) DO UPDATE
+            SET
+                period_start = EXCLUDED.period_start,
+                article_count = EXCLUDED.article_count,
+                status = EXCLUDED.status,
+                error = EXCLUDED.error,
+                ran_at = now()
+            "#,
+        )
+        .bind(period_end)
+        .bind(period_start)
+        .bind(article_count)
+        .bind(status.to_owned())
+        .bind(error.map(str::to_owned));
+        self.queries.push(q);
+        self
+    }
+
+    fn replace_includes(&mut self, path: &str, includes: &[String]) -> &mut Self {
+        let q = sqlx::query("DELETE FROM article_includes WHERE article_path = $1")
+            .bind(path.to_owned());
+        self.queries.push(q);
+
+        if !includes.is_empty() {
+            let q = sqlx::query(
+                r#"
+                INSERT INTO article_includes (article_path, include_path)
+                SELECT $1, UNNEST($2::TEXT[])
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(path.to_owned())
+            .bind(includes.to_vec());
+            self.queries.push(q);
+        }
+        self
+    }
+
+    async fn include_dependents(&self, paths: &[String]) -> Result<Vec<String>, error::Error> {
+        let rows = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT article_path
+            FROM article_includes
+            WHERE include_path = ANY($1)
+            ORDER BY article_path
+            "#,
+        )
+        .bind(paths.to_vec())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    fn savepoint(&self) -> Savepoint {
+        Savepoint(self.queries.len())
+    }
+
+    fn rollback_to(&mut self, savepoint: Savepoint) -> &mut Self {
+        self.queries.truncate(savepoint.0);
+        self
+    }
+
+    fn record_sync_failure(&mut self, path: &str, reason: &str, commit: &str) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            INSERT INTO sync_failures (path, reason, commit_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (path) DO UPDATE
+            SET
+                reason = EXCLUDED.reason,
+                commit_id = EXCLUDED.commit_id,
+                failed_at = now()
+            "#,
+        )
+        .bind(path.to_owned())
+        .bind(reason.to_owned())
+        .bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn clear_sync_failure(&mut self, path: &str) -> &mut Self {
+        let q = sqlx::query("DELETE FROM sync_failures WHERE path = $1").bind(path.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    fn retarget_sync_failures(&mut self, commit: &str) -> &mut Self {
+        let q = sqlx::query("UPDATE sync_failures SET commit_id = $1").bind(commit.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn sync_failures(&self) -> Result<Vec<SyncFailure>, error::Error> {
+        let rows = sqlx::query_as(
+            "SELECT path, reason, commit_id, failed_at FROM sync_failures ORDER BY path",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn fingerprint_rows(
+        &self,
+    ) -> Result<(Vec<GroupFingerprint>, Vec<ArticleFingerprint>), error::Error> {
+        let groups = sqlx::query_as(
+            r#"
+            SELECT id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived, default_tags, kind, category_id, category_name, nav_order, hidden, retain_max_articles, retain_max_age_days, license
+            FROM groups
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rows: Vec<(GroupPath, String, Option<String>, DateTime<FixedOffset>)> = sqlx::query_as(
+            r#"
+            SELECT a.group_id, a.slug, a.content_oid, a.updated_at
+            FROM articles a
+            INNER JOIN groups g ON a.group_id = g.id
+            WHERE COALESCE(a.public, g.public)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let articles = rows
+            .into_iter()
+            .map(
+                |(group_id, slug, content_oid, updated_at)| ArticleFingerprint {
+                    group_id,
+                    slug,
+                    content_oid,
+                    updated_at: updated_at.timestamp_millis(),
+                },
+            )
+            .collect();
+
+        Ok((groups, articles))
+    }
+
+    fn record_sync_state(&mut self, commit: &str, fingerprint: &str) -> &mut Self {
+        let q = sqlx::query(
+            r#"
+            INSERT INTO sync_state (commit_id, content_fingerprint)
+            VALUES ($1, $2)
+            ON CONFLICT (id) DO UPDATE
+            SET
+                commit_id = EXCLUDED.commit_id,
+                content_fingerprint = EXCLUDED.content_fingerprint,
+                computed_at = now()
+            "#,
+        )
+        .bind(commit.to_owned())
+        .bind(fingerprint.to_owned());
+        self.queries.push(q);
+        self
+    }
+
+    async fn dump_page(
+        &self,
+        table: DumpTable,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DumpRecord>, error::Error> {
+        let (after, limit) = (after.map(str::to_owned), limit as i64);
+        let records = match table {
+            DumpTable::Groups => sqlx::query_as(
+                r#"
+                SELECT id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived,
+                    default_tags, kind, category_id, category_name, nav_order, hidden,
+                    retain_max_articles, retain_max_age_days, license, quota_bytes, front_matter_schema,
+                    description_html
+                FROM groups
+                WHERE $1::TEXT IS NULL OR id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::Group)
+            .collect(),
+            DumpTable::Articles => sqlx::query_as(
+                r#"
+                SELECT slug, group_id, title, summary, summary_plain, tags, own_tags, content, content_oid,
+                    content_namespaced, nonce, comment_count, created_at, updated_at, source_path,
+                    source_commit, renderer, canonical_url, public, short_id, retention_pruned,
+                    source_bytes, social
+                FROM articles
+                WHERE $1::TEXT IS NULL OR slug > $1
+                ORDER BY slug
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::Article)
+            .collect(),
+            DumpTable::ContentBlobs => sqlx::query_as(
+                r#"
+                SELECT oid, renderer, raw, content, summary, simhash
+                FROM content_blobs
+                WHERE $1::TEXT IS NULL OR oid > $1
+                ORDER BY oid
+                LIMIT $2
+                "#,
+            )
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(DumpRecord::ContentBlob)
+            .collect(),
+        };
+
+        Ok(records)
+    }
+
+    async fn load_dump<I>(&self, records: I, truncate: bool) -> Result<DumpCounts, error::Error>
+    where
+        I: Iterator<Item = Result<DumpRecord, DumpError>> + Send,
+    {
+        let mut tx = begin_write(&self.pool).await?;
+        if truncate {
+            sqlx::query("DELETE FROM articles")
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query("DELETE FROM groups")
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query("DELETE FROM content_blobs")
+                .execute(tx.as_mut())
+                .await?;
+        }
+
+        let mut counts = DumpCounts::default();
+        for record in records {
+            // 出错时事务随 `tx` 丢弃回滚
+            let record = record?;
+            upsert_record(&record).execute(tx.as_mut()).await?;
+            counts.add(&record);
+        }
+        for q in refresh_tag_index(None)
+            .into_iter()
+            .chain(refresh_group_stats())
+        {
             q.execute(tx.as_mut()).await?;
         }
+        tx.commit().await?;
 
-        Ok(tx.commit().await?)
+        Ok(counts)
+    }
+
+    fn enforce_quotas(&mut self) -> &mut Self {
+        self.enforce_quotas = true;
+        self
+    }
+
+    async fn commit(mut self) -> Result<(), error::Error> {
+        if self.rebuild {
+            // 重建后的标签索引和组的大小统计与从头计算的结果相同
+            self.queries.extend(refresh_tag_index(None));
+            self.queries.extend(refresh_group_stats());
+            self.commit_shadow().await
+        } else {
+            self.commit_live().await
+        }
+    }
+}
+
+/// 查询 `touched` 中的文章和组内文章当前的标签
+async fn touched_tags(
+    conn: &mut sqlx::PgConnection,
+    touched: &Touched,
+) -> Result<BTreeSet<String>, sqlx::Error> {
+    if touched.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let tags: Vec<String> = sqlx::query_scalar(TOUCHED_TAGS)
+        .bind(touched.slugs.iter().cloned().collect::<Vec<_>>())
+        .bind(touched.groups.iter().cloned().collect::<Vec<_>>())
+        .fetch_all(conn)
+        .await?;
+    Ok(tags.into_iter().collect())
+}
+
+/// 重新计算 `tags` 在标签索引中的行，`tags` 为 [`None`] 时重新计算全部标签
+///
+/// 先删除再按公开文章插入，不再有公开文章的标签随之删除。
+fn refresh_tag_index(tags: Option<Vec<String>>) -> [PgQuery; 2] {
+    [
+        sqlx::query("DELETE FROM tag_index WHERE $1::TEXT[] IS NULL OR tag = ANY($1)")
+            .bind(tags.clone()),
+        sqlx::query(REFRESH_TAG_INDEX).bind(tags),
+    ]
+}
+
+/// 重新计算全部组的大小统计，删除已不存在的组的行
+fn refresh_group_stats() -> [PgQuery; 2] {
+    [
+        sqlx::query("DELETE FROM group_stats WHERE group_id NOT IN (SELECT id FROM groups)"),
+        sqlx::query(REFRESH_GROUP_STATS),
+    ]
+}
+
+/// 按导出的记录插入或更新一行，所有列取记录中的值
+fn upsert_record(record: &DumpRecord) -> PgQuery {
+    match record.clone() {
+        DumpRecord::Group(g) => sqlx::query(
+            r#"
+            INSERT INTO groups
+                (id, slug, name, public, encrypted, slug_strategy, author_id, author_name, archived,
+                default_tags, kind, category_id, category_name, nav_order, hidden,
+                retain_max_articles, retain_max_age_days, license, description_html, quota_bytes,
+                front_matter_schema)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+            ON CONFLICT (id) DO UPDATE SET
+                slug = EXCLUDED.slug,
+                name = EXCLUDED.name,
+                public = EXCLUDED.public,
+                encrypted = EXCLUDED.encrypted,
+                slug_strategy = EXCLUDED.slug_strategy,
+                author_id = EXCLUDED.author_id,
+                author_name = EXCLUDED.author_name,
+                archived = EXCLUDED.archived,
+                default_tags = EXCLUDED.default_tags,
+                kind = EXCLUDED.kind,
+                category_id = EXCLUDED.category_id,
+                category_name = EXCLUDED.category_name,
+                nav_order = EXCLUDED.nav_order,
+                hidden = EXCLUDED.hidden,
+                retain_max_articles = EXCLUDED.retain_max_articles,
+                retain_max_age_days = EXCLUDED.retain_max_age_days,
+                license = EXCLUDED.license,
+                description_html = EXCLUDED.description_html,
+                quota_bytes = EXCLUDED.quota_bytes,
+                front_matter_schema = EXCLUDED.front_matter_schema
+            "#,
+        )
+        .bind(g.id)
+        .bind(g.slug)
+        .bind(g.name)
+        .bind(g.public)
+        .bind(g.encrypted)
+        .bind(g.slug_strategy)
+        .bind(g.author_id)
+        .bind(g.author_name)
+        .bind(g.archived)
+        .bind(g.default_tags)
+        .bind(Json(g.kind))
+        .bind(g.category_id)
+        .bind(g.category_name)
+        .bind(g.nav_order)
+        .bind(g.hidden)
+        .bind(g.retain_max_articles)
+        .bind(g.retain_max_age_days)
+        .bind(g.license.map(Json))
+        .bind(g.description_html)
+        .bind(g.quota_bytes)
+        .bind(g.front_matter_schema.map(Json)),
+
+        DumpRecord::Article(a) => sqlx::query(
+            r#"
+            INSERT INTO articles
+                (slug, group_id, title, summary, summary_plain, tags, own_tags, content, content_oid,
+                content_namespaced, nonce, comment_count, created_at, updated_at, source_path,
+                source_commit, renderer, canonical_url, public, short_id, retention_pruned, source_bytes,
+                social, title_sort_key)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)
+            ON CONFLICT (slug) DO UPDATE SET
+                group_id = EXCLUDED.group_id,
+                title = EXCLUDED.title,
+                summary = EXCLUDED.summary,
+                summary_plain = EXCLUDED.summary_plain,
+                tags = EXCLUDED.tags,
+                own_tags = EXCLUDED.own_tags,
+                content = EXCLUDED.content,
+                content_oid = EXCLUDED.content_oid,
+                content_namespaced = EXCLUDED.content_namespaced,
+                nonce = EXCLUDED.nonce,
+                comment_count = EXCLUDED.comment_count,
+                created_at = EXCLUDED.created_at,
+                updated_at = EXCLUDED.updated_at,
+                source_path = EXCLUDED.source_path,
+                source_commit = EXCLUDED.source_commit,
+                renderer = EXCLUDED.renderer,
+                canonical_url = EXCLUDED.canonical_url,
+                public = EXCLUDED.public,
+                short_id = EXCLUDED.short_id,
+                retention_pruned = EXCLUDED.retention_pruned,
+                source_bytes = EXCLUDED.source_bytes,
+                social = EXCLUDED.social,
+                title_sort_key = EXCLUDED.title_sort_key
+            "#,
+        )
+        .bind(a.slug)
+        .bind(a.group_id)
+        .bind(a.title.to_owned())
+        .bind(a.summary)
+        .bind(a.summary_plain)
+        .bind(a.tags)
+        .bind(a.own_tags)
+        .bind(a.content)
+        .bind(a.content_oid)
+        .bind(a.content_namespaced)
+        .bind(a.nonce)
+        .bind(a.comment_count)
+        .bind(a.created_at)
+        .bind(a.updated_at)
+        .bind(a.source_path)
+        .bind(a.source_commit)
+        .bind(a.renderer)
+        .bind(a.canonical_url)
+        .bind(a.public)
+        .bind(a.short_id)
+        .bind(a.retention_pruned)
+        .bind(a.source_bytes)
+        .bind(a.social.map(Json))
+        // 排序键由标题生成，不写入导出文件
+        .bind(title_sort_key(&a.title)),
+
+        DumpRecord::ContentBlob(b) => sqlx::query(
+            r#"
+            INSERT INTO content_blobs (oid, renderer, raw, content, summary, simhash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (oid) DO UPDATE SET
+                renderer = EXCLUDED.renderer,
+                raw = EXCLUDED.raw,
+                content = EXCLUDED.content,
+                summary = EXCLUDED.summary,
+                simhash = EXCLUDED.simhash
+            "#,
+        )
+        .bind(b.oid)
+        .bind(b.renderer)
+        .bind(b.raw)
+        .bind(b.content)
+        .bind(b.summary)
+        .bind(b.simhash),
     }
 }
+
+/// 删除影子 schema 中的表
+async fn drop_shadow_tables(pool: &DBPool) -> Result<(), sqlx::Error> {
+    let tables = TABLES.map(|t| format!("{SHADOW_SCHEMA}.{t}")).join(", ");
+    sqlx::query(&format!("DROP TABLE IF EXISTS {tables}"))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
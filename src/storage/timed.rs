@@ -0,0 +1,390 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::error;
+
+use super::{
+    ArticleDetail, ArticleSlug, ArticleSummary, AuthorSummary, Backend, BrokenLink, CategoryGroup,
+    DateRange, DigestRun, GroupStats, ListColumns, ListSort, OrphanArticle, PrunedArticle, Querier,
+    Redirect, RenderSort, RenderStat, SiteCounts, StaleRender, SyncState, TagCount, TitleEntry,
+    WebmentionSource, models,
+};
+
+/// 默认的单次查询超时
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 默认的慢查询阈值
+pub const DEFAULT_SLOW_QUERY: Duration = Duration::from_secs(1);
+
+/// PostgreSQL 因 `statement_timeout` 取消语句时的错误码
+const QUERY_CANCELED: &str = "57014";
+
+/// 查询超时和慢查询阈值，为 `None` 时不限制或不记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    pub timeout: Option<Duration>,
+    pub slow: Option<Duration>,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Some(DEFAULT_QUERY_TIMEOUT),
+            slow: Some(DEFAULT_SLOW_QUERY),
+        }
+    }
+}
+
+/// 为每次查询加上 [`QueryLimits`] 的 [`Backend`]，用于处理 HTTP 请求
+///
+/// 查询超过 `timeout` 时返回 [`error::Error::QueryTimeout`]，超过 `slow` 时以 WARN 记录查询名和参数。
+/// PostgreSQL 还通过连接参数 `statement_timeout` 在服务端取消超时的语句并释放连接，
+/// 见 [`Backend::connect_with_timeout`]；SQLite 和内存存储只在客户端停止等待。
+#[derive(Debug, Clone)]
+pub struct TimedQuerier {
+    backend: Backend,
+    limits: QueryLimits,
+}
+
+impl TimedQuerier {
+    pub fn new(backend: Backend, limits: QueryLimits) -> Self {
+        Self { backend, limits }
+    }
+
+    /// 在超时限制内执行 `query`
+    ///
+    /// `params` 只在记录慢查询时调用，不应包含文章内容。
+    pub async fn run<T>(
+        &self,
+        name: &'static str,
+        params: impl FnOnce() -> String,
+        query: impl Future<Output = Result<T, sqlx::Error>>,
+    ) -> Result<T, error::Error> {
+        let started = Instant::now();
+        let result = match self.limits.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, query).await.ok(),
+            None => Some(query.await),
+        };
+
+        let elapsed = started.elapsed();
+        let timed_out = match &result {
+            None => true,
+            Some(Err(sqlx::Error::Database(e))) => e.code().as_deref() == Some(QUERY_CANCELED),
+            Some(_) => false,
+        };
+        if timed_out || self.limits.slow.is_some_and(|slow| elapsed >= slow) {
+            tracing::warn!(
+                query = name,
+                params = params(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                timed_out,
+                "slow query"
+            );
+        }
+
+        match result {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(e)) if !timed_out => Err(e.into()),
+            _ => Err(error::Error::QueryTimeout),
+        }
+    }
+}
+
+impl Querier for TimedQuerier {
+    type Error = error::Error;
+
+    async fn get_one(&self, slug: impl AsRef<str>) -> Result<Option<ArticleDetail>, Self::Error> {
+        let slug = slug.as_ref();
+        self.run(
+            "get_one",
+            || format!("{slug:?}"),
+            self.backend.get_one(slug),
+        )
+        .await
+    }
+
+    async fn article_updated_at(
+        &self,
+        slug: &str,
+    ) -> Result<Option<DateTime<FixedOffset>>, Self::Error> {
+        self.run(
+            "article_updated_at",
+            || format!("{slug:?}"),
+            self.backend.article_updated_at(slug),
+        )
+        .await
+    }
+
+    async fn short_id_slug(&self, short_id: &str) -> Result<Option<String>, Self::Error> {
+        self.run(
+            "short_id_slug",
+            || format!("{short_id:?}"),
+            self.backend.short_id_slug(short_id),
+        )
+        .await
+    }
+
+    async fn article_list(
+        &self,
+        page: i32,
+        size: i32,
+        group: Option<&str>,
+        author: Option<&str>,
+        category: Option<&str>,
+        tags: Vec<&str>,
+        archived: Option<bool>,
+        dates: DateRange,
+        columns: ListColumns,
+        sort: ListSort,
+    ) -> Result<Vec<ArticleSummary>, Self::Error> {
+        self.run(
+            "article_list",
+            || {
+                format!(
+                    "page={page} size={size} group={group:?} author={author:?} category={category:?} \
+                     tags={tags:?} archived={archived:?} dates={dates:?} columns={columns:?} sort={sort:?}"
+                )
+            },
+            self.backend.article_list(
+                page,
+                size,
+                group,
+                author,
+                category,
+                tags.clone(),
+                archived,
+                dates,
+                columns,
+                sort,
+            ),
+        )
+        .await
+    }
+
+    async fn category_groups(&self, category: &str) -> Result<Vec<CategoryGroup>, Self::Error> {
+        self.run(
+            "category_groups",
+            || format!("{category:?}"),
+            self.backend.category_groups(category),
+        )
+        .await
+    }
+
+    async fn authors(&self) -> Result<Vec<AuthorSummary>, Self::Error> {
+        self.run("authors", String::new, self.backend.authors())
+            .await
+    }
+
+    async fn groups(&self) -> Result<Vec<models::Group>, Self::Error> {
+        self.run("groups", String::new, self.backend.groups()).await
+    }
+
+    async fn tags(&self) -> Result<Vec<String>, Self::Error> {
+        self.run("tags", String::new, self.backend.tags()).await
+    }
+
+    async fn tags_with_counts(&self) -> Result<Vec<TagCount>, Self::Error> {
+        self.run(
+            "tags_with_counts",
+            String::new,
+            self.backend.tags_with_counts(),
+        )
+        .await
+    }
+
+    async fn article_tags(&self) -> Result<Vec<Vec<String>>, Self::Error> {
+        self.run("article_tags", String::new, self.backend.article_tags())
+            .await
+    }
+
+    async fn public_slugs(&self) -> Result<Vec<String>, Self::Error> {
+        self.run("public_slugs", String::new, self.backend.public_slugs())
+            .await
+    }
+
+    async fn article_slugs(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<ArticleSlug>, Self::Error> {
+        self.run(
+            "article_slugs",
+            || format!("group={group:?} limit={limit}"),
+            self.backend.article_slugs(group, limit),
+        )
+        .await
+    }
+
+    async fn title_index(
+        &self,
+        group: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<TitleEntry>, Self::Error> {
+        self.run(
+            "title_index",
+            || format!("group={group:?} limit={limit}"),
+            self.backend.title_index(group, limit),
+        )
+        .await
+    }
+
+    async fn redirect(
+        &self,
+        group: Option<&str>,
+        slug: &str,
+    ) -> Result<Option<Redirect>, Self::Error> {
+        self.run(
+            "redirect",
+            || format!("group={group:?} slug={slug:?}"),
+            self.backend.redirect(group, slug),
+        )
+        .await
+    }
+
+    async fn links_due(&self, limit: i64) -> Result<Vec<String>, Self::Error> {
+        self.run(
+            "links_due",
+            || format!("limit={limit}"),
+            self.backend.links_due(limit),
+        )
+        .await
+    }
+
+    async fn broken_links(&self) -> Result<Vec<BrokenLink>, Self::Error> {
+        self.run("broken_links", String::new, self.backend.broken_links())
+            .await
+    }
+
+    async fn sync_state(&self) -> Result<Option<SyncState>, Self::Error> {
+        self.run("sync_state", String::new, self.backend.sync_state())
+            .await
+    }
+
+    async fn site_counts(&self) -> Result<SiteCounts, Self::Error> {
+        self.run("site_counts", String::new, self.backend.site_counts())
+            .await
+    }
+
+    async fn webmention_sources(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<WebmentionSource>, Self::Error> {
+        self.run(
+            "webmention_sources",
+            || format!("{} paths", paths.len()),
+            self.backend.webmention_sources(paths),
+        )
+        .await
+    }
+
+    async fn stale_renders(&self, current: &str) -> Result<Vec<StaleRender>, Self::Error> {
+        self.run(
+            "stale_renders",
+            || format!("{current:?}"),
+            self.backend.stale_renders(current),
+        )
+        .await
+    }
+
+    async fn orphan_articles(&self) -> Result<Vec<OrphanArticle>, Self::Error> {
+        self.run(
+            "orphan_articles",
+            String::new,
+            self.backend.orphan_articles(),
+        )
+        .await
+    }
+
+    async fn pruned_articles(&self) -> Result<Vec<PrunedArticle>, Self::Error> {
+        self.run(
+            "pruned_articles",
+            String::new,
+            self.backend.pruned_articles(),
+        )
+        .await
+    }
+
+    async fn group_stats(&self) -> Result<Vec<GroupStats>, Self::Error> {
+        self.run("group_stats", String::new, self.backend.group_stats())
+            .await
+    }
+
+    async fn render_report(
+        &self,
+        sort: RenderSort,
+        limit: i64,
+    ) -> Result<Vec<RenderStat>, Self::Error> {
+        self.run(
+            "render_report",
+            || format!("sort={sort:?} limit={limit}"),
+            self.backend.render_report(sort, limit),
+        )
+        .await
+    }
+
+    async fn last_digest_run(&self) -> Result<Option<DigestRun>, Self::Error> {
+        self.run(
+            "last_digest_run",
+            String::new,
+            self.backend.last_digest_run(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn querier(timeout: Option<Duration>) -> TimedQuerier {
+        TimedQuerier::new(
+            MemoryStorage::new().into(),
+            QueryLimits {
+                timeout,
+                slow: Some(Duration::ZERO),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_query_timeout() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(())
+        };
+        let err = querier(Some(Duration::from_millis(20)))
+            .run("sleep", String::new, slow)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, error::Error::QueryTimeout));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // 没有超时的查询照常返回，连接池本身的超时不视为查询超时
+        let q = querier(Some(Duration::from_secs(5)));
+        assert!(q.groups().await.unwrap().is_empty());
+        let err = q
+            .run("acquire", String::new, async {
+                Err::<(), _>(sqlx::Error::PoolTimedOut)
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, error::Error::Sqlx(sqlx::Error::PoolTimedOut)));
+
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(1)
+        };
+        assert_eq!(
+            querier(None).run("sleep", String::new, slow).await.unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,721 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use reqwest::{
+    StatusCode, Url,
+    header::{CONTENT_TYPE, LINK},
+};
+use tokio::{
+    sync::{Mutex, mpsc},
+    time::Instant,
+};
+
+use crate::{
+    error::{Error, Result},
+    net::{NON_PUBLIC, host_is_public, outbound_client, read_capped},
+    storage::{Backend, Querier, Store},
+};
+
+/// 发现端点时最多读取的目标页面字节数，超出的部分忽略
+pub const MAX_PAGE_BYTES: usize = 1024 * 1024;
+
+/// webmention 发送配置
+#[derive(Debug, Clone)]
+pub struct WebmentionOptions {
+    /// 每秒最多发出的请求数，所有目标共用
+    pub per_second: u32,
+    /// 单个请求的超时时间
+    pub timeout: Duration,
+    /// 临时失败后重试前的等待时间
+    pub retry_delay: Duration,
+    /// 是否允许请求回环、私有网段、链路本地等非公网地址，只用于本地测试
+    pub allow_private: bool,
+}
+
+impl Default for WebmentionOptions {
+    /// 每秒 2 个请求，超时 10 秒，1 秒后重试，只请求公网地址
+    fn default() -> Self {
+        Self {
+            per_second: 2,
+            timeout: Duration::from_secs(10),
+            retry_delay: Duration::from_secs(1),
+            allow_private: false,
+        }
+    }
+}
+
+/// 向单个目标发送 webmention 的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    /// 发现的 webmention 端点，目标不支持时为 [`None`]
+    pub endpoint: Option<String>,
+    /// 端点的响应状态码
+    pub status: Option<i32>,
+    /// 请求失败原因，如 `timeout`
+    pub error: Option<String>,
+}
+
+/// 全局限流，相邻两个请求之间至少间隔 `gap`
+struct RateLimit {
+    gap: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimit {
+    fn new(per_second: u32) -> Self {
+        Self {
+            gap: Duration::from_secs(1) / per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 等待到下一个可用的请求时刻
+    async fn wait(&self) {
+        let at = {
+            let mut next = self.next.lock().await;
+            let at = (*next).max(Instant::now());
+            *next = at + self.gap;
+            at
+        };
+        tokio::time::sleep_until(at).await;
+    }
+}
+
+/// webmention 发送器
+///
+/// 先请求目标页面发现端点：优先使用 `Link` 响应头中 `rel="webmention"` 的地址，
+/// 其次是 HTML 中第一个 `rel` 含 `webmention` 的 `<link>` 或 `<a>` 元素，相对地址按目标的最终地址解析。
+/// 再以表单形式向端点 POST `source` 和 `target`。
+///
+/// 每个请求都经过全局限流，超时、连接失败、5xx 和 429 视为临时失败，等待后重试一次。
+///
+/// 目标地址来自文章内容，未开启 [`WebmentionOptions::allow_private`] 时只请求公网地址：
+/// 域名解析后丢弃非公网地址，没有剩余地址时请求失败；IP 地址形式的目标、端点和重定向地址同样检查，
+/// 最多跟随 [`MAX_REDIRECTS`](crate::net::MAX_REDIRECTS) 次重定向。目标页面最多读取 [`MAX_PAGE_BYTES`] 字节。
+pub struct WebmentionSender {
+    client: reqwest::Client,
+    options: WebmentionOptions,
+    limit: RateLimit,
+}
+
+impl WebmentionSender {
+    pub fn new(options: WebmentionOptions) -> Self {
+        let client = outbound_client(options.timeout, options.allow_private);
+        let limit = RateLimit::new(options.per_second);
+
+        Self {
+            client,
+            options,
+            limit,
+        }
+    }
+
+    /// 发现 `target` 的 webmention 端点，目标不支持时返回 [`None`]
+    pub async fn discover(&self, target: &str) -> Result<Option<Url>> {
+        let target = Url::parse(target).map_err(|_| Error::Custom("invalid target url"))?;
+        self.check_host(&target)?;
+        let resp = self.send(|| self.client.get(target.clone())).await?;
+        let base = resp.url().clone();
+
+        let from_header = resp
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(header_endpoint);
+        let is_html = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_none_or(|v| v.contains("html"));
+        let endpoint = match from_header {
+            Some(endpoint) => Some(endpoint),
+            None if is_html => html_endpoint(&read_capped(resp, MAX_PAGE_BYTES).await?),
+            None => None,
+        };
+
+        Ok(endpoint.and_then(|e| base.join(&e).ok()))
+    }
+
+    /// 通知 `target`：`source` 中链接了它
+    pub async fn notify(&self, source: &str, target: &str) -> Delivery {
+        let endpoint = match self.discover(target).await {
+            Ok(Some(endpoint)) => endpoint,
+            Ok(None) => {
+                return Delivery {
+                    endpoint: None,
+                    status: None,
+                    error: None,
+                };
+            }
+            Err(e) => {
+                return Delivery {
+                    endpoint: None,
+                    status: None,
+                    error: Some(describe(e)),
+                };
+            }
+        };
+
+        let form = [("source", source), ("target", target)];
+        let resp = match self.check_host(&endpoint) {
+            Ok(()) => self
+                .send(|| self.client.post(endpoint.clone()).form(&form))
+                .await
+                .map_err(Error::from),
+            Err(e) => Err(e),
+        };
+
+        Delivery {
+            endpoint: Some(endpoint.to_string()),
+            status: resp.as_ref().ok().map(|r| r.status().as_u16() as i32),
+            error: resp.err().map(describe),
+        }
+    }
+
+    /// 未开启 [`WebmentionOptions::allow_private`] 时，IP 地址形式的主机必须是公网地址
+    ///
+    /// 域名在连接时由 [`PublicResolver`](crate::net::PublicResolver) 检查。
+    fn check_host(&self, url: &Url) -> Result<()> {
+        if self.options.allow_private || host_is_public(url) {
+            Ok(())
+        } else {
+            Err(Error::Custom(NON_PUBLIC))
+        }
+    }
+
+    /// 限流后发送请求，临时失败时重试一次
+    async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        self.limit.wait().await;
+        let resp = request().send().await;
+        if !is_transient(&resp) {
+            return resp;
+        }
+
+        tokio::time::sleep(self.options.retry_delay).await;
+        self.limit.wait().await;
+        request().send().await
+    }
+}
+
+/// 超时、连接失败、5xx 和 429 视为临时失败
+fn is_transient(resp: &reqwest::Result<reqwest::Response>) -> bool {
+    match resp {
+        Ok(resp) => {
+            resp.status().is_server_error() || resp.status() == StatusCode::TOO_MANY_REQUESTS
+        }
+        Err(e) => e.is_timeout() || e.is_connect(),
+    }
+}
+
+fn describe(e: Error) -> String {
+    match e {
+        Error::Reqwest(e) if e.is_timeout() => "timeout".to_string(),
+        Error::Reqwest(e) => e.to_string(),
+        e => e.to_string(),
+    }
+}
+
+/// 从 `Link` 响应头中找出 `rel` 含 `webmention` 的地址
+///
+/// 头的值由逗号分隔的 `<url>; param=value` 组成，`rel` 可以包含空格分隔的多个值。
+fn header_endpoint(value: &str) -> Option<String> {
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let end = start + rest[start..].find('>')?;
+        let url = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        let params = &rest[..rest.find('<').unwrap_or(rest.len())];
+        let is_webmention = params.split(';').any(|param| {
+            let Some((name, value)) = param.split_once('=') else {
+                return false;
+            };
+            name.trim().eq_ignore_ascii_case("rel")
+                && has_webmention(value.trim().trim_end_matches(',').trim_matches('"'))
+        });
+        if is_webmention {
+            return Some(url.trim().to_string());
+        }
+    }
+    None
+}
+
+/// 从 HTML 中找出第一个 `rel` 含 `webmention` 的 `<link>` 或 `<a>` 元素的 `href`，跳过注释
+fn html_endpoint(html: &str) -> Option<String> {
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        // 结束标签、声明和正文中的 `<` 不是要找的元素
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '>' | '/'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let (attrs, next) = tag_attrs(&rest[name_end..]);
+        rest = next;
+
+        if !(name.eq_ignore_ascii_case("link") || name.eq_ignore_ascii_case("a")) {
+            continue;
+        }
+        let attr = |key: &str| attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        if attr("rel").is_some_and(|rel| has_webmention(rel))
+            && let Some(href) = attr("href")
+        {
+            return Some(href.trim().replace("&amp;", "&"));
+        }
+    }
+    None
+}
+
+/// 解析标签名之后的属性，直到 `>`，返回小写的属性名、属性值和标签之后的内容
+fn tag_attrs(mut s: &str) -> (Vec<(String, String)>, &str) {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if s.is_empty() {
+            return (attrs, s);
+        }
+        if let Some(rest) = s.strip_prefix('>') {
+            return (attrs, rest);
+        }
+
+        let name_end = s
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(s.len());
+        let name = s[..name_end].to_ascii_lowercase();
+        s = s[name_end..].trim_start();
+
+        let mut value = "";
+        if let Some(rest) = s.strip_prefix('=') {
+            let rest = rest.trim_start();
+            (value, s) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &rest[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+        }
+        attrs.push((name, value.to_string()));
+    }
+}
+
+/// 空格分隔的 `rel` 值中是否包含 `webmention`
+fn has_webmention(rel: &str) -> bool {
+    rel.split_ascii_whitespace()
+        .any(|r| r.eq_ignore_ascii_case("webmention"))
+}
+
+/// 待发送 webmention 的队列，同步提交后放入变更文件的路径
+#[derive(Debug, Clone)]
+pub struct WebmentionQueue {
+    tx: mpsc::UnboundedSender<Vec<String>>,
+}
+
+impl WebmentionQueue {
+    /// 放入一批变更文件的路径，发送在后台进行
+    pub fn enqueue(&self, paths: Vec<String>) {
+        if !paths.is_empty() && self.tx.send(paths).is_err() {
+            tracing::warn!("webmention worker stopped");
+        }
+    }
+}
+
+/// 启动后台任务，依次处理队列中的路径，见 [`send_round`]
+///
+/// 文章的来源地址为 `{site_url}/api/articles/{slug}`。
+pub fn spawn(backend: Backend, site_url: Url, options: WebmentionOptions) -> WebmentionQueue {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<String>>();
+    let sender = WebmentionSender::new(options);
+
+    tokio::spawn(async move {
+        while let Some(paths) = rx.recv().await {
+            if let Err(e) = send_round(&backend, &sender, &site_url, &paths).await {
+                tracing::warn!(%e, "webmention sending failed");
+            }
+        }
+    });
+
+    WebmentionQueue { tx }
+}
+
+/// 从环境变量配置并启动 webmention 发送任务，未启用时返回 [`None`]。
+///
+/// - `GITNOTE_WEBMENTIONS`：`true` 时启用，默认禁用
+/// - `GITNOTE_WEBMENTIONS_PER_SECOND`：每秒最多发出的请求数，默认 2
+pub fn spawn_from_env(backend: Backend, site_url: &str) -> Option<WebmentionQueue> {
+    if std::env::var("GITNOTE_WEBMENTIONS").as_deref() != Ok("true") {
+        return None;
+    }
+    let Ok(site_url) = Url::parse(site_url) else {
+        tracing::warn!(site_url, "invalid site url, webmentions disabled");
+        return None;
+    };
+
+    let mut options = WebmentionOptions::default();
+    if let Some(per_second) = std::env::var("GITNOTE_WEBMENTIONS_PER_SECOND")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        options.per_second = per_second;
+    }
+
+    Some(spawn(backend, site_url, options))
+}
+
+/// 为源文件在 `paths` 中的公开文章发送 webmention 并记录结果
+///
+/// 链接取自同步时记录的外部链接，加密文章不发送。外部链接与已发送的目标相同时跳过，
+/// 否则通知当前全部链接和已移除的链接，记录当前链接的结果，删除已移除链接的记录。
+pub async fn send_round(
+    backend: &Backend,
+    sender: &WebmentionSender,
+    site_url: &Url,
+    paths: &[String],
+) -> Result<()> {
+    for source in backend.webmention_sources(paths).await? {
+        let links = source.links.iter().collect::<BTreeSet<_>>();
+        let sent = source.sent.iter().collect::<BTreeSet<_>>();
+        if links == sent {
+            continue;
+        }
+
+        let source_url = article_url(site_url, &source.slug);
+        let mut store = backend.store();
+        for target in links.union(&sent) {
+            let delivery = sender.notify(source_url.as_str(), target).await;
+            tracing::debug!(slug = source.slug, target, ?delivery, "webmention sent");
+            if links.contains(target) {
+                store.record_webmention(
+                    &source.slug,
+                    target,
+                    delivery.endpoint.as_deref(),
+                    delivery.status,
+                    delivery.error.as_deref(),
+                );
+            } else {
+                store.remove_webmention(&source.slug, target);
+            }
+        }
+        store.commit().await?;
+    }
+    Ok(())
+}
+
+/// 文章的规范地址 `{site_url}/api/articles/{slug}`
+fn article_url(site_url: &Url, slug: &str) -> Url {
+    let mut url = site_url.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.pop_if_empty().extend(["api", "articles", slug]);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    use axum::{
+        Form, Router,
+        extract::State,
+        http::header,
+        routing::{get, post},
+    };
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        content::{Article, FrontMatter, Group},
+        storage::MemoryStorage,
+    };
+
+    use super::*;
+
+    type Received = Arc<StdMutex<Vec<(String, String)>>>;
+
+    /// 启动测试站点，返回站点地址和端点收到的 `(source, target)`
+    async fn stub_site() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route(
+                "/header",
+                get(|| async {
+                    (
+                        [(header::LINK, r#"</other>; rel="me", </endpoint>; rel="webmention""#)],
+                        "no html here",
+                    )
+                }),
+            )
+            .route(
+                "/html",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "text/html")],
+                        r#"<html><head>
+                        <!-- <link rel="webmention" href="/commented"> -->
+                        <link rel="stylesheet" href="/style.css">
+                        <link href="endpoint?via=html&amp;x=1" rel="webmention" />
+                        </head></html>"#,
+                    )
+                }),
+            )
+            .route(
+                "/plain",
+                get(|| async { ([(header::CONTENT_TYPE, "text/html")], "<p>hi</p>") }),
+            )
+            .route(
+                "/long",
+                get(|| async {
+                    let padding = " ".repeat(MAX_PAGE_BYTES);
+                    (
+                        [(header::CONTENT_TYPE, "text/html")],
+                        format!(r#"<html>{padding}<link rel="webmention" href="/endpoint"></html>"#),
+                    )
+                }),
+            )
+            .route(
+                "/endpoint",
+                post(
+                    |State(received): State<Received>,
+                     Form(form): Form<std::collections::HashMap<String, String>>| async move {
+                        received
+                            .lock()
+                            .unwrap()
+                            .push((form["source"].clone(), form["target"].clone()));
+                        StatusCode::ACCEPTED
+                    },
+                ),
+            )
+            .with_state(received.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{addr}"), received)
+    }
+
+    /// 测试站点在本机上，允许请求非公网地址
+    fn sender() -> WebmentionSender {
+        WebmentionSender::new(WebmentionOptions {
+            per_second: 100,
+            timeout: Duration::from_secs(5),
+            retry_delay: Duration::from_millis(10),
+            allow_private: true,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_discover_endpoint() {
+        let (site, _) = stub_site().await;
+        let sender = sender();
+
+        let discover = |path: &str| {
+            let (sender, url) = (&sender, format!("{site}{path}"));
+            async move { sender.discover(&url).await.unwrap().map(String::from) }
+        };
+        assert_eq!(discover("/header").await, Some(format!("{site}/endpoint")));
+        assert_eq!(
+            discover("/html").await,
+            Some(format!("{site}/endpoint?via=html&x=1"))
+        );
+        assert_eq!(discover("/plain").await, None);
+        // 超过读取上限之后的端点被忽略
+        assert_eq!(discover("/long").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_public_targets() {
+        let (site, received) = stub_site().await;
+        let port = site.rsplit(':').next().unwrap();
+        let sender = WebmentionSender::new(WebmentionOptions {
+            retry_delay: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        // IP 地址形式的目标在请求前拒绝，域名解析到本机时连接失败
+        for target in [
+            format!("{site}/header"),
+            format!("http://localhost:{port}/header"),
+        ] {
+            assert!(sender.discover(&target).await.is_err(), "{target}");
+            let delivery = sender.notify("https://blog.example/a", &target).await;
+            assert_eq!(delivery.endpoint, None, "{target}");
+            assert!(delivery.error.is_some(), "{target}");
+        }
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_notify_posts_source_and_target() {
+        let (site, received) = stub_site().await;
+        let target = format!("{site}/html");
+
+        let delivery = sender().notify("https://blog.example/a", &target).await;
+        assert_eq!(delivery.status, Some(202));
+        assert_eq!(delivery.error, None);
+        assert_eq!(
+            *received.lock().unwrap(),
+            [("https://blog.example/a".to_string(), target)]
+        );
+
+        let delivery = sender()
+            .notify("https://blog.example/a", &format!("{site}/plain"))
+            .await;
+        assert_eq!(
+            delivery,
+            Delivery {
+                endpoint: None,
+                status: None,
+                error: None
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_round_only_when_links_changed() {
+        let (site, received) = stub_site().await;
+        let (header_target, html_target) = (format!("{site}/header"), format!("{site}/html"));
+
+        let memory = MemoryStorage::new();
+        let datetime = Utc
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        let mut store = memory.store();
+        store
+            .upsert_group(
+                &Group::new(
+                    "notes/.group.yaml",
+                    "name: notes\npublic: true\n".to_string(),
+                )
+                .unwrap(),
+            )
+            .upsert_article(&Article {
+                group: "notes".into(),
+                slug: "a".to_string(),
+                frontmatter: FrontMatter {
+                    title: "a".to_string(),
+                    summary: String::new(),
+                    datetime,
+                    tags: vec![],
+                    aliases: vec![],
+                    canonical: None,
+                    public: None,
+                    raw_html: false,
+                    cover: None,
+                },
+                rendered_content: String::new(),
+                namespaced_content: None,
+                rendered_by: None,
+                updated_at: datetime,
+                source_bytes: 0,
+            })
+            .set_article_source("a", "notes/a.md", "c1")
+            .replace_links("a", &[header_target.clone(), html_target.clone()]);
+        store.commit().await.unwrap();
+
+        let backend = Backend::from(memory.clone());
+        let site_url = Url::parse("https://blog.example/").unwrap();
+        let paths = ["notes/a.md".to_string()];
+        let sent = || {
+            let mut targets = received
+                .lock()
+                .unwrap()
+                .drain(..)
+                .map(|(_, t)| t)
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets
+        };
+
+        send_round(&backend, &sender(), &site_url, &paths)
+            .await
+            .unwrap();
+        assert_eq!(sent(), [header_target.clone(), html_target.clone()]);
+        assert_eq!(
+            recorded_targets(&backend).await,
+            [header_target.clone(), html_target.clone()]
+        );
+
+        // 链接未变化时不再发送
+        send_round(&backend, &sender(), &site_url, &paths)
+            .await
+            .unwrap();
+        assert!(sent().is_empty());
+
+        // 移除的链接也会收到通知，之后不再记录
+        let mut store = memory.store();
+        store.replace_links("a", std::slice::from_ref(&html_target));
+        store.commit().await.unwrap();
+        send_round(&backend, &sender(), &site_url, &paths)
+            .await
+            .unwrap();
+        assert_eq!(sent(), [header_target, html_target.clone()]);
+        assert_eq!(recorded_targets(&backend).await, [html_target]);
+    }
+
+    /// 已记录发送结果的目标
+    async fn recorded_targets(backend: &Backend) -> Vec<String> {
+        let sources = backend
+            .webmention_sources(&["notes/a.md".to_string()])
+            .await
+            .unwrap();
+        sources.into_iter().flat_map(|s| s.sent).collect()
+    }
+
+    #[test]
+    fn test_article_url() {
+        let site = Url::parse("https://blog.example/base/").unwrap();
+        assert_eq!(
+            article_url(&site, "a b").as_str(),
+            "https://blog.example/base/api/articles/a%20b"
+        );
+    }
+
+    #[test]
+    fn test_header_endpoint() {
+        assert_eq!(
+            header_endpoint(r#"<https://a.example/wm>; rel="webmention""#).as_deref(),
+            Some("https://a.example/wm")
+        );
+        assert_eq!(
+            header_endpoint(r#"<https://a.example/x>; rel=me, </wm>; rel="other Webmention""#)
+                .as_deref(),
+            Some("/wm")
+        );
+        assert_eq!(header_endpoint(r#"<https://a.example/x>; rel="me""#), None);
+    }
+
+    #[test]
+    fn test_html_endpoint() {
+        assert_eq!(
+            html_endpoint(r#"<a rel='nofollow webmention' href=/wm>x</a>"#).as_deref(),
+            Some("/wm")
+        );
+        assert_eq!(
+            html_endpoint(r#"<link rel="webmention" href="">"#).as_deref(),
+            Some("")
+        );
+        assert_eq!(
+            html_endpoint(r#"<p>1 < 2</p><link rel="webmention" href="/wm">"#).as_deref(),
+            Some("/wm")
+        );
+        assert_eq!(html_endpoint(r#"<area rel="webmention" href="/wm">"#), None);
+    }
+}
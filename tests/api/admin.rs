@@ -0,0 +1,816 @@
+use super::*;
+
+#[tokio::test]
+async fn test_delete_archive() {
+    let app = Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let git = &app.repo.git;
+    for name in ["2024-Q1", "2024-Q2"] {
+        let commit = app.commit(&[("notes/a.md", name)], &[]);
+        git.reference(
+            &format!("refs/heads/archived/{name}"),
+            git2::Oid::from_str(&commit).unwrap(),
+            false,
+            "archive",
+        )
+        .unwrap();
+    }
+
+    let delete = |uri: &'static str, token: Option<&'static str>| {
+        app.send_json(Method::DELETE, uri, None, token)
+    };
+
+    let (status, _) = delete("/api/admin/archives/2024-Q1", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // 只接受 `YYYY-Qn`
+    let (status, _) = delete("/api/admin/archives/main", Some("s3cret")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = delete("/api/admin/archives/2023-Q4", Some("s3cret")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // 最近的归档需要 force
+    let (status, _) = delete("/api/admin/archives/2024-Q2", Some("s3cret")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // 归档的提交仍在主分支上，删除后都可以到达
+    let (status, json) = delete("/api/admin/archives/2024-Q1", Some("s3cret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "name": "2024-Q1",
+            "branch": true,
+            "tag": false,
+            "unreachable_commits": 0,
+        })
+    );
+    let (status, _) = delete("/api/admin/archives/2024-Q2?force=true", Some("s3cret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(git.find_reference("refs/heads/archived/2024-Q2").is_err());
+    assert!(git.head().is_ok());
+}
+
+#[tokio::test]
+async fn test_orphan_articles() {
+    let app = Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a", "")),
+            // 使用 stem 的组不包含子目录中的文章
+            ("notes/sub/b.md", &post("b", "")),
+            // 使用 path 的组包含子目录中的文章
+            ("wiki/.group.yaml", "public: true\nslug_strategy: path\n"),
+            ("wiki/guides/setup.md", &post("setup", "")),
+            ("drafts/deep/idea.md", &post("idea", "")),
+        ],
+        &[],
+    );
+    let warnings = |report: &SyncReport| {
+        report
+            .warnings
+            .iter()
+            .map(|i| (i.path.clone(), i.reason.clone()))
+            .collect::<Vec<_>>()
+    };
+    let orphan = |path: &str, dir: &str| {
+        (
+            path.to_string(),
+            format!("no group owns this article, add `.group.yaml` to `{dir}/` to publish it"),
+        )
+    };
+
+    let report = app.persist(None, &first).await.unwrap();
+    assert_eq!(
+        warnings(&report),
+        [
+            orphan("drafts/deep/idea.md", "drafts/deep"),
+            orphan("notes/sub/b.md", "notes/sub"),
+        ]
+    );
+
+    // 同一次推送中添加的组包含同时添加的文章，删除的组不再包含修改的文章
+    let second = app.commit(
+        &[
+            ("drafts/deep/.group.yaml", "public: false\n"),
+            ("drafts/deep/new.md", &post("new", "")),
+            ("wiki/guides/setup.md", &post("setup v2", "")),
+        ],
+        &["wiki/.group.yaml"],
+    );
+    let report = app.persist(Some(&first), &second).await.unwrap();
+    assert_eq!(warnings(&report), [orphan("wiki/guides/setup.md", "wiki")]);
+
+    // 文章照常写入，可以随时查询，查询需要 token
+    for token in [None, Some("wrong")] {
+        let (status, _, _) = app
+            .get_with_token("/api/admin/orphan-articles", token)
+            .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{token:?}");
+    }
+    let (status, orphans) = app
+        .send_json(
+            Method::GET,
+            "/api/admin/orphan-articles",
+            None,
+            Some("s3cret"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let orphans = orphans
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| {
+            (
+                a["slug"].as_str().unwrap(),
+                a["group"].as_str().unwrap(),
+                a["source_path"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        orphans,
+        [
+            ("b", "notes/sub", "notes/sub/b.md"),
+            ("guides-setup", "wiki", "wiki/guides/setup.md"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stale_render() {
+    let app = &Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post_with_body("t", "", "a")),
+            ("notes/b.md", &post_with_body("t", "", "b")),
+        ],
+        &[],
+    );
+    let second = app.commit(&[("notes/c.md", &post_with_body("t", "", "c"))], &[]);
+    let limits = ContentLimits::default();
+
+    // a、b 由旧渲染器生成，c 由新渲染器生成
+    app.persist_with(&TaggedRenderer::new("old@1"), &limits, None, &first)
+        .await
+        .unwrap();
+    app.persist_with(
+        &TaggedRenderer::new("new@1"),
+        &limits,
+        Some(&first),
+        &second,
+    )
+    .await
+    .unwrap();
+
+    // 查询和重新渲染都需要 token
+    for (method, uri) in [
+        (Method::GET, "/api/admin/stale-render"),
+        (Method::POST, "/api/admin/rerender-stale"),
+    ] {
+        for token in [None, Some("wrong")] {
+            let (status, headers, _) = app.send(method.clone(), uri, None, token).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri} {token:?}");
+            assert_eq!(headers["www-authenticate"], "Bearer");
+        }
+    }
+
+    let stale = |current: &'static str| async move {
+        let (status, items) = app
+            .send_json(
+                Method::GET,
+                &format!("/api/admin/stale-render?current={current}"),
+                None,
+                Some("s3cret"),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| {
+                (
+                    a["slug"].as_str().unwrap().to_string(),
+                    a["renderer"].clone(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        stale("new@1").await,
+        [
+            ("a".to_string(), serde_json::json!("old@1")),
+            ("b".to_string(), serde_json::json!("old@1"))
+        ]
+    );
+    assert_eq!(
+        stale("old@1").await,
+        [("c".to_string(), serde_json::json!("new@1"))]
+    );
+
+    // 每批一篇，只重新渲染标识不同的文章
+    let renderer = TaggedRenderer::new("new@1");
+    let rows = app.backend.stale_renders("new@1").await.unwrap();
+    let report = rerender_stale(
+        &app.storage(),
+        &renderer,
+        &app.repo.client,
+        &second,
+        &limits,
+        &rows,
+        1,
+    )
+    .await
+    .unwrap();
+    assert_eq!(report.modified, ["notes/a.md", "notes/b.md"]);
+    assert!(report.failed.is_empty() && report.skipped.is_empty());
+    // 正文和摘要各渲染一次
+    assert_eq!(renderer.1.load(Ordering::SeqCst), 4);
+
+    assert!(stale("new@1").await.is_empty());
+    for slug in ["a", "b", "c"] {
+        let content = app.article_json(slug, "重新渲染后的文章").await["content"].clone();
+        assert!(
+            content
+                .as_str()
+                .unwrap()
+                .contains(r#"data-renderer="new@1""#),
+            "{slug}: {content}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_render_report() {
+    render_report_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_render_report_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    render_report_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+/// 同步时记录每篇文章的渲染耗时和大小，超过阈值的写入同步报告
+async fn render_report_scenarios(backend: Backend) {
+    let fixture = Fixture::with_backend(backend, |state| state.with_admin_token(Some("s3cret")));
+    let (app, repo) = (&fixture.app, &fixture.repo);
+    let post =
+        |title: &str, body: &str| post_with_body(title, "datetime: 2024-06-01\ntags: [t]\n", body);
+    let limits = ContentLimits {
+        slow_render_ms: 200,
+        ..Default::default()
+    };
+    let report = |query: &'static str| async move {
+        let (status, items) = app
+            .send_json(
+                Method::GET,
+                &format!("/api/admin/render-report{query}"),
+                None,
+                Some("s3cret"),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        items.as_array().unwrap().clone()
+    };
+    let slugs = |items: &[serde_json::Value]| {
+        items
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let first = repo.commit(
+        &[
+            ("posts/.group.yaml", "name: Posts\npublic: true\n"),
+            ("posts/fast.md", &post("Fast", "quick")),
+            ("posts/slow.md", &post("Slow", "sleep:300")),
+            (
+                "posts/big.md",
+                &post("Big", &format!("sleep:100 {}", "x".repeat(20_000))),
+            ),
+        ],
+        &[],
+    );
+    let sync = repo
+        .persist_with(app, &ScriptedRenderer, &limits, None, &first)
+        .await
+        .unwrap();
+    let slow = sync
+        .slow_renders
+        .iter()
+        .map(|i| i.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(slow, ["posts/slow.md"]);
+    assert!(sync.slow_renders[0].render_ms >= 300);
+    assert!(
+        sync.as_summary()
+            .contains("slow render: 1 article over threshold, slowest posts/slow.md")
+    );
+
+    let items = report("").await;
+    assert_eq!(slugs(&items), ["slow", "big", "fast"]);
+    assert!(items[0]["render_ms"].as_i64().unwrap() >= 300);
+    assert_eq!(items[0]["group"], "posts");
+    assert_eq!(items[0]["source_path"], "posts/slow.md");
+    assert!(items[1]["content_bytes"].as_i64().unwrap() > 20_000);
+    assert!(items[0]["rendered_at"].as_i64().unwrap() > 0);
+    assert_eq!(slugs(&report("?sort=content_bytes").await)[0], "big");
+    assert_eq!(slugs(&report("?limit=1").await), ["slow"]);
+
+    // 复用渲染结果的文章不重新计时，之前的记录保持不变
+    let second = repo.commit(&[("posts/copy.md", &post("Slow", "sleep:300"))], &[]);
+    let sync = repo
+        .persist_with(app, &ScriptedRenderer, &limits, Some(&first), &second)
+        .await
+        .unwrap();
+    assert!(sync.slow_renders.is_empty(), "{:?}", sync.slow_renders);
+    let after = report("").await;
+    assert_eq!(slugs(&after), ["slow", "big", "fast"]);
+    assert_eq!(after[0]["render_ms"], items[0]["render_ms"]);
+    assert_eq!(after[0]["rendered_at"], items[0]["rendered_at"]);
+}
+
+#[tokio::test]
+async fn test_duplicates() {
+    duplicates_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_duplicates_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    duplicates_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+/// 不同组之间内容相同或近似的文章：同步报告中提示，`/api/admin/duplicates` 按组列出
+async fn duplicates_scenarios(backend: Backend) {
+    const OWNERSHIP: &str = "Rust manages memory through a system of ownership with a set of rules \
+        that the compiler checks. If any of the rules are violated, the program will not compile. \
+        None of the features of ownership will slow down your program while it is running. Each value \
+        in Rust has an owner, there can only be one owner at a time, and when the owner goes out of \
+        scope the value will be dropped.";
+    const TOKIO: &str = "Tokio is an asynchronous runtime for the Rust programming language. It \
+        provides the building blocks needed for writing network applications, gives the flexibility \
+        to target a wide range of systems, from large servers with dozens of cores to small embedded \
+        devices.";
+    let fixture = Fixture::with_backend(backend, |state| state.with_admin_token(Some("s3cret")));
+    let (app, repo) = (&fixture.app, &fixture.repo);
+    let post =
+        |title: &str, body: &str| post_with_body(title, "datetime: 2024-06-01\ntags: [t]\n", body);
+    let duplicates = || async move {
+        let (status, clusters) = app
+            .send_json(Method::GET, "/api/admin/duplicates", None, Some("s3cret"))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        clusters.as_array().unwrap().clone()
+    };
+
+    let first = repo.commit(
+        &[
+            ("rust/.group.yaml", "name: Rust\npublic: true\n"),
+            ("mirror/.group.yaml", "name: Mirror\npublic: true\n"),
+            ("rust/ownership.md", &post("Ownership", OWNERSHIP)),
+            ("rust/tokio.md", &post("Tokio", TOKIO)),
+        ],
+        &[],
+    );
+    let sync = repo.persist(app, None, &first).await.unwrap();
+    assert!(sync.warnings.is_empty(), "{:?}", sync.warnings);
+    assert!(duplicates().await.is_empty());
+
+    // 相同内容换了标题、少量改动的内容和完全不同的内容
+    let second = repo.commit(
+        &[
+            ("mirror/copy.md", &post("Copy", OWNERSHIP)),
+            (
+                "mirror/edited.md",
+                &post("Edited", &OWNERSHIP.replace("will slow down", "slow down")),
+            ),
+            (
+                "mirror/other.md",
+                &post(
+                    "Other",
+                    "Completely unrelated notes about gardening, \
+                watering tomatoes in the early morning and keeping snails away from the lettuce \
+                with copper tape around the raised beds.",
+                ),
+            ),
+        ],
+        &[],
+    );
+    let sync = repo.persist(app, Some(&first), &second).await.unwrap();
+    let warnings = sync
+        .warnings
+        .iter()
+        .map(|i| (i.path.as_str(), i.reason.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert_eq!(
+        warnings[0],
+        (
+            "mirror/copy.md",
+            "duplicates the content of rust/ownership.md"
+        )
+    );
+    assert_eq!(warnings[1].0, "mirror/edited.md");
+    assert!(
+        warnings[1]
+            .1
+            .starts_with("nearly duplicates rust/ownership.md ("),
+        "{}",
+        warnings[1].1
+    );
+
+    let clusters = duplicates().await;
+    assert_eq!(clusters.len(), 1, "{clusters:?}");
+    let slugs = clusters[0]["articles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["slug"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(slugs, ["copy", "edited", "ownership"]);
+    assert!(clusters[0]["max_distance"].as_u64().unwrap() > 0);
+    assert_eq!(clusters[0]["articles"][2]["group"], "rust");
+    assert_eq!(
+        clusters[0]["articles"][2]["source_path"],
+        "rust/ownership.md"
+    );
+
+    // 同组内容相同的文章不提示，但同样列出
+    let third = repo.commit(&[("rust/again.md", &post("Again", TOKIO))], &[]);
+    let sync = repo.persist(app, Some(&second), &third).await.unwrap();
+    assert!(sync.warnings.is_empty(), "{:?}", sync.warnings);
+    assert_eq!(duplicates().await.len(), 2);
+}
+
+/// 管理报告只对持有 token 的请求开放
+#[tokio::test]
+async fn test_admin_reports_require_token() {
+    let app = Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    for uri in [
+        "/api/admin/link-report",
+        "/api/admin/pruned-articles",
+        "/api/admin/render-report",
+        "/api/admin/duplicates",
+    ] {
+        for token in [None, Some("wrong")] {
+            let (status, headers, _) = app.get_with_token(uri, token).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{uri} {token:?}");
+            assert_eq!(headers["www-authenticate"], "Bearer");
+        }
+        let (status, body) = app.send_json(Method::GET, uri, None, Some("s3cret")).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+        assert_eq!(body, serde_json::json!([]), "{uri}");
+    }
+}
+
+/// 同步状态包含排队的提交范围，同样只对持有 token 的请求开放
+#[tokio::test]
+async fn test_sync_status_requires_token() {
+    let app = Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    for token in [None, Some("wrong")] {
+        let (status, headers, _) = app.get_with_token("/api/admin/sync-status", token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{token:?}");
+        assert_eq!(headers["www-authenticate"], "Bearer");
+    }
+    assert_eq!(app.sync_status().await["state"], "idle");
+}
+
+#[tokio::test]
+async fn test_admin_preview() {
+    let good = "---\ntitle: Hello\nsummary: s\ndatetime: 2024-01-01 08:00:00\ntags: [rust]\ndraft: true\n---\n\nhello body\n";
+    let broken = "---\ntitle: [unclosed\nsummary: s\n---\n\nbody\n";
+    let app = &Fixture::with_state(|state| {
+        state
+            .with_timezone(Tz::Asia__Shanghai)
+            .with_admin_token(Some("s3cret"))
+    });
+    let commit = app.commit(
+        &[
+            ("notes/.group.yaml", "name: Notes\npublic: true\n"),
+            ("notes/good.md", good),
+            ("notes/broken.md", broken),
+        ],
+        &[],
+    );
+    let preview = |body: serde_json::Value, token: Option<&'static str>| async move {
+        app.send_json(Method::POST, "/api/admin/preview", Some(&body), token)
+            .await
+    };
+
+    let (status, _) = preview(serde_json::json!({ "path": "notes/good.md" }), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 从指定提交中读取文件，未知字段记录为警告
+    let (status, body) = preview(
+        serde_json::json!({ "path": "notes/good.md", "ref": commit }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["group"], "notes");
+    assert_eq!(body["slug"], "good");
+    assert_eq!(body["front_matter"]["title"], "Hello");
+    assert_eq!(body["front_matter"]["tags"], serde_json::json!(["rust"]));
+    assert_eq!(
+        body["front_matter"]["datetime"],
+        "2024-01-01T08:00:00+08:00"
+    );
+    assert_eq!(body["warnings"].as_array().unwrap().len(), 1);
+    assert_eq!(body["warnings"][0]["path"], "notes/good.md");
+    assert!(body["html"].as_str().unwrap().contains("hello body"));
+
+    // 无法解析时返回与同步报告相同的失败记录
+    let report = app
+        .repo
+        .persist_mode(
+            app,
+            &CountingRenderer::default(),
+            &ContentLimits::default(),
+            PersistMode::Incremental,
+            None,
+            &commit,
+        )
+        .await
+        .unwrap();
+    let failed = &report.failed[0];
+    let (status, body) = preview(
+        serde_json::json!({ "path": "notes/broken.md" }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["path"], failed.path.as_str());
+    assert_eq!(body["reason"], failed.reason.as_str());
+    // 附带修改建议
+    assert_eq!(
+        failed.suggestions,
+        ["a value starting with `[` is read as a list, quote it: title: \"[unclosed\""]
+    );
+    assert_eq!(body["suggestions"], serde_json::json!(failed.suggestions));
+
+    // 直接给出的内容按路径推断组和 slug，没有组时给出警告
+    let (status, body) = preview(
+        serde_json::json!({
+            "path": "loose/idea.md",
+            "content": post_with_body("Idea", "", "inline body"),
+        }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        (&body["group"], &body["slug"]),
+        (&"loose".into(), &"idea".into())
+    );
+    assert!(
+        body["warnings"][0]["reason"]
+            .as_str()
+            .unwrap()
+            .starts_with("no group owns this article")
+    );
+
+    let (status, _) = preview(
+        serde_json::json!({ "content": "no front matter" }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = preview(
+        serde_json::json!({ "path": "notes/missing.md" }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = preview(serde_json::json!({}), Some("s3cret")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// 时间旅行接口按历史提交中的组配置返回公开文章，不读写数据库
+#[tokio::test]
+async fn test_timetravel() {
+    let app = &Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let post = |title: &str| post_with_body(title, "", &format!("{title} body"));
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "name: Notes\npublic: true\n"),
+            ("notes/a.md", &post("Old")),
+        ],
+        &[],
+    );
+    let second = app.commit(
+        &[
+            ("notes/.group.yaml", "name: Notes\npublic: false\n"),
+            ("notes/a.md", &post("New")),
+            ("blog/.group.yaml", "name: Blog\npublic: true\n"),
+            ("blog/b.md", &post("Blog")),
+        ],
+        &[],
+    );
+    let get = |path: String, token: Option<&'static str>| async move {
+        app.send_json(Method::GET, &path, None, token).await
+    };
+    let json = |(status, body): (StatusCode, serde_json::Value)| {
+        assert_eq!(status, StatusCode::OK);
+        body
+    };
+
+    let (status, _) = get(format!("/api/timetravel/{first}/articles"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 第一个提交中 notes 组公开
+    let list = json(get(format!("/api/timetravel/{first}/articles"), Some("s3cret")).await);
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["slug"], "a");
+    assert_eq!(list[0]["title"], "Old");
+    assert_eq!(list[0]["group"]["name"], "Notes");
+    assert_eq!(list[0]["commit"], first.as_str());
+    assert_eq!(list[0]["timetravel"], true);
+    assert!(list[0]["id"].is_null());
+
+    let detail = json(
+        get(
+            format!("/api/timetravel/{first}/articles/notes/a"),
+            Some("s3cret"),
+        )
+        .await,
+    );
+    assert_eq!(detail["title"], "Old");
+    assert!(detail["content"].as_str().unwrap().contains("Old body"));
+    assert_eq!(detail["timetravel"], true);
+
+    // 第二个提交中 notes 组不再公开，按分支名读取
+    let list = json(get("/api/timetravel/HEAD/articles".into(), Some("s3cret")).await);
+    let slugs = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["slug"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(slugs, ["b"]);
+    assert_eq!(list[0]["commit"], second.as_str());
+    let (status, _) = get(
+        format!("/api/timetravel/{second}/articles/a"),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let detail = json(
+        get(
+            format!("/api/timetravel/{second}/articles/b"),
+            Some("s3cret"),
+        )
+        .await,
+    );
+    assert_eq!(detail["title"], "Blog");
+
+    let (status, _) = get(
+        "/api/timetravel/1111111111111111111111111111111111111111/articles".into(),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // 不写入数据库
+    assert!(app.backend.groups().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_admin_diff_debug() {
+    let app = &Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let base = app.commit(&[("notes/.group.yaml", "public: true\n")], &[]);
+    let added = app.commit(&[("notes/a.md", "draft\n")], &[]);
+    let removed = app.commit(&[], &["notes/a.md"]);
+    let debug = |body: serde_json::Value, token: Option<&'static str>| async move {
+        app.send_json(Method::POST, "/api/admin/diff-debug", Some(&body), token)
+            .await
+    };
+
+    let (status, _) = debug(serde_json::json!({ "before": base, "after": "HEAD" }), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 新增后删除相互抵消，同步不处理该路径
+    let (status, body) = debug(
+        serde_json::json!({ "before": base, "after": "HEAD" }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        serde_json::json!([{
+            "path": "notes/a.md",
+            "changes": [
+                {
+                    "commit": added,
+                    "status": "added",
+                    "file_kind": "markdown",
+                    "blob": blob_oid(b"draft\n"),
+                },
+                {
+                    "commit": removed,
+                    "status": "deleted",
+                    "file_kind": "markdown",
+                    "blob": blob_oid(b"draft\n"),
+                },
+            ],
+            "merged": null,
+        }])
+    );
+
+    // 全零的起点表示仓库初始状态，不存在的提交返回 404
+    let (status, body) = debug(
+        serde_json::json!({ "before": "0".repeat(40), "after": base }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["merged"], "added");
+    let (status, _) = debug(
+        serde_json::json!({ "before": base, "after": "missing" }),
+        Some("s3cret"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_digest_preview() {
+    let app = &Fixture::with_state(|state| {
+        state
+            .with_site_url("https://notes.example.com/")
+            .with_admin_token(Some("s3cret"))
+    });
+    let post = |title: &str, datetime: &str| post(title, &format!("datetime: {datetime}\n"));
+    let commit = app.commit(
+        &[
+            (
+                "rust/.group.yaml",
+                "public: true\ncategory:\n  id: tech\n  name: 技术\n",
+            ),
+            ("notes/.group.yaml", "public: true\n"),
+            ("secret/.group.yaml", "public: false\n"),
+            ("rust/b.md", &post("B <b>", "2024-05-29 08:00:00")),
+            ("rust/a.md", &post("A", "2024-05-27 00:00:00")),
+            ("notes/c.md", &post("C", "2024-06-02 23:59:59")),
+            ("notes/old.md", &post("Old", "2024-05-26 23:59:59")),
+            ("notes/next.md", &post("Next", "2024-06-03 00:00:00")),
+            ("secret/d.md", &post("D", "2024-05-28 00:00:00")),
+        ],
+        &[],
+    );
+    app.persist(None, &commit).await.unwrap();
+
+    let preview = |body: serde_json::Value, token: Option<&'static str>| async move {
+        app.send_json(
+            Method::POST,
+            "/api/admin/digest/preview",
+            Some(&body),
+            token,
+        )
+        .await
+    };
+    let range = serde_json::json!({ "from": "2024-05-27", "to": "2024-06-03" });
+
+    let (status, _) = preview(range.clone(), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 结束时间不包含，未公开的文章不出现，有分类的在前
+    let (status, body) = preview(range, Some("s3cret")).await;
+    assert_eq!(status, StatusCode::OK);
+    let digest = &body["digest"];
+    assert_eq!(digest["title"], "2024-05-27 – 2024-06-03");
+    assert_eq!(digest["article_count"], 3);
+    let categories = digest["categories"].as_array().unwrap();
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0]["id"], "tech");
+    assert_eq!(categories[0]["name"], "技术");
+    let slugs = |category: &serde_json::Value| {
+        category["articles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(slugs(&categories[0]), ["a", "b"]);
+    assert_eq!(
+        categories[0]["articles"][0]["url"],
+        "https://notes.example.com/api/articles/a"
+    );
+    assert_eq!(categories[1]["id"], serde_json::Value::Null);
+    assert_eq!(slugs(&categories[1]), ["c"]);
+
+    // HTML 中的字段经过转义
+    let html = body["html"].as_str().unwrap();
+    assert!(html.contains("<h2>技术</h2>"), "{html}");
+    assert!(html.contains("B &lt;b&gt;"), "{html}");
+
+    let (status, _) = preview(serde_json::json!({ "from": "last week" }), Some("s3cret")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,1238 @@
+use super::*;
+
+#[tokio::test]
+async fn test_nested_article_path() {
+    let (app, _) = synced_app(&[
+        ("rust/.group.yaml", "public: true\n"),
+        ("rust/ownership.md", &post("ownership", "")),
+        ("posts/blog/.group.yaml", "public: true\n"),
+        ("posts/blog/hello.md", &post("hello", "")),
+        ("笔记/.group.yaml", "public: true\n"),
+        ("笔记/所有权.md", &post("所有权", "")),
+        // 与组 `rust` 同名的文章
+        ("misc/.group.yaml", "public: true\n"),
+        ("misc/rust.md", &post("rust", "")),
+    ])
+    .await;
+
+    let get = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let (status, json) = app.get_json(uri).await;
+            (status, json["title"].clone())
+        }
+    };
+    let found = |title: &str| (StatusCode::OK, serde_json::json!(title));
+
+    // 不带组前缀的 slug 保持可用
+    assert_eq!(get("/api/articles/ownership").await, found("ownership"));
+    // 以组 slug 为前缀，组 slug 可以包含 `/`
+    assert_eq!(
+        get("/api/articles/rust/ownership").await,
+        found("ownership")
+    );
+    assert_eq!(get("/api/articles/posts/blog/hello").await, found("hello"));
+    // 多字节的组 slug 和文章 slug 按百分号编码解码
+    assert_eq!(
+        get("/api/articles/%E7%AC%94%E8%AE%B0/%E6%89%80%E6%9C%89%E6%9D%83").await,
+        found("所有权")
+    );
+    // 文章不在该组中
+    assert_eq!(
+        get("/api/articles/posts/blog/ownership").await.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        get("/api/articles/go/ownership").await.0,
+        StatusCode::NOT_FOUND
+    );
+    // slug 同时是组 slug 时返回文章
+    assert_eq!(get("/api/articles/rust").await, found("rust"));
+    assert_eq!(get("/api/articles/misc/rust").await, found("rust"));
+
+    // 带组前缀的路径同样支持条件请求
+    let req = Request::get("/api/articles/posts/blog/hello")
+        .header(IF_MODIFIED_SINCE, "Fri, 01 Jan 2100 00:00:00 GMT")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.request(req).await.status(), StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn test_rename_redirect() {
+    let app = Fixture::new();
+    let first = app.commit(
+        &[
+            ("posts/blog/.group.yaml", "public: true\n"),
+            ("posts/blog/hello.md", &post("hello", "")),
+            ("笔记/.group.yaml", "public: true\n"),
+            ("笔记/所有权.md", &post("所有权", "")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+    let second = app.commit(
+        &[
+            ("posts/blog/hi.md", &post("hello", "")),
+            ("笔记/借用.md", &post("所有权", "")),
+        ],
+        &["posts/blog/hello.md", "笔记/所有权.md"],
+    );
+    app.persist(Some(&first), &second).await.unwrap();
+
+    // 旧位置跳转到带组前缀的当前位置，跟随跳转可以取到文章
+    for (old, location, title) in [
+        (
+            "/api/articles/hello",
+            "/api/articles/posts/blog/hi",
+            "hello",
+        ),
+        (
+            "/api/articles/posts/blog/hello",
+            "/api/articles/posts/blog/hi",
+            "hello",
+        ),
+        (
+            "/api/articles/%E7%AC%94%E8%AE%B0/%E6%89%80%E6%9C%89%E6%9D%83",
+            "/api/articles/%E7%AC%94%E8%AE%B0/%E5%80%9F%E7%94%A8",
+            "所有权",
+        ),
+    ] {
+        let (status, headers, _) = app.get(old).await;
+        assert_eq!(status, StatusCode::PERMANENT_REDIRECT, "{old}");
+        assert_eq!(headers["location"], location, "{old}");
+
+        let (status, json) = app.get_json(location).await;
+        assert_eq!(status, StatusCode::OK, "{location}");
+        assert_eq!(json["title"], title);
+    }
+}
+
+#[tokio::test]
+async fn test_slug_collisions() {
+    let app = Fixture::new();
+    let warnings = |report: &SyncReport| {
+        report
+            .warnings
+            .iter()
+            .map(|i| (i.path.clone(), i.reason.clone()))
+            .collect::<Vec<_>>()
+    };
+    let collision = |path: &str, other: &str| {
+        (
+            path.to_string(),
+            format!("slug `intro` collides with {other}"),
+        )
+    };
+
+    // 同一批次中的冲突
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/intro.md", &post("notes intro", "")),
+            ("docs/.group.yaml", "public: true\n"),
+            ("docs/intro.md", &post("docs intro", "")),
+        ],
+        &[],
+    );
+    let report = app.persist(None, &first).await.unwrap();
+    assert_eq!(
+        warnings(&report),
+        [collision("notes/intro.md", "docs/intro.md")]
+    );
+
+    // 与之前批次写入的文章冲突
+    let second = app.commit(
+        &[
+            ("wiki/.group.yaml", "public: true\n"),
+            ("wiki/intro.md", &post("wiki intro", "")),
+        ],
+        &[],
+    );
+    let report = app.persist(Some(&first), &second).await.unwrap();
+    assert_eq!(
+        warnings(&report),
+        [collision("wiki/intro.md", "notes/intro.md")]
+    );
+
+    // 修改已存储的同一个文件不是冲突
+    let third = app.commit(&[("wiki/intro.md", &post("wiki intro v2", ""))], &[]);
+    let report = app.persist(Some(&second), &third).await.unwrap();
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+    // 移走原文件时不与其比较
+    let fourth = app.commit(
+        &[("docs/intro.md", &post("docs intro v2", ""))],
+        &["wiki/intro.md"],
+    );
+    let report = app.persist(Some(&third), &fourth).await.unwrap();
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+}
+
+#[tokio::test]
+async fn test_default_tags() {
+    let app = Fixture::new();
+    let post = |tags: &str| post("t", &format!("tags: [{tags}]\n"));
+    let group = "k8s/.group.yaml";
+    let first = app.commit(
+        &[
+            (
+                group,
+                "public: true\ndefault_tags: [kubernetes, ' ops/ ']\n",
+            ),
+            ("k8s/pods.md", &post("ops")),
+            ("k8s/helm.md", &post("helm")),
+        ],
+        &[],
+    );
+    let tags = |slug: &'static str| {
+        let app = &app;
+        async move { app.article_json(slug, "文章").await["tags"].clone() }
+    };
+
+    // 合并、规范化并去重
+    app.persist(None, &first).await.unwrap();
+    assert_eq!(tags("pods").await, serde_json::json!(["ops", "kubernetes"]));
+    assert_eq!(
+        tags("helm").await,
+        serde_json::json!(["helm", "kubernetes", "ops"])
+    );
+    assert_eq!(
+        app.article_list_query("?tags=kubernetes", "按默认标签过滤")
+            .await
+            .len(),
+        2
+    );
+
+    // 只修改组配置，组内已有文章在同一次同步中更新
+    let second = app.commit(&[(group, "public: true\ndefault_tags: [cloud]\n")], &[]);
+    app.persist(Some(&first), &second).await.unwrap();
+    assert_eq!(tags("pods").await, serde_json::json!(["ops", "cloud"]));
+    assert_eq!(tags("helm").await, serde_json::json!(["helm", "cloud"]));
+    assert_eq!(
+        app.tags_list("标签列表").await,
+        [
+            serde_json::json!("cloud"),
+            serde_json::json!("helm"),
+            serde_json::json!("ops")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_article_date_range() {
+    let app = &Fixture::new();
+    let post = |datetime: &str| post("t", &format!("datetime: {datetime}\n"));
+    // 更新时间取提交时间，两篇文章单独提交在更新时间的边界上
+    commit_files_at(
+        &app.repo.git,
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/feb.md", &post("2024-02-29 23:59:59")),
+        ],
+        &[],
+        1709251199,
+    );
+    commit_files_at(
+        &app.repo.git,
+        &[("notes/mar-first.md", &post("2024-03-01 00:00:00"))],
+        &[],
+        1709251200,
+    );
+    let commit = app.commit(
+        &[
+            ("notes/mar-last.md", &post("2024-03-31 23:59:59")),
+            ("notes/apr.md", &post("2024-04-01 00:00:00")),
+            ("other/.group.yaml", "public: true\n"),
+            ("other/mar.md", &post("2024-03-15 12:00:00")),
+        ],
+        &[],
+    );
+    app.persist(None, &commit).await.unwrap();
+
+    let slugs = |query: &'static str| async move {
+        let mut slugs = app
+            .article_list_query(query, query)
+            .await
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        slugs.sort();
+        slugs
+    };
+
+    // 只有日期的上界包含当天全部时间
+    assert_eq!(
+        slugs("?created_after=2024-03-01&created_before=2024-03-31").await,
+        ["mar", "mar-first", "mar-last"]
+    );
+    // 毫秒时间戳和 RFC 3339 的边界都包含端点，2024-03-01T00:00:00Z 为 1709251200000
+    assert_eq!(
+        slugs("?created_after=1709251200000&created_before=1709251200000").await,
+        ["mar-first"]
+    );
+    assert_eq!(
+        slugs("?updated_before=2024-03-01T00:00:00%2B00:00&updated_after=2024-02-29T23:59:59Z")
+            .await,
+        ["feb", "mar-first"]
+    );
+    // 与分组过滤和分页组合
+    assert_eq!(
+        slugs("?group=notes&created_after=2024-03-01&created_before=2024-03-31").await,
+        ["mar-first", "mar-last"]
+    );
+    assert_eq!(
+        slugs("?created_after=2024-03-01&created_before=2024-03-31&limit=2&page=2").await,
+        ["mar-first"]
+    );
+    // 空范围返回空列表
+    assert!(
+        slugs("?created_after=2024-04-01&created_before=2024-03-01")
+            .await
+            .is_empty()
+    );
+
+    // 格式错误时返回 400 并指出参数名
+    for (query, name) in [
+        ("?created_after=March", "created_after"),
+        ("?updated_before=2024-13-01", "updated_before"),
+    ] {
+        let (status, _, body) = app.get(&format!("/api/articles{query}")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{query}");
+        assert!(body.contains(name), "{query}");
+    }
+}
+
+#[tokio::test]
+async fn test_sparse_fields() {
+    let (app, _) = synced_app(&[
+        ("notes/.group.yaml", "public: true\n"),
+        (
+            "notes/intro.md",
+            &post("intro", "summary: hello\ntags: [rust]\n"),
+        ),
+    ])
+    .await;
+
+    let keys = |value: &serde_json::Value| {
+        let mut keys = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    };
+
+    // 默认返回所有字段，列表不包含正文
+    let list = app.article_list_query("", "所有字段").await;
+    assert!(list[0]["summary"].is_string() && list[0]["tags"].is_array());
+    assert!(list[0].get("content").is_none());
+
+    // slug 总是返回
+    let list = app
+        .article_list_query("?fields=title,updated_at", "部分字段")
+        .await;
+    assert_eq!(keys(&list[0]), ["slug", "title", "updated_at"]);
+    let list = app
+        .article_list_query("?fields=summary,%20tags&summary=plain", "摘要和标签")
+        .await;
+    assert_eq!(list[0]["summary"], "hello");
+    assert_eq!(list[0]["tags"], serde_json::json!(["rust"]));
+
+    let detail = app
+        .article_json("intro?fields=title", "详情的部分字段")
+        .await;
+    assert_eq!(keys(&detail), ["slug", "title"]);
+    let detail = app.article_json("intro?fields=content", "详情的正文").await;
+    assert_eq!(keys(&detail), ["content", "slug"]);
+
+    // 未选择的摘要和标签不从存储读取
+    assert_narrowed(&app).await;
+
+    // 未知字段返回 400 并列出可以选择的字段，正文只能在详情中选择
+    for uri in [
+        "/api/articles?fields=title,bogus",
+        "/api/articles?fields=content",
+        "/api/articles/intro?fields=bogus",
+    ] {
+        let (status, _, body) = app.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert!(
+            body.contains("unknown field") && body.contains("updated_at"),
+            "{uri}: {body}"
+        );
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sparse_fields_sqlite() {
+    let repo = TempRepo::new();
+    let commit = repo.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            (
+                "notes/intro.md",
+                &post("intro", "summary: hello\ntags: [rust]\n"),
+            ),
+        ],
+        &[],
+    );
+    let app = TestApp::sqlite(repo.dir.path()).await;
+    repo.persist(&app, None, &commit).await.unwrap();
+    assert_narrowed(&app).await;
+}
+
+/// 不读取摘要和标签时结果中为空值，其他字段不变
+async fn assert_narrowed(app: &TestApp) {
+    let data = app
+        .backend
+        .article_list(
+            1,
+            10,
+            None,
+            None,
+            None,
+            vec![],
+            None,
+            DateRange::default(),
+            ListColumns {
+                summary: false,
+                tags: false,
+            },
+            ListSort::Updated,
+        )
+        .await
+        .unwrap();
+    assert_eq!(data[0].slug, "intro");
+    assert!(data[0].summary.is_empty() && data[0].tags.is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_page_caps() {
+    let app = TestApp::memory();
+
+    // 跳过 10000 篇在上限内
+    assert_eq!(
+        app.get("/api/articles?limit=100&page=101").await.0,
+        StatusCode::OK
+    );
+    assert_eq!(app.get("/api/articles?page=770").await.0, StatusCode::OK);
+
+    for uri in [
+        "/api/articles?limit=101",
+        "/api/articles?limit=0",
+        "/api/archives/content?limit=101",
+        "/api/categories/notes?limit=101",
+    ] {
+        let (status, _, body) = app.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert!(body.contains("`limit`"), "{uri}: {body}");
+    }
+    for uri in ["/api/articles?limit=100&page=102", "/api/articles?page=771"] {
+        let (status, _, body) = app.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert!(body.contains("`page`"), "{uri}: {body}");
+    }
+}
+
+#[tokio::test]
+async fn test_article_public_override() {
+    let app = Fixture::new();
+    let post = |title: &str, public: Option<bool>| {
+        let public = public.map(|p| format!("public: {p}\n")).unwrap_or_default();
+        post(title, &public)
+    };
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a", None)),
+            ("notes/b.md", &post("b", Some(false))),
+            ("notes/c.md", &post("c", Some(true))),
+            ("drafts/.group.yaml", "public: false\n"),
+            ("drafts/d.md", &post("d", Some(true))),
+            ("drafts/e.md", &post("e", None)),
+            ("loose/f.md", &post("f", Some(true))),
+        ],
+        &[],
+    );
+    let overrides = |report: &SyncReport| {
+        let mut items = report
+            .visibility_overrides
+            .iter()
+            .map(|i| (i.path.clone(), i.public))
+            .collect::<Vec<_>>();
+        items.sort();
+        items
+    };
+    let status = |slug: &str| {
+        let (app, uri) = (&app, format!("/api/articles/{slug}"));
+        async move { app.get(&uri).await.0 }
+    };
+
+    let report = app.persist(None, &first).await.unwrap();
+    // 只列出与所在组不同的设置，没有组的文章给出警告
+    assert_eq!(
+        overrides(&report),
+        [
+            ("drafts/d.md".to_string(), true),
+            ("notes/b.md".to_string(), false),
+        ]
+    );
+    assert!(
+        report
+            .as_summary()
+            .contains("public drafts/d.md (group is private)")
+    );
+    assert_eq!(report.warnings.len(), 1);
+
+    for (slug, expected) in [
+        ("a", StatusCode::OK),
+        ("b", StatusCode::NOT_FOUND),
+        ("c", StatusCode::OK),
+        ("d", StatusCode::OK),
+        ("e", StatusCode::NOT_FOUND),
+        ("f", StatusCode::NOT_FOUND),
+    ] {
+        assert_eq!(status(slug).await, expected, "{slug}");
+    }
+
+    // 修改公开设置时更新时间随之改变
+    let before = app.article_json("c", "文章").await["updated_at"].clone();
+    let second = app.commit(
+        &[
+            ("notes/b.md", &post("b", None)),
+            ("notes/c.md", &post("c", None)),
+        ],
+        &[],
+    );
+    let report = app.persist(Some(&first), &second).await.unwrap();
+    assert!(report.visibility_overrides.is_empty());
+    assert_eq!(status("b").await, StatusCode::OK);
+    assert_ne!(app.article_json("c", "文章").await["updated_at"], before);
+}
+
+#[tokio::test]
+async fn test_raw_html_articles() {
+    let app = Fixture::new();
+    let html = "<div id=\"demo\"><canvas></canvas></div>";
+    let post = |summary: &str| {
+        post_with_body("t", &format!("summary: '{summary}'\nrender: false\n"), html)
+    };
+    let commit = app.commit(
+        &[
+            ("demos/.group.yaml", "public: true\nallow_raw_html: true\n"),
+            ("demos/canvas.md", &post("")),
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/sneaky.md", &post("s")),
+        ],
+        &[],
+    );
+    let renderer = TaggedRenderer::new("new@1");
+    let report = app
+        .persist_with(&renderer, &ContentLimits::default(), None, &commit)
+        .await
+        .unwrap();
+
+    // 组允许时正文原样保存，空摘要不渲染
+    let canvas = app.article_json("canvas", "原样输出的文章").await;
+    assert_eq!(canvas["content"], format!("{html}\n"));
+    assert_eq!(canvas["summary"], "");
+
+    // 组不允许时给出警告并照常渲染
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(report.warnings[0].path, "notes/sneaky.md");
+    assert!(report.warnings[0].reason.contains("allow_raw_html"));
+    let sneaky = app.article_json("sneaky", "照常渲染的文章").await;
+    assert!(
+        sneaky["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("<div data-renderer=\"new@1\">")
+    );
+
+    // 原样输出的文章不需要重新渲染
+    let stale = app.backend.stale_renders("new@1").await.unwrap();
+    assert!(stale.is_empty(), "{stale:?}");
+    let stale = app.backend.stale_renders("old@1").await.unwrap();
+    assert_eq!(
+        stale.iter().map(|s| s.slug.as_str()).collect::<Vec<_>>(),
+        ["sneaky"]
+    );
+}
+
+#[tokio::test]
+async fn test_front_matter_schema() {
+    let app = Fixture::new();
+    let post = |extra: &str| post("t", extra);
+    let papers =
+        "public: true\nrequired_fields: [doi, authors]\nfield_types: { doi: string, year: int }\n";
+    let first = app.commit(
+        &[
+            ("papers/.group.yaml", papers),
+            (
+                "papers/good.md",
+                &post("doi: '10.1000/1'\nauthors: [a]\nyear: 2024\n"),
+            ),
+            ("papers/missing.md", &post("doi: '10.1000/2'\n")),
+            (
+                "papers/typed.md",
+                &post("doi: '10.1000/3'\nauthors: [a]\nyear: '2024'\n"),
+            ),
+            ("journal/.group.yaml", "public: true\n"),
+            ("journal/day.md", &post("")),
+        ],
+        &[],
+    );
+    let report = app.persist(None, &first).await.unwrap();
+
+    // 默认只给出警告，文章照常写入；组声明的字段不视为未知字段，没有约定的组不检查
+    let mut warnings = report
+        .warnings
+        .iter()
+        .map(|w| (w.path.as_str(), w.reason.as_str()))
+        .collect::<Vec<_>>();
+    warnings.sort();
+    assert_eq!(
+        warnings,
+        [
+            ("papers/missing.md", "missing required field `authors`"),
+            ("papers/typed.md", "field `year` must be int, found string"),
+        ]
+    );
+    for slug in ["good", "missing", "typed", "day"] {
+        app.article_json(slug, "文章照常写入").await;
+    }
+
+    // 同一次推送中修改的约定对本次推送的文章生效，严格模式下不符合的文件处理失败
+    let second = app.commit(
+        &[
+            (
+                "papers/.group.yaml",
+                &papers.replace("[doi, authors]", "[doi, authors, venue]"),
+            ),
+            ("papers/new.md", &post("doi: 10.5\nauthors: [a]\n")),
+            ("journal/day.md", &post("venue: x\n")),
+        ],
+        &[],
+    );
+    let limits = ContentLimits {
+        strict_metadata: true,
+        ..ContentLimits::default()
+    };
+    let report = app
+        .persist_with(&CountingRenderer::default(), &limits, Some(&first), &second)
+        .await
+        .unwrap();
+    let failed = report
+        .failed
+        .iter()
+        .map(|f| (f.path.as_str(), f.reason.as_str()))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(failed.len(), 2, "{failed:?}");
+    assert_eq!(
+        failed["papers/new.md"],
+        "`papers/new.md`: missing required field `venue`, field `doi` must be string, found float"
+    );
+    // 没有约定的组中未知字段照常按严格模式处理
+    assert!(failed["journal/day.md"].contains("unknown field `venue`"));
+    assert_eq!(app.get("/api/articles/new").await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_canonical_url() {
+    let app = Fixture::new();
+    let post = |canonical: &str| post("t", &format!("canonical: {canonical}\n"));
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/own.md", &super::post("t", "")),
+        ],
+        &[],
+    );
+    let second = app.commit(
+        &[
+            (
+                "notes/reposted.md",
+                &post("https://blog.example.com/original"),
+            ),
+            ("notes/bad.md", &post("\"javascript:alert(1)\"")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+    let report = app.persist(Some(&first), &second).await.unwrap();
+
+    // 无效地址记录为该文件的失败，其余文件照常写入
+    assert_eq!(report.added, ["notes/reposted.md"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].path, "notes/bad.md");
+    assert!(
+        report.failed[0].reason.contains("`notes/bad.md`")
+            && report.failed[0].reason.contains("`javascript:`"),
+        "{}",
+        report.failed[0].reason
+    );
+    app.article("bad", StatusCode::NOT_FOUND, "无效的文章不写入")
+        .await;
+
+    assert_eq!(
+        app.article_json("reposted", "转载的文章").await["canonical_url"],
+        "https://blog.example.com/original"
+    );
+    assert!(app.article_json("own", "原创文章").await["canonical_url"].is_null());
+}
+
+#[tokio::test]
+async fn test_slugs() {
+    let app = Fixture::new();
+    let post = |title: &str| post(title, "");
+
+    // 空数据库返回空列表，同样带有 ETag
+    let (status, body, empty) = app.slugs("", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!([]));
+    // 压缩后 ETag 不变，使用弱 ETag
+    assert!(empty.starts_with("W/\""), "{empty}");
+    let (status, _, etag) = app.slugs("", Some(&empty)).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(etag, empty);
+
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/b.md", &post("b")),
+            ("notes/a.md", &post("a")),
+            ("blog/.group.yaml", "public: true\nslug: posts\n"),
+            ("blog/c.md", &post("c")),
+            ("drafts/.group.yaml", "public: false\n"),
+            ("drafts/d.md", &post("d")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+
+    // 按组 id 和 slug 排序，不包含未公开的文章
+    let (status, body, etag) = app.slugs("", Some(&empty)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(etag, empty);
+    let paths = |body: &serde_json::Value| {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                format!(
+                    "{}/{}",
+                    e["group"].as_str().unwrap(),
+                    e["slug"].as_str().unwrap()
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths(&body), ["notes/a", "notes/b", "posts/c"]);
+    assert!(body[0]["updated_at"].is_i64());
+    assert!(body[0].get("summary").is_none());
+
+    let (status, _, _) = app.slugs("", Some(&etag)).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    let (status, body, _) = app.slugs("?group=posts", Some(&etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(paths(&body), ["posts/c"]);
+
+    // 内容变化后 ETag 随之变化
+    let second = app.commit(&[("notes/e.md", &post("e"))], &[]);
+    app.persist(Some(&first), &second).await.unwrap();
+    let (status, body, changed) = app.slugs("", Some(&etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(changed, etag);
+    assert_eq!(body.as_array().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_edit_url() {
+    let template = "https://git.example.com/me/notes/_edit/main/{path}"
+        .parse()
+        .unwrap();
+    let app = Fixture::with_state(|state| state.with_edit_url(Some(template)));
+    let plain = TestApp::with_backend(app.backend.clone(), GithubAPiRenderer::default(), REPO_PATH);
+    let post = post("intro", "");
+    let first = app.commit(
+        &[
+            ("读书 笔记/.group.yaml", "public: true\n"),
+            ("读书 笔记/intro.md", &post),
+            ("c++/.group.yaml", "public: true\n"),
+        ],
+        &[],
+    );
+
+    app.persist(None, &first).await.unwrap();
+    assert_eq!(
+        app.article_json("intro", "文章").await["edit_url"],
+        "https://git.example.com/me/notes/_edit/main/\
+         %E8%AF%BB%E4%B9%A6%20%E7%AC%94%E8%AE%B0/intro.md"
+    );
+    // 未配置模板时为 null
+    assert!(plain.article_json("intro", "文章").await["edit_url"].is_null());
+
+    // 文件移动后链接指向新位置
+    let moved = app.commit(&[("c++/intro.md", &post)], &["读书 笔记/intro.md"]);
+    app.persist(Some(&first), &moved).await.unwrap();
+    assert_eq!(
+        app.article_json("intro", "文章").await["edit_url"],
+        "https://git.example.com/me/notes/_edit/main/c%2B%2B/intro.md"
+    );
+}
+
+#[tokio::test]
+async fn test_social_card() {
+    let app = Fixture::with_state(|state| {
+        state
+            .with_site_url("https://notes.example.com/")
+            .with_default_og_image(Some("/og.png"))
+    });
+    let plain = TestApp::with_backend(app.backend.clone(), GithubAPiRenderer::default(), REPO_PATH);
+    let post = |summary: &str, extra: &str, body: &str| {
+        post_with_body("t", &format!("summary: \"{summary}\"\n{extra}"), body)
+    };
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            (
+                "notes/pic.md",
+                &post("s", "", r#"<img src="images/a.png">"#),
+            ),
+            (
+                "notes/cover.md",
+                &post("s", "cover: /covers/a.jpg\n", r#"<img src="images/a.png">"#),
+            ),
+            ("notes/bare.md", &post("", "", "")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+
+    // 正文中的相对地址按文章地址展开
+    assert_eq!(
+        app.article_json("pic", "正文图片").await["social"],
+        serde_json::json!({
+            "title": "t",
+            "description": "s",
+            "image": "https://notes.example.com/notes/images/a.png",
+            "url": "https://notes.example.com/notes/pic",
+        })
+    );
+    assert_eq!(
+        app.article_json("cover", "封面").await["social"]["image"],
+        "https://notes.example.com/covers/a.jpg"
+    );
+
+    // 没有摘要、正文和图片时使用默认图片，未配置时为 null
+    let social = app.article_json("bare", "空文章").await["social"].clone();
+    assert!(social["description"].is_null());
+    assert_eq!(social["image"], "https://notes.example.com/og.png");
+    assert!(plain.article_json("bare", "空文章").await["social"]["image"].is_null());
+}
+
+#[tokio::test]
+async fn test_article_attachments() {
+    attachment_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_article_attachments_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    attachment_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+/// 文章附件：引用式链接、自动链接和仓库内的相对链接，缺失的文件记录警告，重新写入时整体替换
+async fn attachment_scenarios(backend: Backend) {
+    let app = Fixture::with_backend(backend, |state| state);
+    let post = |body: &str| post_with_body("t", "", body);
+    let persist = |before: Option<String>, after: String| {
+        let app = &app;
+        async move { app.persist(before.as_deref(), &after).await.unwrap() }
+    };
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/files/slides.pdf", "%PDF-1.4"),
+            (
+                "notes/intro.md",
+                &post(
+                    "See [the slides][deck] and <https://data.example.com/set.csv>.\n\n\
+                     ![plot](files/plot.pdf) [missing](../data/missing.zip)\n\n\
+                     [deck]: files/slides.pdf",
+                ),
+            ),
+            ("notes/plain.md", &post("no attachments")),
+        ],
+        &[],
+    );
+
+    // 缺失的文件照常记录并警告，图片不计入
+    let report = persist(None, first.clone()).await;
+    let warnings = report
+        .warnings
+        .iter()
+        .map(|i| (i.path.as_str(), i.reason.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        [(
+            "notes/intro.md",
+            format!("attachment `data/missing.zip` not found in {first}").as_str()
+        )]
+    );
+    assert_eq!(
+        app.article_json("intro", "附件").await["attachments"],
+        serde_json::json!([
+            {
+                "url_or_path": "notes/files/slides.pdf",
+                "external": false,
+                "kind": "pdf",
+                "link_text": "the slides",
+            },
+            {
+                "url_or_path": "https://data.example.com/set.csv",
+                "external": true,
+                "kind": "csv",
+                "link_text": "https://data.example.com/set.csv",
+            },
+            {
+                "url_or_path": "data/missing.zip",
+                "external": false,
+                "kind": "zip",
+                "link_text": "missing",
+            },
+        ])
+    );
+    assert_eq!(
+        app.article_json("plain", "没有附件").await["attachments"],
+        serde_json::json!([])
+    );
+
+    // 重新写入时替换原有的附件
+    let second = app.commit(
+        &[(
+            "notes/intro.md",
+            &post("Only [slides](/notes/files/slides.pdf)."),
+        )],
+        &[],
+    );
+    let report = persist(Some(first), second.clone()).await;
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    assert_eq!(
+        app.article_json("intro", "替换附件").await["attachments"],
+        serde_json::json!([{
+            "url_or_path": "notes/files/slides.pdf",
+            "external": false,
+            "kind": "pdf",
+            "link_text": "slides",
+        }])
+    );
+
+    // 删除文章后再次添加，不保留旧的附件
+    let third = app.commit(&[], &["notes/intro.md"]);
+    persist(Some(second), third.clone()).await;
+    let fourth = app.commit(&[("notes/intro.md", &post("gone"))], &[]);
+    persist(Some(third), fourth).await;
+    assert_eq!(
+        app.article_json("intro", "删除后添加").await["attachments"],
+        serde_json::json!([])
+    );
+}
+
+#[tokio::test]
+async fn test_tag_index_matches_rebuild() {
+    let incremental = Fixture::new();
+    let post = |tags: &str, day: u32| {
+        post(
+            "t",
+            &format!("datetime: 2024-01-{day:02}\ntags: [{tags}]\n"),
+        )
+    };
+    let first = incremental.commit(
+        &[
+            ("notes/.group.yaml", "public: true\ndefault_tags: [notes]\n"),
+            ("drafts/.group.yaml", "public: false\n"),
+            ("notes/a.md", &post("rust, web", 1)),
+            ("notes/b.md", &post("go", 2)),
+            ("drafts/c.md", &post("rust", 3)),
+        ],
+        &[],
+    );
+    let second = incremental.commit(
+        &[
+            ("notes/b.md", &post("rust", 4)),
+            ("drafts/.group.yaml", "public: true\n"),
+        ],
+        &["notes/a.md"],
+    );
+    let third = incremental.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("drafts/d.md", &post("zig", 5)),
+        ],
+        &[],
+    );
+
+    // 逐个提交增量写入，标签索引只按涉及的文章和组更新
+    incremental.persist(None, &first).await.unwrap();
+    incremental.persist(Some(&first), &second).await.unwrap();
+    incremental.persist(Some(&second), &third).await.unwrap();
+
+    // 按最后一个提交重建
+    let rebuilt = TestApp::memory();
+    incremental
+        .repo
+        .persist(&rebuilt, None, &third)
+        .await
+        .unwrap();
+
+    let counts = incremental.backend.tags_with_counts().await.unwrap();
+    assert_eq!(counts, rebuilt.backend.tags_with_counts().await.unwrap());
+    assert_eq!(
+        counts
+            .iter()
+            .map(|t| (t.tag.as_str(), t.article_count))
+            .collect::<Vec<_>>(),
+        [("rust", 2), ("zig", 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_tag_tree() {
+    let app = TestApp::memory();
+    let at = Utc
+        .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+        .unwrap()
+        .fixed_offset();
+    let tagged = |slug: &str, tags: &[&str]| {
+        let mut article = test_article(slug, at);
+        article.frontmatter.tags = tags.iter().map(|t| t.to_string()).collect();
+        article
+    };
+    let mut store = app.storage();
+    store
+        .upsert_group(&Group::new("notes/.group.yaml", "public: true\n".into()).unwrap())
+        .upsert_article(&tagged("rust", &["lang/rust", "lang"]))
+        .upsert_article(&tagged("go", &["lang/go"]))
+        .upsert_article(&tagged("language", &["language"]));
+    store.commit().await.unwrap();
+
+    let get = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let (status, json) = app.get_json(uri).await;
+            assert_eq!(status, StatusCode::OK, "{uri}");
+            json
+        }
+    };
+
+    // 默认仍返回扁平列表
+    assert_eq!(
+        get("/api/tags").await,
+        serde_json::json!(["lang", "lang/go", "lang/rust", "language"])
+    );
+
+    let tree = get("/api/tags?tree=true").await;
+    assert_eq!(tree[0]["path"], "lang");
+    assert_eq!(
+        (tree[0]["count"].as_i64(), tree[0]["direct"].as_i64()),
+        (Some(2), Some(1))
+    );
+    assert_eq!(tree[0]["children"][1]["path"], "lang/rust");
+    assert_eq!(tree[1]["path"], "language");
+
+    // 上层标签过滤匹配后代，不匹配仅前缀相同的标签
+    let slugs = |list: serde_json::Value| {
+        let mut slugs = list
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        slugs.sort();
+        slugs
+    };
+    assert_eq!(slugs(get("/api/articles?tags=lang").await), ["go", "rust"]);
+    assert_eq!(slugs(get("/api/articles?tags=lang/go").await), ["go"]);
+}
+
+#[tokio::test]
+async fn test_title_sort() {
+    title_sort_scenarios(&TestApp::memory()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_title_sort_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    title_sort_scenarios(&TestApp::sqlite(dir.path()).await).await;
+}
+
+/// 按标题排序和首字母索引：中英文标题混合排列，数字和其他文字开头的标题归入 `#`
+async fn title_sort_scenarios(app: &TestApp) {
+    let titles = [
+        ("zhong", "中文标题"),
+        ("zebra", "zebra"),
+        ("apple", "Apple"),
+        ("ali", "阿里"),
+        ("summary", "2024 总结"),
+        ("santi", "《三体》读后感"),
+        ("banana", "banana"),
+        ("beijing", "北京"),
+        ("privet", "Привет"),
+    ];
+    let mut store = app.storage();
+    store.upsert_group(&Group::new("notes/.group.yaml", "public: true\n".into()).unwrap());
+    for (i, (slug, title)) in titles.into_iter().enumerate() {
+        let at = Utc
+            .with_ymd_and_hms(2024, 1, 1 + i as u32, 0, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        let mut article = test_article(slug, at);
+        article.frontmatter.title = title.to_string();
+        store.upsert_article(&article);
+    }
+    store.commit().await.unwrap();
+
+    let slugs = |list: Vec<serde_json::Value>| {
+        list.iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    // 默认仍按更新时间排列
+    assert_eq!(
+        slugs(app.article_list("按更新时间").await)[..2],
+        ["privet", "beijing"]
+    );
+    let sorted = [
+        "summary", "ali", "apple", "banana", "beijing", "santi", "zebra", "zhong", "privet",
+    ];
+    assert_eq!(
+        slugs(app.article_list_query("?sort=title", "按标题").await),
+        sorted
+    );
+    // 分页保持同一顺序
+    assert_eq!(
+        slugs(
+            app.article_list_query("?sort=title&limit=4&page=2", "按标题分页")
+                .await
+        ),
+        sorted[4..8]
+    );
+
+    let (status, index) = app.get_json("/api/articles/index").await;
+    assert_eq!(status, StatusCode::OK);
+    let buckets = index
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            let titles = b["articles"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["title"].as_str().unwrap())
+                .collect::<Vec<_>>();
+            (b["initial"].as_str().unwrap(), titles)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        buckets,
+        [
+            ("A", vec!["阿里", "Apple"]),
+            ("B", vec!["banana", "北京"]),
+            ("S", vec!["《三体》读后感"]),
+            ("Z", vec!["zebra", "中文标题"]),
+            ("#", vec!["2024 总结", "Привет"]),
+        ]
+    );
+    assert_eq!(index[0]["articles"][0]["group"], "notes");
+
+    assert_eq!(
+        app.get("/api/articles?sort=name").await.0,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn test_article_short_id() {
+    let app = Fixture::new();
+    let post = |body: &str| post_with_body("t", "", body);
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a")),
+            ("notes/b.md", &post("b")),
+        ],
+        &[],
+    );
+    let short_link = |id: String| {
+        let app = &app;
+        async move { app.get_json(&format!("/api/a/{id}")).await }
+    };
+    let id_of = |slug: &'static str| {
+        let app = &app;
+        async move {
+            app.article_json(slug, slug).await["id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        }
+    };
+
+    app.persist(None, &first).await.unwrap();
+    let (a, b) = (id_of("a").await, id_of("b").await);
+    assert_ne!(a, b);
+    assert!(is_short_id(&a) && is_short_id(&b), "{a} {b}");
+    // 列表返回同样的短 id
+    let listed = app
+        .article_list("文章列表")
+        .await
+        .into_iter()
+        .map(|item| item["id"].as_str().unwrap().to_string())
+        .collect::<HashSet<_>>();
+    assert_eq!(listed, HashSet::from([a.clone(), b.clone()]));
+
+    let (status, body) = short_link(a.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "a");
+
+    // 文件移动后短 id 不变，返回新的 slug
+    let second = app.commit(&[("notes/moved.md", &post("a"))], &["notes/a.md"]);
+    app.persist(Some(&first), &second).await.unwrap();
+    app.article("a", StatusCode::PERMANENT_REDIRECT, "旧位置重定向")
+        .await;
+    assert_eq!(id_of("moved").await, a);
+    let (status, body) = short_link(a.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "moved");
+
+    // 重建后按源文件路径沿用短 id
+    app.persist(None, &second).await.unwrap();
+    assert_eq!(
+        (id_of("moved").await, id_of("b").await),
+        (a.clone(), b.clone())
+    );
+
+    // 删除的文章和格式不对的短 id 都不存在
+    let third = app.commit(&[], &["notes/b.md"]);
+    app.persist(Some(&second), &third).await.unwrap();
+    assert_eq!(short_link(b).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(
+        short_link("not-an-id".to_string()).await.0,
+        StatusCode::NOT_FOUND
+    );
+
+    // 新文章分配新的短 id
+    let fourth = app.commit(&[("notes/c.md", &post("c"))], &[]);
+    app.persist(Some(&third), &fourth).await.unwrap();
+    let c = id_of("c").await;
+    assert!(is_short_id(&c) && c != a, "{c}");
+}
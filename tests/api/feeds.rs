@@ -0,0 +1,219 @@
+use super::*;
+
+#[tokio::test]
+async fn test_feeds() {
+    let (app, _) = synced_app(&[
+        (
+            "posts/rust/.group.yaml",
+            "public: true\nname: \"<Rust> & Go\"\n",
+        ),
+        ("posts/rust/intro.md", &post("intro", "tags: [rust, web]\n")),
+        ("posts/rust/next.md", &post("next", "tags: [rust]\n")),
+        ("drafts/.group.yaml", "public: false\n"),
+        ("drafts/secret.md", &post("secret", "tags: [hidden]\n")),
+    ])
+    .await;
+
+    let (status, _, xml) = app.get("/api/groups/posts/rust/feed.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        xml.contains("<title>&lt;Rust&gt; &amp; Go</title>"),
+        "{xml}"
+    );
+    assert!(xml.contains(
+        r#"<atom:link href="http://localhost:3000/api/groups/posts/rust/feed.xml" rel="self""#
+    ));
+    assert_eq!(xml.matches("<item>").count(), 2);
+
+    // 同一篇文章出现在多个标签订阅源中，guid 都是文章的规范地址
+    let guid = r#"<guid isPermaLink="true">http://localhost:3000/api/articles/intro</guid>"#;
+    for uri in ["/api/tags/rust/feed.xml", "/api/tags/web/feed.xml"] {
+        let (status, _, xml) = app.get(uri).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+        assert_eq!(xml.matches(guid).count(), 1, "{uri}");
+        assert!(xml.contains("<category>rust</category><category>web</category>"));
+    }
+    assert_eq!(
+        app.get("/api/tags/rust/feed.xml")
+            .await
+            .2
+            .matches("<item>")
+            .count(),
+        2
+    );
+
+    // 未知或未公开的组和标签返回 404
+    for uri in [
+        "/api/groups/unknown/feed.xml",
+        "/api/groups/drafts/feed.xml",
+        "/api/groups/posts/rust/feed",
+        "/api/tags/unknown/feed.xml",
+        "/api/tags/hidden/feed.xml",
+    ] {
+        assert_eq!(app.get(uri).await.0, StatusCode::NOT_FOUND, "{uri}");
+    }
+
+    // 不以 `/feed.xml` 结尾的地址为组详情
+    assert_eq!(app.get("/api/groups/posts/rust").await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_group_id_ending_in_feed() {
+    let (app, _) = synced_app(&[
+        ("news/feed/.group.yaml", "public: true\nname: News\n"),
+        ("news/feed/intro.md", &post("intro", "")),
+        ("feed/.group.yaml", "public: true\nname: Feed\n"),
+        ("feed/first.md", &post("first", "")),
+    ])
+    .await;
+
+    // 组 id 以 `feed` 结尾时仍为组详情
+    for (uri, id) in [
+        ("/api/groups/news/feed", "news/feed"),
+        ("/api/groups/feed", "feed"),
+    ] {
+        let (status, json) = app.get_json(uri).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+        assert_eq!(json["id"], id, "{uri}");
+    }
+
+    // 在组 id 后加 `/feed.xml` 为组的订阅源
+    for (uri, title) in [
+        ("/api/groups/news/feed/feed.xml", "<title>News</title>"),
+        ("/api/groups/feed/feed.xml", "<title>Feed</title>"),
+    ] {
+        let (status, _, xml) = app.get(uri).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+        assert!(xml.contains(title), "{xml}");
+        assert_eq!(xml.matches("<item>").count(), 1, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn test_read_page() {
+    let (app, _) = synced_app(&[
+        (
+            "posts/rust/.group.yaml",
+            "public: true\nname: Rust\nslug: rust\nauthor:\n  name: Alice\n",
+        ),
+        (
+            "posts/rust/intro.md",
+            &post("<Intro>", "aliases: [old-intro]\n"),
+        ),
+        (
+            "posts/rust/next.md",
+            &post("Next", "datetime: 2024-01-02\n"),
+        ),
+        ("drafts/.group.yaml", "public: false\n"),
+        ("drafts/secret.md", &post("secret", "")),
+        ("drafts/shared.md", &post("shared", "public: true\n")),
+    ])
+    .await;
+
+    let (status, headers, html) = app.get("/read/rust/intro").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[CONTENT_TYPE], "text/html; charset=utf-8");
+    assert!(headers.contains_key(CACHE_CONTROL));
+    assert!(headers.contains_key(LAST_MODIFIED));
+    assert!(
+        html.contains(r#"<link rel="canonical" href="http://localhost:3000/rust/intro">"#),
+        "{html}"
+    );
+    assert!(html.contains("<h1>&lt;Intro&gt;</h1>"), "{html}");
+    assert!(html.contains("Alice · "), "{html}");
+    assert!(
+        html.contains(r#"<a rel="next" href="/read/rust/next">"#),
+        "{html}"
+    );
+    assert!(!html.contains("<script"));
+    // 上一篇/下一篇的链接同样带组 slug，不经过旧位置跳转
+    let (_, _, html) = app.get("/read/rust/next").await;
+    assert!(
+        html.contains(r#"<a rel="prev" href="/read/rust/intro">"#),
+        "{html}"
+    );
+
+    // 与文章接口相同，文章 slug 本身也可以访问
+    assert_eq!(app.get("/read/intro").await.0, StatusCode::OK);
+
+    let (status, headers, _) = app.get("/read/old-intro").await;
+    assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(headers["location"], "/read/rust/intro");
+
+    let req = Request::get("/read/rust/intro")
+        .header(IF_MODIFIED_SINCE, "Fri, 31 Dec 9999 23:59:59 GMT")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(app.request(req).await.status(), StatusCode::NOT_MODIFIED);
+
+    // 未公开或不存在的文章返回 404，单独公开的文章可以访问
+    for uri in [
+        "/read/secret",
+        "/read/drafts/secret",
+        "/read/rust/unknown",
+        "/read/rust",
+    ] {
+        assert_eq!(app.get(uri).await.0, StatusCode::NOT_FOUND, "{uri}");
+    }
+    assert_eq!(app.get("/read/drafts/shared").await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_permalink() {
+    let (plain, _) = synced_app(&[
+        (
+            "2023/rust-notes/.group.yaml",
+            "public: true\nslug: rust-notes\n",
+        ),
+        (
+            "2023/rust-notes/intro.md",
+            &post("intro", "datetime: 2023-06-07\n"),
+        ),
+    ])
+    .await;
+    let with_pattern = |pattern: &str| {
+        let state = state::AppState::new(
+            plain.backend.clone(),
+            GithubAPiRenderer::default(),
+            REPO_PATH,
+        )
+        .with_permalink(Some(pattern.parse().unwrap()));
+        TestApp::from_state(plain.backend.clone(), state)
+    };
+    let dated = with_pattern("/blog/:year/:month/:day/:slug");
+    let grouped = with_pattern("/:group/:slug");
+
+    let (status, _, body) = dated.get("/api/permalink/blog/2023/06/07/intro").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains(r#""redirect_to":"rust-notes/intro""#),
+        "{body}"
+    );
+    // 日期与创建时间不一致、不符合格式、文章不存在
+    for uri in [
+        "/api/permalink/blog/2023/06/08/intro",
+        "/api/permalink/blog/2022/06/07/intro",
+        "/api/permalink/2023/06/07/intro",
+        "/api/permalink/blog/2023/06/07/missing",
+    ] {
+        assert_eq!(dated.get(uri).await.0, StatusCode::NOT_FOUND, "{uri}");
+    }
+
+    // 组可以是 slug 或目录路径
+    for uri in [
+        "/api/permalink/rust-notes/intro",
+        "/api/permalink/2023/rust-notes/intro",
+    ] {
+        assert_eq!(grouped.get(uri).await.0, StatusCode::OK, "{uri}");
+    }
+    assert_eq!(
+        grouped.get("/api/permalink/go/intro").await.0,
+        StatusCode::NOT_FOUND
+    );
+
+    // 未配置格式
+    assert_eq!(
+        plain.get("/api/permalink/intro").await.0,
+        StatusCode::NOT_FOUND
+    );
+}
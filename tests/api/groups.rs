@@ -0,0 +1,845 @@
+use super::*;
+
+#[tokio::test]
+async fn test_archive_changes() {
+    let app = Fixture::new();
+    let git = &app.repo.git;
+    let archive = |name: &str, commit: &str| {
+        let oid = git2::Oid::from_str(commit).unwrap();
+        git.reference(
+            &format!("refs/heads/archived/{name}"),
+            oid,
+            false,
+            "archive",
+        )
+        .unwrap();
+    };
+
+    let q1 = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", "a"),
+            ("notes/b.md", "b"),
+        ],
+        &[],
+    );
+    archive("2024-Q1", &q1);
+    let q2 = app.commit(
+        &[
+            ("notes/c.md", "c"),
+            ("notes/a.md", "a2"),
+            ("posts/d.md", "d"),
+        ],
+        &["notes/b.md"],
+    );
+    let target = git
+        .find_object(git2::Oid::from_str(&q2).unwrap(), None)
+        .unwrap();
+    git.tag_lightweight("archive/2024-Q2", &target, false)
+        .unwrap();
+
+    // 省略 since 时与上一个季度的归档比较
+    let (status, json) = app.get_json("/api/archives/2024-Q2/changes").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["since"], "2024-Q1");
+    assert_eq!(json["added"], 2);
+    assert_eq!(json["removed"], 1);
+    assert_eq!(json["groups"][0]["group"], "notes");
+    assert_eq!(
+        json["groups"][0]["added"],
+        serde_json::json!(["notes/c.md"])
+    );
+    assert_eq!(
+        json["groups"][0]["removed"],
+        serde_json::json!(["notes/b.md"])
+    );
+    assert_eq!(json["groups"][1]["group"], "posts");
+
+    // 第一个归档与仓库初始状态比较
+    let (_, json) = app.get_json("/api/archives/2024-Q1/changes").await;
+    assert_eq!(json["since"], serde_json::Value::Null);
+    assert_eq!(json["added"], 2);
+
+    let (status, json) = app
+        .get_json("/api/archives/2024-Q2/changes?since=2024-Q2")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["added"], 0);
+    assert_eq!(json["groups"], serde_json::json!([]));
+
+    let (status, _) = app.get_json("/api/archives/2024-Q3/changes").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app
+        .get_json("/api/archives/2024-Q2/changes?since=2023-Q4")
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_archived_groups() {
+    let app = Fixture::new();
+    let commit = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/current.md", &post("current", "")),
+            ("2024-q1/.group.yaml", "public: true\narchived: true\n"),
+            ("2024-q1/old.md", &post("old", "")),
+        ],
+        &[],
+    );
+    let rebuild = || async { app.persist(None, &commit).await.expect("重建失败") };
+    let get = |uri: &'static str| {
+        let app = &app;
+        async move {
+            let (status, json) = app.get_json(uri).await;
+            assert_eq!(status, StatusCode::OK, "{uri}");
+            json
+        }
+    };
+    let slugs = |json: serde_json::Value| {
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    rebuild().await;
+    // 重建两次，归档状态来自组配置，不随重建丢失
+    for _ in 0..2 {
+        assert_eq!(slugs(get("/api/articles").await), ["current"]);
+        let mut all = slugs(get("/api/articles?include_archived=true").await);
+        all.sort();
+        assert_eq!(all, ["current", "old"]);
+        assert_eq!(slugs(get("/api/archives/content").await), ["old"]);
+
+        let old = get("/api/articles/old").await;
+        assert_eq!(old["archived"], true);
+        assert_eq!(get("/api/articles/current").await["archived"], false);
+
+        rebuild().await;
+    }
+}
+
+#[tokio::test]
+async fn test_group_slug() {
+    let app = &Fixture::new();
+    let rust_group = "2023/rust-notes/.group.yaml";
+    let first = app.commit(
+        &[
+            (rust_group, "public: true\nslug: rust-notes\n"),
+            ("2023/rust-notes/intro.md", &post("intro", "")),
+            ("go/.group.yaml", "public: true\n"),
+            ("go/hello.md", &post("hello", "")),
+        ],
+        &[],
+    );
+    let group_ids = move || async move {
+        let mut ids = app
+            .group_list("组列表")
+            .await
+            .into_iter()
+            .map(|g| g["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    // 设置 slug：对外的组 id 使用 slug，目录路径只作为提示
+    app.persist(None, &first).await.unwrap();
+    assert_eq!(group_ids().await, ["go", "rust-notes"]);
+    assert_eq!(
+        app.article_json("intro", "文章").await["group"]["id"],
+        "rust-notes"
+    );
+    let (status, _, body) = app.get("/api/articles?group=rust-notes").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains(r#""slug":"intro""#), "{body}");
+
+    let (status, _, body) = app.get("/api/articles?group=2023/rust-notes").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("`rust-notes`"), "{body}");
+    let (status, _, body) = app.get("/api/resolve/2023/rust-notes/intro").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains(r#""redirect_to":"rust-notes/intro""#),
+        "{body}"
+    );
+    let (status, headers, _) = app.get("/api/groups/2023/rust-notes/feed.xml").await;
+    assert_eq!(status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(headers["location"], "/api/groups/rust-notes/feed.xml");
+    assert_eq!(
+        app.get("/api/groups/rust-notes/feed.xml").await.0,
+        StatusCode::OK
+    );
+
+    // 修改 slug
+    let second = app.commit(&[(rust_group, "public: true\nslug: rust\n")], &[]);
+    app.persist(Some(&first), &second).await.unwrap();
+    assert_eq!(group_ids().await, ["go", "rust"]);
+    assert_eq!(
+        app.article_json("intro", "文章").await["group"]["id"],
+        "rust"
+    );
+
+    // 与另一个组的目录路径冲突时同步失败，错误信息包含两个组的目录路径
+    let conflict = app.commit(&[(rust_group, "public: true\nurl: go\n")], &[]);
+    let err = app
+        .persist(Some(&second), &conflict)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("`2023/rust-notes`") && err.contains("`go`"),
+        "{err}"
+    );
+    assert_eq!(group_ids().await, ["go", "rust"]);
+
+    // 删除 slug 后恢复使用目录路径
+    let removed = app.commit(&[(rust_group, "public: true\n")], &[]);
+    app.persist(Some(&second), &removed).await.unwrap();
+    assert_eq!(group_ids().await, ["2023/rust-notes", "go"]);
+    let (status, _, body) = app.get("/api/articles?group=2023/rust-notes").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains(r#""slug":"intro""#), "{body}");
+}
+
+#[tokio::test]
+async fn test_api_index() {
+    let memory = MemoryStorage::new();
+    let app = Fixture::with_backend(memory.clone().into(), |state| state);
+    let commit = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a", "tags: [rust, web]\n")),
+            ("notes/b.md", &post("b", "tags: [rust]\n")),
+            ("drafts/.group.yaml", "public: false\n"),
+            ("drafts/c.md", &post("c", "tags: [secret]\n")),
+        ],
+        &[],
+    );
+    app.persist(None, &commit).await.unwrap();
+
+    async fn index(app: &TestApp) -> serde_json::Value {
+        let (status, json) = app.get_json("/api").await;
+        assert_eq!(status, StatusCode::OK);
+        json
+    }
+
+    // 不需要认证，只统计公开内容
+    let reads = memory.reads();
+    let body = index(&app).await;
+    assert_eq!(body["name"], "gitnote");
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!((&body["articles"], &body["groups"]), (&2.into(), &1.into()));
+    assert_eq!(body["tags"], 2);
+    assert!(body["last_synced_at"].is_null() || body["last_synced_at"].is_i64());
+    let endpoints = body["endpoints"].as_array().unwrap();
+    assert!(endpoints.contains(&"/api/articles".into()), "{endpoints:?}");
+    assert!(endpoints.contains(&"/api".into()), "{endpoints:?}");
+    assert!(
+        !body
+            .to_string()
+            .contains(&app.repo.dir.path().display().to_string())
+    );
+    assert_eq!(memory.reads(), reads + 1, "第一次请求查询数据库");
+
+    // 缓存期内的请求不再查询，写入的新文章在缓存过期后才计入
+    let mut store = app.storage();
+    store.remove_article(ArticleRef {
+        slug: "b",
+        group: "notes",
+    });
+    store.commit().await.unwrap();
+    let reads = memory.reads();
+    assert_eq!(index(&app).await["articles"], 2);
+    assert_eq!(memory.reads(), reads, "缓存期内不查询数据库");
+
+    tokio::time::pause();
+    tokio::time::advance(Duration::from_secs(60)).await;
+    assert_eq!(index(&app).await["articles"], 1);
+    assert_eq!(memory.reads(), reads + 1, "缓存过期后重新查询");
+}
+
+#[tokio::test]
+async fn test_group_readme() {
+    let app = Fixture::new();
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            (
+                "notes/README.md",
+                "---\ntitle: ignored\n---\n\nAbout notes\n",
+            ),
+            ("notes/intro.md", &post("t", "")),
+            ("drafts/README.md", "About drafts\n"),
+        ],
+        &[],
+    );
+    let second = app.commit(
+        &[
+            ("notes/README.md", "Notes, updated\n"),
+            ("drafts/.group.yaml", "public: true\n"),
+        ],
+        &[],
+    );
+    let third = app.commit(&[], &["notes/README.md"]);
+
+    // 新增：说明文件不作为文章收录，Front Matter 被忽略
+    app.persist(None, &first).await.unwrap();
+    let group = app.group_json("notes", StatusCode::OK, "组详情").await;
+    assert_eq!(
+        group["description_html"],
+        "<counted>About notes\n</counted>"
+    );
+    let slugs = app
+        .article_list("文章列表")
+        .await
+        .iter()
+        .map(|a| a["slug"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(slugs, ["intro"]);
+    app.article("README", StatusCode::NOT_FOUND, "说明文件不是文章")
+        .await;
+    app.group_json("drafts", StatusCode::NOT_FOUND, "没有组配置的目录不是组")
+        .await;
+
+    // 更新：组配置晚于说明文件提交时，组创建后补上说明
+    app.persist(Some(&first), &second).await.unwrap();
+    let group = app.group_json("notes", StatusCode::OK, "组详情").await;
+    assert_eq!(
+        group["description_html"],
+        "<counted>Notes, updated\n</counted>"
+    );
+    let group = app.group_json("drafts", StatusCode::OK, "新建的组").await;
+    assert_eq!(
+        group["description_html"],
+        "<counted>About drafts\n</counted>"
+    );
+
+    // 删除：清除说明
+    app.persist(Some(&second), &third).await.unwrap();
+    let group = app.group_json("notes", StatusCode::OK, "组详情").await;
+    assert!(group["description_html"].is_null());
+    assert_eq!(group["id"], "notes");
+
+    // 重建后结果一致
+    app.persist(None, &third).await.unwrap();
+    let group = app.group_json("drafts", StatusCode::OK, "重建后的组").await;
+    assert_eq!(
+        group["description_html"],
+        "<counted>About drafts\n</counted>"
+    );
+    assert!(
+        app.group_json("notes", StatusCode::OK, "重建后的组").await["description_html"].is_null()
+    );
+}
+
+/// 修改组配置只原位更新组记录：内存存储中先删除再写入会丢失组说明，组内文章也会短暂消失
+#[tokio::test]
+async fn test_group_metadata_update() {
+    let app = Fixture::new();
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "name: Notes\npublic: true\n"),
+            ("notes/README.md", "About notes\n"),
+            ("notes/intro.md", &post("t", "")),
+        ],
+        &[],
+    );
+    let second = app.commit(
+        &[("notes/.group.yaml", "name: Renamed\npublic: true\n")],
+        &[],
+    );
+    // 范围内先删除再恢复组配置
+    app.commit(&[], &["notes/.group.yaml"]);
+    let fourth = app.commit(
+        &[("notes/.group.yaml", "name: Restored\npublic: true\n")],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+
+    for (before, after, name) in [(&first, &second, "Renamed"), (&second, &fourth, "Restored")] {
+        let entries = app.repo.client.diff_commits(before, after).await.unwrap();
+        let kinds = entries
+            .iter()
+            .map(|e| (e.path().to_string_lossy().into_owned(), e.change_kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [("notes/.group.yaml".to_string(), ChangeKind::Modified)]
+        );
+
+        app.persist(Some(before), after).await.unwrap();
+        let group = app.group_json("notes", StatusCode::OK, "组详情").await;
+        assert_eq!(group["name"], name);
+        assert_eq!(
+            group["description_html"],
+            "<counted>About notes\n</counted>"
+        );
+        assert_eq!(app.article_list("文章列表").await.len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn test_categories() {
+    let app = Fixture::new();
+    let tech = |public: bool| format!("public: {public}\ncategory:\n  id: tech\n  name: 技术\n");
+    app.commit(
+        &[
+            ("rust/.group.yaml", &tech(true)),
+            ("go/.group.yaml", &tech(true)),
+            ("secret/.group.yaml", &tech(false)),
+            (
+                "hidden/.group.yaml",
+                "public: false\ncategory:\n  name: Hidden\n",
+            ),
+        ],
+        &[],
+    );
+    // 逐篇提交，更新时间依次递增
+    let mut commit = String::new();
+    for (d, path) in (1..).zip([
+        "rust/a.md",
+        "rust/b.md",
+        "go/c.md",
+        "secret/d.md",
+        "hidden/e.md",
+    ]) {
+        let post = post(&format!("t{d}"), &format!("datetime: 2024-01-0{d}\n"));
+        commit = commit_files_at(
+            &app.repo.git,
+            &[(path, &post)],
+            &[],
+            1704067200 + i64::from(d),
+        );
+    }
+    app.persist(None, &commit).await.unwrap();
+
+    // 未公开的组不出现，也不计入文章数
+    let category = app.category_json("tech", StatusCode::OK, "分类详情").await;
+    assert_eq!(category["id"], "tech");
+    assert_eq!(category["name"], "技术");
+    assert_eq!(
+        category["groups"],
+        serde_json::json!([
+            {"id": "go", "name": "go", "article_count": 1},
+            {"id": "rust", "name": "rust", "article_count": 2},
+        ])
+    );
+    let slugs = |category: &serde_json::Value| {
+        category["articles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(slugs(&category), ["c", "b", "a"]);
+    assert_eq!(
+        category["articles"][0]["category"],
+        serde_json::json!({"id": "tech", "name": "技术"})
+    );
+
+    let page = app
+        .category_json("tech?limit=2&page=2", StatusCode::OK, "分页")
+        .await;
+    assert_eq!(slugs(&page), ["a"]);
+    assert_eq!(page["groups"].as_array().unwrap().len(), 2);
+
+    // 文章列表也可以按分类筛选
+    let list = app.article_list_query("?category=tech", "按分类筛选").await;
+    assert_eq!(list.len(), 3);
+
+    app.category_json("unknown", StatusCode::NOT_FOUND, "分类不存在")
+        .await;
+    app.category_json("hidden", StatusCode::NOT_FOUND, "只有未公开的组")
+        .await;
+}
+
+#[tokio::test]
+async fn test_group_nav() {
+    let app = Fixture::with_state(|state| state.with_admin_token(Some("s3cret")));
+    let commit = app.commit(
+        &[
+            ("a/.group.yaml", "name: Beta\npublic: true\nnav_order: 1\n"),
+            ("b/.group.yaml", "name: Alpha\npublic: true\nnav_order: 1\n"),
+            ("c/.group.yaml", "name: Aardvark\npublic: true\n"),
+            ("d/.group.yaml", "name: Zed\npublic: true\nnav_order: -5\n"),
+            (
+                "menu/.group.yaml",
+                "name: Menu\npublic: true\nnav_order: 0\nhidden: true\n",
+            ),
+            ("menu/about.md", &post("t", "")),
+        ],
+        &[],
+    );
+    app.persist(None, &commit).await.unwrap();
+    let ids = |groups: &[serde_json::Value]| {
+        groups
+            .iter()
+            .map(|g| g["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // nav_order 相同时按组名排序，未设置的排在最后；隐藏的组不出现
+    let groups = app.group_list("组列表").await;
+    assert_eq!(ids(&groups), ["d", "b", "a", "c"]);
+    assert_eq!(groups[0]["nav_order"], -5);
+    assert_eq!(groups[3]["nav_order"], serde_json::Value::Null);
+
+    // 隐藏组中的文章仍然公开，可以按组筛选，组详情仍可访问
+    app.article_json("about", "隐藏组中的文章").await;
+    let list = app
+        .article_list_query("?group=menu", "按隐藏的组筛选")
+        .await;
+    assert_eq!(list.len(), 1);
+    assert_eq!(app.article_list("文章列表").await.len(), 1);
+    let group = app.group_json("menu", StatusCode::OK, "隐藏组的详情").await;
+    assert_eq!(group["name"], "Menu");
+
+    // include_hidden 需要管理 token
+    let include_hidden = |token| app.get_with_token("/api/groups?include_hidden=true", token);
+    for token in [None, Some("wrong")] {
+        assert_eq!(include_hidden(token).await.0, StatusCode::UNAUTHORIZED);
+    }
+    let (status, _, body) = include_hidden(Some("s3cret")).await;
+    assert_eq!(status, StatusCode::OK);
+    let groups: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(ids(&groups), ["d", "menu", "b", "a", "c"]);
+    assert_eq!(groups[1]["hidden"], true);
+}
+
+#[tokio::test]
+async fn test_group_retain() {
+    retain_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_group_retain_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    retain_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+#[tokio::test]
+async fn test_group_quota() {
+    quota_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_group_quota_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    quota_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+/// 组的大小统计随增量同步累计、随删除减少，超出配额时警告，严格模式下拒绝同步
+async fn quota_scenarios(backend: Backend) {
+    async fn push(app: &TestApp, refname: &str, before: &str, after: &str) -> (StatusCode, String) {
+        let resp = app.push(refname, before, after).await;
+        let status = resp.status();
+        let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(data.to_vec()).unwrap())
+    }
+    /// 组详情中的原文字节数和文章数
+    async fn size(app: &TestApp) -> (usize, u64) {
+        let group = app.group_json("notes", StatusCode::OK, "组详情").await;
+        let size = &group["size"];
+        assert_eq!(size["asset_bytes"], 0);
+        (
+            size["content_bytes"].as_u64().unwrap() as usize,
+            size["article_count"].as_u64().unwrap(),
+        )
+    }
+    async fn over_quota(app: &TestApp) -> serde_json::Value {
+        let (status, json) = app.get_json("/api/status").await;
+        assert_eq!(status, StatusCode::OK);
+        json["over_quota"].clone()
+    }
+
+    let post = |title: &str, words: usize| post_with_body(title, "", &"word ".repeat(words));
+    let (a, b, c) = (post("a", 10), post("b", 20), post("c", 30));
+    let quota = a.len() + b.len();
+    let group = format!("public: true\nquota_bytes: {quota}\n");
+
+    let app = Fixture::with_backend(backend.clone(), |state| state);
+    let state = state::AppState::new(
+        backend.clone(),
+        GithubAPiRenderer::default(),
+        app.repo.path(),
+    )
+    .with_strict_sync(true);
+    let strict = TestApp::from_state(backend, state);
+    let zero = "0".repeat(40);
+
+    let first = app.commit(&[("notes/.group.yaml", &group), ("notes/a.md", &a)], &[]);
+    let (status, text) = push(&app, "refs/heads/main", &zero, &first).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    assert_eq!(size(&app).await, (a.len(), 1));
+
+    // 增量同步累计，恰好等于配额时不警告
+    let second = app.commit(&[("notes/b.md", &b)], &[]);
+    let (status, text) = push(&app, "refs/heads/main", &first, &second).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    assert!(!text.contains("WARNING"), "{text}");
+    assert_eq!(size(&app).await, (quota, 2));
+    assert_eq!(over_quota(&app).await, serde_json::json!([]));
+
+    // 超出配额时警告，仍然写入
+    let third = app.commit(&[("notes/c.md", &c)], &[]);
+    let (status, text) = push(&app, "refs/heads/main", &second, &third).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let used = quota + c.len();
+    let warning = format!("WARNING group notes is over its size quota: {used} of {quota} bytes");
+    assert!(text.lines().any(|l| l == warning), "{text}");
+    assert_eq!(size(&app).await, (used, 3));
+    assert_eq!(
+        over_quota(&app).await,
+        serde_json::json!([{ "group": "notes", "used_bytes": used, "quota_bytes": quota }])
+    );
+    app.article("c", StatusCode::OK, "超出配额仍写入").await;
+
+    // 删除文章后减少
+    let fourth = app.commit(&[], &["notes/c.md"]);
+    let (status, text) = push(&app, "refs/heads/main", &third, &fourth).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    assert!(!text.contains("WARNING"), "{text}");
+    assert_eq!(size(&app).await, (quota, 2));
+    assert_eq!(over_quota(&app).await, serde_json::json!([]));
+
+    // 严格模式下超出配额时整体回滚
+    let fifth = app.commit(&[("notes/c.md", &c)], &[]);
+    let (status, text) = push(&strict, "refs/heads/main", &fourth, &fifth).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{text}");
+    let item = serde_json::from_str::<serde_json::Value>(&text).unwrap();
+    assert_eq!(item["group"], "notes");
+    assert_eq!(item["used_bytes"], used);
+    strict
+        .article("c", StatusCode::NOT_FOUND, "严格模式下未写入")
+        .await;
+    assert_eq!(size(&app).await, (quota, 2));
+
+    // 重建时从头计算
+    let (status, text) = push(&app, "refs/tags/cmd/rebuild", &zero, &fourth).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    assert_eq!(size(&app).await, (quota, 2));
+}
+
+/// 组的保留规则：超出的文章下线但不删除，放宽规则或删除文章后重新上线
+async fn retain_scenarios(backend: Backend) {
+    let fixture = Fixture::with_backend(backend, |state| state.with_admin_token(Some("s3cret")));
+    let (app, repo) = (&fixture.app, &fixture.repo);
+    let post =
+        |date: &str, extra: &str| post(date, &format!("datetime: {date}\ntags: [{date}]\n{extra}"));
+    let recent = Utc::now().format("%Y-%m-%d").to_string();
+    let persist = |before: Option<String>, after: String| async move {
+        repo.persist(app, before.as_deref(), &after).await.unwrap()
+    };
+    // 列表按更新时间排序，这里的文章同时提交，按 slug 排序后比较
+    let listed = |group: &'static str| async move {
+        let mut slugs = app
+            .article_list_query(&format!("?group={group}"), "组内文章")
+            .await
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        slugs.sort();
+        slugs
+    };
+    let pruned = || async {
+        let (status, json) = app
+            .send_json(
+                Method::GET,
+                "/api/admin/pruned-articles",
+                None,
+                Some("s3cret"),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        json.as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let counts = |report: &SyncReport| {
+        report
+            .retention
+            .iter()
+            .map(|i| (i.group.clone(), i.pruned, i.restored))
+            .collect::<Vec<_>>()
+    };
+
+    // 作者设置为不公开的文章不占用数量，也不列为保留规则下线
+    let first = repo.commit(
+        &[
+            (
+                "journal/.group.yaml",
+                "name: Journal\npublic: true\nretain:\n  max_articles: 2\n",
+            ),
+            ("journal/2024-01-01.md", &post("2024-01-01", "")),
+            ("journal/2024-01-02.md", &post("2024-01-02", "")),
+            ("journal/2024-01-03.md", &post("2024-01-03", "")),
+            ("journal/2024-01-04.md", &post("2024-01-04", "")),
+            (
+                "journal/2024-01-05.md",
+                &post("2024-01-05", "public: false\n"),
+            ),
+            (
+                "news/.group.yaml",
+                "name: News\npublic: true\nretain:\n  max_age_days: 30\n",
+            ),
+            ("news/2000-01-01.md", &post("2000-01-01", "")),
+            ("news/recent.md", &post(&recent, "")),
+        ],
+        &[],
+    );
+    let report = persist(None, first.clone()).await;
+    let mut groups = counts(&report);
+    groups.sort();
+    assert_eq!(
+        groups,
+        [("journal".to_string(), 2, 0), ("news".to_string(), 1, 0)]
+    );
+    assert!(
+        report
+            .as_summary()
+            .contains("retention journal: 2 pruned, 0 restored")
+    );
+    assert_eq!(listed("journal").await, ["2024-01-03", "2024-01-04"]);
+    assert_eq!(listed("news").await, ["recent"]);
+    assert_eq!(pruned().await, ["2024-01-02", "2024-01-01", "2000-01-01"]);
+    app.article("2024-01-01", StatusCode::NOT_FOUND, "下线的文章")
+        .await;
+    let tags = app.tags_list("标签列表").await;
+    assert!(!tags.contains(&serde_json::json!("2024-01-01")));
+    assert!(tags.contains(&serde_json::json!("2024-01-04")));
+
+    // 删除文件后由下一篇补上，正好在边界上的文章保持公开
+    let second = repo.commit(&[], &["journal/2024-01-04.md"]);
+    let report = persist(Some(first), second.clone()).await;
+    assert_eq!(counts(&report), [("journal".to_string(), 0, 1)]);
+    assert_eq!(listed("journal").await, ["2024-01-02", "2024-01-03"]);
+    assert_eq!(pruned().await, ["2024-01-01", "2000-01-01"]);
+
+    // 放宽规则后重新上线最新的文章，新增文章挤出最旧的
+    let third = repo.commit(
+        &[(
+            "journal/.group.yaml",
+            "name: Journal\npublic: true\nretain:\n  max_articles: 3\n",
+        )],
+        &[],
+    );
+    let report = persist(Some(second), third.clone()).await;
+    assert_eq!(counts(&report), [("journal".to_string(), 0, 1)]);
+    assert_eq!(
+        listed("journal").await,
+        ["2024-01-01", "2024-01-02", "2024-01-03"]
+    );
+    let tags = app.tags_list("标签列表").await;
+    assert!(tags.contains(&serde_json::json!("2024-01-01")));
+
+    let fourth = repo.commit(&[("journal/2024-01-06.md", &post("2024-01-06", ""))], &[]);
+    let report = persist(Some(third), fourth.clone()).await;
+    assert_eq!(counts(&report), [("journal".to_string(), 1, 0)]);
+    assert_eq!(
+        listed("journal").await,
+        ["2024-01-02", "2024-01-03", "2024-01-06"]
+    );
+
+    // 没有涉及的组不重新计算，重建得到相同的结果
+    let fifth = repo.commit(&[("news/other.md", &post(&recent, ""))], &[]);
+    let report = persist(Some(fourth), fifth.clone()).await;
+    assert!(report.retention.is_empty());
+    persist(None, fifth.clone()).await;
+    assert_eq!(
+        listed("journal").await,
+        ["2024-01-02", "2024-01-03", "2024-01-06"]
+    );
+    assert_eq!(pruned().await, ["2024-01-01", "2000-01-01"]);
+
+    // 去掉规则后全部重新上线
+    let sixth = repo.commit(
+        &[("journal/.group.yaml", "name: Journal\npublic: true\n")],
+        &[],
+    );
+    let report = persist(Some(fifth), sixth).await;
+    assert_eq!(counts(&report), [("journal".to_string(), 0, 1)]);
+    assert_eq!(pruned().await, ["2000-01-01"]);
+}
+
+#[tokio::test]
+async fn test_group_license() {
+    license_scenarios(MemoryStorage::new().into()).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_group_license_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    license_scenarios(TestApp::sqlite(dir.path()).await.backend).await;
+}
+
+/// 组的许可协议：字符串和对象两种写法，未声明时使用部署配置的默认协议
+async fn license_scenarios(backend: Backend) {
+    let app = Fixture::with_backend(backend.clone(), |state| {
+        state.with_default_license(Some("CC0-1.0".parse().unwrap()))
+    });
+    let plain = TestApp::with_backend(backend, GithubAPiRenderer::default(), REPO_PATH);
+    let first = app.commit(
+        &[
+            ("cc/.group.yaml", "public: true\nlicense: CC-BY-4.0\n"),
+            ("cc/a.md", &post("a", "")),
+            (
+                "arr/.group.toml",
+                "public = true\n[license]\nid = \"LicenseRef-ARR\"\nname = \"All rights reserved\"\nurl = \"https://example.com/terms\"\n",
+            ),
+            ("arr/b.md", &post("b", "")),
+            ("plain/.group.yaml", "public: true\n"),
+            ("plain/c.md", &post("c", "")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+
+    // 字符串写法以 id 作为显示名
+    let cc = serde_json::json!({"id": "CC-BY-4.0", "name": "CC-BY-4.0", "url": null});
+    assert_eq!(app.article_json("a", "字符串写法").await["license"], cc);
+    assert_eq!(
+        plain.group_json("cc", StatusCode::OK, "组详情").await["license"],
+        cc
+    );
+
+    let arr = serde_json::json!({
+        "id": "LicenseRef-ARR",
+        "name": "All rights reserved",
+        "url": "https://example.com/terms",
+    });
+    assert_eq!(plain.article_json("b", "对象写法").await["license"], arr);
+    assert_eq!(
+        app.group_json("arr", StatusCode::OK, "组详情").await["license"],
+        arr
+    );
+
+    // 未声明时使用默认协议，都未设置时为 null
+    let cc0 = serde_json::json!({"id": "CC0-1.0", "name": "CC0-1.0", "url": null});
+    assert_eq!(app.article_json("c", "默认协议").await["license"], cc0);
+    assert_eq!(
+        app.group_json("plain", StatusCode::OK, "组详情").await["license"],
+        cc0
+    );
+    assert!(plain.article_json("c", "没有协议").await["license"].is_null());
+    assert!(plain.group_json("plain", StatusCode::OK, "组详情").await["license"].is_null());
+
+    let (_, _, xml) = app.get("/api/groups/arr/feed.xml").await;
+    assert!(xml.contains("<copyright>All rights reserved (https://example.com/terms)</copyright>"));
+    assert!(
+        xml.contains("<atom:rights>All rights reserved (https://example.com/terms)</atom:rights>")
+    );
+
+    // 删除声明后恢复使用默认协议
+    let second = app.commit(&[("cc/.group.yaml", "public: true\n")], &[]);
+    app.persist(Some(&first), &second).await.unwrap();
+    assert_eq!(app.article_json("a", "删除声明").await["license"], cc0);
+}
@@ -0,0 +1,871 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    extract::{ConnectInfo, Request},
+    http::{
+        HeaderMap, Method, Response, StatusCode,
+        header::{
+            ACCEPT_ENCODING, AUTHORIZATION, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE, ETAG,
+            IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER, VARY,
+        },
+    },
+    response::IntoResponse,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use chrono_tz::Tz;
+#[cfg(feature = "sqlite")]
+use gitnote::storage::{
+    SQLITE_MIGRATIONS, new_sqlite_pool, run_sqlite_migrations, sqlite_missing_schema,
+};
+use gitnote::{
+    api,
+    content::{Article, ArticleRef, FrontMatter, Group, Renderer},
+    error::{Error, Result},
+    git_client::{AsSummary, BLOB_PREFETCH_CHUNK, ChangeKind, GitClient, blob_oid},
+    git_sync::{
+        BackupOptions, ContentLimits, PersistMode, Persistable, SyncCoordinator, SyncReport,
+        list_backups, prune_groups, record_fingerprint, repo_fingerprint, rerender_stale,
+        retry_failures,
+    },
+    render::{FallbackRenderer, GithubAPiRenderer, PendingRenderer},
+    state,
+    storage::{
+        Backend, BackendStore, DBPool, DateRange, ListColumns, ListSort, MIGRATIONS, MemoryStorage,
+        Querier, QueryLimits, SqlxStore, Store, TimedQuerier, import_dump, init_db_from_env,
+        is_short_id, migrate, missing_schema, run_migrations,
+    },
+};
+use tower::util::ServiceExt;
+
+mod admin;
+mod articles;
+mod feeds;
+mod groups;
+mod http;
+mod storage;
+mod sync;
+
+/// 测试使用的裸仓库路径
+const REPO_PATH: &str = "tests/data/localtest.git";
+
+struct TestApp {
+    router: Router,
+    backend: Backend,
+    coordinator: SyncCoordinator,
+}
+
+/// 构造 `notes` 组中的文章
+fn test_article(slug: &str, datetime: DateTime<FixedOffset>) -> Article {
+    Article {
+        group: "notes".into(),
+        slug: slug.to_string(),
+        frontmatter: FrontMatter {
+            title: slug.to_string(),
+            summary: String::new(),
+            datetime,
+            tags: vec![],
+            aliases: vec![],
+            canonical: None,
+            public: None,
+            raw_html: false,
+            cover: None,
+        },
+        rendered_content: String::new(),
+        namespaced_content: None,
+        rendered_by: None,
+        updated_at: datetime,
+        source_bytes: 0,
+    }
+}
+
+/// 在临时仓库的工作区写入或删除文件后提交，返回 commit id
+fn commit_files(repo: &git2::Repository, writes: &[(&str, &str)], removes: &[&str]) -> String {
+    commit_files_at(repo, writes, removes, Utc::now().timestamp())
+}
+
+/// 与 [`commit_files`] 相同，提交时间为 Unix 秒时间戳 `time`
+fn commit_files_at(
+    repo: &git2::Repository,
+    writes: &[(&str, &str)],
+    removes: &[&str],
+    time: i64,
+) -> String {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+
+    for (path, content) in writes {
+        let full = workdir.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::fs::write(full, content).unwrap();
+        index.add_path(std::path::Path::new(path)).unwrap();
+    }
+    for path in removes {
+        std::fs::remove_file(workdir.join(path)).unwrap();
+        index.remove_path(std::path::Path::new(path)).unwrap();
+    }
+
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig =
+        git2::Signature::new("tester", "tester@example.com", &git2::Time::new(time, 0)).unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents = parent.iter().collect::<Vec<_>>();
+
+    repo.commit(Some("HEAD"), &sig, &sig, "test", &tree, &parents)
+        .unwrap()
+        .to_string()
+}
+
+/// 在临时仓库的工作区创建符号链接后提交，返回 commit id
+#[cfg(unix)]
+fn commit_symlinks(repo: &git2::Repository, links: &[(&str, &str)]) -> String {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+
+    for (path, target) in links {
+        let full = workdir.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(target, full).unwrap();
+        index.add_path(std::path::Path::new(path)).unwrap();
+    }
+
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("tester", "tester@example.com").unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+
+    repo.commit(Some("HEAD"), &sig, &sig, "test", &tree, &[&parent])
+        .unwrap()
+        .to_string()
+}
+
+/// 将 `before` 到 `after` 的变更增量写入，`before` 为 `None` 时按 `after` 的快照重建
+async fn persist_commits(
+    app: &TestApp,
+    repo: &GitClient,
+    before: Option<&str>,
+    after: &str,
+) -> Result<SyncReport> {
+    let renderer = CountingRenderer::default();
+    let limits = ContentLimits::default();
+    persist_commits_with(app, repo, &renderer, &limits, before, after).await
+}
+
+/// 与 [`persist_commits`] 相同，使用指定的渲染器和内容限制
+async fn persist_commits_with(
+    app: &TestApp,
+    repo: &GitClient,
+    renderer: &impl Renderer,
+    limits: &ContentLimits,
+    before: Option<&str>,
+    after: &str,
+) -> Result<SyncReport> {
+    let (entries, mode) = match before {
+        Some(before) => (
+            repo.diff_commits(before, after).await?,
+            PersistMode::Incremental,
+        ),
+        None => (repo.snapshot(after).await?, PersistMode::ResetAll),
+    };
+    entries
+        .persist(app.storage(), renderer, repo, after, mode, limits)
+        .await
+}
+
+/// 使用默认 Front Matter 的文章，正文为 `body`
+///
+/// `extra` 中的字段追加到 Front Matter 末尾，与 `summary`、`datetime`、`tags` 同名时替换默认值。
+fn post(title: &str, extra: &str) -> String {
+    post_with_body(title, extra, "body")
+}
+
+/// 与 [`post`] 相同，正文为 `body`
+fn post_with_body(title: &str, extra: &str, body: &str) -> String {
+    let mut front = format!("title: {title}\n");
+    for (key, value) in [("summary", "s"), ("datetime", "2024-01-01"), ("tags", "[]")] {
+        if !extra
+            .lines()
+            .any(|line| line.starts_with(&format!("{key}:")))
+        {
+            front.push_str(&format!("{key}: {value}\n"));
+        }
+    }
+    format!("---\n{front}{extra}---\n\n{body}\n")
+}
+
+/// 临时目录中的 git 仓库
+struct TempRepo {
+    git: git2::Repository,
+    client: GitClient,
+    dir: tempfile::TempDir,
+}
+
+impl TempRepo {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let git = git2::Repository::init(dir.path()).unwrap();
+        let client = GitClient::open(dir.path().join(".git")).expect("打开仓库失败");
+        Self { git, client, dir }
+    }
+
+    /// 仓库的 `.git` 目录
+    fn path(&self) -> std::path::PathBuf {
+        self.dir.path().join(".git")
+    }
+
+    /// 见 [`commit_files`]
+    fn commit(&self, writes: &[(&str, &str)], removes: &[&str]) -> String {
+        commit_files(&self.git, writes, removes)
+    }
+
+    /// 见 [`persist_commits`]
+    async fn persist(
+        &self,
+        app: &TestApp,
+        before: Option<&str>,
+        after: &str,
+    ) -> Result<SyncReport> {
+        persist_commits(app, &self.client, before, after).await
+    }
+
+    /// 见 [`persist_commits_with`]
+    async fn persist_with(
+        &self,
+        app: &TestApp,
+        renderer: &impl Renderer,
+        limits: &ContentLimits,
+        before: Option<&str>,
+        after: &str,
+    ) -> Result<SyncReport> {
+        persist_commits_with(app, &self.client, renderer, limits, before, after).await
+    }
+
+    /// 与 [`TempRepo::persist_with`] 相同，按 `mode` 写入
+    ///
+    /// 多个场景共用同一个存储时使用 [`PersistMode::Incremental`] 写入快照，避免清空其他场景的数据。
+    async fn persist_mode(
+        &self,
+        app: &TestApp,
+        renderer: &impl Renderer,
+        limits: &ContentLimits,
+        mode: PersistMode,
+        before: Option<&str>,
+        after: &str,
+    ) -> Result<SyncReport> {
+        let entries = match before {
+            Some(before) => self.client.diff_commits(before, after).await?,
+            None => self.client.snapshot(after).await?,
+        };
+        entries
+            .persist(app.storage(), renderer, &self.client, after, mode, limits)
+            .await
+    }
+}
+
+/// 临时 git 仓库，以及以它为内容源、默认使用内存存储的测试应用
+struct Fixture {
+    app: TestApp,
+    repo: TempRepo,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self::with_state(|state| state)
+    }
+
+    /// 用 `configure` 调整应用配置，例如设置 admin token
+    fn with_state(configure: impl FnOnce(state::AppState) -> state::AppState) -> Self {
+        Self::with_backend(MemoryStorage::new().into(), configure)
+    }
+
+    fn with_backend(
+        backend: Backend,
+        configure: impl FnOnce(state::AppState) -> state::AppState,
+    ) -> Self {
+        let repo = TempRepo::new();
+        let state = configure(state::AppState::new(
+            backend.clone(),
+            GithubAPiRenderer::default(),
+            repo.path(),
+        ));
+
+        Self {
+            app: TestApp::from_state(backend, state),
+            repo,
+        }
+    }
+
+    fn commit(&self, writes: &[(&str, &str)], removes: &[&str]) -> String {
+        self.repo.commit(writes, removes)
+    }
+
+    async fn persist(&self, before: Option<&str>, after: &str) -> Result<SyncReport> {
+        self.repo.persist(&self.app, before, after).await
+    }
+
+    async fn persist_with(
+        &self,
+        renderer: &impl Renderer,
+        limits: &ContentLimits,
+        before: Option<&str>,
+        after: &str,
+    ) -> Result<SyncReport> {
+        self.repo
+            .persist_with(&self.app, renderer, limits, before, after)
+            .await
+    }
+}
+
+impl std::ops::Deref for Fixture {
+    type Target = TestApp;
+
+    fn deref(&self) -> &TestApp {
+        &self.app
+    }
+}
+
+/// 提交 `files` 并按快照写入，返回测试环境和提交 id
+async fn synced_app(files: &[(&str, &str)]) -> (Fixture, String) {
+    let fixture = Fixture::new();
+    let commit = fixture.commit(files, &[]);
+    fixture.persist(None, &commit).await.expect("同步失败");
+    (fixture, commit)
+}
+
+/// 每次渲染都会等待一段时间，用于模拟耗时的重建
+struct SlowRenderer;
+
+impl Renderer for SlowRenderer {
+    fn render<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<String>> {
+        let content = content.as_ref().to_string();
+        async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(format!("<slow>{}</slow>", content))
+        }
+    }
+
+    fn identity(&self) -> String {
+        "slow@1".to_string()
+    }
+}
+
+/// 记录渲染次数并声明版本，用于检查渲染结果的复用
+#[derive(Default)]
+struct CountingRenderer(AtomicUsize);
+
+impl Renderer for CountingRenderer {
+    fn render<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<String>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let content = content.as_ref().to_string();
+        async move { Ok(format!("<counted>{}</counted>", content)) }
+    }
+
+    fn identity(&self) -> String {
+        "counting@1".to_string()
+    }
+
+    fn version(&self) -> Option<String> {
+        Some("counting-v1".to_string())
+    }
+}
+
+/// 按正文中的 `sleep:毫秒` 延迟渲染并声明版本，用于检查渲染耗时的记录
+struct ScriptedRenderer;
+
+impl Renderer for ScriptedRenderer {
+    fn render<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<String>> {
+        let content = content.as_ref().to_string();
+        let ms = content
+            .split_once("sleep:")
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .and_then(|ms| ms.parse().ok())
+            .unwrap_or(0);
+        async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(format!("<scripted>{}</scripted>", content))
+        }
+    }
+
+    fn identity(&self) -> String {
+        "scripted@1".to_string()
+    }
+
+    fn version(&self) -> Option<String> {
+        Some("scripted-v1".to_string())
+    }
+}
+
+/// 在渲染结果中标注渲染器标识并记录渲染次数，用于检查渲染器切换
+struct TaggedRenderer(&'static str, AtomicUsize);
+
+impl TaggedRenderer {
+    fn new(identity: &'static str) -> Self {
+        Self(identity, AtomicUsize::new(0))
+    }
+}
+
+impl Renderer for TaggedRenderer {
+    fn render<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<String>> {
+        self.1.fetch_add(1, Ordering::SeqCst);
+        let html = format!(
+            "<div data-renderer=\"{}\">{}</div>",
+            self.0,
+            content.as_ref()
+        );
+        async move { Ok(html) }
+    }
+
+    fn identity(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn version(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+/// 按开关模拟渲染服务不可用
+struct FlakyRenderer(Arc<AtomicBool>);
+
+impl Renderer for FlakyRenderer {
+    fn render<T: AsRef<str>>(
+        &self,
+        content: T,
+    ) -> impl std::future::Future<Output = Result<String>> {
+        let result = if self.0.load(Ordering::SeqCst) {
+            Err(Error::Custom("service unavailable"))
+        } else {
+            Ok(format!("<flaky>{}</flaky>", content.as_ref()))
+        };
+        async move { result }
+    }
+
+    fn identity(&self) -> String {
+        "flaky@1".to_string()
+    }
+
+    fn version(&self) -> Option<String> {
+        Some("flaky-v1".to_string())
+    }
+}
+
+impl TestApp {
+    /// 使用 `DATABASE_URL` 指向的数据库
+    async fn new() -> Self {
+        let db = init_db_from_env().await;
+
+        db.run_migrations().await.expect("初始化sql失败");
+
+        Self::with_backend(db, GithubAPiRenderer::default(), REPO_PATH)
+    }
+
+    /// 使用 `dir` 下新建的 SQLite 数据库
+    #[cfg(feature = "sqlite")]
+    async fn sqlite(dir: &std::path::Path) -> Self {
+        let url = format!("sqlite://{}", dir.join("gitnote.db").display());
+        let db = new_sqlite_pool(&url).await.expect("打开数据库失败");
+
+        run_sqlite_migrations(&db).await.expect("初始化sql失败");
+
+        let token = std::env::var("GITHUB_MARKDOWN_RENDER_KEY").unwrap_or_default();
+        Self::with_backend(db.into(), GithubAPiRenderer::new(token), REPO_PATH)
+    }
+
+    /// 使用内存存储，不依赖数据库
+    ///
+    /// 未设置 `GITHUB_MARKDOWN_RENDER_KEY` 时同步接口不可用，直接调用 `persist` 不受影响。
+    fn memory() -> Self {
+        let token = std::env::var("GITHUB_MARKDOWN_RENDER_KEY").unwrap_or_default();
+        Self::with_backend(
+            MemoryStorage::new().into(),
+            GithubAPiRenderer::new(token),
+            REPO_PATH,
+        )
+    }
+
+    /// 管理接口的 token 为 `s3cret`
+    fn with_backend(
+        backend: Backend,
+        renderer: GithubAPiRenderer,
+        repo_path: impl AsRef<std::path::Path>,
+    ) -> Self {
+        let state = state::AppState::new(backend.clone(), renderer, repo_path)
+            .with_admin_token(Some("s3cret"));
+        Self::from_state(backend, state)
+    }
+
+    /// 使用调整过配置的应用状态，`backend` 与 `state` 使用的存储相同
+    fn from_state(backend: Backend, state: state::AppState) -> Self {
+        Self {
+            router: api::setup_route(state.clone()),
+            coordinator: state.coordinator().clone(),
+            backend,
+        }
+    }
+
+    fn storage(&self) -> BackendStore {
+        self.backend.store()
+    }
+
+    /// 使用数据库时返回连接池
+    fn pool(&self) -> Option<&DBPool> {
+        match &self.backend {
+            Backend::Postgres(db) => Some(db),
+            _ => None,
+        }
+    }
+
+    pub async fn request(&self, req: Request<Body>) -> Response<Body> {
+        self.router
+            .clone()
+            .oneshot(req)
+            .await
+            .expect("oneshot fail")
+    }
+
+    /// 发送 GET 请求，返回状态码、响应头和响应体
+    async fn get(&self, uri: &str) -> (StatusCode, HeaderMap, String) {
+        self.get_with_token(uri, None).await
+    }
+
+    /// 与 [`TestApp::get`] 相同，`token` 不为 `None` 时带上 bearer token
+    async fn get_with_token(
+        &self,
+        uri: &str,
+        token: Option<&str>,
+    ) -> (StatusCode, HeaderMap, String) {
+        self.send(Method::GET, uri, None, token).await
+    }
+
+    /// 发送 GET 请求，响应体按 JSON 解析，不是 JSON 时为 `null`
+    async fn get_json(&self, uri: &str) -> (StatusCode, serde_json::Value) {
+        self.send_json(Method::GET, uri, None, None).await
+    }
+
+    /// 发送请求，`json` 不为 `None` 时作为请求体，`token` 不为 `None` 时带上 bearer token
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        json: Option<&serde_json::Value>,
+        token: Option<&str>,
+    ) -> (StatusCode, HeaderMap, String) {
+        let mut req = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            req = req.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match json {
+            Some(json) => {
+                req = req.header(CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        let resp = self.request(req.body(body).unwrap()).await;
+        let (status, headers) = (resp.status(), resp.headers().clone());
+        let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        (status, headers, String::from_utf8(data.to_vec()).unwrap())
+    }
+
+    /// 与 [`TestApp::send`] 相同，响应体按 JSON 解析，不是 JSON 时为 `null`
+    async fn send_json(
+        &self,
+        method: Method,
+        uri: &str,
+        json: Option<&serde_json::Value>,
+        token: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let (status, _, body) = self.send(method, uri, json, token).await;
+        (status, serde_json::from_str(&body).unwrap_or_default())
+    }
+}
+
+impl TestApp {
+    async fn push(&self, refname: &str, before: &str, after: &str) -> Response<Body> {
+        use serde_json::json;
+
+        let req = Request::post("/api/repo/update")
+            .header("Content-Type", "application/json")
+            .body(Body::new(
+                json!({
+                    "refname": refname,
+                    "before": before,
+                    "after": after
+                })
+                .to_string(),
+            ))
+            .expect("请求失败");
+
+        self.request(req).await
+    }
+
+    async fn retry_failures(&self) -> Response<Body> {
+        let req = Request::post("/api/repo/retry-failures")
+            .header("Accept", "application/json")
+            .body(Body::empty())
+            .expect("请求失败");
+
+        self.request(req).await
+    }
+
+    async fn sync_status(&self) -> serde_json::Value {
+        let req = Request::get("/api/admin/sync-status")
+            .header(AUTHORIZATION, "Bearer s3cret")
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status());
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        serde_json::from_slice(&data).expect("反序列化失败")
+    }
+
+    async fn git_repo_sync(&self, oid: &str, lines: usize, msg: &str) {
+        use serde_json::json;
+
+        let req = Request::post("/api/repo/update")
+            .header("Content-Type", "application/json")
+            .header("X-Request-Id", "sync-test")
+            .body(Body::new(
+                json!({
+                    "refname": "refs/tags/cmd/rebuild",
+                    "before": "0000000000000000000000000000000000000000",
+                    "after": oid
+                })
+                .to_string(),
+            ))
+            .expect("请求失败");
+
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        assert_eq!(resp.headers()["x-request-id"], "sync-test", "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+
+        // 最后一行为请求 id
+        let text = String::from_utf8(data.to_vec()).expect("读取数据失败");
+        let (text, request_id) = text.rsplit_once('\n').expect("缺少请求 id");
+        assert_eq!(request_id, "request-id sync-test", "{}", msg);
+        assert_eq!(text.lines().count(), lines, "{}", msg);
+    }
+
+    async fn git_repo_sync_json(&self, oid: &str, msg: &str) -> serde_json::Value {
+        use serde_json::json;
+
+        let req = Request::post("/api/repo/update")
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(Body::new(
+                json!({
+                    "refname": "refs/tags/cmd/rebuild",
+                    "before": "0000000000000000000000000000000000000000",
+                    "after": oid
+                })
+                .to_string(),
+            ))
+            .expect("请求失败");
+
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        serde_json::from_slice(&data).expect("反序列化失败")
+    }
+
+    async fn git_repo_sync_ndjson(&self, oid: &str, msg: &str) -> Vec<serde_json::Value> {
+        use serde_json::json;
+
+        let req = Request::post("/api/repo/update")
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/x-ndjson")
+            .body(Body::new(
+                json!({
+                    "refname": "refs/tags/cmd/rebuild",
+                    "before": "0000000000000000000000000000000000000000",
+                    "after": oid
+                })
+                .to_string(),
+            ))
+            .expect("请求失败");
+
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        assert_eq!(
+            resp.headers()["content-type"],
+            "application/x-ndjson",
+            "{}",
+            msg
+        );
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        String::from_utf8(data.to_vec())
+            .expect("读取数据失败")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("反序列化失败"))
+            .collect()
+    }
+
+    /// 使用数据库时，检查影子 schema 中没有剩余的表
+    async fn assert_no_shadow_tables(&self, msg: &str) {
+        let Some(db) = self.pool() else {
+            return;
+        };
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM information_schema.tables WHERE table_schema = 'gitnote_shadow'",
+        )
+        .fetch_one(db)
+        .await
+        .expect("查询影子表失败");
+        assert_eq!(count, 0, "{}", msg);
+    }
+
+    async fn article_list(&self, msg: &str) -> Vec<serde_json::Value> {
+        self.article_list_query("", msg).await
+    }
+
+    async fn article_list_query(&self, query: &str, msg: &str) -> Vec<serde_json::Value> {
+        let req = Request::get(format!("/api/articles{}", query))
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        let json: Vec<serde_json::Value> = serde_json::from_slice(&data).expect("反序列化失败");
+        json
+    }
+
+    async fn article(&self, slug: &str, code: StatusCode, msg: &str) {
+        let req = Request::get(format!("/api/articles/{}", slug))
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(resp.status(), code, "{}", msg);
+    }
+
+    async fn article_json(&self, slug: &str, msg: &str) -> serde_json::Value {
+        let req = Request::get(format!("/api/articles/{}", slug))
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        serde_json::from_slice(&data).expect("反序列化失败")
+    }
+
+    /// 请求 `GET /api/slugs`，返回状态码、响应体和 `ETag`，304 时响应体为 `null`
+    async fn slugs(
+        &self,
+        query: &str,
+        if_none_match: Option<&str>,
+    ) -> (StatusCode, serde_json::Value, String) {
+        let mut req = Request::get(format!("/api/slugs{query}"));
+        if let Some(etag) = if_none_match {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        let resp = self.request(req.body(Body::empty()).unwrap()).await;
+        let status = resp.status();
+        let etag = resp
+            .headers()
+            .get(ETAG)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&data).unwrap_or_default();
+        (status, body, etag)
+    }
+
+    async fn group_list(&self, msg: &str) -> Vec<serde_json::Value> {
+        let req = Request::get("/api/groups")
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        let json: Vec<serde_json::Value> = serde_json::from_slice(&data).expect("反序列化失败");
+        json
+    }
+
+    async fn group_json(&self, id: &str, code: StatusCode, msg: &str) -> serde_json::Value {
+        let req = Request::get(format!("/api/groups/{}", id))
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(code, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        serde_json::from_slice(&data).unwrap_or_default()
+    }
+
+    async fn category_json(&self, path: &str, code: StatusCode, msg: &str) -> serde_json::Value {
+        let req = Request::get(format!("/api/categories/{}", path))
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(code, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        serde_json::from_slice(&data).unwrap_or_default()
+    }
+
+    async fn author_list(&self, msg: &str) -> Vec<serde_json::Value> {
+        let req = Request::get("/api/authors")
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        let json: Vec<serde_json::Value> = serde_json::from_slice(&data).expect("反序列化失败");
+        json
+    }
+
+    async fn tags_list(&self, msg: &str) -> Vec<serde_json::Value> {
+        let req = Request::get("/api/tags")
+            .body(Body::empty())
+            .expect("请求失败");
+        let resp = self.request(req).await;
+        assert_eq!(StatusCode::OK, resp.status(), "{}", msg);
+        let data = to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("读取数据失败");
+        let json: Vec<serde_json::Value> = serde_json::from_slice(&data).expect("反序列化失败");
+        json
+    }
+}
@@ -0,0 +1,913 @@
+use super::*;
+
+#[tokio::test]
+#[ignore = "API测试 依赖真实数据库"]
+async fn test_api() {
+    let app = TestApp::new().await;
+    sync_scenarios(&app).await;
+    storage_scenarios(&app).await;
+}
+
+#[tokio::test]
+#[ignore = "API测试 依赖真实数据库"]
+async fn test_migrations() {
+    let Backend::Postgres(db) = init_db_from_env().await else {
+        panic!("`DATABASE_URL` 需要指向 PostgreSQL");
+    };
+
+    // 按文件名顺序执行，重复执行时跳过已执行的文件
+    run_migrations(&db).await.expect("迁移失败");
+    assert!(run_migrations(&db).await.expect("迁移失败").is_empty());
+    migrate(&db, "sql/03-ARTICLE_INDEXES.sql")
+        .await
+        .expect("迁移失败");
+
+    let applied: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM gitnote.schema_migrations ORDER BY applied_at, name")
+            .fetch_all(&db)
+            .await
+            .unwrap();
+    let expected = MIGRATIONS.iter().map(|m| m.name).collect::<Vec<_>>();
+    assert_eq!(applied, expected);
+    assert!(missing_schema(&db).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_not_migrated() {
+    let backend: Backend = MemoryStorage::new().into();
+    let state = state::AppState::new(backend.clone(), GithubAPiRenderer::default(), REPO_PATH)
+        .with_schema_ready(false);
+    let app = TestApp::from_state(backend, state);
+
+    // 同步接口返回 503
+    for resp in [
+        app.push("refs/heads/main", "0", "1").await,
+        app.retry_failures().await,
+    ] {
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "database not migrated");
+    }
+
+    // 查询接口返回空结果
+    assert!(app.article_list("应为空").await.is_empty());
+    assert!(app.tags_list("应为空").await.is_empty());
+}
+
+#[tokio::test]
+#[ignore = "依赖 GitHub Markdown 渲染接口"]
+async fn test_api_memory_sync() {
+    let app = TestApp::memory();
+    sync_scenarios(&app).await;
+    storage_scenarios(&app).await;
+}
+
+#[tokio::test]
+async fn test_api_memory() {
+    let app = TestApp::memory();
+    persist_snapshot(&app).await;
+    storage_scenarios(&app).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+#[ignore = "依赖 GitHub Markdown 渲染接口"]
+async fn test_api_sqlite_sync() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = TestApp::sqlite(dir.path()).await;
+    sync_scenarios(&app).await;
+    storage_scenarios(&app).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_api_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = TestApp::sqlite(dir.path()).await;
+    persist_snapshot(&app).await;
+    storage_scenarios(&app).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_migrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("gitnote.db").display());
+    let db = new_sqlite_pool(&url).await.unwrap();
+
+    // 重复执行时跳过已执行的文件
+    assert!(!sqlite_missing_schema(&db).await.unwrap().is_empty());
+    let expected = SQLITE_MIGRATIONS.iter().map(|m| m.name).collect::<Vec<_>>();
+    assert_eq!(run_sqlite_migrations(&db).await.unwrap(), expected);
+    assert!(run_sqlite_migrations(&db).await.unwrap().is_empty());
+    assert!(sqlite_missing_schema(&db).await.unwrap().is_empty());
+}
+
+/// 不经过同步接口，直接写入第二个提交的快照
+async fn persist_snapshot(app: &TestApp) {
+    let repo = GitClient::open(REPO_PATH).expect("打开仓库失败");
+    let oid = "4db775450dee399c328935eb03fd4fcc6c60e333";
+    repo.snapshot(oid)
+        .await
+        .expect("获取快照失败")
+        .persist(
+            app.storage(),
+            &SlowRenderer,
+            &repo,
+            oid,
+            PersistMode::ResetAll,
+            &ContentLimits::default(),
+        )
+        .await
+        .expect("同步失败");
+}
+
+#[tokio::test]
+#[ignore = "API测试 依赖真实数据库"]
+async fn test_query_timeout() {
+    let url = std::env::var("DATABASE_URL").expect("`DATABASE_URL` env not set");
+    let backend = Backend::connect_with_timeout(&url, Some(Duration::from_millis(100)))
+        .await
+        .unwrap();
+    let Backend::Postgres(db) = backend.clone() else {
+        panic!("`DATABASE_URL` 需要指向 PostgreSQL");
+    };
+
+    // 客户端不限制时由服务端的 statement_timeout 取消
+    let querier = TimedQuerier::new(
+        backend,
+        QueryLimits {
+            timeout: None,
+            slow: None,
+        },
+    );
+    let err = querier
+        .run(
+            "pg_sleep",
+            String::new,
+            sqlx::query("SELECT pg_sleep(1)").execute(&db),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::QueryTimeout), "{err:?}");
+    assert_eq!(
+        err.into_response().status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+/// 通过同步接口写入数据的场景，渲染时调用 GitHub Markdown 接口
+async fn sync_scenarios(app: &TestApp) {
+    // 同步第一个hash
+    {
+        app.git_repo_sync(
+            "1b931e64cd251b0a98d9216b96ba4c51e69c7797",
+            3,
+            "提交第一个文件",
+        )
+        .await;
+
+        let data = app
+            .article_list("第一个文件还没有组策略 未设置public 应为空")
+            .await;
+        assert_eq!(data.len(), 0);
+        assert_eq!(app.group_list("没有组策略 应为空").await.len(), 0);
+        assert_eq!(app.tags_list("应为空").await.len(), 0);
+        app.article("markdown-test", StatusCode::NOT_FOUND, "获取未公开的文章")
+            .await;
+    }
+
+    // 同步第二个hash
+    {
+        app.git_repo_sync(
+            "4db775450dee399c328935eb03fd4fcc6c60e333",
+            3,
+            "提交文件于组策略",
+        )
+        .await;
+
+        let data = app.article_list("此时应可以获取到一个文件").await;
+        assert_eq!(data.len(), 1);
+        assert_eq!(app.group_list("此时应可以获取到一个组策略").await.len(), 1);
+        assert_eq!(
+            app.article_list("应可以获取到这个唯一的文件对应的的tags")
+                .await
+                .len(),
+            1
+        );
+        app.article("markdown-test", StatusCode::OK, "获取文章")
+            .await;
+    }
+
+    // 以 JSON 格式重新同步
+    {
+        let oid = "4db775450dee399c328935eb03fd4fcc6c60e333";
+        let report = app.git_repo_sync_json(oid, "JSON 同步报告").await;
+
+        assert_eq!(report["push_kind"], "rebuild");
+        assert_eq!(report["range"]["after"], oid);
+        assert_eq!(report["added"].as_array().unwrap().len(), 1);
+        assert_eq!(report["removed"].as_array().unwrap().len(), 0);
+        assert_eq!(report["groups_changed"].as_array().unwrap().len(), 1);
+        assert!(report["duration_ms"].is_u64());
+        assert_eq!(app.article_list("重新同步后文章不变").await.len(), 1);
+    }
+
+    // 以 NDJSON 流重新同步，每个文件一行，最后一行为结果
+    {
+        let oid = "4db775450dee399c328935eb03fd4fcc6c60e333";
+        let lines = app.git_repo_sync_ndjson(oid, "NDJSON 同步").await;
+
+        let (done, entries) = lines.split_last().expect("至少包含结果行");
+        assert_eq!(entries.len(), 2, "两个文件各一行");
+        for entry in entries {
+            assert_eq!(entry["event"], "entry");
+            assert_eq!(entry["action"], "added");
+            assert_eq!(entry["outcome"], "ok");
+            assert!(entry["duration_ms"].is_u64());
+        }
+        assert_eq!(done["event"], "done");
+        assert_eq!(done["outcome"], "committed");
+        assert_eq!(done["report"]["range"]["after"], oid);
+        assert_eq!(app.article_list("流式同步后文章不变").await.len(), 1);
+    }
+}
+
+/// 直接调用 [`Persistable::persist`] 的场景，两种存储后端共用
+///
+/// 要求存储中已有第二个提交的数据。
+async fn storage_scenarios(app: &TestApp) {
+    // 重建期间继续提供旧数据
+    {
+        let repo = GitClient::open(REPO_PATH).expect("打开仓库失败");
+        let entries = repo
+            .snapshot("4db775450dee399c328935eb03fd4fcc6c60e333")
+            .await
+            .expect("获取快照失败");
+        let limits = ContentLimits::default();
+
+        let rebuild = entries.persist(
+            app.storage(),
+            &SlowRenderer,
+            &repo,
+            "4db775450dee399c328935eb03fd4fcc6c60e333",
+            PersistMode::ResetAll,
+            &limits,
+        );
+        let probe = async {
+            for _ in 0..5 {
+                assert_eq!(app.article_list("重建期间仍返回旧数据").await.len(), 1);
+                app.article("markdown-test", StatusCode::OK, "重建期间仍可获取文章")
+                    .await;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+
+        let (result, ()) = tokio::join!(rebuild, probe);
+        result.expect("重建失败");
+        assert_eq!(app.article_list("重建完成").await.len(), 1);
+        app.assert_no_shadow_tables("重建完成后影子表已移入线上")
+            .await;
+    }
+
+    // API 索引中的数量与查询接口一致
+    {
+        let (status, index) = app.get_json("/api").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(index["articles"], app.article_list("API 索引").await.len());
+        assert_eq!(index["groups"], app.group_list("API 索引").await.len());
+        assert_eq!(index["tags"], app.tags_list("API 索引").await.len());
+    }
+
+    // 摘要格式
+    {
+        let html = app.article_list_query("?summary=html", "HTML 摘要").await;
+        let html = html[0]["summary"].as_str().unwrap();
+        assert!(html.starts_with("<slow>"), "{}", html);
+
+        let plain = app.article_list_query("?summary=plain", "纯文本摘要").await;
+        let plain = plain[0]["summary"].as_str().unwrap();
+        assert!(!plain.is_empty() && !plain.contains('<'), "{}", plain);
+
+        let none = app.article_list_query("?summary=none", "不返回摘要").await;
+        assert!(none[0].get("summary").is_none(), "{}", none[0]);
+    }
+
+    // Last-Modified、If-Modified-Since 和 HEAD 请求
+    for uri in ["/api/articles/markdown-test", "/api/articles"] {
+        let send = |method: &str, since: Option<&str>| {
+            let mut req = Request::builder().method(method).uri(uri);
+            if let Some(since) = since {
+                req = req.header(IF_MODIFIED_SINCE, since);
+            }
+            app.request(req.body(Body::empty()).unwrap())
+        };
+
+        let get = send("GET", None).await;
+        assert_eq!(get.status(), StatusCode::OK, "{uri}");
+        let modified = get.headers()[LAST_MODIFIED].to_str().unwrap().to_string();
+        assert!(modified.ends_with(" GMT"), "{uri}: {modified}");
+
+        let head = send("HEAD", None).await;
+        assert_eq!(head.status(), StatusCode::OK, "{uri}");
+        for name in [LAST_MODIFIED, CONTENT_TYPE] {
+            assert_eq!(
+                head.headers().get(&name),
+                get.headers().get(&name),
+                "{uri}: {name}"
+            );
+        }
+        let body = to_bytes(head.into_body(), usize::MAX).await.unwrap();
+        assert!(body.is_empty(), "{uri}");
+
+        // 原样回传 Last-Modified
+        let same = send("GET", Some(&modified)).await;
+        assert_eq!(same.status(), StatusCode::NOT_MODIFIED, "{uri}");
+        assert_eq!(same.headers()[LAST_MODIFIED], modified.as_str(), "{uri}");
+
+        let later = send("GET", Some("Fri, 01 Jan 2100 00:00:00 GMT")).await;
+        assert_eq!(later.status(), StatusCode::NOT_MODIFIED, "{uri}");
+        assert_eq!(later.headers()[LAST_MODIFIED], modified.as_str());
+        let head = send("HEAD", Some("Fri, 01 Jan 2100 00:00:00 GMT")).await;
+        assert_eq!(head.status(), StatusCode::NOT_MODIFIED, "{uri}");
+
+        let earlier = send("GET", Some("Thu, 01 Jan 1970 00:00:00 GMT")).await;
+        assert_eq!(earlier.status(), StatusCode::OK, "{uri}");
+    }
+
+    // 文章路径列表
+    {
+        let list = app.article_list("文章列表").await;
+        let group = list[0]["group"]["id"].as_str().unwrap();
+        let (status, slugs, _) = app.slugs("", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            slugs,
+            serde_json::json!([{
+                "group": group,
+                "slug": "markdown-test",
+                "updated_at": list[0]["updated_at"],
+            }])
+        );
+        let (_, filtered, _) = app.slugs(&format!("?group={group}"), None).await;
+        assert_eq!(filtered, slugs);
+        let (_, other, _) = app.slugs("?group=unknown", None).await;
+        assert_eq!(other, serde_json::json!([]));
+    }
+
+    // 重建期间再次重建返回 409，增量同步排队到重建完成后执行
+    {
+        let repo = GitClient::open(REPO_PATH).expect("打开仓库失败");
+        let oid = "4db775450dee399c328935eb03fd4fcc6c60e333";
+        let limits = ContentLimits::default();
+
+        let permit = app.coordinator.rebuild().await.expect("获取重建许可失败");
+        let rebuild = async {
+            let result = repo
+                .snapshot(oid)
+                .await
+                .expect("获取快照失败")
+                .persist(
+                    app.storage(),
+                    &SlowRenderer,
+                    &repo,
+                    oid,
+                    PersistMode::ResetAll,
+                    &limits,
+                )
+                .await;
+            drop(permit);
+            result
+        };
+        let probe = async {
+            let resp = app
+                .push(
+                    "refs/tags/cmd/rebuild",
+                    "0000000000000000000000000000000000000000",
+                    oid,
+                )
+                .await;
+            assert_eq!(resp.status(), StatusCode::CONFLICT, "重建期间再次重建");
+            assert!(resp.headers().contains_key(RETRY_AFTER));
+
+            // 前后 commit 相同，增量同步没有需要渲染的文件
+            let sync = || app.push("refs/heads/main", oid, oid);
+            let status = async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                app.sync_status().await
+            };
+            let (first, second, status) = tokio::join!(sync(), sync(), status);
+            assert_eq!(status["state"], "rebuilding");
+            assert_eq!(status["queued"], 2);
+            assert!(status["rebuild_started_at"].is_i64());
+            assert_eq!(first.status(), StatusCode::OK, "重建完成后执行排队的同步");
+            assert_eq!(second.status(), StatusCode::OK, "重建完成后执行排队的同步");
+        };
+
+        let (result, ()) = tokio::join!(rebuild, probe);
+        result.expect("重建失败");
+
+        let status = app.sync_status().await;
+        assert_eq!(status["state"], "idle");
+        assert_eq!(status["queued"], 0);
+        assert_eq!(app.article_list("重建完成").await.len(), 1);
+    }
+
+    // 重建失败时线上数据不变，影子表被清理；内存存储没有长度限制，不会失败
+    if let Some(db) = app.pool() {
+        let article = test_article(&"s".repeat(300), Utc::now().fixed_offset());
+
+        let mut store = SqlxStore::new(db.clone());
+        store.clean().upsert_article(&article);
+        store.commit().await.expect_err("slug 过长应导致重建失败");
+
+        assert_eq!(app.article_list("重建失败后仍返回旧数据").await.len(), 1);
+        app.article("markdown-test", StatusCode::OK, "重建失败后仍可获取文章")
+            .await;
+        app.assert_no_shadow_tables("重建失败后影子表被清理").await;
+    }
+
+    // 同组相邻文章，created_at 相同时按 slug 排序
+    {
+        let single = app.article_json("markdown-test", "组内唯一文章").await;
+        assert!(single["prev"].is_null());
+        assert!(single["next"].is_null());
+
+        let tie = Tz::Asia__Shanghai
+            .with_ymd_and_hms(2100, 1, 1, 0, 0, 0)
+            .unwrap()
+            .fixed_offset();
+        let mut store = app.storage();
+        store
+            .upsert_article(&test_article("tie-b", tie))
+            .upsert_article(&test_article("tie-a", tie));
+        store.commit().await.expect("写入文章失败");
+
+        let first = app.article_json("markdown-test", "第一篇").await;
+        assert!(first["prev"].is_null());
+        assert_eq!(first["next"]["slug"], "tie-a");
+
+        let middle = app.article_json("tie-a", "中间").await;
+        assert_eq!(middle["prev"]["slug"], "markdown-test");
+        assert_eq!(middle["next"]["slug"], "tie-b");
+        assert_eq!(middle["next"]["title"], "tie-b");
+
+        let last = app.article_json("tie-b", "最后一篇").await;
+        assert_eq!(last["prev"]["slug"], "tie-a");
+        assert!(last["next"].is_null());
+
+        let mut store = app.storage();
+        for slug in ["tie-a", "tie-b"] {
+            store.remove_article(ArticleRef {
+                slug,
+                group: "notes",
+            });
+        }
+        store.commit().await.expect("删除文章失败");
+    }
+
+    // 组目录重命名时迁移组，孤立组在同步后被清理
+    {
+        let repo = TempRepo::new();
+        let group = "name: Rust\npublic: true\n";
+        let article = &post("intro", "summary: intro\n");
+
+        let first = repo.commit(
+            &[("rust/.group.yaml", group), ("rust/rust-intro.md", article)],
+            &[],
+        );
+        let second = repo.commit(
+            &[
+                ("programming/rust/.group.yaml", group),
+                ("programming/rust/rust-intro.md", article),
+            ],
+            &["rust/.group.yaml", "rust/rust-intro.md"],
+        );
+
+        let limits = ContentLimits::default();
+        for (before, after) in [(None, &first), (Some(first.as_str()), &second)] {
+            repo.persist_mode(
+                app,
+                &SlowRenderer,
+                &limits,
+                PersistMode::Incremental,
+                before,
+                after,
+            )
+            .await
+            .expect("同步失败");
+        }
+
+        let ids = app
+            .group_list("重命名后的组")
+            .await
+            .into_iter()
+            .map(|g| g["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert!(ids.contains(&"programming/rust".to_string()), "{ids:?}");
+        assert!(!ids.contains(&"rust".to_string()), "{ids:?}");
+        app.article("rust-intro", StatusCode::OK, "组迁移后文章保持公开")
+            .await;
+
+        // 没有文章且仓库中没有组配置文件的组被清理
+        let stale = Group::new("stale/.group.yaml", "public: true".to_string()).unwrap();
+        let mut store = app.storage();
+        store.upsert_group(&stale);
+        store.commit().await.expect("写入组失败");
+
+        let removed = prune_groups(&app.storage(), &repo.client, &second)
+            .await
+            .expect("清理组失败");
+        assert_eq!(removed, vec!["stale".to_string()]);
+
+        let mut store = app.storage();
+        store
+            .remove_article(ArticleRef {
+                slug: "rust-intro",
+                group: "programming/rust",
+            })
+            .remove_group(&Group::empty("programming/rust/.group.yaml"));
+        store.commit().await.expect("删除数据失败");
+    }
+
+    // path 策略下子目录中的同名文章使用不同的 slug
+    {
+        let repo = TempRepo::new();
+        let group = "public = true\nslug_strategy = \"path\"\n";
+        let (guides, reference) = (post("guides", ""), post("reference", ""));
+
+        let first = repo.commit(
+            &[
+                ("wiki/.group.toml", group),
+                ("wiki/guides/setup.md", &guides),
+                ("wiki/reference/setup.md", &reference),
+                ("wiki/guides-setup.md", &guides),
+            ],
+            &[],
+        );
+        let second = repo.commit(&[], &["wiki/reference/setup.md"]);
+
+        let limits = ContentLimits::default();
+        let persist = |before, after| {
+            repo.persist_mode(
+                app,
+                &SlowRenderer,
+                &limits,
+                PersistMode::Incremental,
+                before,
+                after,
+            )
+        };
+        let report = persist(None, &first).await.expect("同步失败");
+
+        // 剩余的冲突记录在同步报告中
+        assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+        assert!(report.warnings[0].reason.contains("guides-setup"));
+
+        let json = app.article_json("reference-setup", "子目录文章").await;
+        assert_eq!(json["title"], "reference");
+        assert_eq!(json["group"]["id"], "wiki");
+        app.article("guides-setup", StatusCode::OK, "子目录文章")
+            .await;
+
+        // 删除时按相同策略计算 slug
+        persist(Some(&first), &second).await.expect("同步失败");
+        app.article("reference-setup", StatusCode::NOT_FOUND, "文章已删除")
+            .await;
+
+        let mut store = app.storage();
+        store
+            .remove_article(ArticleRef {
+                slug: "guides-setup",
+                group: "wiki",
+            })
+            .remove_group(&Group::empty("wiki/.group.toml"));
+        store.commit().await.expect("删除数据失败");
+    }
+
+    // 片段变更后，引用它的文章在同一次同步中重新渲染
+    {
+        let repo = TempRepo::new();
+        let post = post_with_body("post", "", "{{include \"snippets/disclaimer.md\"}}");
+
+        let first = repo.commit(
+            &[
+                ("notes/.group.yaml", "public: true\n"),
+                ("notes/include-post.md", &post),
+                ("snippets/disclaimer.md", "disclaimer v1"),
+            ],
+            &[],
+        );
+        let second = repo.commit(&[("snippets/disclaimer.md", "disclaimer v2")], &[]);
+
+        let limits = ContentLimits::default();
+        let persist = |before, after| {
+            repo.persist_mode(
+                app,
+                &SlowRenderer,
+                &limits,
+                PersistMode::Incremental,
+                before,
+                after,
+            )
+        };
+        let report = persist(None, &first).await.expect("同步失败");
+
+        // 没有 Front Matter 的片段不发布
+        assert_eq!(report.skipped.len(), 1, "{:?}", report.skipped);
+        assert_eq!(report.skipped[0].path, "snippets/disclaimer.md");
+        let json = app.article_json("include-post", "引用片段的文章").await;
+        assert!(json["content"].as_str().unwrap().contains("disclaimer v1"));
+
+        let report = persist(Some(&first), &second).await.expect("同步失败");
+        assert_eq!(report.modified, vec!["notes/include-post.md".to_string()]);
+        let json = app.article_json("include-post", "片段变更后").await;
+        assert!(json["content"].as_str().unwrap().contains("disclaimer v2"));
+
+        let mut store = app.storage();
+        store
+            .remove_article(ArticleRef {
+                slug: "include-post",
+                group: "notes",
+            })
+            .replace_includes("notes/include-post.md", &[]);
+        store.commit().await.expect("删除数据失败");
+    }
+
+    // 增量同步中单个文件失败时其余文件照常写入，失败文件可在后续提交后重试
+    {
+        let repo = TempRepo::new();
+        let broken = post_with_body("broken", "", "{{include \"snippets/partial.md\"}}");
+
+        let first = repo.commit(
+            &[
+                ("notes/.group.yaml", "public: true\n"),
+                ("notes/partial-a.md", &post("a", "")),
+                ("notes/partial-b.md", &post("b", "")),
+                ("notes/partial-broken.md", &broken),
+            ],
+            &[],
+        );
+        let second = repo.commit(&[("snippets/partial.md", "fixed")], &[]);
+
+        let limits = ContentLimits::default();
+        let persist = |mode, before, after| {
+            repo.persist_mode(app, &SlowRenderer, &limits, mode, before, after)
+        };
+
+        // 严格模式下整体失败，不写入任何文件
+        persist(PersistMode::Strict, None, &first)
+            .await
+            .expect_err("严格模式下应整体失败");
+        app.article("partial-a", StatusCode::NOT_FOUND, "严格模式未写入")
+            .await;
+
+        let report = persist(PersistMode::Incremental, None, &first)
+            .await
+            .expect("部分失败不应导致同步失败");
+        assert_eq!(report.failed.len(), 1, "{:?}", report.failed);
+        assert_eq!(report.failed[0].path, "notes/partial-broken.md");
+        assert!(report.failed[0].reason.contains("snippets/partial.md"));
+        assert!(report.added.contains(&"notes/partial-a.md".to_string()));
+        assert!(report.added.contains(&"notes/partial-b.md".to_string()));
+
+        app.article("partial-a", StatusCode::OK, "成功的文件已写入")
+            .await;
+        app.article("partial-b", StatusCode::OK, "成功的文件已写入")
+            .await;
+        app.article("partial-broken", StatusCode::NOT_FOUND, "失败的文件未写入")
+            .await;
+        let failures = app.storage().sync_failures().await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].commit_id, first);
+
+        // 后续提交补上被引用的文件，失败记录指向新的提交
+        persist(PersistMode::Incremental, Some(&first), &second)
+            .await
+            .expect("同步失败");
+        let failures = app.storage().sync_failures().await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].commit_id, second);
+
+        let report = retry_failures(app.storage(), &SlowRenderer, &repo.client, &limits)
+            .await
+            .expect("重试失败");
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(report.modified, vec!["notes/partial-broken.md".to_string()]);
+        let json = app.article_json("partial-broken", "重试后写入").await;
+        assert!(json["content"].as_str().unwrap().contains("fixed"));
+        assert!(app.storage().sync_failures().await.unwrap().is_empty());
+
+        // 没有失败记录时重试接口返回空报告
+        let resp = app.retry_failures().await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let mut store = app.storage();
+        for slug in ["partial-a", "partial-b", "partial-broken"] {
+            store.remove_article(ArticleRef {
+                slug,
+                group: "notes",
+            });
+        }
+        store.replace_includes("notes/partial-broken.md", &[]);
+        store.commit().await.expect("删除数据失败");
+    }
+
+    // 内容相同的文章共享渲染结果，最后一篇引用的文章删除后清理
+    {
+        let repo = TempRepo::new();
+        // 正文包含临时目录，避免复用之前运行留下的渲染结果
+        let shared = post_with_body("shared", "", &repo.dir.path().display().to_string());
+        let oid = blob_oid(shared.as_bytes());
+
+        let first = repo.commit(
+            &[
+                ("notes/.group.yaml", "public: true\n"),
+                ("notes/shared-a.md", &shared),
+                ("notes/shared-b.md", &shared),
+            ],
+            &[],
+        );
+        let second = repo.commit(&[], &["notes/shared-a.md"]);
+        let third = repo.commit(&[], &["notes/shared-b.md"]);
+
+        let limits = ContentLimits::default();
+        let renderer = CountingRenderer::default();
+        let persist = |before, after| {
+            repo.persist_mode(
+                app,
+                &renderer,
+                &limits,
+                PersistMode::Incremental,
+                before,
+                after,
+            )
+        };
+
+        persist(None, &first).await.expect("同步失败");
+        // 正文和摘要各渲染一次
+        assert_eq!(renderer.0.load(Ordering::SeqCst), 2, "相同内容只渲染一次");
+        let a = app.article_json("shared-a", "共享内容的文章").await;
+        let b = app.article_json("shared-b", "共享内容的文章").await;
+        assert_eq!(a["content"], b["content"]);
+        assert!(a["content"].as_str().unwrap().starts_with("<counted>"));
+
+        // 已存储的渲染结果直接复用
+        persist(None, &first).await.expect("同步失败");
+        assert_eq!(renderer.0.load(Ordering::SeqCst), 2, "已存储的内容不再渲染");
+
+        persist(Some(&first), &second).await.expect("同步失败");
+        app.article("shared-a", StatusCode::NOT_FOUND, "删除的文章")
+            .await;
+        let b = app.article_json("shared-b", "仍引用渲染结果的文章").await;
+        assert_eq!(b["content"], a["content"]);
+        let blob = app.storage().content_blob(&oid, "counting-v1").await;
+        assert!(blob.unwrap().is_some(), "仍被引用的渲染结果保留");
+
+        persist(Some(&second), &third).await.expect("同步失败");
+        let blob = app.storage().content_blob(&oid, "counting-v1").await;
+        assert!(blob.unwrap().is_none(), "不再被引用的渲染结果被清理");
+    }
+
+    // 按稳定的作者 id 筛选，只修改显示名不影响筛选；不同作者名生成相同 id 时报告冲突
+    {
+        let repo = TempRepo::new();
+        let group =
+            |name: &str| format!("public: true\nauthor:\n  id: author-jane\n  name: {name}\n");
+
+        let first = repo.commit(
+            &[
+                ("authored/.group.yaml", &group("Jane Doe")),
+                ("authored/author-post.md", &post("author post", "")),
+            ],
+            &[],
+        );
+        let second = repo.commit(&[("authored/.group.yaml", &group("Jane D."))], &[]);
+        let third = repo.commit(
+            &[
+                ("clash-a/.group.yaml", "author:\n  name: Clash Author\n"),
+                ("clash-b/.group.yaml", "author:\n  name: clash_author\n"),
+            ],
+            &[],
+        );
+
+        let limits = ContentLimits::default();
+        let persist = |before, after| {
+            repo.persist_mode(
+                app,
+                &SlowRenderer,
+                &limits,
+                PersistMode::Incremental,
+                before,
+                after,
+            )
+        };
+
+        persist(None, &first).await.expect("同步失败");
+        let list = app
+            .article_list_query("?author=author-jane", "按作者筛选")
+            .await;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["slug"], "author-post");
+        assert_eq!(list[0]["author"]["name"], "Jane Doe");
+        let authors = app.author_list("作者列表").await;
+        let jane = authors
+            .iter()
+            .find(|a| a["id"] == "author-jane")
+            .expect("作者列表中应包含 author-jane");
+        assert_eq!(jane["article_count"], 1);
+        assert_eq!(jane["latest"]["slug"], "author-post");
+
+        // 只修改显示名，id 不变
+        persist(Some(&first), &second).await.expect("同步失败");
+        let list = app
+            .article_list_query("?author=author-jane", "修改显示名后按作者筛选")
+            .await;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["author"]["name"], "Jane D.");
+        let json = app.article_json("author-post", "文章详情中的作者").await;
+        assert_eq!(json["author"]["id"], "author-jane");
+        assert_eq!(json["author"]["name"], "Jane D.");
+
+        let report = persist(Some(&second), &third).await.expect("同步失败");
+        assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+        assert!(report.warnings[0].reason.contains("clash-author"));
+        assert!(report.as_summary().contains("clash-author"));
+
+        let mut store = app.storage();
+        store.remove_article(ArticleRef {
+            slug: "author-post",
+            group: "authored",
+        });
+        for path in [
+            "authored/.group.yaml",
+            "clash-a/.group.yaml",
+            "clash-b/.group.yaml",
+        ] {
+            store.remove_group(&Group::empty(path));
+        }
+        store.commit().await.expect("删除数据失败");
+    }
+
+    // 组配置和 Front Matter 中的未知字段记录为警告，严格模式下视为处理失败
+    {
+        let repo = TempRepo::new();
+        let commit = repo.commit(
+            &[
+                ("typo/.group.yaml", "pubic: true\n"),
+                ("typo/typo-post.md", &post("typo post", "cathegory: x\n")),
+            ],
+            &[],
+        );
+        let persist = |limits: ContentLimits| {
+            let (repo, commit) = (&repo, &commit);
+            async move {
+                repo.persist_mode(
+                    app,
+                    &SlowRenderer,
+                    &limits,
+                    PersistMode::Incremental,
+                    None,
+                    commit,
+                )
+                .await
+                .expect("同步失败")
+            }
+        };
+
+        let report = persist(ContentLimits {
+            strict_metadata: true,
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(report.failed.len(), 2, "{:?}", report.failed);
+        assert!(report.failed.iter().any(|i| i.reason.contains("`pubic`")));
+        app.article("typo-post", StatusCode::NOT_FOUND, "严格模式下未写入")
+            .await;
+
+        let report = persist(ContentLimits::default()).await;
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(report.warnings.len(), 2, "{:?}", report.warnings);
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|i| i.path == "typo/.group.yaml" && i.reason.contains("did you mean `public`"))
+        );
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|i| i.path == "typo/typo-post.md" && i.reason.contains("`cathegory`"))
+        );
+        assert!(app.storage().sync_failures().await.unwrap().is_empty());
+
+        let mut store = app.storage();
+        store.remove_article(ArticleRef {
+            slug: "typo-post",
+            group: "typo",
+        });
+        store.remove_group(&Group::empty("typo/.group.yaml"));
+        store.commit().await.expect("删除数据失败");
+    }
+}
@@ -0,0 +1,1026 @@
+use super::*;
+
+#[tokio::test]
+async fn test_debounced_sync() {
+    let backend: Backend = MemoryStorage::new().into();
+    let state = state::AppState::new(backend.clone(), GithubAPiRenderer::default(), REPO_PATH)
+        .with_sync_debounce(Duration::from_secs(3600))
+        .with_admin_token(Some("s3cret"));
+    let app = TestApp::from_state(backend, state);
+
+    let resp = app.push("refs/heads/main", "a", "b").await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.starts_with("deferred a..b (1 pushes)"), "{text}");
+
+    let req = Request::post("/api/repo/update")
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .body(Body::new(
+            serde_json::json!({"refname": "refs/heads/main", "before": "b", "after": "c"})
+                .to_string(),
+        ))
+        .unwrap();
+    let resp = app.request(req).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let deferred: serde_json::Value = serde_json::from_slice(&data).unwrap();
+    assert_eq!(deferred["pending"]["before"], "a");
+    assert_eq!(deferred["pending"]["after"], "c");
+    assert_eq!(deferred["pending"]["pushes"], 2);
+    assert_eq!(deferred["quiet_secs"], 3600);
+
+    // 同步状态展示合并后的范围，未到静默期不会执行
+    let status = app.sync_status().await;
+    assert_eq!(status["state"], "idle");
+    assert_eq!(status["pending"], deferred["pending"]);
+
+    // 重建请求到达时丢弃等待中的范围
+    let resp = app
+        .push(
+            "refs/tags/cmd/rebuild",
+            "0000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000001",
+        )
+        .await;
+    assert!(!resp.status().is_success());
+    assert!(app.sync_status().await["pending"].is_null());
+}
+
+#[tokio::test]
+async fn test_hook_allowlist() {
+    let hook_request = |peer: Option<&str>, forwarded: Option<&str>, body: &str| {
+        let mut req = Request::post("/api/repo/update").header("Content-Type", "application/json");
+        if let Some(forwarded) = forwarded {
+            req = req.header("X-Forwarded-For", forwarded);
+        }
+        if let Some(peer) = peer {
+            req = req.extension(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        }
+        req.body(Body::new(body.to_string())).unwrap()
+    };
+    let push = r#"{"refname": "refs/heads/main", "before": "a", "after": "b"}"#;
+    let router = |trust_proxy: bool| {
+        let allowlist = "127.0.0.1/32,10.0.0.0/8"
+            .parse::<api::HookAllowlist>()
+            .unwrap()
+            .with_trust_proxy(trust_proxy);
+        // 未迁移时同步接口返回 503，用来确认请求通过了来源检查
+        let app = state::AppState::new(
+            MemoryStorage::new(),
+            GithubAPiRenderer::default(),
+            REPO_PATH,
+        )
+        .with_schema_ready(false)
+        .with_hook_allowlist(Some(allowlist));
+        api::setup_route(app)
+    };
+    let status =
+        async |router: Router, req: Request<Body>| router.oneshot(req).await.unwrap().status();
+
+    // 允许的来源到达处理函数
+    for peer in [
+        "127.0.0.1:50000",
+        "10.1.2.3:50000",
+        "[::ffff:127.0.0.1]:50000",
+    ] {
+        let req = hook_request(Some(peer), None, push);
+        assert_eq!(
+            status(router(false), req).await,
+            StatusCode::SERVICE_UNAVAILABLE,
+            "{peer}"
+        );
+    }
+
+    // 其他来源在解析请求体之前被拒绝，没有连接信息时同样拒绝
+    let req = hook_request(Some("203.0.113.9:50000"), None, "{");
+    assert_eq!(status(router(false), req).await, StatusCode::FORBIDDEN);
+    let req = hook_request(None, None, push);
+    assert_eq!(status(router(false), req).await, StatusCode::FORBIDDEN);
+    let req = Request::post("/api/repo/retry-failures")
+        .extension(ConnectInfo(
+            "203.0.113.9:50000".parse::<SocketAddr>().unwrap(),
+        ))
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(status(router(false), req).await, StatusCode::FORBIDDEN);
+
+    // 不信任代理时忽略伪造的 X-Forwarded-For
+    let req = hook_request(Some("203.0.113.9:50000"), Some("127.0.0.1"), push);
+    assert_eq!(status(router(false), req).await, StatusCode::FORBIDDEN);
+
+    // 信任代理时以代理追加的最后一项为准，客户端在前面添加的地址无效
+    let req = hook_request(Some("127.0.0.1:50000"), Some("10.1.2.3"), push);
+    assert_eq!(
+        status(router(true), req).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    let req = hook_request(
+        Some("127.0.0.1:50000"),
+        Some("127.0.0.1, 203.0.113.9"),
+        push,
+    );
+    assert_eq!(status(router(true), req).await, StatusCode::FORBIDDEN);
+
+    // 其他接口不受影响
+    let req = Request::get("/api/openapi.json")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(status(router(false), req).await, StatusCode::OK);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_persist_retry() {
+    let memory = MemoryStorage::new();
+    let app = Fixture::with_backend(memory.clone().into(), |state| state.with_strict_sync(true));
+    let post = &post("retry", "");
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", post),
+        ],
+        &[],
+    );
+    let second = app.commit(&[("notes/b.md", post)], &[]);
+    let broken = app.commit(&[("notes/c.md", "---\ntitle: [c\n---\n")], &[]);
+    let zero = "0".repeat(40);
+    let push = |before: &str, after: &str| {
+        let (app, before, after) = (&app, before.to_string(), after.to_string());
+        async move {
+            let started = tokio::time::Instant::now();
+            let resp = app.push("refs/heads/main", &before, &after).await;
+            let status = resp.status();
+            let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            let text = String::from_utf8(data.to_vec()).unwrap();
+            (status, text, started.elapsed())
+        }
+    };
+
+    // 第一次提交失败，退避后重试成功
+    memory.fail_commits(1);
+    let (status, text, elapsed) = push(&zero, &first).await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    assert!(
+        text.contains("retried 1 time after transient database errors"),
+        "{text}"
+    );
+    assert!(elapsed >= Duration::from_secs(2), "{elapsed:?}");
+    app.article("a", StatusCode::OK, "重试后写入").await;
+
+    // 连续失败时最多尝试 3 次
+    memory.fail_commits(3);
+    let (status, text, elapsed) = push(&first, &second).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{text}");
+    assert!(elapsed >= Duration::from_secs(6), "{elapsed:?}");
+    app.article("b", StatusCode::NOT_FOUND, "重试用尽后未写入")
+        .await;
+
+    // 非临时错误立即失败，不消耗注入的故障
+    memory.fail_commits(1);
+    let (status, text, elapsed) = push(&second, &broken).await;
+    assert!(!status.is_success(), "{text}");
+    assert_eq!(elapsed, Duration::ZERO);
+    memory.fail_commits(0);
+}
+
+#[tokio::test]
+async fn test_symlinks_and_unnamed_group() {
+    let app = Fixture::new();
+    app.commit(
+        &[
+            (".group.yaml", "public: true\n"),
+            ("notes/.group.yaml", "public: true\n"),
+            ("shared/.group.yaml", "public: true\n"),
+            (
+                "shared/post.md",
+                &post_with_body("shared", "", "shared body"),
+            ),
+        ],
+        &[],
+    );
+    let commit = commit_symlinks(
+        &app.repo.git,
+        &[
+            ("notes/linked.md", "../shared/post.md"),
+            ("notes/outside.md", "../../outside.md"),
+        ],
+    );
+    let report = app.persist(None, &commit).await.unwrap();
+
+    // 根目录未命名的组和指向仓库外的链接被跳过，其余照常写入
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|i| (i.path.as_str(), i.reason.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        skipped,
+        [
+            (".group.yaml", "group directory has no name, set `name`"),
+            ("notes/outside.md", "symlink points outside the repository"),
+        ]
+    );
+    assert!(report.failed.is_empty());
+
+    let article = app.article_json("linked", "符号链接的文章").await;
+    assert!(
+        article["content"].as_str().unwrap().contains("shared body"),
+        "{article}"
+    );
+}
+
+#[tokio::test]
+async fn test_persist_prefetches_blobs() {
+    const ARTICLES: usize = 250;
+
+    let app = Fixture::new();
+    let files = (0..ARTICLES)
+        .map(|i| {
+            let content = post_with_body(&format!("post {i}"), "", &format!("body {i}"));
+            (format!("notes/post-{i}.md"), content)
+        })
+        .chain([(
+            "notes/.group.yaml".to_string(),
+            "public: true\n".to_string(),
+        )])
+        .collect::<Vec<_>>();
+    let writes = files
+        .iter()
+        .map(|(p, c)| (p.as_str(), c.as_str()))
+        .collect::<Vec<_>>();
+    let commit = app.commit(&writes, &[]);
+
+    let repo = &app.repo.client;
+    let entries = repo.snapshot(&commit).await.unwrap();
+    let before = repo.operations();
+    entries
+        .persist(
+            app.storage(),
+            &CountingRenderer::default(),
+            repo,
+            &commit,
+            PersistMode::ResetAll,
+            &ContentLimits::default(),
+        )
+        .await
+        .unwrap();
+    let operations = repo.operations() - before;
+
+    assert_eq!(app.backend.public_slugs().await.unwrap().len(), ARTICLES);
+    // 内容按批读取，每篇文章只剩展开引用时的一次仓库访问，逐个读取时至少是文章数的两倍
+    let chunks = (ARTICLES + 1).div_ceil(BLOB_PREFETCH_CHUNK);
+    assert!(
+        operations <= ARTICLES + chunks + 5,
+        "{operations} operations for {ARTICLES} articles"
+    );
+}
+
+#[tokio::test]
+async fn test_queries_during_slow_git() {
+    let app = Fixture::new();
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a", "")),
+        ],
+        &[],
+    );
+    app.persist(None, &first).await.unwrap();
+    let second = app.commit(
+        &[
+            ("notes/b.md", &post("b", "")),
+            ("notes/c.md", &post("c", "")),
+        ],
+        &[],
+    );
+
+    // 每个仓库操作都阻塞所在线程 200 毫秒。操作在阻塞线程池中执行时，
+    // 单线程运行时上的文章列表请求不等待同步完成，返回同步前的内容
+    const BLOCKING: Duration = Duration::from_millis(200);
+    let repo = app
+        .repo
+        .client
+        .clone()
+        .with_operation_hook(|| std::thread::sleep(BLOCKING));
+    let syncing = AtomicBool::new(true);
+    let sync = async {
+        let started = tokio::time::Instant::now();
+        let report = persist_commits_with(
+            &app,
+            &repo,
+            &CountingRenderer::default(),
+            &ContentLimits::default(),
+            Some(&first),
+            &second,
+        )
+        .await;
+        syncing.store(false, Ordering::SeqCst);
+        (report, started.elapsed())
+    };
+    let queries = async {
+        let mut latencies = Vec::new();
+        while syncing.load(Ordering::SeqCst) {
+            let started = tokio::time::Instant::now();
+            let (status, _, body) = app.get("/api/articles").await;
+            latencies.push(started.elapsed());
+            assert_eq!(status, StatusCode::OK, "{body}");
+            assert!(body.contains(r#""slug":"a""#), "{body}");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        latencies
+    };
+    let ((report, elapsed), latencies) = tokio::join!(sync, queries);
+    report.unwrap();
+
+    assert!(elapsed >= BLOCKING * 2, "{elapsed:?}");
+    assert!(latencies.len() >= 5, "{latencies:?}");
+    let slowest = latencies.iter().max().unwrap();
+    assert!(*slowest < BLOCKING / 2, "{latencies:?}");
+    app.article("b", StatusCode::OK, "同步完成后写入").await;
+}
+
+#[tokio::test]
+async fn test_render_fallback() {
+    let app = Fixture::new();
+    let post = &post_with_body("t", "", "<b>a</b>");
+    let commit = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", post),
+            ("notes/b.md", post),
+        ],
+        &[],
+    );
+    let limits = ContentLimits::default();
+    let down = Arc::new(AtomicBool::new(true));
+    let renderer = FallbackRenderer::new(FlakyRenderer(down.clone()), PendingRenderer)
+        .with_breaker(1, Duration::ZERO);
+
+    // 渲染服务不可用时文章仍然发布，正文为转义后的原文
+    let report = app
+        .persist_with(&renderer, &limits, None, &commit)
+        .await
+        .unwrap();
+    assert!(report.failed.is_empty(), "{report:?}");
+    let content = app.article_json("a", "降级发布的文章").await["content"].clone();
+    assert_eq!(content, "<pre>&lt;b&gt;a&lt;/b&gt;\n</pre>");
+
+    let rows = app.backend.stale_renders("flaky@1").await.unwrap();
+    assert_eq!(
+        rows.iter()
+            .map(|r| (r.slug.as_str(), r.renderer.as_deref()))
+            .collect::<Vec<_>>(),
+        [
+            ("a", Some(PendingRenderer::IDENTITY)),
+            ("b", Some(PendingRenderer::IDENTITY))
+        ]
+    );
+
+    let (_, status) = app.get_json("/api/status").await;
+    assert_eq!(status["stale_renders"], 2);
+    assert_eq!(status["renderer_circuit"]["state"], "closed");
+
+    // 恢复后重新渲染降级的文章，不复用降级的结果
+    down.store(false, Ordering::SeqCst);
+    let report = rerender_stale(
+        &app.storage(),
+        &renderer,
+        &app.repo.client,
+        &commit,
+        &limits,
+        &rows,
+        20,
+    )
+    .await
+    .unwrap();
+    assert_eq!(report.modified, ["notes/a.md", "notes/b.md"]);
+    assert!(
+        app.backend
+            .stale_renders("flaky@1")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let content = app.article_json("b", "重新渲染后的文章").await["content"].clone();
+    assert_eq!(content, "<flaky><b>a</b>\n</flaky>");
+}
+
+#[tokio::test]
+async fn test_content_fingerprint() {
+    let app = Fixture::new();
+    let post = |body: &str| post_with_body("t", "", body);
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "public: true\ndefault_tags: [n]\n"),
+            ("notes/a.md", &post("a")),
+            ("notes/b.md", &post("b")),
+            ("drafts/.group.yaml", "public: false\n"),
+            ("drafts/c.md", &post("c")),
+        ],
+        &[],
+    );
+
+    let repo = &app.repo.client;
+    let limits = ContentLimits::default();
+    let status = || async {
+        let (status, body) = app.get_json("/api/status").await;
+        assert_eq!(status, StatusCode::OK);
+        body
+    };
+
+    // 尚未同步过
+    assert!(status().await["content_fingerprint"].is_null());
+
+    app.persist(None, &first).await.unwrap();
+    record_fingerprint(app.storage(), &first).await.unwrap();
+    let synced = status().await;
+    assert_eq!(synced["commit"], first.as_str());
+    assert_eq!(
+        synced["content_fingerprint"],
+        repo_fingerprint(repo, &first, &limits).await.unwrap()
+    );
+
+    // 修改一篇文章后数据库与旧快照不再一致
+    let second = app.commit(&[("notes/b.md", &post("b2"))], &[]);
+    assert_ne!(
+        repo_fingerprint(repo, &second, &limits).await.unwrap(),
+        synced["content_fingerprint"].as_str().unwrap()
+    );
+    app.persist(Some(&first), &second).await.unwrap();
+    record_fingerprint(app.storage(), &second).await.unwrap();
+    let updated = status().await;
+    assert_eq!(updated["commit"], second.as_str());
+    assert_eq!(
+        updated["content_fingerprint"],
+        repo_fingerprint(repo, &second, &limits).await.unwrap()
+    );
+
+    // 私有组中的文章不参与计算
+    let third = app.commit(&[("drafts/c.md", &post("c2"))], &[]);
+    assert_eq!(
+        repo_fingerprint(repo, &third, &limits).await.unwrap(),
+        updated["content_fingerprint"].as_str().unwrap()
+    );
+}
+
+/// 还没有提交的裸仓库：同步和重建返回空报告，不写入数据，状态接口的 `head` 为 `null`；推送第一个提交后正常同步
+#[tokio::test]
+async fn test_empty_repository() {
+    const ZERO: &str = "0000000000000000000000000000000000000000";
+    const MISSING: &str = "1111111111111111111111111111111111111111";
+    let dir = tempfile::tempdir().unwrap();
+    let bare = git2::Repository::init_bare(dir.path()).unwrap();
+    let app = TestApp::with_backend(
+        MemoryStorage::new().into(),
+        GithubAPiRenderer::default(),
+        dir.path(),
+    );
+    let status = || async {
+        let (status, body) = app.get_json("/api/status").await;
+        assert_eq!(status, StatusCode::OK);
+        body
+    };
+    let push = |refname: &'static str, before: &'static str, after: String| {
+        let app = &app;
+        async move {
+            let resp = app.push(refname, before, &after).await;
+            let code = resp.status();
+            let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            (code, String::from_utf8(data.to_vec()).unwrap())
+        }
+    };
+
+    let empty = status().await;
+    assert!(empty["head"].is_null());
+    assert!(empty["commit"].is_null());
+
+    for (refname, after) in [
+        ("refs/tags/cmd/rebuild", MISSING),
+        ("refs/heads/main", MISSING),
+        ("refs/heads/main", ZERO),
+    ] {
+        let (code, text) = push(refname, ZERO, after.to_string()).await;
+        assert_eq!(code, StatusCode::OK, "{refname} {after}: {text}");
+        assert!(
+            text.starts_with("repository has no commits yet\nrequest-id "),
+            "{text}"
+        );
+    }
+    assert!(status().await["commit"].is_null());
+    assert!(app.backend.groups().await.unwrap().is_empty());
+
+    // 第一个提交
+    let blob = bare.blob(b"name: Notes\npublic: true\n").unwrap();
+    let mut notes = bare.treebuilder(None).unwrap();
+    notes
+        .insert(".group.yaml", blob, git2::FileMode::Blob.into())
+        .unwrap();
+    let mut root = bare.treebuilder(None).unwrap();
+    root.insert("notes", notes.write().unwrap(), git2::FileMode::Tree.into())
+        .unwrap();
+    let tree = bare.find_tree(root.write().unwrap()).unwrap();
+    let sig = git2::Signature::now("tester", "tester@example.com").unwrap();
+    let first = bare
+        .commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])
+        .unwrap()
+        .to_string();
+
+    let (code, text) = push("refs/heads/main", ZERO, first.clone()).await;
+    assert_eq!(code, StatusCode::OK, "{text}");
+    assert!(text.contains("notes/.group.yaml"), "{text}");
+    let synced = status().await;
+    assert_eq!(synced["head"], first.as_str());
+    assert_eq!(synced["commit"], first.as_str());
+    assert_eq!(app.backend.groups().await.unwrap().len(), 1);
+
+    // 有提交后，不存在的提交按错误处理
+    let (code, _) = push("refs/heads/main", ZERO, MISSING.to_string()).await;
+    assert_ne!(code, StatusCode::OK);
+}
+
+/// 重建前备份现有数据，备份失败时不重建，`force=true` 时跳过备份
+#[tokio::test]
+async fn test_rebuild_backup() {
+    const ZERO: &str = "0000000000000000000000000000000000000000";
+    let backups = tempfile::tempdir().unwrap();
+    let backup_dir = backups.path().join("gitnote");
+    let app = Fixture::with_state(|state| state.with_backup(Some(BackupOptions::new(&backup_dir))));
+    let post = &post("t", "");
+    let first = app.commit(
+        &[
+            ("notes/.group.yaml", "name: Notes\npublic: true\n"),
+            ("notes/a.md", post),
+        ],
+        &[],
+    );
+    let second = app.commit(&[("notes/b.md", post)], &["notes/a.md"]);
+    let rebuild = |after: &str, query: &str| {
+        let req = Request::post(format!("/api/repo/update{query}"))
+            .header(CONTENT_TYPE, "application/json")
+            .header("Accept", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "refname": "refs/tags/cmd/rebuild",
+                    "before": ZERO,
+                    "after": after,
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let app = &app;
+        async move {
+            let resp = app.request(req).await;
+            let status = resp.status();
+            let data = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(data.to_vec()).unwrap())
+        }
+    };
+    let slugs = || async {
+        let mut slugs = vec![];
+        for slug in ["a", "b"] {
+            if app.backend.get_one(slug).await.unwrap().is_some() {
+                slugs.push(slug);
+            }
+        }
+        slugs
+    };
+
+    let resp = app.push("refs/heads/main", ZERO, &first).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(slugs().await, ["a"]);
+
+    // 重建前的数据写入备份，文件名在同步报告中
+    let (status, text) = rebuild(&second, "").await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+    let file = report["backup"].as_str().unwrap();
+    assert_eq!(list_backups(&backup_dir).unwrap(), [file]);
+    assert_eq!(slugs().await, ["b"]);
+
+    // 备份可以导回，替换现有数据
+    let input = std::io::BufReader::new(flate2::read::MultiGzDecoder::new(
+        std::fs::File::open(backup_dir.join(file)).unwrap(),
+    ));
+    import_dump(&app.storage(), input, true).await.unwrap();
+    assert_eq!(slugs().await, ["a"]);
+
+    // 备份目录不可写时不重建
+    std::fs::remove_dir_all(&backup_dir).unwrap();
+    std::fs::write(&backup_dir, "").unwrap();
+    let (status, text) = rebuild(&second, "").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(text.contains("force=true"), "{text}");
+    assert_eq!(slugs().await, ["a"]);
+
+    let (status, text) = rebuild(&second, "?force=true").await;
+    assert_eq!(status, StatusCode::OK, "{text}");
+    let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert!(report.get("backup").is_none());
+    assert_eq!(slugs().await, ["b"]);
+
+    // 增量同步不备份
+    std::fs::remove_file(&backup_dir).unwrap();
+    let third = app.commit(&[("notes/c.md", post)], &[]);
+    let resp = app.push("refs/heads/main", &second, &third).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(!backup_dir.exists());
+}
+
+#[tokio::test]
+async fn test_sync_log() {
+    let app =
+        &Fixture::with_state(|state| state.with_sync_log_size(2).with_admin_token(Some("s3cret")));
+    let commit = app.commit(&[("notes/data.txt", "not an article")], &[]);
+    let sync_log = |query: &'static str, token: Option<&'static str>| async move {
+        app.get_with_token(&format!("/api/admin/sync-log{query}"), token)
+            .await
+    };
+    let entries = |(status, _, body): (StatusCode, HeaderMap, String)| {
+        assert_eq!(status, StatusCode::OK);
+        serde_json::from_str::<Vec<serde_json::Value>>(&body).unwrap()
+    };
+
+    // 失败的增量同步和成功的重建都会记录，忽略的 ref 不记录
+    let resp = app.push("refs/heads/main", "a", "b").await;
+    assert!(!resp.status().is_success());
+    let resp = app
+        .push(
+            "refs/tags/cmd/rebuild",
+            "0000000000000000000000000000000000000000",
+            &commit,
+        )
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app.push("refs/heads/feature", "a", "b").await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // 缺少或错误的 token
+    let (status, headers, _) = sync_log("", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(headers["www-authenticate"], "Bearer");
+    let (status, _, _) = sync_log("", Some("wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 最新的在前
+    let log = entries(sync_log("", Some("s3cret")).await);
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0]["push_kind"], "rebuild");
+    assert_eq!(log[0]["outcome"], "succeeded");
+    assert_eq!(log[0]["range"]["after"], commit.as_str());
+    assert!(log[0]["request_id"].is_string());
+    assert_eq!(log[1]["push_kind"], "sync");
+    assert_eq!(log[1]["outcome"], "failed");
+    assert_eq!(log[1]["range"]["before"], "a");
+    assert!(!log[1]["text"].as_str().unwrap().is_empty());
+
+    let failed = entries(sync_log("?failed=true", Some("s3cret")).await);
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["range"]["after"], "b");
+
+    // 超过容量时丢弃最早的记录
+    app.push("refs/heads/main", "c", "d").await;
+    let log = entries(sync_log("", Some("s3cret")).await);
+    let afters = log
+        .iter()
+        .map(|e| e["range"]["after"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(afters, ["d", commit.as_str()]);
+
+    // 未配置 token 时接口不可用
+    let backend: Backend = MemoryStorage::new().into();
+    let state = state::AppState::new(
+        backend.clone(),
+        GithubAPiRenderer::default(),
+        app.repo.path(),
+    );
+    let plain = TestApp::from_state(backend, state);
+    let (status, _, _) = plain.get_with_token("/api/admin/sync-log", Some("")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_updated_at_from_commit_time() {
+    let app = Fixture::new();
+    let git = &app.repo.git;
+    let post =
+        |datetime: &str, body: &str| post_with_body("t", &format!("datetime: {datetime}\n"), body);
+    let time = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+    commit_files_at(
+        git,
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/old.md", &post("2020-01-01", "old")),
+        ],
+        &[],
+        time(2024, 1, 1).timestamp(),
+    );
+    let second = commit_files_at(
+        git,
+        &[("notes/new.md", &post("2023-12-01", "new"))],
+        &[],
+        time(2024, 2, 1).timestamp(),
+    );
+
+    let list = || async {
+        app.article_list("文章列表")
+            .await
+            .into_iter()
+            .map(|a| {
+                let at = |key: &str| a[key].as_i64().unwrap();
+                (
+                    a["slug"].as_str().unwrap().to_string(),
+                    at("created_at"),
+                    at("updated_at"),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let ms = |t: DateTime<Utc>| t.timestamp_millis();
+
+    // 重建时更新时间取最近一次修改该文件的提交，而不是快照所在的提交
+    app.persist(None, &second).await.unwrap();
+    assert_eq!(
+        list().await,
+        [
+            (
+                "new".to_string(),
+                ms(time(2023, 12, 1)),
+                ms(time(2024, 2, 1))
+            ),
+            (
+                "old".to_string(),
+                ms(time(2020, 1, 1)),
+                ms(time(2024, 1, 1))
+            ),
+        ]
+    );
+
+    // 修改旧文章后排在最前，创建时间不变
+    let third = commit_files_at(
+        git,
+        &[("notes/old.md", &post("2020-01-01", "old, revised"))],
+        &[],
+        time(2024, 3, 1).timestamp(),
+    );
+    app.persist(Some(&second), &third).await.unwrap();
+    assert_eq!(
+        list().await,
+        [
+            (
+                "old".to_string(),
+                ms(time(2020, 1, 1)),
+                ms(time(2024, 3, 1))
+            ),
+            (
+                "new".to_string(),
+                ms(time(2023, 12, 1)),
+                ms(time(2024, 2, 1))
+            ),
+        ]
+    );
+
+    // 增量同步与重建的结果一致
+    assert_eq!(
+        record_fingerprint(app.storage(), &third).await.unwrap(),
+        repo_fingerprint(&app.repo.client, &third, &ContentLimits::default())
+            .await
+            .unwrap()
+    );
+}
+
+/// 收集日志输出的缓冲区
+#[derive(Clone, Default)]
+struct LogCapture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogCapture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_entry_spans() {
+    let repo = TempRepo::new();
+    let commit = repo.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a", "")),
+        ],
+        &[],
+    );
+    let app = TestApp::memory();
+
+    let capture = LogCapture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_span_list(true)
+        .with_env_filter("debug")
+        .with_writer({
+            let capture = capture.clone();
+            move || capture.clone()
+        })
+        .finish();
+    let guard = tracing::subscriber::set_default(subscriber);
+    repo.persist(&app, None, &commit).await.expect("重建失败");
+    drop(guard);
+
+    let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    let entries = logs
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|log| log["fields"]["message"] == "entry processed")
+        .map(|log| log["span"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2, "{logs}");
+
+    for (path, kind) in [("notes/.group.yaml", "Group"), ("notes/a.md", "Markdown")] {
+        let span = entries
+            .iter()
+            .find(|s| s["path"] == path)
+            .unwrap_or_else(|| panic!("missing span for {path}: {logs}"));
+        assert_eq!(span["name"], "entry");
+        assert_eq!(span["file_kind"], kind);
+        assert_eq!(span["change_kind"], "Added");
+        assert_eq!(span["outcome"], "ok");
+        assert!(span["render_ms"].is_u64(), "{span}");
+        assert!(span["db_ms"].is_u64(), "{span}");
+    }
+}
+
+#[tokio::test]
+async fn test_rebuild_reuses_renders() {
+    let repo = TempRepo::new();
+    let post = |body: &str| post_with_body(body, "", body);
+    let first = repo.commit(
+        &[
+            ("notes/.group.yaml", "public: true\n"),
+            ("notes/a.md", &post("a")),
+            ("notes/b.md", &post("b")),
+            ("notes/c.md", &post("c")),
+        ],
+        &[],
+    );
+    let second = repo.commit(&[("notes/b.md", &post("b, edited"))], &[]);
+
+    let app = TestApp::memory();
+    let renderer = CountingRenderer::default();
+    let limits = ContentLimits::default();
+
+    let report = repo
+        .persist_with(&app, &renderer, &limits, None, &first)
+        .await
+        .expect("重建失败");
+    assert_eq!((report.rendered, report.reused), (3, 0));
+    // 正文和摘要各渲染一次
+    assert_eq!(renderer.0.load(Ordering::SeqCst), 6);
+
+    // 重建时只重新渲染内容变化的文章
+    let report = repo
+        .persist_with(&app, &renderer, &limits, None, &second)
+        .await
+        .expect("重建失败");
+    assert_eq!((report.rendered, report.reused), (1, 2));
+    assert_eq!(renderer.0.load(Ordering::SeqCst), 8);
+    assert!(
+        report
+            .as_summary()
+            .lines()
+            .any(|line| line == "render: 1 rendered, 2 reused"),
+        "{}",
+        report.as_summary()
+    );
+    let b = app.article_json("b", "重新渲染的文章").await;
+    assert!(b["content"].as_str().unwrap().contains("b, edited"), "{b}");
+    assert_eq!(app.article_list("重建后的文章").await.len(), 3);
+
+    let report = repo
+        .persist_with(&app, &renderer, &limits, None, &second)
+        .await
+        .expect("重建失败");
+    assert_eq!((report.rendered, report.reused), (0, 3));
+    assert_eq!(renderer.0.load(Ordering::SeqCst), 8);
+}
+
+/// 以 `--stdin` 模式运行 update hook，返回退出码和标准错误输出
+fn run_update_hook(stdin: &str) -> (Option<i32>, String) {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("sh")
+        .args(["git/hooks/update", "--stdin", "--quiet"])
+        // 没有服务监听的地址，请求总是失败
+        .env("GITNOTE_UPDATE_API", "http://127.0.0.1:9/api/repo/update")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("启动 hook 失败");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_update_hook_stdin() {
+    let zero = "0".repeat(40);
+    let oid = "a".repeat(40);
+
+    // 格式错误时不发送任何请求
+    for line in [
+        "refs/heads/main".to_string(),
+        format!("{zero} {oid}"),
+        format!("{zero} {oid} refs/heads/main extra"),
+        format!("{zero} not-an-oid refs/heads/main"),
+    ] {
+        let (code, stderr) = run_update_hook(&format!("{zero} {oid} refs/heads/main\n{line}\n"));
+        assert_eq!(code, Some(2), "{line}");
+        assert!(stderr.contains("malformed line 2"), "{stderr}");
+    }
+
+    // 全零的 newrev 表示删除 ref，可以正常解析；第一个 ref 失败后停止
+    let (code, stderr) = run_update_hook(&format!(
+        "{oid} {zero} refs/heads/gone\n\n{zero} {oid} refs/tags/v1"
+    ));
+    assert_eq!(code, Some(1));
+    assert!(
+        stderr.contains("Stopped at refs/heads/gone after 0 of 2 refs"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_update_hook_hints() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::process::Command;
+
+    // 返回固定 NDJSON 流的服务，只处理一个请求
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !String::from_utf8_lossy(&request).contains("\"after\"") {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0, "请求不完整");
+            request.extend_from_slice(&buf[..n]);
+        }
+        let body = [
+            r#"{"event":"entry","path":"notes/a.md","action":"added","outcome":"failed","reason":"missing field `title`","duration_ms":1}"#,
+            r#"{"event":"hint","path":"notes/a.md","suggestion":"add `title:` to the front matter"}"#,
+            r#"{"event":"hint","path":"notes/a.md","suggestion":"quote it: summary: \"[x] y\""}"#,
+            r#"{"event":"done","outcome":"rolled_back","report":null,"error":"missing field `title`"}"#,
+        ]
+        .join("\n");
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}\n",
+            body.len() + 1
+        )
+        .unwrap();
+    });
+
+    let oid = "a".repeat(40);
+    let output = Command::new("sh")
+        .args([
+            "git/hooks/update",
+            "--quiet",
+            "refs/heads/main",
+            &"0".repeat(40),
+            &oid,
+        ])
+        .env(
+            "GITNOTE_UPDATE_API",
+            format!("http://{addr}/api/repo/update"),
+        )
+        .output()
+        .expect("启动 hook 失败");
+    server.join().unwrap();
+
+    // quiet 模式下同样在文件下方输出修改建议
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("  💡 notes/a.md: add `title:` to the front matter\n"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("  💡 notes/a.md: quote it: summary: \"[x] y\"\n"),
+        "{stderr}"
+    );
+}